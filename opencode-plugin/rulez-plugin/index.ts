@@ -1,9 +1,9 @@
 /**
  * RuleZ Policy Engine Plugin for OpenCode CLI
  *
- * Intercepts tool.execute.before and tool.execute.after lifecycle events,
- * pipes JSON to `rulez opencode hook` via subprocess, and enforces
- * allow/deny/inject decisions.
+ * Intercepts tool.execute.before and tool.execute.after lifecycle events and
+ * permission.ask approval callbacks, pipes JSON to `rulez opencode hook` via
+ * subprocess, and enforces allow/deny/inject decisions.
  *
  * Install: copy this folder to .opencode/plugins/rulez-plugin/ (project)
  * or ~/.config/opencode/plugins/rulez-plugin/ (global).
@@ -25,10 +25,23 @@ interface ToolEventContext {
   [key: string]: unknown;
 }
 
+interface PermissionInfo {
+  id: string;
+  type: string;
+  pattern?: string | string[];
+  sessionID: string;
+  title: string;
+  metadata: Record<string, unknown>;
+  [key: string]: unknown;
+}
+
+type PermissionStatus = "allow" | "deny" | "ask";
+
 interface RulezResponse {
   continue: boolean;
   reason?: string;
   context?: string;
+  status?: PermissionStatus;
 }
 
 const RULEZ_BINARY = process.env.RULEZ_BINARY_PATH || "rulez";
@@ -103,5 +116,27 @@ export default async function rulezPlugin(_ctx: PluginContext) {
       await callRulezHook("tool.execute.after", eventCtx);
       return undefined;
     },
+
+    async "permission.ask"(
+      permission: PermissionInfo,
+      output: { status: PermissionStatus }
+    ) {
+      const response = await callRulezHook("permission.ask", {
+        sessionId: permission.sessionID,
+        toolName: permission.type,
+        toolInput: {
+          ...permission.metadata,
+          pattern: permission.pattern,
+          title: permission.title,
+        },
+      });
+
+      // Without a RuleZ decision OpenCode keeps its own answer
+      if (response.status) {
+        output.status = response.status;
+      } else if (!response.continue) {
+        output.status = "deny";
+      }
+    },
   };
 }
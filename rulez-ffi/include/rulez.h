@@ -0,0 +1,42 @@
+/*
+ * rulez.h - C ABI for the RuleZ policy engine
+ *
+ * Link against librulez_ffi (cdylib or staticlib built from rulez-ffi/).
+ * All strings are UTF-8 and NUL-terminated. Strings returned by
+ * rulez_evaluate_json and rulez_validate_json are owned by the caller and
+ * must be released with rulez_string_free. They are never NULL: failures
+ * are returned as {"error":{"kind":"...","message":"...","rule":...}}.
+ */
+
+#ifndef RULEZ_H
+#define RULEZ_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Evaluate a hook event (the JSON Claude Code sends on stdin).
+ *
+ * config_path: a hooks.yaml file, or NULL to discover the config from the
+ * event's cwd (.claude/hooks.yaml), then ~/.claude/hooks.yaml.
+ *
+ * Returns JSON: {"response":{"continue":...},"outcome":"allow|block|inject",
+ * "matched_rules":[...],"decision":...,"violation":...}
+ */
+char *rulez_evaluate_json(const char *event_json, const char *config_path);
+
+/* Validate a hooks.yaml file. Returns {"valid":true,"rules":N} or an error. */
+char *rulez_validate_json(const char *config_path);
+
+/* Library version, e.g. "2.3.0". Static; do not free. */
+const char *rulez_version(void);
+
+/* Free a string returned by this library. NULL is ignored. */
+void rulez_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RULEZ_H */
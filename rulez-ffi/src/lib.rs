@@ -0,0 +1,293 @@
+//! C ABI for the RuleZ policy engine.
+//!
+//! IDE plugins and agents written in other languages can evaluate hook
+//! events in-process through a few `extern "C"` functions that take and
+//! return JSON strings. The header is `include/rulez.h`.
+//!
+//! ```c
+//! char *result = rulez_evaluate_json(event_json, ".claude/hooks.yaml");
+//! /* {"response":{"continue":false,...},"outcome":"block",...} */
+//! rulez_string_free(result);
+//! ```
+//!
+//! Every returned string is allocated here and must be released with
+//! [`rulez_string_free`]. Failures are returned as
+//! `{"error":{"kind":"...","message":"...","rule":...}}` rather than NULL,
+//! and panics never cross the boundary.
+//!
+//! Build releases with `--profile release-ffi`. The workspace `release`
+//! profile sets `panic = "abort"`, under which a panic aborts the host
+//! process before it can be reported as a `Panic` error.
+
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::doc_markdown)]
+#![allow(clippy::uninlined_format_args)]
+
+use std::ffi::{CStr, CString, c_char};
+use std::path::Path;
+
+use rulez::config::Config;
+use rulez::engine::RuleEngine;
+use rulez::error::RulezError;
+use rulez::models::Event;
+
+thread_local! {
+    /// One runtime per calling thread; evaluation is driven by `block_on`
+    static RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+}
+
+/// Evaluate a hook event against a config
+///
+/// `event_json` is a hook event (the JSON Claude Code sends on stdin).
+/// `config_path` is a `hooks.yaml` file; if NULL, the config is discovered
+/// as the hook does, from the event's `cwd` then `~/.claude/hooks.yaml`.
+///
+/// Returns the evaluation result (`response`, `outcome`, `matched_rules`,
+/// `decision`, `violation`) or an error object, as JSON. Free it with
+/// [`rulez_string_free`].
+///
+/// # Safety
+///
+/// `event_json` must be a valid NUL-terminated string. `config_path` must be
+/// NULL or a valid NUL-terminated string. Both only need to live for the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rulez_evaluate_json(
+    event_json: *const c_char,
+    config_path: *const c_char,
+) -> *mut c_char {
+    // SAFETY: the caller guarantees both pointers are NULL or valid C strings
+    let (event_json, config_path) = unsafe { (read_str(event_json), read_str(config_path)) };
+    guarded(|| {
+        let event_json = event_json.ok_or_else(|| invalid_arg("event_json is NULL"))??;
+        let config_path = config_path.transpose()?;
+        evaluate_json(event_json, config_path.map(Path::new))
+    })
+}
+
+/// Validate a `hooks.yaml` file
+///
+/// Returns `{"valid":true,"rules":N}` or an error object, as JSON. Free it
+/// with [`rulez_string_free`].
+///
+/// # Safety
+///
+/// `config_path` must be a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rulez_validate_json(config_path: *const c_char) -> *mut c_char {
+    // SAFETY: the caller guarantees the pointer is NULL or a valid C string
+    let config_path = unsafe { read_str(config_path) };
+    guarded(|| {
+        let config_path = config_path.ok_or_else(|| invalid_arg("config_path is NULL"))??;
+        let config = Config::from_file(config_path)?;
+        Ok(serde_json::json!({"valid": true, "rules": config.rules.len()}))
+    })
+}
+
+/// The RuleZ version, e.g. `"2.3.0"`
+///
+/// The string is static; do not free it.
+#[unsafe(no_mangle)]
+pub extern "C" fn rulez_version() -> *const c_char {
+    static VERSION: &CStr =
+        match CStr::from_bytes_with_nul(concat!(env!("CARGO_PKG_VERSION"), "\0").as_bytes()) {
+            Ok(version) => version,
+            Err(_) => c"unknown",
+        };
+    VERSION.as_ptr()
+}
+
+/// Free a string returned by `rulez_evaluate_json` or `rulez_validate_json`
+///
+/// # Safety
+///
+/// `s` must be NULL or a pointer returned by this library that has not been
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rulez_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the pointer came from CString::into_raw in `to_c_string`
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Evaluate with an explicit or discovered config
+pub fn evaluate_json(
+    event_json: &str,
+    config_path: Option<&Path>,
+) -> Result<serde_json::Value, RulezError> {
+    let event: Event =
+        serde_json::from_str(event_json).map_err(|source| RulezError::InvalidEvent { source })?;
+    let config = match config_path {
+        Some(path) => Config::from_file(path)?,
+        None => Config::load(event.cwd.as_deref().map(Path::new))?,
+    };
+    let engine = RuleEngine::from_config(config)?;
+    let result = RUNTIME.with(|runtime| runtime.block_on(engine.evaluate(&event)))?;
+    serde_json::to_value(result).map_err(|e| RulezError::Other(e.into()))
+}
+
+/// Copy a C string argument; `None` for NULL, `Some(Err)` for invalid UTF-8
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a valid NUL-terminated string.
+unsafe fn read_str<'a>(ptr: *const c_char) -> Option<Result<&'a str, RulezError>> {
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: non-NULL, and the caller guarantees it is NUL-terminated
+    let s = unsafe { CStr::from_ptr(ptr) };
+    Some(
+        s.to_str()
+            .map_err(|_| invalid_arg("argument is not valid UTF-8")),
+    )
+}
+
+fn invalid_arg(message: &str) -> RulezError {
+    RulezError::Other(anyhow::anyhow!("{}", message))
+}
+
+/// Run `f`, turning errors and panics into an error object
+///
+/// Panics are only caught when the library unwinds (`release-ffi`, or any
+/// dev build).
+fn guarded(f: impl FnOnce() -> Result<serde_json::Value, RulezError>) -> *mut c_char {
+    // Nothing observes state left behind by a panic: the error object is all
+    // the caller sees
+    let value = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(e)) => error_json(&e),
+        Err(_) => serde_json::json!({
+            "error": {"kind": "Panic", "message": "rulez panicked during evaluation"}
+        }),
+    };
+    to_c_string(&value.to_string())
+}
+
+fn error_json(error: &RulezError) -> serde_json::Value {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    serde_json::json!({
+        "error": {
+            "kind": error.kind(),
+            "message": message,
+            "rule": error.rule(),
+        }
+    })
+}
+
+fn to_c_string(s: &str) -> *mut c_char {
+    // JSON escapes control characters, so an interior NUL can't occur
+    CString::new(s)
+        .unwrap_or_else(|_| CString::from(c"{\"error\":{\"kind\":\"Other\"}}"))
+        .into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(event: &str, config: Option<&Path>) -> serde_json::Value {
+        let event = CString::new(event).unwrap();
+        let config = config.map(|p| CString::new(p.to_str().unwrap()).unwrap());
+        // SAFETY: both arguments are valid C strings for the duration of the call
+        unsafe {
+            let out = rulez_evaluate_json(
+                event.as_ptr(),
+                config.as_ref().map_or(std::ptr::null(), |c| c.as_ptr()),
+            );
+            let value = serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+            rulez_string_free(out);
+            value
+        }
+    }
+
+    fn write_config(dir: &Path) -> std::path::PathBuf {
+        let path = dir.join("hooks.yaml");
+        std::fs::write(
+            &path,
+            r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+"#,
+        )
+        .unwrap();
+        path
+    }
+
+    const FORCE_PUSH: &str = r#"{"hook_event_name":"PreToolUse","session_id":"ffi","tool_name":"Bash","tool_input":{"command":"git push --force"}}"#;
+
+    #[test]
+    fn test_evaluate_json_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = call(FORCE_PUSH, Some(&write_config(dir.path())));
+        assert_eq!(result["outcome"], "block");
+        assert_eq!(result["response"]["continue"], false);
+        assert_eq!(result["matched_rules"][0], "no-force-push");
+    }
+
+    #[test]
+    fn test_errors_are_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = call("not json", Some(&write_config(dir.path())));
+        assert_eq!(result["error"]["kind"], "InvalidEvent");
+
+        let missing = dir.path().join("missing.yaml");
+        let result = call(FORCE_PUSH, Some(&missing));
+        assert_eq!(result["error"]["kind"], "Io");
+
+        // SAFETY: NULL is allowed and reported as an error
+        let out = unsafe { rulez_evaluate_json(std::ptr::null(), std::ptr::null()) };
+        // SAFETY: `out` is a valid string from this library
+        let text = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        // SAFETY: freed once
+        unsafe { rulez_string_free(out) };
+        assert!(text.contains("event_json is NULL"));
+    }
+
+    #[test]
+    fn test_panics_become_error_json() {
+        let out = guarded(|| panic!("engine bug"));
+        // SAFETY: `out` is a valid string from this library, freed once
+        let text = unsafe {
+            let text = CStr::from_ptr(out).to_str().unwrap().to_string();
+            rulez_string_free(out);
+            text
+        };
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["error"]["kind"], "Panic");
+    }
+
+    #[test]
+    fn test_validate_json_and_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = CString::new(write_config(dir.path()).to_str().unwrap()).unwrap();
+        // SAFETY: valid C string; result freed once
+        let text = unsafe {
+            let out = rulez_validate_json(path.as_ptr());
+            let text = CStr::from_ptr(out).to_str().unwrap().to_string();
+            rulez_string_free(out);
+            text
+        };
+        assert_eq!(text, r#"{"rules":1,"valid":true}"#);
+
+        // SAFETY: static NUL-terminated string
+        let version = unsafe { CStr::from_ptr(rulez_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
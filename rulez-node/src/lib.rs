@@ -0,0 +1,155 @@
+//! Node.js bindings for the RuleZ policy engine.
+//!
+//! Exposes `validate` and `evaluate` (plus a reusable `RuleEngine` class) so
+//! VS Code extensions and dashboards can check events in-process instead of
+//! spawning `rulez` per call. Events and results cross the boundary as plain
+//! JS objects with the same shape as the hook protocol's JSON (typed in
+//! `types.d.ts`).
+//!
+//! Config problems are thrown with `code: "InvalidArg"` and runtime failures
+//! with `code: "GenericFailure"`. `validate` returns the `RulezError` kind
+//! (`ConfigParse`, `RegexCompile`, `InvalidConfig`, ...), rule and line as
+//! data instead of throwing.
+
+#![deny(clippy::all)]
+
+use std::sync::Arc;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use rulez::config::Config;
+use rulez::error::RulezError;
+use rulez::models::Event;
+
+/// One problem found by `validate`
+#[napi(object)]
+pub struct ValidationIssue {
+    /// `RulezError` variant name
+    pub kind: String,
+    pub message: String,
+    /// Rule the problem is in, if known
+    pub rule: Option<String>,
+    /// 1-based line in the YAML, for parse errors
+    pub line: Option<u32>,
+}
+
+/// Result of `validate`
+#[napi(object)]
+pub struct ValidationResult {
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+    /// Number of rules in the config, including built-in packs
+    pub rule_count: u32,
+}
+
+/// A validated config, ready to evaluate events
+#[napi]
+pub struct RuleEngine {
+    inner: Arc<rulez::engine::RuleEngine>,
+}
+
+#[napi]
+impl RuleEngine {
+    /// Create an engine from `hooks.yaml` content
+    #[napi(factory)]
+    pub fn from_yaml(yaml: String) -> Result<Self> {
+        let engine = rulez::engine::RuleEngine::from_yaml(&yaml).map_err(to_js_error)?;
+        Ok(Self {
+            inner: Arc::new(engine),
+        })
+    }
+
+    /// Create an engine from a `hooks.yaml` file
+    #[napi(factory)]
+    pub fn from_file(path: String) -> Result<Self> {
+        let config = Config::from_file(&path).map_err(to_js_error)?;
+        let engine = rulez::engine::RuleEngine::from_config(config).map_err(to_js_error)?;
+        Ok(Self {
+            inner: Arc::new(engine),
+        })
+    }
+
+    /// Names of the configured rules, highest priority first
+    #[napi(getter)]
+    pub fn rule_names(&self) -> Vec<String> {
+        self.inner
+            .config()
+            .enabled_rules()
+            .iter()
+            .map(|rule| rule.name.clone())
+            .collect()
+    }
+
+    /// Evaluate a hook event, resolving to an `EvaluationResult` object
+    #[napi]
+    pub async fn evaluate(&self, event: serde_json::Value) -> Result<serde_json::Value> {
+        evaluate_with(Arc::clone(&self.inner), event).await
+    }
+}
+
+/// Validate `hooks.yaml` content without creating an engine
+#[napi]
+pub fn validate(yaml: String) -> ValidationResult {
+    match rulez::engine::RuleEngine::from_yaml(&yaml) {
+        Ok(engine) => ValidationResult {
+            valid: true,
+            issues: Vec::new(),
+            rule_count: u32::try_from(engine.config().rules.len()).unwrap_or(u32::MAX),
+        },
+        Err(e) => ValidationResult {
+            valid: false,
+            issues: vec![ValidationIssue {
+                kind: e.kind().to_string(),
+                message: error_chain(&e),
+                rule: e.rule().map(str::to_string),
+                line: match e {
+                    RulezError::ConfigParse { line, .. } => {
+                        line.and_then(|line| u32::try_from(line).ok())
+                    }
+                    _ => None,
+                },
+            }],
+            rule_count: 0,
+        },
+    }
+}
+
+/// Evaluate one event against `hooks.yaml` content
+///
+/// Parses the config on every call; create a `RuleEngine` to reuse it.
+#[napi]
+pub async fn evaluate(event: serde_json::Value, config_yaml: String) -> Result<serde_json::Value> {
+    let engine = rulez::engine::RuleEngine::from_yaml(&config_yaml).map_err(to_js_error)?;
+    evaluate_with(Arc::new(engine), event).await
+}
+
+async fn evaluate_with(
+    engine: Arc<rulez::engine::RuleEngine>,
+    event: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let event: Event = serde_json::from_value(event)
+        .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid hook event: {}", e)))?;
+    let result = engine.evaluate(&event).await.map_err(to_js_error)?;
+    serde_json::to_value(result).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Message with its causes, e.g. "Failed to parse config (line 4): invalid type"
+fn error_chain(error: &RulezError) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn to_js_error(error: RulezError) -> Error {
+    let status = if error.is_config_error() {
+        Status::InvalidArg
+    } else {
+        Status::GenericFailure
+    };
+    Error::new(status, error_chain(&error))
+}
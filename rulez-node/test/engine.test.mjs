@@ -0,0 +1,49 @@
+import { test } from 'node:test'
+import assert from 'node:assert/strict'
+import { createRequire } from 'node:module'
+
+const require = createRequire(import.meta.url)
+const { RuleEngine, evaluate, validate } = require('../index.js')
+
+const CONFIG = `
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+`
+
+const bash = (command) => ({
+  hook_event_name: 'PreToolUse',
+  session_id: 'node-test',
+  tool_name: 'Bash',
+  tool_input: { command },
+})
+
+test('validate reports rule count and structured issues', () => {
+  assert.deepEqual(validate(CONFIG), { valid: true, issues: [], ruleCount: 1 })
+
+  const result = validate(CONFIG.replace('git push --force', 'git push ('))
+  assert.equal(result.valid, false)
+  assert.equal(result.issues[0].kind, 'RegexCompile')
+  assert.equal(result.issues[0].rule, 'no-force-push')
+})
+
+test('RuleEngine blocks and allows', async () => {
+  const engine = RuleEngine.fromYaml(CONFIG)
+  assert.deepEqual(engine.ruleNames, ['no-force-push'])
+
+  const blocked = await engine.evaluate(bash('git push --force'))
+  assert.equal(blocked.outcome, 'block')
+  assert.deepEqual(blocked.matched_rules, ['no-force-push'])
+
+  const allowed = await engine.evaluate(bash('ls'))
+  assert.equal(allowed.response.continue, true)
+})
+
+test('evaluate rejects bad configs with InvalidArg', async () => {
+  await assert.rejects(evaluate(bash('ls'), 'rules: ['), { code: 'InvalidArg' })
+})
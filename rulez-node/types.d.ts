@@ -0,0 +1,30 @@
+// Shapes of the JSON objects passed to and returned from `evaluate`.
+// They mirror rulez::models::Event and rulez::engine::EvaluationResult.
+
+export interface HookEvent {
+  hook_event_name: string
+  session_id: string
+  tool_name?: string
+  tool_input?: Record<string, unknown>
+  prompt?: string
+  cwd?: string
+  [field: string]: unknown
+}
+
+export interface HookResponse {
+  continue: boolean
+  context?: string
+  reason?: string
+  systemMessage?: string
+  hookSpecificOutput?: Record<string, unknown>
+  updatedPrompt?: string
+}
+
+export interface EvaluationResult {
+  response: HookResponse
+  outcome: 'allow' | 'block' | 'inject'
+  matched_rules: string[]
+  decision?: 'allowed' | 'blocked' | 'warned' | 'audited'
+  violation?: string
+  rule_evaluations?: Array<{ rule_name: string; matched: boolean; matcher_results?: Record<string, unknown> }>
+}
@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
 use tokio::fs;
 
+use super::debug::resolve_rulez_binary_path;
+use super::workspaces::active_project_dir;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigFile {
     pub path: String,
@@ -11,15 +18,85 @@ pub struct ConfigFile {
     pub has_errors: bool,
 }
 
+/// One changed value, addressed by a dotted path (`settings.log_level`)
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// A rule present in both configs whose definition changed
+#[derive(Debug, Serialize)]
+pub struct RuleChange {
+    pub name: String,
+    /// Changed fields, relative to the rule (`matchers.tools`)
+    pub changes: Vec<FieldChange>,
+}
+
+/// Semantic difference between two configs
+#[derive(Debug, Serialize)]
+pub struct ConfigDiff {
+    /// Rules only in the second config
+    #[serde(rename = "addedRules")]
+    pub added_rules: Vec<Value>,
+    /// Rules only in the first config
+    #[serde(rename = "removedRules")]
+    pub removed_rules: Vec<Value>,
+    #[serde(rename = "modifiedRules")]
+    pub modified_rules: Vec<RuleChange>,
+    /// Changed settings and other top-level keys (`version`, `waivers`, ...)
+    pub settings: Vec<FieldChange>,
+}
+
+/// A configuration key and what it does
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyDoc {
+    pub key: String,
+    pub doc: String,
+}
+
+/// A tool name to suggest in `tools:` matchers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolCompletion {
+    pub name: String,
+    pub builtin: bool,
+    /// Events in the audit log that used this tool
+    pub seen: u64,
+}
+
+/// A configured rule, for references such as `pre_rules`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuleCompletion {
+    pub name: String,
+    pub description: Option<String>,
+    pub priority: i32,
+}
+
+/// Editor completion data, as printed by `rulez completion-data`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct CompletionData {
+    pub tools: Vec<ToolCompletion>,
+    pub rules: Vec<RuleCompletion>,
+    pub event_types: Vec<String>,
+    pub rule_keys: Vec<KeyDoc>,
+    pub matcher_keys: Vec<KeyDoc>,
+    pub action_keys: Vec<KeyDoc>,
+}
+
 /// Get the global config path (~/.claude/hooks.yaml)
 fn get_global_config_path() -> Option<PathBuf> {
     dirs::home_dir().map(|home| home.join(".claude").join("hooks.yaml"))
 }
 
 /// Get the project config path (.claude/hooks.yaml)
+///
+/// Without a project dir this is the active workspace, then the working directory.
 fn get_project_config_path(project_dir: Option<String>) -> PathBuf {
     project_dir
         .map(PathBuf::from)
+        .or_else(active_project_dir)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default())
         .join(".claude")
         .join("hooks.yaml")
@@ -95,8 +172,81 @@ pub async fn write_config(path: String, content: String) -> Result<(), String> {
         .map_err(|e| format!("Failed to write file: {}", e))
 }
 
+/// Apply structured edits (insert/update/delete rule, toggle enabled, change
+/// priority) through `rulez rule apply`, which rewrites only the lines it
+/// changes so comments survive. Returns the updated file content.
+#[tauri::command]
+pub async fn edit_config(
+    app_handle: tauri::AppHandle,
+    path: String,
+    edits: Vec<Value>,
+) -> Result<String, String> {
+    let command_path = resolve_rulez_binary_path(&app_handle)?;
+    let edits =
+        serde_json::to_string(&edits).map_err(|e| format!("Failed to serialize edits: {}", e))?;
+
+    let mut child = Command::new(&command_path)
+        .args(["rule", "apply", "--print", "--config", &expand_tilde(&path)])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                "RuleZ binary not found. Configure a binary path or ensure 'rulez' is in your PATH."
+                    .to_string()
+            } else {
+                format!("Failed to execute RuleZ: {}", e)
+            }
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(edits.as_bytes())
+            .map_err(|e| format!("Failed to send edits to RuleZ: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to execute RuleZ: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to edit config: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Completion data for the YAML editor: tool names (built-in and seen in
+/// the logs), rule names, event types, and rule/matcher/action keys
+#[tauri::command]
+pub async fn get_completion_data(
+    app_handle: tauri::AppHandle,
+    config_path: Option<String>,
+) -> Result<CompletionData, String> {
+    let command_path = resolve_rulez_binary_path(&app_handle)?;
+    let mut command = Command::new(&command_path);
+    command.arg("completion-data");
+    if let Some(path) = config_path {
+        command.args(["--config", &expand_tilde(&path)]);
+    }
+    let output = command.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            "RuleZ binary not found. Configure a binary path or ensure 'rulez' is in your PATH."
+                .to_string()
+        } else {
+            format!("Failed to execute RuleZ: {}", e)
+        }
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to get completion data: {}", stderr.trim()));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse RuleZ output: {}", e))
+}
+
 /// Expand ~ to home directory
-fn expand_tilde(path: &str) -> String {
+pub(crate) fn expand_tilde(path: &str) -> String {
     if path.starts_with("~/") {
         if let Some(home) = dirs::home_dir() {
             return path.replacen("~", &home.to_string_lossy(), 1);
@@ -104,3 +254,115 @@ fn expand_tilde(path: &str) -> String {
     }
     path.to_string()
 }
+
+/// Compare two configs (YAML content) rule by rule and setting by setting
+///
+/// Rules are matched by name, so reordering rules is not a change.
+#[tauri::command]
+pub async fn diff_configs(a: String, b: String) -> Result<ConfigDiff, String> {
+    let before = parse_yaml(&a).map_err(|e| format!("Failed to parse first config: {}", e))?;
+    let after = parse_yaml(&b).map_err(|e| format!("Failed to parse second config: {}", e))?;
+
+    let before_rules = rules_by_name(&before);
+    let after_rules = rules_by_name(&after);
+
+    let added_rules = after_rules
+        .iter()
+        .filter(|(name, _)| !before_rules.contains_key(*name))
+        .map(|(_, rule)| (*rule).clone())
+        .collect();
+    let removed_rules = before_rules
+        .iter()
+        .filter(|(name, _)| !after_rules.contains_key(*name))
+        .map(|(_, rule)| (*rule).clone())
+        .collect();
+    let modified_rules = before_rules
+        .iter()
+        .filter_map(|(name, old)| {
+            let new = after_rules.get(name)?;
+            let mut changes = Vec::new();
+            diff_values("", Some(*old), Some(*new), &mut changes);
+            (!changes.is_empty()).then(|| RuleChange {
+                name: name.clone(),
+                changes,
+            })
+        })
+        .collect();
+
+    let mut settings = Vec::new();
+    let without_rules = |config: &Value| match config {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(key, _)| key.as_str() != "rules")
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ),
+        _ => Value::Null,
+    };
+    diff_values(
+        "",
+        Some(&without_rules(&before)),
+        Some(&without_rules(&after)),
+        &mut settings,
+    );
+
+    Ok(ConfigDiff {
+        added_rules,
+        removed_rules,
+        modified_rules,
+        settings,
+    })
+}
+
+/// Parse YAML into JSON values; an empty document is an empty config
+fn parse_yaml(content: &str) -> Result<Value, serde_yaml::Error> {
+    if content.trim().is_empty() {
+        return Ok(Value::Object(serde_json::Map::new()));
+    }
+    serde_yaml::from_str(content)
+}
+
+/// Rules keyed by name (unnamed rules can't be matched up and are skipped)
+fn rules_by_name(config: &Value) -> BTreeMap<String, &Value> {
+    config
+        .get("rules")
+        .and_then(Value::as_array)
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|rule| Some((rule.get("name")?.as_str()?.to_string(), rule)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Record the leaf-level differences between two values
+///
+/// Objects are compared key by key; anything else (including lists) is
+/// compared as a whole.
+fn diff_values(
+    path: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(&child, old.get(key), new.get(key), changes);
+            }
+        }
+        (old, new) if old != new => changes.push(FieldChange {
+            path: path.to_string(),
+            before: old.cloned(),
+            after: new.cloned(),
+        }),
+        _ => {}
+    }
+}
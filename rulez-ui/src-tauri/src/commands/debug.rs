@@ -12,6 +12,25 @@ pub struct RuleEvaluation {
     pub details: Option<String>,
     pub pattern: Option<String>,
     pub input: Option<String>,
+    #[serde(rename = "scriptOutput", default)]
+    pub script_output: Option<String>,
+    #[serde(rename = "elapsedUs", default)]
+    pub elapsed_us: u64,
+    /// Result of each matcher the rule defines
+    #[serde(default)]
+    pub matchers: Vec<MatcherTrace>,
+    /// What the rule does when matched (`block`, `warn`, `inject`, ...)
+    #[serde(default)]
+    pub action: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatcherTrace {
+    pub matcher: String,
+    pub matched: bool,
+    pub pattern: Option<String>,
+    /// The event value the matcher looked at (truncated)
+    pub input: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -111,7 +130,7 @@ pub async fn validate_config(
     serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse RuleZ output: {}", e))
 }
 
-fn resolve_rulez_binary_path(app_handle: &tauri::AppHandle) -> Result<String, String> {
+pub(crate) fn resolve_rulez_binary_path(app_handle: &tauri::AppHandle) -> Result<String, String> {
     if let Some(path) = read_rulez_binary_path(app_handle) {
         return Ok(path);
     }
@@ -176,19 +195,70 @@ fn is_executable(path: &PathBuf) -> bool {
 pub struct BinaryCheckResult {
     pub found: bool,
     pub path: Option<String>,
+    /// Version reported by the binary (`rulez --version`)
+    pub version: Option<String>,
+    /// Latest release, when asked to check
+    #[serde(rename = "latestVersion")]
+    pub latest_version: Option<String>,
+    #[serde(rename = "updateAvailable")]
+    pub update_available: Option<bool>,
+}
+
+/// `rulez self-update --check --json` output
+#[derive(Debug, Deserialize)]
+struct VersionStatus {
+    latest: Option<String>,
+    update_available: bool,
 }
 
 /// Check if the RuleZ binary is installed and accessible
+///
+/// With `check_latest`, also asks the binary to compare itself against the
+/// latest release (a network call), so stale installs can be flagged.
 #[tauri::command]
-pub async fn check_binary(app_handle: tauri::AppHandle) -> Result<BinaryCheckResult, String> {
-    match resolve_rulez_binary_path(&app_handle) {
-        Ok(path) => Ok(BinaryCheckResult {
-            found: true,
-            path: Some(path),
-        }),
-        Err(_) => Ok(BinaryCheckResult {
+pub async fn check_binary(
+    app_handle: tauri::AppHandle,
+    check_latest: Option<bool>,
+) -> Result<BinaryCheckResult, String> {
+    let Ok(path) = resolve_rulez_binary_path(&app_handle) else {
+        return Ok(BinaryCheckResult {
             found: false,
             path: None,
-        }),
-    }
+            version: None,
+            latest_version: None,
+            update_available: None,
+        });
+    };
+
+    // "rulez 2.3.0" -> "2.3.0"
+    let version = Command::new(&path)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .last()
+                .map(String::from)
+        });
+
+    let status = if check_latest.unwrap_or(false) {
+        Command::new(&path)
+            .args(["self-update", "--check", "--json"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| serde_json::from_slice::<VersionStatus>(&output.stdout).ok())
+    } else {
+        None
+    };
+
+    Ok(BinaryCheckResult {
+        found: true,
+        path: Some(path),
+        version,
+        latest_version: status.as_ref().and_then(|s| s.latest.clone()),
+        update_available: status.map(|s| s.update_available),
+    })
 }
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tokio::fs;
+
+use super::debug::resolve_rulez_binary_path;
+use super::workspaces::active_project_dir;
+
+/// One scenario in a fixture file (same format as `rulez test`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FixtureCase {
+    pub name: String,
+    pub event_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Expected outcome: "allow", "block", or "inject"
+    pub expected: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FixtureFileContent {
+    tests: Vec<FixtureCase>,
+}
+
+/// A fixture file under `.claude/tests/`
+#[derive(Debug, Serialize)]
+pub struct Fixture {
+    pub name: String,
+    pub path: String,
+    pub tests: Vec<FixtureCase>,
+    /// Parse error, when the file is not a valid fixture
+    pub error: Option<String>,
+}
+
+/// Result of one expectation, as reported by `rulez test --json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpectationResult {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+    pub passed: bool,
+    pub reason: Option<String>,
+}
+
+/// Result of running a fixture file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FixtureRunResult {
+    pub file: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub total: usize,
+    pub results: Vec<ExpectationResult>,
+}
+
+/// Get the fixture directory (.claude/tests)
+fn get_fixture_dir(project_dir: Option<String>) -> PathBuf {
+    get_project_dir(project_dir).join(".claude").join("tests")
+}
+
+fn get_project_dir(project_dir: Option<String>) -> PathBuf {
+    project_dir
+        .map(PathBuf::from)
+        .or_else(active_project_dir)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default())
+}
+
+/// Path of a named fixture; names are plain file stems, never paths
+fn fixture_path(project_dir: Option<String>, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.');
+    if !valid {
+        return Err(format!(
+            "Invalid fixture name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        ));
+    }
+    let stem = name
+        .strip_suffix(".yaml")
+        .or_else(|| name.strip_suffix(".yml"))
+        .unwrap_or(name);
+    Ok(get_fixture_dir(project_dir).join(format!("{}.yaml", stem)))
+}
+
+fn parse_fixture(content: &str) -> Result<Vec<FixtureCase>, String> {
+    serde_yaml::from_str::<FixtureFileContent>(content)
+        .map(|file| file.tests)
+        .map_err(|e| format!("Invalid fixture: {}", e))
+}
+
+/// List fixture files in `.claude/tests/`
+#[tauri::command]
+pub async fn list_fixtures(project_dir: Option<String>) -> Result<Vec<Fixture>, String> {
+    let dir = get_fixture_dir(project_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = fs::read_dir(&dir)
+        .await
+        .map_err(|e| format!("Failed to read fixture directory: {}", e))?;
+    let mut fixtures = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to read fixture directory: {}", e))?
+    {
+        let path = entry.path();
+        let is_yaml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext == "yaml" || ext == "yml");
+        if !is_yaml {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let (tests, error) = match fs::read_to_string(&path).await {
+            Ok(content) => match parse_fixture(&content) {
+                Ok(tests) => (tests, None),
+                Err(e) => (Vec::new(), Some(e)),
+            },
+            Err(e) => (Vec::new(), Some(format!("Failed to read file: {}", e))),
+        };
+        fixtures.push(Fixture {
+            name,
+            path: path.to_string_lossy().to_string(),
+            tests,
+            error,
+        });
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+/// Read a fixture file's YAML content
+#[tauri::command]
+pub async fn read_fixture(project_dir: Option<String>, name: String) -> Result<String, String> {
+    let path = fixture_path(project_dir, &name)?;
+    fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read fixture '{}': {}", name, e))
+}
+
+/// Create or replace a fixture file (the content must be a valid fixture)
+#[tauri::command]
+pub async fn save_fixture(
+    project_dir: Option<String>,
+    name: String,
+    content: String,
+) -> Result<Fixture, String> {
+    let path = fixture_path(project_dir, &name)?;
+    let tests = parse_fixture(&content)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write fixture '{}': {}", name, e))?;
+
+    Ok(Fixture {
+        name: fixture_stem(&path),
+        path: path.to_string_lossy().to_string(),
+        tests,
+        error: None,
+    })
+}
+
+/// Delete a fixture file
+#[tauri::command]
+pub async fn delete_fixture(project_dir: Option<String>, name: String) -> Result<(), String> {
+    let path = fixture_path(project_dir, &name)?;
+    fs::remove_file(&path)
+        .await
+        .map_err(|e| format!("Failed to delete fixture '{}': {}", name, e))
+}
+
+/// Run a fixture with `rulez test` against the project's config
+#[tauri::command]
+pub async fn run_fixture(
+    app_handle: tauri::AppHandle,
+    project_dir: Option<String>,
+    name: String,
+) -> Result<FixtureRunResult, String> {
+    let path = fixture_path(project_dir.clone(), &name)?;
+    if !path.exists() {
+        return Err(format!("Fixture '{}' not found", name));
+    }
+
+    let command_path = resolve_rulez_binary_path(&app_handle)?;
+    let output = Command::new(&command_path)
+        .current_dir(get_project_dir(project_dir))
+        .arg("test")
+        .arg(&path)
+        .arg("--json")
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                "RuleZ binary not found. Configure a binary path or ensure 'rulez' is in your PATH."
+                    .to_string()
+            } else {
+                format!("Failed to execute RuleZ: {}", e)
+            }
+        })?;
+
+    // Failing expectations exit non-zero but still print the report
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    serde_json::from_str(&stdout).map_err(|_| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        format!("RuleZ test failed: {}", stderr.trim())
+    })
+}
+
+fn fixture_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
@@ -1,6 +1,13 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::workspaces::active_project_dir;
 
 /// Flattened DTO for log entries sent to the frontend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +42,48 @@ pub struct LogQueryParams {
     pub limit: Option<usize>,
 }
 
+/// Time-bucketed decision counts for the dashboard charts.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionStats {
+    pub range: String,
+    pub group_by: String,
+    pub bucket_seconds: i64,
+    /// Every bucket in the range, oldest first (empty buckets included)
+    pub buckets: Vec<StatsBucket>,
+    /// Counts per key over the whole range
+    pub totals: BTreeMap<String, usize>,
+    /// Log entries in the range
+    pub total_entries: usize,
+}
+
+/// Counts per key in one time bucket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsBucket {
+    pub start: String,
+    pub counts: BTreeMap<String, usize>,
+}
+
+/// Filters and polling interval for a live log tail.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TailParams {
+    pub text_filter: Option<String>,
+    pub outcome_filter: Option<String>,
+    pub decision_filter: Option<String>,
+    pub interval_ms: Option<u64>,
+}
+
+/// Event carrying each new log entry to the frontend.
+pub const LOG_ENTRY_EVENT: &str = "log-entry";
+
+/// Event emitted when the tail stops because the log can't be read.
+pub const LOG_TAIL_ERROR_EVENT: &str = "log-tail-error";
+
+/// Bumped on every start and stop; a tail task exits once it is outdated.
+static TAIL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 /// Statistics about the log file.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -45,15 +94,28 @@ pub struct LogStats {
     pub newest_entry: Option<String>,
 }
 
-fn get_log_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("/tmp"))
-        .join(".claude")
-        .join("logs")
-        .join("rulez.log")
+/// The log file the CLI writes for the active project (the user-global
+/// config's log when no project is active)
+pub(crate) fn get_log_path() -> PathBuf {
+    match active_project_dir().or_else(dirs::home_dir) {
+        Some(project) => log_path_for(&project),
+        None => PathBuf::from("/tmp")
+            .join(".claude")
+            .join("logs")
+            .join("rulez.log"),
+    }
 }
 
-fn parse_entry(value: &serde_json::Value) -> LogEntryDto {
+/// The log file `rulez` writes for hooks run in `project`: its
+/// `settings.logging.path`, resolved the way the CLI resolves it
+pub(crate) fn log_path_for(project: &Path) -> PathBuf {
+    match rulez::config::Config::load(Some(project)) {
+        Ok(config) => config.settings.logging.log_path_in(project),
+        Err(_) => rulez::logging::Logger::default_log_path(),
+    }
+}
+
+pub(crate) fn parse_entry(value: &serde_json::Value) -> LogEntryDto {
     let timing = value.get("timing").unwrap_or(&serde_json::Value::Null);
     let response = value.get("response").unwrap_or(&serde_json::Value::Null);
     let event_details = value
@@ -162,6 +224,20 @@ fn matches_text_filter(entry: &LogEntryDto, text: &str) -> bool {
             .is_some_and(|d| d.to_lowercase().contains(&lower))
 }
 
+fn matches_outcome_and_decision(
+    entry: &LogEntryDto,
+    outcome_filter: Option<&str>,
+    decision_filter: Option<&str>,
+) -> bool {
+    if outcome_filter.is_some_and(|o| entry.outcome != o) {
+        return false;
+    }
+    match decision_filter {
+        Some(filter) => entry.decision.as_deref() == Some(filter),
+        None => true,
+    }
+}
+
 #[tauri::command]
 pub async fn read_logs(params: LogQueryParams) -> Result<Vec<LogEntryDto>, String> {
     let log_path = get_log_path();
@@ -201,19 +277,13 @@ pub async fn read_logs(params: LogQueryParams) -> Result<Vec<LogEntryDto>, Strin
 
         let entry = parse_entry(&value);
 
-        // Apply outcome filter
-        if let Some(ref outcome_filter) = params.outcome_filter {
-            if entry.outcome != *outcome_filter {
-                continue;
-            }
-        }
-
-        // Apply decision filter
-        if let Some(ref decision_filter) = params.decision_filter {
-            match &entry.decision {
-                Some(d) if d == decision_filter => {}
-                _ => continue,
-            }
+        // Apply outcome and decision filters
+        if !matches_outcome_and_decision(
+            &entry,
+            params.outcome_filter.as_deref(),
+            params.decision_filter.as_deref(),
+        ) {
+            continue;
         }
 
         // Apply time range filters
@@ -300,3 +370,231 @@ pub async fn get_log_stats() -> Result<LogStats, String> {
         newest_entry: newest,
     })
 }
+
+/// Start streaming new log entries to the frontend as `log-entry` events.
+///
+/// Only entries written after the call are sent; use `read_logs` for history.
+/// Starting a new tail replaces the previous one. The file is polled, and a
+/// file that shrinks (rotated or cleared) is read again from the start.
+#[tauri::command]
+pub async fn tail_logs(app_handle: AppHandle, params: Option<TailParams>) -> Result<(), String> {
+    let params = params.unwrap_or_default();
+    let interval = Duration::from_millis(params.interval_ms.unwrap_or(500).max(100));
+    let generation = TAIL_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let log_path = get_log_path();
+    let mut offset = match tokio::fs::metadata(&log_path).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    };
+
+    tauri::async_runtime::spawn(async move {
+        // Bytes of a line still being written, kept until its newline arrives
+        let mut partial: Vec<u8> = Vec::new();
+
+        while TAIL_GENERATION.load(Ordering::SeqCst) == generation {
+            tokio::time::sleep(interval).await;
+
+            let chunk = match read_from(&log_path, &mut offset).await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => {
+                    partial.clear();
+                    continue;
+                }
+                Err(e) => {
+                    let _ = app_handle.emit(LOG_TAIL_ERROR_EVENT, e);
+                    break;
+                }
+            };
+            partial.extend_from_slice(&chunk);
+
+            let Some(end) = partial.iter().rposition(|b| *b == b'\n') else {
+                continue;
+            };
+            let complete: Vec<u8> = partial.drain(..=end).collect();
+            for line in String::from_utf8_lossy(&complete).lines() {
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+                    continue;
+                };
+                let entry = parse_entry(&value);
+                if !matches_outcome_and_decision(
+                    &entry,
+                    params.outcome_filter.as_deref(),
+                    params.decision_filter.as_deref(),
+                ) {
+                    continue;
+                }
+                if let Some(ref text) = params.text_filter {
+                    if !text.is_empty() && !matches_text_filter(&entry, text) {
+                        continue;
+                    }
+                }
+                if TAIL_GENERATION.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let _ = app_handle.emit(LOG_ENTRY_EVENT, &entry);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Stop the live log tail started by `tail_logs`.
+#[tauri::command]
+pub async fn stop_tail_logs() -> Result<(), String> {
+    TAIL_GENERATION.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Read bytes appended since `offset` and advance it.
+///
+/// Returns `None` when the file is missing or was truncated (the offset is
+/// reset so the next read starts from the beginning).
+async fn read_from(path: &std::path::Path, offset: &mut u64) -> Result<Option<Vec<u8>>, String> {
+    let Ok(metadata) = tokio::fs::metadata(path).await else {
+        *offset = 0;
+        return Ok(None);
+    };
+    let len = metadata.len();
+    if len < *offset {
+        *offset = 0;
+        return Ok(None);
+    }
+    if len == *offset {
+        return Ok(Some(Vec::new()));
+    }
+
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open log file: {e}"))?;
+    file.seek(std::io::SeekFrom::Start(*offset))
+        .await
+        .map_err(|e| format!("Failed to seek log file: {e}"))?;
+    let mut buf = Vec::with_capacity((len - *offset) as usize);
+    file.take(len - *offset)
+        .read_to_end(&mut buf)
+        .await
+        .map_err(|e| format!("Failed to read log file: {e}"))?;
+    *offset += buf.len() as u64;
+    Ok(Some(buf))
+}
+
+/// Count decisions over a time range, bucketed and grouped for charts
+///
+/// `range` is `1h`, `24h`, `7d`, `30d` (any number of hours or days) or
+/// `all`; ranges up to two days use hourly buckets, longer ones daily.
+/// `group_by` is `rule`, `decision` or `tool`.
+#[tauri::command]
+pub async fn get_decision_stats(range: String, group_by: String) -> Result<DecisionStats, String> {
+    let log_path = get_log_path();
+    let content = if log_path.exists() {
+        tokio::fs::read_to_string(&log_path)
+            .await
+            .map_err(|e| format!("Failed to read log file: {e}"))?
+    } else {
+        String::new()
+    };
+
+    let entries: Vec<LogEntryDto> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line.trim()).ok())
+        .map(|value| parse_entry(&value))
+        .collect();
+    aggregate_decisions(&entries, Utc::now(), &range, &group_by)
+}
+
+fn aggregate_decisions(
+    entries: &[LogEntryDto],
+    now: DateTime<Utc>,
+    range: &str,
+    group_by: &str,
+) -> Result<DecisionStats, String> {
+    let span = parse_range(range)?;
+    let keys: fn(&LogEntryDto) -> Vec<String> = match group_by {
+        "rule" => |entry| entry.rules_matched.clone(),
+        "decision" => |entry| {
+            vec![entry
+                .decision
+                .clone()
+                .unwrap_or_else(|| entry.outcome.clone())]
+        },
+        "tool" => |entry| vec![entry.tool_name.clone().unwrap_or_else(|| "(none)".into())],
+        other => {
+            return Err(format!(
+                "Unknown group_by '{other}' (expected rule, decision or tool)"
+            ))
+        }
+    };
+
+    let timed: Vec<(DateTime<Utc>, &LogEntryDto)> = entries
+        .iter()
+        .filter_map(|entry| Some((entry.timestamp.parse::<DateTime<Utc>>().ok()?, entry)))
+        .filter(|(ts, _)| *ts <= now)
+        .collect();
+    let since = match span {
+        Some(span) => now - span,
+        None => timed.iter().map(|(ts, _)| *ts).min().unwrap_or(now),
+    };
+    let bucket = if span.is_some_and(|span| span <= ChronoDuration::days(2)) {
+        ChronoDuration::hours(1)
+    } else {
+        ChronoDuration::days(1)
+    };
+    let floor = |ts: DateTime<Utc>| ts.duration_trunc(bucket).unwrap_or(ts);
+
+    let mut buckets: BTreeMap<DateTime<Utc>, BTreeMap<String, usize>> = BTreeMap::new();
+    let mut start = floor(since);
+    while start <= now {
+        buckets.insert(start, BTreeMap::new());
+        start += bucket;
+    }
+
+    let mut totals: BTreeMap<String, usize> = BTreeMap::new();
+    let mut total_entries = 0;
+    for (ts, entry) in timed.into_iter().filter(|(ts, _)| *ts >= since) {
+        total_entries += 1;
+        let counts = buckets.entry(floor(ts)).or_default();
+        for key in keys(entry) {
+            *counts.entry(key.clone()).or_default() += 1;
+            *totals.entry(key).or_default() += 1;
+        }
+    }
+
+    Ok(DecisionStats {
+        range: range.to_string(),
+        group_by: group_by.to_string(),
+        bucket_seconds: bucket.num_seconds(),
+        buckets: buckets
+            .into_iter()
+            .map(|(start, counts)| StatsBucket {
+                start: start.to_rfc3339(),
+                counts,
+            })
+            .collect(),
+        totals,
+        total_entries,
+    })
+}
+
+/// Parse `24h` / `7d` into a duration; `all` is unbounded
+fn parse_range(range: &str) -> Result<Option<ChronoDuration>, String> {
+    if range == "all" {
+        return Ok(None);
+    }
+    let invalid = || format!("Invalid range '{range}' (expected e.g. 24h, 7d or all)");
+    let Some((unit_at, _)) = range.char_indices().last() else {
+        return Err(invalid());
+    };
+    let (number, unit) = range.split_at(unit_at);
+    let n: i64 = number.parse().map_err(|_| invalid())?;
+    // Ten years is plenty and keeps the bucket count bounded
+    if n <= 0 || n > 87_600 {
+        return Err(invalid());
+    }
+    match unit {
+        "h" => Ok(Some(ChronoDuration::hours(n))),
+        "d" => Ok(Some(ChronoDuration::days(n))),
+        _ => Err(invalid()),
+    }
+}
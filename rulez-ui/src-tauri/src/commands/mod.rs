@@ -1,3 +1,6 @@
 pub mod config;
 pub mod debug;
+pub mod fixtures;
 pub mod logs;
+pub mod packs;
+pub mod workspaces;
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::config::expand_tilde;
+use super::debug::resolve_rulez_binary_path;
+
+/// A built-in rule pack, as listed by `rulez packs list --json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackInfo {
+    pub name: String,
+    pub description: String,
+    pub rules: Vec<String>,
+    pub installed: bool,
+}
+
+/// List the built-in rule packs and whether the config enables them
+#[tauri::command]
+pub async fn list_available(
+    app_handle: tauri::AppHandle,
+    config_path: Option<String>,
+) -> Result<Vec<PackInfo>, String> {
+    let mut args = vec![
+        "packs".to_string(),
+        "list".to_string(),
+        "--json".to_string(),
+    ];
+    push_config_arg(&mut args, config_path);
+    let stdout = run_rulez(&app_handle, &args)?;
+    serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse RuleZ output: {}", e))
+}
+
+/// Enable a pack in the config's `settings.builtin_packs`
+#[tauri::command]
+pub async fn install(
+    app_handle: tauri::AppHandle,
+    name: String,
+    config_path: Option<String>,
+) -> Result<String, String> {
+    let mut args = vec!["packs".to_string(), "install".to_string(), name];
+    push_config_arg(&mut args, config_path);
+    run_rulez(&app_handle, &args).map(|stdout| stdout.trim().to_string())
+}
+
+/// Disable a pack in the config's `settings.builtin_packs`
+#[tauri::command]
+pub async fn remove(
+    app_handle: tauri::AppHandle,
+    name: String,
+    config_path: Option<String>,
+) -> Result<String, String> {
+    let mut args = vec!["packs".to_string(), "remove".to_string(), name];
+    push_config_arg(&mut args, config_path);
+    run_rulez(&app_handle, &args).map(|stdout| stdout.trim().to_string())
+}
+
+fn push_config_arg(args: &mut Vec<String>, config_path: Option<String>) {
+    if let Some(path) = config_path {
+        args.push("--config".to_string());
+        args.push(expand_tilde(&path));
+    }
+}
+
+/// Run the RuleZ binary and return its stdout, or its stderr as the error
+fn run_rulez(app_handle: &tauri::AppHandle, args: &[String]) -> Result<String, String> {
+    let command_path = resolve_rulez_binary_path(app_handle)?;
+    let output = Command::new(&command_path)
+        .args(args)
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                "RuleZ binary not found. Configure a binary path or ensure 'rulez' is in your PATH."
+                    .to_string()
+            } else {
+                format!("Failed to execute RuleZ: {}", e)
+            }
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("RuleZ packs failed: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
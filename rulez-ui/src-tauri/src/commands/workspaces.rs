@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::config::expand_tilde;
+use super::logs::{log_path_for, parse_entry, LogEntryDto};
+
+/// A registered project root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub path: String,
+    pub name: String,
+}
+
+/// Registered projects and the active one, persisted between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceState {
+    #[serde(default)]
+    pub projects: Vec<Workspace>,
+    /// Path of the active project; commands without a project dir use it
+    #[serde(default)]
+    pub active: Option<String>,
+}
+
+/// A project with its config status and latest decisions
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    pub path: String,
+    pub name: String,
+    pub active: bool,
+    pub config_path: String,
+    pub config_exists: bool,
+    /// Number of rules in the project config, when it parses
+    pub rule_count: Option<usize>,
+    /// Parse error, when the project config is not valid YAML
+    pub config_error: Option<String>,
+    /// Latest decisions made in this project, newest first
+    pub recent_decisions: Vec<LogEntryDto>,
+}
+
+/// Serializes read-modify-write cycles of the state file
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Get the workspace state path (~/.claude/rulez-ui/workspaces.json)
+fn get_state_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".claude")
+        .join("rulez-ui")
+        .join("workspaces.json")
+}
+
+fn load_state() -> Result<WorkspaceState, String> {
+    let path = get_state_path();
+    if !path.exists() {
+        return Ok(WorkspaceState::default());
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read workspaces: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse workspaces: {}", e))
+}
+
+fn save_state(state: &WorkspaceState) -> Result<(), String> {
+    let path = get_state_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize workspaces: {}", e))?;
+    // Write then rename, so a crash never leaves a truncated state file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write workspaces: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write workspaces: {}", e))
+}
+
+/// Load, change and save the state under the lock
+fn update_state(
+    change: impl FnOnce(&mut WorkspaceState) -> Result<(), String>,
+) -> Result<WorkspaceState, String> {
+    let _guard = STATE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut state = load_state()?;
+    change(&mut state)?;
+    save_state(&state)?;
+    Ok(state)
+}
+
+/// The active project root, if one is set and still exists
+pub(crate) fn active_project_dir() -> Option<PathBuf> {
+    let _guard = STATE_LOCK.lock().ok()?;
+    load_state()
+        .ok()?
+        .active
+        .map(PathBuf::from)
+        .filter(|path| path.is_dir())
+}
+
+/// Register a project root; re-adding a known root only renames it
+#[tauri::command]
+pub async fn add_workspace(path: String, name: Option<String>) -> Result<WorkspaceState, String> {
+    let root = std::fs::canonicalize(expand_tilde(&path))
+        .map_err(|e| format!("Failed to resolve {}: {}", path, e))?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let root_str = root.to_string_lossy().to_string();
+    let name = name
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| root.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| root_str.clone());
+
+    update_state(|state| {
+        match state.projects.iter_mut().find(|p| p.path == root_str) {
+            Some(existing) => existing.name = name,
+            None => state.projects.push(Workspace {
+                path: root_str.clone(),
+                name,
+            }),
+        }
+        // The first project becomes active
+        if state.active.is_none() {
+            state.active = Some(root_str);
+        }
+        Ok(())
+    })
+}
+
+/// Unregister a project root (its files are left alone)
+#[tauri::command]
+pub async fn remove_workspace(path: String) -> Result<WorkspaceState, String> {
+    update_state(|state| {
+        let before = state.projects.len();
+        state.projects.retain(|p| p.path != path);
+        if state.projects.len() == before {
+            return Err(format!("Workspace not registered: {}", path));
+        }
+        if state.active.as_deref() == Some(path.as_str()) {
+            state.active = state.projects.first().map(|p| p.path.clone());
+        }
+        Ok(())
+    })
+}
+
+/// Switch the active project; `None` falls back to the working directory
+#[tauri::command]
+pub async fn set_active_workspace(path: Option<String>) -> Result<WorkspaceState, String> {
+    update_state(|state| {
+        if let Some(ref path) = path {
+            if !state.projects.iter().any(|p| &p.path == path) {
+                return Err(format!("Workspace not registered: {}", path));
+            }
+        }
+        state.active = path;
+        Ok(())
+    })
+}
+
+/// List registered projects with their config status and recent decisions
+#[tauri::command]
+pub async fn list_workspaces(limit: Option<usize>) -> Result<Vec<WorkspaceSummary>, String> {
+    let state = {
+        let _guard = STATE_LOCK.lock().map_err(|e| e.to_string())?;
+        load_state()?
+    };
+    let limit = limit.unwrap_or(10);
+
+    // Projects may log to different files (settings.logging.path); read each file once
+    let log_paths: Vec<PathBuf> = state
+        .projects
+        .iter()
+        .map(|project| log_path_for(Path::new(&project.path)))
+        .collect();
+    let mut decisions = vec![Vec::new(); state.projects.len()];
+    for (index, log_path) in log_paths.iter().enumerate() {
+        if log_paths[..index].contains(log_path) || !log_path.exists() {
+            continue;
+        }
+        let log = tokio::fs::read_to_string(log_path)
+            .await
+            .map_err(|e| format!("Failed to read log file: {}", e))?;
+        let found = recent_decisions(&log, &state.projects, limit);
+        for (slot, (path, entries)) in log_paths.iter().zip(found).enumerate() {
+            if path == log_path {
+                decisions[slot] = entries;
+            }
+        }
+    }
+
+    let mut summaries = Vec::with_capacity(state.projects.len());
+    for (project, recent_decisions) in state.projects.iter().zip(decisions) {
+        let config_path = Path::new(&project.path).join(".claude").join("hooks.yaml");
+        let config_exists = config_path.exists();
+        let (rule_count, config_error) = if config_exists {
+            match tokio::fs::read_to_string(&config_path).await {
+                Ok(content) => count_rules(&content),
+                Err(e) => (None, Some(format!("Failed to read file: {}", e))),
+            }
+        } else {
+            (None, None)
+        };
+        summaries.push(WorkspaceSummary {
+            path: project.path.clone(),
+            name: project.name.clone(),
+            active: state.active.as_deref() == Some(project.path.as_str()),
+            config_path: config_path.to_string_lossy().to_string(),
+            config_exists,
+            rule_count,
+            config_error,
+            recent_decisions,
+        });
+    }
+    Ok(summaries)
+}
+
+fn count_rules(content: &str) -> (Option<usize>, Option<String>) {
+    match serde_yaml::from_str::<serde_json::Value>(content) {
+        Ok(config) => (
+            Some(
+                config
+                    .get("rules")
+                    .and_then(|rules| rules.as_array())
+                    .map_or(0, Vec::len),
+            ),
+            None,
+        ),
+        Err(e) => (None, Some(e.to_string())),
+    }
+}
+
+/// The newest `limit` decisions per project, in project order
+///
+/// Entries are attributed by the event's `cwd` (present when the raw event
+/// is logged) or else the absolute file path the tool touched; the deepest
+/// matching project root wins. Entries without a matched rule are skipped.
+fn recent_decisions(log: &str, projects: &[Workspace], limit: usize) -> Vec<Vec<LogEntryDto>> {
+    let mut decisions: Vec<Vec<LogEntryDto>> = vec![Vec::new(); projects.len()];
+    if projects.is_empty() || limit == 0 {
+        return decisions;
+    }
+
+    // The log is append-only, so walk it backwards and stop once every project is full
+    for line in log.lines().rev() {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        let entry = parse_entry(&value);
+        if entry.rules_matched.is_empty() {
+            continue;
+        }
+        let location = value
+            .pointer("/raw_event/cwd")
+            .and_then(|v| v.as_str())
+            .or(entry.event_detail_file_path.as_deref());
+        let Some(index) = location.and_then(|location| owning_project(projects, location)) else {
+            continue;
+        };
+        if decisions[index].len() < limit {
+            decisions[index].push(entry);
+            if decisions.iter().all(|d| d.len() >= limit) {
+                break;
+            }
+        }
+    }
+    decisions
+}
+
+/// Index of the deepest project root containing `location`
+fn owning_project(projects: &[Workspace], location: &str) -> Option<usize> {
+    let location = Path::new(location);
+    projects
+        .iter()
+        .enumerate()
+        .filter(|(_, project)| location.starts_with(&project.path))
+        .max_by_key(|(_, project)| project.path.len())
+        .map(|(index, _)| index)
+}
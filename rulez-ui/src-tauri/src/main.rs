@@ -3,7 +3,7 @@
 
 mod commands;
 
-use commands::{config, debug, logs};
+use commands::{config, debug, fixtures, logs, packs, workspaces};
 
 fn main() {
     tauri::Builder::default()
@@ -16,11 +16,29 @@ fn main() {
             config::list_config_files,
             config::read_config,
             config::write_config,
+            config::edit_config,
+            config::diff_configs,
+            config::get_completion_data,
             debug::run_debug,
             debug::validate_config,
             debug::check_binary,
+            fixtures::list_fixtures,
+            fixtures::read_fixture,
+            fixtures::save_fixture,
+            fixtures::delete_fixture,
+            fixtures::run_fixture,
             logs::read_logs,
             logs::get_log_stats,
+            logs::get_decision_stats,
+            logs::tail_logs,
+            logs::stop_tail_logs,
+            packs::list_available,
+            packs::install,
+            packs::remove,
+            workspaces::list_workspaces,
+            workspaces::add_workspace,
+            workspaces::remove_workspace,
+            workspaces::set_active_workspace,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
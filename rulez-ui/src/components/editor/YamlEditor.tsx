@@ -1,4 +1,5 @@
 import { configureYamlSchema } from "@/lib/schema";
+import { registerYamlCompletions } from "@/lib/yaml-completion";
 import { registerYamlFormatter } from "@/lib/yaml-formatter";
 import { useEditorStore } from "@/stores/editorStore";
 import { useSettingsStore } from "@/stores/settingsStore";
@@ -42,6 +43,7 @@ export function YamlEditor({ value, path, onChange, onSave }: YamlEditorProps) {
   const schemaConfigured = useRef(false);
   const yamlConfigDisposableRef = useRef<IDisposable | null>(null);
   const formatterDisposableRef = useRef<IDisposable | null>(null);
+  const completionDisposableRef = useRef<IDisposable | null>(null);
   const disposablesRef = useRef<IDisposable[]>([]);
 
   const handleBeforeMount: BeforeMount = useCallback((monaco) => {
@@ -53,6 +55,7 @@ export function YamlEditor({ value, path, onChange, onSave }: YamlEditorProps) {
     if (!schemaConfigured.current) {
       yamlConfigDisposableRef.current = configureYamlSchema(monaco);
       formatterDisposableRef.current = registerYamlFormatter(monaco);
+      completionDisposableRef.current = registerYamlCompletions(monaco);
       schemaConfigured.current = true;
     }
   }, []);
@@ -156,6 +159,8 @@ export function YamlEditor({ value, path, onChange, onSave }: YamlEditorProps) {
       disposablesRef.current = [];
       formatterDisposableRef.current?.dispose();
       formatterDisposableRef.current = null;
+      completionDisposableRef.current?.dispose();
+      completionDisposableRef.current = null;
       setEditorRef(null);
     };
   }, [setEditorRef]);
@@ -1,4 +1,7 @@
+import { checkBinary } from "@/lib/tauri";
 import { useSettingsStore } from "@/stores/settingsStore";
+import type { BinaryCheckResult } from "@/types";
+import { useState } from "react";
 
 const inputClassName =
   "w-full px-3 py-2 text-sm rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-[#1A1A1A] text-gray-900 dark:text-gray-100 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-accent";
@@ -10,6 +13,15 @@ export function SettingsPanel() {
   const setEditorTabSize = useSettingsStore((s) => s.setEditorTabSize);
   const setRulezBinaryPath = useSettingsStore((s) => s.setRulezBinaryPath);
   const setOnboardingComplete = useSettingsStore((s) => s.setOnboardingComplete);
+  const [versionCheck, setVersionCheck] = useState<BinaryCheckResult | null>(null);
+  const [checkingVersion, setCheckingVersion] = useState(false);
+
+  const handleCheckVersion = () => {
+    setCheckingVersion(true);
+    void checkBinary(true)
+      .then(setVersionCheck)
+      .finally(() => setCheckingVersion(false));
+  };
 
   const handleFontSizeChange = (value: string) => {
     const next = Number.parseInt(value, 10);
@@ -110,6 +122,25 @@ export function SettingsPanel() {
           <p className="mt-1 text-xs text-gray-500 dark:text-gray-400">
             Leave blank to use the default PATH lookup.
           </p>
+          <div className="mt-2 flex items-center gap-2">
+            <button
+              type="button"
+              onClick={handleCheckVersion}
+              disabled={checkingVersion}
+              className="px-3 py-1 text-xs font-medium text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-[#1A1A1A] border border-gray-300 dark:border-gray-600 rounded hover:bg-gray-200 dark:hover:bg-gray-700 transition-colors disabled:opacity-50"
+            >
+              {checkingVersion ? "Checking..." : "Check for updates"}
+            </button>
+            {versionCheck && (
+              <span className="text-xs text-gray-500 dark:text-gray-400">
+                {!versionCheck.found
+                  ? "RuleZ binary not found"
+                  : versionCheck.updateAvailable
+                    ? `${versionCheck.version ?? "unknown"} installed, ${versionCheck.latestVersion} available — run \`rulez self-update\``
+                    : `${versionCheck.version ?? "unknown"} installed${versionCheck.latestVersion ? " (latest)" : ""}`}
+              </span>
+            )}
+          </div>
         </div>
 
         <div className="pt-2 border-t border-gray-200 dark:border-gray-700">
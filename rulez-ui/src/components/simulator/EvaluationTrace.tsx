@@ -1,4 +1,4 @@
-import type { RuleEvaluation } from "@/types";
+import type { MatcherTrace, RuleEvaluation } from "@/types";
 import { useState } from "react";
 
 interface EvaluationTraceProps {
@@ -82,26 +82,38 @@ function EvaluationItem({ evaluation }: { evaluation: RuleEvaluation }) {
           </span>
         </div>
         <span className="text-xs text-gray-500 dark:text-gray-400">
-          {evaluation.timeMs.toFixed(1)} ms
+          {evaluation.action && <span className="mr-2 font-medium">{evaluation.action}</span>}
+          {evaluation.elapsedUs !== undefined
+            ? `${evaluation.elapsedUs} µs`
+            : `${evaluation.timeMs.toFixed(1)} ms`}
         </span>
       </div>
 
-      {evaluation.pattern && (
-        <div className="text-xs">
-          <span className="text-gray-500 dark:text-gray-400">Pattern: </span>
-          <code className="font-mono text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-800 px-1 py-0.5 rounded">
-            {evaluation.pattern}
-          </code>
-        </div>
-      )}
-
-      {evaluation.input && (
-        <div className="text-xs">
-          <span className="text-gray-500 dark:text-gray-400">Input: </span>
-          <code className="font-mono text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-800 px-1 py-0.5 rounded">
-            {evaluation.input}
-          </code>
-        </div>
+      {evaluation.matchers && evaluation.matchers.length > 0 ? (
+        <ul className="space-y-0.5">
+          {evaluation.matchers.map((trace) => (
+            <MatcherItem key={trace.matcher} trace={trace} />
+          ))}
+        </ul>
+      ) : (
+        <>
+          {evaluation.pattern && (
+            <div className="text-xs">
+              <span className="text-gray-500 dark:text-gray-400">Pattern: </span>
+              <code className="font-mono text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-800 px-1 py-0.5 rounded">
+                {evaluation.pattern}
+              </code>
+            </div>
+          )}
+          {evaluation.input && (
+            <div className="text-xs">
+              <span className="text-gray-500 dark:text-gray-400">Input: </span>
+              <code className="font-mono text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-800 px-1 py-0.5 rounded">
+                {evaluation.input}
+              </code>
+            </div>
+          )}
+        </>
       )}
 
       {evaluation.details && (
@@ -110,3 +122,33 @@ function EvaluationItem({ evaluation }: { evaluation: RuleEvaluation }) {
     </div>
   );
 }
+
+function MatcherItem({ trace }: { trace: MatcherTrace }) {
+  return (
+    <li className="text-xs flex flex-wrap items-baseline gap-1">
+      <span
+        className={
+          trace.matched
+            ? "text-green-600 dark:text-green-400"
+            : "text-gray-400 dark:text-gray-500"
+        }
+      >
+        {trace.matched ? "✓" : "✗"}
+      </span>
+      <span className="text-gray-500 dark:text-gray-400">{trace.matcher}</span>
+      {trace.pattern && (
+        <code className="font-mono text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-800 px-1 py-0.5 rounded">
+          {trace.pattern}
+        </code>
+      )}
+      {trace.input && (
+        <>
+          <span className="text-gray-500 dark:text-gray-400">on</span>
+          <code className="font-mono text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-800 px-1 py-0.5 rounded">
+            {trace.input}
+          </code>
+        </>
+      )}
+    </li>
+  );
+}
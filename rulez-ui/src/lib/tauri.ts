@@ -6,12 +6,27 @@
  */
 
 import type {
+  BinaryCheckResult,
+  CompletionData,
+  ConfigDiff,
+  ConfigEdit,
   ConfigFile,
   DebugParams,
   DebugResult,
+  DecisionStats,
+  FieldChange,
+  Fixture,
+  FixtureRunResult,
   LogEntryDto,
   LogQueryParams,
   LogStats,
+  PackInfo,
+  RuleEvaluation,
+  StatsGroupBy,
+  StatsRange,
+  TailParams,
+  WorkspaceState,
+  WorkspaceSummary,
 } from "@/types";
 
 /**
@@ -54,6 +69,41 @@ export async function writeConfig(path: string, content: string): Promise<void>
   return mockWriteConfig(path, content);
 }
 
+/**
+ * Apply structured edits to a config file, keeping comments and formatting.
+ * Returns the updated content.
+ */
+export async function editConfig(path: string, edits: ConfigEdit[]): Promise<string> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<string>("edit_config", { path, edits });
+  }
+  return mockEditConfig(path, edits);
+}
+
+/**
+ * Compare two configs (YAML content): added, removed, and modified rules
+ * plus changed settings
+ */
+export async function diffConfigs(a: string, b: string): Promise<ConfigDiff> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<ConfigDiff>("diff_configs", { a, b });
+  }
+  return mockDiffConfigs(a, b);
+}
+
+/**
+ * Completion data for the YAML editor (tool names, rule names, config keys)
+ */
+export async function getCompletionData(configPath?: string): Promise<CompletionData> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<CompletionData>("get_completion_data", { configPath });
+  }
+  return mockGetCompletionData();
+}
+
 /**
  * Run RuleZ debug command
  */
@@ -77,14 +127,15 @@ export async function validateConfig(path: string): Promise<{ valid: boolean; er
 }
 
 /**
- * Check if the RuleZ binary is installed and accessible
+ * Check if the RuleZ binary is installed and accessible.
+ * With checkLatest, also compares its version against the latest release.
  */
-export async function checkBinary(): Promise<{ found: boolean; path: string | null }> {
+export async function checkBinary(checkLatest = false): Promise<BinaryCheckResult> {
   if (isTauri()) {
     const { invoke } = await import("@tauri-apps/api/core");
-    return invoke<{ found: boolean; path: string | null }>("check_binary");
+    return invoke<BinaryCheckResult>("check_binary", { checkLatest });
   }
-  return mockCheckBinary();
+  return mockCheckBinary(checkLatest);
 }
 
 /**
@@ -159,6 +210,184 @@ export async function getLogStats(): Promise<LogStats> {
   return mockGetLogStats();
 }
 
+/**
+ * Time-bucketed decision counts grouped by rule, decision, or tool
+ */
+export async function getDecisionStats(
+  range: StatsRange,
+  groupBy: StatsGroupBy,
+): Promise<DecisionStats> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<DecisionStats>("get_decision_stats", { range, groupBy });
+  }
+  return mockGetDecisionStats(range, groupBy);
+}
+
+/**
+ * Stream new log entries as they are written.
+ * Calls onEntry for each entry and returns a function that stops the tail.
+ */
+export async function tailLogs(
+  onEntry: (entry: LogEntryDto) => void,
+  params: TailParams = {},
+  onError?: (message: string) => void,
+): Promise<() => Promise<void>> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    const { listen } = await import("@tauri-apps/api/event");
+    const unlistenEntry = await listen<LogEntryDto>("log-entry", (event) =>
+      onEntry(event.payload),
+    );
+    const unlistenError = await listen<string>("log-tail-error", (event) =>
+      onError?.(event.payload),
+    );
+    await invoke("tail_logs", { params });
+    return async () => {
+      unlistenEntry();
+      unlistenError();
+      await invoke("stop_tail_logs");
+    };
+  }
+  return mockTailLogs(onEntry, params);
+}
+
+/**
+ * List rule test fixtures in the project's .claude/tests/
+ */
+export async function listFixtures(projectDir?: string): Promise<Fixture[]> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<Fixture[]>("list_fixtures", { projectDir });
+  }
+  return mockListFixtures();
+}
+
+/**
+ * Read a fixture's YAML content
+ */
+export async function readFixture(name: string, projectDir?: string): Promise<string> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<string>("read_fixture", { projectDir, name });
+  }
+  return mockReadFixture(name);
+}
+
+/**
+ * Create or replace a fixture (content must be a valid `rulez test` file)
+ */
+export async function saveFixture(
+  name: string,
+  content: string,
+  projectDir?: string,
+): Promise<Fixture> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<Fixture>("save_fixture", { projectDir, name, content });
+  }
+  return mockSaveFixture(name, content);
+}
+
+/**
+ * Delete a fixture
+ */
+export async function deleteFixture(name: string, projectDir?: string): Promise<void> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<void>("delete_fixture", { projectDir, name });
+  }
+  return mockDeleteFixture(name);
+}
+
+/**
+ * Run a fixture with `rulez test` and get pass/fail per expectation
+ */
+export async function runFixture(name: string, projectDir?: string): Promise<FixtureRunResult> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<FixtureRunResult>("run_fixture", { projectDir, name });
+  }
+  return mockRunFixture(name);
+}
+
+/**
+ * List built-in rule packs and whether the config enables them
+ */
+export async function listAvailablePacks(configPath?: string): Promise<PackInfo[]> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<PackInfo[]>("list_available", { configPath });
+  }
+  return mockListAvailablePacks();
+}
+
+/**
+ * Enable a rule pack in the config's settings.builtin_packs
+ */
+export async function installPack(name: string, configPath?: string): Promise<string> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<string>("install", { name, configPath });
+  }
+  return mockSetPackInstalled(name, true);
+}
+
+/**
+ * Disable a rule pack in the config's settings.builtin_packs
+ */
+export async function removePack(name: string, configPath?: string): Promise<string> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<string>("remove", { name, configPath });
+  }
+  return mockSetPackInstalled(name, false);
+}
+
+/**
+ * List registered projects with their config status and recent decisions
+ */
+export async function listWorkspaces(limit?: number): Promise<WorkspaceSummary[]> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<WorkspaceSummary[]>("list_workspaces", { limit });
+  }
+  return mockListWorkspaces(limit);
+}
+
+/**
+ * Register a project root (the first one becomes active)
+ */
+export async function addWorkspace(path: string, name?: string): Promise<WorkspaceState> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<WorkspaceState>("add_workspace", { path, name });
+  }
+  return mockAddWorkspace(path, name);
+}
+
+/**
+ * Unregister a project root
+ */
+export async function removeWorkspace(path: string): Promise<WorkspaceState> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<WorkspaceState>("remove_workspace", { path });
+  }
+  return mockRemoveWorkspace(path);
+}
+
+/**
+ * Switch the active project (null falls back to the working directory)
+ */
+export async function setActiveWorkspace(path: string | null): Promise<WorkspaceState> {
+  if (isTauri()) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return invoke<WorkspaceState>("set_active_workspace", { path });
+  }
+  return mockSetActiveWorkspace(path);
+}
+
 // ============================================================================
 // Mock implementations for browser testing mode
 // ============================================================================
@@ -181,6 +410,279 @@ async function mockWriteConfig(path: string, content: string): Promise<void> {
   setMockConfig(path, content);
 }
 
+async function mockEditConfig(path: string, edits: ConfigEdit[]): Promise<string> {
+  await delay(30);
+  const { isMap, isSeq, parseDocument } = await import("yaml");
+  const doc = parseDocument(getMockConfig(path));
+  const rules = doc.get("rules");
+  if (!isSeq(rules)) throw new Error("Config has no `rules` list");
+  const indexOf = (name: string) => {
+    const index = rules.items.findIndex((item) => isMap(item) && item.get("name") === name);
+    if (index === -1) throw new Error(`Rule '${name}' not found`);
+    return index;
+  };
+  for (const edit of edits) {
+    switch (edit.op) {
+      case "insert_rule":
+        rules.add(doc.createNode(edit.rule));
+        break;
+      case "update_rule":
+        rules.set(indexOf(edit.name), doc.createNode(edit.rule));
+        break;
+      case "delete_rule":
+        rules.delete(indexOf(edit.name));
+        break;
+      case "set_enabled":
+        doc.setIn(["rules", indexOf(edit.name), "metadata", "enabled"], edit.enabled);
+        break;
+      case "set_priority":
+        doc.setIn(["rules", indexOf(edit.name), "priority"], edit.priority);
+        break;
+    }
+  }
+  const content = doc.toString();
+  setMockConfig(path, content);
+  return content;
+}
+
+async function mockGetCompletionData(): Promise<CompletionData> {
+  await delay(30);
+  const tools = ["Bash", "Edit", "Glob", "Grep", "MultiEdit", "Read", "Task", "WebFetch", "Write"];
+  return {
+    tools: tools.map((name, i) => ({ name, builtin: true, seen: Math.max(0, 40 - i * 5) })),
+    rules: [
+      { name: "block-force-push", description: "Prevent force pushes", priority: 100 },
+      { name: "inject-python-context", description: null, priority: 0 },
+    ],
+    eventTypes: ["PreToolUse", "PostToolUse", "UserPromptSubmit", "SessionStart", "Stop"],
+    ruleKeys: [
+      { key: "name", doc: "Unique rule name" },
+      { key: "description", doc: "What the rule enforces and why" },
+      { key: "matchers", doc: "Conditions that trigger the rule (all must match)" },
+      { key: "actions", doc: "What to do when the rule matches (a map or a pipeline list)" },
+      { key: "mode", doc: "enforce, warn or audit" },
+      { key: "priority", doc: "Evaluation order; higher runs first" },
+    ],
+    matcherKeys: [
+      { key: "tools", doc: "Tool names to match, e.g. [Bash, Edit]" },
+      { key: "extensions", doc: "File extensions to match, e.g. [.rs, .ts]" },
+      { key: "directories", doc: "Directory globs to match, e.g. [src/**]" },
+      { key: "command_match", doc: "Regex matched against the Bash command" },
+    ],
+    actionKeys: [
+      { key: "block", doc: "Block the operation" },
+      { key: "inject", doc: "Path of a context file to inject" },
+      { key: "inject_inline", doc: "Markdown to inject directly" },
+      { key: "validate_expr", doc: "Expression that must be true to allow the operation" },
+    ],
+  };
+}
+
+async function mockDiffConfigs(a: string, b: string): Promise<ConfigDiff> {
+  await delay(30);
+  const { parse } = await import("yaml");
+  const before = (parse(a) ?? {}) as Record<string, unknown>;
+  const after = (parse(b) ?? {}) as Record<string, unknown>;
+
+  const rulesByName = (config: Record<string, unknown>) => {
+    const rules = Array.isArray(config.rules) ? (config.rules as Record<string, unknown>[]) : [];
+    return new Map(
+      rules
+        .filter((rule) => typeof rule?.name === "string")
+        .map((rule) => [rule.name as string, rule]),
+    );
+  };
+  const beforeRules = rulesByName(before);
+  const afterRules = rulesByName(after);
+
+  const modifiedRules = [...beforeRules].flatMap(([name, rule]) => {
+    const next = afterRules.get(name);
+    if (!next) return [];
+    const changes: FieldChange[] = [];
+    diffValues("", rule, next, changes);
+    return changes.length > 0 ? [{ name, changes }] : [];
+  });
+
+  const { rules: _beforeRules, ...beforeRest } = before;
+  const { rules: _afterRules, ...afterRest } = after;
+  const settings: FieldChange[] = [];
+  diffValues("", beforeRest, afterRest, settings);
+
+  return {
+    addedRules: [...afterRules].filter(([name]) => !beforeRules.has(name)).map(([, r]) => r),
+    removedRules: [...beforeRules].filter(([name]) => !afterRules.has(name)).map(([, r]) => r),
+    modifiedRules,
+    settings,
+  };
+}
+
+function isPlainObject(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+function diffValues(path: string, before: unknown, after: unknown, changes: FieldChange[]) {
+  if (isPlainObject(before) && isPlainObject(after)) {
+    const keys = [...new Set([...Object.keys(before), ...Object.keys(after)])].sort();
+    for (const key of keys) {
+      diffValues(path ? `${path}.${key}` : key, before[key], after[key], changes);
+    }
+  } else if (JSON.stringify(before) !== JSON.stringify(after)) {
+    changes.push({ path, before: before ?? null, after: after ?? null });
+  }
+}
+
+const mockFixtures = new Map<string, string>([
+  [
+    "git-safety",
+    `tests:
+  - name: "Block force push"
+    event_type: PreToolUse
+    tool: Bash
+    command: "git push --force origin main"
+    expected: block
+  - name: "Allow status"
+    event_type: PreToolUse
+    tool: Bash
+    command: "git status"
+    expected: allow
+`,
+  ],
+]);
+
+async function mockFixture(name: string, content: string): Promise<Fixture> {
+  const { parse } = await import("yaml");
+  const parsed = parse(content) as { tests?: Fixture["tests"] } | null;
+  if (!parsed || !Array.isArray(parsed.tests)) {
+    return { name, path: `.claude/tests/${name}.yaml`, tests: [], error: "Invalid fixture" };
+  }
+  return { name, path: `.claude/tests/${name}.yaml`, tests: parsed.tests, error: null };
+}
+
+async function mockListFixtures(): Promise<Fixture[]> {
+  await delay(50);
+  return Promise.all([...mockFixtures].map(([name, content]) => mockFixture(name, content)));
+}
+
+async function mockReadFixture(name: string): Promise<string> {
+  await delay(30);
+  const content = mockFixtures.get(name);
+  if (content === undefined) throw new Error(`Fixture '${name}' not found`);
+  return content;
+}
+
+async function mockSaveFixture(name: string, content: string): Promise<Fixture> {
+  await delay(30);
+  const fixture = await mockFixture(name, content);
+  if (fixture.error) throw new Error(fixture.error);
+  mockFixtures.set(name, content);
+  return fixture;
+}
+
+async function mockDeleteFixture(name: string): Promise<void> {
+  await delay(30);
+  mockFixtures.delete(name);
+}
+
+async function mockRunFixture(name: string): Promise<FixtureRunResult> {
+  await delay(100);
+  const fixture = await mockFixture(name, await mockReadFixture(name));
+  const results = fixture.tests.map((test) => {
+    const actual = test.command?.includes("--force") ? "block" : "allow";
+    return {
+      name: test.name,
+      expected: test.expected,
+      actual,
+      passed: actual === test.expected,
+      reason: actual === "block" ? "Force push to main/master is prohibited" : null,
+    };
+  });
+  const passed = results.filter((r) => r.passed).length;
+  return {
+    file: fixture.path,
+    passed,
+    failed: results.length - passed,
+    total: results.length,
+    results,
+  };
+}
+
+const mockPacks: PackInfo[] = [
+  {
+    name: "shell-safety",
+    description: "Blocks shell commands that are almost never intended during an agent run",
+    rules: [
+      "shell-safety-rm-rf-root",
+      "shell-safety-curl-pipe-shell",
+      "shell-safety-force-push-main",
+      "shell-safety-chmod-777",
+      "shell-safety-write-block-device",
+    ],
+    installed: false,
+  },
+];
+
+async function mockListAvailablePacks(): Promise<PackInfo[]> {
+  await delay(50);
+  return mockPacks.map((pack) => ({ ...pack }));
+}
+
+async function mockSetPackInstalled(name: string, installed: boolean): Promise<string> {
+  await delay(50);
+  const pack = mockPacks.find((p) => p.name === name);
+  if (!pack) throw new Error(`Unknown builtin pack '${name}'`);
+  pack.installed = installed;
+  return `${installed ? "Installed" : "Removed"} pack '${name}' in .claude/hooks.yaml`;
+}
+
+const mockWorkspaces: WorkspaceState = { projects: [], active: null };
+
+async function mockListWorkspaces(limit = 10): Promise<WorkspaceSummary[]> {
+  await delay(50);
+  return mockWorkspaces.projects.map((project) => ({
+    ...project,
+    active: mockWorkspaces.active === project.path,
+    configPath: `${project.path}/.claude/hooks.yaml`,
+    configExists: true,
+    ruleCount: 3,
+    configError: null,
+    recentDecisions: generateMockLogEntries(limit).filter((e) => e.rulesMatched.length > 0),
+  }));
+}
+
+async function mockAddWorkspace(path: string, name?: string): Promise<WorkspaceState> {
+  await delay(50);
+  const root = path.replace(/\/+$/, "") || "/";
+  const label = name?.trim() || root.split("/").pop() || root;
+  const existing = mockWorkspaces.projects.find((p) => p.path === root);
+  if (existing) {
+    existing.name = label;
+  } else {
+    mockWorkspaces.projects.push({ path: root, name: label });
+  }
+  mockWorkspaces.active ??= root;
+  return structuredClone(mockWorkspaces);
+}
+
+async function mockRemoveWorkspace(path: string): Promise<WorkspaceState> {
+  await delay(50);
+  const index = mockWorkspaces.projects.findIndex((p) => p.path === path);
+  if (index === -1) throw new Error(`Workspace not registered: ${path}`);
+  mockWorkspaces.projects.splice(index, 1);
+  if (mockWorkspaces.active === path) {
+    mockWorkspaces.active = mockWorkspaces.projects[0]?.path ?? null;
+  }
+  return structuredClone(mockWorkspaces);
+}
+
+async function mockSetActiveWorkspace(path: string | null): Promise<WorkspaceState> {
+  await delay(50);
+  if (path !== null && !mockWorkspaces.projects.some((p) => p.path === path)) {
+    throw new Error(`Workspace not registered: ${path}`);
+  }
+  mockWorkspaces.active = path;
+  return structuredClone(mockWorkspaces);
+}
+
 async function mockRunDebug(params: DebugParams): Promise<DebugResult> {
   await delay(100);
 
@@ -194,7 +696,7 @@ async function mockRunDebug(params: DebugParams): Promise<DebugResult> {
   }
 
   // Simulate debug evaluation
-  const evaluations = [
+  const evaluations: RuleEvaluation[] = [
     {
       ruleName: "block-force-push",
       matched: params.command?.includes("--force") || params.command?.includes("-f") || false,
@@ -202,18 +704,33 @@ async function mockRunDebug(params: DebugParams): Promise<DebugResult> {
       details: "command_match evaluated",
       pattern: "git push.*(--force|-f).*(main|master)",
       input: params.command,
+      elapsedUs: 800,
+      matchers: [
+        { matcher: "tools", matched: params.tool === "Bash", pattern: "Bash", input: params.tool },
+        {
+          matcher: "command_match",
+          matched: params.command?.includes("--force") || params.command?.includes("-f") || false,
+          pattern: "git push.*(--force|-f).*(main|master)",
+          input: params.command,
+        },
+      ],
     },
     {
       ruleName: "inject-python-context",
       matched: false,
       timeMs: 0.1,
       details: "tool mismatch",
+      elapsedUs: 100,
+      matchers: [{ matcher: "tools", matched: false, pattern: "Write, Edit", input: params.tool }],
     },
   ];
 
   const matched = evaluations.filter((e) => e.matched);
   const isBlocked = matched.some((e) => e.ruleName === "block-force-push");
 
+  const block = evaluations.find((e) => e.ruleName === "block-force-push");
+  if (block?.matched) block.action = "block";
+
   return {
     outcome: isBlocked ? "Block" : "Allow",
     reason: isBlocked ? "Force push to main/master is prohibited" : undefined,
@@ -229,9 +746,15 @@ async function mockValidateConfig(_path: string): Promise<{ valid: boolean; erro
   return { valid: true, errors: [] };
 }
 
-async function mockCheckBinary(): Promise<{ found: boolean; path: string | null }> {
+async function mockCheckBinary(checkLatest: boolean): Promise<BinaryCheckResult> {
   await delay(50);
-  return { found: true, path: "/usr/local/bin/rulez" };
+  return {
+    found: true,
+    path: "/usr/local/bin/rulez",
+    version: "2.2.0",
+    latestVersion: checkLatest ? "2.2.0" : null,
+    updateAvailable: checkLatest ? false : null,
+  };
 }
 
 async function mockImportConfigFile(): Promise<{ path: string; content: string } | null> {
@@ -317,6 +840,54 @@ async function mockReadLogs(_params: LogQueryParams): Promise<LogEntryDto[]> {
   return generateMockLogEntries(50);
 }
 
+async function mockGetDecisionStats(
+  range: StatsRange,
+  groupBy: StatsGroupBy,
+): Promise<DecisionStats> {
+  await delay(50);
+  const hourly = range === "1h" || range === "24h";
+  const bucketSeconds = hourly ? 3600 : 86400;
+  const bucketCount = range === "1h" ? 2 : range === "24h" ? 25 : range === "30d" ? 31 : 8;
+  const keyOf = (entry: LogEntryDto): string[] =>
+    groupBy === "rule"
+      ? entry.rulesMatched
+      : groupBy === "tool"
+        ? [entry.toolName ?? "(none)"]
+        : [entry.decision ?? entry.outcome];
+
+  const now = Math.floor(Date.now() / 1000 / bucketSeconds) * bucketSeconds;
+  const buckets = Array.from({ length: bucketCount }, (_, i) => ({
+    start: new Date((now - (bucketCount - 1 - i) * bucketSeconds) * 1000).toISOString(),
+    counts: {} as Record<string, number>,
+  }));
+  const totals: Record<string, number> = {};
+  const entries = generateMockLogEntries(bucketCount * 6);
+  entries.forEach((entry, i) => {
+    const bucket = buckets[buckets.length - 1 - (i % bucketCount)];
+    if (!bucket) return;
+    for (const key of keyOf(entry)) {
+      bucket.counts[key] = (bucket.counts[key] ?? 0) + 1;
+      totals[key] = (totals[key] ?? 0) + 1;
+    }
+  });
+
+  return { range, groupBy, bucketSeconds, buckets, totals, totalEntries: entries.length };
+}
+
+async function mockTailLogs(
+  onEntry: (entry: LogEntryDto) => void,
+  params: TailParams,
+): Promise<() => Promise<void>> {
+  const timer = setInterval(
+    () => {
+      const [entry] = generateMockLogEntries(1);
+      if (entry) onEntry(entry);
+    },
+    Math.max(params.intervalMs ?? 2000, 100),
+  );
+  return async () => clearInterval(timer);
+}
+
 async function mockGetLogStats(): Promise<LogStats> {
   await delay(50);
   return {
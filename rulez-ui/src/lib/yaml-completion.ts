@@ -0,0 +1,190 @@
+import type { CompletionData, KeyDoc } from "@/types";
+import type { IDisposable, Position, editor, languages } from "monaco-editor";
+import { getCompletionData } from "./tauri";
+
+type Monaco = typeof import("monaco-editor");
+
+/** How long fetched completion data is reused before asking RuleZ again */
+const CACHE_MS = 30_000;
+
+/** Keys whose values are rule names */
+const RULE_REFERENCE_KEYS = new Set(["pre_rules", "replacement"]);
+
+interface LineInfo {
+  lineNumber: number;
+  text: string;
+  indent: number;
+  listItem: boolean;
+}
+
+type CompletionContext =
+  | { kind: "value"; key: string }
+  | { kind: "key"; mapping: "rule" | "matchers" | "actions" };
+
+function lineInfo(model: editor.ITextModel, lineNumber: number, text?: string): LineInfo {
+  const content = text ?? model.getLineContent(lineNumber);
+  const indent = content.search(/\S|$/);
+  return { lineNumber, text: content, indent, listItem: content.trimStart().startsWith("-") };
+}
+
+/** The nearest line above that owns `line` (a key one level up, or the list item it is in) */
+function parentOf(model: editor.ITextModel, line: LineInfo): LineInfo | null {
+  for (let n = line.lineNumber - 1; n >= 1; n--) {
+    const candidate = lineInfo(model, n);
+    const trimmed = candidate.text.trim();
+    if (!trimmed || trimmed.startsWith("#")) continue;
+    // List items may sit at the same indentation as their key
+    if (
+      candidate.indent < line.indent ||
+      (candidate.indent === line.indent && line.listItem && !candidate.listItem)
+    ) {
+      return candidate;
+    }
+  }
+  return null;
+}
+
+/** Key name on a line such as `  - matchers:` or `tools: [Bash]` */
+function keyOf(line: LineInfo): string | null {
+  return /^\s*(?:-\s+)?([\w.]+):/.exec(line.text)?.[1] ?? null;
+}
+
+function findContext(model: editor.ITextModel, position: Position): CompletionContext | null {
+  const before = model.getLineContent(position.lineNumber).slice(0, position.column - 1);
+
+  // Inline value, e.g. `tools: [Bash, ` or `replacement: `
+  const inline = /^\s*(?:-\s+)?([\w.]+):\s+(?:\[[^\]]*|[^\s[{][^:]*|)$/.exec(before);
+  if (inline?.[1]) return { kind: "value", key: inline[1] };
+
+  const current = lineInfo(model, position.lineNumber, before);
+  const parent = parentOf(model, current);
+  if (!parent) return null;
+
+  // Block list item, e.g. `  - Ba`
+  if (/^\s*-\s+[\w"'.-]*$/.test(before)) {
+    const key = keyOf(parent);
+    if (!key || !/:\s*$/.test(parent.text)) return null;
+    // Pipeline steps take action keys
+    return key === "actions" ? { kind: "key", mapping: "actions" } : { kind: "value", key };
+  }
+
+  if (!/^\s*[\w]*$/.test(before)) return null;
+  const parentKey = keyOf(parent);
+  if (parentKey === "matchers" && /:\s*$/.test(parent.text)) {
+    return { kind: "key", mapping: "matchers" };
+  }
+  if (parentKey === "actions" && /:\s*$/.test(parent.text)) {
+    return { kind: "key", mapping: "actions" };
+  }
+  // A key beside `- name: ...`: inside a rule if that item is under `rules:`
+  if (parent.listItem) {
+    const list = parentOf(model, parent);
+    if (list && keyOf(list) === "rules") return { kind: "key", mapping: "rule" };
+    if (list && keyOf(list) === "actions") return { kind: "key", mapping: "actions" };
+  }
+  return null;
+}
+
+/** Rule names defined in the open document */
+function documentRuleNames(model: editor.ITextModel): string[] {
+  return [...model.getValue().matchAll(/^\s*-\s+name:\s*["']?([^"'#\s]+)/gm)]
+    .map((m) => m[1])
+    .filter((name): name is string => Boolean(name));
+}
+
+/**
+ * Register context-aware completions for hooks.yaml: tool names in `tools`,
+ * rule names in `pre_rules`/`replacement`, event types in `operations`, and
+ * documented rule/matcher/action keys. Complements the schema completions
+ * from monaco-yaml with data from `rulez completion-data`.
+ */
+export function registerYamlCompletions(monaco: Monaco): IDisposable {
+  let cached: { data: CompletionData; at: number } | null = null;
+
+  const load = async (): Promise<CompletionData | null> => {
+    if (cached && Date.now() - cached.at < CACHE_MS) return cached.data;
+    try {
+      const data = await getCompletionData();
+      cached = { data, at: Date.now() };
+      return data;
+    } catch {
+      return cached?.data ?? null;
+    }
+  };
+
+  return monaco.languages.registerCompletionItemProvider("yaml", {
+    triggerCharacters: [" ", "[", ","],
+    async provideCompletionItems(
+      model: editor.ITextModel,
+      position: Position,
+    ): Promise<languages.CompletionList> {
+      const context = findContext(model, position);
+      if (!context) return { suggestions: [] };
+      const data = await load();
+      if (!data) return { suggestions: [] };
+
+      const word = model.getWordUntilPosition(position);
+      const range = {
+        startLineNumber: position.lineNumber,
+        endLineNumber: position.lineNumber,
+        startColumn: word.startColumn,
+        endColumn: word.endColumn,
+      };
+      const Kind = monaco.languages.CompletionItemKind;
+
+      if (context.kind === "key") {
+        const keys: KeyDoc[] =
+          context.mapping === "matchers"
+            ? data.matcherKeys
+            : context.mapping === "actions"
+              ? data.actionKeys
+              : data.ruleKeys;
+        return {
+          suggestions: keys.map((k) => ({
+            label: k.key,
+            kind: Kind.Property,
+            documentation: k.doc,
+            insertText: `${k.key}: `,
+            range,
+          })),
+        };
+      }
+
+      if (context.key === "tools") {
+        return {
+          suggestions: data.tools.map((tool, index) => ({
+            label: tool.name,
+            kind: Kind.Value,
+            detail: tool.seen > 0 ? `seen ${tool.seen} times` : tool.builtin ? "built-in" : "",
+            insertText: tool.name,
+            sortText: String(index).padStart(4, "0"),
+            range,
+          })),
+        };
+      }
+      if (RULE_REFERENCE_KEYS.has(context.key)) {
+        const names = new Set([...data.rules.map((r) => r.name), ...documentRuleNames(model)]);
+        return {
+          suggestions: [...names].map((name) => ({
+            label: name,
+            kind: Kind.Reference,
+            documentation: data.rules.find((r) => r.name === name)?.description ?? undefined,
+            insertText: name,
+            range,
+          })),
+        };
+      }
+      if (context.key === "operations") {
+        return {
+          suggestions: data.eventTypes.map((eventType) => ({
+            label: eventType,
+            kind: Kind.EnumMember,
+            insertText: eventType,
+            range,
+          })),
+        };
+      }
+      return { suggestions: [] };
+    },
+  });
+}
@@ -70,6 +70,144 @@ export interface RuleEvaluation {
   details?: string;
   pattern?: string;
   input?: string;
+  scriptOutput?: string;
+  elapsedUs?: number;
+  matchers?: MatcherTrace[];
+  action?: string;
+}
+
+export interface MatcherTrace {
+  matcher: string;
+  matched: boolean;
+  pattern?: string;
+  input?: string;
+}
+
+export interface FieldChange {
+  path: string;
+  before: unknown;
+  after: unknown;
+}
+
+export interface RuleChange {
+  name: string;
+  changes: FieldChange[];
+}
+
+export interface ConfigDiff {
+  addedRules: Record<string, unknown>[];
+  removedRules: Record<string, unknown>[];
+  modifiedRules: RuleChange[];
+  settings: FieldChange[];
+}
+
+// Rule test suite fixtures (.claude/tests/*.yaml, same format as `rulez test`)
+export interface FixtureCase {
+  name: string;
+  event_type: string;
+  tool?: string;
+  command?: string;
+  path?: string;
+  prompt?: string;
+  expected: "allow" | "block" | "inject";
+}
+
+export interface Fixture {
+  name: string;
+  path: string;
+  tests: FixtureCase[];
+  error: string | null;
+}
+
+export interface ExpectationResult {
+  name: string;
+  expected: string;
+  actual: string;
+  passed: boolean;
+  reason: string | null;
+}
+
+export interface FixtureRunResult {
+  file: string;
+  passed: number;
+  failed: number;
+  total: number;
+  results: ExpectationResult[];
+}
+
+export interface BinaryCheckResult {
+  found: boolean;
+  path: string | null;
+  version: string | null;
+  latestVersion: string | null;
+  updateAvailable: boolean | null;
+}
+
+export interface PackInfo {
+  name: string;
+  description: string;
+  rules: string[];
+  installed: boolean;
+}
+
+/** A configuration key and what it does */
+export interface KeyDoc {
+  key: string;
+  doc: string;
+}
+
+export interface ToolCompletion {
+  name: string;
+  builtin: boolean;
+  /** Events in the audit log that used this tool */
+  seen: number;
+}
+
+export interface RuleCompletion {
+  name: string;
+  description: string | null;
+  priority: number;
+}
+
+/** Editor completion data from `rulez completion-data` */
+export interface CompletionData {
+  tools: ToolCompletion[];
+  rules: RuleCompletion[];
+  eventTypes: string[];
+  ruleKeys: KeyDoc[];
+  matcherKeys: KeyDoc[];
+  actionKeys: KeyDoc[];
+}
+
+/** A structured config edit, applied without disturbing comments */
+export type ConfigEdit =
+  | { op: "insert_rule"; rule: Rule }
+  | { op: "update_rule"; name: string; rule: Rule }
+  | { op: "delete_rule"; name: string }
+  | { op: "set_enabled"; name: string; enabled: boolean }
+  | { op: "set_priority"; name: string; priority: number };
+
+export interface Workspace {
+  path: string;
+  name: string;
+}
+
+export interface WorkspaceState {
+  projects: Workspace[];
+  /** Active project path; commands without a project dir use it */
+  active: string | null;
+}
+
+export interface WorkspaceSummary {
+  path: string;
+  name: string;
+  active: boolean;
+  configPath: string;
+  configExists: boolean;
+  ruleCount: number | null;
+  configError: string | null;
+  /** Latest decisions made in this project, newest first */
+  recentDecisions: LogEntryDto[];
 }
 
 export interface DebugResult {
@@ -156,6 +294,30 @@ export interface LogQueryParams {
   limit?: number;
 }
 
+export interface TailParams {
+  textFilter?: string;
+  outcomeFilter?: string;
+  decisionFilter?: string;
+  intervalMs?: number;
+}
+
+export type StatsRange = "1h" | "24h" | "7d" | "30d" | "all" | (string & {});
+export type StatsGroupBy = "rule" | "decision" | "tool";
+
+export interface StatsBucket {
+  start: string;
+  counts: Record<string, number>;
+}
+
+export interface DecisionStats {
+  range: string;
+  groupBy: StatsGroupBy;
+  bucketSeconds: number;
+  buckets: StatsBucket[];
+  totals: Record<string, number>;
+  totalEntries: number;
+}
+
 export interface LogStats {
   totalEntries: number;
   fileSizeBytes: number;
@@ -0,0 +1,228 @@
+//! Rule actions: the `Action` trait, its outcome, and custom action plugins.
+//!
+//! Every action a rule can take (`block`, `inject`, `run`, ...) implements
+//! [`Action`]. When a rule matches, its actions run in a fixed order until
+//! one returns an outcome other than [`ActionOutcome::Continue`]. The
+//! built-in actions live in `hooks` and run first, in this order:
+//!
+//! `webhook`, `validate_expr`, `inline_script`, `script_rhai`, `block`,
+//! `block_if_match`, `scan_secrets`, `scan_pii`, `inject_inline`,
+//! `inject_command`, `inject`, `run`
+//!
+//! Embedders add their own, such as posting to an internal service, by
+//! registering an [`Action`] before the config is loaded. Rules configure it
+//! under `actions.custom`, keyed by the action's name, and custom actions run
+//! after the built-in ones in name order:
+//!
+//! ```yaml
+//! actions:
+//!   custom:
+//!     notify_oncall: { channel: "#deploys" }
+//! ```
+//!
+//! A name with no registered action blocks the operation (fail closed), and
+//! `Config::validate` rejects it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+use crate::config::Config;
+use crate::models::{Event, PolicyMode, Response, Rule};
+
+/// What an action decided
+#[allow(clippy::large_enum_variant)] // Short-lived return value; boxing would only add an allocation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionOutcome {
+    /// Nothing to report; the next action runs
+    Continue,
+
+    /// Block the operation with this reason
+    ///
+    /// In warn mode the operation proceeds with a warning instead.
+    Block(String),
+
+    /// Inject this context and stop
+    Inject(String),
+
+    /// Answer with this response and stop (e.g. a validator script's)
+    Respond(Response),
+}
+
+impl ActionOutcome {
+    /// The response for an outcome that ends the action chain
+    pub fn into_response(self) -> Option<Response> {
+        match self {
+            Self::Continue => None,
+            Self::Block(reason) => Some(Response::block(reason)),
+            Self::Inject(context) => Some(Response::inject(context)),
+            Self::Respond(response) => Some(response),
+        }
+    }
+}
+
+/// What an action runs with besides the event
+pub struct ActionContext<'a> {
+    /// The matched rule
+    pub rule: &'a Rule,
+
+    /// The loaded configuration
+    pub config: &'a Config,
+
+    /// The rule's effective policy mode (never `Audit`; audit runs no actions)
+    pub mode: PolicyMode,
+
+    /// The rule's configured value under `actions.custom` (null for built-ins)
+    #[allow(dead_code)] // Read by custom actions
+    pub value: &'a serde_json::Value,
+}
+
+/// Something a rule does when it matches
+pub trait Action: Send + Sync {
+    /// Key under `actions.custom`, e.g. `"notify_oncall"`
+    fn name(&self) -> &str;
+
+    /// Check a rule's configured value when the config is validated
+    fn validate(&self, value: &serde_json::Value) -> Result<()> {
+        let _ = value;
+        Ok(())
+    }
+
+    /// Run the action
+    ///
+    /// Errors stop the chain and are handled like a failed validator script,
+    /// honouring the rule's `fail_open` (default `settings.fail_open`).
+    fn execute<'a>(
+        &'a self,
+        event: &'a Event,
+        ctx: &'a ActionContext<'a>,
+    ) -> BoxFuture<'a, Result<ActionOutcome>>;
+}
+
+static REGISTRY: LazyLock<RwLock<HashMap<String, Arc<dyn Action>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Register an action, replacing (and returning) one with the same name
+#[allow(dead_code)] // Library API for embedders
+pub fn register(action: impl Action + 'static) -> Option<Arc<dyn Action>> {
+    let action: Arc<dyn Action> = Arc::new(action);
+    REGISTRY
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(action.name().to_string(), action)
+}
+
+/// Remove a registered action
+#[allow(dead_code)] // Library API for embedders
+pub fn unregister(name: &str) -> Option<Arc<dyn Action>> {
+    REGISTRY
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .remove(name)
+}
+
+/// Look up a registered action by name
+pub fn get(name: &str) -> Option<Arc<dyn Action>> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(name)
+        .cloned()
+}
+
+/// Names of all registered actions, sorted
+#[allow(dead_code)] // Library API for embedders
+pub fn registered() -> Vec<String> {
+    let mut names: Vec<String> = REGISTRY
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+/// Check every entry of a rule's `actions.custom`
+pub(crate) fn validate_custom(
+    custom: &std::collections::BTreeMap<String, serde_json::Value>,
+) -> Result<()> {
+    for (name, value) in custom {
+        let Some(action) = get(name) else {
+            anyhow::bail!("Unknown custom action '{}'", name);
+        };
+        action
+            .validate(value)
+            .map_err(|e| e.context(format!("Invalid value for custom action '{}'", name)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Reject;
+
+    impl Action for Reject {
+        fn name(&self) -> &'static str {
+            "test_reject"
+        }
+
+        fn validate(&self, value: &serde_json::Value) -> Result<()> {
+            if value.is_string() {
+                Ok(())
+            } else {
+                anyhow::bail!("expected a reason")
+            }
+        }
+
+        fn execute<'a>(
+            &'a self,
+            _event: &'a Event,
+            ctx: &'a ActionContext<'a>,
+        ) -> BoxFuture<'a, Result<ActionOutcome>> {
+            Box::pin(async move {
+                Ok(ActionOutcome::Block(
+                    ctx.value.as_str().unwrap_or_default().to_string(),
+                ))
+            })
+        }
+    }
+
+    #[test]
+    fn test_register_and_validate() {
+        register(Reject);
+        assert!(registered().contains(&"test_reject".to_string()));
+
+        let custom = |value| std::collections::BTreeMap::from([("test_reject".to_string(), value)]);
+        assert!(validate_custom(&custom(serde_json::json!("no"))).is_ok());
+        let err = validate_custom(&custom(serde_json::json!(1))).unwrap_err();
+        assert!(format!("{:#}", err).contains("expected a reason"));
+
+        let unknown =
+            std::collections::BTreeMap::from([("nope".to_string(), serde_json::json!(1))]);
+        assert_eq!(
+            validate_custom(&unknown).unwrap_err().to_string(),
+            "Unknown custom action 'nope'"
+        );
+
+        assert!(unregister("test_reject").is_some());
+        assert!(get("test_reject").is_none());
+    }
+
+    #[test]
+    fn test_outcome_into_response() {
+        assert!(ActionOutcome::Continue.into_response().is_none());
+        let blocked = ActionOutcome::Block("no".to_string())
+            .into_response()
+            .unwrap();
+        assert!(!blocked.continue_);
+        let injected = ActionOutcome::Inject("hi".to_string())
+            .into_response()
+            .unwrap();
+        assert_eq!(injected.context.as_deref(), Some("hi"));
+    }
+}
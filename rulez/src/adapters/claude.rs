@@ -0,0 +1,67 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use super::{AdaptedEvent, FormatAdapter, HookOutput};
+use crate::models::{Event, Response};
+
+/// Claude Code's native hook format
+///
+/// Also the fallback for payloads no other adapter claims, so `detect`
+/// accepts anything.
+pub struct ClaudeAdapter;
+
+impl FormatAdapter for ClaudeAdapter {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn label(&self) -> &'static str {
+        "Claude Code"
+    }
+
+    fn detect(&self, _value: &Value) -> bool {
+        true
+    }
+
+    fn parse(&self, value: Value) -> Result<AdaptedEvent> {
+        let event: Event = serde_json::from_value(value)?;
+        Ok(AdaptedEvent {
+            hook_event_name: event.hook_event_name.to_string(),
+            is_tool_event: event.tool_name.is_some(),
+            additional_event_types: Vec::new(),
+            event,
+        })
+    }
+
+    fn render(&self, response: &Response, _event: &AdaptedEvent) -> Result<HookOutput> {
+        Self::output(response)
+    }
+}
+
+impl ClaudeAdapter {
+    /// Exit code 2 with the reason on stderr to block, otherwise JSON on stdout
+    pub fn output(response: &Response) -> Result<HookOutput> {
+        if !response.continue_ {
+            // Claude Code hooks protocol: exit code 2 BLOCKS the tool call.
+            // Only stderr is used as the error message and fed back to Claude.
+            // Exit code 0 with "continue":false only stops the conversation,
+            // it does NOT prevent the tool from executing.
+            let reason = response
+                .reason
+                .as_deref()
+                .unwrap_or("Blocked by RuleZ policy");
+            return Ok(HookOutput {
+                stdout: None,
+                stderr: Some(reason.to_string()),
+                exit_code: 2,
+            });
+        }
+
+        // `inject_as: stderr` content; with exit code 0 the client shows it in
+        // the transcript only
+        Ok(HookOutput {
+            stderr: response.stderr.clone(),
+            ..HookOutput::json(response)?
+        })
+    }
+}
@@ -3,8 +3,54 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{Map, Value};
 
+use super::{AdaptedEvent, FormatAdapter, HookOutput};
 use crate::models::{CopilotDecision, CopilotHookResponse, Event, EventType, Response};
 
+/// Copilot hook names; Claude Code's are PascalCase
+const COPILOT_EVENTS: &[&str] = &[
+    "preToolUse",
+    "postToolUse",
+    "promptSubmit",
+    "sessionStart",
+    "sessionEnd",
+    "errorOccurred",
+    "preCompact",
+];
+
+/// GitHub Copilot CLI hooks (`rulez copilot hook`)
+pub struct CopilotAdapter;
+
+impl FormatAdapter for CopilotAdapter {
+    fn name(&self) -> &'static str {
+        "copilot"
+    }
+
+    fn label(&self) -> &'static str {
+        "Copilot"
+    }
+
+    fn detect(&self, value: &Value) -> bool {
+        detect(value)
+    }
+
+    fn parse(&self, value: Value) -> Result<AdaptedEvent> {
+        parse_event(value)
+    }
+
+    fn render(&self, response: &Response, event: &AdaptedEvent) -> Result<HookOutput> {
+        HookOutput::json(&translate_response(response, event))
+    }
+
+    fn fail_open(&self, reason: &str) -> Option<HookOutput> {
+        HookOutput::json(&CopilotHookResponse {
+            permission_decision: CopilotDecision::Allow,
+            permission_decision_reason: Some(reason.to_string()),
+            tool_input: None,
+        })
+        .ok()
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 struct CopilotHookInput {
@@ -24,16 +70,15 @@ struct CopilotHookInput {
     extra: Map<String, Value>,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct CopilotEvent {
-    pub hook_event_name: String,
-    pub event: Event,
-    pub is_tool_event: bool,
+/// Whether a hook payload comes from Copilot CLI (camelCase event names)
+pub fn detect(value: &Value) -> bool {
+    value
+        .get("hook_event_name")
+        .and_then(Value::as_str)
+        .is_some_and(|name| COPILOT_EVENTS.contains(&name))
 }
 
-#[allow(dead_code)]
-pub fn parse_event(value: Value) -> Result<CopilotEvent> {
+pub fn parse_event(value: Value) -> Result<AdaptedEvent> {
     let input: CopilotHookInput = serde_json::from_value(value)?;
     let (event_type, is_tool_event) = map_event_type(&input.hook_event_name);
     let preserve_name = input.hook_event_name != event_type.to_string();
@@ -97,19 +142,26 @@ pub fn parse_event(value: Value) -> Result<CopilotEvent> {
         permission_mode: None,
         tool_use_id: None,
         prompt: None,
+        tool_response: None,
+        stop_hook_active: None,
+        trigger: None,
+        custom_instructions: None,
+        message: None,
+        pre_tool_use: None,
+        identity: None,
     };
 
-    Ok(CopilotEvent {
+    Ok(AdaptedEvent {
         hook_event_name: input.hook_event_name,
         event,
         is_tool_event,
+        additional_event_types: Vec::new(),
     })
 }
 
-#[allow(dead_code)]
 pub fn translate_response(
     response: &Response,
-    copilot_event: &CopilotEvent,
+    copilot_event: &AdaptedEvent,
 ) -> CopilotHookResponse {
     let decision = if response.continue_ {
         CopilotDecision::Allow
@@ -141,7 +193,6 @@ pub fn translate_response(
     }
 }
 
-#[allow(dead_code)]
 fn map_event_type(hook_event_name: &str) -> (EventType, bool) {
     match hook_event_name {
         "preToolUse" => (EventType::PreToolUse, true),
@@ -159,7 +210,6 @@ fn map_event_type(hook_event_name: &str) -> (EventType, bool) {
 ///
 /// Copilot tool names are mostly PascalCase like Claude Code, but some
 /// differ (e.g., `shell` → `Bash`). Unknown names pass through unchanged.
-#[allow(dead_code)]
 fn map_tool_name(platform_name: &str) -> String {
     match platform_name {
         "shell" => "Bash".to_string(),
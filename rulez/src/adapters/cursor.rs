@@ -0,0 +1,229 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{AdaptedEvent, FormatAdapter, HookOutput};
+use crate::models::{CursorHookResponse, Event, EventType, PermissionDecision, Response};
+
+/// Cursor hook names that RuleZ maps onto its own event types
+const CURSOR_EVENTS: &[&str] = &[
+    "beforeShellExecution",
+    "afterShellExecution",
+    "beforeMCPExecution",
+    "afterMCPExecution",
+    "beforeReadFile",
+    "afterFileEdit",
+    "beforeSubmitPrompt",
+    "afterAgentResponse",
+    "stop",
+];
+
+#[derive(Debug, Deserialize)]
+struct CursorHookInput {
+    conversation_id: String,
+    hook_event_name: String,
+    #[serde(default)]
+    workspace_roots: Vec<String>,
+    #[serde(default)]
+    user_email: Option<String>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+/// Cursor agent hooks (`rulez --agent cursor`)
+///
+/// Cursor reads the decision from the JSON on stdout, so blocks exit 0.
+pub struct CursorAdapter;
+
+impl FormatAdapter for CursorAdapter {
+    fn name(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn label(&self) -> &'static str {
+        "Cursor"
+    }
+
+    fn detect(&self, value: &Value) -> bool {
+        detect(value)
+    }
+
+    fn parse(&self, value: Value) -> Result<AdaptedEvent> {
+        parse_event(value)
+    }
+
+    fn render(&self, response: &Response, event: &AdaptedEvent) -> Result<HookOutput> {
+        HookOutput::json(&translate_response(response, event))
+    }
+}
+
+/// Whether a hook payload comes from Cursor rather than Claude Code
+///
+/// Cursor identifies the chat by `conversation_id` and names its hooks in
+/// camelCase (`beforeShellExecution`); Claude Code sends `session_id` and
+/// PascalCase event names.
+pub fn detect(value: &Value) -> bool {
+    let Some(map) = value.as_object() else {
+        return false;
+    };
+    if map.contains_key("session_id") {
+        return false;
+    }
+    map.contains_key("conversation_id")
+        || map
+            .get("hook_event_name")
+            .and_then(Value::as_str)
+            .is_some_and(|name| CURSOR_EVENTS.contains(&name))
+}
+
+pub fn parse_event(value: Value) -> Result<AdaptedEvent> {
+    let input: CursorHookInput = serde_json::from_value(value)?;
+    let (event_type, tool_name) = map_event_type(&input.hook_event_name, &input.extra);
+    let mut extra = input.extra;
+    extra.remove("tool_name");
+
+    // Cursor sends MCP arguments as a JSON string
+    if let Some(Value::String(raw)) = extra.get("tool_input") {
+        if let Ok(parsed @ Value::Object(_)) = serde_json::from_str::<Value>(raw) {
+            extra.insert("tool_input".to_string(), parsed);
+        }
+    }
+
+    let prompt = extra
+        .get("prompt")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let tool_response = ["output", "result_json", "text"]
+        .iter()
+        .find_map(|key| extra.get(*key).cloned());
+    let cwd = extra
+        .get("cwd")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| input.workspace_roots.first().cloned());
+
+    // Map-first pattern: MCP calls carry their own tool_input object, every
+    // other hook's fields become the tool input
+    let mut tool_input_map = match extra.remove("tool_input") {
+        Some(Value::Object(map)) => map,
+        Some(value) => {
+            let mut map = Map::new();
+            map.insert("tool_input".to_string(), value);
+            map
+        }
+        None => Map::new(),
+    };
+    for (key, value) in extra {
+        tool_input_map.entry(key).or_insert(value);
+    }
+    if input.hook_event_name != event_type.to_string() {
+        tool_input_map
+            .entry("cursor_hook_event_name".to_string())
+            .or_insert(Value::String(input.hook_event_name.clone()));
+    }
+
+    let event = Event {
+        hook_event_name: event_type,
+        tool_name,
+        tool_input: if tool_input_map.is_empty() {
+            None
+        } else {
+            Some(Value::Object(tool_input_map))
+        },
+        session_id: input.conversation_id,
+        timestamp: Utc::now(),
+        user_id: input.user_email,
+        transcript_path: None,
+        cwd,
+        permission_mode: None,
+        tool_use_id: None,
+        prompt,
+        tool_response,
+        stop_hook_active: None,
+        trigger: None,
+        custom_instructions: None,
+        message: None,
+        pre_tool_use: None,
+        identity: None,
+    };
+
+    Ok(AdaptedEvent {
+        hook_event_name: input.hook_event_name,
+        is_tool_event: event.tool_name.is_some(),
+        event,
+        additional_event_types: Vec::new(),
+    })
+}
+
+/// Render a RuleZ response in the shape the Cursor hook expects
+///
+/// Shell, MCP, and file-read hooks get `permission` (a rule's
+/// `permission_decision`, else allow/deny from `continue`); injected context
+/// reaches the agent as `agentMessage`. Prompt hooks get `continue`.
+pub fn translate_response(response: &Response, cursor_event: &AdaptedEvent) -> CursorHookResponse {
+    let user_message = if response.continue_ {
+        response.system_message.clone()
+    } else {
+        response.reason.clone()
+    };
+
+    match cursor_event.hook_event_name.as_str() {
+        "beforeShellExecution" | "beforeMCPExecution" | "beforeReadFile" => {
+            let permission = response
+                .hook_specific_output
+                .as_ref()
+                .and_then(|output| output.permission_decision)
+                .filter(|_| response.continue_)
+                .unwrap_or(if response.continue_ {
+                    PermissionDecision::Allow
+                } else {
+                    PermissionDecision::Deny
+                });
+            let agent_message = if response.continue_ {
+                response.context.clone()
+            } else {
+                response.reason.clone()
+            };
+            CursorHookResponse {
+                permission: Some(permission),
+                user_message,
+                agent_message,
+                ..CursorHookResponse::default()
+            }
+        }
+        "beforeSubmitPrompt" => CursorHookResponse {
+            continue_: Some(response.continue_),
+            user_message,
+            ..CursorHookResponse::default()
+        },
+        _ => CursorHookResponse::default(),
+    }
+}
+
+/// Map a Cursor hook name to a RuleZ event type and canonical tool name
+///
+/// Shell hooks become `Bash`, file hooks `Read`/`Edit`, and MCP hooks keep
+/// the MCP tool's own name. Unknown hooks map to `Notification`.
+fn map_event_type(
+    hook_event_name: &str,
+    extra: &Map<String, Value>,
+) -> (EventType, Option<String>) {
+    let mcp_tool = || {
+        extra
+            .get("tool_name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    match hook_event_name {
+        "beforeShellExecution" => (EventType::PreToolUse, Some("Bash".to_string())),
+        "afterShellExecution" => (EventType::PostToolUse, Some("Bash".to_string())),
+        "beforeMCPExecution" => (EventType::PreToolUse, mcp_tool()),
+        "afterMCPExecution" => (EventType::PostToolUse, mcp_tool()),
+        "beforeReadFile" => (EventType::PreToolUse, Some("Read".to_string())),
+        "afterFileEdit" => (EventType::PostToolUse, Some("Edit".to_string())),
+        "beforeSubmitPrompt" => (EventType::UserPromptSubmit, None),
+        "stop" => (EventType::Stop, None),
+        _ => (EventType::Notification, None),
+    }
+}
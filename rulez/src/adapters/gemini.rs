@@ -3,8 +3,49 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{Map, Value};
 
+use super::{AdaptedEvent, FormatAdapter, HookOutput};
 use crate::models::{Event, EventType, GeminiDecision, GeminiHookResponse, Response};
 
+/// Gemini CLI hooks (`rulez gemini hook`)
+pub struct GeminiAdapter;
+
+impl FormatAdapter for GeminiAdapter {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn label(&self) -> &'static str {
+        "Gemini"
+    }
+
+    fn detect(&self, value: &Value) -> bool {
+        detect(value)
+    }
+
+    fn parse(&self, value: Value) -> Result<AdaptedEvent> {
+        parse_event(value)
+    }
+
+    fn render(&self, response: &Response, event: &AdaptedEvent) -> Result<HookOutput> {
+        let mut hook_response = translate_response(response, event);
+        if event.is_tool_event {
+            ensure_hook_event_name(&mut hook_response, &event.hook_event_name);
+        }
+        HookOutput::json(&hook_response)
+    }
+
+    fn fail_open(&self, reason: &str) -> Option<HookOutput> {
+        HookOutput::json(&GeminiHookResponse {
+            decision: GeminiDecision::Allow,
+            reason: Some(reason.to_string()),
+            continue_: None,
+            system_message: None,
+            tool_input: None,
+        })
+        .ok()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct GeminiHookInput {
     session_id: String,
@@ -25,18 +66,19 @@ struct GeminiHookInput {
     extra: Map<String, Value>,
 }
 
-#[derive(Debug, Clone)]
-pub struct GeminiEvent {
-    pub hook_event_name: String,
-    pub event: Event,
-    pub is_tool_event: bool,
-    /// Additional event types to evaluate (dual-fire support).
-    /// Consumed by the platform-specific hook runner when processing events.
-    #[allow(dead_code)]
-    pub additional_event_types: Vec<EventType>,
+/// Whether a hook payload comes from Gemini CLI rather than Claude Code
+///
+/// Only the tool hooks have names of their own (`BeforeTool`, `AfterTool`);
+/// Gemini's other events share RuleZ's names, so select them with
+/// `--agent gemini` or `rulez gemini hook`.
+pub fn detect(value: &Value) -> bool {
+    matches!(
+        value.get("hook_event_name").and_then(Value::as_str),
+        Some("BeforeTool" | "AfterTool")
+    )
 }
 
-pub fn parse_event(value: Value) -> Result<GeminiEvent> {
+pub fn parse_event(value: Value) -> Result<AdaptedEvent> {
     let input: GeminiHookInput = serde_json::from_value(value)?;
     let mappings = map_event_type(
         &input.hook_event_name,
@@ -94,6 +136,12 @@ pub fn parse_event(value: Value) -> Result<GeminiEvent> {
         );
     }
 
+    // BeforeAgent carries the user's prompt; prompt_match reads Event.prompt
+    let prompt = tool_input_map
+        .get("prompt")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
     let event = Event {
         hook_event_name: primary_event_type,
         tool_name: canonical_tool_name,
@@ -109,10 +157,17 @@ pub fn parse_event(value: Value) -> Result<GeminiEvent> {
         cwd: input.cwd,
         permission_mode: None,
         tool_use_id: None,
-        prompt: None,
+        prompt,
+        tool_response: None,
+        stop_hook_active: None,
+        trigger: None,
+        custom_instructions: None,
+        message: None,
+        pre_tool_use: None,
+        identity: None,
     };
 
-    Ok(GeminiEvent {
+    Ok(AdaptedEvent {
         hook_event_name: input.hook_event_name,
         event,
         is_tool_event,
@@ -120,7 +175,7 @@ pub fn parse_event(value: Value) -> Result<GeminiEvent> {
     })
 }
 
-pub fn translate_response(response: &Response, gemini_event: &GeminiEvent) -> GeminiHookResponse {
+pub fn translate_response(response: &Response, gemini_event: &AdaptedEvent) -> GeminiHookResponse {
     let decision = if response.continue_ {
         GeminiDecision::Allow
     } else {
@@ -161,6 +216,26 @@ pub fn translate_response(response: &Response, gemini_event: &GeminiEvent) -> Ge
     }
 }
 
+/// Tag a tool input override with the Gemini event it answers
+fn ensure_hook_event_name(response: &mut GeminiHookResponse, hook_event_name: &str) {
+    match response.tool_input.as_mut() {
+        Some(Value::Object(map)) => {
+            map.entry("gemini_hook_event_name".to_string())
+                .or_insert(Value::String(hook_event_name.to_string()));
+        }
+        Some(other) => {
+            let mut map = Map::new();
+            map.insert("tool_input".to_string(), other.clone());
+            map.insert(
+                "gemini_hook_event_name".to_string(),
+                Value::String(hook_event_name.to_string()),
+            );
+            response.tool_input = Some(Value::Object(map));
+        }
+        None => {}
+    }
+}
+
 /// Map a Gemini hook event name to one or more RuleZ event types.
 ///
 /// Returns a Vec of (EventType, is_tool_event) tuples. The first entry is the primary
@@ -1,3 +1,179 @@
+//! Agent hook formats
+//!
+//! Each supported agent is a [`FormatAdapter`]: it recognizes the agent's
+//! payloads, parses them into a RuleZ [`Event`], and renders the engine's
+//! [`Response`] the way the agent expects. Supporting a new agent means adding
+//! a module here and an entry in [`ADAPTERS`]; `rulez --agent <name>` and the
+//! shared runner in `cli::agent_hook` pick it up from there.
+
+pub mod claude;
 pub mod copilot;
+pub mod cursor;
 pub mod gemini;
 pub mod opencode;
+
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::hooks;
+use crate::models::{DebugConfig, Event, EventType, Response};
+
+/// Built-in adapters, in auto-detection order (Claude Code last, as the fallback)
+pub static ADAPTERS: &[&dyn FormatAdapter] = &[
+    &cursor::CursorAdapter,
+    &gemini::GeminiAdapter,
+    &opencode::OpenCodeAdapter,
+    &copilot::CopilotAdapter,
+    &claude::ClaudeAdapter,
+];
+
+/// Future returned by [`FormatAdapter::evaluate`]
+pub type Evaluation<'a> = Pin<Box<dyn Future<Output = Result<Response>> + 'a>>;
+
+/// An agent's hook wire format
+pub trait FormatAdapter: Sync {
+    /// Name accepted by `--agent`, e.g. `gemini`
+    fn name(&self) -> &'static str;
+
+    /// Product name for messages, e.g. `Gemini`
+    fn label(&self) -> &'static str;
+
+    /// Whether a payload is in this agent's format
+    fn detect(&self, value: &Value) -> bool;
+
+    /// Parse a payload into a RuleZ event
+    fn parse(&self, value: Value) -> Result<AdaptedEvent>;
+
+    /// Render the engine's answer for the agent
+    fn render(&self, response: &Response, event: &AdaptedEvent) -> Result<HookOutput>;
+
+    /// Answer when the event can't be evaluated; `None` reports the error instead
+    fn fail_open(&self, _reason: &str) -> Option<HookOutput> {
+        None
+    }
+
+    /// Run the rules for an event (and its dual-fire types)
+    fn evaluate<'a>(
+        &'a self,
+        event: &'a AdaptedEvent,
+        debug_config: &'a DebugConfig,
+    ) -> Evaluation<'a> {
+        Box::pin(process_dual_fire(
+            event.event.clone(),
+            &event.additional_event_types,
+            debug_config,
+        ))
+    }
+}
+
+/// A RuleZ event parsed from an agent's payload
+#[derive(Debug, Clone)]
+pub struct AdaptedEvent {
+    /// Event name as the agent sent it
+    pub hook_event_name: String,
+    pub event: Event,
+    /// Whether the agent treats the event as a tool call
+    pub is_tool_event: bool,
+    /// Additional event types to evaluate (dual-fire support)
+    pub additional_event_types: Vec<EventType>,
+}
+
+/// What a hook writes back to the agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookOutput {
+    /// Text for stdout (usually JSON)
+    pub stdout: Option<String>,
+    /// Text for stderr
+    pub stderr: Option<String>,
+    /// Process exit code
+    pub exit_code: i32,
+}
+
+impl HookOutput {
+    /// JSON on stdout with exit code 0
+    pub fn json<T: Serialize>(value: &T) -> Result<Self> {
+        Ok(Self {
+            stdout: Some(serde_json::to_string(value)?),
+            ..Self::default()
+        })
+    }
+}
+
+/// Look up a built-in adapter by its `--agent` name
+pub fn find(name: &str) -> Option<&'static dyn FormatAdapter> {
+    ADAPTERS
+        .iter()
+        .copied()
+        .find(|adapter| adapter.name().eq_ignore_ascii_case(name))
+}
+
+/// The adapter for a payload, falling back to Claude Code
+pub fn detect(value: &Value) -> &'static dyn FormatAdapter {
+    ADAPTERS
+        .iter()
+        .copied()
+        .find(|adapter| adapter.detect(value))
+        .unwrap_or(&claude::ClaudeAdapter)
+}
+
+/// Evaluate an adapted event and its dual-fire event types
+///
+/// Rules for every mapped type run in one [`hooks::process_dual_fire_event`]
+/// pass, so the event is logged and counted once. The first block wins;
+/// otherwise the responses are merged.
+pub async fn process_dual_fire(
+    event: Event,
+    additional_event_types: &[EventType],
+    debug_config: &DebugConfig,
+) -> Result<Response> {
+    Ok(hooks::process_dual_fire_event(event, additional_event_types, debug_config).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_by_name() {
+        for adapter in ADAPTERS {
+            assert_eq!(find(adapter.name()).unwrap().name(), adapter.name());
+        }
+        assert_eq!(find("OpenCode").unwrap().name(), "opencode");
+        assert!(find("codex").is_none());
+    }
+
+    #[test]
+    fn test_detect_each_format() {
+        let cases = [
+            (
+                json!({"conversation_id": "c", "hook_event_name": "beforeShellExecution"}),
+                "cursor",
+            ),
+            (
+                json!({"session_id": "s", "hook_event_name": "BeforeTool"}),
+                "gemini",
+            ),
+            (
+                json!({"session_id": "s", "hook_event_name": "tool.execute.before"}),
+                "opencode",
+            ),
+            (
+                json!({"session_id": "s", "hook_event_name": "preToolUse"}),
+                "copilot",
+            ),
+            (
+                json!({"session_id": "s", "hook_event_name": "PreToolUse"}),
+                "claude",
+            ),
+            (json!("not an object"), "claude"),
+        ];
+        for (payload, expected) in cases {
+            assert_eq!(detect(&payload).name(), expected, "{payload}");
+        }
+    }
+}
@@ -3,7 +3,56 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{Map, Value};
 
-use crate::models::{Event, EventType, Response};
+use super::{AdaptedEvent, Evaluation, FormatAdapter, HookOutput};
+use crate::models::{DebugConfig, Event, EventType, PermissionDecision, Response};
+use crate::opencode::config::OpenCodePluginConfig;
+use crate::opencode::dispatcher::OpenCodeDispatcher;
+
+/// OpenCode plugin events (`rulez opencode hook`)
+///
+/// Evaluation goes through the plugin dispatcher for its event filters and
+/// audit log; denials exit with code 2.
+pub struct OpenCodeAdapter;
+
+impl FormatAdapter for OpenCodeAdapter {
+    fn name(&self) -> &'static str {
+        "opencode"
+    }
+
+    fn label(&self) -> &'static str {
+        "OpenCode"
+    }
+
+    fn detect(&self, value: &Value) -> bool {
+        detect(value)
+    }
+
+    fn parse(&self, value: Value) -> Result<AdaptedEvent> {
+        parse_event(value)
+    }
+
+    fn render(&self, response: &Response, event: &AdaptedEvent) -> Result<HookOutput> {
+        Ok(HookOutput {
+            exit_code: if response.continue_ { 0 } else { 2 },
+            ..HookOutput::json(&translate_response(response, event))?
+        })
+    }
+
+    fn fail_open(&self, reason: &str) -> Option<HookOutput> {
+        let mut response = Response::allow();
+        response.reason = Some(reason.to_string());
+        HookOutput::json(&response).ok()
+    }
+
+    fn evaluate<'a>(
+        &'a self,
+        event: &'a AdaptedEvent,
+        debug_config: &'a DebugConfig,
+    ) -> Evaluation<'a> {
+        let dispatcher = OpenCodeDispatcher::new(OpenCodePluginConfig::load().unwrap_or_default());
+        Box::pin(async move { dispatcher.dispatch(event, debug_config).await })
+    }
+}
 
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
@@ -22,16 +71,7 @@ struct OpenCodeHookInput {
     extra: Map<String, Value>,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct OpenCodeEvent {
-    pub hook_event_name: String,
-    pub event: Event,
-    /// Additional event types to evaluate (dual-fire support)
-    pub additional_event_types: Vec<EventType>,
-}
-
-pub fn parse_event(value: Value) -> Result<OpenCodeEvent> {
+pub fn parse_event(value: Value) -> Result<AdaptedEvent> {
     let input: OpenCodeHookInput = serde_json::from_value(value)?;
     let event_types = map_event_type(
         &input.hook_event_name,
@@ -94,19 +134,46 @@ pub fn parse_event(value: Value) -> Result<OpenCodeEvent> {
         permission_mode: None,
         tool_use_id: None,
         prompt: None,
+        tool_response: None,
+        stop_hook_active: None,
+        trigger: None,
+        custom_instructions: None,
+        message: None,
+        pre_tool_use: None,
+        identity: None,
     };
 
-    Ok(OpenCodeEvent {
+    Ok(AdaptedEvent {
+        is_tool_event: input.hook_event_name.starts_with("tool."),
         hook_event_name: input.hook_event_name,
         event,
         additional_event_types,
     })
 }
 
-pub fn translate_response(response: &Response, _opencode_event: &OpenCodeEvent) -> Value {
+/// Whether a hook payload comes from the OpenCode plugin
+///
+/// OpenCode's event names are dotted (`tool.execute.before`,
+/// `permission.ask`); Claude Code's never are.
+pub fn detect(value: &Value) -> bool {
+    value
+        .get("hook_event_name")
+        .and_then(Value::as_str)
+        .is_some_and(|name| name.contains('.'))
+}
+
+pub fn translate_response(response: &Response, opencode_event: &AdaptedEvent) -> Value {
     let mut map = Map::new();
     map.insert("continue".to_string(), Value::Bool(response.continue_));
 
+    // permission.ask answers with OpenCode's approval status
+    if opencode_event.hook_event_name == "permission.ask" {
+        map.insert(
+            "status".to_string(),
+            Value::String(permission_status(response).to_string()),
+        );
+    }
+
     if let Some(reason) = &response.reason {
         map.insert("reason".to_string(), Value::String(reason.clone()));
     }
@@ -142,6 +209,21 @@ pub fn translate_response(response: &Response, _opencode_event: &OpenCodeEvent)
     Value::Object(map)
 }
 
+/// Approval status for a `permission.ask` callback
+///
+/// A block denies; a rule's `permission_decision` is passed through;
+/// otherwise OpenCode asks the user as it would without RuleZ.
+fn permission_status(response: &Response) -> PermissionDecision {
+    if !response.continue_ {
+        return PermissionDecision::Deny;
+    }
+    response
+        .hook_specific_output
+        .as_ref()
+        .and_then(|output| output.permission_decision)
+        .unwrap_or(PermissionDecision::Ask)
+}
+
 /// Map an OpenCode hook event name to one or more RuleZ event types.
 ///
 /// Returns a Vec of EventTypes. The first entry is the primary event type;
@@ -153,6 +235,7 @@ fn map_event_type(
 ) -> Vec<EventType> {
     match hook_event_name {
         "tool.execute.before" => vec![EventType::PreToolUse],
+        "permission.ask" => vec![EventType::PermissionRequest],
         "tool.execute.after" => {
             let mut types = vec![EventType::PostToolUse];
             // Dual-fire: if payload indicates failure, also fire PostToolUseFailure
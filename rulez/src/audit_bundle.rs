@@ -0,0 +1,412 @@
+//! Compliance evidence bundles (`rulez audit export`).
+//!
+//! A bundle is a plain, uncompressed POSIX tar archive that auditors can open
+//! with standard tools. Every file is listed in `manifest.json` with its
+//! SHA-256, and `manifest.sig` holds an HMAC-SHA256 of the manifest made with
+//! the log signing key, so the whole bundle is covered by one signature.
+//! The layout is documented in `docs/audit-bundle.md`.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::io::Write;
+
+use crate::log_chain::{hex, hmac_sha256};
+
+/// `format` field of the manifest
+pub const BUNDLE_FORMAT: &str = "rulez-audit-bundle";
+
+/// Version of the bundle layout
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Name of the manifest inside the bundle
+pub const MANIFEST: &str = "manifest.json";
+
+/// Name of the manifest signature inside the bundle
+pub const SIGNATURE: &str = "manifest.sig";
+
+const SIGNATURE_PREFIX: &str = "hmac-sha256:";
+
+pub(crate) const BLOCK: usize = 512;
+
+/// One file listed in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestFile {
+    pub path: String,
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Manifest {
+    pub format: String,
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub rulez_version: String,
+    /// Start of the evidence period (`None`: the whole log)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    /// End of the evidence period
+    pub until: DateTime<Utc>,
+    pub files: Vec<ManifestFile>,
+}
+
+/// Files collected for a bundle, in archive order
+#[derive(Debug, Default)]
+pub struct Bundle {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file; `path` is relative to the bundle root
+    pub fn add(&mut self, path: impl Into<String>, content: impl Into<Vec<u8>>) {
+        self.files.push((path.into(), content.into()));
+    }
+
+    /// Add a value as pretty-printed JSON
+    pub fn add_json<T: Serialize>(&mut self, path: impl Into<String>, value: &T) -> Result<()> {
+        let mut json = serde_json::to_vec_pretty(value)?;
+        json.push(b'\n');
+        self.add(path, json);
+        Ok(())
+    }
+
+    /// Write the archive: the files, `manifest.json`, and with a key `manifest.sig`
+    ///
+    /// Returns the manifest that was written.
+    pub fn write<W: Write>(
+        self,
+        mut writer: W,
+        since: Option<DateTime<Utc>>,
+        until: DateTime<Utc>,
+        key: Option<&[u8]>,
+    ) -> Result<Manifest> {
+        let now = Utc::now();
+        let manifest = Manifest {
+            format: BUNDLE_FORMAT.to_string(),
+            format_version: BUNDLE_VERSION,
+            created_at: now,
+            rulez_version: env!("CARGO_PKG_VERSION").to_string(),
+            since,
+            until,
+            files: self
+                .files
+                .iter()
+                .map(|(path, content)| ManifestFile {
+                    path: path.clone(),
+                    sha256: hex(&Sha256::digest(content)),
+                    bytes: content.len() as u64,
+                })
+                .collect(),
+        };
+        let mut manifest_json = serde_json::to_vec_pretty(&manifest)?;
+        manifest_json.push(b'\n');
+
+        let mtime = u64::try_from(now.timestamp()).unwrap_or(0);
+        for (path, content) in &self.files {
+            write_tar_entry(&mut writer, path, content, mtime)?;
+        }
+        write_tar_entry(&mut writer, MANIFEST, &manifest_json, mtime)?;
+        if let Some(key) = key {
+            let signature = format!(
+                "{}{}\n",
+                SIGNATURE_PREFIX,
+                hex(&hmac_sha256(key, &manifest_json))
+            );
+            write_tar_entry(&mut writer, SIGNATURE, signature.as_bytes(), mtime)?;
+        }
+        writer.write_all(&[0u8; BLOCK * 2])?;
+        writer.flush()?;
+        Ok(manifest)
+    }
+}
+
+/// Result of checking a bundle
+#[derive(Debug, Default)]
+pub struct BundleReport {
+    pub manifest: Option<Manifest>,
+    /// `Some(valid)` when the signature was checked
+    pub signature_valid: Option<bool>,
+    /// True when the bundle carries a signature
+    pub signed: bool,
+    pub problems: Vec<String>,
+}
+
+impl BundleReport {
+    pub fn is_intact(&self) -> bool {
+        self.problems.is_empty() && self.signature_valid != Some(false)
+    }
+}
+
+/// Check every file against the manifest and, with a key, the signature
+pub fn verify_bundle(archive: &[u8], key: Option<&[u8]>) -> Result<BundleReport> {
+    let entries = read_tar(archive)?;
+    let mut report = BundleReport::default();
+
+    let Some((_, manifest_json)) = entries.iter().find(|(path, _)| path == MANIFEST) else {
+        report.problems.push(format!("{} is missing", MANIFEST));
+        return Ok(report);
+    };
+    let manifest: Manifest =
+        serde_json::from_slice(manifest_json).context("manifest.json is not a valid manifest")?;
+    if manifest.format != BUNDLE_FORMAT {
+        report
+            .problems
+            .push(format!("unknown bundle format '{}'", manifest.format));
+    }
+
+    for file in &manifest.files {
+        match entries.iter().find(|(path, _)| *path == file.path) {
+            None => report.problems.push(format!("{}: missing", file.path)),
+            Some((_, content)) if hex(&Sha256::digest(content)) != file.sha256 => {
+                report.problems.push(format!("{}: modified", file.path));
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, _) in &entries {
+        let listed = manifest.files.iter().any(|f| f.path == *path);
+        if !listed && path != MANIFEST && path != SIGNATURE {
+            report
+                .problems
+                .push(format!("{}: not listed in the manifest", path));
+        }
+    }
+
+    if let Some((_, signature)) = entries.iter().find(|(path, _)| path == SIGNATURE) {
+        report.signed = true;
+        if let Some(key) = key {
+            let expected = format!(
+                "{}{}",
+                SIGNATURE_PREFIX,
+                hex(&hmac_sha256(key, manifest_json))
+            );
+            report.signature_valid = Some(String::from_utf8_lossy(signature).trim() == expected);
+        }
+    }
+    report.manifest = Some(manifest);
+    Ok(report)
+}
+
+/// Parse an evidence period: a quarter (`Q3`, `2026-Q3`), an RFC 3339
+/// timestamp, or an age like `90d`
+///
+/// Returns the start, and the end for quarters. A bare `Q3` means the most
+/// recent third quarter that has started.
+pub fn parse_period(
+    value: &str,
+    now: DateTime<Utc>,
+) -> Result<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    let upper = value.trim().to_ascii_uppercase();
+    let (year, quarter) = match upper.split_once("-Q") {
+        Some((year, quarter)) => (
+            Some(
+                year.parse::<i32>()
+                    .with_context(|| format!("Invalid year in '{}'", value))?,
+            ),
+            quarter,
+        ),
+        None => match upper.strip_prefix('Q') {
+            Some(quarter) => (None, quarter),
+            None => {
+                return crate::log_export::parse_time_bound(value, now).map(|since| (since, None));
+            }
+        },
+    };
+    let quarter: u32 = match quarter.parse() {
+        Ok(q @ 1..=4) => q,
+        _ => bail!("Invalid quarter in '{}': use Q1 to Q4", value),
+    };
+
+    let start_of = |year: i32, quarter: u32| {
+        Utc.with_ymd_and_hms(year, (quarter - 1) * 3 + 1, 1, 0, 0, 0)
+            .single()
+            .with_context(|| format!("Invalid quarter '{}'", value))
+    };
+    let mut start = start_of(year.unwrap_or(now.year()), quarter)?;
+    if year.is_none() && start > now {
+        start = start_of(now.year() - 1, quarter)?;
+    }
+    let end = if quarter == 4 {
+        start_of(start.year() + 1, 1)?
+    } else {
+        start_of(start.year(), quarter + 1)?
+    };
+    Ok((start, Some(end)))
+}
+
+// =============================================================================
+// Tar (POSIX ustar)
+// =============================================================================
+
+pub(crate) fn write_tar_entry<W: Write>(
+    writer: &mut W,
+    path: &str,
+    content: &[u8],
+    mtime: u64,
+) -> Result<()> {
+    if path.len() > 100 {
+        bail!("Bundle path too long for tar: {}", path);
+    }
+    let mut header = [0u8; BLOCK];
+    header[..path.len()].copy_from_slice(path.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], content.len() as u64);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with its own field set to spaces
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+    write_octal(&mut header[148..155], checksum);
+
+    writer.write_all(&header)?;
+    writer.write_all(content)?;
+    let padding = (BLOCK - content.len() % BLOCK) % BLOCK;
+    writer.write_all(&[0u8; BLOCK][..padding])?;
+    Ok(())
+}
+
+/// Zero-padded octal, NUL-terminated, filling `field`
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+    field[digits] = 0;
+}
+
+/// Regular files of a tar archive, as `(path, content)`
+pub(crate) fn read_tar(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut offset = 0;
+    while offset + BLOCK <= archive.len() {
+        let header = &archive[offset..offset + BLOCK];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let name_end = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        let path = String::from_utf8_lossy(&header[..name_end]).into_owned();
+        let size_text = String::from_utf8_lossy(&header[124..136]);
+        let size =
+            usize::from_str_radix(size_text.trim_matches(|c: char| c == '\0' || c == ' '), 8)
+                .with_context(|| format!("Corrupt tar header for {}", path))?;
+        let start = offset + BLOCK;
+        let end = start + size;
+        if end > archive.len() {
+            bail!("Truncated archive: {} is cut short", path);
+        }
+        if matches!(header[156], b'0' | 0) {
+            files.push((path, archive[start..end].to_vec()));
+        }
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(key: Option<&[u8]>) -> Vec<u8> {
+        let mut bundle = Bundle::new();
+        bundle.add("config/project/hooks.yaml", "version: \"1.0\"\nrules: []\n");
+        bundle.add("records/bypasses.ndjson", "");
+        let mut archive = Vec::new();
+        bundle.write(&mut archive, None, Utc::now(), key).unwrap();
+        archive
+    }
+
+    #[test]
+    fn test_tar_round_trip() {
+        let archive = bundle(None);
+        assert_eq!(archive.len() % BLOCK, 0);
+        let files = read_tar(&archive).unwrap();
+        let names: Vec<&str> = files.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "config/project/hooks.yaml",
+                "records/bypasses.ndjson",
+                MANIFEST
+            ]
+        );
+        assert_eq!(files[0].1, b"version: \"1.0\"\nrules: []\n");
+    }
+
+    #[test]
+    fn test_tar_header_checksum() {
+        let archive = bundle(None);
+        let header = &archive[..BLOCK];
+        let stored =
+            u64::from_str_radix(std::str::from_utf8(&header[148..154]).unwrap(), 8).unwrap();
+        let mut blank = header.to_vec();
+        blank[148..156].fill(b' ');
+        assert_eq!(stored, blank.iter().map(|&b| u64::from(b)).sum::<u64>());
+        assert_eq!(&header[257..263], b"ustar\0");
+    }
+
+    #[test]
+    fn test_verify_signed_bundle() {
+        let archive = bundle(Some(b"secret"));
+        let report = verify_bundle(&archive, Some(b"secret")).unwrap();
+        assert!(report.is_intact(), "{:?}", report.problems);
+        assert_eq!(report.signature_valid, Some(true));
+
+        let wrong_key = verify_bundle(&archive, Some(b"other")).unwrap();
+        assert!(!wrong_key.is_intact());
+
+        let unchecked = verify_bundle(&archive, None).unwrap();
+        assert!(unchecked.signed);
+        assert_eq!(unchecked.signature_valid, None);
+    }
+
+    #[test]
+    fn test_verify_detects_modified_file() {
+        let mut archive = bundle(Some(b"secret"));
+        let at = archive.windows(5).position(|w| w == b"rules").unwrap();
+        archive[at] = b'R';
+        let report = verify_bundle(&archive, Some(b"secret")).unwrap();
+        assert_eq!(report.problems, ["config/project/hooks.yaml: modified"]);
+    }
+
+    #[test]
+    fn test_parse_period_quarters() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 0, 0, 0).unwrap();
+        let q = |s| parse_period(s, now).unwrap();
+
+        assert_eq!(
+            q("Q3"),
+            (
+                Utc.with_ymd_and_hms(2026, 7, 1, 0, 0, 0).unwrap(),
+                Some(Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap())
+            )
+        );
+        // Q4 has started; Q1 of this year too. A later quarter means last year.
+        assert_eq!(
+            q("q4").0,
+            Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            q("2025-Q4").1,
+            Some(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap())
+        );
+        let early = Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            parse_period("Q2", early).unwrap().0,
+            Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap()
+        );
+
+        assert_eq!(q("30d").1, None);
+        assert!(parse_period("Q5", now).is_err());
+    }
+}
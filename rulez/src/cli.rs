@@ -1,19 +1,30 @@
+pub mod agent_hook;
+pub mod audit;
+pub mod completion_data;
 pub mod copilot_doctor;
-pub mod copilot_hook;
 pub mod copilot_install;
+pub mod daemon;
+pub mod daemon_http;
 pub mod debug;
+pub mod digest;
 pub mod explain;
 pub mod gemini_doctor;
-pub mod gemini_hook;
 pub mod gemini_install;
+pub mod governance;
 pub mod init;
 pub mod install;
+pub mod kill_switch;
 pub mod lint;
 pub mod logs;
+pub mod migrate;
 pub mod opencode_doctor;
-pub mod opencode_hook;
 pub mod opencode_install;
+pub mod packs;
+pub mod report;
+pub mod rule;
 pub mod skills;
+pub mod stats;
+pub mod sync;
 pub mod test;
 pub mod upgrade;
 pub mod validate;
@@ -0,0 +1,111 @@
+use anyhow::{Result, bail};
+use serde_json::Value;
+use std::io::{self, Read};
+use tracing::info;
+
+use crate::adapters::{FormatAdapter, HookOutput};
+use crate::config;
+use crate::kill_switch;
+use crate::logging;
+use crate::models::DebugConfig;
+
+/// Hook runner for an agent's format (`rulez gemini hook`, `rulez --agent cursor`, ...)
+///
+/// Reads one payload from stdin and answers in the adapter's format.
+pub async fn run(adapter: &dyn FormatAdapter, debug_logs: bool, dry_run: bool) -> Result<()> {
+    let mut buffer = String::new();
+    let raw_value = io::stdin()
+        .read_to_string(&mut buffer)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| {
+            if buffer.trim().is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(serde_json::from_str::<Value>(&buffer)?))
+            }
+        });
+    match raw_value {
+        Ok(Some(raw_value)) => run_event(adapter, raw_value, debug_logs, dry_run).await,
+        Ok(None) => match adapter.fail_open("No input received on stdin") {
+            Some(output) => write_output(&output),
+            None => bail!("No input received on stdin"),
+        },
+        Err(err) => fail_open(adapter, err),
+    }
+}
+
+/// Evaluate a parsed payload and write the adapter's answer
+///
+/// Errors fail open when the adapter has a fail-open answer.
+pub async fn run_event(
+    adapter: &dyn FormatAdapter,
+    raw_value: Value,
+    debug_logs: bool,
+    dry_run: bool,
+) -> Result<()> {
+    match respond(adapter, raw_value, debug_logs, dry_run).await {
+        Ok(output) => write_output(&output),
+        Err(err) => fail_open(adapter, err),
+    }
+}
+
+async fn respond(
+    adapter: &dyn FormatAdapter,
+    raw_value: Value,
+    debug_logs: bool,
+    dry_run: bool,
+) -> Result<HookOutput> {
+    let event = adapter.parse(raw_value)?;
+    info!(
+        "Processing {} event: {} ({})",
+        adapter.label(),
+        event.hook_event_name,
+        event.event.session_id
+    );
+
+    // While RuleZ is disabled the project config isn't read, so a broken one
+    // can't fail the hook; evaluation logs the bypass
+    let project_debug_logs = if kill_switch::bypass_reason(chrono::Utc::now()).is_some() {
+        false
+    } else {
+        config::Config::load(
+            event
+                .event
+                .cwd
+                .as_ref()
+                .map(|p| std::path::Path::new(p.as_str())),
+        )?
+        .settings
+        .debug_logs
+    };
+    let debug_config = DebugConfig::new(debug_logs, project_debug_logs).with_dry_run(dry_run);
+
+    let response = adapter.evaluate(&event, &debug_config).await?;
+    adapter.render(&response, &event)
+}
+
+fn fail_open(adapter: &dyn FormatAdapter, err: anyhow::Error) -> Result<()> {
+    let reason = format!("{} hook runner error: {}", adapter.label(), err);
+    match adapter.fail_open(&reason) {
+        Some(output) => {
+            eprintln!("{}", reason);
+            write_output(&output)
+        }
+        None => Err(err),
+    }
+}
+
+/// Write a hook's output; a nonzero exit code ends the process
+pub fn write_output(output: &HookOutput) -> Result<()> {
+    if let Some(ref stderr) = output.stderr {
+        eprintln!("{}", stderr);
+    }
+    if let Some(ref stdout) = output.stdout {
+        println!("{}", stdout);
+    }
+    if output.exit_code != 0 {
+        logging::flush_global_logger();
+        std::process::exit(output.exit_code);
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sha2::{Digest as _, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::audit_bundle::{Bundle, parse_period, verify_bundle};
+use crate::cli::stats::rule_stats;
+use crate::config::Config;
+use crate::digest::Digest;
+use crate::log_chain::{hex, verify_log_file};
+use crate::logging::{LogQuery, QueryFilters, log_path};
+use crate::models::{Decision, LogEntry, PermissionDecision};
+
+/// Export a signed compliance evidence bundle
+///
+/// # Arguments
+/// * `since` - Start of the period: a quarter (`Q3`, `2026-Q3`), RFC3339, or an age like `90d`
+/// * `until` - End of the period (defaults to the end of the quarter, or now)
+/// * `output` - Archive to write; defaults to `rulez-audit-<from>-<to>.tar`
+/// * `path` - Log file to read (defaults to ~/.claude/logs/rulez.log)
+/// * `key_env` - Variable holding the signing key (defaults to
+///   `logging.integrity.signing_key_env`)
+pub fn export(
+    since: Option<String>,
+    until: Option<String>,
+    output: Option<PathBuf>,
+    path: Option<PathBuf>,
+    key_env: Option<String>,
+) -> Result<()> {
+    let now = Utc::now();
+    let (since, period_end) = match since {
+        Some(ref since) => {
+            let (start, end) = parse_period(since, now)?;
+            (Some(start), end)
+        }
+        None => (None, None),
+    };
+    let until = match until {
+        Some(ref until) => crate::log_export::parse_time_bound(until, now)?,
+        None => period_end.map_or(now, |end| end.min(now)),
+    };
+    let key = super::logs::signing_key(key_env)?;
+
+    let log_path = path.unwrap_or_else(log_path);
+    let mut entries = LogQuery::with_path(&log_path).query(QueryFilters {
+        since,
+        until: Some(until),
+        ..Default::default()
+    })?;
+    entries.reverse();
+
+    let config = Config::load(None)?;
+    let mut bundle = Bundle::new();
+    add_config(&mut bundle, &config)?;
+    add_decisions(&mut bundle, &entries, &config, since, until)?;
+    if log_path.exists() {
+        let report = verify_log_file(&log_path, key.as_ref().map(String::as_bytes))?;
+        bundle.add_json(
+            "logs/integrity.json",
+            &serde_json::json!({
+                "log": log_path,
+                "intact": report.is_intact(),
+                "report": report,
+            }),
+        )?;
+    }
+
+    let output = output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "rulez-audit-{}-{}.tar",
+            since.map_or_else(|| "start".to_string(), |s| s.format("%Y%m%d").to_string()),
+            until.format("%Y%m%d")
+        ))
+    });
+    let file = std::fs::File::create(&output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    let manifest = bundle.write(
+        std::io::BufWriter::new(file),
+        since,
+        until,
+        key.as_ref().map(String::as_bytes),
+    )?;
+
+    println!(
+        "Wrote {} files covering {} log entries to {}",
+        manifest.files.len() + 1,
+        entries.len(),
+        output.display()
+    );
+    if key.is_none() {
+        println!(
+            "Warning: bundle is not signed. Set logging.integrity.signing_key_env or pass --key-env."
+        );
+    }
+    Ok(())
+}
+
+/// Config files as written, the effective config, and a lock of their hashes
+fn add_config(bundle: &mut Bundle, config: &Config) -> Result<()> {
+    let mut sources = Vec::new();
+    let project = std::env::current_dir()
+        .ok()
+        .map(|dir| dir.join(".claude").join("hooks.yaml"));
+    let global = dirs::home_dir().map(|home| home.join(".claude").join("hooks.yaml"));
+    for (scope, path) in [("project", project), ("global", global)] {
+        let Some(path) = path.filter(|p| p.exists()) else {
+            continue;
+        };
+        let content =
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        sources.push(serde_json::json!({
+            "scope": scope,
+            "path": path,
+            "sha256": hex(&Sha256::digest(&content)),
+        }));
+        bundle.add(format!("config/{}/hooks.yaml", scope), content);
+    }
+
+    bundle.add_json("config/effective.json", config)?;
+    bundle.add_json(
+        "config/lock.json",
+        &serde_json::json!({
+            "rulez_version": env!("CARGO_PKG_VERSION"),
+            "sources": sources,
+            "builtin_packs": config.settings.builtin_packs,
+            "rules": config.rules.iter().map(|r| &r.name).collect::<Vec<_>>(),
+        }),
+    )
+}
+
+/// Summaries of the period, and the bypass, approval and anomaly records
+fn add_decisions(
+    bundle: &mut Bundle,
+    entries: &[LogEntry],
+    config: &Config,
+    since: Option<chrono::DateTime<Utc>>,
+    until: chrono::DateTime<Utc>,
+) -> Result<()> {
+    let start = since
+        .or_else(|| entries.first().map(|e| e.timestamp))
+        .unwrap_or(until);
+    let digest = Digest::build(entries, start, until, usize::MAX);
+    let configured: Vec<String> = config.rules.iter().map(|r| r.name.clone()).collect();
+    bundle.add_json(
+        "decisions/summary.json",
+        &serde_json::json!({
+            "digest": digest,
+            "rules": rule_stats(entries, &configured),
+        }),
+    )?;
+    bundle.add("decisions/summary.md", digest.to_markdown());
+
+    bundle.add(
+        "records/bypasses.ndjson",
+        ndjson(entries, |e| e.decision == Some(Decision::Warned))?,
+    );
+    bundle.add(
+        "records/approvals.ndjson",
+        ndjson(entries, |e| {
+            e.response
+                .as_ref()
+                .is_some_and(|r| r.permission_decision == Some(PermissionDecision::Allow))
+        })?,
+    );
+    bundle.add(
+        "records/anomalies.ndjson",
+        ndjson(entries, |e| e.anomaly.is_some())?,
+    );
+    Ok(())
+}
+
+fn ndjson(entries: &[LogEntry], keep: impl Fn(&LogEntry) -> bool) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for entry in entries.iter().filter(|e| keep(e)) {
+        serde_json::to_writer(&mut out, entry)?;
+        out.push(b'\n');
+    }
+    Ok(out)
+}
+
+/// Check a bundle's files against its manifest, and its signature
+///
+/// Exits with status 1 when the bundle was modified or the signature is wrong.
+pub fn verify(bundle: &Path, key_env: Option<String>) -> Result<()> {
+    let archive =
+        std::fs::read(bundle).with_context(|| format!("Failed to read {}", bundle.display()))?;
+    let key = super::logs::signing_key(key_env)?;
+    let report = verify_bundle(&archive, key.as_ref().map(String::as_bytes))?;
+
+    for problem in &report.problems {
+        println!("  {}", problem);
+    }
+    if let Some(ref manifest) = report.manifest {
+        println!(
+            "{}: {} files, created {} by rulez {}",
+            bundle.display(),
+            manifest.files.len(),
+            manifest.created_at.format("%Y-%m-%d %H:%M UTC"),
+            manifest.rulez_version
+        );
+    }
+    match (report.signed, report.signature_valid) {
+        (false, _) => println!("Bundle is not signed"),
+        (true, None) => println!("Signature not checked (no signing key)"),
+        (true, Some(true)) => println!("Signature is valid"),
+        (true, Some(false)) => println!("Signature is INVALID"),
+    }
+
+    if !report.is_intact() {
+        println!("Bundle verification FAILED");
+        std::process::exit(1);
+    }
+    println!("Bundle is intact");
+    Ok(())
+}
@@ -0,0 +1,361 @@
+//! Editor completion data (`rulez completion-data`)
+//!
+//! Emits everything an editor needs for context-aware suggestions in
+//! `hooks.yaml` as one JSON document: tool names (built-in and seen in the
+//! audit log), the configured rule names, event types, and the rule,
+//! matcher and action keys with a one-line description each. The desktop
+//! app's YAML editor uses it, and IDE plugins can call it the same way.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::EventType;
+
+/// A configuration key and what it does
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyDoc {
+    pub key: &'static str,
+    pub doc: &'static str,
+}
+
+/// A tool name to suggest in `tools:` matchers
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCompletion {
+    pub name: String,
+    /// One of Claude Code's built-in tools
+    pub builtin: bool,
+    /// Events in the audit log that used this tool
+    pub seen: u64,
+}
+
+/// A configured rule, for `pre_rules`, `replacement` and similar references
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleCompletion {
+    pub name: String,
+    pub description: Option<String>,
+    pub priority: i32,
+}
+
+/// Everything `rulez completion-data` prints
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionData {
+    pub tools: Vec<ToolCompletion>,
+    pub rules: Vec<RuleCompletion>,
+    pub event_types: Vec<String>,
+    pub rule_keys: &'static [KeyDoc],
+    pub matcher_keys: &'static [KeyDoc],
+    pub action_keys: &'static [KeyDoc],
+}
+
+const fn key(key: &'static str, doc: &'static str) -> KeyDoc {
+    KeyDoc { key, doc }
+}
+
+/// Keys of a rule
+pub const RULE_KEYS: &[KeyDoc] = &[
+    key("name", "Unique rule name"),
+    key("description", "What the rule enforces and why"),
+    key(
+        "enabled_when",
+        "Expression that must be true for the rule to run",
+    ),
+    key(
+        "matchers",
+        "Conditions that trigger the rule (all must match)",
+    ),
+    key(
+        "actions",
+        "What to do when the rule matches (a map or a pipeline list)",
+    ),
+    key("mode", "enforce, warn or audit"),
+    key("priority", "Evaluation order; higher runs first"),
+    key(
+        "inject_priority",
+        "Order of injected context among matched rules; higher first",
+    ),
+    key(
+        "context_weight",
+        "Share of the context budget when injected context is cut",
+    ),
+    key(
+        "fail_open",
+        "Allow the operation when this rule's validator errors",
+    ),
+    key(
+        "timeout_ms",
+        "Timeout in milliseconds for this rule's scripts and commands",
+    ),
+    key(
+        "governance",
+        "Author, reason, review date and tags for audits",
+    ),
+    key(
+        "metadata",
+        "Legacy priority, script timeout and enabled flag",
+    ),
+    key("log", "Audit log detail for this rule: minimal or full"),
+    key("deprecated", "Mark the rule as deprecated"),
+    key("replacement", "Rule that replaces this deprecated rule"),
+    key(
+        "remove_after",
+        "Date after which `rulez migrate` removes the rule",
+    ),
+];
+
+/// Keys of `matchers:`
+pub const MATCHER_KEYS: &[KeyDoc] = &[
+    key("tools", "Tool names to match, e.g. [Bash, Edit]"),
+    key("extensions", "File extensions to match, e.g. [.rs, .ts]"),
+    key("directories", "Directory globs to match, e.g. [src/**]"),
+    key("operations", "Event types to match"),
+    key("command_match", "Regex matched against the Bash command"),
+    key(
+        "prompt_match",
+        "Patterns matched against the user prompt (UserPromptSubmit)",
+    ),
+    key(
+        "require_fields",
+        "tool_input fields that must exist (dot notation)",
+    ),
+    key("field_types", "Expected types of tool_input fields"),
+    key(
+        "response_match",
+        "Regex matched against the tool response (PostToolUse)",
+    ),
+    key(
+        "tool_failed",
+        "Match only failed (true) or successful (false) tool calls",
+    ),
+    key(
+        "message_match",
+        "Regex matched against the notification text",
+    ),
+    key(
+        "compact_trigger",
+        "Compaction trigger: manual or auto (PreCompact)",
+    ),
+    key(
+        "transcript_match",
+        "Regex matched against recent transcript messages",
+    ),
+    key(
+        "pre_rules",
+        "Rules that matched the paired PreToolUse event",
+    ),
+    key("users", "User ids or globs the rule applies to"),
+    key("teams", "Teams the rule applies to"),
+    key("custom", "Registered custom matchers and their values"),
+];
+
+/// Keys of `actions:` (and of each pipeline step)
+pub const ACTION_KEYS: &[KeyDoc] = &[
+    key("inject", "Path of a context file to inject"),
+    key("inject_inline", "Markdown to inject directly"),
+    key(
+        "inject_command",
+        "Command whose output is injected as context",
+    ),
+    key(
+        "summarize",
+        "Shrink oversized inject/inject_command content",
+    ),
+    key(
+        "inject_as",
+        "Deliver injected content to context, system_message or stderr",
+    ),
+    key("run", "Validator script to run with the event on stdin"),
+    key("block", "Block the operation"),
+    key(
+        "block_if_match",
+        "Block when this regex matches the content",
+    ),
+    key(
+        "validate_expr",
+        "Expression that must be true to allow the operation",
+    ),
+    key(
+        "inline_script",
+        "Inline validator script; non-zero exit blocks",
+    ),
+    key("script_rhai", "Embedded Rhai validator script"),
+    key("scan_secrets", "Block writes that contain secrets"),
+    key("scan_pii", "Block or redact PII in writes and prompts"),
+    key("permission_decision", "allow, ask or deny (PreToolUse)"),
+    key("system_message", "Message shown to the user, not the model"),
+    key(
+        "suppress_output",
+        "Hide the hook's output from the transcript",
+    ),
+    key("webhook", "POST the event to a URL"),
+    key("rewrite_prompt", "Prepend, append or redact prompt text"),
+    key("custom", "Registered custom actions and their values"),
+    key("when", "Run these actions only when the expression is true"),
+];
+
+/// Gather completion data
+///
+/// A config or log that can't be read just contributes nothing, so editors
+/// still get the static keys while the file is half-written.
+pub fn collect(config: Option<&Config>, log_path: Option<PathBuf>) -> CompletionData {
+    let mut tools: BTreeMap<String, ToolCompletion> = crate::hooks::KNOWN_TOOLS
+        .iter()
+        .map(|name| {
+            (
+                (*name).to_string(),
+                ToolCompletion {
+                    name: (*name).to_string(),
+                    builtin: true,
+                    seen: 0,
+                },
+            )
+        })
+        .collect();
+    let query = log_path.map_or_else(LogQuery::new, LogQuery::with_path);
+    for entry in query.query(QueryFilters::default()).unwrap_or_default() {
+        let Some(name) = entry.tool_name else {
+            continue;
+        };
+        tools
+            .entry(name.clone())
+            .or_insert_with(|| ToolCompletion {
+                name,
+                builtin: false,
+                seen: 0,
+            })
+            .seen += 1;
+    }
+    // Tools named in rules count as known too (MCP tools, custom agents)
+    for rule in config.map(|c| c.rules.as_slice()).unwrap_or_default() {
+        for name in rule.matchers.tools.iter().flatten() {
+            if !name.contains('*') {
+                tools.entry(name.clone()).or_insert_with(|| ToolCompletion {
+                    name: name.clone(),
+                    builtin: false,
+                    seen: 0,
+                });
+            }
+        }
+    }
+
+    let mut tools: Vec<ToolCompletion> = tools.into_values().collect();
+    // Most used first, then alphabetical
+    tools.sort_by(|a, b| b.seen.cmp(&a.seen).then_with(|| a.name.cmp(&b.name)));
+
+    CompletionData {
+        tools,
+        rules: config
+            .map(|c| c.rules.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|rule| RuleCompletion {
+                name: rule.name.clone(),
+                description: rule.description.clone(),
+                priority: rule.effective_priority(),
+            })
+            .collect(),
+        event_types: EventType::ALL.iter().map(ToString::to_string).collect(),
+        rule_keys: RULE_KEYS,
+        matcher_keys: MATCHER_KEYS,
+        action_keys: ACTION_KEYS,
+    }
+}
+
+/// Print completion data as JSON
+pub fn run(config_path: Option<String>, log_path: Option<PathBuf>) -> Result<()> {
+    let config = match config_path {
+        Some(path) => Config::from_file(&path).ok(),
+        None => Config::load(None).ok(),
+    };
+    let data = collect(config.as_ref(), log_path);
+    println!("{}", serde_json::to_string_pretty(&data)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::{self, Deserialize, Deserializer, Visitor};
+
+    /// Reads the field names a type's derived `Deserialize` accepts
+    struct FieldProbe<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldProbe<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("probed"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    fn fields<'de, T: Deserialize<'de>>() -> Vec<&'static str> {
+        let mut fields: &'static [&'static str] = &[];
+        let _ = T::deserialize(FieldProbe(&mut fields));
+        let mut fields = fields.to_vec();
+        fields.sort_unstable();
+        fields
+    }
+
+    fn keys(docs: &[KeyDoc]) -> Vec<&'static str> {
+        let mut keys: Vec<&str> = docs.iter().map(|d| d.key).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn test_key_docs_cover_every_field() {
+        assert_eq!(keys(RULE_KEYS), fields::<crate::models::Rule>());
+        assert_eq!(keys(MATCHER_KEYS), fields::<crate::models::Matchers>());
+        assert_eq!(keys(ACTION_KEYS), fields::<crate::models::Actions>());
+    }
+
+    #[test]
+    fn test_collect_includes_rules_and_rule_tools() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: guard-mcp
+    description: Guard the deploy tool
+    priority: 7
+    matchers:
+      tools: ["mcp__deploy__run", "mcp__*"]
+    actions:
+      block: true
+"#,
+        )
+        .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let data = collect(Some(&config), Some(temp.path().join("missing.log")));
+
+        assert_eq!(data.rules.len(), 1);
+        assert_eq!(data.rules[0].priority, 7);
+        assert!(data.tools.iter().any(|t| t.name == "Bash" && t.builtin));
+        assert!(
+            data.tools
+                .iter()
+                .any(|t| t.name == "mcp__deploy__run" && !t.builtin)
+        );
+        assert!(!data.tools.iter().any(|t| t.name == "mcp__*"));
+        assert!(data.event_types.contains(&"PreToolUse".to_string()));
+    }
+}
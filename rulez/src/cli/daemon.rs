@@ -0,0 +1,432 @@
+//! `rulez daemon` — serve hook evaluations over a Unix domain socket.
+//!
+//! The daemon keeps the parsed config (via the config cache), compiled
+//! regexes, and other per-process caches warm, so a hook call costs a socket
+//! round trip instead of a process start plus config parse.
+//!
+//! Protocol: newline-delimited JSON. Each line sent by a client is a hook
+//! event; the daemon answers each line with one line holding either a
+//! `Response` or `{"error": "..."}`. A connection whose first line is a
+//! `hello` speaks the versioned protocol instead (see [`crate::protocol`]).
+//! Connections are served concurrently.
+//!
+//! The hook entry point becomes a thin client with `rulez --socket <PATH>`
+//! (or `RULEZ_SOCKET`): it forwards the event and prints the daemon's answer,
+//! falling back to in-process evaluation when the daemon can't be reached.
+//!
+//! `rulez daemon --http <ADDR>` also serves a JSON HTTP API for the UI,
+//! editors, and CI (see [`super::daemon_http`]).
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::models::Response;
+
+/// Default socket location: `~/.claude/rulez.sock`
+pub fn default_socket_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".claude").join("rulez.sock"))
+}
+
+/// Evaluation counters shared by all daemon connections
+#[derive(Debug)]
+pub struct DaemonStats {
+    started: std::time::Instant,
+    evaluations: std::cell::Cell<u64>,
+    blocked: std::cell::Cell<u64>,
+    errors: std::cell::Cell<u64>,
+}
+
+impl DaemonStats {
+    fn new() -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            evaluations: std::cell::Cell::new(0),
+            blocked: std::cell::Cell::new(0),
+            errors: std::cell::Cell::new(0),
+        }
+    }
+
+    fn record(&self, result: &Result<Response>) {
+        self.evaluations.set(self.evaluations.get() + 1);
+        match result {
+            Ok(response) if !response.continue_ => self.blocked.set(self.blocked.get() + 1),
+            Ok(_) => {}
+            Err(_) => self.errors.set(self.errors.get() + 1),
+        }
+    }
+
+    /// Counters as JSON for the `/v1/stats` endpoint
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "evaluations": self.evaluations.get(),
+            "blocked": self.blocked.get(),
+            "errors": self.errors.get(),
+        })
+    }
+}
+
+/// Run the daemon until the process is stopped
+///
+/// `handler` evaluates one parsed hook event. Refuses to start if another
+/// daemon already answers on `socket`; a stale socket file left by a daemon
+/// that exited is replaced. The socket is created with mode 0600.
+///
+/// With `http`, the HTTP API (see [`super::daemon_http`]) is also served on
+/// that loopback address.
+#[cfg(unix)]
+pub async fn serve<F, Fut>(
+    socket: &Path,
+    http: Option<std::net::SocketAddr>,
+    handler: F,
+) -> Result<()>
+where
+    F: Fn(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    use futures::StreamExt;
+    use futures::future::LocalBoxFuture;
+    use futures::stream::FuturesUnordered;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::UnixStream;
+
+    if socket.exists() {
+        if UnixStream::connect(socket).await.is_ok() {
+            anyhow::bail!("A rulez daemon is already running on {}", socket.display());
+        }
+        std::fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let http = match http {
+        Some(addr) => Some(super::daemon_http::bind(addr, &token_path(socket)).await?),
+        None => None,
+    };
+
+    let listener = bind_private(socket)?;
+    // Second safeguard: bind_private already made it 0600
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    println!("rulez daemon listening on {}", socket.display());
+    if let Some((listener, _)) = &http {
+        println!(
+            "rulez HTTP API listening on http://{}",
+            listener.local_addr()?
+        );
+    }
+
+    let stats = DaemonStats::new();
+    let evaluate = |event_value: serde_json::Value| {
+        let stats = &stats;
+        let handler = &handler;
+        async move {
+            let result = handler(event_value).await;
+            stats.record(&result);
+            result
+        }
+    };
+    let evaluate = &evaluate;
+
+    let mut connections: FuturesUnordered<LocalBoxFuture<'_, Result<()>>> = FuturesUnordered::new();
+    loop {
+        let http_accept = async {
+            match &http {
+                Some((listener, _)) => listener.accept().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => connections.push(Box::pin(serve_connection(stream, evaluate))),
+                Err(e) => tracing::warn!("Failed to accept daemon connection: {}", e),
+            },
+            accepted = http_accept => match (accepted, &http) {
+                (Ok((stream, _)), Some((_, token))) => connections.push(Box::pin(
+                    super::daemon_http::serve_request(stream, token, &stats, evaluate),
+                )),
+                (Err(e), _) => tracing::warn!("Failed to accept HTTP connection: {}", e),
+                (Ok(_), None) => {}
+            },
+            Some(result) = connections.next(), if !connections.is_empty() => {
+                if let Err(e) = result {
+                    tracing::warn!("Daemon connection failed: {:#}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Bind `socket` so that no other user can connect to it, even briefly
+///
+/// A socket takes its mode from the umask when it is bound. It is therefore
+/// bound inside a fresh 0700 directory next to `socket`, made 0600 there,
+/// and only then renamed into place.
+#[cfg(unix)]
+fn bind_private(socket: &Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let parent = socket
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    // Short names: socket paths are limited to about 100 bytes
+    let staging = parent.join(format!(".rulez.{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .with_context(|| format!("Failed to create {}", staging.display()))?;
+
+    let staged = staging.join("s");
+    let bound = tokio::net::UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, socket)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    bound.with_context(|| format!("Failed to bind {}", socket.display()))
+}
+
+/// Bearer token file for the HTTP API: the socket path with a `.token` extension
+pub fn token_path(socket: &Path) -> PathBuf {
+    socket.with_extension("token")
+}
+
+/// Answer each event line on one connection until the client closes it
+///
+/// A line over [`crate::protocol::MAX_LINE_BYTES`] gets an error reply and
+/// closes the connection, so a client can't make the daemon buffer without
+/// bound.
+#[cfg(unix)]
+async fn serve_connection<F, Fut>(stream: tokio::net::UnixStream, handler: &F) -> Result<()>
+where
+    F: Fn(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines =
+        crate::protocol::LineReader::new(BufReader::new(reader), crate::protocol::MAX_LINE_BYTES);
+    let mut first = true;
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                let mut json =
+                    serde_json::json!({"error": format!("Invalid request: {e}")}).to_string();
+                json.push('\n');
+                writer.write_all(json.as_bytes()).await?;
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if std::mem::take(&mut first) && crate::protocol::is_hello(&line) {
+            return crate::protocol::serve(&mut lines, &mut writer, Some(line), handler).await;
+        }
+        let result = match serde_json::from_str(&line) {
+            Ok(event_value) => handler(event_value).await,
+            Err(e) => Err(anyhow::anyhow!("Failed to parse hook event JSON: {}", e)),
+        };
+        let mut json = match result {
+            Ok(response) => to_reply(&response)?,
+            Err(e) => serde_json::json!({"error": format!("{e:#}")}).to_string(),
+        };
+        json.push('\n');
+        writer.write_all(json.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Serialize a response for a plain-protocol client
+///
+/// `stderr` isn't part of the hook output, so it's added here for the
+/// client to print.
+#[cfg(unix)]
+fn to_reply(response: &Response) -> Result<String> {
+    let mut reply = serde_json::to_value(response)?;
+    if let Some(ref stderr) = response.stderr {
+        reply["stderr"] = serde_json::Value::String(stderr.clone());
+    }
+    Ok(reply.to_string())
+}
+
+/// Send one event to the daemon on `socket` and return its response
+///
+/// A missing `cwd` is filled in from the client's working directory so the
+/// daemon loads the same project config the hook would have.
+#[cfg(unix)]
+pub async fn forward(socket: &Path, mut event_value: serde_json::Value) -> Result<Response> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    if let Some(event) = event_value.as_object_mut() {
+        if !event.contains_key("cwd") {
+            if let Ok(cwd) = std::env::current_dir() {
+                event.insert(
+                    "cwd".to_string(),
+                    serde_json::Value::String(cwd.to_string_lossy().into_owned()),
+                );
+            }
+        }
+    }
+
+    let stream = UnixStream::connect(socket)
+        .await
+        .with_context(|| format!("Failed to connect to {}", socket.display()))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut line = event_value.to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.shutdown().await?;
+
+    let reply = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .context("Daemon closed the connection without a response")?;
+    let reply: serde_json::Value = serde_json::from_str(&reply)?;
+    if let Some(error) = reply.get("error").and_then(|e| e.as_str()) {
+        anyhow::bail!("Daemon failed to evaluate event: {}", error);
+    }
+    let stderr = reply
+        .get("stderr")
+        .and_then(|s| s.as_str())
+        .map(str::to_string);
+    let mut response: Response = serde_json::from_value(reply)?;
+    response.stderr = stderr;
+    Ok(response)
+}
+
+#[cfg(not(unix))]
+pub async fn serve<F, Fut>(
+    _socket: &Path,
+    _http: Option<std::net::SocketAddr>,
+    _handler: F,
+) -> Result<()>
+where
+    F: Fn(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    anyhow::bail!("rulez daemon requires Unix domain sockets")
+}
+
+#[cfg(not(unix))]
+pub async fn forward(_socket: &Path, _event_value: serde_json::Value) -> Result<Response> {
+    anyhow::bail!("rulez daemon requires Unix domain sockets")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forward_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("rulez.sock");
+
+        let server = serve(&socket, None, |event: serde_json::Value| async move {
+            let tool = event["tool_name"].as_str().unwrap_or_default().to_string();
+            if tool == "Bad" {
+                anyhow::bail!("bad tool");
+            }
+            Ok(Response::inject(event["cwd"].as_str().unwrap_or_default()))
+        });
+        let client = async {
+            while !socket.exists() {
+                tokio::task::yield_now().await;
+            }
+            let ok = forward(&socket, serde_json::json!({"tool_name": "Bash"})).await;
+            let err = forward(&socket, serde_json::json!({"tool_name": "Bad"})).await;
+            (ok, err)
+        };
+
+        let (ok, err) = tokio::select! {
+            result = server => panic!("server exited: {result:?}"),
+            pair = client => pair,
+        };
+        let response = ok.unwrap();
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(response.context.as_deref(), Some(cwd.to_str().unwrap()));
+        assert!(err.unwrap_err().to_string().contains("bad tool"));
+    }
+
+    #[tokio::test]
+    async fn test_over_long_line_gets_error_reply() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("rulez.sock");
+        let handler = |_: serde_json::Value| async { Ok(Response::allow()) };
+
+        let server = serve(&socket, None, handler);
+        let client = async {
+            while !socket.exists() {
+                tokio::task::yield_now().await;
+            }
+            let mut stream = tokio::net::UnixStream::connect(&socket).await.unwrap();
+            let mut line = vec![b' '; crate::protocol::MAX_LINE_BYTES + 1];
+            line.push(b'\n');
+            stream.write_all(&line).await.unwrap();
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).await.unwrap();
+            reply
+        };
+
+        let reply = tokio::select! {
+            result = server => panic!("server exited: {result:?}"),
+            reply = client => reply,
+        };
+        let reply: serde_json::Value = serde_json::from_str(reply.trim()).unwrap();
+        assert!(
+            reply["error"].as_str().unwrap().contains("exceeds"),
+            "{reply}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bind_private_creates_socket_0600() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("rulez.sock");
+        let _listener = bind_private(&socket).unwrap();
+
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        tokio::net::UnixStream::connect(&socket).await.unwrap();
+        // The staging directory is gone
+        let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_serve_refuses_second_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("rulez.sock");
+        let handler = |_: serde_json::Value| async { Ok(Response::allow()) };
+
+        let first = serve(&socket, None, handler);
+        let second = async {
+            while !socket.exists() {
+                tokio::task::yield_now().await;
+            }
+            serve(&socket, None, handler).await
+        };
+        let result = tokio::select! {
+            result = first => panic!("first daemon exited: {result:?}"),
+            result = second => result,
+        };
+        assert!(result.unwrap_err().to_string().contains("already running"));
+    }
+}
@@ -0,0 +1,450 @@
+//! HTTP API for `rulez daemon --http <ADDR>`.
+//!
+//! A minimal HTTP/1.1 JSON API so the UI, editors, and CI can talk to one
+//! long-lived engine instead of starting the binary per interaction. One
+//! request per connection (`Connection: close`), which must arrive within
+//! 10 seconds.
+//!
+//! | Method | Path | Body / query | Answer |
+//! |--------|------|--------------|--------|
+//! | `GET` | `/v1/health` | | `{"status": "ok", "version"}` |
+//! | `POST` | `/v1/evaluate` | hook event | `Response` |
+//! | `POST` | `/v1/validate` | `{"config": path}` or `{"cwd": dir}` | `{"valid", "version", "rules", "enabled_rules"}` or `{"valid": false, "error"}` |
+//! | `GET` | `/v1/logs` | `limit`, `session_id`, `tool`, `rule`, `outcome`, `decision`, `mode`, `since`, `until` | `{"entries": [...]}` |
+//! | `GET` | `/v1/stats` | | daemon counters |
+//!
+//! Evaluation can run configured scripts, so every endpoint except
+//! `/v1/health` requires `Authorization: Bearer <token>`. The daemon writes a
+//! fresh token (mode 0600) next to its socket at startup, and only loopback
+//! addresses may be bound.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::daemon::DaemonStats;
+use crate::config::Config;
+use crate::logging::{LogQuery, QueryFilters, flush_global_logger};
+use crate::models::{Decision, Outcome, PolicyMode, Response};
+
+/// Largest accepted request head (request line and headers)
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// Largest accepted request body
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+/// How long a client may take to send its whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bind the HTTP listener and write a fresh bearer token to `token_file`
+pub async fn bind(addr: SocketAddr, token_file: &Path) -> Result<(TcpListener, String)> {
+    if !addr.ip().is_loopback() {
+        anyhow::bail!(
+            "The HTTP API only binds to loopback addresses (got {})",
+            addr.ip()
+        );
+    }
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind HTTP API on {}", addr))?;
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    write_token(token_file, &token)?;
+    Ok((listener, token))
+}
+
+#[cfg(unix)]
+fn write_token(path: &Path, token: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let _ = std::fs::remove_file(path);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Failed to write token file {}", path.display()))?;
+    file.write_all(token.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_token(path: &Path, token: &str) -> Result<()> {
+    std::fs::write(path, token)
+        .with_context(|| format!("Failed to write token file {}", path.display()))
+}
+
+/// A parsed HTTP request
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Answer one HTTP request on `stream`
+pub async fn serve_request<F, Fut>(
+    mut stream: TcpStream,
+    token: &str,
+    stats: &DaemonStats,
+    evaluate: &F,
+) -> Result<()>
+where
+    F: Fn(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    let (code, body) = match read_request_within(&mut stream, READ_TIMEOUT).await {
+        Ok(Ok(request)) => route(&request, token, stats, evaluate).await,
+        Ok(Err(e)) => (400, serde_json::json!({"error": format!("{e:#}")})),
+        Err(_) => (
+            408,
+            serde_json::json!({"error": "Timed out reading the request"}),
+        ),
+    };
+
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        code,
+        reason_phrase(code),
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn route<F, Fut>(
+    request: &Request,
+    token: &str,
+    stats: &DaemonStats,
+    evaluate: &F,
+) -> (u16, serde_json::Value)
+where
+    F: Fn(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    if request.path == "/v1/health" {
+        return (
+            200,
+            serde_json::json!({"status": "ok", "version": env!("CARGO_PKG_VERSION")}),
+        );
+    }
+
+    if !token_matches(request.authorization.as_deref(), token) {
+        return (
+            401,
+            serde_json::json!({"error": "Missing or invalid bearer token"}),
+        );
+    }
+
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/v1/evaluate") => match serde_json::from_slice(&request.body) {
+            Ok(event_value) => evaluate(event_value)
+                .await
+                .and_then(|response| Ok(serde_json::to_value(response)?))
+                .map_err(|e| (422, e)),
+            Err(e) => Err((400, anyhow::anyhow!("Invalid JSON body: {}", e))),
+        },
+        ("POST", "/v1/validate") => validate(&request.body).map_err(|e| (400, e)),
+        ("GET", "/v1/logs") => logs(&request.query).map_err(|e| (400, e)),
+        ("GET", "/v1/stats") => Ok(stats.to_json()),
+        (_, "/v1/evaluate" | "/v1/validate" | "/v1/logs" | "/v1/stats") => {
+            return (405, serde_json::json!({"error": "Method not allowed"}));
+        }
+        _ => return (404, serde_json::json!({"error": "Not found"})),
+    };
+    match result {
+        Ok(body) => (200, body),
+        Err((code, e)) => (code, serde_json::json!({"error": format!("{e:#}")})),
+    }
+}
+
+/// `POST /v1/validate`: load a config by path or project directory
+fn validate(body: &[u8]) -> Result<serde_json::Value> {
+    let request: serde_json::Value = if body.is_empty() {
+        serde_json::json!({})
+    } else {
+        serde_json::from_slice(body).context("Invalid JSON body")?
+    };
+
+    let loaded = match (
+        request.get("config").and_then(|c| c.as_str()),
+        request.get("cwd").and_then(|c| c.as_str()),
+    ) {
+        (Some(path), _) => Config::from_file(path),
+        (None, Some(cwd)) => Config::load(Some(Path::new(cwd))),
+        (None, None) => anyhow::bail!("Expected \"config\" or \"cwd\""),
+    };
+
+    Ok(match loaded {
+        Ok(config) => serde_json::json!({
+            "valid": true,
+            "version": config.version,
+            "rules": config.rules.len(),
+            "enabled_rules": config.enabled_rules().len(),
+        }),
+        Err(e) => serde_json::json!({"valid": false, "error": format!("{e:#}")}),
+    })
+}
+
+/// `GET /v1/logs`: query the audit log with the same filters as `rulez logs`
+fn logs(query: &[(String, String)]) -> Result<serde_json::Value> {
+    let mut filters = QueryFilters {
+        limit: Some(50),
+        ..Default::default()
+    };
+    for (key, value) in query {
+        match key.as_str() {
+            "limit" => filters.limit = Some(value.parse().context("Invalid limit")?),
+            "session_id" => filters.session_id = Some(value.clone()),
+            "tool" => filters.tool_name = Some(value.clone()),
+            "rule" => filters.rule_name = Some(value.clone()),
+            "outcome" => {
+                filters.outcome = Some(match value.to_lowercase().as_str() {
+                    "allow" => Outcome::Allow,
+                    "block" => Outcome::Block,
+                    "inject" => Outcome::Inject,
+                    _ => anyhow::bail!("Invalid outcome '{}'", value),
+                });
+            }
+            "decision" => {
+                filters.decision = Some(value.parse::<Decision>().map_err(anyhow::Error::msg)?);
+            }
+            "mode" => {
+                filters.mode = Some(match value.to_lowercase().as_str() {
+                    "enforce" => PolicyMode::Enforce,
+                    "warn" => PolicyMode::Warn,
+                    "audit" => PolicyMode::Audit,
+                    _ => anyhow::bail!("Invalid mode '{}'", value),
+                });
+            }
+            "since" => filters.since = Some(parse_time(value)?),
+            "until" => filters.until = Some(parse_time(value)?),
+            _ => anyhow::bail!("Unknown query parameter '{}'", key),
+        }
+    }
+    // Include entries still queued on this daemon's background log writer
+    flush_global_logger();
+    let entries = LogQuery::new().query(filters)?;
+    Ok(serde_json::json!({"entries": entries}))
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("Invalid RFC3339 timestamp '{}'", value))?
+        .with_timezone(&Utc))
+}
+
+/// Whether an `Authorization` header carries the bearer token
+///
+/// Compared in constant time, so response timing doesn't reveal how much
+/// of a guess was right.
+fn token_matches(authorization: Option<&str>, token: &str) -> bool {
+    let Some(presented) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// [`read_request`], giving up after `limit` so an idle client can't hold
+/// its connection open
+async fn read_request_within(
+    stream: &mut TcpStream,
+    limit: Duration,
+) -> Result<Result<Request>, tokio::time::error::Elapsed> {
+    tokio::time::timeout(limit, read_request(stream)).await
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut buf = Vec::new();
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            anyhow::bail!("Request head too large");
+        }
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            anyhow::bail!("Connection closed before end of request head");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let (mut request, content_length) = parse_head(&String::from_utf8_lossy(&buf[..head_end]))?;
+    if content_length > MAX_BODY_BYTES {
+        anyhow::bail!("Request body too large");
+    }
+
+    let mut body = buf.split_off(head_end + 4);
+    if body.len() < content_length {
+        let mut rest = vec![0u8; content_length - body.len()];
+        stream.read_exact(&mut rest).await?;
+        body.extend_from_slice(&rest);
+    }
+    body.truncate(content_length);
+    request.body = body;
+    Ok(request)
+}
+
+/// Parse the request line and headers into a request without a body and
+/// its `Content-Length`
+fn parse_head(head: &str) -> Result<(Request, usize)> {
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line");
+    };
+
+    let mut authorization = None;
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().context("Invalid Content-Length")?;
+        }
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+
+    let request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query,
+        authorization,
+        body: Vec::new(),
+    };
+    Ok((request, content_length))
+}
+
+/// Decode `%XX` escapes and `+` in a query component
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match u8::from_str_radix(input.get(i + 1..i + 3).unwrap_or(""), 16) {
+                    Ok(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn reason_phrase(code: u16) -> &'static str {
+    match code {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_head() {
+        let (request, content_length) = parse_head(
+            "GET /v1/logs?limit=5&since=2025-01-01T00%3A00%3A00Z HTTP/1.1\r\nHost: x\r\nauthorization: Bearer abc\r\nContent-Length: 3",
+        )
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/v1/logs");
+        assert_eq!(
+            request.query,
+            [
+                ("limit".to_string(), "5".to_string()),
+                ("since".to_string(), "2025-01-01T00:00:00Z".to_string())
+            ]
+        );
+        assert_eq!(request.authorization.as_deref(), Some("Bearer abc"));
+        assert_eq!(content_length, 3);
+
+        assert!(parse_head("").is_err());
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches(Some("Bearer abc"), "abc"));
+        assert!(!token_matches(Some("Bearer abd"), "abc"));
+        assert!(!token_matches(Some("Bearer ab"), "abc"));
+        assert!(!token_matches(Some("abc"), "abc"));
+        assert!(!token_matches(None, "abc"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_client_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        client
+            .write_all(b"GET /v1/stats HTTP/1.1\r\n")
+            .await
+            .unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+        let read = read_request_within(&mut server, Duration::from_millis(50)).await;
+        assert!(read.is_err());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a+b%20c"), "a b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_validate_requires_target() {
+        assert!(validate(b"{}").is_err());
+        let result = validate(br#"{"config": "/nonexistent/hooks.yaml"}"#).unwrap();
+        assert_eq!(result["valid"], false);
+    }
+}
@@ -89,8 +89,31 @@ struct JsonRuleEvaluation {
     /// since process_event() merges all matched rules into one response.
     #[serde(skip_serializing_if = "Option::is_none")]
     script_output: Option<String>,
+    /// Matching time in microseconds
+    elapsed_us: u64,
+    /// Result of each matcher the rule defines, in evaluation order
+    matchers: Vec<JsonMatcherTrace>,
+    /// What the rule does when it matches (`block`, `warn`, `inject`, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<String>,
 }
 
+/// One matcher's result within a rule's trace
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonMatcherTrace {
+    matcher: String,
+    matched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    /// The part of the event the matcher looked at, truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+}
+
+/// Longest input snippet kept in a matcher trace
+const SNIPPET_CHARS: usize = 120;
+
 /// Run the debug command
 pub async fn run(
     event_type: String,
@@ -103,8 +126,9 @@ pub async fn run(
 ) -> Result<()> {
     // Clear regex cache for state isolation between debug invocations
     {
-        use crate::hooks::REGEX_CACHE;
+        use crate::hooks::{REGEX_CACHE, REGEX_SET_CACHE};
         REGEX_CACHE.lock().unwrap().clear();
+        REGEX_SET_CACHE.lock().unwrap().clear();
     }
 
     let event_type = SimEventType::parse_event_type(&event_type).context(format!(
@@ -190,7 +214,10 @@ pub async fn run(
     }
 
     // Note if any matched rules have run scripts (exercised via process_event above)
-    let has_run_scripts = config.rules.iter().any(|r| r.actions.run.is_some());
+    let has_run_scripts = config
+        .rules
+        .iter()
+        .any(|r| r.actions.steps().any(|actions| actions.run.is_some()));
     if has_run_scripts {
         println!();
         println!("[DEBUG] Run scripts were exercised via hooks::process_event() above");
@@ -209,8 +236,8 @@ async fn run_json_mode(event: Event, config: &Config) -> Result<()> {
 
     for rule in &config.rules {
         let rule_start = std::time::Instant::now();
-        let matches = rule_matches_event(rule, &event);
-        let rule_time = rule_start.elapsed().as_secs_f64() * 1000.0;
+        let (matches, matcher_results) = hooks::matches_rule_with_debug(&event, rule);
+        let elapsed = rule_start.elapsed();
 
         let details = if matches {
             Some("Rule matched".to_string())
@@ -229,11 +256,16 @@ async fn run_json_mode(event: Event, config: &Config) -> Result<()> {
         evaluations.push(JsonRuleEvaluation {
             rule_name: rule.name.clone(),
             matched: matches,
-            time_ms: rule_time,
+            time_ms: elapsed.as_secs_f64() * 1000.0,
             details,
             pattern,
             input: input.clone(),
             script_output: None, // Enriched below after process_event()
+            elapsed_us: u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+            matchers: matcher_results
+                .map(|results| matcher_trace(rule, &event, &results))
+                .unwrap_or_default(),
+            action: matches.then(|| action_taken(rule)),
         });
     }
 
@@ -287,92 +319,134 @@ async fn run_json_mode(event: Event, config: &Config) -> Result<()> {
     Ok(())
 }
 
-/// Check if a single rule matches the given event (simplified version for JSON trace)
-fn rule_matches_event(rule: &crate::models::Rule, event: &Event) -> bool {
-    let matchers = &rule.matchers;
-
-    // Check operations (event types)
-    if let Some(ref operations) = matchers.operations {
-        let event_name = event.hook_event_name.to_string();
-        if !operations.contains(&event_name) {
-            return false;
-        }
-    }
+/// Per-matcher trace for a rule, from the engine's own matcher results
+fn matcher_trace(
+    rule: &crate::models::Rule,
+    event: &Event,
+    results: &crate::models::MatcherResults,
+) -> Vec<JsonMatcherTrace> {
+    let definitions = serde_json::to_value(&rule.matchers).unwrap_or_default();
+    let Ok(serde_json::Value::Object(results)) = serde_json::to_value(results) else {
+        return Vec::new();
+    };
 
-    // Check tool filter
-    if let Some(ref tools) = matchers.tools {
-        if let Some(ref tool_name) = event.tool_name {
-            if !tools.contains(tool_name) {
-                return false;
+    let mut trace = Vec::new();
+    for (key, result) in results {
+        let name = key.trim_end_matches("_matched");
+        if let serde_json::Value::Object(custom) = result {
+            // Custom matchers report one result per registered matcher
+            for (custom_name, matched) in custom {
+                trace.push(JsonMatcherTrace {
+                    matcher: format!("custom.{}", custom_name),
+                    matched: matched.as_bool().unwrap_or(false),
+                    pattern: definitions
+                        .get("custom")
+                        .and_then(|c| c.get(&custom_name))
+                        .map(display_value),
+                    input: None,
+                });
             }
-        } else {
-            return false;
+            continue;
         }
+        let pattern = if name == "field_validation" {
+            ["require_fields", "field_types"]
+                .iter()
+                .filter_map(|field| definitions.get(field).map(display_value))
+                .reduce(|a, b| format!("{}; {}", a, b))
+        } else {
+            definitions.get(name).map(display_value)
+        };
+        trace.push(JsonMatcherTrace {
+            matcher: name.to_string(),
+            matched: result.as_bool().unwrap_or(false),
+            pattern,
+            input: matcher_input(name, event).map(|input| snippet(&input)),
+        });
     }
+    trace
+}
 
-    // Check command_match pattern
-    if let Some(ref cmd_pattern) = matchers.command_match {
-        if let Some(ref tool_input) = event.tool_input {
-            let cmd = tool_input
-                .get("command")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            match crate::hooks::get_or_compile_regex(cmd_pattern, false) {
-                Ok(re) => {
-                    if !re.is_match(cmd) {
-                        return false;
-                    }
-                }
-                Err(_) => {
-                    return false; // Fail-closed: invalid regex blocks
-                }
-            }
-        } else {
-            return false;
-        }
+/// Render a matcher definition compactly: lists joined, strings bare
+fn display_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
     }
+}
 
-    // Check file extensions
-    if let Some(ref extensions) = matchers.extensions {
-        if let Some(ref tool_input) = event.tool_input {
-            let file_path = tool_input
-                .get("filePath")
-                .or_else(|| tool_input.get("file_path"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let path_ext = std::path::Path::new(file_path)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("");
-            if !extensions
-                .iter()
-                .any(|ext| ext == &format!(".{}", path_ext))
-            {
-                return false;
-            }
-        } else {
-            return false;
-        }
+/// The event value a matcher is evaluated against
+fn matcher_input(matcher: &str, event: &Event) -> Option<String> {
+    let tool_input = event.tool_input.as_ref();
+    let file_path = || {
+        tool_input
+            .and_then(|ti| ti.get("filePath").or_else(|| ti.get("file_path")))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    };
+    match matcher {
+        "tools" => event.tool_name.clone(),
+        "command_match" => tool_input
+            .and_then(|ti| ti.get("command"))
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        "extensions" | "directories" => file_path(),
+        "operations" => Some(event.hook_event_name.to_string()),
+        "prompt_match" => event.prompt.clone(),
+        "response_match" => event.tool_response_text(),
+        "message_match" => event.message.clone(),
+        "compact_trigger" => event.trigger.clone(),
+        "users" => crate::identity::of(event).user.clone(),
+        "teams" => Some(crate::identity::of(event).teams.join(", ")),
+        _ => None,
     }
+}
 
-    // Check directory patterns
-    if let Some(ref directories) = matchers.directories {
-        if let Some(ref tool_input) = event.tool_input {
-            let file_path = tool_input
-                .get("filePath")
-                .or_else(|| tool_input.get("file_path"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let glob_set = crate::hooks::build_glob_set(directories);
-            if !glob_set.is_match(file_path) {
-                return false;
-            }
-        } else {
-            return false;
-        }
+/// Truncate an input to `SNIPPET_CHARS` characters
+fn snippet(input: &str) -> String {
+    if input.chars().count() <= SNIPPET_CHARS {
+        input.to_string()
+    } else {
+        format!("{}…", input.chars().take(SNIPPET_CHARS).collect::<String>())
     }
+}
 
-    true
+/// What a matched rule does, given its actions and mode
+fn action_taken(rule: &crate::models::Rule) -> String {
+    let actions = &rule.actions;
+    let mut kinds: Vec<&str> = Vec::new();
+    if actions.block == Some(true) || actions.block_if_match.is_some() {
+        kinds.push(match rule.effective_mode() {
+            crate::models::PolicyMode::Enforce => "block",
+            crate::models::PolicyMode::Warn => "warn",
+            crate::models::PolicyMode::Audit => "audit",
+        });
+    }
+    if actions.inject.is_some()
+        || actions.inject_inline.is_some()
+        || actions.inject_command.is_some()
+    {
+        kinds.push("inject");
+    }
+    if actions.run.is_some()
+        || actions.inline_script.is_some()
+        || actions.validate_expr.is_some()
+        || actions.script_rhai.is_some()
+    {
+        kinds.push("validate");
+    }
+    if actions.pipeline.is_some() {
+        kinds.push("pipeline");
+    }
+    if kinds.is_empty() {
+        "none".to_string()
+    } else {
+        kinds.join("+")
+    }
 }
 
 /// Extract the primary matching pattern from a rule for display
@@ -427,8 +501,10 @@ pub fn build_event(
             }
             "Write" | "Edit" | "Read" => {
                 let file_path = path.unwrap_or_else(|| "src/main.rs".to_string());
+                // The engine's extensions/directories matchers read `filePath`
                 json!({
-                    "file_path": file_path,
+                    "file_path": file_path.clone(),
+                    "filePath": file_path,
                     "content": "// Simulated content"
                 })
             }
@@ -460,6 +536,13 @@ pub fn build_event(
         permission_mode: None,
         tool_use_id: None,
         prompt,
+        tool_response: None,
+        stop_hook_active: None,
+        trigger: None,
+        custom_instructions: None,
+        message: None,
+        pre_tool_use: None,
+        identity: None,
     }
 }
 
@@ -474,7 +557,7 @@ fn print_rule_summary(config: &Config) {
         let priority = metadata.map_or(50, |m| m.priority);
         let status = if enabled { "✓" } else { "○" };
 
-        println!("  {} [P{}] {}", status, priority, rule.name,);
+        println!("  {} [P{}] {}", status, priority, rule.name);
         if let Some(desc) = &rule.description {
             println!("      {}", desc);
         }
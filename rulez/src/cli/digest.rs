@@ -0,0 +1,63 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::digest::{DigestPeriod, build_from_log, expand_home, send_webhook, write_markdown};
+use crate::log_export::parse_time_bound;
+
+/// Summarize a period of the audit log
+///
+/// Destinations default to `settings.digest`. With neither a webhook nor an
+/// output file, the digest is printed to stdout.
+///
+/// # Arguments
+/// * `period` - Daily or weekly; sets the start of the window
+/// * `since` - Override the start (RFC3339 or an age like `3d`)
+/// * `webhook` - POST the digest to this URL
+/// * `output` - Write the markdown to this file (`-` for stdout)
+/// * `json` - Print JSON instead of markdown to stdout
+/// * `path` - Log file to read (defaults to ~/.claude/logs/rulez.log)
+pub fn run(
+    period: DigestPeriod,
+    since: Option<String>,
+    webhook: Option<String>,
+    output: Option<PathBuf>,
+    json: bool,
+    path: Option<PathBuf>,
+) -> Result<()> {
+    let mut config = Config::load(None)?.settings.digest.unwrap_or_default();
+    if webhook.is_some() {
+        config.webhook = webhook;
+    }
+    let output = output.or_else(|| config.output.as_deref().map(expand_home));
+
+    let until = Utc::now();
+    let since = match since {
+        Some(since) => parse_time_bound(&since, until)?,
+        None => until - period.duration(),
+    };
+    let digest = build_from_log(path, since, until, config.top_rules)?;
+
+    let to_stdout = match output {
+        Some(ref output) if output.as_os_str() == "-" => true,
+        Some(ref output) => {
+            write_markdown(output, &digest)?;
+            println!("Wrote {} digest to {}", period, output.display());
+            false
+        }
+        None => config.webhook.is_none(),
+    };
+    if let Some(ref url) = config.webhook {
+        send_webhook(&config, &digest)?;
+        println!("Sent {} digest to {}", period, url);
+    }
+    if to_stdout {
+        if json {
+            println!("{}", serde_json::to_string_pretty(&digest)?);
+        } else {
+            print!("{}", digest.to_markdown());
+        }
+    }
+    Ok(())
+}
@@ -3,7 +3,7 @@ use serde::Serialize;
 
 use crate::config::Config;
 use crate::logging::{LogQuery, QueryFilters};
-use crate::models::{Decision, Outcome, PolicyMode, Rule};
+use crate::models::{Actions, Decision, Outcome, PolicyMode, Rule};
 
 /// Explain why rules fired for a given event
 pub async fn run(event_id: String) -> Result<()> {
@@ -170,24 +170,58 @@ async fn output_rule_text(rule: &Rule, no_stats: bool) -> Result<()> {
     if let Some(ref cmd_match) = rule.matchers.command_match {
         println!("  command_match: \"{}\"", cmd_match);
     }
+    if let Some(ref response_match) = rule.matchers.response_match {
+        println!("  response_match: \"{}\"", response_match);
+    }
+    if let Some(tool_failed) = rule.matchers.tool_failed {
+        println!("  tool_failed: {}", tool_failed);
+    }
+    if let Some(ref message_match) = rule.matchers.message_match {
+        println!("  message_match: \"{}\"", message_match);
+    }
+    if let Some(ref trigger) = rule.matchers.compact_trigger {
+        println!("  compact_trigger: {}", trigger);
+    }
+    if let Some(ref pre_rules) = rule.matchers.pre_rules {
+        println!("  pre_rules: {:?}", pre_rules);
+    }
+    if let Some(ref users) = rule.matchers.users {
+        println!("  users: {:?}", users);
+    }
+    if let Some(ref teams) = rule.matchers.teams {
+        println!("  teams: {:?}", teams);
+    }
+    if let Some(ref transcript_match) = rule.matchers.transcript_match {
+        println!(
+            "  transcript_match: \"{}\" (last {} messages{})",
+            transcript_match.pattern(),
+            transcript_match.last_messages(),
+            if transcript_match.negate() {
+                ", negated"
+            } else {
+                ""
+            }
+        );
+    }
     println!();
 
     // Actions
     println!("Actions:");
-    if let Some(ref inject) = rule.actions.inject {
-        println!("  inject: {}", inject);
-    }
-    if let Some(script_path) = rule.actions.script_path() {
-        println!("  run: {}", script_path);
-        if let Some(trust) = rule.actions.trust_level() {
-            println!("  trust: {}", trust);
+    if let Some(ref steps) = rule.actions.pipeline {
+        for (index, step) in steps.iter().enumerate() {
+            println!(
+                "  step {}{}:",
+                index + 1,
+                if step.continue_on_failure {
+                    " (continue_on_failure)"
+                } else {
+                    ""
+                }
+            );
+            print_actions(&step.actions, "  ");
         }
-    }
-    if let Some(block) = rule.actions.block {
-        println!("  block: {}", block);
-    }
-    if let Some(ref block_if) = rule.actions.block_if_match {
-        println!("  block_if_match: \"{}\"", block_if);
+    } else {
+        print_actions(&rule.actions, "");
     }
     println!();
 
@@ -226,6 +260,55 @@ async fn output_rule_text(rule: &Rule, no_stats: bool) -> Result<()> {
     Ok(())
 }
 
+/// Print one set of actions, one per line
+fn print_actions(actions: &Actions, indent: &str) {
+    if let Some(ref when) = actions.when {
+        println!("{}  when: {}", indent, when);
+    }
+    if let Some(ref inject) = actions.inject {
+        println!("{}  inject: {}", indent, inject);
+    }
+    if let Some(script_path) = actions.script_path() {
+        println!("{}  run: {}", indent, script_path);
+        if let Some(trust) = actions.trust_level() {
+            println!("{}  trust: {}", indent, trust);
+        }
+    }
+    if let Some(block) = actions.block {
+        println!("{}  block: {}", indent, block);
+    }
+    if let Some(ref block_if) = actions.block_if_match {
+        println!("{}  block_if_match: \"{}\"", indent, block_if);
+    }
+    if let Some(scan_secrets) = actions.scan_secrets {
+        println!("{}  scan_secrets: {}", indent, scan_secrets);
+    }
+    if let Some(severities) = actions.scan_pii.and_then(|scan| scan.severities()) {
+        println!(
+            "{}  scan_pii: email={}, phone={}, national_id={}",
+            indent, severities.email, severities.phone, severities.national_id
+        );
+    }
+    if let Some(decision) = actions.permission_decision {
+        println!("{}  permission_decision: {}", indent, decision);
+    }
+    if let Some(ref message) = actions.system_message {
+        println!("{}  system_message: \"{}\"", indent, message);
+    }
+    if let Some(ref webhook) = actions.webhook {
+        println!("{}  webhook: {}", indent, webhook.url());
+    }
+    if let Some(ref rewrite) = actions.rewrite_prompt {
+        println!(
+            "{}  rewrite_prompt: prepend={}, append={}, redact={:?}",
+            indent,
+            rewrite.prepend.is_some(),
+            rewrite.append.is_some(),
+            rewrite.redact
+        );
+    }
+}
+
 /// Output rule details as JSON (P2.3-T03)
 async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
     #[derive(Serialize)]
@@ -250,6 +333,52 @@ async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
         trust: Option<crate::models::TrustLevel>,
         block: Option<bool>,
         block_if_match: Option<&'a str>,
+        scan_secrets: Option<bool>,
+        scan_pii: Option<crate::models::ScanPii>,
+        permission_decision: Option<crate::models::PermissionDecision>,
+        system_message: Option<&'a str>,
+        webhook: Option<&'a str>,
+        rewrite_prompt: Option<&'a crate::models::PromptRewrite>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        when: Option<&'a str>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        steps: Vec<StepOutput<'a>>,
+    }
+
+    #[derive(Serialize)]
+    struct StepOutput<'a> {
+        #[serde(flatten)]
+        actions: ActionsOutput<'a>,
+        continue_on_failure: bool,
+    }
+
+    fn actions_output(actions: &Actions) -> ActionsOutput<'_> {
+        ActionsOutput {
+            inject: actions.inject.as_deref(),
+            run: actions.script_path(),
+            trust: actions.trust_level(),
+            block: actions.block,
+            block_if_match: actions.block_if_match.as_deref(),
+            scan_secrets: actions.scan_secrets,
+            scan_pii: actions.scan_pii,
+            permission_decision: actions.permission_decision,
+            system_message: actions.system_message.as_deref(),
+            webhook: actions
+                .webhook
+                .as_ref()
+                .map(crate::models::WebhookAction::url),
+            rewrite_prompt: actions.rewrite_prompt.as_ref(),
+            when: actions.when.as_deref(),
+            steps: actions
+                .pipeline
+                .iter()
+                .flatten()
+                .map(|step| StepOutput {
+                    actions: actions_output(&step.actions),
+                    continue_on_failure: step.continue_on_failure,
+                })
+                .collect(),
+        }
     }
 
     #[derive(Serialize)]
@@ -272,13 +401,7 @@ async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
             .map(|m| m.priority == 0)
             .unwrap_or(true);
 
-    let actions = ActionsOutput {
-        inject: rule.actions.inject.as_deref(),
-        run: rule.actions.script_path(),
-        trust: rule.actions.trust_level(),
-        block: rule.actions.block,
-        block_if_match: rule.actions.block_if_match.as_deref(),
-    };
+    let actions = actions_output(&rule.actions);
 
     let activity: Option<ActivityStats> = if !no_stats {
         get_activity_stats(&rule.name)
@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::governance::{self, GovernanceConfig, ReviewStatus};
+
+/// One rule's row in `rulez governance report`
+#[derive(Debug, Serialize)]
+pub struct ReviewRow {
+    pub rule: String,
+    pub mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reviewed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<i64>,
+    pub status: &'static str,
+    /// Mode the rule actually runs in, when governance settings change it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runs_as: Option<String>,
+}
+
+/// Review status of every rule, overdue ones first
+pub fn review_rows(config: &Config, today: chrono::NaiveDate) -> Vec<ReviewRow> {
+    let policy = config.settings.governance.clone().unwrap_or_default();
+    let mut applied = config.clone();
+    governance::apply(&mut applied, today);
+    let mut rows: Vec<ReviewRow> = config
+        .rules
+        .iter()
+        .zip(&applied.rules)
+        .map(|(rule, applied)| {
+            let status = governance::review_status(rule, policy.review_max_age_days, today);
+            ReviewRow {
+                rule: rule.name.clone(),
+                mode: rule.effective_mode().to_string(),
+                runs_as: (applied.mode != rule.mode).then(|| applied.effective_mode().to_string()),
+                last_reviewed: rule
+                    .governance
+                    .as_ref()
+                    .and_then(|g| g.last_reviewed.clone()),
+                age_days: match status {
+                    ReviewStatus::Current { age_days } | ReviewStatus::Overdue { age_days } => {
+                        Some(age_days)
+                    }
+                    _ => None,
+                },
+                status: status.label(),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| std::cmp::Reverse(row.age_days.unwrap_or(i64::MIN)));
+    rows
+}
+
+/// Print rule review status (`rulez governance report`)
+pub fn report(config_path: Option<String>, json: bool) -> Result<()> {
+    let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    // Read without the load-time downgrade so the report shows configured modes
+    let config = Config::read_compiled(std::path::Path::new(&config_path))
+        .context("Failed to load configuration")?;
+    let policy: GovernanceConfig = config.settings.governance.clone().unwrap_or_default();
+    let rows = review_rows(&config, governance::today());
+
+    if json {
+        let output = serde_json::json!({
+            "review_max_age_days": policy.review_max_age_days,
+            "downgrade_overdue": policy.downgrade_overdue,
+            "strict": policy.strict,
+            "rules": rows,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("rulez governance report — Rule Review Status");
+    println!("============================================");
+    println!();
+    println!(
+        "Review limit: {} days{}",
+        policy.review_max_age_days,
+        if policy.downgrade_overdue {
+            " (overdue enforce rules run in warn mode)"
+        } else {
+            ""
+        }
+    );
+    if policy.strict {
+        println!(
+            "Strict governance: enforce rules need {} ({})",
+            governance::REQUIRED_PROVENANCE.join(", "),
+            match policy.strict_action {
+                governance::StrictAction::Reject => "config rejected otherwise",
+                governance::StrictAction::Audit => "offenders run in audit mode",
+            }
+        );
+    }
+    println!();
+
+    let width = rows.iter().map(|r| r.rule.len()).max().unwrap_or(4).max(4);
+    println!(
+        "{:<width$}  {:<8}  {:<14}  {:>5}  STATUS",
+        "RULE", "MODE", "LAST REVIEWED", "AGE"
+    );
+    for row in &rows {
+        let mode = match &row.runs_as {
+            Some(runs_as) => format!("{}→{}", row.mode, runs_as),
+            None => row.mode.clone(),
+        };
+        println!(
+            "{:<width$}  {:<8}  {:<14}  {:>5}  {}",
+            row.rule,
+            mode,
+            row.last_reviewed.as_deref().unwrap_or("-"),
+            row.age_days
+                .map_or_else(|| "-".to_string(), |d| format!("{}d", d)),
+            row.status
+        );
+    }
+
+    let overdue = rows.iter().filter(|r| r.status == "overdue").count();
+    let never = rows.iter().filter(|r| r.status == "never-reviewed").count();
+    println!();
+    println!(
+        "Summary: {} rule{}, {} overdue, {} never reviewed",
+        rows.len(),
+        if rows.len() == 1 { "" } else { "s" },
+        overdue,
+        never
+    );
+    Ok(())
+}
+
+/// One rule's row in `rulez governance list`
+#[derive(Debug, Serialize)]
+pub struct InventoryRow {
+    pub rule: String,
+    pub mode: String,
+    pub priority: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reviewed: Option<String>,
+}
+
+/// Every rule with its governance metadata, highest priority first
+///
+/// With `tag`, only rules carrying that tag (compared case-insensitively).
+pub fn inventory_rows(config: &Config, tag: Option<&str>) -> Vec<InventoryRow> {
+    let mut rules: Vec<&crate::models::Rule> = config.rules.iter().collect();
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.effective_priority()));
+    rules
+        .into_iter()
+        .map(|rule| {
+            let governance = rule.governance.clone().unwrap_or_default();
+            InventoryRow {
+                rule: rule.name.clone(),
+                mode: rule.effective_mode().to_string(),
+                priority: rule.effective_priority(),
+                owner: governance.author,
+                confidence: governance.confidence.map(|c| c.to_string()),
+                tags: governance.tags.unwrap_or_default(),
+                last_reviewed: governance.last_reviewed,
+            }
+        })
+        .filter(|row| tag.is_none_or(|tag| row.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))))
+        .collect()
+}
+
+/// Print every rule's mode, priority and governance metadata (`rulez governance list`)
+pub fn list(config_path: Option<String>, tag: Option<&str>, json: bool) -> Result<()> {
+    let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    // Configured modes, as written (see `report`)
+    let config = Config::read_compiled(std::path::Path::new(&config_path))
+        .context("Failed to load configuration")?;
+    let rows = inventory_rows(&config, tag);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        match tag {
+            Some(tag) => println!("No rules tagged '{}'", tag),
+            None => println!("No rules configured"),
+        }
+        return Ok(());
+    }
+
+    let width = rows.iter().map(|r| r.rule.len()).max().unwrap_or(4).max(4);
+    let owner_width = rows
+        .iter()
+        .filter_map(|r| r.owner.as_ref().map(String::len))
+        .max()
+        .unwrap_or(5)
+        .max(5);
+    println!(
+        "{:<width$}  {:<7}  {:>8}  {:<owner_width$}  {:<10}  {:<13}  TAGS",
+        "RULE", "MODE", "PRIORITY", "OWNER", "CONFIDENCE", "LAST REVIEWED"
+    );
+    for row in &rows {
+        println!(
+            "{:<width$}  {:<7}  {:>8}  {:<owner_width$}  {:<10}  {:<13}  {}",
+            row.rule,
+            row.mode,
+            row.priority,
+            row.owner.as_deref().unwrap_or("-"),
+            row.confidence.as_deref().unwrap_or("-"),
+            row.last_reviewed.as_deref().unwrap_or("-"),
+            if row.tags.is_empty() {
+                "-".to_string()
+            } else {
+                row.tags.join(", ")
+            }
+        );
+    }
+    println!();
+    println!(
+        "{} rule{}{}",
+        rows.len(),
+        if rows.len() == 1 { "" } else { "s" },
+        tag.map(|tag| format!(" tagged '{}'", tag))
+            .unwrap_or_default()
+    );
+    Ok(())
+}
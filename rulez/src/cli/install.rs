@@ -37,12 +37,28 @@ struct HooksConfig {
     post_tool_use: Vec<MatcherEntry>,
     #[serde(rename = "Stop", default, skip_serializing_if = "Vec::is_empty")]
     stop: Vec<MatcherEntry>,
+    #[serde(
+        rename = "SubagentStop",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    subagent_stop: Vec<MatcherEntry>,
     #[serde(
         rename = "SessionStart",
         default,
         skip_serializing_if = "Vec::is_empty"
     )]
     session_start: Vec<MatcherEntry>,
+    #[serde(rename = "SessionEnd", default, skip_serializing_if = "Vec::is_empty")]
+    session_end: Vec<MatcherEntry>,
+    #[serde(rename = "PreCompact", default, skip_serializing_if = "Vec::is_empty")]
+    pre_compact: Vec<MatcherEntry>,
+    #[serde(
+        rename = "Notification",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    notification: Vec<MatcherEntry>,
 }
 
 /// A matcher entry groups a glob pattern with its hook commands
@@ -129,7 +145,11 @@ pub async fn run(scope: Scope, binary_path: Option<String>) -> Result<()> {
     hooks.pre_tool_use.push(matcher_entry.clone());
     hooks.post_tool_use.push(matcher_entry.clone());
     hooks.stop.push(matcher_entry.clone());
-    hooks.session_start.push(matcher_entry);
+    hooks.subagent_stop.push(matcher_entry.clone());
+    hooks.session_start.push(matcher_entry.clone());
+    hooks.session_end.push(matcher_entry.clone());
+    hooks.pre_compact.push(matcher_entry.clone());
+    hooks.notification.push(matcher_entry);
 
     // Save settings
     save_settings(&settings_path, &settings)?;
@@ -139,7 +159,11 @@ pub async fn run(scope: Scope, binary_path: Option<String>) -> Result<()> {
     println!("  • PreToolUse");
     println!("  • PostToolUse");
     println!("  • Stop");
+    println!("  • SubagentStop");
     println!("  • SessionStart");
+    println!("  • SessionEnd");
+    println!("  • PreCompact");
+    println!("  • Notification");
     println!();
     println!("To verify installation:");
     println!("  rulez validate");
@@ -260,17 +284,29 @@ pub async fn uninstall(scope: Scope) -> Result<()> {
         let before = hooks.pre_tool_use.len()
             + hooks.post_tool_use.len()
             + hooks.stop.len()
-            + hooks.session_start.len();
+            + hooks.subagent_stop.len()
+            + hooks.session_start.len()
+            + hooks.session_end.len()
+            + hooks.pre_compact.len()
+            + hooks.notification.len();
 
         hooks.pre_tool_use.retain(|m| !is_rulez_hook(m));
         hooks.post_tool_use.retain(|m| !is_rulez_hook(m));
         hooks.stop.retain(|m| !is_rulez_hook(m));
+        hooks.subagent_stop.retain(|m| !is_rulez_hook(m));
         hooks.session_start.retain(|m| !is_rulez_hook(m));
+        hooks.session_end.retain(|m| !is_rulez_hook(m));
+        hooks.pre_compact.retain(|m| !is_rulez_hook(m));
+        hooks.notification.retain(|m| !is_rulez_hook(m));
 
         let after = hooks.pre_tool_use.len()
             + hooks.post_tool_use.len()
             + hooks.stop.len()
-            + hooks.session_start.len();
+            + hooks.subagent_stop.len()
+            + hooks.session_start.len()
+            + hooks.session_end.len()
+            + hooks.pre_compact.len()
+            + hooks.notification.len();
 
         if before == after {
             println!("RuleZ was not installed");
@@ -281,7 +317,11 @@ pub async fn uninstall(scope: Scope) -> Result<()> {
         if hooks.pre_tool_use.is_empty()
             && hooks.post_tool_use.is_empty()
             && hooks.stop.is_empty()
+            && hooks.subagent_stop.is_empty()
             && hooks.session_start.is_empty()
+            && hooks.session_end.is_empty()
+            && hooks.pre_compact.is_empty()
+            && hooks.notification.is_empty()
         {
             settings.hooks = None;
         }
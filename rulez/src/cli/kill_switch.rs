@@ -0,0 +1,27 @@
+use anyhow::Result;
+
+use crate::kill_switch;
+
+/// Turn RuleZ off (`rulez disable --all`)
+pub fn disable(ttl: Option<&str>, reason: Option<String>) -> Result<()> {
+    let state = kill_switch::disable(ttl, reason, chrono::Utc::now())?;
+    println!("RuleZ {}", state.describe());
+    println!("Every event is allowed and logged with decision 'bypassed'.");
+    Ok(())
+}
+
+/// Turn RuleZ back on (`rulez enable --all`)
+pub fn enable() -> Result<()> {
+    if kill_switch::enable()? {
+        println!("RuleZ enabled");
+    } else {
+        println!("RuleZ was not disabled with 'rulez disable --all'");
+    }
+    if std::env::var(kill_switch::ENV_VAR).is_ok_and(|value| kill_switch::is_set(&value)) {
+        println!(
+            "Note: {} is still set in this shell and keeps RuleZ disabled there.",
+            kill_switch::ENV_VAR
+        );
+    }
+    Ok(())
+}
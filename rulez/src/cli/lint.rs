@@ -2,7 +2,13 @@ use anyhow::{Context, Result};
 use std::collections::{HashMap, HashSet};
 
 use crate::config::Config;
-use crate::models::Rule;
+use crate::governance::{ReviewStatus, review_status};
+use crate::log_export::parse_time_bound;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::{LogEntry, Outcome, PolicyMode, Rule};
+
+/// Log window for `--with-logs` when `--since` is not given
+pub const DEFAULT_LOG_WINDOW: &str = "30d";
 
 /// Diagnostic severity level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,8 +44,24 @@ impl std::fmt::Display for Diagnostic {
     }
 }
 
+/// Options for checking rules against the audit log (`--with-logs`)
+#[derive(Debug)]
+pub struct LogUsage {
+    /// Start of the window (RFC3339 or an age like `30d`)
+    pub since: String,
+    /// Log file to read (defaults to ~/.claude/logs/rulez.log)
+    pub path: Option<std::path::PathBuf>,
+}
+
 /// Run the lint command
-pub async fn run(config_path: Option<String>, verbose: bool) -> Result<()> {
+///
+/// With `with_logs`, rules are also checked against the audit log for the
+/// given window.
+pub async fn run(
+    config_path: Option<String>,
+    verbose: bool,
+    with_logs: Option<LogUsage>,
+) -> Result<()> {
     let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
 
     println!("rulez lint — Rule Quality Analysis");
@@ -63,6 +85,29 @@ pub async fn run(config_path: Option<String>, verbose: bool) -> Result<()> {
     check_invalid_regex(&config.rules, &mut diagnostics);
     check_glob_consolidation(&config.rules, &mut diagnostics, verbose);
     check_missing_priority(&config.rules, &mut diagnostics);
+    check_review_age(&config, crate::governance::today(), &mut diagnostics);
+    check_waivers(&config, crate::governance::today(), &mut diagnostics);
+    check_deprecations(&config.rules, crate::governance::today(), &mut diagnostics);
+
+    if let Some(usage) = with_logs {
+        let filters = QueryFilters {
+            since: Some(parse_time_bound(&usage.since, chrono::Utc::now())?),
+            ..Default::default()
+        };
+        let query = usage.path.map_or_else(LogQuery::new, LogQuery::with_path);
+        let entries = query
+            .query(filters)
+            .context("Failed to read the audit log")?;
+        // Ages read as "in the last 30d", timestamps as "since <time>"
+        let window = if chrono::DateTime::parse_from_rfc3339(&usage.since).is_ok() {
+            format!("since {}", usage.since)
+        } else {
+            format!("in the last {}", usage.since)
+        };
+        println!("Checked {} log entries {}", entries.len(), window);
+        println!();
+        check_log_usage(&config.rules, &entries, &window, &mut diagnostics);
+    }
 
     // Print diagnostics
     for diag in &diagnostics {
@@ -136,7 +181,15 @@ fn check_empty_matchers(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>) {
             || m.command_match.is_some()
             || m.prompt_match.is_some()
             || m.require_fields.is_some()
-            || m.field_types.is_some();
+            || m.field_types.is_some()
+            || m.response_match.is_some()
+            || m.tool_failed.is_some()
+            || m.message_match.is_some()
+            || m.compact_trigger.is_some()
+            || m.transcript_match.is_some()
+            || m.pre_rules.is_some()
+            || m.users.is_some()
+            || m.teams.is_some();
 
         if !has_matchers {
             diagnostics.push(Diagnostic {
@@ -154,12 +207,16 @@ fn check_empty_matchers(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>) {
 /// Check for conflicting actions (block + inject)
 fn check_conflicting_actions(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>) {
     for rule in rules {
-        let blocks = rule.actions.block == Some(true);
-        let injects = rule.actions.inject.is_some()
-            || rule.actions.inject_inline.is_some()
-            || rule.actions.inject_command.is_some();
-
-        if blocks && injects {
+        // Steps of an actions list run in turn, so only a single step conflicts
+        let conflicting = rule.actions.steps().any(|actions| {
+            let blocks = actions.block == Some(true);
+            let injects = actions.inject.is_some()
+                || actions.inject_inline.is_some()
+                || actions.inject_command.is_some();
+            blocks && injects
+        });
+
+        if conflicting {
             diagnostics.push(Diagnostic {
                 severity: Severity::Error,
                 code: "conflicting-actions".to_string(),
@@ -241,6 +298,64 @@ fn check_dead_rules(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>) {
     }
 }
 
+/// Check rules against the audit log: flag enabled rules that never matched,
+/// and rules whose every match was blocked by a higher-priority rule
+fn check_log_usage(
+    rules: &[Rule],
+    entries: &[LogEntry],
+    window: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if entries.is_empty() {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Info,
+            code: "no-log-data".to_string(),
+            message: format!("No log entries {window} — skipping log checks"),
+        });
+        return;
+    }
+
+    // Per rule: (events matched, events where another rule had already blocked)
+    let mut usage: HashMap<&str, (usize, usize)> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.anomaly.is_none()) {
+        let blocked = entry.outcome == Outcome::Block;
+        for (position, name) in entry.rules_matched.iter().enumerate() {
+            let counts = usage.entry(name.as_str()).or_default();
+            counts.0 += 1;
+            if blocked && position > 0 {
+                counts.1 += 1;
+            }
+        }
+    }
+
+    for rule in rules.iter().filter(|r| r.is_enabled()) {
+        match usage.get(rule.name.as_str()) {
+            None => diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "unused-rule".to_string(),
+                message: format!(
+                    "Rule '{}' never matched {} — consider removing it",
+                    rule.name, window
+                ),
+            }),
+            Some(&(matched, superseded)) if matched == superseded => {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "superseded-rule".to_string(),
+                    message: format!(
+                        "Rule '{}' matched {} time{} {}, but a higher-priority rule always blocked first",
+                        rule.name,
+                        matched,
+                        if matched == 1 { "" } else { "s" },
+                        window
+                    ),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+}
+
 /// Check for rules missing descriptions
 fn check_missing_descriptions(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>) {
     for rule in rules {
@@ -316,6 +431,102 @@ fn check_glob_consolidation(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>, v
     }
 }
 
+/// Check `governance.last_reviewed` against `settings.governance.review_max_age_days`
+fn check_review_age(config: &Config, today: chrono::NaiveDate, diagnostics: &mut Vec<Diagnostic>) {
+    let policy = config.settings.governance.clone().unwrap_or_default();
+    for rule in &config.rules {
+        match review_status(rule, policy.review_max_age_days, today) {
+            ReviewStatus::Overdue { age_days } => {
+                // `from_file` has already switched these rules to warn
+                let downgraded =
+                    policy.downgrade_overdue && rule.effective_mode() == PolicyMode::Warn;
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "review-overdue".to_string(),
+                    message: format!(
+                        "Rule '{}' was last reviewed {} days ago (limit {}){}",
+                        rule.name,
+                        age_days,
+                        policy.review_max_age_days,
+                        if downgraded {
+                            "; running in warn mode"
+                        } else {
+                            ""
+                        }
+                    ),
+                });
+            }
+            ReviewStatus::InvalidDate(value) => diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "invalid-review-date".to_string(),
+                message: format!(
+                    "Rule '{}' has governance.last_reviewed '{}', expected a date like 2026-01-31",
+                    rule.name, value
+                ),
+            }),
+            ReviewStatus::NeverReviewed | ReviewStatus::Current { .. } => {}
+        }
+    }
+}
+
+/// Check waivers for expiry and for rules that no longer exist
+fn check_waivers(config: &Config, today: chrono::NaiveDate, diagnostics: &mut Vec<Diagnostic>) {
+    for waiver in crate::waivers::expired(config, today) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            code: "expired-waiver".to_string(),
+            message: format!(
+                "Waiver for rule '{}' (approved by {}) expired on {}; remove it or renew it",
+                waiver.rule, waiver.approver, waiver.expires
+            ),
+        });
+    }
+    for waiver in &config.waivers {
+        if !config.rules.iter().any(|rule| rule.name == waiver.rule) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "unknown-waiver-rule".to_string(),
+                message: format!("Waiver names rule '{}', which does not exist", waiver.rule),
+            });
+        }
+    }
+}
+
+/// Check deprecated rules, their removal dates and replacements
+fn check_deprecations(rules: &[Rule], today: chrono::NaiveDate, diagnostics: &mut Vec<Diagnostic>) {
+    for rule in rules {
+        let Some(notice) = rule.deprecation_notice() else {
+            continue;
+        };
+        let overdue = rule
+            .remove_after
+            .as_deref()
+            .and_then(crate::governance::parse_review_date)
+            .is_some_and(|date| today > date);
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            code: "deprecated-rule".to_string(),
+            message: if overdue {
+                format!("{}; past its removal date, run `rulez migrate`", notice)
+            } else {
+                notice
+            },
+        });
+        if let Some(ref replacement) = rule.replacement {
+            if !rules.iter().any(|r| &r.name == replacement) {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "unknown-replacement".to_string(),
+                    message: format!(
+                        "Rule '{}' names replacement '{}', which does not exist",
+                        rule.name, replacement
+                    ),
+                });
+            }
+        }
+    }
+}
+
 /// Check for rules without explicit priority
 fn check_missing_priority(rules: &[Rule], diagnostics: &mut Vec<Diagnostic>) {
     for rule in rules {
@@ -1,8 +1,13 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use std::path::PathBuf;
 
-use crate::logging::{LogQuery, QueryFilters};
-use crate::models::{Decision, Outcome, PolicyMode};
+use crate::config::Config;
+use crate::log_chain::verify_log_file;
+use crate::log_export::{parse_time_bound, write_csv, write_parquet};
+use crate::logging::{LogQuery, QueryFilters, log_path, purge_log_file, upgrade_log_file};
+use crate::models::{Decision, LOG_SCHEMA_VERSION, Outcome, PolicyMode};
 
 /// Query and display logs with optional filtering
 ///
@@ -56,7 +61,7 @@ pub async fn run(
             Ok(d) => filters.decision = Some(d),
             Err(_) => {
                 println!(
-                    "Warning: Invalid decision '{}'. Valid values: allowed, blocked, warned, audited",
+                    "Warning: Invalid decision '{}'. Valid values: allowed, blocked, warned, audited, bypassed",
                     decision_str
                 );
             }
@@ -106,3 +111,204 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Rewrite older log entries to the current schema version
+///
+/// Defaults to the main log file (`~/.claude/logs/rulez.log`).
+pub fn upgrade(path: Option<PathBuf>) -> Result<()> {
+    let path = path.unwrap_or_else(log_path);
+    let summary = upgrade_log_file(&path)?;
+
+    println!(
+        "{}: {} upgraded, {} already at schema version {}, {} unreadable",
+        path.display(),
+        summary.upgraded,
+        summary.current,
+        LOG_SCHEMA_VERSION,
+        summary.unreadable
+    );
+    Ok(())
+}
+
+/// File format for `rulez logs export`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// Export log entries as flat CSV or Parquet rows, oldest first
+///
+/// # Arguments
+/// * `since` / `until` - RFC3339 timestamps or ages like `30d`
+/// * `output` - Destination file (`-` for stdout); defaults to
+///   `rulez-logs.<format>` in the current directory
+/// * `path` - Log file to read (defaults to ~/.claude/logs/rulez.log)
+pub fn export(
+    format: ExportFormat,
+    since: Option<String>,
+    until: Option<String>,
+    output: Option<PathBuf>,
+    path: Option<PathBuf>,
+) -> Result<()> {
+    let now = Utc::now();
+    let filters = QueryFilters {
+        since: since
+            .as_deref()
+            .map(|s| parse_time_bound(s, now))
+            .transpose()?,
+        until: until
+            .as_deref()
+            .map(|s| parse_time_bound(s, now))
+            .transpose()?,
+        ..Default::default()
+    };
+    let query = path.map_or_else(LogQuery::new, LogQuery::with_path);
+    let mut entries = query.query(filters)?;
+    entries.reverse();
+
+    let output =
+        output.unwrap_or_else(|| PathBuf::from(format!("rulez-logs.{}", format.extension())));
+    if output.as_os_str() == "-" {
+        let stdout = std::io::stdout().lock();
+        return match format {
+            ExportFormat::Csv => write_csv(&entries, stdout),
+            ExportFormat::Parquet => write_parquet(&entries, stdout),
+        };
+    }
+
+    let file = std::fs::File::create(&output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    let writer = std::io::BufWriter::new(file);
+    match format {
+        ExportFormat::Csv => write_csv(&entries, writer)?,
+        ExportFormat::Parquet => write_parquet(&entries, writer)?,
+    }
+    println!(
+        "Exported {} log entries to {}",
+        entries.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Remove log entries older than a cutoff from the start of the log
+///
+/// # Arguments
+/// * `before` - Cutoff (RFC3339, a date, or an age like `90d`); defaults to
+///   `logging.retention_days` ago
+/// * `dry_run` - Report what would be removed without changing the file
+/// * `path` - Log file to purge (defaults to ~/.claude/logs/rulez.log)
+pub fn purge(before: Option<String>, dry_run: bool, path: Option<PathBuf>) -> Result<()> {
+    let now = Utc::now();
+    let before = if let Some(before) = before {
+        parse_time_bound(&before, now)?
+    } else {
+        let days = Config::load(None)?
+            .settings
+            .logging
+            .retention_days
+            .context("Pass --before, or set settings.logging.retention_days")?;
+        now - chrono::Duration::days(i64::from(days))
+    };
+    let path = path.unwrap_or_else(log_path);
+    // Only needed to carry a signed checkpoint over
+    let key = if dry_run { None } else { signing_key(None)? };
+    let summary = purge_log_file(&path, before, dry_run, key.as_ref().map(String::as_bytes))?;
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    #[allow(clippy::cast_precision_loss)]
+    let kib = summary.bytes_removed as f64 / 1024.0;
+    println!(
+        "{}: {} {} entries ({:.1} KiB) older than {}; {} kept",
+        path.display(),
+        verb,
+        summary.removed,
+        kib,
+        before.format("%Y-%m-%d %H:%M UTC"),
+        summary.kept
+    );
+    Ok(())
+}
+
+/// Verify the hash chain (and signatures) of a log file
+///
+/// The signing key is read from `key_env`, or from the variable named by
+/// `logging.integrity.signing_key_env`. Exits with status 1 when the chain
+/// is broken.
+pub fn verify(path: Option<PathBuf>, key_env: Option<String>) -> Result<()> {
+    let path = path.unwrap_or_else(log_path);
+    let key = signing_key(key_env)?;
+
+    let report = verify_log_file(&path, key.as_ref().map(String::as_bytes))?;
+    for problem in &report.problems {
+        println!("  {}", problem);
+    }
+    if report.chained == 0 {
+        println!(
+            "{}: no chained entries ({} entries). Enable logging.integrity.hash_chain.",
+            path.display(),
+            report.entries
+        );
+    } else if key.is_some() {
+        println!(
+            "{}: {} entries, {} chained, {} with valid signatures",
+            path.display(),
+            report.entries,
+            report.chained,
+            report.signed
+        );
+    } else {
+        println!(
+            "{}: {} entries, {} chained (signatures not checked)",
+            path.display(),
+            report.entries,
+            report.chained
+        );
+    }
+
+    if !report.is_intact() {
+        println!("Log chain is BROKEN: {} problem(s)", report.problems.len());
+        std::process::exit(1);
+    }
+    if report.chained > 0 {
+        println!("Log chain is intact");
+    }
+    Ok(())
+}
+
+/// Read the signing key from `key_env`, or from the variable named by
+/// `logging.integrity.signing_key_env`
+///
+/// `None` when no variable is configured; an error when the named variable
+/// is unset or empty.
+pub(crate) fn signing_key(key_env: Option<String>) -> Result<Option<String>> {
+    let key_env = match key_env {
+        Some(var) => Some(var),
+        None => {
+            crate::config::Config::load(None)?
+                .settings
+                .logging
+                .integrity
+                .signing_key_env
+        }
+    };
+    key_env
+        .map(|var| {
+            std::env::var(&var)
+                .ok()
+                .filter(|key| !key.is_empty())
+                .with_context(|| format!("Signing key variable {} is not set", var))
+        })
+        .transpose()
+}
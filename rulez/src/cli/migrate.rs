@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use std::fs;
+
+use crate::config::Config;
+use crate::config_edit::ConfigEditor;
+use crate::models::Rule;
+
+/// Deprecated rules whose `remove_after` date has passed
+pub fn expired_rules(rules: &[Rule], today: chrono::NaiveDate) -> Vec<&Rule> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_deprecated())
+        .filter(|rule| {
+            rule.remove_after
+                .as_deref()
+                .and_then(crate::governance::parse_review_date)
+                .is_some_and(|date| today > date)
+        })
+        .collect()
+}
+
+/// Remove deprecated rules past their removal date (`rulez migrate`)
+///
+/// Rules are cut out of the YAML text, so comments and formatting elsewhere
+/// in the file are kept.
+pub fn run(config_path: Option<String>, dry_run: bool) -> Result<()> {
+    let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    let content = fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read {}", config_path))?;
+    // Only rules written in this file can be removed from it (not pack rules)
+    let config: Config = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path))?;
+
+    let expired = expired_rules(&config.rules, crate::governance::today());
+    if expired.is_empty() {
+        println!("No deprecated rules are past their removal date");
+        return Ok(());
+    }
+
+    let mut updated = content.clone();
+    for rule in &expired {
+        updated = remove_rule(&updated, &rule.name).with_context(|| {
+            format!(
+                "Could not find rule '{}' in a block-style rules list",
+                rule.name
+            )
+        })?;
+        println!(
+            "{} '{}' (removal after {}{})",
+            if dry_run { "Would remove" } else { "Removed" },
+            rule.name,
+            rule.remove_after.as_deref().unwrap_or("-"),
+            rule.replacement
+                .as_deref()
+                .map(|r| format!(", replaced by '{}'", r))
+                .unwrap_or_default()
+        );
+    }
+
+    // The result must still be a valid config before it replaces the file
+    let migrated: Config =
+        serde_yaml::from_str(&updated).context("Migrated config does not parse")?;
+    migrated
+        .validate()
+        .context("Migrated config does not validate")?;
+
+    if dry_run {
+        println!("Dry run: {} left unchanged", config_path);
+    } else {
+        fs::write(&config_path, updated)
+            .with_context(|| format!("Failed to write {}", config_path))?;
+        println!(
+            "Updated {} ({} rule{} removed)",
+            config_path,
+            expired.len(),
+            if expired.len() == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+/// Cut one rule's list item (and the comments just above it) out of the YAML text
+fn remove_rule(content: &str, name: &str) -> Option<String> {
+    let mut editor = ConfigEditor::new(content);
+    editor.delete_rule(name).ok()?;
+    Some(editor.into_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"version: "1.0"
+
+# Team rules
+rules:
+  # Old push guard
+  - name: old-guard
+    deprecated: true
+    replacement: new-guard
+    remove_after: "2026-01-31"
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+
+  - name: new-guard   # keeps this comment
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+
+settings:
+  log_level: info
+"#;
+
+    #[test]
+    fn test_remove_rule_keeps_the_rest() {
+        let updated = remove_rule(YAML, "old-guard").unwrap();
+        assert_eq!(
+            updated,
+            r#"version: "1.0"
+
+# Team rules
+rules:
+  - name: new-guard   # keeps this comment
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+
+settings:
+  log_level: info
+"#
+        );
+        let config: Config = serde_yaml::from_str(&updated).unwrap();
+        assert_eq!(config.rules.len(), 1);
+
+        let last = remove_rule(YAML, "new-guard").unwrap();
+        assert!(last.contains("old-guard") && !last.contains("- name: new-guard"));
+        assert!(last.contains("\n\nsettings:"));
+        assert!(remove_rule(YAML, "missing").is_none());
+    }
+
+    #[test]
+    fn test_expired_rules() {
+        let config: Config = serde_yaml::from_str(YAML).unwrap();
+        let on = |date: &str| {
+            expired_rules(
+                &config.rules,
+                crate::governance::parse_review_date(date).unwrap(),
+            )
+            .len()
+        };
+        assert_eq!(on("2026-01-31"), 0);
+        assert_eq!(on("2026-02-01"), 1);
+    }
+}
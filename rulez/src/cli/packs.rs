@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+
+use crate::config::Config;
+use crate::config_edit::ConfigEditor;
+
+/// A built-in pack and whether the config enables it
+#[derive(Debug, Serialize)]
+pub struct PackInfo {
+    pub name: String,
+    pub description: String,
+    pub rules: Vec<String>,
+    pub installed: bool,
+}
+
+/// Every built-in pack, marked installed if `enabled` lists it
+pub fn available(enabled: &[String]) -> Result<Vec<PackInfo>> {
+    crate::packs::pack_names()
+        .map(|name| {
+            Ok(PackInfo {
+                name: name.to_string(),
+                description: crate::packs::pack_description(name)
+                    .unwrap_or_default()
+                    .to_string(),
+                rules: crate::packs::load_pack(name)?
+                    .into_iter()
+                    .map(|rule| rule.name)
+                    .collect(),
+                installed: enabled.iter().any(|pack| pack == name),
+            })
+        })
+        .collect()
+}
+
+/// List the built-in packs (`rulez packs list`)
+pub fn list(config_path: Option<String>, json: bool) -> Result<()> {
+    let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    // A missing config just means nothing is installed yet
+    let enabled = match fs::read_to_string(&config_path) {
+        Ok(content) => {
+            let config: Config = serde_yaml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", config_path))?;
+            config.settings.builtin_packs
+        }
+        Err(_) => Vec::new(),
+    };
+    let packs = available(&enabled)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&packs)?);
+        return Ok(());
+    }
+
+    let width = packs.iter().map(|p| p.name.len()).max().unwrap_or(4).max(4);
+    for pack in &packs {
+        println!(
+            "{} {:<width$}  {} ({} rule{})",
+            if pack.installed { "*" } else { " " },
+            pack.name,
+            pack.description,
+            pack.rules.len(),
+            if pack.rules.len() == 1 { "" } else { "s" },
+            width = width
+        );
+    }
+    println!();
+    println!("* installed in {}", config_path);
+    Ok(())
+}
+
+/// Enable a built-in pack in the config (`rulez packs install`)
+pub fn install(config_path: Option<String>, name: &str) -> Result<()> {
+    crate::packs::load_pack(name)?;
+    update(config_path, name, |packs| {
+        if packs.iter().any(|pack| pack == name) {
+            return false;
+        }
+        packs.push(name.to_string());
+        true
+    })
+}
+
+/// Disable a built-in pack in the config (`rulez packs remove`)
+pub fn remove(config_path: Option<String>, name: &str) -> Result<()> {
+    update(config_path, name, |packs| {
+        let before = packs.len();
+        packs.retain(|pack| pack != name);
+        packs.len() != before
+    })
+}
+
+/// Apply `change` to `settings.builtin_packs` and rewrite the config if it changed
+fn update(
+    config_path: Option<String>,
+    name: &str,
+    change: impl FnOnce(&mut Vec<String>) -> bool,
+) -> Result<()> {
+    let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    let content = fs::read_to_string(&config_path).with_context(|| {
+        format!(
+            "Failed to read {} (run `rulez init` to create it)",
+            config_path
+        )
+    })?;
+    let config: Config = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path))?;
+
+    let mut packs = config.settings.builtin_packs.clone();
+    if !change(&mut packs) {
+        let state = if packs.iter().any(|pack| pack == name) {
+            "already installed"
+        } else {
+            "not installed"
+        };
+        println!("Pack '{}' is {} in {}", name, state, config_path);
+        return Ok(());
+    }
+
+    let updated = set_builtin_packs(&content, &packs)
+        .with_context(|| format!("Could not update settings.builtin_packs in {}", config_path))?;
+    let parsed: Config = serde_yaml::from_str(&updated).context("Updated config does not parse")?;
+    parsed
+        .validate()
+        .context("Updated config does not validate")?;
+
+    fs::write(&config_path, updated).with_context(|| format!("Failed to write {}", config_path))?;
+    let action = if packs.iter().any(|pack| pack == name) {
+        "Installed"
+    } else {
+        "Removed"
+    };
+    println!("{} pack '{}' in {}", action, name, config_path);
+    Ok(())
+}
+
+/// Rewrite the `builtin_packs` key of the top-level `settings` block
+///
+/// Only that key is touched, so comments and formatting elsewhere are kept.
+/// An empty list removes the key.
+fn set_builtin_packs(content: &str, packs: &[String]) -> Result<String> {
+    let mut editor = ConfigEditor::new(content);
+    editor.set_builtin_packs(packs)?;
+    Ok(editor.into_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"version: "1.0"
+
+# Team settings
+settings:
+  log_level: info   # keep
+  builtin_packs:
+    - shell-safety
+  fail_open: true
+
+rules: []
+"#;
+
+    fn packs(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_set_builtin_packs_replaces_block_list() {
+        let updated = set_builtin_packs(YAML, &packs(&["shell-safety", "extra"])).unwrap();
+        assert_eq!(
+            updated,
+            YAML.replace(
+                "  builtin_packs:\n    - shell-safety\n",
+                "  builtin_packs: [shell-safety, extra]\n"
+            )
+        );
+
+        let removed = set_builtin_packs(YAML, &[]).unwrap();
+        assert!(!removed.contains("builtin_packs"));
+        assert!(removed.contains("  log_level: info   # keep\n  fail_open: true\n"));
+    }
+
+    #[test]
+    fn test_set_builtin_packs_adds_key_or_section() {
+        let without = YAML.replace("  builtin_packs:\n    - shell-safety\n", "");
+        let added = set_builtin_packs(&without, &packs(&["shell-safety"])).unwrap();
+        assert!(added.contains("settings:\n  builtin_packs: [shell-safety]\n  log_level"));
+
+        let bare =
+            set_builtin_packs("version: \"1.0\"\nrules: []", &packs(&["shell-safety"])).unwrap();
+        let config: Config = serde_yaml::from_str(&bare).unwrap();
+        assert_eq!(config.settings.builtin_packs, packs(&["shell-safety"]));
+    }
+
+    #[test]
+    fn test_available_marks_installed() {
+        let packs = available(&packs(&["shell-safety"])).unwrap();
+        let shell = packs.iter().find(|p| p.name == "shell-safety").unwrap();
+        assert!(shell.installed);
+        assert!(!shell.description.is_empty());
+        assert!(
+            shell
+                .rules
+                .contains(&"shell-safety-force-push-main".to_string())
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! `rulez report pr`: policy events for a pull request.
+//!
+//! Collects the blocked and warned events from the sessions that produced a
+//! change, maps them to the files they touched, and renders them as a PR
+//! comment, GitHub Actions annotations, or JSON for other CI systems.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::log_export::{detail_columns, parse_time_bound};
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::{Decision, LogEntry, Outcome};
+
+/// Marks the comment so later runs can find it
+const COMMENT_MARKER: &str = "<!-- rulez-report-pr -->";
+
+/// Output format for `rulez report pr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// A PR comment body
+    Markdown,
+    /// Summary, events and check-run annotations as JSON
+    Json,
+    /// GitHub Actions workflow commands (`::error file=...::`)
+    Github,
+}
+
+/// How a policy event was resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Blocked,
+    Warned,
+}
+
+/// A blocked or warned event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyEvent {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    pub action: PolicyAction,
+    pub rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// File the event touched, relative to the repository root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl PolicyEvent {
+    fn title(&self) -> String {
+        let verb = match self.action {
+            PolicyAction::Blocked => "blocked",
+            PolicyAction::Warned => "warned",
+        };
+        format!(
+            "RuleZ {} {}",
+            self.rule.as_deref().unwrap_or("(no rule)"),
+            verb
+        )
+    }
+
+    fn message(&self) -> String {
+        let mut message = self
+            .reason
+            .clone()
+            .unwrap_or_else(|| "Blocked by policy".to_string());
+        if let Some(ref command) = self.command {
+            let _ = write!(message, " (command: {})", command);
+        }
+        let _ = write!(message, " [session {}]", self.session_id);
+        message
+    }
+}
+
+/// A GitHub check-run annotation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Annotation {
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// `failure` for blocks, `warning` for warnings
+    pub annotation_level: &'static str,
+    pub title: String,
+    pub message: String,
+}
+
+/// Policy events for a PR, with file annotations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrReport {
+    pub blocked: usize,
+    pub warned: usize,
+    pub sessions: usize,
+    pub events: Vec<PolicyEvent>,
+    pub annotations: Vec<Annotation>,
+}
+
+impl PrReport {
+    /// Collect blocked and warned events, oldest first
+    ///
+    /// Absolute paths under `root` become relative; files outside it keep
+    /// their path but get no annotation.
+    pub fn build(entries: &[LogEntry], root: &Path) -> Self {
+        let mut events: Vec<PolicyEvent> = entries
+            .iter()
+            .filter_map(|entry| policy_event(entry, root))
+            .collect();
+        events.sort_by_key(|e| e.timestamp);
+
+        let annotations = events
+            .iter()
+            .filter_map(|event| {
+                let path = event
+                    .file
+                    .as_ref()
+                    .filter(|f| !Path::new(f).is_absolute())?;
+                Some(Annotation {
+                    path: path.clone(),
+                    start_line: 1,
+                    end_line: 1,
+                    annotation_level: match event.action {
+                        PolicyAction::Blocked => "failure",
+                        PolicyAction::Warned => "warning",
+                    },
+                    title: event.title(),
+                    message: event.message(),
+                })
+            })
+            .collect();
+
+        let count = |action| events.iter().filter(|e| e.action == action).count();
+        Self {
+            blocked: count(PolicyAction::Blocked),
+            warned: count(PolicyAction::Warned),
+            sessions: events
+                .iter()
+                .map(|e| e.session_id.as_str())
+                .collect::<HashSet<_>>()
+                .len(),
+            annotations,
+            events,
+        }
+    }
+
+    /// PR comment body
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("{}\n## RuleZ policy events\n\n", COMMENT_MARKER);
+        if self.events.is_empty() {
+            out.push_str("No blocked or warned agent actions. ✅\n");
+            return out;
+        }
+        let _ = writeln!(
+            out,
+            "The agent sessions behind this change had **{}** blocked and **{}** warned action(s) across **{}** session(s).\n",
+            self.blocked, self.warned, self.sessions
+        );
+
+        let mut by_file: BTreeMap<&str, Vec<&PolicyEvent>> = BTreeMap::new();
+        let mut other = Vec::new();
+        for event in &self.events {
+            match event.file {
+                Some(ref file) => by_file.entry(file).or_default().push(event),
+                None => other.push(event),
+            }
+        }
+        if !by_file.is_empty() {
+            out.push_str("### By file\n\n| File | Action | Rule | Reason |\n|---|---|---|---|\n");
+            for (file, events) in &by_file {
+                for event in events {
+                    let _ = writeln!(out, "| `{}` | {} |", file, row(event));
+                }
+            }
+            out.push('\n');
+        }
+        if !other.is_empty() {
+            out.push_str(
+                "### Commands and other actions\n\n| Attempt | Action | Rule | Reason |\n|---|---|---|---|\n",
+            );
+            for event in other {
+                let attempt = event
+                    .command
+                    .as_deref()
+                    .or(event.tool_name.as_deref())
+                    .unwrap_or("-");
+                let _ = writeln!(out, "| `{}` | {} |", cell(attempt), row(event));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// GitHub Actions workflow commands, one per event
+    pub fn to_workflow_commands(&self) -> String {
+        let mut out = String::new();
+        for event in &self.events {
+            let level = match event.action {
+                PolicyAction::Blocked => "error",
+                PolicyAction::Warned => "warning",
+            };
+            let mut properties = Vec::new();
+            if let Some(file) = event.file.as_ref().filter(|f| !Path::new(f).is_absolute()) {
+                properties.push(format!("file={}", escape_property(file)));
+            }
+            properties.push(format!("title={}", escape_property(&event.title())));
+            let _ = writeln!(
+                out,
+                "::{} {}::{}",
+                level,
+                properties.join(","),
+                escape_data(&event.message())
+            );
+        }
+        out
+    }
+}
+
+fn row(event: &PolicyEvent) -> String {
+    let action = match event.action {
+        PolicyAction::Blocked => "⛔ blocked",
+        PolicyAction::Warned => "⚠️ warned",
+    };
+    format!(
+        "{} | `{}` | {}",
+        action,
+        event.rule.as_deref().unwrap_or("-"),
+        cell(event.reason.as_deref().unwrap_or("-"))
+    )
+}
+
+fn cell(value: &str) -> String {
+    value.replace('\n', " ").replace('|', "\\|")
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn policy_event(entry: &LogEntry, root: &Path) -> Option<PolicyEvent> {
+    if entry.anomaly.is_some() {
+        return None;
+    }
+    let action = match (entry.decision, &entry.outcome) {
+        (Some(Decision::Warned), _) => PolicyAction::Warned,
+        (Some(Decision::Blocked), _) | (None, Outcome::Block) => PolicyAction::Blocked,
+        _ => return None,
+    };
+    let (command, file, _) = entry
+        .event_details
+        .as_ref()
+        .map(detail_columns)
+        .unwrap_or_default();
+    Some(PolicyEvent {
+        timestamp: entry.timestamp,
+        session_id: entry.session_id.clone(),
+        action,
+        rule: entry.rules_matched.first().cloned(),
+        tool_name: entry.tool_name.clone(),
+        file: file.map(|f| relative_to(&f, root)),
+        command,
+        reason: entry.response.as_ref().and_then(|r| r.reason.clone()),
+    })
+}
+
+/// `path` relative to `root` when it lies inside it
+fn relative_to(path: &str, root: &Path) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .ok()
+        .and_then(Path::to_str)
+        .map_or_else(|| path.to_string(), String::from)
+}
+
+/// Report blocked and warned events for a pull request
+///
+/// # Arguments
+/// * `sessions` - Only these sessions (all sessions if empty)
+/// * `since` / `until` - Time window (RFC3339, or an age like `7d`)
+/// * `format` - Markdown comment, JSON, or GitHub workflow commands
+/// * `root` - Repository root for relative paths (defaults to the current directory)
+/// * `comment` - Post the markdown as a comment on this PR number
+/// * `fail_on_block` - Exit with status 1 if anything was blocked
+/// * `output` - Write the report to a file instead of stdout
+/// * `path` - Log file to read (defaults to ~/.claude/logs/rulez.log)
+#[allow(clippy::too_many_arguments)]
+pub fn pr(
+    sessions: Vec<String>,
+    since: Option<String>,
+    until: Option<String>,
+    format: ReportFormat,
+    root: Option<PathBuf>,
+    comment: Option<u64>,
+    fail_on_block: bool,
+    output: Option<PathBuf>,
+    path: Option<PathBuf>,
+) -> Result<()> {
+    let now = Utc::now();
+    let filters = QueryFilters {
+        since: since
+            .as_deref()
+            .map(|s| parse_time_bound(s, now))
+            .transpose()?,
+        until: until
+            .as_deref()
+            .map(|s| parse_time_bound(s, now))
+            .transpose()?,
+        ..Default::default()
+    };
+    let query = path.map_or_else(LogQuery::new, LogQuery::with_path);
+    let entries: Vec<LogEntry> = query
+        .query(filters)?
+        .into_iter()
+        .filter(|e| sessions.is_empty() || sessions.contains(&e.session_id))
+        .collect();
+
+    let root = match root {
+        Some(root) => root,
+        None => std::env::current_dir()?,
+    };
+    let report = PrReport::build(&entries, &root);
+
+    let rendered = match format {
+        ReportFormat::Markdown => report.to_markdown(),
+        ReportFormat::Json => format!("{}\n", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Github => report.to_workflow_commands(),
+    };
+    match output {
+        Some(ref output) => {
+            std::fs::write(output, &rendered)
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+            println!("Wrote report to {}", output.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    if let Some(number) = comment {
+        post_comment(number, &report.to_markdown())?;
+        eprintln!("Posted report to PR #{}", number);
+    }
+
+    if fail_on_block && report.blocked > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Post a PR comment through the GitHub API
+///
+/// Uses `GITHUB_TOKEN`, `GITHUB_REPOSITORY` (`owner/repo`) and
+/// `GITHUB_API_URL`, as set in GitHub Actions.
+fn post_comment(number: u64, body: &str) -> Result<()> {
+    let token = std::env::var("GITHUB_TOKEN").context("GITHUB_TOKEN is not set")?;
+    let repo = std::env::var("GITHUB_REPOSITORY")
+        .context("GITHUB_REPOSITORY is not set (expected owner/repo)")?;
+    let api = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| "https://api.github.com".into());
+    let url = format!(
+        "{}/repos/{}/issues/{}/comments",
+        api.trim_end_matches('/'),
+        repo,
+        number
+    );
+    let headers = [
+        ("Authorization".to_string(), format!("Bearer {}", token)),
+        (
+            "Accept".to_string(),
+            "application/vnd.github+json".to_string(),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    crate::logging::send_via_curl(
+        &url,
+        &serde_json::to_vec(&serde_json::json!({ "body": body }))?,
+        &headers,
+        std::time::Duration::from_secs(30),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EventDetails, LOG_SCHEMA_VERSION, ResponseSummary};
+
+    fn entry(
+        session: &str,
+        rule: &str,
+        decision: Decision,
+        details: EventDetails,
+        reason: &str,
+    ) -> LogEntry {
+        let mut entry: LogEntry = serde_json::from_value(serde_json::json!({
+            "schema_version": LOG_SCHEMA_VERSION,
+            "timestamp": "2026-10-01T12:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": session,
+            "rules_matched": [rule],
+            "outcome": if decision == Decision::Blocked { "block" } else { "inject" },
+            "timing": {"processing_ms": 1, "rules_evaluated": 1},
+        }))
+        .unwrap();
+        entry.decision = Some(decision);
+        entry.event_details = Some(details);
+        entry.response = Some(ResponseSummary {
+            continue_: decision != Decision::Blocked,
+            reason: Some(reason.to_string()),
+            context_length: None,
+            permission_decision: None,
+        });
+        entry
+    }
+
+    fn sample() -> Vec<LogEntry> {
+        vec![
+            entry(
+                "s1",
+                "no-env-writes",
+                Decision::Blocked,
+                EventDetails::Write {
+                    file_path: "/repo/config/.env".to_string(),
+                },
+                "Secrets belong in the vault",
+            ),
+            entry(
+                "s1",
+                "warn-sudo",
+                Decision::Warned,
+                EventDetails::Bash {
+                    command: "sudo make install".to_string(),
+                },
+                "Avoid sudo",
+            ),
+            entry(
+                "s2",
+                "no-lock-edits",
+                Decision::Warned,
+                EventDetails::Edit {
+                    file_path: "/elsewhere/Cargo.lock".to_string(),
+                },
+                "Lockfile edited",
+            ),
+            entry(
+                "s2",
+                "allowed",
+                Decision::Allowed,
+                EventDetails::Bash {
+                    command: "ls".to_string(),
+                },
+                "-",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_build_maps_events_to_files() {
+        let report = PrReport::build(&sample(), Path::new("/repo"));
+        assert_eq!((report.blocked, report.warned, report.sessions), (1, 2, 2));
+        assert_eq!(report.events[0].file.as_deref(), Some("config/.env"));
+        assert_eq!(
+            report.events[2].file.as_deref(),
+            Some("/elsewhere/Cargo.lock")
+        );
+
+        // Only files inside the repository are annotated
+        assert_eq!(report.annotations.len(), 1);
+        let annotation = &report.annotations[0];
+        assert_eq!(annotation.path, "config/.env");
+        assert_eq!(annotation.annotation_level, "failure");
+        assert_eq!(annotation.title, "RuleZ no-env-writes blocked");
+        assert!(
+            annotation
+                .message
+                .starts_with("Secrets belong in the vault")
+        );
+    }
+
+    #[test]
+    fn test_markdown_groups_by_file() {
+        let markdown = PrReport::build(&sample(), Path::new("/repo")).to_markdown();
+        assert!(markdown.starts_with(COMMENT_MARKER));
+        assert!(markdown.contains("**1** blocked and **2** warned action(s)"));
+        assert!(markdown.contains(
+            "| `config/.env` | ⛔ blocked | `no-env-writes` | Secrets belong in the vault |"
+        ));
+        assert!(
+            markdown.contains("| `sudo make install` | ⚠️ warned | `warn-sudo` | Avoid sudo |")
+        );
+
+        let empty = PrReport::build(&[], Path::new("/repo")).to_markdown();
+        assert!(empty.contains("No blocked or warned agent actions."));
+    }
+
+    #[test]
+    fn test_workflow_commands_escape() {
+        let commands = PrReport::build(&sample(), Path::new("/repo")).to_workflow_commands();
+        let lines: Vec<&str> = commands.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with(
+            "::error file=config/.env,title=RuleZ no-env-writes blocked::Secrets belong"
+        ));
+        assert!(lines[1].starts_with("::warning title=RuleZ warn-sudo warned::Avoid sudo"));
+        // Files outside the repository get no file property
+        assert!(lines[2].starts_with("::warning title="));
+
+        assert_eq!(escape_property("a:b,c%\n"), "a%3Ab%2Cc%25%0A");
+    }
+}
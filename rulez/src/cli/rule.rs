@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Read;
+
+use crate::config::Config;
+use crate::config_edit::{ConfigEdit, ConfigEditor};
+
+/// Enable or disable a rule (`rulez rule enable|disable`)
+pub fn set_enabled(config_path: Option<String>, name: &str, enabled: bool) -> Result<()> {
+    let path = edit(config_path, |editor| editor.set_enabled(name, enabled))?;
+    println!(
+        "{} rule '{}' in {}",
+        if enabled { "Enabled" } else { "Disabled" },
+        name,
+        path
+    );
+    Ok(())
+}
+
+/// Change a rule's priority (`rulez rule priority`)
+pub fn set_priority(config_path: Option<String>, name: &str, priority: i32) -> Result<()> {
+    let path = edit(config_path, |editor| editor.set_priority(name, priority))?;
+    println!(
+        "Set priority of rule '{}' to {} in {}",
+        name, priority, path
+    );
+    Ok(())
+}
+
+/// Delete a rule (`rulez rule remove`)
+pub fn remove(config_path: Option<String>, name: &str) -> Result<()> {
+    let path = edit(config_path, |editor| editor.delete_rule(name))?;
+    println!("Removed rule '{}' from {}", name, path);
+    Ok(())
+}
+
+/// Apply a JSON list of edits from a file or stdin (`rulez rule apply`)
+///
+/// All edits are applied or none are. With `print`, the updated config is
+/// written to stdout instead of a summary.
+pub fn apply(config_path: Option<String>, file: Option<String>, print: bool) -> Result<()> {
+    let input = if let Some(file) = file {
+        fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file))?
+    } else {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .context("Failed to read edits from stdin")?;
+        input
+    };
+    let edits: Vec<ConfigEdit> =
+        serde_json::from_str(&input).context("Edits must be a JSON array of edit objects")?;
+
+    let mut updated = String::new();
+    let path = edit(config_path, |editor| {
+        for (i, edit) in edits.iter().enumerate() {
+            editor
+                .apply(edit)
+                .with_context(|| format!("Edit {} failed", i + 1))?;
+        }
+        updated = editor.as_str().to_string();
+        Ok(())
+    })?;
+    if print {
+        print!("{}", updated);
+    } else {
+        println!(
+            "Applied {} edit{} to {}",
+            edits.len(),
+            if edits.len() == 1 { "" } else { "s" },
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Run `change` on the config text and write it back if it still validates
+fn edit(
+    config_path: Option<String>,
+    change: impl FnOnce(&mut ConfigEditor) -> Result<()>,
+) -> Result<String> {
+    let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    let content = fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read {}", config_path))?;
+
+    let mut editor = ConfigEditor::new(content);
+    change(&mut editor)?;
+
+    let config: Config =
+        serde_yaml::from_str(editor.as_str()).context("Updated config does not parse")?;
+    config
+        .validate()
+        .context("Updated config does not validate")?;
+    fs::write(&config_path, editor.into_string())
+        .with_context(|| format!("Failed to write {}", config_path))?;
+    Ok(config_path)
+}
@@ -0,0 +1,465 @@
+//! Rule hit statistics (`rulez stats`)
+//!
+//! Aggregates the audit log into per-rule counters so users can see which
+//! rules fire, how often they block, and which ones never match at all.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::log_export::parse_time_bound;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::{Decision, LogEntry, Outcome};
+
+/// Width of the heat bar in text and markdown reports
+const HEAT_WIDTH: usize = 20;
+
+/// Output format for `rulez stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum StatsFormat {
+    Text,
+    Markdown,
+    Html,
+}
+
+/// Counters for a single rule
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RuleStats {
+    pub name: String,
+    /// Estimated number of events the rule matched (weighted by `sample_rate`)
+    pub matches: u64,
+    /// Estimated number of those events the rule blocked
+    pub blocks: u64,
+    /// Median processing time of the events the rule matched
+    pub median_ms: Option<u64>,
+    pub last_fired: Option<DateTime<Utc>>,
+    /// False for rules that appear in the log but are no longer configured
+    pub configured: bool,
+}
+
+impl RuleStats {
+    fn new(name: &str, configured: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            matches: 0,
+            blocks: 0,
+            median_ms: None,
+            last_fired: None,
+            configured,
+        }
+    }
+
+    /// Fraction of matches that were blocked, as a percentage
+    #[allow(clippy::cast_precision_loss)]
+    pub fn block_rate(&self) -> f64 {
+        if self.matches == 0 {
+            0.0
+        } else {
+            self.blocks as f64 * 100.0 / self.matches as f64
+        }
+    }
+}
+
+/// Aggregate log entries into per-rule statistics
+///
+/// Every configured rule gets a row, including rules that never fired.
+/// A block counts toward the highest-priority matched rule, which is the
+/// one that decided the event. Sampled entries count as `sample_rate`
+/// events. `Anomaly` entries are not events and are skipped. Rows are
+/// sorted by matches, busiest first.
+pub fn rule_stats(entries: &[LogEntry], configured: &[String]) -> Vec<RuleStats> {
+    let mut stats: Vec<RuleStats> = configured
+        .iter()
+        .map(|name| RuleStats::new(name, true))
+        .collect();
+    let mut index: HashMap<String, usize> = configured
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), i))
+        .collect();
+    let mut latencies: Vec<Vec<u64>> = vec![Vec::new(); stats.len()];
+
+    for entry in entries.iter().filter(|e| e.anomaly.is_none()) {
+        let weight = u64::from(entry.sample_rate.unwrap_or(1).max(1));
+        let blocked = entry.outcome == Outcome::Block || entry.decision == Some(Decision::Blocked);
+
+        for (position, name) in entry.rules_matched.iter().enumerate() {
+            let i = *index.entry(name.clone()).or_insert_with(|| {
+                stats.push(RuleStats::new(name, false));
+                latencies.push(Vec::new());
+                stats.len() - 1
+            });
+            let rule = &mut stats[i];
+            rule.matches += weight;
+            if blocked && position == 0 {
+                rule.blocks += weight;
+            }
+            if rule.last_fired.is_none_or(|last| entry.timestamp > last) {
+                rule.last_fired = Some(entry.timestamp);
+            }
+            latencies[i].push(entry.timing.processing_ms);
+        }
+    }
+
+    for (rule, mut samples) in stats.iter_mut().zip(latencies) {
+        rule.median_ms = median(&mut samples);
+    }
+    stats.sort_by(|a, b| b.matches.cmp(&a.matches).then_with(|| a.name.cmp(&b.name)));
+    stats
+}
+
+fn median(samples: &mut [u64]) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let mid = samples.len() / 2;
+    if samples.len() % 2 == 0 {
+        Some(u64::midpoint(samples[mid - 1], samples[mid]))
+    } else {
+        Some(samples[mid])
+    }
+}
+
+/// Show statistics for the audit log
+///
+/// # Arguments
+/// * `per_rule` - Break the statistics down per rule
+/// * `format` - Text table, or a markdown/HTML report with a heat column
+/// * `since` / `until` - RFC3339 timestamps or ages like `30d`
+/// * `output` - Write the report to this file instead of stdout
+/// * `path` - Log file to read (defaults to ~/.claude/logs/rulez.log)
+pub fn run(
+    per_rule: bool,
+    format: StatsFormat,
+    since: Option<String>,
+    until: Option<String>,
+    output: Option<PathBuf>,
+    path: Option<PathBuf>,
+) -> Result<()> {
+    let now = Utc::now();
+    let filters = QueryFilters {
+        since: since
+            .as_deref()
+            .map(|s| parse_time_bound(s, now))
+            .transpose()?,
+        until: until
+            .as_deref()
+            .map(|s| parse_time_bound(s, now))
+            .transpose()?,
+        ..Default::default()
+    };
+    let query = path.map_or_else(LogQuery::new, LogQuery::with_path);
+    let entries = query.query(filters)?;
+
+    let report = if per_rule {
+        let config = Config::load(None)?;
+        let configured: Vec<String> = config.rules.iter().map(|r| r.name.clone()).collect();
+        let stats = rule_stats(&entries, &configured);
+        match format {
+            StatsFormat::Text => render_text(&stats),
+            StatsFormat::Markdown => render_markdown(&stats),
+            StatsFormat::Html => render_html(&stats),
+        }
+    } else {
+        render_summary(&entries)
+    };
+
+    match output {
+        Some(output) => {
+            std::fs::write(&output, report)
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+            println!("Wrote statistics to {}", output.display());
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+fn render_summary(entries: &[LogEntry]) -> String {
+    let mut totals = [0u64; 3];
+    let mut context_tokens = 0u64;
+    let mut samples = Vec::with_capacity(entries.len());
+    for entry in entries.iter().filter(|e| e.anomaly.is_none()) {
+        let weight = u64::from(entry.sample_rate.unwrap_or(1).max(1));
+        let slot = match entry.outcome {
+            Outcome::Allow => 0,
+            Outcome::Block => 1,
+            Outcome::Inject => 2,
+        };
+        totals[slot] += weight;
+        context_tokens += entry
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.context_tokens)
+            .map_or(0, |tokens| tokens as u64 * weight);
+        samples.push(entry.timing.processing_ms);
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Events:  {}", totals.iter().sum::<u64>());
+    let _ = writeln!(out, "  Allowed:  {}", totals[0]);
+    let _ = writeln!(out, "  Blocked:  {}", totals[1]);
+    let _ = writeln!(out, "  Injected: {}", totals[2]);
+    let _ = writeln!(out, "Context injected: ~{} tokens", context_tokens);
+    let _ = writeln!(out, "Median latency: {}", format_ms(median(&mut samples)));
+    let _ = writeln!(out, "Use --per-rule for a breakdown by rule.");
+    out
+}
+
+fn render_text(stats: &[RuleStats]) -> String {
+    let mut out = String::new();
+    if stats.is_empty() {
+        out.push_str("No rules configured and no rule matches logged.\n");
+        return out;
+    }
+    let max = max_matches(stats);
+    let _ = writeln!(
+        out,
+        "{:<30} {:>8} {:>7} {:>8}  {:<16}  Heat",
+        "Rule", "Matches", "Block%", "Median", "Last fired"
+    );
+    for rule in stats {
+        let _ = writeln!(
+            out,
+            "{:<30} {:>8} {:>6.1}% {:>8}  {:<16}  {}",
+            display_name(rule),
+            rule.matches,
+            rule.block_rate(),
+            format_ms(rule.median_ms),
+            format_last_fired(rule.last_fired),
+            heat_bar(rule.matches, max, '#')
+        );
+    }
+    out
+}
+
+fn render_markdown(stats: &[RuleStats]) -> String {
+    let max = max_matches(stats);
+    let mut out = String::from(
+        "# RuleZ rule statistics\n\n\
+         | Rule | Matches | Block rate | Median latency | Last fired | Heat |\n\
+         |------|--------:|-----------:|---------------:|------------|------|\n",
+    );
+    for rule in stats {
+        let _ = writeln!(
+            out,
+            "| `{}` | {} | {:.1}% | {} | {} | {} |",
+            display_name(rule),
+            rule.matches,
+            rule.block_rate(),
+            format_ms(rule.median_ms),
+            format_last_fired(rule.last_fired),
+            heat_bar(rule.matches, max, '█')
+        );
+    }
+    out
+}
+
+fn render_html(stats: &[RuleStats]) -> String {
+    let max = max_matches(stats);
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>RuleZ rule statistics</title>\n<style>\n\
+         body { font-family: sans-serif; }\n\
+         table { border-collapse: collapse; }\n\
+         th, td { padding: 4px 10px; border: 1px solid #ddd; text-align: right; }\n\
+         th:first-child, td:first-child { text-align: left; }\n\
+         </style>\n</head>\n<body>\n<h1>RuleZ rule statistics</h1>\n<table>\n\
+         <tr><th>Rule</th><th>Matches</th><th>Block rate</th>\
+         <th>Median latency</th><th>Last fired</th></tr>\n",
+    );
+    for rule in stats {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td style=\"background: rgba(220, 38, 38, {:.2})\">{}</td>\
+             <td>{:.1}%</td><td>{}</td><td>{}</td></tr>",
+            html_escape(&display_name(rule)),
+            heat(rule.matches, max),
+            rule.matches,
+            rule.block_rate(),
+            format_ms(rule.median_ms),
+            format_last_fired(rule.last_fired)
+        );
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+fn display_name(rule: &RuleStats) -> String {
+    if rule.configured {
+        rule.name.clone()
+    } else {
+        format!("{} (removed)", rule.name)
+    }
+}
+
+fn max_matches(stats: &[RuleStats]) -> u64 {
+    stats.iter().map(|r| r.matches).max().unwrap_or(0)
+}
+
+/// Share of the busiest rule's matches, from 0.0 to 1.0
+#[allow(clippy::cast_precision_loss)]
+fn heat(matches: u64, max: u64) -> f64 {
+    if max == 0 {
+        0.0
+    } else {
+        matches as f64 / max as f64
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn heat_bar(matches: u64, max: u64, fill: char) -> String {
+    let mut width = (heat(matches, max) * HEAT_WIDTH as f64).round() as usize;
+    if matches > 0 {
+        width = width.max(1);
+    }
+    std::iter::repeat_n(fill, width).collect()
+}
+
+fn format_ms(ms: Option<u64>) -> String {
+    ms.map_or_else(|| "-".to_string(), |ms| format!("{}ms", ms))
+}
+
+fn format_last_fired(last: Option<DateTime<Utc>>) -> String {
+    last.map_or_else(
+        || "never".to_string(),
+        |t| t.format("%Y-%m-%d %H:%M").to_string(),
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(time: &str, rules: &[&str], outcome: &str, ms: u64, rate: Option<u32>) -> LogEntry {
+        let mut json = serde_json::json!({
+            "schema_version": 2,
+            "timestamp": time,
+            "event_type": "PreToolUse",
+            "session_id": "s",
+            "rules_matched": rules,
+            "outcome": outcome,
+            "timing": { "processing_ms": ms, "rules_evaluated": 3 }
+        });
+        if let Some(rate) = rate {
+            json["sample_rate"] = rate.into();
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_rule_stats_counts_blocks_and_latency() {
+        let entries = vec![
+            entry("2026-03-01T00:00:00Z", &["no-force-push"], "block", 2, None),
+            entry(
+                "2026-03-02T00:00:00Z",
+                &["no-force-push", "audit-git"],
+                "allow",
+                4,
+                None,
+            ),
+            entry(
+                "2026-03-03T00:00:00Z",
+                &["no-force-push", "audit-git"],
+                "block",
+                9,
+                None,
+            ),
+            entry("2026-03-04T00:00:00Z", &["audit-git"], "allow", 1, Some(10)),
+        ];
+        let configured = vec![
+            "audit-git".to_string(),
+            "no-force-push".to_string(),
+            "idle".to_string(),
+        ];
+        let stats = rule_stats(&entries, &configured);
+
+        let names: Vec<&str> = stats.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["audit-git", "no-force-push", "idle"]);
+
+        // Sampled entry counts 10 times; blocks go to the deciding rule only
+        assert_eq!(stats[0].matches, 12);
+        assert_eq!(stats[0].blocks, 0);
+        assert_eq!(stats[0].median_ms, Some(4));
+        assert_eq!(
+            stats[0].last_fired.unwrap().to_rfc3339(),
+            "2026-03-04T00:00:00+00:00"
+        );
+
+        assert_eq!(stats[1].matches, 3);
+        assert_eq!(stats[1].blocks, 2);
+        assert_eq!(stats[1].median_ms, Some(4));
+
+        assert_eq!(stats[2].matches, 0);
+        assert_eq!(stats[2].median_ms, None);
+        assert_eq!(stats[2].last_fired, None);
+    }
+
+    #[test]
+    fn test_rule_stats_keeps_removed_rules() {
+        let entries = vec![entry(
+            "2026-03-01T00:00:00Z",
+            &["old-rule"],
+            "allow",
+            2,
+            None,
+        )];
+        let stats = rule_stats(&entries, &[]);
+        assert_eq!(stats.len(), 1);
+        assert!(!stats[0].configured);
+        assert!(render_text(&stats).contains("old-rule (removed)"));
+    }
+
+    #[test]
+    fn test_summary_adds_up_context_tokens() {
+        let mut injected = entry("2026-03-01T00:00:00Z", &["docs"], "inject", 2, Some(2));
+        injected.metadata =
+            serde_json::from_value(serde_json::json!({ "context_tokens": 30 })).unwrap();
+        let plain = entry("2026-03-01T00:00:01Z", &[], "allow", 2, None);
+        assert!(render_summary(&[injected, plain]).contains("Context injected: ~60 tokens"));
+    }
+
+    #[test]
+    fn test_median_even_count() {
+        assert_eq!(median(&mut [4, 1, 3, 2]), Some(2));
+        assert_eq!(median(&mut []), None);
+    }
+
+    #[test]
+    fn test_reports_render_heat() {
+        let entries = vec![
+            entry("2026-03-01T00:00:00Z", &["busy"], "block", 2, None),
+            entry("2026-03-01T00:00:01Z", &["busy"], "allow", 2, None),
+            entry("2026-03-01T00:00:02Z", &["quiet<x>"], "allow", 2, None),
+        ];
+        let stats = rule_stats(&entries, &["busy".to_string()]);
+
+        let markdown = render_markdown(&stats);
+        assert!(
+            markdown
+                .contains("| `busy` | 2 | 50.0% | 2ms | 2026-03-01 00:00 | ████████████████████ |")
+        );
+        assert!(markdown.contains("██████████ |"));
+
+        let html = render_html(&stats);
+        assert!(html.contains("rgba(220, 38, 38, 1.00)\">2<"));
+        assert!(html.contains("quiet&lt;x&gt; (removed)"));
+    }
+}
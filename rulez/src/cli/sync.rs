@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::log_chain::hex;
+use crate::sync::{self, SyncConfig, SyncReport};
+use ed25519_dalek::SigningKey;
+
+/// Fetch central policies into the overlay directory (`rulez sync`)
+pub fn run(
+    config_path: Option<String>,
+    source: Option<String>,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let config = match config_path {
+        Some(path) => Config::from_file(&path)?,
+        None => Config::load(None)?,
+    };
+    let settings = match (config.settings.sync, source) {
+        (Some(settings), Some(source)) => SyncConfig { source, ..settings },
+        (Some(settings), None) => settings,
+        (None, Some(source)) => SyncConfig::new(source),
+        (None, None) => {
+            anyhow::bail!("No policy source: set settings.sync.source or pass --source")
+        }
+    };
+    let key = settings.verifying_key()?;
+    let report = sync::sync(&settings, key.as_ref(), dry_run)?;
+    if !report.signed {
+        eprintln!(
+            "warning: policies from {} are unsigned; whoever can change that source controls \
+             these rules. Set settings.sync.public_key to verify them.",
+            report.source
+        );
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report, dry_run);
+    }
+    Ok(())
+}
+
+fn print_report(report: &SyncReport, dry_run: bool) {
+    println!("rulez sync — {}", report.source);
+    if report.signed {
+        println!("Signature: verified (ed25519)");
+    } else {
+        println!("Signature: NOT VERIFIED (settings.sync.allow_unsigned)");
+    }
+
+    if report.is_unchanged() {
+        println!("Policies are up to date in {}", report.dir.display());
+        return;
+    }
+    println!(
+        "Files: {} added, {} changed, {} removed",
+        report.files_added.len(),
+        report.files_changed.len(),
+        report.files_removed.len()
+    );
+    let changes = [
+        ('+', &report.rules_added),
+        ('~', &report.rules_changed),
+        ('-', &report.rules_removed),
+    ];
+    if changes.iter().any(|(_, rules)| !rules.is_empty()) {
+        println!("Rules:");
+        for (mark, rules) in changes {
+            for rule in rules {
+                println!("  {} {}", mark, rule);
+            }
+        }
+    }
+    if dry_run {
+        println!("Dry run: nothing installed");
+    } else {
+        println!("Installed to {}", report.dir.display());
+    }
+}
+
+/// Write a manifest and signature for a policy directory (`rulez sync sign`)
+///
+/// `key_env` names a variable holding the hex-encoded ed25519 private key;
+/// the matching public key is printed for `settings.sync.public_key`.
+pub fn sign(dir: &Path, output: Option<&Path>, key_env: Option<String>) -> Result<()> {
+    let key = key_env
+        .as_deref()
+        .map(|var| {
+            let hex = std::env::var(var)
+                .ok()
+                .filter(|key| !key.is_empty())
+                .with_context(|| format!("Signing key variable {} is not set", var))?;
+            sync::decode_hex(&hex)
+                .map(|seed| SigningKey::from_bytes(&seed))
+                .with_context(|| format!("{} is not a hex-encoded ed25519 private key", var))
+        })
+        .transpose()?;
+    let manifest = sync::sign(dir, key.as_ref(), output)?;
+
+    println!(
+        "{} {} file{}",
+        if key.is_some() {
+            "Signed"
+        } else {
+            "Listed (unsigned)"
+        },
+        manifest.files.len(),
+        if manifest.files.len() == 1 { "" } else { "s" }
+    );
+    if let Some(ref key) = key {
+        println!("Public key: {}", hex(key.verifying_key().as_bytes()));
+    }
+    match output {
+        Some(output) => println!("Wrote bundle {}", output.display()),
+        None => println!(
+            "Wrote {} and {} in {}",
+            sync::MANIFEST,
+            sync::SIGNATURE,
+            dir.display()
+        ),
+    }
+    Ok(())
+}
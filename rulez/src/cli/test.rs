@@ -3,7 +3,7 @@
 //! Allows running multiple event scenarios and comparing results against expected outcomes.
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fs;
 
 use crate::config::Config;
@@ -32,16 +32,33 @@ struct TestCase {
 }
 
 /// Result of running a single test case
+#[derive(Serialize)]
 struct TestResult {
+    name: String,
+    expected: String,
+    actual: String,
     passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+/// Results of a whole test file (`--json` output)
+#[derive(Serialize)]
+struct TestReport {
+    file: String,
+    passed: usize,
+    failed: usize,
+    total: usize,
+    results: Vec<TestResult>,
 }
 
 /// Run the test command
-pub async fn run(test_file: String, verbose: bool) -> Result<()> {
+pub async fn run(test_file: String, verbose: bool, json: bool) -> Result<()> {
     // Clear regex cache for state isolation
     {
-        use crate::hooks::REGEX_CACHE;
+        use crate::hooks::{REGEX_CACHE, REGEX_SET_CACHE};
         REGEX_CACHE.lock().unwrap().clear();
+        REGEX_SET_CACHE.lock().unwrap().clear();
     }
 
     // Read and parse the test file
@@ -50,7 +67,7 @@ pub async fn run(test_file: String, verbose: bool) -> Result<()> {
     let test_data: TestFile = serde_yaml::from_str(&content)
         .with_context(|| format!("Failed to parse test file: {}", test_file))?;
 
-    if test_data.tests.is_empty() {
+    if test_data.tests.is_empty() && !json {
         println!("No test cases found in {}", test_file);
         return Ok(());
     }
@@ -59,13 +76,15 @@ pub async fn run(test_file: String, verbose: bool) -> Result<()> {
     let config = Config::load(None)?;
     let debug_config = DebugConfig::new(false, config.settings.debug_logs);
 
-    println!(
-        "Running {} test(s) from {}",
-        test_data.tests.len(),
-        test_file
-    );
-    println!("{}", "=".repeat(60));
-    println!();
+    if !json {
+        println!(
+            "Running {} test(s) from {}",
+            test_data.tests.len(),
+            test_file
+        );
+        println!("{}", "=".repeat(60));
+        println!();
+    }
 
     let mut results: Vec<TestResult> = Vec::new();
 
@@ -104,7 +123,9 @@ pub async fn run(test_file: String, verbose: bool) -> Result<()> {
         let passed = actual == expected;
 
         // Print result
-        if passed {
+        if json {
+            // Reported all at once below
+        } else if passed {
             println!("  PASS  {}", test_case.name);
         } else {
             println!("  FAIL  {}", test_case.name);
@@ -116,7 +137,13 @@ pub async fn run(test_file: String, verbose: bool) -> Result<()> {
             }
         }
 
-        results.push(TestResult { passed });
+        results.push(TestResult {
+            name: test_case.name.clone(),
+            expected,
+            actual,
+            passed,
+            reason: response.reason,
+        });
     }
 
     // Print summary
@@ -124,9 +151,20 @@ pub async fn run(test_file: String, verbose: bool) -> Result<()> {
     let failed = results.iter().filter(|r| !r.passed).count();
     let total = results.len();
 
-    println!();
-    println!("{}", "=".repeat(60));
-    println!("{} passed, {} failed, {} total", passed, failed, total);
+    if json {
+        let report = TestReport {
+            file: test_file,
+            passed,
+            failed,
+            total,
+            results,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!();
+        println!("{}", "=".repeat(60));
+        println!("{} passed, {} failed, {} total", passed, failed, total);
+    }
 
     if failed > 0 {
         std::process::exit(1);
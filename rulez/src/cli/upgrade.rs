@@ -1,71 +1,168 @@
-//! `rulez upgrade` — check for and install newer binary releases from GitHub.
+//! `rulez self-update` — check for and install newer binary releases from GitHub.
+//!
+//! Downloads are verified against the release signing key (an ed25519 key,
+//! artifacts signed with zipsign) compiled in from `RULEZ_RELEASE_PUBLIC_KEY`
+//! at build time. A build without the key can still check for updates but
+//! refuses to install one. The new binary replaces the running one with an
+//! atomic rename, so an interrupted update never leaves a half-written file.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::Serialize;
 
-/// Run the upgrade command.
+/// Hex-encoded release signing key, set when release binaries are built
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("RULEZ_RELEASE_PUBLIC_KEY");
+
+/// Running version vs the latest release (`--json` output)
+#[derive(Debug, Serialize, PartialEq)]
+pub struct VersionStatus {
+    pub current: String,
+    pub latest: Option<String>,
+    pub update_available: bool,
+    /// Whether this build can verify (and so install) releases
+    pub can_verify: bool,
+}
+
+impl VersionStatus {
+    fn new(current: &str, latest: Option<&str>) -> Self {
+        let latest = latest.map(|v| v.trim_start_matches('v').to_string());
+        let update_available = latest.as_deref().is_some_and(|latest| {
+            self_update::version::bump_is_greater(current, latest).unwrap_or(false)
+        });
+        Self {
+            current: current.to_string(),
+            latest,
+            update_available,
+            can_verify: RELEASE_PUBLIC_KEY.is_some(),
+        }
+    }
+}
+
+/// Parse a hex-encoded ed25519 public key
+fn parse_public_key(hex: &str) -> Result<[u8; 32]> {
+    crate::sync::decode_hex(hex)
+}
+
+/// Run the self-update command.
 ///
-/// With `--check`: prints current and latest version, exits 0 if current, exits 1 if update available.
-/// Without `--check`: downloads and installs the latest binary if a newer version exists.
-pub async fn run(check_only: bool) -> Result<()> {
+/// With `--check`: prints current and latest version without installing.
+/// Without `--check`: downloads, verifies and installs the latest binary if a newer version exists.
+pub async fn run(check_only: bool, json: bool) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
 
-    println!("Current version: {}", current_version);
-    println!("Checking GitHub releases for latest version...");
+    if !json {
+        println!("Current version: {}", current_version);
+        println!("Checking GitHub releases for latest version...");
+    }
 
-    // Build the updater
-    // NOTE: Replace "SpillwaveSolutions" and "agent_rulez" with actual GitHub owner/repo
-    // when the public repo is configured. These values are placeholders.
     let releases = self_update::backends::github::ReleaseList::configure()
         .repo_owner("SpillwaveSolutions")
         .repo_name("agent_rulez")
         .build()?
         .fetch()?;
+    let status = VersionStatus::new(
+        current_version,
+        releases.first().map(|release| release.version.as_str()),
+    );
 
-    if releases.is_empty() {
-        println!("No releases found on GitHub. Is the repository public with releases?");
+    if json && (check_only || !status.update_available) {
+        println!("{}", serde_json::to_string_pretty(&status)?);
         return Ok(());
     }
 
-    let latest = &releases[0];
-    let latest_version = latest.version.trim_start_matches('v');
-
-    println!("Latest version: {}", latest_version);
+    let Some(ref latest_version) = status.latest else {
+        println!("No releases found on GitHub. Is the repository public with releases?");
+        return Ok(());
+    };
+    if !json {
+        println!("Latest version: {}", latest_version);
+    }
 
-    // Compare versions using self_update's built-in comparison
-    if !self_update::version::bump_is_greater(current_version, latest_version).unwrap_or(false) {
+    if !status.update_available {
         println!("Already on the latest version.");
         return Ok(());
     }
 
-    println!(
-        "Upgrade available: {} -> {}",
-        current_version, latest_version
-    );
+    if !json {
+        println!(
+            "Update available: {} -> {}",
+            current_version, latest_version
+        );
+    }
 
     if check_only {
-        println!("Run 'rulez upgrade' (without --check) to install.");
+        println!("Run 'rulez self-update' (without --check) to install.");
         return Ok(());
     }
 
-    println!("Downloading and installing {}...", latest_version);
+    let Some(key) = RELEASE_PUBLIC_KEY else {
+        anyhow::bail!(
+            "This build has no release signing key, so downloads can't be verified.\n\
+             Download {} from https://github.com/SpillwaveSolutions/agent_rulez/releases instead.",
+            latest_version
+        );
+    };
+    let key = parse_public_key(key).context("Invalid RULEZ_RELEASE_PUBLIC_KEY")?;
 
-    let status = self_update::backends::github::Update::configure()
+    if !json {
+        println!("Downloading and verifying {}...", latest_version);
+    }
+
+    let update = self_update::backends::github::Update::configure()
         .repo_owner("SpillwaveSolutions")
         .repo_name("agent_rulez")
         .bin_name("rulez")
         .current_version(current_version)
+        .verifying_keys(vec![key])
+        .show_output(!json)
+        .no_confirm(json)
         .build()?
-        .update()?;
+        .update()
+        .context("Update failed (the current binary was left in place)")?;
 
-    match status {
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&VersionStatus::new(
+                update.version(),
+                status.latest.as_deref()
+            ))?
+        );
+        return Ok(());
+    }
+    match update {
         self_update::Status::UpToDate(v) => {
             println!("Already up to date: {}", v);
         }
         self_update::Status::Updated(v) => {
-            println!("Successfully upgraded to {}!", v);
+            println!("Successfully updated to {} (signature verified)!", v);
             println!("Restart rulez to use the new version.");
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_public_key() {
+        let hex = "00ff".repeat(16);
+        let key = parse_public_key(&hex).unwrap();
+        assert_eq!(key[0], 0x00);
+        assert_eq!(key[1], 0xff);
+        assert!(parse_public_key("abcd").is_err());
+        assert!(parse_public_key(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_version_status() {
+        let status = VersionStatus::new("2.2.0", Some("v2.3.0"));
+        assert_eq!(status.latest.as_deref(), Some("2.3.0"));
+        assert!(status.update_available);
+
+        assert!(!VersionStatus::new("2.3.0", Some("2.3.0")).update_available);
+        assert!(!VersionStatus::new("2.3.0", None).update_available);
+    }
+}
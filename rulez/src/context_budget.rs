@@ -0,0 +1,237 @@
+//! Size budget for injected context (`settings.max_context_bytes`).
+//!
+//! Several injecting rules can add up to more context than the model's
+//! window should take. Their pieces are fitted into the budget according to
+//! `settings.context_truncation`:
+//! - `truncate_tail` (default): each piece gets a share of the budget in
+//!   proportion to its rule's `context_weight`, and pieces over their share
+//!   are cut and end with [`TRUNCATION_MARKER`]. Space a small piece doesn't
+//!   need is shared out among the others.
+//! - `drop_lowest`: whole pieces are dropped, lowest `inject_priority`
+//!   first. If a single piece is still over budget it is cut.
+//!
+//! `settings.max_context_tokens` sets the budget in estimated tokens instead
+//! (see [`estimate_tokens`]); with both set, the smaller one applies.
+
+use serde::{Deserialize, Serialize};
+
+/// How injected context over budget is cut down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextTruncation {
+    /// Cut every piece to its weighted share of the budget (default)
+    #[default]
+    TruncateTail,
+    /// Drop the lowest-priority pieces until the rest fit
+    DropLowest,
+}
+
+/// Appended to a piece of context that was cut short
+pub const TRUNCATION_MARKER: &str = "\n… [truncated]";
+
+/// Placed between pieces; counts against the budget
+pub const SEPARATOR: &str = "\n\n";
+
+/// Bytes per token assumed by [`estimate_tokens`]
+pub const BYTES_PER_TOKEN: usize = 4;
+
+/// Rough token count of `text`
+///
+/// English prose and code average about four bytes per token across
+/// common tokenizers. Other languages usually take more tokens per byte,
+/// so treat the estimate as a lower bound there.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(BYTES_PER_TOKEN)
+}
+
+/// The budget in bytes for `max_context_bytes` and `max_context_tokens`
+pub fn budget_bytes(max_bytes: usize, max_tokens: Option<usize>) -> usize {
+    max_tokens.map_or(max_bytes, |tokens| {
+        max_bytes.min(tokens.saturating_mul(BYTES_PER_TOKEN))
+    })
+}
+
+/// One rule's injected context
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+    pub rule: String,
+    pub text: String,
+    pub weight: u32,
+    /// Other rules that injected the same content
+    pub merged: Vec<String>,
+}
+
+/// Length of the pieces once joined with [`SEPARATOR`]
+fn joined_len(pieces: &[Piece]) -> usize {
+    pieces.iter().map(|p| p.text.len()).sum::<usize>()
+        + SEPARATOR.len() * pieces.len().saturating_sub(1)
+}
+
+/// Fit `pieces`, ordered highest `inject_priority` first, into `budget` bytes
+///
+/// Returns the names of the rules whose context was cut or dropped.
+pub fn fit(pieces: &mut Vec<Piece>, budget: usize, strategy: ContextTruncation) -> Vec<String> {
+    if joined_len(pieces) <= budget {
+        return Vec::new();
+    }
+    let mut affected = Vec::new();
+    match strategy {
+        ContextTruncation::DropLowest => {
+            while pieces.len() > 1 && joined_len(pieces) > budget {
+                if let Some(piece) = pieces.pop() {
+                    affected.push(piece.rule);
+                }
+            }
+            if let [piece] = pieces.as_mut_slice()
+                && truncate(&mut piece.text, budget)
+            {
+                affected.push(piece.rule.clone());
+            }
+        }
+        ContextTruncation::TruncateTail => {
+            let available = budget.saturating_sub(SEPARATOR.len() * (pieces.len() - 1));
+            let sizes: Vec<(usize, u32)> =
+                pieces.iter().map(|p| (p.text.len(), p.weight)).collect();
+            for (piece, share) in pieces.iter_mut().zip(shares(&sizes, available)) {
+                if truncate(&mut piece.text, share) {
+                    affected.push(piece.rule.clone());
+                }
+            }
+        }
+    }
+    pieces.retain(|piece| !piece.text.is_empty());
+    affected
+}
+
+/// Split `available` bytes by weight, giving unused space to larger pieces
+///
+/// Pieces that fit in their share keep their full length; the rest is
+/// shared again among the others until no more pieces fit.
+fn shares(pieces: &[(usize, u32)], available: usize) -> Vec<usize> {
+    let mut shares = vec![0; pieces.len()];
+    let mut open: Vec<usize> = (0..pieces.len()).collect();
+    let mut remaining = available;
+    while !open.is_empty() {
+        let total_weight: u64 = open.iter().map(|&i| u64::from(pieces[i].1)).sum();
+        if total_weight == 0 {
+            break;
+        }
+        let share = |i: usize| {
+            let share = remaining as u128 * u128::from(pieces[i].1) / u128::from(total_weight);
+            usize::try_from(share).unwrap_or(usize::MAX)
+        };
+        let (fits, over): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&i| pieces[i].0 <= share(i));
+        if fits.is_empty() {
+            for i in over {
+                shares[i] = share(i);
+            }
+            break;
+        }
+        for i in fits {
+            shares[i] = pieces[i].0;
+            remaining -= pieces[i].0;
+        }
+        open = over;
+    }
+    shares
+}
+
+/// Cut `text` to at most `limit` bytes ending in the marker; true if it was cut
+///
+/// A limit too small to hold the marker empties the text.
+pub fn truncate(text: &mut String, limit: usize) -> bool {
+    if text.len() <= limit {
+        return false;
+    }
+    if limit < TRUNCATION_MARKER.len() {
+        text.clear();
+        return true;
+    }
+    let mut end = limit - TRUNCATION_MARKER.len();
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(TRUNCATION_MARKER);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(rule: &str, len: usize, weight: u32) -> Piece {
+        Piece {
+            rule: rule.to_string(),
+            text: "x".repeat(len),
+            weight,
+            merged: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fit_under_budget_is_untouched() {
+        let mut pieces = vec![piece("a", 10, 1), piece("b", 10, 1)];
+        assert!(fit(&mut pieces, 22, ContextTruncation::TruncateTail).is_empty());
+        assert_eq!(joined_len(&pieces), 22);
+    }
+
+    #[test]
+    fn test_fit_drop_lowest_pops_from_the_end() {
+        let mut pieces = vec![
+            piece("high", 40, 1),
+            piece("mid", 40, 1),
+            piece("low", 40, 1),
+        ];
+        let affected = fit(&mut pieces, 90, ContextTruncation::DropLowest);
+        assert_eq!(affected, vec!["low"]);
+        assert_eq!(pieces.len(), 2);
+        assert!(joined_len(&pieces) <= 90);
+    }
+
+    #[test]
+    fn test_fit_drop_lowest_cuts_a_lone_piece() {
+        let mut pieces = vec![piece("a", 100, 1), piece("b", 100, 1)];
+        let affected = fit(&mut pieces, 50, ContextTruncation::DropLowest);
+        assert_eq!(affected, vec!["b", "a"]);
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].text.len(), 50);
+        assert!(pieces[0].text.ends_with(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_fit_truncate_tail_shares_by_weight() {
+        // 302 bytes minus two separators leaves 298: the small piece keeps
+        // its 10 bytes and the other two split 288 three to one
+        let mut pieces = vec![
+            piece("big", 500, 3),
+            piece("small", 10, 1),
+            piece("other", 500, 1),
+        ];
+        let affected = fit(&mut pieces, 302, ContextTruncation::TruncateTail);
+        assert_eq!(affected, vec!["big", "other"]);
+        assert_eq!(pieces[0].text.len(), 216);
+        assert_eq!(pieces[1].text.len(), 10);
+        assert_eq!(pieces[2].text.len(), 72);
+        assert!(joined_len(&pieces) <= 302);
+    }
+
+    #[test]
+    fn test_estimate_tokens_and_token_budget() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(budget_bytes(1000, None), 1000);
+        assert_eq!(budget_bytes(1000, Some(100)), 400);
+        assert_eq!(budget_bytes(100, Some(1000)), 100);
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        let mut text = "é".repeat(20);
+        assert!(truncate(&mut text, 21));
+        assert!(text.len() <= 21);
+        assert!(text.ends_with(TRUNCATION_MARKER));
+    }
+}
@@ -0,0 +1,535 @@
+//! Periodic digests of the audit log.
+//!
+//! A digest summarizes one period of decisions — totals, the busiest rules,
+//! events that warn-mode rules let through, tool calls rules approved, and
+//! anomalies — as markdown for people and JSON for tools. `rulez digest`
+//! builds one on demand; `rulez daemon --digest daily|weekly` sends one on a
+//! schedule.
+//!
+//! ```yaml
+//! settings:
+//!   digest:
+//!     webhook: "https://hooks.slack.com/services/T000/B000/XXXX"
+//!     output: "~/rulez-digests/latest.md"
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::anomaly::Anomaly;
+use crate::cli::stats::rule_stats;
+use crate::log_export::detail_columns;
+use crate::logging::{LogQuery, QueryFilters, log_path};
+use crate::models::{Decision, LogEntry, Outcome, PermissionDecision};
+
+/// Most entries listed per digest section
+const MAX_ITEMS: usize = 10;
+
+/// How often the daemon checks whether a scheduled digest is due
+const SCHEDULE_CHECK_SECS: u64 = 60;
+
+/// Digest delivery settings (`settings.digest`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DigestConfig {
+    /// URL the digest is POSTed to as JSON (with a Slack-style `text` field)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+
+    /// Extra HTTP headers for the webhook (`${VAR}` is expanded)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+
+    /// Webhook timeout in seconds
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Markdown file the digest is written to (`~` is expanded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+
+    /// Number of rules listed under "Top rules"
+    #[serde(default = "default_top_rules")]
+    pub top_rules: usize,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            webhook: None,
+            headers: HashMap::new(),
+            timeout_secs: default_timeout_secs(),
+            output: None,
+            top_rules: default_top_rules(),
+        }
+    }
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+fn default_top_rules() -> usize {
+    5
+}
+
+/// Period covered by a digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[clap(rename_all = "lowercase")]
+pub enum DigestPeriod {
+    Daily,
+    Weekly,
+}
+
+impl DigestPeriod {
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            Self::Daily => chrono::Duration::days(1),
+            Self::Weekly => chrono::Duration::weeks(1),
+        }
+    }
+}
+
+impl std::fmt::Display for DigestPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+/// A rule's share of the period
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DigestRule {
+    pub name: String,
+    pub matches: u64,
+    pub blocks: u64,
+}
+
+/// Events grouped by rule and what was attempted
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DigestItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Command, file path, pattern, or tool name
+    pub subject: String,
+    pub count: u64,
+}
+
+/// Summary of one period of the audit log
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub events: u64,
+    pub blocked: u64,
+    pub injected: u64,
+    pub sessions: usize,
+    pub top_rules: Vec<DigestRule>,
+    /// Events a warn-mode rule matched but let through
+    pub bypasses: Vec<DigestItem>,
+    /// Tool calls a rule approved with `permission_decision: allow`
+    pub approvals: Vec<DigestItem>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl Digest {
+    /// Summarize entries (any order) for the period `since..until`
+    pub fn build(
+        entries: &[LogEntry],
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        top_rules: usize,
+    ) -> Self {
+        let mut digest = Self {
+            since,
+            until,
+            events: 0,
+            blocked: 0,
+            injected: 0,
+            sessions: 0,
+            top_rules: Vec::new(),
+            bypasses: Vec::new(),
+            approvals: Vec::new(),
+            anomalies: Vec::new(),
+        };
+        let mut sessions = HashSet::new();
+        let mut bypasses = HashMap::new();
+        let mut approvals = HashMap::new();
+
+        for entry in entries {
+            if let Some(ref anomaly) = entry.anomaly {
+                digest.anomalies.push(anomaly.clone());
+                continue;
+            }
+            let weight = u64::from(entry.sample_rate.unwrap_or(1).max(1));
+            digest.events += weight;
+            match entry.outcome {
+                Outcome::Block => digest.blocked += weight,
+                Outcome::Inject => digest.injected += weight,
+                Outcome::Allow => {}
+            }
+            sessions.insert(entry.session_id.as_str());
+
+            if entry.decision == Some(Decision::Warned) {
+                *bypasses.entry(item_key(entry)).or_default() += weight;
+            }
+            let approved = entry
+                .response
+                .as_ref()
+                .is_some_and(|r| r.permission_decision == Some(PermissionDecision::Allow));
+            if approved {
+                *approvals.entry(item_key(entry)).or_default() += weight;
+            }
+        }
+
+        digest.sessions = sessions.len();
+        digest.top_rules = rule_stats(entries, &[])
+            .into_iter()
+            .filter(|rule| rule.matches > 0)
+            .take(top_rules)
+            .map(|rule| DigestRule {
+                name: rule.name,
+                matches: rule.matches,
+                blocks: rule.blocks,
+            })
+            .collect();
+        digest.bypasses = top_items(bypasses);
+        digest.approvals = top_items(approvals);
+        digest
+    }
+
+    /// The digest as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# RuleZ digest: {} to {}\n",
+            self.since.format("%Y-%m-%d %H:%M"),
+            self.until.format("%Y-%m-%d %H:%M UTC")
+        );
+        let _ = writeln!(
+            out,
+            "**{}** events in **{}** sessions: {} blocked, {} injected.\n",
+            self.events, self.sessions, self.blocked, self.injected
+        );
+
+        out.push_str("## Top rules\n\n");
+        if self.top_rules.is_empty() {
+            out.push_str("No rules matched.\n");
+        } else {
+            out.push_str("| Rule | Matches | Blocks |\n|------|--------:|-------:|\n");
+            for rule in &self.top_rules {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} |",
+                    rule.name, rule.matches, rule.blocks
+                );
+            }
+        }
+
+        write_items(&mut out, "Let through by warn-mode rules", &self.bypasses);
+        write_items(&mut out, "Approved by rules", &self.approvals);
+
+        out.push_str("\n## Anomalies\n\n");
+        if self.anomalies.is_empty() {
+            out.push_str("None.\n");
+        }
+        for anomaly in &self.anomalies {
+            let _ = writeln!(out, "- {}", anomaly.describe());
+        }
+        out
+    }
+}
+
+/// `(rule, subject)` for grouping an entry
+fn item_key(entry: &LogEntry) -> (Option<String>, String) {
+    let (command, file_path, pattern) = entry
+        .event_details
+        .as_ref()
+        .map(detail_columns)
+        .unwrap_or_default();
+    let subject = command
+        .or(file_path)
+        .or(pattern)
+        .or_else(|| entry.tool_name.clone())
+        .unwrap_or_else(|| entry.event_type.clone());
+    (entry.rules_matched.first().cloned(), subject)
+}
+
+/// The most frequent items, at most [`MAX_ITEMS`]
+fn top_items(counts: HashMap<(Option<String>, String), u64>) -> Vec<DigestItem> {
+    let mut items: Vec<DigestItem> = counts
+        .into_iter()
+        .map(|((rule, subject), count)| DigestItem {
+            rule,
+            subject,
+            count,
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.rule.cmp(&b.rule))
+            .then_with(|| a.subject.cmp(&b.subject))
+    });
+    items.truncate(MAX_ITEMS);
+    items
+}
+
+fn write_items(out: &mut String, title: &str, items: &[DigestItem]) {
+    let _ = writeln!(out, "\n## {}\n", title);
+    if items.is_empty() {
+        out.push_str("None.\n");
+    }
+    for item in items {
+        let _ = writeln!(
+            out,
+            "- `{}` ({}): {}×",
+            item.subject.replace('`', "'"),
+            item.rule.as_deref().unwrap_or("no rule"),
+            item.count
+        );
+    }
+}
+
+/// Build the digest for `since..until` from a log file
+pub fn build_from_log(
+    log_path: Option<PathBuf>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    top_rules: usize,
+) -> Result<Digest> {
+    let query = log_path.map_or_else(LogQuery::new, LogQuery::with_path);
+    let entries = query.query(QueryFilters {
+        since: Some(since),
+        until: Some(until),
+        ..Default::default()
+    })?;
+    Ok(Digest::build(&entries, since, until, top_rules))
+}
+
+/// POST the digest as JSON: the structured digest plus its markdown as `text`
+pub fn send_webhook(config: &DigestConfig, digest: &Digest) -> Result<()> {
+    let Some(ref url) = config.webhook else {
+        return Ok(());
+    };
+    let payload = serde_json::json!({
+        "text": digest.to_markdown(),
+        "digest": digest,
+    });
+    let headers = config
+        .headers
+        .iter()
+        .map(|(k, v)| (k.clone(), crate::logging::expand_env_vars(v)))
+        .collect();
+    crate::logging::send_via_curl(
+        url,
+        &serde_json::to_vec(&payload)?,
+        &headers,
+        std::time::Duration::from_secs(config.timeout_secs),
+    )
+}
+
+/// Write the digest's markdown to a file, creating parent directories
+pub fn write_markdown(path: &Path, digest: &Digest) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, digest.to_markdown())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Expand a leading `~/` in a configured path
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Send a digest every `period` until the daemon stops
+///
+/// The time of the last digest is kept in `~/.claude/logs/digest-<period>.last`,
+/// so restarting the daemon neither skips nor repeats a period. The first
+/// run only records the current time. Delivery settings are re-read from
+/// `settings.digest` each time a digest is due.
+pub async fn run_schedule(period: DigestPeriod) -> Result<()> {
+    let stamp = stamp_path(period);
+    let mut ticks = tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_CHECK_SECS));
+    loop {
+        ticks.tick().await;
+        let now = Utc::now();
+        let Some(last) = read_stamp(&stamp) else {
+            write_stamp(&stamp, now)?;
+            continue;
+        };
+        if now - last < period.duration() {
+            continue;
+        }
+
+        if let Err(e) = send_scheduled(last, now) {
+            tracing::warn!("Failed to send {} digest: {:#}", period, e);
+        }
+        write_stamp(&stamp, now)?;
+    }
+}
+
+fn send_scheduled(since: DateTime<Utc>, until: DateTime<Utc>) -> Result<()> {
+    let config = crate::config::Config::load(None)?
+        .settings
+        .digest
+        .unwrap_or_default();
+    crate::logging::flush_global_logger();
+    let digest = build_from_log(None, since, until, config.top_rules)?;
+    if let Some(ref output) = config.output {
+        write_markdown(&expand_home(output), &digest)?;
+    }
+    send_webhook(&config, &digest)
+}
+
+fn stamp_path(period: DigestPeriod) -> PathBuf {
+    log_path().with_file_name(format!("digest-{}.last", period))
+}
+
+fn read_stamp(path: &Path) -> Option<DateTime<Utc>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn write_stamp(path: &Path, time: DateTime<Utc>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, time.to_rfc3339())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: serde_json::Value) -> LogEntry {
+        let mut base = serde_json::json!({
+            "schema_version": 2,
+            "timestamp": "2026-03-01T12:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": "s1",
+            "tool_name": "Bash",
+            "rules_matched": [],
+            "outcome": "allow",
+            "timing": { "processing_ms": 1, "rules_evaluated": 3 }
+        });
+        for (key, value) in json.as_object().unwrap() {
+            base[key] = value.clone();
+        }
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn sample_entries() -> Vec<LogEntry> {
+        vec![
+            entry(serde_json::json!({
+                "rules_matched": ["no-force-push"],
+                "outcome": "block",
+                "decision": "blocked",
+                "event_details": { "tool_type": "Bash", "command": "git push --force" }
+            })),
+            entry(serde_json::json!({
+                "session_id": "s2",
+                "rules_matched": ["warn-sudo"],
+                "outcome": "inject",
+                "decision": "warned",
+                "event_details": { "tool_type": "Bash", "command": "sudo ls" }
+            })),
+            entry(serde_json::json!({
+                "session_id": "s2",
+                "rules_matched": ["warn-sudo"],
+                "outcome": "inject",
+                "decision": "warned",
+                "event_details": { "tool_type": "Bash", "command": "sudo ls" }
+            })),
+            entry(serde_json::json!({
+                "rules_matched": ["approve-tests"],
+                "response": { "continue": true, "permission_decision": "allow" },
+                "event_details": { "tool_type": "Bash", "command": "cargo test" }
+            })),
+            entry(serde_json::json!({
+                "event_type": "Anomaly",
+                "rules_matched": ["no-force-push"],
+                "outcome": "block",
+                "anomaly": { "kind": "repeated_block", "rule": "no-force-push", "count": 3, "window_secs": 600 }
+            })),
+        ]
+    }
+
+    #[test]
+    fn test_digest_build() {
+        let since = "2026-03-01T00:00:00Z".parse().unwrap();
+        let until = "2026-03-02T00:00:00Z".parse().unwrap();
+        let digest = Digest::build(&sample_entries(), since, until, 2);
+
+        assert_eq!(digest.events, 4, "anomaly entries are not events");
+        assert_eq!(digest.blocked, 1);
+        assert_eq!(digest.injected, 2);
+        assert_eq!(digest.sessions, 2);
+        assert_eq!(
+            digest.top_rules,
+            vec![
+                DigestRule {
+                    name: "warn-sudo".to_string(),
+                    matches: 2,
+                    blocks: 0
+                },
+                DigestRule {
+                    name: "approve-tests".to_string(),
+                    matches: 1,
+                    blocks: 0
+                },
+            ]
+        );
+        assert_eq!(
+            digest.bypasses,
+            vec![DigestItem {
+                rule: Some("warn-sudo".to_string()),
+                subject: "sudo ls".to_string(),
+                count: 2
+            }]
+        );
+        assert_eq!(digest.approvals[0].subject, "cargo test");
+        assert_eq!(digest.anomalies.len(), 1);
+    }
+
+    #[test]
+    fn test_digest_markdown() {
+        let since = "2026-03-01T00:00:00Z".parse().unwrap();
+        let until = "2026-03-02T00:00:00Z".parse().unwrap();
+        let markdown = Digest::build(&sample_entries(), since, until, 5).to_markdown();
+
+        assert!(markdown.starts_with("# RuleZ digest: 2026-03-01 00:00 to 2026-03-02 00:00 UTC"));
+        assert!(markdown.contains("**4** events in **2** sessions: 1 blocked, 2 injected."));
+        assert!(markdown.contains("| `no-force-push` | 1 | 1 |"));
+        assert!(markdown.contains("- `sudo ls` (warn-sudo): 2×"));
+        assert!(markdown.contains("- `cargo test` (approve-tests): 1×"));
+        assert!(markdown.contains("Identical call blocked 3 times"));
+    }
+
+    #[test]
+    fn test_stamp_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("digest-daily.last");
+        assert_eq!(read_stamp(&path), None);
+        let now = "2026-03-01T00:00:00Z".parse().unwrap();
+        write_stamp(&path, now).unwrap();
+        assert_eq!(read_stamp(&path), Some(now));
+    }
+}
@@ -0,0 +1,536 @@
+//! Embeddable policy engine.
+//!
+//! [`RuleEngine`] evaluates events against a [`Config`] without the hook
+//! binary's stdin/stdout protocol, config discovery, audit logging, or
+//! session state. CI bots, editors and other Rust tools can use it to apply
+//! the same `hooks.yaml` policy:
+//!
+//! ```no_run
+//! use rulez::engine::RuleEngine;
+//! use rulez::models::Event;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let engine = RuleEngine::from_yaml(
+//!     r#"
+//! version: "1.0"
+//! rules:
+//!   - name: no-force-push
+//!     matchers:
+//!       tools: ["Bash"]
+//!       command_match: "git push --force"
+//!     actions:
+//!       block: true
+//! "#,
+//! )?;
+//!
+//! let event: Event = serde_json::from_str(
+//!     r#"{"hook_event_name":"PreToolUse","session_id":"ci","tool_name":"Bash","tool_input":{"command":"git push --force"}}"#,
+//! )?;
+//! let result = engine.evaluate(&event).await?;
+//! assert!(result.is_blocked());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Actions that spawn processes (`inline_script`, `inject_command`,
+//! `summarize.command`, `run`, `webhook`) need the default `process` feature. Without it, a rule using
+//! one blocks in enforce mode and warns in warn mode instead of running it:
+//!
+//! ```toml
+//! rulez = { version = "2", default-features = false }
+//! ```
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::RulezError;
+use crate::hooks::{PolicyEvaluation, determine_decision, evaluate_policy, finalize_hook_output};
+use crate::models::{DebugConfig, Decision, Event, Outcome, Response, RuleEvaluation};
+
+/// A validated config, ready to evaluate events
+#[derive(Debug, Clone)]
+pub struct RuleEngine {
+    config: Config,
+    debug: DebugConfig,
+}
+
+/// The result of evaluating one event
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EvaluationResult {
+    /// Response a hook would send back to the assistant
+    pub response: Response,
+
+    /// Allow, block, or inject
+    pub outcome: Outcome,
+
+    /// Names of the matched rules, highest priority first
+    pub matched_rules: Vec<String>,
+
+    /// Governance decision of the highest-priority matched rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<Decision>,
+
+    /// Self-protection violation, if the event touched RuleZ's own files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violation: Option<String>,
+
+    /// Per-rule matcher details (only with [`RuleEngine::with_debug`])
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rule_evaluations: Vec<RuleEvaluation>,
+}
+
+impl EvaluationResult {
+    /// Whether the operation would be blocked
+    pub fn is_blocked(&self) -> bool {
+        !self.response.continue_
+    }
+}
+
+impl RuleEngine {
+    /// Create an engine from a config, validating it first
+    pub fn from_config(config: Config) -> Result<Self, RulezError> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            debug: DebugConfig::default(),
+        })
+    }
+
+    /// Parse `hooks.yaml` content, apply its built-in packs, and create an engine
+    pub fn from_yaml(yaml: &str) -> Result<Self, RulezError> {
+        let mut config: Config =
+            serde_yaml::from_str(yaml).map_err(|e| RulezError::config_parse(None, yaml, e))?;
+        crate::packs::apply_builtin_packs(&mut config)?;
+        crate::sync::apply_overlay(&mut config)?;
+        config.validate()?;
+        crate::governance::apply(&mut config, crate::governance::today());
+        Ok(Self {
+            config,
+            debug: DebugConfig::default(),
+        })
+    }
+
+    /// Evaluate every enabled rule and record matcher details
+    #[must_use]
+    pub fn with_debug(mut self, enabled: bool) -> Self {
+        self.debug.enabled = enabled;
+        self
+    }
+
+    /// The engine's config
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Evaluate an event against the rules
+    ///
+    /// Nothing is logged, and `max_stop_blocks`, anomaly detection and
+    /// session summaries (which need state across events) are not applied.
+    pub async fn evaluate(&self, event: &Event) -> Result<EvaluationResult, RulezError> {
+        let PolicyEvaluation {
+            matched_rules,
+            mut response,
+            rule_evaluations,
+            violation,
+            ..
+        } = evaluate_policy(event, &self.config, &self.debug).await?;
+        finalize_hook_output(&mut response, event.hook_event_name);
+
+        let outcome = match response.continue_ {
+            true if response.context.is_some() => Outcome::Inject,
+            true => Outcome::Allow,
+            false => Outcome::Block,
+        };
+        let decision = matched_rules
+            .first()
+            .map(|rule| determine_decision(&response, rule.effective_mode()));
+        Ok(EvaluationResult {
+            outcome,
+            matched_rules: matched_rules.iter().map(|r| r.name.clone()).collect(),
+            decision,
+            violation,
+            rule_evaluations: if self.debug.enabled {
+                rule_evaluations
+            } else {
+                Vec::new()
+            },
+            response,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+  - name: rust-hint
+    matchers:
+      tools: ["Write"]
+      extensions: [".rs"]
+    actions:
+      inject_inline: "Run cargo fmt."
+"#;
+
+    fn event(tool: &str, input: serde_json::Value) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "engine-test",
+            "tool_name": tool,
+            "tool_input": input,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_blocks_and_injects() {
+        let engine = RuleEngine::from_yaml(CONFIG).unwrap();
+
+        let result = engine
+            .evaluate(&event(
+                "Bash",
+                serde_json::json!({"command": "git push --force"}),
+            ))
+            .await
+            .unwrap();
+        assert!(result.is_blocked());
+        assert_eq!(result.outcome, Outcome::Block);
+        assert_eq!(result.matched_rules, vec!["no-force-push"]);
+        assert_eq!(result.decision, Some(Decision::Blocked));
+
+        let result = engine
+            .evaluate(&event(
+                "Write",
+                serde_json::json!({"file_path": "src/main.rs", "content": ""}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result.outcome, Outcome::Inject);
+        assert_eq!(result.response.context.as_deref(), Some("Run cargo fmt."));
+
+        let result = engine
+            .evaluate(&event("Bash", serde_json::json!({"command": "ls"})))
+            .await
+            .unwrap();
+        assert_eq!(result.outcome, Outcome::Allow);
+        assert!(result.matched_rules.is_empty());
+        assert!(result.rule_evaluations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_debug_reports_rule_evaluations() {
+        let engine = RuleEngine::from_yaml(CONFIG).unwrap().with_debug(true);
+        let result = engine
+            .evaluate(&event("Bash", serde_json::json!({"command": "ls"})))
+            .await
+            .unwrap();
+        assert_eq!(result.rule_evaluations.len(), 2);
+        assert!(result.rule_evaluations.iter().all(|e| !e.matched));
+    }
+
+    #[cfg(not(feature = "process"))]
+    #[tokio::test]
+    async fn test_process_actions_fail_closed_without_feature() {
+        let engine = RuleEngine::from_yaml(
+            r#"
+version: "1.0"
+rules:
+  - name: lint-check
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inject_command: "echo hi"
+"#,
+        )
+        .unwrap();
+        let result = engine
+            .evaluate(&event("Bash", serde_json::json!({"command": "ls"})))
+            .await
+            .unwrap();
+        assert!(result.is_blocked());
+        assert!(result.response.reason.unwrap().contains("'inject_command'"));
+    }
+
+    #[cfg(not(feature = "process"))]
+    #[tokio::test]
+    async fn test_summarize_command_fails_closed_without_feature() {
+        let engine = RuleEngine::from_yaml(
+            r#"
+version: "1.0"
+rules:
+  - name: long-context
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inject_inline: "context"
+      summarize:
+        max_bytes: 1
+        command: "head -c 5"
+"#,
+        )
+        .unwrap();
+        let result = engine
+            .evaluate(&event("Bash", serde_json::json!({"command": "ls"})))
+            .await
+            .unwrap();
+        assert!(result.is_blocked());
+        assert!(result.response.reason.unwrap().contains("'summarize'"));
+    }
+
+    #[test]
+    fn test_invalid_regex_is_a_structured_error() {
+        let err =
+            RuleEngine::from_yaml(&CONFIG.replace("git push --force", "git push (")).unwrap_err();
+        let RulezError::RegexCompile { rule, field, .. } = err else {
+            panic!("expected RegexCompile, got {err}");
+        };
+        assert_eq!(
+            (rule.as_str(), field.as_str()),
+            ("no-force-push", "command_match")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_matcher() {
+        struct ToolPrefix;
+
+        impl crate::matchers::Matcher for ToolPrefix {
+            fn name(&self) -> &'static str {
+                "engine_tool_prefix"
+            }
+
+            fn matches(&self, event: &Event, value: &serde_json::Value) -> bool {
+                let prefix = value.as_str().unwrap_or_default();
+                event
+                    .tool_name
+                    .as_deref()
+                    .is_some_and(|tool| tool.starts_with(prefix))
+            }
+        }
+
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: no-mcp
+    matchers:
+      custom:
+        engine_tool_prefix: "mcp__"
+    actions:
+      block: true
+"#;
+        let err = RuleEngine::from_yaml(yaml).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unknown custom matcher 'engine_tool_prefix'")
+        );
+        assert_eq!(err.rule(), Some("no-mcp"));
+
+        crate::matchers::register(ToolPrefix);
+        let engine = RuleEngine::from_yaml(yaml).unwrap().with_debug(true);
+        let result = engine
+            .evaluate(&event("mcp__github__merge", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(result.is_blocked());
+        let result = engine
+            .evaluate(&event("Bash", serde_json::json!({"command": "ls"})))
+            .await
+            .unwrap();
+        assert!(!result.is_blocked());
+        let custom = result.rule_evaluations[0]
+            .matcher_results
+            .as_ref()
+            .and_then(|results| results.custom_matched.clone());
+        assert_eq!(
+            custom,
+            Some(std::collections::BTreeMap::from([(
+                "engine_tool_prefix".to_string(),
+                false
+            )]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_action() {
+        use crate::actions::{Action, ActionContext, ActionOutcome};
+
+        struct RequireTicket;
+
+        impl Action for RequireTicket {
+            fn name(&self) -> &'static str {
+                "engine_require_ticket"
+            }
+
+            fn execute<'a>(
+                &'a self,
+                event: &'a Event,
+                ctx: &'a ActionContext<'a>,
+            ) -> futures::future::BoxFuture<'a, anyhow::Result<ActionOutcome>> {
+                Box::pin(async move {
+                    let prefix = ctx.value.as_str().unwrap_or_default();
+                    let command = event
+                        .tool_input
+                        .as_ref()
+                        .and_then(|input| input["command"].as_str())
+                        .unwrap_or_default();
+                    Ok(if command.contains(prefix) {
+                        ActionOutcome::Continue
+                    } else {
+                        ActionOutcome::Block(format!("commit needs a {} ticket", prefix))
+                    })
+                })
+            }
+        }
+
+        crate::actions::register(RequireTicket);
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: ticketed-commits
+    mode: MODE
+    matchers:
+      tools: ["Bash"]
+      command_match: "git commit"
+    actions:
+      custom:
+        engine_require_ticket: "JIRA-"
+"#;
+        let engine = RuleEngine::from_yaml(&yaml.replace("MODE", "enforce")).unwrap();
+        let commit = |message: &str| {
+            event(
+                "Bash",
+                serde_json::json!({"command": format!("git commit -m '{}'", message)}),
+            )
+        };
+        let result = engine.evaluate(&commit("fix")).await.unwrap();
+        assert!(result.is_blocked());
+        assert_eq!(
+            result.response.reason.as_deref(),
+            Some("commit needs a JIRA- ticket")
+        );
+        let result = engine.evaluate(&commit("JIRA-12 fix")).await.unwrap();
+        assert!(!result.is_blocked());
+
+        let engine = RuleEngine::from_yaml(&yaml.replace("MODE", "warn")).unwrap();
+        let result = engine.evaluate(&commit("fix")).await.unwrap();
+        assert!(!result.is_blocked());
+        assert!(
+            result
+                .response
+                .context
+                .unwrap()
+                .starts_with("[WARNING] Rule 'ticketed-commits' would block this operation")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_action_pipeline() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: write-pipeline
+    matchers:
+      tools: ["Write"]
+    actions:
+      - validate_expr: 'has_field("file_path")'
+      - inject_inline: "Follow the style guide."
+      - block_if_match: "TODO"
+        continue_on_failure: true
+      - inject_inline: "Update the changelog."
+"#;
+        let engine = RuleEngine::from_yaml(yaml).unwrap();
+
+        let result = engine
+            .evaluate(&event(
+                "Write",
+                serde_json::json!({"file_path": "a.rs", "content": "TODO"}),
+            ))
+            .await
+            .unwrap();
+        assert!(
+            !result.is_blocked(),
+            "the blocking step continues on failure"
+        );
+        assert_eq!(
+            result.response.context.as_deref(),
+            Some("Follow the style guide.\n\nUpdate the changelog.")
+        );
+
+        let result = engine
+            .evaluate(&event("Write", serde_json::json!({"content": "x"})))
+            .await
+            .unwrap();
+        assert!(result.is_blocked(), "a failed validation ends the pipeline");
+        assert!(result.response.context.is_none());
+
+        let err = RuleEngine::from_yaml(&yaml.replace("has_field(", "has_field((")).unwrap_err();
+        assert_eq!(err.rule(), Some("write-pipeline"));
+    }
+
+    #[tokio::test]
+    async fn test_action_when_conditions() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: terraform
+    matchers:
+      tools: ["Write"]
+    actions:
+      - inject_inline: "Run terraform fmt."
+        when: 'ends_with(tool_input_file_path, ".tf")'
+      - inject_inline: "Keep changes small."
+  - name: ci-only
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+      when: 'tool_input_command == "deploy"'
+"#;
+        let engine = RuleEngine::from_yaml(yaml).unwrap();
+        let write = |path: &str| {
+            event(
+                "Write",
+                serde_json::json!({"file_path": path, "content": ""}),
+            )
+        };
+
+        let result = engine.evaluate(&write("main.tf")).await.unwrap();
+        assert_eq!(
+            result.response.context.as_deref(),
+            Some("Run terraform fmt.\n\nKeep changes small.")
+        );
+        let result = engine.evaluate(&write("main.rs")).await.unwrap();
+        assert_eq!(
+            result.response.context.as_deref(),
+            Some("Keep changes small.")
+        );
+
+        let bash = |command: &str| event("Bash", serde_json::json!({"command": command}));
+        assert!(engine.evaluate(&bash("deploy")).await.unwrap().is_blocked());
+        let result = engine.evaluate(&bash("ls")).await.unwrap();
+        assert!(!result.is_blocked());
+        assert_eq!(
+            result.matched_rules,
+            vec!["ci-only"],
+            "the rule still matches"
+        );
+
+        let err = RuleEngine::from_yaml(&yaml.replace("tool_input_command ==", "((")).unwrap_err();
+        assert_eq!(err.rule(), Some("ci-only"));
+    }
+
+    #[test]
+    fn test_from_config_validates() {
+        let mut config: Config = serde_yaml::from_str(CONFIG).unwrap();
+        config.rules[1].name = "no-force-push".to_string();
+        assert!(RuleEngine::from_config(config).is_err());
+    }
+}
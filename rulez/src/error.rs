@@ -0,0 +1,227 @@
+//! Structured errors for library consumers.
+//!
+//! Config loading, validation and event processing return [`RulezError`] so
+//! embedders and the UI can tell a YAML syntax error from a bad regex or a
+//! failed validator script without matching on message text. Everything else
+//! in the crate still uses `anyhow`; `RulezError` converts into
+//! `anyhow::Error` with `?`, and errors raised below these entry points are
+//! carried as [`RulezError::Other`].
+
+use std::path::PathBuf;
+
+/// Errors returned by [`crate::config::Config`], [`crate::hooks`] and
+/// [`crate::engine`] entry points
+#[derive(Debug, thiserror::Error)]
+pub enum RulezError {
+    /// `hooks.yaml` is not valid YAML or does not match the config schema
+    #[error("Failed to parse config{}{}", parse_path(path.as_ref()), parse_location(*line, rule.as_deref()))]
+    ConfigParse {
+        /// Config file, when loaded from disk
+        path: Option<PathBuf>,
+        /// 1-based line of the error, when known
+        line: Option<usize>,
+        /// Rule defined around that line, when there is one
+        rule: Option<String>,
+        #[source]
+        source: serde_yaml::Error,
+    },
+
+    /// A matcher or action regex does not compile
+    #[error("Invalid {field} regex '{pattern}' in rule '{rule}': {source}")]
+    RegexCompile {
+        rule: String,
+        /// Config field holding the pattern, e.g. `command_match`
+        field: String,
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+
+    /// The config parsed but breaks a validation rule
+    #[error("{message}")]
+    InvalidConfig {
+        /// Rule the problem is in, if it is specific to one
+        rule: Option<String>,
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    /// The hook event JSON doesn't match the event schema
+    #[error("Invalid hook event")]
+    #[allow(dead_code)] // Library API for embedders
+    InvalidEvent {
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// A `run` validator failed to execute (and `fail_open` is off)
+    #[error("Script execution failed for rule '{rule}'")]
+    ScriptFailure {
+        rule: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// Reading a file failed
+    #[error("Failed to read {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Any other failure
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl RulezError {
+    /// Rule the error is about, if any
+    #[allow(dead_code)] // Library API for embedders
+    pub fn rule(&self) -> Option<&str> {
+        match self {
+            Self::ConfigParse { rule, .. } | Self::InvalidConfig { rule, .. } => rule.as_deref(),
+            Self::RegexCompile { rule, .. } | Self::ScriptFailure { rule, .. } => Some(rule),
+            Self::InvalidEvent { .. } | Self::Io { .. } | Self::Other(_) => None,
+        }
+    }
+
+    /// Variant name, for bindings that expose the error kind as a string
+    #[allow(dead_code)] // Library API for embedders
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ConfigParse { .. } => "ConfigParse",
+            Self::RegexCompile { .. } => "RegexCompile",
+            Self::InvalidConfig { .. } => "InvalidConfig",
+            Self::InvalidEvent { .. } => "InvalidEvent",
+            Self::ScriptFailure { .. } => "ScriptFailure",
+            Self::Io { .. } => "Io",
+            Self::Other(_) => "Other",
+        }
+    }
+
+    /// Whether the error is in the config (exit code 1) rather than at runtime
+    #[allow(dead_code)] // Library API for embedders
+    pub fn is_config_error(&self) -> bool {
+        matches!(
+            self,
+            Self::ConfigParse { .. } | Self::RegexCompile { .. } | Self::InvalidConfig { .. }
+        )
+    }
+
+    /// A YAML parse error, with the line and the rule it falls in
+    pub(crate) fn config_parse(
+        path: Option<PathBuf>,
+        content: &str,
+        source: serde_yaml::Error,
+    ) -> Self {
+        let line = source.location().map(|location| location.line());
+        Self::ConfigParse {
+            path,
+            line,
+            rule: line.and_then(|line| rule_at_line(content, line)),
+            source,
+        }
+    }
+
+    /// A validation error for one rule
+    pub(crate) fn invalid_rule(rule: &str, message: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            rule: Some(rule.to_string()),
+            message: message.into(),
+            source: None,
+        }
+    }
+}
+
+/// Keeps a `RulezError` raised below an `anyhow` layer intact
+impl From<anyhow::Error> for RulezError {
+    fn from(error: anyhow::Error) -> Self {
+        error.downcast::<Self>().unwrap_or_else(Self::Other)
+    }
+}
+
+fn parse_path(path: Option<&PathBuf>) -> String {
+    path.map(|path| format!(" file: {}", path.display()))
+        .unwrap_or_default()
+}
+
+fn parse_location(line: Option<usize>, rule: Option<&str>) -> String {
+    match (line, rule) {
+        (Some(line), Some(rule)) => format!(" (line {}, rule '{}')", line, rule),
+        (Some(line), None) => format!(" (line {})", line),
+        _ => String::new(),
+    }
+}
+
+/// Name of the rule whose `- name:` entry most recently precedes `line`
+fn rule_at_line(content: &str, line: usize) -> Option<String> {
+    let mut in_rules = false;
+    let mut rule = None;
+    for text in content.lines().take(line) {
+        if !text.starts_with([' ', '\t', '-', '#']) && !text.trim().is_empty() {
+            in_rules = text.trim_end() == "rules:";
+            rule = None;
+        } else if in_rules {
+            if let Some(name) = text.trim_start().strip_prefix("- name:") {
+                rule = Some(name.trim().trim_matches(['"', '\'']).to_string());
+            }
+        }
+    }
+    rule
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"version: "1.0"
+rules:
+  - name: first
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+  - name: "second"
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: maybe
+settings:
+  debug_logs: false
+"#;
+
+    #[test]
+    fn test_config_parse_reports_line_and_rule() {
+        let source = serde_yaml::from_str::<crate::config::Config>(CONFIG).unwrap_err();
+        let err = RulezError::config_parse(Some(PathBuf::from("hooks.yaml")), CONFIG, source);
+        assert!(err.is_config_error());
+        assert_eq!(err.kind(), "ConfigParse");
+        assert_eq!(err.rule(), Some("second"));
+        let RulezError::ConfigParse { line, .. } = err else {
+            panic!("expected ConfigParse");
+        };
+        assert!(line.is_some_and(|line| line >= 8));
+    }
+
+    #[test]
+    fn test_rule_at_line_outside_rules() {
+        assert_eq!(rule_at_line(CONFIG, 1), None);
+        assert_eq!(rule_at_line(CONFIG, 4), Some("first".to_string()));
+        assert_eq!(rule_at_line(CONFIG, 14), None);
+    }
+
+    #[test]
+    fn test_from_anyhow_keeps_structured_errors() {
+        let err: anyhow::Error = RulezError::invalid_rule("r", "bad").into();
+        assert!(matches!(
+            RulezError::from(err.context("while loading")),
+            RulezError::InvalidConfig { .. }
+        ));
+        assert!(matches!(
+            RulezError::from(anyhow::anyhow!("other")),
+            RulezError::Other(_)
+        ));
+    }
+}
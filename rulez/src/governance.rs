@@ -0,0 +1,387 @@
+//! Governance checks on rule provenance metadata.
+//!
+//! A rule's `governance.last_reviewed` date says when someone last confirmed
+//! the rule still makes sense. With `settings.governance` set, rules whose
+//! review is older than `review_max_age_days` are overdue: `rulez lint` flags
+//! them, `rulez governance report` lists them, and with `downgrade_overdue`
+//! an overdue `enforce` rule runs in `warn` mode until it is reviewed again.
+//!
+//! ```yaml
+//! settings:
+//!   governance:
+//!     review_max_age_days: 90
+//!     downgrade_overdue: true
+//! ```
+//!
+//! Rules without a `last_reviewed` date are never overdue.
+//!
+//! With `strict: true`, every `enforce` rule must name its `author`,
+//! `reason` and `ticket`. By default a config with an offender fails to
+//! load; with `strict_action: audit` offenders run in `audit` mode instead.
+//! Rules from built-in packs (`created_by: "builtin:..."`) are exempt.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::models::{PolicyMode, Rule};
+
+/// Governance settings (`settings.governance`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GovernanceConfig {
+    /// Days after `last_reviewed` before a rule is overdue for review
+    #[serde(default = "default_review_max_age_days")]
+    pub review_max_age_days: u32,
+
+    /// Run overdue `enforce` rules in `warn` mode
+    #[serde(default)]
+    pub downgrade_overdue: bool,
+
+    /// Require `author`, `reason` and `ticket` on every `enforce` rule
+    #[serde(default)]
+    pub strict: bool,
+
+    /// What `strict` does with a rule missing provenance
+    #[serde(default)]
+    pub strict_action: StrictAction,
+}
+
+/// How `strict` governance treats an `enforce` rule missing provenance
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StrictAction {
+    /// Fail config validation
+    #[default]
+    Reject,
+
+    /// Run the rule in `audit` mode
+    Audit,
+}
+
+/// Governance fields `strict` requires on `enforce` rules
+pub const REQUIRED_PROVENANCE: [&str; 3] = ["author", "reason", "ticket"];
+
+impl Default for GovernanceConfig {
+    fn default() -> Self {
+        Self {
+            review_max_age_days: default_review_max_age_days(),
+            downgrade_overdue: false,
+            strict: false,
+            strict_action: StrictAction::default(),
+        }
+    }
+}
+
+fn default_review_max_age_days() -> u32 {
+    180
+}
+
+/// Where a rule stands against the review policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewStatus {
+    /// No `governance.last_reviewed` date
+    NeverReviewed,
+
+    /// `last_reviewed` is not a date
+    InvalidDate(String),
+
+    /// Reviewed `age_days` ago, within the maximum age
+    Current { age_days: i64 },
+
+    /// Reviewed `age_days` ago, past the maximum age
+    Overdue { age_days: i64 },
+}
+
+impl ReviewStatus {
+    /// Short label for reports, e.g. `overdue`
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NeverReviewed => "never-reviewed",
+            Self::InvalidDate(_) => "invalid-date",
+            Self::Current { .. } => "current",
+            Self::Overdue { .. } => "overdue",
+        }
+    }
+}
+
+/// Parse a `last_reviewed` value: a date (`2026-03-01`) or an RFC3339 timestamp
+pub fn parse_review_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            chrono::DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|t| t.date_naive())
+        })
+}
+
+/// A rule's review status on `today`
+pub fn review_status(rule: &Rule, max_age_days: u32, today: NaiveDate) -> ReviewStatus {
+    let Some(reviewed) = rule
+        .governance
+        .as_ref()
+        .and_then(|g| g.last_reviewed.as_deref())
+    else {
+        return ReviewStatus::NeverReviewed;
+    };
+    let Some(date) = parse_review_date(reviewed) else {
+        return ReviewStatus::InvalidDate(reviewed.to_string());
+    };
+    let age_days = (today - date).num_days();
+    if age_days > i64::from(max_age_days) {
+        ReviewStatus::Overdue { age_days }
+    } else {
+        ReviewStatus::Current { age_days }
+    }
+}
+
+/// Required provenance fields a rule lacks (empty values count as missing)
+///
+/// Always empty for rules from built-in packs.
+pub fn missing_provenance(rule: &Rule) -> Vec<&'static str> {
+    let governance = rule.governance.clone().unwrap_or_default();
+    if governance
+        .created_by
+        .as_deref()
+        .is_some_and(|source| source.starts_with("builtin:"))
+    {
+        return Vec::new();
+    }
+    let present = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+    REQUIRED_PROVENANCE
+        .into_iter()
+        .zip([&governance.author, &governance.reason, &governance.ticket])
+        .filter(|(_, value)| !present(value))
+        .map(|(field, _)| field)
+        .collect()
+}
+
+/// `enforce` rules missing provenance under `strict`, with what they lack
+pub fn strict_violations(config: &Config) -> Vec<(&Rule, Vec<&'static str>)> {
+    if !config
+        .settings
+        .governance
+        .as_ref()
+        .is_some_and(|g| g.strict)
+    {
+        return Vec::new();
+    }
+    config
+        .rules
+        .iter()
+        .filter(|rule| rule.effective_mode() == PolicyMode::Enforce)
+        .map(|rule| (rule, missing_provenance(rule)))
+        .filter(|(_, missing)| !missing.is_empty())
+        .collect()
+}
+
+/// Today's date in local time
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Names of `enforce` rules that `downgrade_overdue` would run in `warn` mode
+pub fn overdue_enforce_rules(config: &Config, today: NaiveDate) -> Vec<String> {
+    let Some(governance) = config
+        .settings
+        .governance
+        .as_ref()
+        .filter(|g| g.downgrade_overdue)
+    else {
+        return Vec::new();
+    };
+    config
+        .rules
+        .iter()
+        .filter(|rule| rule.effective_mode() == PolicyMode::Enforce)
+        .filter(|rule| {
+            matches!(
+                review_status(rule, governance.review_max_age_days, today),
+                ReviewStatus::Overdue { .. }
+            )
+        })
+        .map(|rule| rule.name.clone())
+        .collect()
+}
+
+/// Apply the mode changes governance settings call for
+///
+/// Rules missing provenance under `strict_action: audit` switch to `audit`,
+/// then overdue `enforce` rules switch to `warn` if `downgrade_overdue` is
+/// set. Applied each time a validated config is loaded rather than stored in
+/// the compiled cache, since the review check depends on the date.
+pub fn apply(config: &mut Config, today: NaiveDate) {
+    if config
+        .settings
+        .governance
+        .as_ref()
+        .is_some_and(|g| g.strict_action == StrictAction::Audit)
+    {
+        let offenders: Vec<String> = strict_violations(config)
+            .into_iter()
+            .map(|(rule, _)| rule.name.clone())
+            .collect();
+        for rule in &mut config.rules {
+            if offenders.contains(&rule.name) {
+                tracing::debug!(
+                    "Rule '{}' is missing governance provenance; running in audit mode",
+                    rule.name
+                );
+                rule.mode = Some(PolicyMode::Audit);
+            }
+        }
+    }
+
+    let overdue = overdue_enforce_rules(config, today);
+    for rule in &mut config.rules {
+        if overdue.contains(&rule.name) {
+            tracing::debug!(
+                "Rule '{}' is overdue for review; running in warn mode",
+                rule.name
+            );
+            rule.mode = Some(PolicyMode::Warn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> Config {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        parse_review_date(s).unwrap()
+    }
+
+    const YAML: &str = r#"
+version: "1.0"
+settings:
+  governance:
+    review_max_age_days: 30
+    downgrade_overdue: true
+rules:
+  - name: stale
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { last_reviewed: "2026-01-01" }
+  - name: fresh
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { last_reviewed: "2026-02-20T10:00:00Z" }
+  - name: stale-audit
+    mode: audit
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { last_reviewed: "2025-06-01" }
+  - name: unreviewed
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+  - name: typo
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { last_reviewed: "last spring" }
+"#;
+
+    #[test]
+    fn test_review_status() {
+        let config = config(YAML);
+        let today = date("2026-03-01");
+        let status: Vec<ReviewStatus> = config
+            .rules
+            .iter()
+            .map(|rule| review_status(rule, 30, today))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ReviewStatus::Overdue { age_days: 59 },
+                ReviewStatus::Current { age_days: 9 },
+                ReviewStatus::Overdue { age_days: 273 },
+                ReviewStatus::NeverReviewed,
+                ReviewStatus::InvalidDate("last spring".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_downgrade_only_overdue_enforce_rules() {
+        let mut config = config(YAML);
+        apply(&mut config, date("2026-03-01"));
+        let modes: Vec<PolicyMode> = config.rules.iter().map(Rule::effective_mode).collect();
+        assert_eq!(
+            modes,
+            vec![
+                PolicyMode::Warn,
+                PolicyMode::Enforce,
+                PolicyMode::Audit,
+                PolicyMode::Enforce,
+                PolicyMode::Enforce,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_downgrade_unless_enabled() {
+        let mut config =
+            config(&YAML.replace("downgrade_overdue: true", "downgrade_overdue: false"));
+        apply(&mut config, date("2026-03-01"));
+        assert_eq!(config.rules[0].effective_mode(), PolicyMode::Enforce);
+    }
+
+    const STRICT_YAML: &str = r#"
+version: "1.0"
+settings:
+  governance:
+    strict: true
+rules:
+  - name: complete
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { author: sec, reason: "why", ticket: "SEC-1" }
+  - name: partial
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { author: sec, reason: "  " }
+  - name: warn-only
+    mode: warn
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+  - name: from-pack
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+    governance: { created_by: "builtin:shell-safety" }
+"#;
+
+    #[test]
+    fn test_strict_violations() {
+        let config: Config = serde_yaml::from_str(STRICT_YAML).unwrap();
+        let violations: Vec<(&str, Vec<&str>)> = strict_violations(&config)
+            .into_iter()
+            .map(|(rule, missing)| (rule.name.as_str(), missing))
+            .collect();
+        assert_eq!(violations, vec![("partial", vec!["reason", "ticket"])]);
+    }
+
+    #[test]
+    fn test_strict_audit_downgrades_offenders() {
+        let mut config: Config = serde_yaml::from_str(
+            &STRICT_YAML.replace("strict: true", "strict: true\n    strict_action: audit"),
+        )
+        .unwrap();
+        apply(&mut config, today());
+        let modes: Vec<PolicyMode> = config.rules.iter().map(Rule::effective_mode).collect();
+        assert_eq!(
+            modes,
+            vec![
+                PolicyMode::Enforce,
+                PolicyMode::Audit,
+                PolicyMode::Warn,
+                PolicyMode::Enforce,
+            ]
+        );
+    }
+}
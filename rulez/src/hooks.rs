@@ -6,23 +6,33 @@ use evalexpr::{
 use futures::future::join_all;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use lru::LruCache;
-use regex::{Regex, RegexBuilder};
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use sha2::{Digest, Sha256};
 use std::num::NonZeroUsize;
-use std::path::Path;
-use std::sync::{LazyLock, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 
 use crate::models::{MatchMode, PromptMatch};
 use tokio::process::Command;
 use tokio::time::{Duration, timeout};
+use tracing::Instrument;
 
-use crate::config::Config;
+use crate::actions::{Action, ActionContext, ActionOutcome};
+use crate::config::{Config, Settings, UnknownPolicy};
+use crate::context_budget;
+use crate::error::RulezError;
+use crate::limits::{ResourceLimitExceeded, SCRIPT_SLOTS, wait_with_limited_output};
 use crate::logging::log_entry;
 use crate::models::LogMetadata;
 use crate::models::{
-    DebugConfig, Decision, Event, EventDetails, GovernanceMetadata, LogEntry, LogTiming,
-    MatcherResults, Outcome, PolicyMode, Response, ResponseSummary, Rule, RuleEvaluation, Timing,
-    TrustLevel,
+    ActionStep, Actions, ContextEstimate, DebugConfig, Decision, Event, EventDetails, EventType,
+    GovernanceMetadata, HookSpecificOutput, InjectCommand, InjectTarget, InlineScript,
+    LOG_SCHEMA_VERSION, LatencyOverrun, LogEntry, LogTiming, MatcherResults, Outcome,
+    PermissionDecision, PiiSeverity, PolicyMode, PromptRewrite, Response, ResponseSummary, Rule,
+    RuleEvaluation, RuleLogLevel, RuleTiming, RunAction, ScriptEnvironment, ScriptShell, Summarize,
+    Timing, TranscriptMatch, TrustLevel, dot_to_pointer,
 };
+use crate::scripting::{ScriptDecision, run_rhai_script};
 
 // =============================================================================
 // Regex Caching for Performance
@@ -75,6 +85,42 @@ pub(crate) fn get_or_compile_regex(pattern: &str, case_insensitive: bool) -> Res
     Ok(regex)
 }
 
+/// Global cache of multi-pattern `RegexSet`s, keyed like `REGEX_CACHE` with
+/// the patterns joined by NUL.
+///
+/// A rule with many alternative patterns is answered by one scan of the
+/// text instead of one scan per pattern. Cleared together with
+/// `REGEX_CACHE` by the debug and test commands.
+pub static REGEX_SET_CACHE: LazyLock<Mutex<LruCache<String, RegexSet>>> = LazyLock::new(|| {
+    Mutex::new(LruCache::new(
+        NonZeroUsize::new(REGEX_CACHE_MAX_SIZE).unwrap(),
+    ))
+});
+
+/// Get or compile a `RegexSet` over `patterns` with caching
+pub(crate) fn get_or_compile_regex_set(
+    patterns: &[String],
+    case_insensitive: bool,
+) -> Result<RegexSet> {
+    let cache_key = format!("{}:{}", patterns.join("\0"), case_insensitive);
+
+    {
+        let mut cache = REGEX_SET_CACHE.lock().unwrap();
+        if let Some(set) = cache.get(&cache_key) {
+            return Ok(set.clone());
+        }
+    }
+
+    let set = RegexSetBuilder::new(patterns)
+        .case_insensitive(case_insensitive)
+        .build()
+        .with_context(|| format!("Invalid regex set: {:?}", patterns))?;
+
+    let mut cache = REGEX_SET_CACHE.lock().unwrap();
+    cache.put(cache_key, set.clone());
+    Ok(set)
+}
+
 // =============================================================================
 // Prompt Pattern Matching (Phase 4)
 // =============================================================================
@@ -96,38 +142,51 @@ fn matches_prompt(prompt: &str, prompt_match: &PromptMatch) -> bool {
         return false;
     }
 
-    let mut results = Vec::with_capacity(patterns.len());
-
-    for pattern in patterns {
-        // Check for negation prefix
-        let (is_negated, effective_pattern) = if let Some(inner) = pattern.strip_prefix("not:") {
-            (true, inner.trim().to_string())
-        } else {
-            (false, pattern.clone())
-        };
-
-        // Expand shorthand patterns
-        let expanded = PromptMatch::expand_pattern(&effective_pattern);
+    // Split off negation prefixes, expand shorthands, and apply the anchor
+    let (negated, anchored): (Vec<bool>, Vec<String>) = patterns
+        .iter()
+        .map(|pattern| {
+            let (is_negated, effective_pattern) = match pattern.strip_prefix("not:") {
+                Some(inner) => (true, inner.trim()),
+                None => (false, pattern.as_str()),
+            };
+            let expanded = PromptMatch::expand_pattern(effective_pattern);
+            (is_negated, PromptMatch::apply_anchor(&expanded, anchor))
+        })
+        .unzip();
 
-        // Apply anchor
-        let anchored = PromptMatch::apply_anchor(&expanded, anchor);
+    // Several patterns: one RegexSet scan answers all of them. If any pattern
+    // is invalid, fall through so the others are still checked one by one.
+    let mut results = Vec::with_capacity(patterns.len());
+    if anchored.len() > 1 {
+        if let Ok(set) = get_or_compile_regex_set(&anchored, case_insensitive) {
+            let matches = set.matches(prompt);
+            results.extend(
+                negated
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &is_negated)| matches.matched(i) != is_negated),
+            );
+        }
+    }
 
-        // Compile and match
-        match get_or_compile_regex(&anchored, case_insensitive) {
-            Ok(regex) => {
-                let matched = regex.is_match(prompt);
-                // Apply negation
-                let result = if is_negated { !matched } else { matched };
-                results.push(result);
-            }
-            Err(e) => {
-                // Log warning and treat as non-match (fail-closed)
-                tracing::warn!(
-                    "Invalid prompt_match pattern '{}': {} - treating as non-match",
-                    pattern,
-                    e
-                );
-                results.push(false);
+    if results.is_empty() {
+        for ((pattern, is_negated), anchored) in patterns.iter().zip(&negated).zip(&anchored) {
+            // Compile and match
+            match get_or_compile_regex(anchored, case_insensitive) {
+                Ok(regex) => {
+                    // Apply negation
+                    results.push(regex.is_match(prompt) != *is_negated);
+                }
+                Err(e) => {
+                    // Log warning and treat as non-match (fail-closed)
+                    tracing::warn!(
+                        "Invalid prompt_match pattern '{}': {} - treating as non-match",
+                        pattern,
+                        e
+                    );
+                    results.push(false);
+                }
             }
         }
     }
@@ -274,21 +333,21 @@ fn validate_required_fields(rule: &Rule, event: &Event) -> bool {
 /// Extends build_eval_context with two custom functions:
 /// - get_field(path_string): Returns field value from tool_input JSON using dot notation
 /// - has_field(path_string): Returns boolean indicating field exists and is not null
-fn build_eval_context_with_custom_functions(event: &Event) -> HashMapContext<DefaultNumericTypes> {
+fn build_eval_context_with_custom_functions(event: &Event) -> EvalContext {
     use crate::models::dot_to_pointer;
 
     let mut ctx = build_eval_context(event);
 
-    // Clone tool_input for 'static lifetime in closures
-    let tool_input_for_get = event.tool_input.clone();
-    let tool_input_for_has = event.tool_input.clone();
+    // Share the context's tool_input for 'static lifetime in closures
+    let tool_input_for_get = ctx.tool_input.clone();
+    let tool_input_for_has = ctx.tool_input.clone();
 
     // Register get_field function
     let get_field_fn = Function::new(move |argument| {
         let path = argument.as_string()?;
         let pointer = dot_to_pointer(&path);
 
-        match &tool_input_for_get {
+        match tool_input_for_get.as_deref() {
             None => Ok(Value::String(String::new())),
             Some(input) => {
                 match input.pointer(&pointer) {
@@ -308,7 +367,7 @@ fn build_eval_context_with_custom_functions(event: &Event) -> HashMapContext<Def
         let path = argument.as_string()?;
         let pointer = dot_to_pointer(&path);
 
-        match &tool_input_for_has {
+        match tool_input_for_has.as_deref() {
             None => Ok(Value::Boolean(false)),
             Some(input) => match input.pointer(&pointer) {
                 None | Some(serde_json::Value::Null) => Ok(Value::Boolean(false)),
@@ -324,7 +383,239 @@ fn build_eval_context_with_custom_functions(event: &Event) -> HashMapContext<Def
     ctx
 }
 
-/// Execute an inline shell script with timeout protection
+/// Environment variables kept when a script sets `env_clear: true`
+///
+/// Only what's needed to locate interpreters and write temp files - nothing
+/// that commonly carries credentials.
+const ENV_CLEAR_PASSTHROUGH: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SYSTEMROOT",
+    "COMSPEC",
+    "PATHEXT",
+];
+
+/// Apply a script's configured environment to a command
+///
+/// With no configuration the command inherits the parent environment
+/// (backward compatible). Templated values are set as env values, never
+/// passed through a shell, so event content cannot inject commands.
+fn apply_script_environment(
+    command: &mut Command,
+    environment: Option<&ScriptEnvironment>,
+    event: &Event,
+) {
+    let Some(environment) = environment else {
+        return;
+    };
+
+    if environment.env_clear {
+        command.env_clear();
+        for name in ENV_CLEAR_PASSTHROUGH {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+    }
+
+    if environment.env.is_empty() {
+        return;
+    }
+
+    let event_value = serde_json::to_value(event).unwrap_or_default();
+    for (name, value) in &environment.env {
+        command.env(name, render_event_template(value, &event_value));
+    }
+}
+
+/// Render a script's configured arguments
+///
+/// Each templated argument is passed as a separate argv entry, never
+/// through a shell.
+fn script_args(environment: Option<&ScriptEnvironment>, event: &Event) -> Vec<String> {
+    let Some(environment) = environment.filter(|e| !e.args.is_empty()) else {
+        return Vec::new();
+    };
+
+    let event_value = serde_json::to_value(event).unwrap_or_default();
+    environment
+        .args
+        .iter()
+        .map(|arg| render_event_template(arg, &event_value))
+        .collect()
+}
+
+/// Resolve the working directory for a script or command
+///
+/// Defaults to the event's cwd so git/npm-based validators operate on the
+/// repository the agent is working in, not wherever the hook was spawned.
+/// A configured directory is templated and resolved relative to the event
+/// cwd. Returns None to inherit the hook process's directory (no event cwd,
+/// or the event cwd no longer exists).
+fn resolve_working_dir(configured: Option<&str>, event: &Event) -> Option<PathBuf> {
+    let event_cwd = event
+        .cwd
+        .as_deref()
+        .filter(|cwd| !cwd.is_empty())
+        .map(PathBuf::from);
+
+    match configured {
+        Some(template) => {
+            let event_value = serde_json::to_value(event).unwrap_or_default();
+            let dir = PathBuf::from(render_event_template(template, &event_value));
+            match event_cwd {
+                Some(base) if dir.is_relative() => Some(base.join(dir)),
+                _ => Some(dir),
+            }
+        }
+        None => event_cwd.filter(|cwd| cwd.is_dir()),
+    }
+}
+
+/// Render `{{path}}` placeholders with event fields (dot notation)
+///
+/// Strings are inserted as-is, other values as JSON; missing or null
+/// fields render as empty strings.
+fn render_event_template(template: &str, event_value: &serde_json::Value) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            // Unterminated placeholder - keep it literally
+            rendered.push_str(&rest[start..]);
+            return rendered;
+        };
+
+        let path = after[..end].trim();
+        match event_value.pointer(&dot_to_pointer(path)) {
+            Some(serde_json::Value::String(value)) => rendered.push_str(value),
+            Some(serde_json::Value::Null) | None => {}
+            Some(other) => rendered.push_str(&other.to_string()),
+        }
+        rest = &after[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Largest inline script passed on the command line
+///
+/// Linux caps a single argument at 128 KiB and Windows the whole command
+/// line at 32K characters; bigger scripts fall back to a file.
+const MAX_INLINE_CODE_BYTES: usize = if cfg!(windows) { 30_000 } else { 100_000 };
+
+/// Script written to a private file, removed on drop
+///
+/// Only needed for interpreters that can't take code on the command line
+/// (cmd.exe, unrecognised shebang interpreters), oversized scripts, and
+/// pinned validators. The file gets an
+/// unpredictable name and is created exclusively with owner-only
+/// permissions, so it can't be pre-created or swapped by another user.
+struct TempScript {
+    path: PathBuf,
+}
+
+impl TempScript {
+    fn create(code: &str, shell: Option<ScriptShell>) -> Result<Self> {
+        let content = match shell {
+            // cmd.exe would try to run a shebang line, and mis-parses LF-only batch files
+            Some(ScriptShell::Cmd) => code
+                .lines()
+                .filter(|line| !line.starts_with("#!"))
+                .collect::<Vec<_>>()
+                .join("\r\n"),
+            _ => code.to_string(),
+        };
+        let extension = shell.map_or("sh", ScriptShell::extension);
+        Self::write("inline", content.as_bytes(), extension)
+    }
+
+    fn write(kind: &str, content: &[u8], extension: &str) -> Result<Self> {
+        use std::io::Write;
+
+        let path =
+            std::env::temp_dir().join(format!("rulez-{kind}-{}.{extension}", uuid::Uuid::new_v4()));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o700);
+        }
+        let mut file = options
+            .open(&path)
+            .with_context(|| format!("Failed to create {kind} script file"))?;
+        let script = Self { path };
+        file.write_all(content)
+            .with_context(|| format!("Failed to write {kind} script file"))?;
+
+        Ok(script)
+    }
+}
+
+impl Drop for TempScript {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Build the command for an inline script
+///
+/// The code is passed on the interpreter's command line (`sh -c`,
+/// `python -c`, `pwsh -Command`) so no file touches disk. On Unix a plain
+/// string picks its interpreter from the shebang, and runs under `sh` without
+/// one. Returns the temp file backing the command when a file is unavoidable.
+fn inline_script_command(
+    inline_script: &InlineScript,
+    args: &[String],
+) -> Result<(Command, Option<TempScript>)> {
+    let code = inline_script.code();
+    let (shell, program, shebang_args) = match inline_script.resolve_shell() {
+        Some(shell) => (Some(shell), shell.program().to_string(), Vec::new()),
+        None => match ScriptShell::parse_shebang(code) {
+            Some((program, shebang_args)) => {
+                (ScriptShell::from_shebang(code), program, shebang_args)
+            }
+            None => (Some(ScriptShell::Sh), "sh".to_string(), Vec::new()),
+        },
+    };
+
+    let inline_args = shell
+        .filter(|_| code.len() <= MAX_INLINE_CODE_BYTES)
+        .and_then(|shell| shell.inline_args(code, args));
+    if let Some(inline_args) = inline_args {
+        let mut command = Command::new(program);
+        command.args(shebang_args).args(inline_args);
+        return Ok((command, None));
+    }
+
+    let script = TempScript::create(code, shell)?;
+    let mut command = match shell {
+        Some(shell) => {
+            let mut command = Command::new(program);
+            command
+                .args(shebang_args)
+                .args(shell.file_args())
+                .arg(&script.path);
+            command
+        }
+        // Unrecognised interpreter: let the kernel honour the shebang
+        None => Command::new(&script.path),
+    };
+    command.args(args);
+    Ok((command, Some(script)))
+}
+
+/// Execute an inline script with timeout protection
 ///
 /// The script receives event JSON on stdin and must exit with code 0 to allow the operation.
 /// Non-zero exit code or timeout causes the operation to be blocked (fail-closed).
@@ -334,7 +625,7 @@ fn build_eval_context_with_custom_functions(event: &Event) -> HashMapContext<Def
 /// - Ok(false): Script failed (non-zero exit or timeout)
 /// - Err: Script execution error
 async fn execute_inline_script(
-    script_content: &str,
+    inline_script: &InlineScript,
     event: &Event,
     rule: &Rule,
     config: &Config,
@@ -342,50 +633,47 @@ async fn execute_inline_script(
     use tokio::io::AsyncWriteExt;
 
     // Get timeout from rule metadata or config settings
-    let timeout_secs = rule
-        .metadata
-        .as_ref()
-        .map(|m| m.timeout)
-        .unwrap_or(config.settings.script_timeout);
-
-    // Create unique temp file name using process ID and timestamp
-    let unique_id = format!(
-        "{}-{}",
-        std::process::id(),
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos()
-    );
-    let script_path = std::env::temp_dir().join(format!("rulez-inline-{}.sh", unique_id));
+    let timeout_after = script_timeout(rule, config, ScriptKind::InlineScript);
 
-    // Write script to temp file
-    tokio::fs::write(&script_path, script_content)
-        .await
-        .context("Failed to write inline script to temp file")?;
+    // Bound concurrent children; waiting for a slot counts against the timeout
+    let limits = &config.settings.script_limits;
+    let Ok(_slot) = timeout(
+        timeout_after,
+        SCRIPT_SLOTS.acquire(limits.max_concurrent, rule.effective_priority()),
+    )
+    .await
+    else {
+        tracing::warn!(
+            "Inline script for rule '{}' timed out waiting for a script slot - blocking (fail-closed)",
+            rule.name
+        );
+        return Ok(false);
+    };
 
-    // Set permissions to 0o700 (owner read/write/execute only) on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = tokio::fs::metadata(&script_path).await?.permissions();
-        perms.set_mode(0o700);
-        tokio::fs::set_permissions(&script_path, perms).await?;
-    }
-
-    // Execute script directly so the kernel honours any shebang line.
-    // The file already has 0o700 permissions. Scripts without a shebang
-    // will be executed by the system's default shell (typically /bin/sh).
-    // Use Stdio::null() for stdout/stderr since we only check exit code.
-    // Piped handles that are never drained cause "Broken pipe" on Linux.
-    let mut command = Command::new(&script_path);
+    let environment = inline_script.environment();
+    let (mut command, _temp_script) =
+        inline_script_command(inline_script, &script_args(environment, event))?;
+    apply_script_environment(&mut command, environment, event);
+    limits.apply(&mut command);
+    if let Some(dir) = resolve_working_dir(environment.and_then(|e| e.cwd.as_deref()), event) {
+        command.current_dir(dir);
+    }
+    // Only the exit code matters; stderr is drained to recognize
+    // out-of-memory failures
     command.stdout(std::process::Stdio::null());
-    command.stderr(std::process::Stdio::null());
+    command.stderr(std::process::Stdio::piped());
     command.stdin(std::process::Stdio::piped());
 
     let mut child = command
         .spawn()
         .context("Failed to spawn inline script process")?;
+    let containment = match limits.contain(&child) {
+        Ok(containment) => containment,
+        Err(e) => {
+            child.kill().await.ok();
+            return Err(e).context("Failed to apply resource limits to inline script");
+        }
+    };
 
     // Serialize event to JSON and write to stdin
     let event_json = serde_json::to_string(event)?;
@@ -395,8 +683,6 @@ async fn execute_inline_script(
             // all input (e.g., `exit 0` without consuming stdin). On Linux
             // this surfaces as EPIPE; on macOS it's typically silent.
             if e.kind() != std::io::ErrorKind::BrokenPipe {
-                // Clean up temp file before returning error
-                tokio::fs::remove_file(&script_path).await.ok();
                 return Err(e.into());
             }
         }
@@ -405,60 +691,144 @@ async fn execute_inline_script(
     }
 
     // Wait for script with timeout
-    let wait_result = timeout(Duration::from_secs(timeout_secs as u64), child.wait()).await;
+    let wait_result = timeout(
+        timeout_after,
+        wait_with_limited_output(&mut child, limits.max_output_bytes, None),
+    )
+    .await;
 
     match wait_result {
-        Ok(Ok(status)) => {
-            let success = status.success();
+        Ok(Ok(output)) => {
+            // Killed for exceeding a resource limit - fail closed with the reason
+            limits.check_exit(&output, &containment)?;
+
+            let success = output.status.success();
 
             if !success {
                 tracing::warn!(
                     "Inline script for rule '{}' failed with exit code {}",
                     rule.name,
-                    status.code().unwrap_or(-1),
+                    output.status.code().unwrap_or(-1),
                 );
             }
 
-            // Clean up temp file
-            tokio::fs::remove_file(&script_path).await.ok();
-
             Ok(success)
         }
-        Ok(Err(e)) => {
-            tokio::fs::remove_file(&script_path).await.ok();
-            Err(e.into())
-        }
+        Ok(Err(e)) => Err(e),
         Err(_) => {
             // Timeout — kill the child process and reap it
             child.kill().await.ok();
             child.wait().await.ok();
 
             tracing::warn!(
-                "Inline script for rule '{}' timed out after {}s - blocking (fail-closed)",
+                "Inline script for rule '{}' timed out after {:?} - blocking (fail-closed)",
                 rule.name,
-                timeout_secs
+                timeout_after
             );
 
-            // Clean up temp file
-            tokio::fs::remove_file(&script_path).await.ok();
-
             Ok(false) // Timeout = fail-closed
         }
     }
 }
 
 /// Process a hook event and return the appropriate response
-pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<Response> {
+pub async fn process_event(
+    event: Event,
+    debug_config: &DebugConfig,
+) -> Result<Response, RulezError> {
+    process_dual_fire_event(event, &[], debug_config).await
+}
+
+/// Process an agent hook that maps to several RuleZ event types (dual-fire)
+///
+/// Rules for the event's own type and for each additional type are
+/// evaluated in one pass and merged into one response, the first block
+/// winning. The event is logged, tracked and paired with its `PreToolUse`
+/// decision once, under its own type.
+#[allow(clippy::too_many_lines)]
+pub async fn process_dual_fire_event(
+    mut event: Event,
+    additional_event_types: &[EventType],
+    debug_config: &DebugConfig,
+) -> Result<Response, RulezError> {
     let start_time = std::time::Instant::now();
 
+    // Kill switch: allow without evaluating, but leave a trace in the log
+    if let Some(reason) = crate::kill_switch::bypass_reason(chrono::Utc::now()) {
+        tracing::info!(
+            "RuleZ is disabled ({}); allowing {}",
+            reason,
+            event.hook_event_name
+        );
+        let _ = log_entry(crate::kill_switch::log_entry(&event, &reason)).await;
+        return Ok(Response::allow());
+    }
+
+    // Pair PostToolUse with the PreToolUse decision for the same tool call
+    if matches!(
+        event.hook_event_name,
+        EventType::PostToolUse | EventType::PostToolUseFailure
+    ) {
+        event.pre_tool_use = event
+            .tool_use_id
+            .as_deref()
+            .and_then(crate::session::take_tool_use);
+    }
+
     // Load configuration using the event's cwd (sent by Claude Code) for project-level config
-    let config = Config::load(event.cwd.as_ref().map(|p| Path::new(p.as_str())))?;
+    let config_started = std::time::Instant::now();
+    let mut config = tracing::debug_span!("config_load")
+        .in_scope(|| Config::load(event.cwd.as_ref().map(|p| Path::new(p.as_str()))))?;
+    event.identity = Some(crate::identity::resolve(
+        &event,
+        config.settings.identity.as_ref(),
+    ));
+    let profile = crate::profiles::apply(&mut config, &event);
+    let config_load_time = config_started.elapsed();
+
+    let dual_events: Vec<Event> = additional_event_types
+        .iter()
+        .filter(|event_type| **event_type != event.hook_event_name)
+        .map(|event_type| Event {
+            hook_event_name: *event_type,
+            ..event.clone()
+        })
+        .collect();
+    let mut evaluation = evaluate_matched_policy(&event, &config, debug_config).await?;
+    for dual_event in &dual_events {
+        if !evaluation.response.continue_ {
+            break;
+        }
+        evaluation.merge(evaluate_matched_policy(dual_event, &config, debug_config).await?);
+    }
+    // Only when no type matched a rule, so a dual-fire event gets the same
+    // decision as the event sent once
+    for default_event in std::iter::once(&event).chain(&dual_events) {
+        evaluation.apply_default_policy(default_event, &config);
+    }
+    let PolicyEvaluation {
+        matched_rules,
+        mut response,
+        rule_evaluations,
+        rule_timings,
+        violation,
+        waivers,
+    } = evaluation;
 
-    // Evaluate rules (with optional debug tracking)
-    let (matched_rules, response, rule_evaluations) =
-        evaluate_rules(&event, &config, debug_config).await?;
+    // Stop gating: a block sends the agent back to work, up to a limit
+    if event.hook_event_name.is_stop() {
+        response = limit_stop_blocks(response, &event, config.settings.max_stop_blocks);
+    }
+
+    finalize_hook_output(&mut response, event.hook_event_name);
 
     let processing_time = start_time.elapsed().as_millis() as u64;
+    let over_budget = check_latency_budget(
+        processing_time,
+        config.settings.latency_budget_ms,
+        config_load_time,
+        &rule_timings,
+    );
 
     // Build enhanced logging fields
     let event_details = EventDetails::extract(&event);
@@ -471,21 +841,63 @@ pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<R
     // Determine decision based on response and mode
     let decision = primary_mode.map(|m| determine_decision(&response, m));
 
+    let rules_matched: Vec<String> = violation
+        .as_ref()
+        .map(|_| crate::protection::SELF_PROTECTION_RULE.to_string())
+        .into_iter()
+        .chain(matched_rules.iter().map(|r| r.name.clone()))
+        .collect();
+    let outcome = match response.continue_ {
+        true if response.context.is_some() => Outcome::Inject,
+        true => Outcome::Allow,
+        false => Outcome::Block,
+    };
+
+    // Remember the decision so PostToolUse rules and logs can refer to it
+    if event.hook_event_name == EventType::PreToolUse {
+        if let Err(e) = crate::session::record_tool_use(&event, &rules_matched, &outcome) {
+            tracing::warn!("Failed to record tool use: {}", e);
+        }
+    }
+
+    // Session state (opt-in): summary is emitted on SessionEnd
+    let session_summary = match config.settings.session_summary {
+        Some(ref summary_config) => {
+            track_session(&event, &rules_matched, &outcome, summary_config).await
+        }
+        None => None,
+    };
+
+    // Anomaly detection (opt-in) looks at blocked events only
+    let anomalies = match config.settings.anomaly_detection {
+        Some(ref anomaly_config) if outcome == Outcome::Block => {
+            detect_anomalies(&event, rules_matched.first(), anomaly_config)
+        }
+        _ => Vec::new(),
+    };
+
+    // Deprecated rules still run; each match is noted so owners can migrate
+    let deprecations: Vec<String> = matched_rules
+        .iter()
+        .filter_map(|rule| rule.deprecation_notice())
+        .collect();
+    for notice in &deprecations {
+        tracing::warn!("{}", notice);
+    }
+
     // Log the event with enhanced fields
-    let entry = LogEntry {
+    let mut entry = LogEntry {
+        schema_version: LOG_SCHEMA_VERSION,
         timestamp: event.timestamp,
         event_type: format!("{:?}", event.hook_event_name),
         session_id: event.session_id.clone(),
         tool_name: event.tool_name.clone(),
-        rules_matched: matched_rules.iter().map(|r| r.name.clone()).collect(),
-        outcome: match response.continue_ {
-            true if response.context.is_some() => Outcome::Inject,
-            true => Outcome::Allow,
-            false => Outcome::Block,
-        },
+        rules_matched,
+        outcome,
         timing: LogTiming {
             processing_ms: processing_time,
             rules_evaluated: config.enabled_rules().len(),
+            over_budget,
         },
         metadata: Some(LogMetadata {
             injected_files: response
@@ -493,16 +905,40 @@ pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<R
                 .as_ref()
                 .map(|_| vec!["injected".to_string()]),
             validator_output: None,
+            context_tokens: response
+                .context
+                .as_deref()
+                .map(context_budget::estimate_tokens),
+            context_blocks: (!response.context_blocks.is_empty())
+                .then(|| response.context_blocks.clone()),
         }),
         // Enhanced logging fields (CRD-001)
         event_details: Some(event_details),
         response: Some(response_summary),
-        raw_event: if debug_config.enabled {
-            Some(serde_json::to_value(&event).unwrap_or_default())
+        raw_event: if logs_raw_event(debug_config.enabled, &matched_rules) {
+            let mut raw_event = serde_json::to_value(&event).unwrap_or_default();
+            // Rules that scan for secrets keep them out of the audit log too
+            if matched_rules.iter().any(|rule| {
+                rule.actions
+                    .steps()
+                    .any(|actions| actions.scan_secrets == Some(true))
+            }) {
+                crate::secrets::redact_value(&mut raw_event);
+            }
+            if matched_rules.iter().any(|rule| {
+                rule.actions.steps().any(|actions| {
+                    actions
+                        .scan_pii
+                        .is_some_and(|scan| scan.severities().is_some())
+                })
+            }) {
+                crate::pii::redact_value(&mut raw_event);
+            }
+            Some(raw_event)
         } else {
             None
         },
-        rule_evaluations: if debug_config.enabled {
+        rule_evaluations: if logs_rule_evaluations(debug_config.enabled, &matched_rules) {
             Some(rule_evaluations)
         } else {
             None
@@ -511,15 +947,46 @@ pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<R
         mode: primary_mode,
         priority: primary_priority,
         decision,
+        bypass_reason: None,
+        dry_run: debug_config.dry_run.then_some(true),
         governance: primary_governance,
         trust_level,
+        session_summary,
+        tool_use_id: event.tool_use_id.clone(),
+        pre_tool_use: event.pre_tool_use.clone(),
+        rule_timings: rule_timings.to_log(start_time),
+        sample_rate: None,
+        prev_hash: None,
+        signature: None,
+        anomaly: None,
+        waivers: (!waivers.is_empty()).then_some(waivers),
+        profile,
+        deprecations: (!deprecations.is_empty()).then_some(deprecations),
     };
 
     // Log asynchronously (don't fail the response if logging fails)
-    let _ = log_entry(entry).await;
+    if !log_suppressed(&matched_rules, &entry)
+        && config.settings.logging.sampling.sample(&mut entry)
+    {
+        let _ = log_entry(entry).await;
+    }
+    for anomaly in anomalies {
+        let _ = log_entry(crate::anomaly::log_entry(&event, anomaly)).await;
+    }
+
+    // Dry run: everything above ran and was logged, but the agent is never held up
+    if debug_config.dry_run {
+        if !response.continue_ {
+            tracing::info!(
+                "Dry run: would have blocked {} ({})",
+                event.hook_event_name,
+                response.reason.as_deref().unwrap_or_default()
+            );
+        }
+        response = Response::allow();
+    }
 
     // Add timing to response
-    let mut response = response;
     response.timing = Some(Timing {
         processing_ms: processing_time,
         rules_evaluated: config.enabled_rules().len(),
@@ -528,88 +995,963 @@ pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<R
     Ok(response)
 }
 
-/// Extract governance data from matched rules
-/// Returns (mode, priority, governance, trust_level) from the primary (first) matched rule
-fn extract_governance_data(
-    matched_rules: &[&Rule],
-) -> (
-    Option<PolicyMode>,
-    Option<i32>,
-    Option<GovernanceMetadata>,
-    Option<TrustLevel>,
-) {
-    if let Some(primary) = matched_rules.first() {
-        let mode = Some(primary.effective_mode());
-        let priority = Some(primary.effective_priority());
-        let governance = primary.governance.clone();
-        let trust_level = primary.actions.trust_level();
-        (mode, priority, governance, trust_level)
-    } else {
-        (None, None, None, None)
-    }
+/// Rule evaluation results for one event, before logging and session tracking
+pub(crate) struct PolicyEvaluation<'a> {
+    pub matched_rules: Vec<&'a Rule>,
+    pub response: Response,
+    pub rule_evaluations: Vec<RuleEvaluation>,
+    rule_timings: RuleTimings<'a>,
+    /// Self-protection violation, if any
+    pub violation: Option<String>,
+    /// Waivers that suppressed otherwise-matching rules
+    pub waivers: Vec<crate::waivers::AppliedWaiver>,
 }
 
-/// Build evaluation context for enabled_when expressions
-///
-/// Creates a context with:
-/// - env_* variables for all environment variables
-/// - tool_name: the tool being used (or empty string)
-/// - event_type: the hook event type
-fn build_eval_context(event: &Event) -> HashMapContext<DefaultNumericTypes> {
-    let mut ctx = HashMapContext::new();
+impl PolicyEvaluation<'_> {
+    /// Fold in the evaluation of a dual-fire event type
+    ///
+    /// Responses merge as rule responses do; a rule matched under both types
+    /// is listed once.
+    fn merge(&mut self, other: Self) {
+        self.response = merge_responses(
+            std::mem::replace(&mut self.response, Response::allow()),
+            other.response,
+        );
+        for rule in other.matched_rules {
+            if !self.matched_rules.iter().any(|r| r.name == rule.name) {
+                self.matched_rules.push(rule);
+            }
+        }
+        self.rule_evaluations.extend(other.rule_evaluations);
+        self.rule_timings.extend(&other.rule_timings);
+        self.violation = self.violation.take().or(other.violation);
+        for waiver in other.waivers {
+            if !self.waivers.contains(&waiver) {
+                self.waivers.push(waiver);
+            }
+        }
+    }
 
-    // Add environment variables with env_ prefix
-    for (key, value) in std::env::vars() {
-        let var_name = format!("env_{}", key);
-        ctx.set_value(var_name, Value::String(value)).ok();
+    /// Let `settings.default_policy` decide, if nothing matched the event
+    fn apply_default_policy(&mut self, event: &Event, config: &Config) {
+        if self.matched_rules.is_empty() && self.violation.is_none() && self.response.continue_ {
+            self.response = apply_default_policy(
+                std::mem::replace(&mut self.response, Response::allow()),
+                event,
+                config,
+            );
+        }
     }
+}
 
-    // Add tool name (empty string if none)
-    let tool_name = event.tool_name.as_deref().unwrap_or("").to_string();
-    ctx.set_value("tool_name".into(), Value::String(tool_name))
-        .ok();
+/// Evaluate an event against a loaded config
+///
+/// Runs self-protection, the rules, `unknown_tool_policy`, `default_policy`
+/// and prompt rewrites. Nothing is logged and no session state is touched.
+/// [`process_event`] evaluates the same steps per dual-fire type and applies
+/// `default_policy` once, after merging.
+#[allow(dead_code)] // Used by the embeddable `RuleEngine`
+pub(crate) async fn evaluate_policy<'a>(
+    event: &'a Event,
+    config: &'a Config,
+    debug_config: &DebugConfig,
+) -> Result<PolicyEvaluation<'a>> {
+    let mut evaluation = evaluate_matched_policy(event, config, debug_config).await?;
+    evaluation.apply_default_policy(event, config);
+    Ok(evaluation)
+}
 
-    // Add event type
-    ctx.set_value(
-        "event_type".into(),
-        Value::String(event.hook_event_name.to_string()),
-    )
-    .ok();
+/// [`evaluate_policy`] without `default_policy`, for one type of a
+/// dual-fire event
+async fn evaluate_matched_policy<'a>(
+    event: &'a Event,
+    config: &'a Config,
+    debug_config: &DebugConfig,
+) -> Result<PolicyEvaluation<'a>> {
+    // Built-in self-protection runs before user rules
+    let protection_mode = config.settings.self_protection.mode;
+    let violation = crate::protection::check(event, config, project_dir(event).as_deref());
 
-    // Add prompt text (if available - primarily for UserPromptSubmit events)
-    if let Some(ref prompt) = event.prompt {
-        ctx.set_value("prompt".into(), Value::String(prompt.clone()))
-            .ok();
+    // Evaluate rules (with optional debug tracking)
+    let (matched_rules, mut response, rule_evaluations, rule_timings) = match violation {
+        Some(ref reason) if protection_mode == PolicyMode::Enforce => (
+            Vec::new(),
+            Response::block(reason.clone()),
+            Vec::new(),
+            RuleTimings::default(),
+        ),
+        _ => {
+            let span = tracing::debug_span!("evaluate_rules", event = %event.hook_event_name);
+            evaluate_rules(event, config, debug_config)
+                .instrument(span)
+                .await?
+        }
+    };
+    if let Some(ref reason) = violation {
+        tracing::warn!("{}", reason);
+        if protection_mode == PolicyMode::Warn {
+            let warning = Response::inject(format!(
+                "[WARNING] {}\nSelf-protection is in 'warn' mode - operation will proceed.",
+                reason
+            ));
+            response = merge_responses(response, warning);
+        }
     }
 
-    // Expose tool_input fields with tool_input_ prefix for use in enabled_when expressions
-    // Supports string, bool, and number (f64) field values. Arrays, objects, and null are skipped.
-    // Example: enabled_when: "tool_input_command =~ \"git push\""
-    if let Some(ref tool_input) = event.tool_input {
-        if let Some(obj) = tool_input.as_object() {
-            for (key, val) in obj {
-                let var_name = format!("tool_input_{}", key);
-                match val {
-                    serde_json::Value::String(s) => {
-                        ctx.set_value(var_name, Value::String(s.clone())).ok();
-                    }
-                    serde_json::Value::Bool(b) => {
-                        ctx.set_value(var_name, Value::Boolean(*b)).ok();
-                    }
-                    serde_json::Value::Number(n) => {
-                        if let Some(f) = n.as_f64() {
-                            ctx.set_value(var_name, Value::Float(f)).ok();
-                        }
-                    }
-                    _ => {} // Arrays, objects, null -- not supported by evalexpr
-                }
+    // Tools RuleZ has never heard of (settings.unknown_tool_policy)
+    if let Some(tool) = unknown_tool(event, config) {
+        let reason = format!("Unknown tool '{}'", tool);
+        match config.settings.unknown_tool_policy {
+            UnknownPolicy::Allow => {}
+            UnknownPolicy::Warn => {
+                tracing::warn!("{}", reason);
+                let warning = Response::inject(format!(
+                    "[WARNING] {}\nunknown_tool_policy is 'warn' - operation will proceed.",
+                    reason
+                ));
+                response = merge_responses(response, warning);
+            }
+            UnknownPolicy::Block => {
+                tracing::warn!("{}", reason);
+                response = Response::block(format!(
+                    "{} blocked by unknown_tool_policy. Add it to settings.known_tools to allow it.",
+                    reason
+                ));
             }
         }
     }
 
+    // Prompt rewriting: matched rules edit the prompt in priority order
+    if event.hook_event_name == EventType::UserPromptSubmit {
+        response = apply_prompt_rewrites(response, event, &matched_rules);
+    }
+
+    Ok(PolicyEvaluation {
+        matched_rules,
+        response,
+        rule_evaluations,
+        rule_timings,
+        violation,
+        waivers: applied_waivers(event, config),
+    })
+}
+
+/// Compare total processing time against `settings.latency_budget_ms`
+///
+/// Returns the overrun details for the log entry (and warns) when the budget
+/// is exceeded. A budget of 0 disables the check.
+fn check_latency_budget(
+    processing_ms: u64,
+    budget_ms: u64,
+    config_load: Duration,
+    rule_timings: &RuleTimings<'_>,
+) -> Option<LatencyOverrun> {
+    if budget_ms == 0 || processing_ms <= budget_ms {
+        return None;
+    }
+
+    let slowest = rule_timings.slowest();
+    let overrun = LatencyOverrun {
+        budget_ms,
+        config_load_us: u64::try_from(config_load.as_micros()).unwrap_or(u64::MAX),
+        slowest_rule: slowest.map(|(name, _)| name.to_string()),
+        slowest_rule_us: slowest.map_or(0, |(_, elapsed)| {
+            u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX)
+        }),
+    };
+    if let Some(ref rule) = overrun.slowest_rule {
+        tracing::warn!(
+            "Hook processing took {}ms (budget {}ms); slowest rule '{}' took {}ms, config load took {}ms",
+            processing_ms,
+            budget_ms,
+            rule,
+            overrun.slowest_rule_us / 1000,
+            overrun.config_load_us / 1000
+        );
+    } else {
+        tracing::warn!(
+            "Hook processing took {}ms (budget {}ms); config load took {}ms",
+            processing_ms,
+            budget_ms,
+            overrun.config_load_us / 1000
+        );
+    }
+    Some(overrun)
+}
+
+/// Claude Code's built-in tools, known to `unknown_tool_policy`
+pub(crate) const KNOWN_TOOLS: &[&str] = &[
+    "Agent",
+    "AskUserQuestion",
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "ListMcpResourcesTool",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "ReadMcpResourceTool",
+    "SlashCommand",
+    "Skill",
+    "Task",
+    "TodoRead",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// The event's tool name, if RuleZ doesn't recognize it
+///
+/// Known tools are Claude Code's built-ins, MCP tools (`mcp__*`), tools
+/// named in any rule's `tools` matcher, and `settings.known_tools`.
+fn unknown_tool<'a>(event: &'a Event, config: &Config) -> Option<&'a str> {
+    let tool = event.tool_name.as_deref()?;
+    let known = KNOWN_TOOLS.contains(&tool)
+        || tool.starts_with("mcp__")
+        || config.settings.known_tools.iter().any(|t| t == tool)
+        || config.rules.iter().any(|rule| {
+            rule.matchers
+                .tools
+                .as_ref()
+                .is_some_and(|tools| tools.iter().any(|t| t == tool))
+        });
+    (!known).then_some(tool)
+}
+
+/// Name of the event if it isn't a hook event RuleZ recognizes
+///
+/// Returns `None` for known events and for payloads without an event name
+/// (those fail deserialization as before).
+pub fn unknown_event_name(event: &serde_json::Value) -> Option<&str> {
+    let name = event
+        .get("hook_event_name")
+        .or_else(|| event.get("event_type"))?
+        .as_str()?;
+    serde_json::from_value::<EventType>(serde_json::Value::String(name.to_string()))
+        .is_err()
+        .then_some(name)
+}
+
+/// The `settings.default_policy` entry for an event, with its key
+///
+/// `PreToolUse` events look up their tool name, then the event type, then
+/// `*`. Other events look up their event type only.
+fn default_policy_entry<'a>(
+    policy: &'a std::collections::HashMap<String, PermissionDecision>,
+    event: &Event,
+) -> Option<(&'a str, PermissionDecision)> {
+    let event_type = event.hook_event_name.to_string();
+    let keys: Vec<&str> = match event.tool_name.as_deref() {
+        Some(tool) if event.hook_event_name == EventType::PreToolUse => {
+            vec![tool, &event_type, "*"]
+        }
+        _ => vec![&event_type],
+    };
+    keys.into_iter().find_map(|key| {
+        policy
+            .get_key_value(key)
+            .map(|(key, decision)| (key.as_str(), *decision))
+    })
+}
+
+/// Apply `settings.default_policy` to an event no rule matched
+///
+/// `allow` leaves the response as it is, so the client's own permission
+/// handling still applies. `ask` only has an effect on `PreToolUse`.
+fn apply_default_policy(mut response: Response, event: &Event, config: &Config) -> Response {
+    let Some((key, decision)) = default_policy_entry(&config.settings.default_policy, event) else {
+        return response;
+    };
+    let target = match event.tool_name.as_deref() {
+        Some(tool) if event.hook_event_name == EventType::PreToolUse => format!("tool '{tool}'"),
+        _ => format!("event '{}'", event.hook_event_name),
+    };
+    let reason = format!("No rule matched {target}; default_policy '{key}' is '{decision}'");
+    match decision {
+        PermissionDecision::Allow => {}
+        PermissionDecision::Deny => {
+            tracing::info!("{}", reason);
+            return Response::block(reason);
+        }
+        PermissionDecision::Ask => {
+            if event.hook_event_name == EventType::PreToolUse {
+                tracing::info!("{}", reason);
+                let mut output = HookSpecificOutput::new(event.hook_event_name);
+                output.permission_decision = Some(PermissionDecision::Ask);
+                output.permission_decision_reason = Some(reason);
+                response.hook_specific_output = Some(output);
+            }
+        }
+    }
+    response
+}
+
+/// Answer a hook event RuleZ doesn't recognize (settings.unknown_event_policy)
+///
+/// No rules are evaluated; the decision is logged with the raw event name.
+pub async fn process_unknown_event(
+    event: &serde_json::Value,
+    name: &str,
+    debug_config: &DebugConfig,
+) -> Result<Response, RulezError> {
+    let session_id = event
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let tool_name = event
+        .get("tool_name")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    if let Some(reason) = crate::kill_switch::bypass_reason(chrono::Utc::now()) {
+        let entry =
+            crate::kill_switch::bypassed_entry(name.to_string(), session_id, tool_name, &reason);
+        let _ = log_entry(entry).await;
+        return Ok(Response::allow());
+    }
+
+    let cwd = event.get("cwd").and_then(|v| v.as_str());
+    let config = Config::load(cwd.map(Path::new))?;
+    let reason = format!("Unknown hook event '{}'", name);
+
+    let response = match config.settings.unknown_event_policy {
+        UnknownPolicy::Allow => Response::allow(),
+        UnknownPolicy::Warn => {
+            tracing::warn!("{}", reason);
+            let mut response = Response::allow();
+            response.system_message = Some(format!(
+                "RuleZ: {} (unknown_event_policy is 'warn')",
+                reason
+            ));
+            response
+        }
+        UnknownPolicy::Block => {
+            tracing::warn!("{}", reason);
+            Response::block(format!("{} blocked by unknown_event_policy", reason))
+        }
+    };
+
+    let entry = LogEntry {
+        schema_version: LOG_SCHEMA_VERSION,
+        timestamp: chrono::Utc::now(),
+        event_type: name.to_string(),
+        session_id,
+        tool_name,
+        rules_matched: Vec::new(),
+        outcome: if response.continue_ {
+            Outcome::Allow
+        } else {
+            Outcome::Block
+        },
+        timing: LogTiming {
+            processing_ms: 0,
+            rules_evaluated: 0,
+            over_budget: None,
+        },
+        metadata: None,
+        event_details: None,
+        response: Some(ResponseSummary::from_response(&response)),
+        raw_event: None,
+        rule_evaluations: None,
+        mode: None,
+        priority: None,
+        decision: None,
+        bypass_reason: None,
+        dry_run: debug_config.dry_run.then_some(true),
+        governance: None,
+        trust_level: None,
+        session_summary: None,
+        tool_use_id: None,
+        pre_tool_use: None,
+        rule_timings: Vec::new(),
+        sample_rate: None,
+        prev_hash: None,
+        signature: None,
+        anomaly: None,
+        waivers: None,
+        profile: None,
+        deprecations: None,
+    };
+    let _ = log_entry(entry).await;
+
+    if debug_config.dry_run {
+        return Ok(Response::allow());
+    }
+    Ok(response)
+}
+
+/// Cap consecutive Stop/SubagentStop blocks for a session
+///
+/// Blocking a Stop event keeps the agent working, so a condition it can't
+/// meet would loop forever. Attempts are counted while `stop_hook_active`
+/// is set; once `max_blocks` is reached the stop is allowed with a
+/// `systemMessage` explaining why.
+fn limit_stop_blocks(response: Response, event: &Event, max_blocks: u32) -> Response {
+    let attempts = if event.stop_hook_active == Some(true) {
+        crate::session::stop_blocks(&event.session_id)
+    } else {
+        0
+    };
+
+    let (response, count) = if response.continue_ {
+        (response, 0)
+    } else if max_blocks > 0 && attempts >= max_blocks {
+        let reason = response.reason.unwrap_or_default();
+        tracing::warn!(
+            "Allowing stop after {} blocked attempts: {}",
+            attempts,
+            reason
+        );
+        let mut allowed = Response::allow();
+        allowed.system_message = Some(format!(
+            "RuleZ allowed the agent to stop after {} blocked attempts: {}",
+            attempts, reason
+        ));
+        (allowed, 0)
+    } else {
+        (response, attempts + 1)
+    };
+
+    if let Err(e) = crate::session::set_stop_blocks(&event.session_id, count) {
+        tracing::warn!("Failed to record stop attempts: {}", e);
+    }
+    response
+}
+
+/// Update session state, sending the summary to the webhook on SessionEnd
+///
+/// Failures are logged and never affect the hook response.
+async fn track_session(
+    event: &Event,
+    rules_matched: &[String],
+    outcome: &Outcome,
+    summary_config: &crate::session::SessionSummaryConfig,
+) -> Option<crate::session::SessionSummary> {
+    let summary = crate::session::track(event, rules_matched, outcome)
+        .map_err(|e| tracing::warn!("Failed to update session state: {}", e))
+        .ok()??;
+    if let Err(e) = crate::session::send_webhook(summary_config, &event.session_id, &summary).await
+    {
+        tracing::warn!("Session summary webhook failed: {}", e);
+    }
+    Some(summary)
+}
+
+/// Record a blocked event, sending any anomaly it triggers to the webhook
+/// and filing a ticket when the rule keeps blocking
+///
+/// Failures are logged and never affect the hook response.
+fn detect_anomalies(
+    event: &Event,
+    rule: Option<&String>,
+    anomaly_config: &crate::anomaly::AnomalyConfig,
+) -> Vec<crate::anomaly::Anomaly> {
+    let findings =
+        match crate::anomaly::record_block(anomaly_config, event, rule.map(String::as_str)) {
+            Ok(findings) => findings,
+            Err(e) => {
+                tracing::warn!("Failed to update anomaly history: {}", e);
+                return Vec::new();
+            }
+        };
+    for anomaly in &findings.anomalies {
+        tracing::warn!("Anomaly: {}", anomaly.describe());
+        if let Err(e) = crate::anomaly::send_webhook(anomaly_config, &event.session_id, anomaly) {
+            tracing::warn!("Anomaly webhook failed: {}", e);
+        }
+    }
+    if let (Some(ticket), Some(ticket_config)) = (findings.ticket, &anomaly_config.ticket) {
+        file_ticket(ticket_config, &ticket, event.timestamp);
+    }
+    findings.anomalies
+}
+
+/// File a ticket for a rule over its threshold, unless one was filed recently
+fn file_ticket(
+    config: &crate::ticketing::TicketConfig,
+    ticket: &crate::ticketing::Ticket,
+    now: chrono::DateTime<chrono::Utc>,
+) {
+    match crate::ticketing::claim(config, &ticket.rule, now) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::warn!("Failed to update ticket history: {}", e);
+            return;
+        }
+    }
+    tracing::warn!("Filing ticket: {}", ticket.title());
+    if let Err(e) = crate::ticketing::file(config, ticket) {
+        tracing::warn!("Failed to file ticket for rule '{}': {}", ticket.rule, e);
+    }
+}
+
+/// Whether a log entry should carry the raw event
+///
+/// `log: full` on any matched rule always captures it; otherwise
+/// `log: minimal` on a matched rule keeps it out even with debug logging.
+fn logs_raw_event(debug: bool, matched_rules: &[&Rule]) -> bool {
+    let any_rule = |level| matched_rules.iter().any(|rule| rule.log == Some(level));
+    any_rule(RuleLogLevel::Full)
+        || any_rule(RuleLogLevel::Debug)
+        || (debug && !any_rule(RuleLogLevel::Minimal) && !any_rule(RuleLogLevel::Off))
+}
+
+/// Whether a log entry should carry the rule evaluations: with debug
+/// logging, or when a matched rule has `log: debug`
+fn logs_rule_evaluations(debug: bool, matched_rules: &[&Rule]) -> bool {
+    debug
+        || matched_rules
+            .iter()
+            .any(|rule| rule.log == Some(RuleLogLevel::Debug))
+}
+
+/// Whether to record per-matcher results for a rule (`log: debug` or debug logging)
+fn traces_matchers(debug: bool, rule: &Rule) -> bool {
+    debug || rule.log == Some(RuleLogLevel::Debug)
+}
+
+/// Whether to skip logging an event: every matched rule has `log: off`
+/// and the event was neither blocked, warned nor waived
+fn log_suppressed(matched_rules: &[&Rule], entry: &LogEntry) -> bool {
+    !matched_rules.is_empty()
+        && matched_rules
+            .iter()
+            .all(|rule| rule.log == Some(RuleLogLevel::Off))
+        && entry.outcome != Outcome::Block
+        && entry.waivers.is_none()
+        && !matches!(entry.decision, Some(Decision::Blocked | Decision::Warned))
+}
+
+/// Extract governance data from matched rules
+/// Returns (mode, priority, governance, trust_level) from the primary (first) matched rule
+fn extract_governance_data(
+    matched_rules: &[&Rule],
+) -> (
+    Option<PolicyMode>,
+    Option<i32>,
+    Option<GovernanceMetadata>,
+    Option<TrustLevel>,
+) {
+    if let Some(primary) = matched_rules.first() {
+        let mode = Some(primary.effective_mode());
+        let priority = Some(primary.effective_priority());
+        let governance = primary.governance.clone();
+        let trust_level = primary.actions.trust_level();
+        (mode, priority, governance, trust_level)
+    } else {
+        (None, None, None, None)
+    }
+}
+
+/// Environment variables as `env_*` expression values, captured once per process
+///
+/// Rule expressions read the environment through [`EvalContext`], which
+/// looks names up here on demand instead of copying every variable into
+/// each rule's context.
+static ENV_SNAPSHOT: LazyLock<std::collections::HashMap<String, Value<DefaultNumericTypes>>> =
+    LazyLock::new(|| {
+        std::env::vars()
+            .map(|(key, value)| (format!("env_{}", key), Value::String(value)))
+            .collect()
+    });
+
+/// Expression context for `enabled_when` and `validate_expr`
+///
+/// Event values and functions live in a `HashMapContext`; `env_*`
+/// identifiers that aren't set there resolve against [`ENV_SNAPSHOT`].
+/// `git_branch` reads `.git/HEAD`, so it is only resolved the first time an
+/// expression uses it. `tool_input` is copied once per context and shared by
+/// every function that reads it (`get_field`, `has_field`, `get_fields`).
+pub(crate) struct EvalContext {
+    inner: HashMapContext<DefaultNumericTypes>,
+    tool_input: Option<Arc<serde_json::Value>>,
+    cwd: Option<String>,
+    git_branch: OnceLock<Value<DefaultNumericTypes>>,
+}
+
+impl evalexpr::Context for EvalContext {
+    type NumericTypes = DefaultNumericTypes;
+
+    fn get_value(&self, identifier: &str) -> Option<&Value<DefaultNumericTypes>> {
+        if identifier == "git_branch" {
+            return Some(self.git_branch.get_or_init(|| {
+                let branch = self
+                    .cwd
+                    .as_deref()
+                    .filter(|cwd| !cwd.is_empty())
+                    .and_then(|cwd| resolve_git_branch(Path::new(cwd)));
+                Value::String(branch.unwrap_or_default())
+            }));
+        }
+        self.inner.get_value(identifier).or_else(|| {
+            identifier
+                .starts_with("env_")
+                .then(|| ENV_SNAPSHOT.get(identifier))
+                .flatten()
+        })
+    }
+
+    fn call_function(
+        &self,
+        identifier: &str,
+        argument: &Value<DefaultNumericTypes>,
+    ) -> evalexpr::EvalexprResult<Value<DefaultNumericTypes>, DefaultNumericTypes> {
+        self.inner.call_function(identifier, argument)
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.inner.are_builtin_functions_disabled()
+    }
+
+    fn set_builtin_functions_disabled(
+        &mut self,
+        disabled: bool,
+    ) -> evalexpr::EvalexprResult<(), DefaultNumericTypes> {
+        self.inner.set_builtin_functions_disabled(disabled)
+    }
+}
+
+impl ContextWithMutableVariables for EvalContext {
+    fn set_value(
+        &mut self,
+        identifier: String,
+        value: Value<DefaultNumericTypes>,
+    ) -> evalexpr::EvalexprResult<(), DefaultNumericTypes> {
+        self.inner.set_value(identifier, value)
+    }
+}
+
+impl ContextWithMutableFunctions for EvalContext {
+    fn set_function(
+        &mut self,
+        identifier: String,
+        function: Function<DefaultNumericTypes>,
+    ) -> evalexpr::EvalexprResult<(), DefaultNumericTypes> {
+        self.inner.set_function(identifier, function)
+    }
+}
+
+/// Build evaluation context for enabled_when expressions
+///
+/// Creates a context with:
+/// - env_* variables for all environment variables (looked up on demand)
+/// - tool_name: the tool being used (or empty string)
+/// - event_type: the hook event type
+/// - session_id, cwd, permission_mode, git_branch (empty string if unknown)
+/// - file_path, extension: target file of the tool call (empty string if none)
+/// - tool_response, tool_failed: the tool result on PostToolUse events
+/// - stop_hook_active: whether a Stop hook already kept the agent going
+/// - trigger, message: the PreCompact trigger and Notification text
+/// - pre_outcome, pre_rules: the PreToolUse decision for this tool call
+/// - the expression standard library (see register_stdlib_functions)
+fn build_eval_context(event: &Event) -> EvalContext {
+    let mut ctx = EvalContext {
+        inner: HashMapContext::new(),
+        tool_input: event.tool_input.clone().map(Arc::new),
+        cwd: event.cwd.clone(),
+        git_branch: OnceLock::new(),
+    };
+
+    // Add tool name (empty string if none)
+    let tool_name = event.tool_name.as_deref().unwrap_or("").to_string();
+    ctx.set_value("tool_name".into(), Value::String(tool_name))
+        .ok();
+
+    // Add event type
+    ctx.set_value(
+        "event_type".into(),
+        Value::String(event.hook_event_name.to_string()),
+    )
+    .ok();
+
+    // Add prompt text (if available - primarily for UserPromptSubmit events)
+    if let Some(ref prompt) = event.prompt {
+        ctx.set_value("prompt".into(), Value::String(prompt.clone()))
+            .ok();
+    }
+
+    // Add tool response text and failure status (PostToolUse events)
+    if let Some(text) = event.tool_response_text() {
+        ctx.set_value("tool_response".into(), Value::String(text))
+            .ok();
+    }
+    ctx.set_value("tool_failed".into(), Value::Boolean(event.tool_failed()))
+        .ok();
+    ctx.set_value(
+        "stop_hook_active".into(),
+        Value::Boolean(event.stop_hook_active.unwrap_or(false)),
+    )
+    .ok();
+
+    // Add the PreToolUse decision for this tool call (PostToolUse events)
+    let (pre_outcome, pre_rules) = match event.pre_tool_use {
+        Some(ref record) => (
+            serde_json::to_value(&record.outcome)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default(),
+            record
+                .rules_matched
+                .iter()
+                .map(|name| Value::String(name.clone()))
+                .collect(),
+        ),
+        None => (String::new(), Vec::new()),
+    };
+    ctx.set_value("pre_outcome".into(), Value::String(pre_outcome))
+        .ok();
+    ctx.set_value("pre_rules".into(), Value::Tuple(pre_rules))
+        .ok();
+
+    // Add compaction trigger and notification text (empty string if absent)
+    let trigger = event.trigger.clone().unwrap_or_default();
+    ctx.set_value("trigger".into(), Value::String(trigger)).ok();
+    let message = event.message.clone().unwrap_or_default();
+    ctx.set_value("message".into(), Value::String(message)).ok();
+
+    // Add session and environment context (empty string if absent)
+    ctx.set_value("session_id".into(), Value::String(event.session_id.clone()))
+        .ok();
+    let cwd = event.cwd.clone().unwrap_or_default();
+    ctx.set_value(
+        "permission_mode".into(),
+        Value::String(event.permission_mode.clone().unwrap_or_default()),
+    )
+    .ok();
+    ctx.set_value("cwd".into(), Value::String(cwd)).ok();
+
+    // Add target file path and its extension (".rs" form, matching matchers.extensions)
+    let file_path = event
+        .tool_input
+        .as_ref()
+        .and_then(|ti| ti.get("file_path").or_else(|| ti.get("filePath")))
+        .and_then(|p| p.as_str())
+        .unwrap_or("");
+    let extension = Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| format!(".{}", ext))
+        .unwrap_or_default();
+    ctx.set_value("file_path".into(), Value::String(file_path.to_string()))
+        .ok();
+    ctx.set_value("extension".into(), Value::String(extension))
+        .ok();
+
+    // Expose tool_input fields with tool_input_ prefix for use in enabled_when expressions
+    // Supports string, bool, and number (f64) field values. Arrays, objects, and null are skipped.
+    // Example: enabled_when: "tool_input_command =~ \"git push\""
+    if let Some(ref tool_input) = event.tool_input {
+        if let Some(obj) = tool_input.as_object() {
+            for (key, val) in obj {
+                let var_name = format!("tool_input_{}", key);
+                match val {
+                    serde_json::Value::String(s) => {
+                        ctx.set_value(var_name, Value::String(s.clone())).ok();
+                    }
+                    serde_json::Value::Bool(b) => {
+                        ctx.set_value(var_name, Value::Boolean(*b)).ok();
+                    }
+                    serde_json::Value::Number(n) => {
+                        if let Some(f) = n.as_f64() {
+                            ctx.set_value(var_name, Value::Float(f)).ok();
+                        }
+                    }
+                    _ => {} // Arrays, objects, null -- not supported by evalexpr
+                }
+            }
+        }
+    }
+
+    register_stdlib_functions(&mut ctx);
+
     ctx
 }
 
+// =============================================================================
+// Expression Standard Library
+// =============================================================================
+
+/// Register the RuleZ expression standard library on an evalexpr context
+///
+/// Available in both `enabled_when` and `validate_expr`:
+/// - matches(pattern, text): regex match (cached compilation)
+/// - contains(haystack, needle): substring check, or tuple membership
+/// - starts_with(text, prefix) / ends_with(text, suffix)
+/// - lower(text): lowercase copy of text
+/// - len(value): character count of a string, length of a tuple, 0 for empty
+/// - to_number(value): parse a string/bool/number into a float
+/// - get_fields(path): all values at a dot-notation path in tool_input as a
+///   tuple, fanning out across arrays (e.g. `get_fields("edits.old_string")`)
+#[allow(clippy::cast_precision_loss)] // Expression integers are far below 2^52
+fn register_stdlib_functions(ctx: &mut EvalContext) {
+    let matches_fn = Function::new(|argument| {
+        let arguments = argument.as_fixed_len_tuple(2)?;
+        let pattern = arguments[0].as_string()?;
+        let text = arguments[1].as_string()?;
+        let regex = get_or_compile_regex(&pattern, false)
+            .map_err(|e| evalexpr::EvalexprError::CustomMessage(e.to_string()))?;
+        Ok(Value::Boolean(regex.is_match(&text)))
+    });
+
+    let contains_fn = Function::new(|argument| {
+        let arguments = argument.as_fixed_len_tuple(2)?;
+        match (&arguments[0], &arguments[1]) {
+            (Value::String(haystack), needle) => {
+                Ok(Value::Boolean(haystack.contains(&needle.as_string()?)))
+            }
+            (Value::Tuple(items), needle) => Ok(Value::Boolean(items.contains(needle))),
+            (other, _) => Err(evalexpr::EvalexprError::type_error(
+                other.clone(),
+                vec![evalexpr::ValueType::String, evalexpr::ValueType::Tuple],
+            )),
+        }
+    });
+
+    let starts_with_fn = Function::new(|argument| {
+        let arguments = argument.as_fixed_len_tuple(2)?;
+        let text = arguments[0].as_string()?;
+        let prefix = arguments[1].as_string()?;
+        Ok(Value::Boolean(text.starts_with(&prefix)))
+    });
+
+    let ends_with_fn = Function::new(|argument| {
+        let arguments = argument.as_fixed_len_tuple(2)?;
+        let text = arguments[0].as_string()?;
+        let suffix = arguments[1].as_string()?;
+        Ok(Value::Boolean(text.ends_with(&suffix)))
+    });
+
+    let lower_fn =
+        Function::new(|argument| Ok(Value::String(argument.as_string()?.to_lowercase())));
+
+    let len_fn = Function::new(|argument| match argument {
+        Value::String(s) => Ok(Value::Int(
+            i64::try_from(s.chars().count()).unwrap_or(i64::MAX),
+        )),
+        Value::Tuple(items) => Ok(Value::Int(i64::try_from(items.len()).unwrap_or(i64::MAX))),
+        Value::Empty => Ok(Value::Int(0)),
+        other => Err(evalexpr::EvalexprError::type_error(
+            other.clone(),
+            vec![evalexpr::ValueType::String, evalexpr::ValueType::Tuple],
+        )),
+    });
+
+    let to_number_fn = Function::new(|argument| match argument {
+        Value::Float(f) => Ok(Value::Float(*f)),
+        Value::Int(i) => Ok(Value::Float(*i as f64)),
+        Value::Boolean(b) => Ok(Value::Float(if *b { 1.0 } else { 0.0 })),
+        Value::String(s) => s.trim().parse::<f64>().map(Value::Float).map_err(|_| {
+            evalexpr::EvalexprError::CustomMessage(format!("Cannot convert '{}' to a number", s))
+        }),
+        other => Err(evalexpr::EvalexprError::type_error(
+            other.clone(),
+            vec![evalexpr::ValueType::String, evalexpr::ValueType::Float],
+        )),
+    });
+
+    let tool_input = ctx.tool_input.clone();
+    let get_fields_fn = Function::new(move |argument| {
+        let path = argument.as_string()?;
+        let mut values = Vec::new();
+        if let Some(input) = tool_input.as_deref() {
+            collect_field_values(input, &path.split('.').collect::<Vec<_>>(), &mut values);
+        }
+        Ok(Value::Tuple(values))
+    });
+
+    ctx.set_function("matches".to_string(), matches_fn).ok();
+    ctx.set_function("contains".to_string(), contains_fn).ok();
+    ctx.set_function("starts_with".to_string(), starts_with_fn)
+        .ok();
+    ctx.set_function("ends_with".to_string(), ends_with_fn).ok();
+    ctx.set_function("lower".to_string(), lower_fn).ok();
+    ctx.set_function("len".to_string(), len_fn).ok();
+    ctx.set_function("to_number".to_string(), to_number_fn).ok();
+    ctx.set_function("get_fields".to_string(), get_fields_fn)
+        .ok();
+}
+
+/// Collect scalar values at a dot-notation path, fanning out across arrays
+///
+/// Numeric segments index into arrays; any other segment applied to an array
+/// is applied to every element. Nulls, objects, and non-scalar leaves are skipped.
+fn collect_field_values(
+    value: &serde_json::Value,
+    segments: &[&str],
+    out: &mut Vec<Value<DefaultNumericTypes>>,
+) {
+    match (segments.split_first(), value) {
+        (None, serde_json::Value::Array(items)) => {
+            for item in items {
+                collect_field_values(item, segments, out);
+            }
+        }
+        (None, serde_json::Value::String(s)) => out.push(Value::String(s.clone())),
+        (None, serde_json::Value::Number(n)) => {
+            out.push(Value::Float(n.as_f64().unwrap_or(0.0)));
+        }
+        (None, serde_json::Value::Bool(b)) => out.push(Value::Boolean(*b)),
+        (Some((segment, rest)), serde_json::Value::Array(items)) => {
+            if let Ok(index) = segment.parse::<usize>() {
+                if let Some(item) = items.get(index) {
+                    collect_field_values(item, rest, out);
+                }
+            } else {
+                for item in items {
+                    collect_field_values(item, segments, out);
+                }
+            }
+        }
+        (Some((segment, rest)), serde_json::Value::Object(map)) => {
+            if let Some(child) = map.get(*segment) {
+                collect_field_values(child, rest, out);
+            }
+        }
+        _ => {} // Null/object leaves and paths through scalars yield nothing
+    }
+}
+
+/// Resolve the current git branch for a working directory
+///
+/// Walks up from `cwd` to the nearest `.git` (directory or worktree file)
+/// and reads `HEAD` directly rather than spawning `git`. Returns the branch
+/// name, the short commit hash for a detached HEAD, or None outside a repo.
+fn resolve_git_branch(cwd: &Path) -> Option<String> {
+    let dot_git = cwd
+        .ancestors()
+        .map(|dir| dir.join(".git"))
+        .find(|p| p.exists())?;
+
+    // Worktrees and submodules use a `.git` file pointing at the real git dir
+    let git_dir = if dot_git.is_file() {
+        let content = std::fs::read_to_string(&dot_git).ok()?;
+        let target = content.trim().strip_prefix("gitdir:")?.trim().to_string();
+        let target = Path::new(&target);
+        if target.is_absolute() {
+            target.to_path_buf()
+        } else {
+            dot_git.parent()?.join(target)
+        }
+    } else {
+        dot_git
+    };
+
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: refs/heads/") {
+        Some(branch) => Some(branch.to_string()),
+        None => Some(head.chars().take(7).collect()),
+    }
+}
+
 /// Check if a rule is enabled based on its enabled_when expression
 ///
 /// Returns true if:
@@ -643,42 +1985,180 @@ fn is_rule_enabled(rule: &Rule, event: &Event) -> bool {
 /// Below this threshold, the sequential path is used (lower overhead).
 const PARALLEL_THRESHOLD: usize = 10;
 
-/// Evaluate all enabled rules against an event
-/// Rules are sorted by priority (higher first) by config.enabled_rules()
+/// Matched rules, merged response, per-rule evaluations, and per-rule timings
+type Evaluation<'a> = (
+    Vec<&'a Rule>,
+    Response,
+    Vec<RuleEvaluation>,
+    RuleTimings<'a>,
+);
+
+/// Time spent on each rule (matching plus actions) during one evaluation
+///
+/// Each rule keeps when its work first started and the total time spent.
+#[derive(Debug, Default)]
+struct RuleTimings<'a> {
+    by_rule: std::collections::HashMap<&'a str, (std::time::Instant, Duration)>,
+}
+
+impl<'a> RuleTimings<'a> {
+    fn record(&mut self, rule: &'a Rule, started: std::time::Instant, elapsed: Duration) {
+        let timing = self
+            .by_rule
+            .entry(rule.name.as_str())
+            .or_insert((started, Duration::ZERO));
+        timing.0 = timing.0.min(started);
+        timing.1 += elapsed;
+    }
+
+    /// Add another evaluation's timings
+    fn extend(&mut self, other: &Self) {
+        for (&name, &(started, elapsed)) in &other.by_rule {
+            let timing = self
+                .by_rule
+                .entry(name)
+                .or_insert((started, Duration::ZERO));
+            timing.0 = timing.0.min(started);
+            timing.1 += elapsed;
+        }
+    }
+
+    /// The rule that took longest, ties broken by name
+    fn slowest(&self) -> Option<(&'a str, Duration)> {
+        self.by_rule
+            .iter()
+            .map(|(&name, &(_, elapsed))| (name, elapsed))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+    }
+
+    /// Per-rule timings relative to `origin`, in the order the rules started
+    fn to_log(&self, origin: std::time::Instant) -> Vec<RuleTiming> {
+        let micros = |d: Duration| u64::try_from(d.as_micros()).unwrap_or(u64::MAX);
+        let mut timings: Vec<RuleTiming> = self
+            .by_rule
+            .iter()
+            .map(|(&name, &(started, elapsed))| RuleTiming {
+                rule: name.to_string(),
+                offset_us: micros(started.saturating_duration_since(origin)),
+                duration_us: micros(elapsed),
+            })
+            .collect();
+        timings.sort_by(|a, b| {
+            a.offset_us
+                .cmp(&b.offset_us)
+                .then_with(|| a.rule.cmp(&b.rule))
+        });
+        timings
+    }
+}
+
+/// Check a rule's `enabled_when` and matchers inside a `match_rule` span
+///
+/// Returns (enabled, matched, matcher_results); matcher results are only
+/// collected in debug mode.
+fn match_rule_traced(
+    event: &Event,
+    rule: &Rule,
+    debug_enabled: bool,
+) -> (bool, bool, Option<MatcherResults>) {
+    tracing::debug_span!("match_rule", rule = %rule.name).in_scope(|| {
+        // Check enabled_when before matchers (Phase 3: conditional rule activation)
+        if !is_rule_enabled(rule, event) {
+            return (false, false, None);
+        }
+
+        let (matched, matcher_results) = if debug_enabled {
+            matches_rule_with_debug(event, rule)
+        } else {
+            (matches_rule(event, rule), None)
+        };
+        (true, matched, matcher_results)
+    })
+}
+
+/// Evaluate all enabled rules against an event, highest priority first
+///
+/// Outside debug mode, only the candidates from the config's rule index
+/// (rules whose `tools` and `operations` allow this event) are evaluated.
+/// Debug mode evaluates every enabled rule so each one is reported.
 ///
 /// Parallel matching: enabled for rule sets with >= PARALLEL_THRESHOLD rules.
-/// When threshold is met, rule matching runs concurrently via join_all.
-/// Action execution remains sequential to preserve merge semantics
-/// (block takes precedence, inject accumulates, priority order matters).
+/// When threshold is met, rule matching runs concurrently via join_all, or on
+/// worker threads with `settings.parallel_evaluation`. Action execution stays
+/// sequential to preserve merge semantics (block takes precedence, inject
+/// accumulates, priority order matters) unless every matched rule is
+/// read-only (see [`evaluate_rules_parallel`]).
 async fn evaluate_rules<'a>(
     event: &'a Event,
     config: &'a Config,
     debug_config: &DebugConfig,
-) -> Result<(Vec<&'a Rule>, Response, Vec<RuleEvaluation>)> {
-    let rules = config.enabled_rules();
+) -> Result<Evaluation<'a>> {
+    let mut rules = if debug_config.enabled {
+        config.enabled_rules()
+    } else {
+        config.candidate_rules(event.tool_name.as_deref(), event.hook_event_name)
+    };
+
+    // Rules waived for this event sit out (see `applied_waivers`)
+    if !config.waivers.is_empty() {
+        let today = crate::governance::today();
+        rules.retain(|rule| crate::waivers::find(config, rule, event, today).is_none());
+    }
 
     if rules.len() >= PARALLEL_THRESHOLD {
-        evaluate_rules_parallel(event, config, debug_config).await
+        evaluate_rules_parallel(event, rules, config, debug_config).await
     } else {
-        evaluate_rules_sequential(event, config, debug_config).await
+        evaluate_rules_sequential(event, rules, config, debug_config).await
     }
 }
 
+/// Waivers that kept a rule from matching this event
+///
+/// A waiver counts as applied only when its rule is enabled and would have
+/// matched; each one is logged so exemptions stay visible.
+fn applied_waivers(event: &Event, config: &Config) -> Vec<crate::waivers::AppliedWaiver> {
+    if config.waivers.is_empty() {
+        return Vec::new();
+    }
+    let today = crate::governance::today();
+    config
+        .enabled_rules()
+        .into_iter()
+        .filter_map(|rule| {
+            let waiver = crate::waivers::find(config, rule, event, today)?;
+            (is_rule_enabled(rule, event) && matches_rule(event, rule)).then(|| {
+                tracing::info!(
+                    "Rule '{}' waived (approved by {}, expires {}): {}",
+                    rule.name,
+                    waiver.approver,
+                    waiver.expires,
+                    waiver.justification
+                );
+                waiver.applied()
+            })
+        })
+        .collect()
+}
+
 /// Sequential rule evaluation — used for small rule sets (< PARALLEL_THRESHOLD rules).
 /// Original implementation: matches and executes actions inline per rule.
 async fn evaluate_rules_sequential<'a>(
     event: &'a Event,
+    rules: Vec<&'a Rule>,
     config: &'a Config,
     debug_config: &DebugConfig,
-) -> Result<(Vec<&'a Rule>, Response, Vec<RuleEvaluation>)> {
+) -> Result<Evaluation<'a>> {
     let mut matched_rules = Vec::new();
-    let mut response = Response::allow();
+    let mut rule_responses = Vec::new();
     let mut rule_evaluations = Vec::new();
-
-    // Get enabled rules (already sorted by priority in Config::enabled_rules)
-    for rule in config.enabled_rules() {
-        // Check enabled_when before matchers (Phase 3: conditional rule activation)
-        if !is_rule_enabled(rule, event) {
+    let mut timings = RuleTimings::default();
+
+    // Rules arrive sorted by priority
+    for rule in rules {
+        let started = std::time::Instant::now();
+        let (enabled, matched, matcher_results) =
+            match_rule_traced(event, rule, traces_matchers(debug_config.enabled, rule));
+        if !enabled {
             if debug_config.enabled {
                 rule_evaluations.push(RuleEvaluation {
                     rule_name: rule.name.clone(),
@@ -686,15 +2166,10 @@ async fn evaluate_rules_sequential<'a>(
                     matcher_results: None,
                 });
             }
+            timings.record(rule, started, started.elapsed());
             continue; // Skip rule entirely
         }
 
-        let (matched, matcher_results) = if debug_config.enabled {
-            matches_rule_with_debug(event, rule)
-        } else {
-            (matches_rule(event, rule), None)
-        };
-
         let rule_evaluation = RuleEvaluation {
             rule_name: rule.name.clone(),
             matched,
@@ -708,70 +2183,79 @@ async fn evaluate_rules_sequential<'a>(
             // Execute rule actions based on mode (Phase 2 Governance)
             let mode = rule.effective_mode();
             let rule_response = execute_rule_actions_with_mode(event, rule, config, mode).await?;
-
-            // Merge responses based on mode (block takes precedence, inject accumulates)
-            response = merge_responses_with_mode(response, rule_response, mode);
+            rule_responses.push((rule, rule_response));
         }
+        timings.record(rule, started, started.elapsed());
     }
 
-    Ok((matched_rules, response, rule_evaluations))
+    // Merge responses (block takes precedence, inject accumulates)
+    let response = merge_rule_responses(rule_responses, &config.settings);
+    Ok((matched_rules, response, rule_evaluations, timings))
 }
 
 /// Parallel rule evaluation — used for large rule sets (>= PARALLEL_THRESHOLD rules).
 ///
-/// Phase 1: Parallel matching — all rules are matched concurrently via join_all.
+/// Phase 1: Parallel matching — all rules are matched concurrently via join_all,
+/// or split across worker threads with `settings.parallel_evaluation`.
 /// This is safe because matches_rule() and is_rule_enabled() are pure/stateless.
 ///
-/// Phase 2: Sequential action execution — matched rules execute actions in priority
+/// Phase 2: Action execution — matched rules execute actions in priority
 /// order (highest first) to preserve merge semantics (block > inject > allow).
+/// With `settings.parallel_evaluation`, rules without outside side effects
+/// (validators, injects, blocks) run concurrently, while rules with webhooks
+/// or custom actions still run one after another; the responses are merged
+/// in the same priority order either way.
 async fn evaluate_rules_parallel<'a>(
     event: &'a Event,
+    rules: Vec<&'a Rule>,
     config: &'a Config,
     debug_config: &DebugConfig,
-) -> Result<(Vec<&'a Rule>, Response, Vec<RuleEvaluation>)> {
-    let rules = config.enabled_rules();
+) -> Result<Evaluation<'a>> {
     let debug_enabled = debug_config.enabled;
+    let parallel = config.settings.parallel_evaluation;
 
     // Phase 1: Parallel matching — run is_rule_enabled + matches_rule concurrently
-    let match_futures: Vec<_> = rules
-        .iter()
-        .map(|&rule| async move {
-            // Check enabled_when before matchers
-            if !is_rule_enabled(rule, event) {
-                return (rule, false, None, false); // (rule, matched, matcher_results, enabled)
-            }
-
-            let (matched, matcher_results) = if debug_enabled {
-                matches_rule_with_debug(event, rule)
-            } else {
-                (matches_rule(event, rule), None)
-            };
-
-            (rule, matched, matcher_results, true)
-        })
-        .collect();
+    let match_one = |rule: &'a Rule| {
+        let started = std::time::Instant::now();
+        let (enabled, matched, matcher_results) =
+            match_rule_traced(event, rule, traces_matchers(debug_enabled, rule));
+        (
+            rule,
+            matched,
+            matcher_results,
+            enabled,
+            started,
+            started.elapsed(),
+        )
+    };
 
-    let match_results = join_all(match_futures).await;
+    let match_results = if parallel {
+        match_on_threads(&rules, match_one)
+    } else {
+        join_all(rules.iter().map(|&rule| async move { match_one(rule) })).await
+    };
 
     // Collect evaluations and matched rules (preserving priority order from config)
     let mut matched_rules = Vec::new();
     let mut rule_evaluations = Vec::new();
-
-    for (rule, matched, matcher_results, enabled) in match_results {
-        if debug_enabled {
-            if !enabled {
-                rule_evaluations.push(RuleEvaluation {
-                    rule_name: rule.name.clone(),
-                    matched: false,
-                    matcher_results: None,
-                });
-            } else {
-                rule_evaluations.push(RuleEvaluation {
-                    rule_name: rule.name.clone(),
-                    matched,
-                    matcher_results,
-                });
-            }
+    let mut timings = RuleTimings::default();
+
+    for (rule, matched, matcher_results, enabled, started, elapsed) in match_results {
+        timings.record(rule, started, elapsed);
+        // As in sequential evaluation: enabled rules are always recorded (a
+        // `log: debug` rule traces its matchers), disabled ones with debug on
+        if enabled {
+            rule_evaluations.push(RuleEvaluation {
+                rule_name: rule.name.clone(),
+                matched,
+                matcher_results,
+            });
+        } else if debug_enabled {
+            rule_evaluations.push(RuleEvaluation {
+                rule_name: rule.name.clone(),
+                matched: false,
+                matcher_results: None,
+            });
         }
 
         if matched {
@@ -779,15 +2263,110 @@ async fn evaluate_rules_parallel<'a>(
         }
     }
 
-    // Phase 2: Sequential action execution — preserves merge semantics
-    let mut response = Response::allow();
-    for rule in &matched_rules {
-        let mode = rule.effective_mode();
-        let rule_response = execute_rule_actions_with_mode(event, rule, config, mode).await?;
-        response = merge_responses_with_mode(response, rule_response, mode);
+    // Phase 2: Action execution — merged in priority order either way
+    let mut rule_responses = Vec::with_capacity(matched_rules.len());
+    if parallel {
+        let execute = |rule: &'a Rule| async move {
+            let started = std::time::Instant::now();
+            let rule_response =
+                execute_rule_actions_with_mode(event, rule, config, rule.effective_mode()).await;
+            (rule_response, started, started.elapsed())
+        };
+        let (concurrent, ordered): (Vec<usize>, Vec<usize>) =
+            (0..matched_rules.len()).partition(|&index| is_side_effect_free(matched_rules[index]));
+        let (concurrent_results, ordered_results) = tokio::join!(
+            join_all(
+                concurrent
+                    .iter()
+                    .map(|&index| execute(matched_rules[index]))
+            ),
+            async {
+                let mut results = Vec::with_capacity(ordered.len());
+                for &index in &ordered {
+                    results.push(execute(matched_rules[index]).await);
+                }
+                results
+            }
+        );
+        let mut executed: Vec<_> = concurrent
+            .into_iter()
+            .zip(concurrent_results)
+            .chain(ordered.into_iter().zip(ordered_results))
+            .collect();
+        executed.sort_by_key(|(index, _)| *index);
+        for (index, (rule_response, started, elapsed)) in executed {
+            let rule = matched_rules[index];
+            timings.record(rule, started, elapsed);
+            rule_responses.push((rule, rule_response?));
+        }
+    } else {
+        for rule in &matched_rules {
+            let started = std::time::Instant::now();
+            let mode = rule.effective_mode();
+            let rule_response = execute_rule_actions_with_mode(event, rule, config, mode).await?;
+            rule_responses.push((*rule, rule_response));
+            timings.record(rule, started, started.elapsed());
+        }
     }
 
-    Ok((matched_rules, response, rule_evaluations))
+    let response = merge_rule_responses(rule_responses, &config.settings);
+    Ok((matched_rules, response, rule_evaluations, timings))
+}
+
+/// Whether a rule's actions can run concurrently with other rules
+///
+/// Audit-mode rules run no actions. Validators (`run`, `inline_script`,
+/// `script_rhai`) and `inject_command` only decide or produce context, so
+/// they qualify in any mode; webhooks and custom actions act outside RuleZ
+/// and keep their order.
+fn is_side_effect_free(rule: &Rule) -> bool {
+    rule.effective_mode() == PolicyMode::Audit
+        || rule.actions.steps().all(|actions| {
+            actions.webhook.is_none()
+                && actions
+                    .custom
+                    .as_ref()
+                    .is_none_or(std::collections::BTreeMap::is_empty)
+        })
+}
+
+/// Apply `match_one` to each rule on scoped worker threads, keeping rule order
+///
+/// Rules are split into one contiguous chunk per available CPU and the
+/// per-chunk results concatenated, so the output lines up with `rules`.
+fn match_on_threads<'a, T: Send>(
+    rules: &[&'a Rule],
+    match_one: impl Fn(&'a Rule) -> T + Sync,
+) -> Vec<T> {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(rules.len());
+    if workers <= 1 {
+        return rules.iter().map(|&rule| match_one(rule)).collect();
+    }
+
+    let match_one = &match_one;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = rules
+            .chunks(rules.len().div_ceil(workers))
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&rule| match_one(rule))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
 }
 
 /// Build a GlobSet from a list of directory patterns.
@@ -829,6 +2408,7 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
 
     // Check tool name
     if let Some(ref tools) = matchers.tools {
+        let _span = tracing::trace_span!("matcher", kind = "tools").entered();
         if let Some(ref tool_name) = event.tool_name {
             if !tools.contains(tool_name) {
                 return false;
@@ -840,6 +2420,7 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
 
     // Check command patterns (for Bash tool)
     if let Some(ref pattern) = matchers.command_match {
+        let _span = tracing::trace_span!("matcher", kind = "command_match").entered();
         if let Some(ref tool_input) = event.tool_input {
             if let Some(command) = tool_input.get("command").and_then(|c| c.as_str()) {
                 if let Ok(regex) = get_or_compile_regex(pattern, false) {
@@ -859,6 +2440,7 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
 
     // Check file extensions
     if let Some(ref extensions) = matchers.extensions {
+        let _span = tracing::trace_span!("matcher", kind = "extensions").entered();
         if let Some(ref tool_input) = event.tool_input {
             if let Some(file_path) = tool_input.get("filePath").and_then(|p| p.as_str()) {
                 let path_ext = Path::new(file_path)
@@ -878,6 +2460,7 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
 
     // Check directory patterns
     if let Some(ref directories) = matchers.directories {
+        let _span = tracing::trace_span!("matcher", kind = "directories").entered();
         if let Some(ref tool_input) = event.tool_input {
             if let Some(file_path) = tool_input.get("filePath").and_then(|p| p.as_str()) {
                 let glob_set = build_glob_set(directories);
@@ -890,14 +2473,18 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
 
     // Check operations (event types)
     if let Some(ref operations) = matchers.operations {
-        let event_type_str = event.hook_event_name.to_string();
-        if !operations.contains(&event_type_str) {
+        let _span = tracing::trace_span!("matcher", kind = "operations").entered();
+        if !operations
+            .iter()
+            .any(|name| event.hook_event_name.matches_name(name))
+        {
             return false;
         }
     }
 
     // Check prompt patterns (for UserPromptSubmit events)
     if let Some(ref prompt_match) = matchers.prompt_match {
+        let _span = tracing::trace_span!("matcher", kind = "prompt_match").entered();
         // If rule has prompt_match but event has no prompt, rule doesn't match
         if let Some(ref prompt_text) = event.prompt {
             if !matches_prompt(prompt_text, prompt_match) {
@@ -909,18 +2496,165 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
         }
     }
 
-    // Check field validation (require_fields / field_types)
-    if (rule.matchers.require_fields.is_some() || rule.matchers.field_types.is_some())
-        && !validate_required_fields(rule, event)
-    {
+    // Check tool response patterns (for PostToolUse events)
+    if let Some(ref pattern) = matchers.response_match {
+        let _span = tracing::trace_span!("matcher", kind = "response_match").entered();
+        if !matches_response(event, pattern) {
+            return false;
+        }
+    }
+
+    // Check tool failure status (for PostToolUse events)
+    if let Some(expected) = matchers.tool_failed {
+        let _span = tracing::trace_span!("matcher", kind = "tool_failed").entered();
+        if event.tool_response.is_none() || event.tool_failed() != expected {
+            return false;
+        }
+    }
+
+    // Check notification text (for Notification events)
+    if let Some(ref pattern) = matchers.message_match {
+        let _span = tracing::trace_span!("matcher", kind = "message_match").entered();
+        if !matches_message(event, pattern) {
+            return false;
+        }
+    }
+
+    // Check compaction trigger (for PreCompact events)
+    if let Some(ref trigger) = matchers.compact_trigger {
+        let _span = tracing::trace_span!("matcher", kind = "compact_trigger").entered();
+        if event.trigger.as_deref() != Some(trigger.as_str()) {
+            return false;
+        }
+    }
+
+    // Check rules matched at PreToolUse (for PostToolUse events)
+    if let Some(ref pre_rules) = matchers.pre_rules {
+        let _span = tracing::trace_span!("matcher", kind = "pre_rules").entered();
+        if !matches_pre_rules(event, pre_rules) {
+            return false;
+        }
+    }
+
+    // Check who is running the agent
+    if let Some(ref users) = matchers.users {
+        let _span = tracing::trace_span!("matcher", kind = "users").entered();
+        if !crate::identity::matches_users(event, users) {
+            return false;
+        }
+    }
+    if let Some(ref teams) = matchers.teams {
+        let _span = tracing::trace_span!("matcher", kind = "teams").entered();
+        if !crate::identity::matches_teams(event, teams) {
+            return false;
+        }
+    }
+
+    // Check recent transcript messages (read last: it does file I/O)
+    if let Some(ref transcript_match) = matchers.transcript_match {
+        let _span = tracing::trace_span!("matcher", kind = "transcript_match").entered();
+        if !matches_transcript(event, transcript_match) {
+            return false;
+        }
+    }
+
+    // Check field validation (require_fields / field_types)
+    if (rule.matchers.require_fields.is_some() || rule.matchers.field_types.is_some())
+        && !tracing::trace_span!("matcher", kind = "require_fields")
+            .in_scope(|| validate_required_fields(rule, event))
+    {
+        return false;
+    }
+
+    // Check registered custom matchers
+    if let Some(ref custom) = matchers.custom {
+        if !crate::matchers::matches_custom(event, custom) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Match a `response_match` regex against the event's tool response
+///
+/// Events without a tool response never match. Invalid regexes fail closed.
+fn matches_response(event: &Event, pattern: &str) -> bool {
+    let Some(text) = event.tool_response_text() else {
+        return false;
+    };
+    if let Ok(regex) = get_or_compile_regex(pattern, false) {
+        regex.is_match(&text)
+    } else {
+        tracing::warn!(
+            "Invalid response_match regex '{}' in rule — failing closed",
+            pattern
+        );
+        false
+    }
+}
+
+/// Match a `message_match` regex against the event's notification text
+///
+/// Events without a message never match. Invalid regexes fail closed.
+fn matches_message(event: &Event, pattern: &str) -> bool {
+    let Some(ref message) = event.message else {
         return false;
+    };
+    if let Ok(regex) = get_or_compile_regex(pattern, false) {
+        regex.is_match(message)
+    } else {
+        tracing::warn!(
+            "Invalid message_match regex '{}' in rule — failing closed",
+            pattern
+        );
+        false
     }
+}
 
-    true
+/// Whether any of `pre_rules` matched this tool call's PreToolUse event
+///
+/// Events without a PreToolUse record never match.
+fn matches_pre_rules(event: &Event, pre_rules: &[String]) -> bool {
+    event.pre_tool_use.as_ref().is_some_and(|record| {
+        record
+            .rules_matched
+            .iter()
+            .any(|name| pre_rules.contains(name))
+    })
+}
+
+/// Match a `transcript_match` regex against recent transcript messages
+///
+/// A missing transcript has no messages, so it only matches when negated.
+/// Invalid regexes fail closed.
+fn matches_transcript(event: &Event, transcript_match: &TranscriptMatch) -> bool {
+    let Ok(regex) = get_or_compile_regex(transcript_match.pattern(), false) else {
+        tracing::warn!(
+            "Invalid transcript_match regex '{}' in rule — failing closed",
+            transcript_match.pattern()
+        );
+        return false;
+    };
+    let found = event.transcript_path.as_deref().is_some_and(|path| {
+        crate::transcript::read_tail(
+            Path::new(path),
+            transcript_match.role(),
+            transcript_match.last_messages(),
+            transcript_match.max_bytes(),
+        )
+        .iter()
+        .any(|message| regex.is_match(&message.text))
+    });
+    found != transcript_match.negate()
 }
 
 /// Check if a rule matches the given event (debug version with matcher results)
-fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherResults>) {
+#[allow(clippy::too_many_lines)]
+pub(crate) fn matches_rule_with_debug(
+    event: &Event,
+    rule: &Rule,
+) -> (bool, Option<MatcherResults>) {
     let matchers = &rule.matchers;
     let mut matcher_results = MatcherResults::default();
     let mut overall_match = true;
@@ -1006,8 +2740,9 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
     // Check operations (event types)
     if let Some(ref operations) = matchers.operations {
         matcher_results.operations_matched = Some({
-            let event_type_str = event.hook_event_name.to_string();
-            operations.contains(&event_type_str)
+            operations
+                .iter()
+                .any(|name| event.hook_event_name.matches_name(name))
         });
         if !matcher_results.operations_matched.unwrap() {
             overall_match = false;
@@ -1026,6 +2761,71 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
         }
     }
 
+    // Check tool response patterns
+    if let Some(ref pattern) = matchers.response_match {
+        matcher_results.response_match_matched = Some(matches_response(event, pattern));
+        if !matcher_results.response_match_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check tool failure status
+    if let Some(expected) = matchers.tool_failed {
+        matcher_results.tool_failed_matched =
+            Some(event.tool_response.is_some() && event.tool_failed() == expected);
+        if !matcher_results.tool_failed_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check notification text
+    if let Some(ref pattern) = matchers.message_match {
+        matcher_results.message_match_matched = Some(matches_message(event, pattern));
+        if !matcher_results.message_match_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check compaction trigger
+    if let Some(ref trigger) = matchers.compact_trigger {
+        matcher_results.compact_trigger_matched =
+            Some(event.trigger.as_deref() == Some(trigger.as_str()));
+        if !matcher_results.compact_trigger_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check rules matched at PreToolUse
+    if let Some(ref pre_rules) = matchers.pre_rules {
+        matcher_results.pre_rules_matched = Some(matches_pre_rules(event, pre_rules));
+        if !matcher_results.pre_rules_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check who is running the agent
+    if let Some(ref users) = matchers.users {
+        matcher_results.users_matched = Some(crate::identity::matches_users(event, users));
+        if !matcher_results.users_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref teams) = matchers.teams {
+        matcher_results.teams_matched = Some(crate::identity::matches_teams(event, teams));
+        if !matcher_results.teams_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check recent transcript messages
+    if let Some(ref transcript_match) = matchers.transcript_match {
+        matcher_results.transcript_match_matched =
+            Some(matches_transcript(event, transcript_match));
+        if !matcher_results.transcript_match_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
     // Check field validation (require_fields / field_types)
     if rule.matchers.require_fields.is_some() || rule.matchers.field_types.is_some() {
         let field_valid = validate_required_fields(rule, event);
@@ -1035,39 +2835,93 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
         }
     }
 
+    // Check registered custom matchers
+    if let Some(ref custom) = matchers.custom {
+        let results = crate::matchers::evaluate_custom(event, custom);
+        if results.values().any(|matched| !matched) {
+            overall_match = false;
+        }
+        matcher_results.custom_matched = Some(results);
+    }
+
     (overall_match, Some(matcher_results))
 }
 
+/// Script-running action types with their own timeout (`settings.script_timeouts`)
+#[derive(Debug, Clone, Copy)]
+enum ScriptKind {
+    Run,
+    InjectCommand,
+    InlineScript,
+}
+
+/// Timeout for one of a rule's scripts or commands
+///
+/// The rule's `timeout_ms` wins, then its legacy `metadata.timeout`, then
+/// `settings.script_timeouts` for the action type, then
+/// `settings.script_timeout`.
+fn script_timeout(rule: &Rule, config: &Config, kind: ScriptKind) -> Duration {
+    if let Some(ms) = rule.timeout_ms {
+        return Duration::from_millis(ms);
+    }
+    if let Some(seconds) = rule.metadata.as_ref().and_then(|m| m.timeout) {
+        return Duration::from_secs(u64::from(seconds));
+    }
+    let timeouts = &config.settings.script_timeouts;
+    let budget = match kind {
+        ScriptKind::Run => timeouts.run_ms,
+        ScriptKind::InjectCommand => timeouts.inject_command_ms,
+        ScriptKind::InlineScript => timeouts.inline_script_ms,
+    };
+    budget.map_or_else(
+        || Duration::from_secs(u64::from(config.settings.script_timeout)),
+        Duration::from_millis,
+    )
+}
+
 /// Execute a shell command and capture stdout for context injection
 ///
 /// Unlike validators:
 /// - No stdin input needed
 /// - Raw text output (not JSON)
 /// - Fail-open: command failures log warning but don't block
-async fn execute_inject_command(command_str: &str, rule: &Rule, config: &Config) -> Option<String> {
-    let timeout_secs = rule
-        .metadata
-        .as_ref()
-        .map(|m| m.timeout)
-        .unwrap_or(config.settings.script_timeout);
+async fn execute_inject_command(
+    inject_command: &InjectCommand,
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+) -> Result<Option<String>> {
+    let command_str = inject_command.command();
+    let timeout_after = script_timeout(rule, config, ScriptKind::InjectCommand);
 
-    // Use platform-specific shell to execute (enables pipes, redirects, etc.)
-    let mut command = if cfg!(target_os = "windows") {
-        let mut cmd = Command::new("cmd");
-        cmd.arg("/C");
-        cmd.arg(command_str);
-        cmd
-    } else {
-        let mut cmd = Command::new("sh");
-        cmd.arg("-c");
-        cmd.arg(command_str);
-        cmd
+    let Some(mut command) = policy_command(command_str, "inject_command", rule, config) else {
+        return Ok(None);
     };
+    if let Some(dir) = resolve_working_dir(inject_command.cwd(), event) {
+        command.current_dir(dir);
+    }
+    let limits = &config.settings.script_limits;
+    limits.apply(&mut command);
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
     // No stdin - don't pipe it (causes hangs)
 
-    let child = match command.spawn() {
+    // Bound concurrent children; waiting for a slot counts against the timeout
+    let Ok(_slot) = timeout(
+        timeout_after,
+        SCRIPT_SLOTS.acquire(limits.max_concurrent, rule.effective_priority()),
+    )
+    .await
+    else {
+        tracing::warn!(
+            "inject_command '{}' for rule '{}' timed out waiting for a script slot",
+            command_str,
+            rule.name
+        );
+        return Ok(None);
+    };
+
+    let mut child = match command.spawn() {
         Ok(c) => c,
         Err(e) => {
             tracing::warn!(
@@ -1076,200 +2930,1019 @@ async fn execute_inject_command(command_str: &str, rule: &Rule, config: &Config)
                 rule.name,
                 e
             );
-            return None;
+            return Ok(None);
+        }
+    };
+
+    let containment = limits
+        .contain(&child)
+        .map_err(|e| anyhow::anyhow!("failed to apply resource limits to inject_command: {}", e))?;
+
+    let output = match timeout(
+        timeout_after,
+        wait_with_limited_output(
+            &mut child,
+            limits.max_output_bytes,
+            limits.truncate_stdout_bytes,
+        ),
+    )
+    .await
+    {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) if e.is::<ResourceLimitExceeded>() => return Err(e),
+        Ok(Err(e)) => {
+            tracing::warn!(
+                "inject_command '{}' for rule '{}' failed: {}",
+                command_str,
+                rule.name,
+                e
+            );
+            return Ok(None);
+        }
+        Err(_) => {
+            child.kill().await.ok();
+            tracing::warn!(
+                "inject_command '{}' for rule '{}' timed out after {:?}",
+                command_str,
+                rule.name,
+                timeout_after
+            );
+            return Ok(None);
+        }
+    };
+
+    limits.check_exit(&output, &containment)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        tracing::warn!(
+            "inject_command '{}' for rule '{}' failed with exit code {}: {}",
+            command_str,
+            rule.name,
+            output.status.code().unwrap_or(-1),
+            stderr.trim()
+        );
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if stdout.trim().is_empty() {
+        return Ok(None); // No content to inject
+    }
+
+    Ok(Some(stdout))
+}
+
+/// Process for an `inject_command` or summarizer command line
+///
+/// With an `inject_command_policy`, allowed commands run without a shell
+/// and others are refused (logged, `None`); without one, the shell runs it.
+fn policy_command(command_str: &str, what: &str, rule: &Rule, config: &Config) -> Option<Command> {
+    let argv = match config.settings.inject_command_policy {
+        Some(ref policy) => match policy.argv(command_str) {
+            Ok(argv) => argv,
+            Err(e) => {
+                tracing::warn!(
+                    "{} '{}' for rule '{}' is not allowed: {}",
+                    what,
+                    command_str,
+                    rule.name,
+                    e
+                );
+                return None;
+            }
+        },
+        None => None,
+    };
+    Some(match argv {
+        Some(argv) => {
+            let mut command = Command::new(&argv[0]);
+            command.args(&argv[1..]);
+            command
+        }
+        None => shell_command(command_str),
+    })
+}
+
+/// Shrink oversized injected content per the rule's `summarize` action
+///
+/// Content within `max_bytes` is returned as is. Otherwise the summarizer
+/// command's output is used, falling back to head/tail extraction when there
+/// is no command or it fails, and the result is capped at `max_bytes`.
+async fn summarize_context(
+    content: String,
+    summarize: &Summarize,
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+) -> String {
+    if content.len() <= summarize.max_bytes {
+        return content;
+    }
+    let summary = match summarize.command {
+        Some(ref command) => run_summarizer(command, &content, event, rule, config).await,
+        None => None,
+    };
+    let mut summary =
+        summary.unwrap_or_else(|| head_tail(&content, summarize.head_lines, summarize.tail_lines));
+    context_budget::truncate(&mut summary, summarize.max_bytes);
+    tracing::debug!(
+        "Summarized {} bytes of context for rule '{}' to {} bytes",
+        content.len(),
+        rule.name,
+        summary.len()
+    );
+    summary
+}
+
+/// The first `head` and last `tail` lines, with a note of how many were left out
+fn head_tail(content: &str, head: usize, tail: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() <= head + tail {
+        return content.to_string();
+    }
+    let mut kept: Vec<String> = lines[..head].iter().map(ToString::to_string).collect();
+    kept.push(format!("… [{} lines omitted]", lines.len() - head - tail));
+    kept.extend(lines[lines.len() - tail..].iter().map(ToString::to_string));
+    kept.join("\n")
+}
+
+/// Pipe `content` through a summarizer command and return its output
+///
+/// Runs like `inject_command` (`inject_command_policy`, event cwd, script
+/// limits and timeout). Any failure, including a command the policy refuses,
+/// is logged and returns None so the caller can fall back.
+async fn run_summarizer(
+    command_str: &str,
+    content: &str,
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+) -> Option<String> {
+    use tokio::io::AsyncWriteExt;
+
+    let timeout_after = script_timeout(rule, config, ScriptKind::InjectCommand);
+    let mut command = policy_command(command_str, "Summarizer", rule, config)?;
+    if let Some(dir) = resolve_working_dir(None, event) {
+        command.current_dir(dir);
+    }
+    let limits = &config.settings.script_limits;
+    limits.apply(&mut command);
+    command.stdin(std::process::Stdio::piped());
+    command.stdout(std::process::Stdio::piped());
+    command.stderr(std::process::Stdio::piped());
+
+    let result = timeout(timeout_after, async {
+        let _slot = SCRIPT_SLOTS
+            .acquire(limits.max_concurrent, rule.effective_priority())
+            .await;
+        let mut child = command.spawn()?;
+        let _containment = limits.contain(&child)?;
+        let mut stdin = child.stdin.take();
+        // Feed stdin while reading stdout, so a chatty summarizer can't deadlock
+        let write = async {
+            if let Some(ref mut stdin) = stdin {
+                // The summarizer may stop reading early (e.g. `head`)
+                let _ = stdin.write_all(content.as_bytes()).await;
+            }
+            drop(stdin);
+        };
+        let ((), output) = tokio::join!(
+            write,
+            wait_with_limited_output(&mut child, limits.max_output_bytes, None)
+        );
+        let output = output?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "exit code {}: {}",
+                output.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(summary)) if !summary.trim().is_empty() => Some(summary),
+        Ok(Ok(_)) => {
+            tracing::warn!(
+                "Summarizer '{}' for rule '{}' printed nothing",
+                command_str,
+                rule.name
+            );
+            None
+        }
+        Ok(Err(e)) => {
+            tracing::warn!(
+                "Summarizer '{}' for rule '{}' failed: {}",
+                command_str,
+                rule.name,
+                e
+            );
+            None
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Summarizer '{}' for rule '{}' timed out after {:?}",
+                command_str,
+                rule.name,
+                timeout_after
+            );
+            None
+        }
+    }
+}
+
+/// A command run through the platform shell (enables pipes, redirects, etc.)
+fn shell_command(command_str: &str) -> Command {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    command.arg(command_str);
+    command
+}
+
+/// Built-in actions, in the order they run (see `crate::actions`)
+static BUILTIN_ACTIONS: [&dyn Action; 12] = [
+    &builtin::Webhook,
+    &builtin::ValidateExpr,
+    &builtin::InlineScriptGate,
+    &builtin::RhaiScript,
+    &builtin::Block,
+    &builtin::BlockIfMatch,
+    &builtin::ScanSecrets,
+    &builtin::ScanPii,
+    &builtin::InjectInline,
+    &builtin::InjectCommand,
+    &builtin::Inject,
+    &builtin::Run,
+];
+
+/// Run a matched rule's actions until one decides the outcome
+///
+/// Built-in actions run first, then the rule's custom actions in name order.
+/// In warn mode a `Block` outcome becomes a warning; the built-ins word their
+/// own warnings.
+async fn execute_rule_actions(
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+    mode: PolicyMode,
+) -> Result<Response> {
+    let null = serde_json::Value::Null;
+    let ctx = ActionContext {
+        rule,
+        config,
+        mode,
+        value: &null,
+    };
+    for action in BUILTIN_ACTIONS {
+        if let Some(response) = action_response(action.execute(event, &ctx).await?, rule, mode) {
+            return Ok(response);
+        }
+    }
+
+    for (name, value) in rule.actions.custom.iter().flatten() {
+        let ctx = ActionContext {
+            rule,
+            config,
+            mode,
+            value,
+        };
+        let outcome = if let Some(action) = crate::actions::get(name) {
+            action
+                .execute(event, &ctx)
+                .instrument(tracing::debug_span!("action", kind = "custom", name = %name))
+                .await
+        } else {
+            tracing::warn!(
+                "Unknown custom action '{}' in rule '{}' — failing closed",
+                name,
+                rule.name
+            );
+            Ok(ActionOutcome::Block(format!(
+                "Unknown custom action '{}' in rule '{}'",
+                name, rule.name
+            )))
+        };
+        match outcome {
+            Ok(outcome) => {
+                if let Some(response) = action_response(outcome, rule, mode) {
+                    return Ok(response);
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Custom action '{}' failed for rule '{}': {}",
+                    name,
+                    rule.name,
+                    e
+                );
+                if !rule.effective_fail_open(config.settings.fail_open) {
+                    return Err(RulezError::ScriptFailure {
+                        rule: rule.name.clone(),
+                        source: e.into(),
+                    }
+                    .into());
+                }
+            }
+        }
+    }
+
+    Ok(Response::allow())
+}
+
+/// The response for an outcome that ends the chain, with blocks softened
+/// to warnings in warn mode
+fn action_response(outcome: ActionOutcome, rule: &Rule, mode: PolicyMode) -> Option<Response> {
+    match outcome {
+        ActionOutcome::Block(reason) if mode == PolicyMode::Warn => {
+            Some(Response::inject(warn_mode_warning(&format!(
+                "Rule '{}' would block this operation: {}",
+                rule.name, reason
+            ))))
+        }
+        outcome => outcome.into_response(),
+    }
+}
+
+/// Last line of every warning generated for a warn-mode rule
+const WARN_MODE_NOTICE: &str = "\nThis rule is in 'warn' mode - operation will proceed.";
+
+/// `[WARNING] <message>` followed by the warn-mode notice
+fn warn_mode_warning(message: &str) -> String {
+    format!("[WARNING] {}{}", message, WARN_MODE_NOTICE)
+}
+
+/// Context with the generated warn-mode warnings removed
+///
+/// Returns `None` when there are none. The rule's own injected context is
+/// kept.
+fn strip_warn_mode_warnings(context: &str) -> Option<String> {
+    let mut kept = String::new();
+    let mut rest = context;
+    let mut found = false;
+    while let Some(end) = rest.find(WARN_MODE_NOTICE) {
+        let Some(start) = rest[..end].rfind("[WARNING] ") else {
+            break;
+        };
+        found = true;
+        kept.push_str(&rest[..start]);
+        rest = &rest[end + WARN_MODE_NOTICE.len()..];
+        // Drop the separator the warning was merged in with
+        if kept.ends_with("\n\n") {
+            kept.truncate(kept.len() - 2);
+        } else {
+            rest = rest.strip_prefix("\n\n").unwrap_or(rest);
         }
+    }
+    kept.push_str(rest);
+    found.then_some(kept)
+}
+
+/// Block in enforce mode; in warn mode inject `warning` instead
+fn block_or_warn(ctx: &ActionContext<'_>, reason: String, warning: &str) -> ActionOutcome {
+    if ctx.mode == PolicyMode::Warn {
+        ActionOutcome::Inject(warn_mode_warning(warning))
+    } else {
+        ActionOutcome::Block(reason)
+    }
+}
+
+/// The built-in actions, one per `actions` field
+mod builtin {
+    use futures::future::BoxFuture;
+    use tracing::Instrument;
+
+    use super::{
+        RulezError, ScriptDecision, block_or_warn, execute_inject_command, execute_inline_script,
+        execute_validator_script, find_pii, find_secrets, get_or_compile_regex, read_context_file,
+        run_rhai_script, send_rule_webhook, summarize_context, warn_mode_warning,
     };
+    use crate::actions::{Action, ActionContext, ActionOutcome};
+    use crate::models::{Event, PiiSeverity, PolicyMode};
 
-    let output = match timeout(
-        Duration::from_secs(timeout_secs as u64),
-        child.wait_with_output(),
-    )
-    .await
-    {
-        Ok(Ok(output)) => output,
-        Ok(Err(e)) => {
-            tracing::warn!(
-                "inject_command '{}' for rule '{}' failed: {}",
-                command_str,
-                rule.name,
-                e
-            );
-            return None;
-        }
-        Err(_) => {
-            tracing::warn!(
-                "inject_command '{}' for rule '{}' timed out after {}s",
-                command_str,
-                rule.name,
-                timeout_secs
-            );
-            return None;
+    type Outcome<'a> = BoxFuture<'a, anyhow::Result<ActionOutcome>>;
+
+    /// `webhook`: notify and carry on
+    pub(super) struct Webhook;
+
+    impl Action for Webhook {
+        fn name(&self) -> &'static str {
+            "webhook"
         }
-    };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        tracing::warn!(
-            "inject_command '{}' for rule '{}' failed with exit code {}: {}",
-            command_str,
-            rule.name,
-            output.status.code().unwrap_or(-1),
-            stderr.trim()
-        );
-        return None;
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                send_rule_webhook(event, ctx.rule).await;
+                Ok(ActionOutcome::Continue)
+            })
+        }
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    if stdout.trim().is_empty() {
-        return None; // No content to inject
+    /// `validate_expr`: gates all later actions
+    pub(super) struct ValidateExpr;
+
+    impl Action for ValidateExpr {
+        fn name(&self) -> &'static str {
+            "validate_expr"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some(ref expr) = ctx.rule.actions.validate_expr else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
+                let eval_ctx = super::build_eval_context_with_custom_functions(event);
+                Ok(match evalexpr::eval_boolean_with_context(expr, &eval_ctx) {
+                    Ok(true) => ActionOutcome::Continue,
+                    Ok(false) => block_or_warn(
+                        ctx,
+                        format!(
+                            "Validation failed for rule '{}': expression '{}' returned false",
+                            rule, expr
+                        ),
+                        &format!(
+                            "Rule '{}' validation expression '{}' returned false.",
+                            rule, expr
+                        ),
+                    ),
+                    // Expression error = fail-closed
+                    Err(e) => {
+                        if ctx.mode == PolicyMode::Enforce {
+                            tracing::warn!(
+                                "validate_expr error for rule '{}': {} - blocking (fail-closed)",
+                                rule,
+                                e
+                            );
+                        }
+                        block_or_warn(
+                            ctx,
+                            format!("Validation error for rule '{}': {}", rule, e),
+                            &format!("Rule '{}' validation expression error: {}.", rule, e),
+                        )
+                    }
+                })
+            })
+        }
     }
 
-    Some(stdout)
-}
+    /// `inline_script`: gates later actions unless `validate_expr` is set
+    pub(super) struct InlineScriptGate;
 
-/// Execute actions for a matching rule
-async fn execute_rule_actions(event: &Event, rule: &Rule, config: &Config) -> Result<Response> {
-    let actions = &rule.actions;
+    impl Action for InlineScriptGate {
+        fn name(&self) -> &'static str {
+            "inline_script"
+        }
 
-    // Step 0: Run inline validation (if present) - gates all subsequent actions
-    if let Some(ref expr) = actions.validate_expr {
-        let ctx = build_eval_context_with_custom_functions(event);
-        match eval_boolean_with_context(expr, &ctx) {
-            Ok(true) => {
-                // Validation passed, continue to other actions
-            }
-            Ok(false) => {
-                return Ok(Response::block(format!(
-                    "Validation failed for rule '{}': expression '{}' returned false",
-                    rule.name, expr
-                )));
-            }
-            Err(e) => {
-                // Expression error = fail-closed
-                tracing::warn!(
-                    "validate_expr error for rule '{}': {} - blocking (fail-closed)",
-                    rule.name,
-                    e
-                );
-                return Ok(Response::block(format!(
-                    "Validation error for rule '{}': {}",
-                    rule.name, e
-                )));
-            }
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let actions = &ctx.rule.actions;
+                let (None, Some(script)) = (&actions.validate_expr, &actions.inline_script) else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
+                Ok(
+                    match execute_inline_script(script, event, ctx.rule, ctx.config)
+                        .instrument(tracing::debug_span!("action", kind = "inline_script"))
+                        .await
+                    {
+                        Ok(true) => ActionOutcome::Continue,
+                        Ok(false) => block_or_warn(
+                            ctx,
+                            format!("Inline script validation failed for rule '{}'", rule),
+                            &format!("Rule '{}' inline script validation failed.", rule),
+                        ),
+                        Err(e) => {
+                            if ctx.mode == PolicyMode::Enforce {
+                                tracing::warn!(
+                                    "inline_script error for rule '{}': {} - blocking (fail-closed)",
+                                    rule,
+                                    e
+                                );
+                            }
+                            block_or_warn(
+                                ctx,
+                                format!("Inline script error for rule '{}': {}", rule, e),
+                                &format!("Rule '{}' inline script error: {}.", rule, e),
+                            )
+                        }
+                    },
+                )
+            })
         }
-    } else if let Some(ref script) = actions.inline_script {
-        match execute_inline_script(script, event, rule, config).await {
-            Ok(true) => {
-                // Validation passed, continue
-            }
-            Ok(false) => {
-                return Ok(Response::block(format!(
-                    "Inline script validation failed for rule '{}'",
-                    rule.name
-                )));
-            }
-            Err(e) => {
-                tracing::warn!(
-                    "inline_script error for rule '{}': {} - blocking (fail-closed)",
-                    rule.name,
-                    e
-                );
-                return Ok(Response::block(format!(
-                    "Inline script error for rule '{}': {}",
-                    rule.name, e
-                )));
-            }
+    }
+
+    /// `script_rhai`: gates later actions unless another validation is set
+    pub(super) struct RhaiScript;
+
+    impl Action for RhaiScript {
+        fn name(&self) -> &'static str {
+            "script_rhai"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let actions = &ctx.rule.actions;
+                let (None, None, Some(script)) = (
+                    &actions.validate_expr,
+                    &actions.inline_script,
+                    &actions.script_rhai,
+                ) else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
+                Ok(match run_rhai_script(script, event) {
+                    Ok(ScriptDecision::Allow) => ActionOutcome::Continue,
+                    Ok(ScriptDecision::Block(reason)) => block_or_warn(
+                        ctx,
+                        format!("Script validation failed for rule '{}': {}", rule, reason),
+                        &format!("Rule '{}' script validation failed: {}", rule, reason),
+                    ),
+                    Ok(ScriptDecision::Inject(context)) => ActionOutcome::Inject(context),
+                    Err(e) => {
+                        if ctx.mode == PolicyMode::Enforce {
+                            tracing::warn!(
+                                "script_rhai error for rule '{}': {} - blocking (fail-closed)",
+                                rule,
+                                e
+                            );
+                        }
+                        block_or_warn(
+                            ctx,
+                            format!("Script error for rule '{}': {}", rule, e),
+                            &format!("Rule '{}' script error: {}.", rule, e),
+                        )
+                    }
+                })
+            })
         }
     }
 
-    // Handle blocking
-    if let Some(block) = actions.block {
-        if block {
-            return Ok(Response::block(format!(
-                "Blocked by rule '{}': {}",
-                rule.name,
-                rule.description.as_deref().unwrap_or("No description")
-            )));
+    /// `block: true`
+    pub(super) struct Block;
+
+    impl Action for Block {
+        fn name(&self) -> &'static str {
+            "block"
+        }
+
+        fn execute<'a>(&'a self, _event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                if ctx.rule.actions.block != Some(true) {
+                    return Ok(ActionOutcome::Continue);
+                }
+                let rule = &ctx.rule.name;
+                let description = ctx.rule.description.as_deref().unwrap_or("No description");
+                Ok(block_or_warn(
+                    ctx,
+                    format!("Blocked by rule '{}': {}", rule, description),
+                    &format!(
+                        "Rule '{}' would block this operation: {}",
+                        rule, description
+                    ),
+                ))
+            })
         }
     }
 
-    // Handle conditional blocking
-    if let Some(ref pattern) = actions.block_if_match {
-        if let Some(ref tool_input) = event.tool_input {
-            if let Some(content) = tool_input
-                .get("newString")
-                .or_else(|| tool_input.get("content"))
-                .and_then(|c| c.as_str())
-            {
+    /// `block_if_match`: block written content matching a regex
+    pub(super) struct BlockIfMatch;
+
+    impl Action for BlockIfMatch {
+        fn name(&self) -> &'static str {
+            "block_if_match"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some(ref pattern) = ctx.rule.actions.block_if_match else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let Some(content) = event.tool_input.as_ref().and_then(|tool_input| {
+                    tool_input
+                        .get("newString")
+                        .or_else(|| tool_input.get("content"))
+                        .and_then(|c| c.as_str())
+                }) else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
                 if let Ok(regex) = get_or_compile_regex(pattern, false) {
                     if regex.is_match(content) {
-                        return Ok(Response::block(format!(
-                            "Content blocked by rule '{}': matches pattern '{}'",
-                            rule.name, pattern
-                        )));
+                        return Ok(block_or_warn(
+                            ctx,
+                            format!(
+                                "Content blocked by rule '{}': matches pattern '{}'",
+                                rule, pattern
+                            ),
+                            &format!(
+                                "Rule '{}' would block this content (matches pattern '{}').",
+                                rule, pattern
+                            ),
+                        ));
                     }
                 } else {
                     tracing::warn!(
                         "Invalid block_if_match regex '{}' in rule '{}' — failing closed",
                         pattern,
-                        rule.name
+                        rule
                     );
                 }
-            }
+                Ok(ActionOutcome::Continue)
+            })
         }
     }
 
-    // Handle inline content injection (takes precedence over inject)
-    if let Some(ref inline_content) = actions.inject_inline {
-        return Ok(Response::inject(inline_content.clone()));
+    /// `scan_secrets`
+    pub(super) struct ScanSecrets;
+
+    impl Action for ScanSecrets {
+        fn name(&self) -> &'static str {
+            "scan_secrets"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some(findings) = find_secrets(event, ctx.rule) else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
+                Ok(block_or_warn(
+                    ctx,
+                    format!("Possible secrets found by rule '{}': {}", rule, findings),
+                    &format!("Rule '{}' found possible secrets: {}", rule, findings),
+                ))
+            })
+        }
     }
 
-    // Handle command-based injection (after inject_inline, before inject file)
-    if let Some(ref command_str) = actions.inject_command {
-        if let Some(output) = execute_inject_command(command_str, rule, config).await {
-            return Ok(Response::inject(output));
+    /// `scan_pii`: the most severe category decides
+    pub(super) struct ScanPii;
+
+    impl Action for ScanPii {
+        fn name(&self) -> &'static str {
+            "scan_pii"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some((severity, findings)) = find_pii(event, ctx.rule) else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
+                Ok(match (ctx.mode, severity) {
+                    (_, PiiSeverity::Audit | PiiSeverity::Off) => ActionOutcome::Continue,
+                    (PolicyMode::Warn, _) => ActionOutcome::Inject(warn_mode_warning(&format!(
+                        "Rule '{}' found PII: {}",
+                        rule, findings
+                    ))),
+                    (_, PiiSeverity::Block) => {
+                        ActionOutcome::Block(format!("PII found by rule '{}': {}", rule, findings))
+                    }
+                    (_, PiiSeverity::Warn) => ActionOutcome::Inject(format!(
+                        "[WARNING] Rule '{}' found PII: {}",
+                        rule, findings
+                    )),
+                })
+            })
         }
-        // Command failed or produced no output - continue to next action
     }
 
-    // Handle context injection
-    if let Some(ref inject_path) = actions.inject {
-        match read_context_file(inject_path).await {
-            Ok(context) => {
-                return Ok(Response::inject(context));
-            }
-            Err(e) => {
-                tracing::warn!("Failed to read context file '{}': {}", inject_path, e);
-                // Continue without injection rather than failing
-            }
+    /// `inject_inline` (takes precedence over the other injections)
+    pub(super) struct InjectInline;
+
+    impl Action for InjectInline {
+        fn name(&self) -> &'static str {
+            "inject_inline"
+        }
+
+        fn execute<'a>(&'a self, _event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                Ok(ctx
+                    .rule
+                    .actions
+                    .inject_inline
+                    .clone()
+                    .map_or(ActionOutcome::Continue, ActionOutcome::Inject))
+            })
         }
     }
 
-    // Handle script execution
-    if let Some(script_path) = actions.script_path() {
-        match execute_validator_script(event, script_path, rule, config).await {
-            Ok(script_response) => {
-                return Ok(script_response);
+    /// Apply the rule's `summarize` action, if any, to injected content
+    async fn summarize_if_configured(
+        content: String,
+        event: &Event,
+        ctx: &ActionContext<'_>,
+    ) -> String {
+        match ctx.rule.actions.summarize {
+            Some(ref summarize) => {
+                summarize_context(content, summarize, event, ctx.rule, ctx.config)
+                    .instrument(tracing::debug_span!("action", kind = "summarize"))
+                    .await
             }
-            Err(e) => {
-                tracing::warn!("Script execution failed for rule '{}': {}", rule.name, e);
-                if !config.settings.fail_open {
-                    return Err(e);
+            None => content,
+        }
+    }
+
+    /// `inject_command`: inject a command's output
+    pub(super) struct InjectCommand;
+
+    impl Action for InjectCommand {
+        fn name(&self) -> &'static str {
+            "inject_command"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some(ref inject_command) = ctx.rule.actions.inject_command else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                let rule = &ctx.rule.name;
+                Ok(
+                    match execute_inject_command(inject_command, event, ctx.rule, ctx.config)
+                        .instrument(tracing::debug_span!("action", kind = "inject_command"))
+                        .await
+                    {
+                        Ok(Some(output)) => {
+                            ActionOutcome::Inject(summarize_if_configured(output, event, ctx).await)
+                        }
+                        // Command failed or produced no output - continue to next action
+                        Ok(None) => ActionOutcome::Continue,
+                        // Resource limits fail closed
+                        Err(e) => block_or_warn(
+                            ctx,
+                            format!(
+                                "inject_command for rule '{}' exceeded resource limits: {}",
+                                rule, e
+                            ),
+                            &format!(
+                                "Rule '{}' inject_command exceeded resource limits: {}",
+                                rule, e
+                            ),
+                        ),
+                    },
+                )
+            })
+        }
+    }
+
+    /// `inject`: inject a context file
+    pub(super) struct Inject;
+
+    impl Action for Inject {
+        fn name(&self) -> &'static str {
+            "inject"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some(ref inject_path) = ctx.rule.actions.inject else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                match read_context_file(inject_path, ctx.config)
+                    .instrument(tracing::debug_span!("action", kind = "inject"))
+                    .await
+                {
+                    Ok(context) => Ok(ActionOutcome::Inject(
+                        summarize_if_configured(context, event, ctx).await,
+                    )),
+                    Err(e) => {
+                        // Continue without injection rather than failing
+                        tracing::warn!(
+                            "Rule '{}' skipped inject '{}': {}",
+                            ctx.rule.name,
+                            inject_path,
+                            e
+                        );
+                        Ok(ActionOutcome::Continue)
+                    }
                 }
-                // Continue if fail_open is enabled
-            }
+            })
         }
     }
 
-    Ok(Response::allow())
+    /// `run`: a validator script decides
+    pub(super) struct Run;
+
+    impl Action for Run {
+        fn name(&self) -> &'static str {
+            "run"
+        }
+
+        fn execute<'a>(&'a self, event: &'a Event, ctx: &'a ActionContext<'a>) -> Outcome<'a> {
+            Box::pin(async move {
+                let Some(script_path) = ctx.rule.actions.script_path() else {
+                    return Ok(ActionOutcome::Continue);
+                };
+                match execute_validator_script(event, script_path, ctx.rule, ctx.config)
+                    .instrument(tracing::debug_span!("action", kind = "run"))
+                    .await
+                {
+                    Ok(response) if !response.continue_ && ctx.mode == PolicyMode::Warn => {
+                        Ok(ActionOutcome::Inject(warn_mode_warning(&format!(
+                            "Validator script '{}' would block this operation: {}",
+                            script_path,
+                            response.reason.as_deref().unwrap_or("No reason")
+                        ))))
+                    }
+                    Ok(response) => Ok(ActionOutcome::Respond(response)),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Script execution failed for rule '{}': {}",
+                            ctx.rule.name,
+                            e
+                        );
+                        if ctx.rule.effective_fail_open(ctx.config.settings.fail_open) {
+                            // Continue if fail_open is enabled
+                            Ok(ActionOutcome::Continue)
+                        } else {
+                            Err(RulezError::ScriptFailure {
+                                rule: ctx.rule.name.clone(),
+                                source: e.into(),
+                            }
+                            .into())
+                        }
+                    }
+                }
+            })
+        }
+    }
 }
 
+/// Scan written content for secrets when the rule enables `scan_secrets`
+///
+/// Returns the redacted findings, joined for a block reason or warning.
+fn find_secrets(event: &Event, rule: &Rule) -> Option<String> {
+    if rule.actions.scan_secrets != Some(true) {
+        return None;
+    }
+    let findings = crate::secrets::scan_tool_input(event.tool_input.as_ref()?);
+    if findings.is_empty() {
+        return None;
+    }
+    Some(
+        findings
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// Scan for PII when the rule enables `scan_pii`
+///
+/// Returns the most severe finding's severity and the redacted findings.
+/// Audit-level findings are logged here since they don't change the response.
+fn find_pii(event: &Event, rule: &Rule) -> Option<(PiiSeverity, String)> {
+    let severities = rule.actions.scan_pii.as_ref()?.severities()?;
+    let findings = crate::pii::scan_event(event, &severities);
+    if findings.is_empty() {
+        return None;
+    }
+    let severity = crate::pii::max_severity(&findings);
+    let summary = findings
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if severity == PiiSeverity::Audit {
+        tracing::info!("Rule '{}' found PII (audit): {}", rule.name, summary);
+    }
+    Some((severity, summary))
+}
+
+/// Files never injected, even from inside an inject root
+const CREDENTIAL_FILES: &[&str] = &[".credentials.json"];
+
 /// Read context file for injection
-async fn read_context_file(path: &str) -> Result<String> {
-    let content = tokio::fs::read_to_string(path).await?;
+///
+/// The file must resolve (after symlinks and `..`) to a location under one
+/// of `settings.inject_roots`, so a config edit can't pull arbitrary files
+/// such as `/etc/passwd` into the model context. Credential files such as
+/// `~/.claude/.credentials.json` are refused wherever they are.
+async fn read_context_file(path: &str, config: &Config) -> Result<String> {
+    let resolved = tokio::fs::canonicalize(crate::config::expand_path(path))
+        .await
+        .with_context(|| format!("Failed to resolve '{}'", path))?;
+    if resolved
+        .file_name()
+        .is_some_and(|name| CREDENTIAL_FILES.iter().any(|file| name == *file))
+    {
+        return Err(anyhow::anyhow!(
+            "'{}' is a credential file and is never injected",
+            resolved.display()
+        ));
+    }
+    let roots = inject_roots(&config.settings.inject_roots);
+    if !roots.iter().any(|root| resolved.starts_with(root)) {
+        return Err(anyhow::anyhow!(
+            "'{}' is outside settings.inject_roots; add its directory there to inject it",
+            resolved.display()
+        ));
+    }
+    let content = tokio::fs::read_to_string(&resolved).await?;
     Ok(content)
 }
 
+/// Project directory for an event: its cwd, else the process cwd
+fn project_dir(event: &Event) -> Option<PathBuf> {
+    event
+        .cwd
+        .as_deref()
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .or_else(|| std::env::current_dir().ok())
+}
+
+/// `inject` files of enabled rules that exist but resolve outside
+/// `settings.inject_roots`, as (rule, path); these are skipped when the rule
+/// fires
+pub(crate) fn injects_outside_roots(config: &Config) -> Vec<(String, String)> {
+    let roots = inject_roots(&config.settings.inject_roots);
+    let mut outside = Vec::new();
+    for rule in config.rules.iter().filter(|rule| rule.is_enabled()) {
+        for inject in rule.actions.steps().filter_map(|a| a.inject.as_deref()) {
+            let Ok(resolved) = std::fs::canonicalize(crate::config::expand_path(inject)) else {
+                continue;
+            };
+            if !roots.iter().any(|root| resolved.starts_with(root)) {
+                outside.push((rule.name.clone(), inject.to_string()));
+            }
+        }
+    }
+    outside
+}
+
+/// Resolve configured inject roots to canonical directories
+///
+/// Relative roots were made absolute against the config's project directory
+/// when it loaded; a config that didn't come from a file resolves them
+/// against the process cwd. `~/` is taken from the home directory, and the
+/// event's cwd is never used. Roots that don't exist are skipped.
+fn inject_roots(configured: &[String]) -> Vec<PathBuf> {
+    configured
+        .iter()
+        .filter_map(|root| {
+            let path = match root.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir()?.join(rest),
+                None => PathBuf::from(root),
+            };
+            std::fs::canonicalize(path).ok()
+        })
+        .collect()
+}
+
+/// Program to execute for an unpinned `run` script
+///
+/// Relative script paths stay relative to the hook process, not the
+/// working directory the script runs in; bare names are left to `PATH`.
+fn script_program(script_path: &str) -> PathBuf {
+    let script = crate::config::expand_path(script_path);
+    match std::env::current_dir() {
+        Ok(dir) if script.is_relative() && script_path.contains(['/', '\\']) => dir.join(script),
+        _ => script,
+    }
+}
+
+/// Copy a pinned script to a private file once its SHA-256 matches
+///
+/// The script is resolved like `config validate` does (bare names on
+/// `PATH`), read once, and the verified bytes are what runs, so the file
+/// can't be swapped between the check and exec.
+async fn pin_script(script_path: &str, expected: &str) -> Result<TempScript> {
+    let path = crate::config::find_script(script_path)
+        .and_then(|path| std::fs::canonicalize(path).ok())
+        .ok_or_else(|| anyhow::anyhow!("script not found"))?;
+    let content = tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read '{}'", path.display()))?;
+    verify_script_hash(&content, expected)?;
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    TempScript::write("pinned", &content, extension)
+}
+
+/// Check script content against its pinned SHA-256 (hex, case-insensitive)
+fn verify_script_hash(content: &[u8], expected: &str) -> Result<()> {
+    let actual = crate::log_chain::hex(&Sha256::digest(content));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(anyhow::anyhow!(
+            "expected sha256 {}, found {}",
+            expected.to_ascii_lowercase(),
+            actual
+        ));
+    }
+    Ok(())
+}
+
 /// Execute a validator script
 async fn execute_validator_script(
     event: &Event,
@@ -1277,13 +3950,70 @@ async fn execute_validator_script(
     rule: &Rule,
     config: &Config,
 ) -> Result<Response> {
-    let timeout_duration = rule
-        .metadata
-        .as_ref()
-        .map(|m| m.timeout)
-        .unwrap_or(config.settings.script_timeout);
+    let timeout_after = script_timeout(rule, config, ScriptKind::Run);
+
+    let run = rule.actions.run.as_ref();
+    let trust = run.map_or(TrustLevel::Local, RunAction::effective_trust_level);
+    if let Some(minimum) = config.settings.minimum_trust {
+        if !trust.satisfies(minimum) {
+            return Ok(Response::block(format!(
+                "Validator script '{}' has trust level '{}', below the required '{}'",
+                script_path, trust, minimum
+            )));
+        }
+    }
+
+    let mut environment = run.and_then(RunAction::environment).cloned();
+    if trust == TrustLevel::Untrusted && config.settings.restrict_untrusted {
+        environment
+            .get_or_insert_with(ScriptEnvironment::default)
+            .env_clear = true;
+    }
+    let environment = environment.as_ref();
 
-    let mut command = Command::new(script_path);
+    // Pinned scripts fail closed: an edited validator must not run, and the
+    // verified copy is what executes
+    let pinned = match run.and_then(RunAction::sha256) {
+        Some(expected) => match pin_script(script_path, expected).await {
+            Ok(pinned) => Some(pinned),
+            Err(e) => {
+                return Ok(Response::block(format!(
+                    "Validator script '{}' failed hash verification: {}",
+                    script_path, e
+                )));
+            }
+        },
+        None => None,
+    };
+    let program = pinned
+        .as_ref()
+        .map_or_else(|| script_program(script_path), |pinned| pinned.path.clone());
+
+    let mut command = Command::new(program);
+    command.args(script_args(environment, event));
+    apply_script_environment(&mut command, environment, event);
+    let limits = &config.settings.script_limits;
+    limits.apply(&mut command);
+
+    // Bound concurrent children; waiting for a slot counts against the timeout
+    let Ok(_slot) = timeout(
+        timeout_after,
+        SCRIPT_SLOTS.acquire(limits.max_concurrent, rule.effective_priority()),
+    )
+    .await
+    else {
+        tracing::warn!(
+            "Validator script '{}' timed out waiting for a script slot",
+            script_path
+        );
+        if rule.effective_fail_open(config.settings.fail_open) {
+            return Ok(Response::allow());
+        }
+        return Err(anyhow::anyhow!("Timed out waiting for a script slot"));
+    };
+    if let Some(dir) = resolve_working_dir(environment.and_then(|e| e.cwd.as_deref()), event) {
+        command.current_dir(dir);
+    }
     command.stdin(std::process::Stdio::piped());
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
@@ -1294,13 +4024,24 @@ async fn execute_validator_script(
         Ok(c) => c,
         Err(e) => {
             tracing::warn!("Failed to spawn validator script '{}': {}", script_path, e);
-            if config.settings.fail_open {
+            if rule.effective_fail_open(config.settings.fail_open) {
                 return Ok(Response::allow());
             }
             return Err(e.into());
         }
     };
 
+    let containment = match limits.contain(&child) {
+        Ok(containment) => containment,
+        Err(e) => {
+            child.kill().await.ok();
+            return Ok(Response::block(format!(
+                "Failed to apply resource limits to validator script '{}': {}",
+                script_path, e
+            )));
+        }
+    };
+
     // Send event as JSON to script stdin
     if let Some(stdin) = child.stdin.as_mut() {
         let event_json = serde_json::to_string(event)?;
@@ -1312,33 +4053,52 @@ async fn execute_validator_script(
 
     // Wait for script completion with timeout
     let output_result = timeout(
-        Duration::from_secs(timeout_duration as u64),
-        child.wait_with_output(),
+        timeout_after,
+        wait_with_limited_output(
+            &mut child,
+            limits.max_output_bytes,
+            limits.truncate_stdout_bytes,
+        ),
     )
     .await;
 
     let output = match output_result {
         Ok(Ok(o)) => o,
+        Ok(Err(e)) if e.is::<ResourceLimitExceeded>() => {
+            // Resource limits fail closed regardless of fail_open
+            return Ok(Response::block(format!(
+                "Validator script '{}' exceeded resource limits: {}",
+                script_path, e
+            )));
+        }
         Ok(Err(e)) => {
             tracing::warn!("Validator script '{}' failed: {}", script_path, e);
-            if config.settings.fail_open {
+            if rule.effective_fail_open(config.settings.fail_open) {
                 return Ok(Response::allow());
             }
-            return Err(e.into());
+            return Err(e);
         }
         Err(_) => {
+            child.kill().await.ok();
             tracing::warn!(
-                "Validator script '{}' timed out after {}s",
+                "Validator script '{}' timed out after {:?}",
                 script_path,
-                timeout_duration
+                timeout_after
             );
-            if config.settings.fail_open {
+            if rule.effective_fail_open(config.settings.fail_open) {
                 return Ok(Response::allow());
             }
             return Err(anyhow::anyhow!("Script timed out"));
         }
     };
 
+    if let Err(e) = limits.check_exit(&output, &containment) {
+        return Ok(Response::block(format!(
+            "Validator script '{}' exceeded resource limits: {}",
+            script_path, e
+        )));
+    }
+
     let exit_code = output.status.code().unwrap_or(-1);
 
     if exit_code == 0 {
@@ -1361,24 +4121,173 @@ async fn execute_validator_script(
     }
 }
 
-/// Merge two responses (block takes precedence, inject accumulates)
-fn merge_responses(mut existing: Response, new: Response) -> Response {
-    // Block takes precedence
-    if !new.continue_ {
-        return new;
+/// Merge two responses (block takes precedence, inject accumulates)
+fn merge_responses(mut existing: Response, new: Response) -> Response {
+    // Block takes precedence
+    if !new.continue_ {
+        return new;
+    }
+
+    // Accumulate context
+    if let Some(new_context) = new.context {
+        if let Some(existing_context) = existing.context.as_mut() {
+            existing_context.push_str("\n\n");
+            existing_context.push_str(&new_context);
+        } else {
+            existing.context = Some(new_context);
+        }
+    }
+
+    // Accumulate user-facing messages
+    if let Some(new_message) = new.system_message {
+        if let Some(existing_message) = existing.system_message.as_mut() {
+            existing_message.push('\n');
+            existing_message.push_str(&new_message);
+        } else {
+            existing.system_message = Some(new_message);
+        }
+    }
+
+    // Accumulate transcript output
+    if let Some(new_stderr) = new.stderr {
+        if let Some(existing_stderr) = existing.stderr.as_mut() {
+            existing_stderr.push('\n');
+            existing_stderr.push_str(&new_stderr);
+        } else {
+            existing.stderr = Some(new_stderr);
+        }
+    }
+
+    // One rule asking to keep its output out of the transcript is enough,
+    // but a block always stays visible
+    if new.suppress_output == Some(true) && existing.continue_ {
+        existing.suppress_output = Some(true);
+    }
+
+    // The strictest permission decision wins (ask over allow)
+    if let Some(new_output) = new.hook_specific_output {
+        let stricter = existing
+            .hook_specific_output
+            .as_ref()
+            .is_none_or(|output| new_output.permission_decision > output.permission_decision);
+        if stricter {
+            existing.hook_specific_output = Some(new_output);
+        }
+    }
+
+    existing
+}
+
+/// Fill in `hookSpecificOutput.additionalContext` for events that accept it
+///
+/// The legacy top-level `context` field is kept for existing integrations.
+pub(crate) fn finalize_hook_output(response: &mut Response, event_type: EventType) {
+    let Some(ref context) = response.context else {
+        return;
+    };
+    if matches!(
+        event_type,
+        EventType::UserPromptSubmit | EventType::SessionStart | EventType::PostToolUse
+    ) {
+        response
+            .hook_specific_output
+            .get_or_insert_with(|| HookSpecificOutput::new(event_type))
+            .additional_context = Some(context.clone());
     }
+}
 
-    // Accumulate context
-    if let Some(new_context) = new.context {
-        if let Some(existing_context) = existing.context.as_mut() {
-            existing_context.push_str("\n\n");
-            existing_context.push_str(&new_context);
-        } else {
-            existing.context = Some(new_context);
+/// Apply the `rewrite_prompt` actions of matched rules to the event's prompt
+///
+/// Rewrites chain in priority order and the result is returned as
+/// `updatedPrompt`. Claude Code can't replace a submitted prompt, so:
+/// - prepended/appended text is also injected as context, and
+/// - a redaction blocks the prompt (which erases it from the conversation)
+///   and shows the redacted prompt so the user can resubmit it.
+///
+/// Warn-mode rules only warn when they would redact; audit-mode rules do
+/// nothing.
+fn apply_prompt_rewrites(mut response: Response, event: &Event, rules: &[&Rule]) -> Response {
+    let Some(ref original) = event.prompt else {
+        return response;
+    };
+    if !response.continue_ {
+        return response;
+    }
+
+    let mut prompt = original.clone();
+    let mut added = Vec::new();
+    let mut redacted_by = Vec::new();
+    for rule in rules {
+        let Some(rewrite) = rule
+            .actions
+            .steps()
+            .find_map(|actions| actions.rewrite_prompt.as_ref())
+        else {
+            continue;
+        };
+        match rule.effective_mode() {
+            PolicyMode::Audit => {}
+            PolicyMode::Warn => {
+                if redact_prompt(&prompt, rewrite).is_some() {
+                    let warning = format!(
+                        "[WARNING] Rule '{}' would redact part of this prompt.\n\
+                         This rule is in 'warn' mode - operation will proceed.",
+                        rule.name
+                    );
+                    response = merge_responses(response, Response::inject(warning));
+                }
+            }
+            PolicyMode::Enforce => {
+                if let Some(redacted) = redact_prompt(&prompt, rewrite) {
+                    prompt = redacted;
+                    redacted_by.push(rule.name.as_str());
+                }
+                if let Some(ref prepend) = rewrite.prepend {
+                    prompt = format!("{prepend}\n\n{prompt}");
+                    added.push(prepend.clone());
+                }
+                if let Some(ref append) = rewrite.append {
+                    prompt = format!("{prompt}\n\n{append}");
+                    added.push(append.clone());
+                }
+            }
         }
     }
 
-    existing
+    if prompt == *original {
+        return response;
+    }
+    if !redacted_by.is_empty() {
+        let mut blocked = Response::block(format!(
+            "Prompt blocked by rule '{}': it contains text that must not be sent to the model. \
+             Resubmit the redacted prompt:\n\n{}",
+            redacted_by.join("', '"),
+            prompt
+        ));
+        blocked.updated_prompt = Some(prompt);
+        return blocked;
+    }
+    response = merge_responses(response, Response::inject(added.join("\n\n")));
+    response.updated_prompt = Some(prompt);
+    response
+}
+
+/// Replace every `redact` match in a prompt; `None` if nothing matched
+///
+/// Invalid regexes are skipped (config validation rejects them up front).
+fn redact_prompt(prompt: &str, rewrite: &PromptRewrite) -> Option<String> {
+    let mut redacted = prompt.to_string();
+    for pattern in &rewrite.redact {
+        match get_or_compile_regex(pattern, false) {
+            Ok(regex) => {
+                redacted = regex
+                    .replace_all(&redacted, rewrite.replacement())
+                    .into_owned();
+            }
+            Err(e) => tracing::warn!("Invalid redact regex '{}': {}", pattern, e),
+        }
+    }
+    (redacted != prompt).then_some(redacted)
 }
 
 // =============================================================================
@@ -1397,171 +4306,376 @@ async fn execute_rule_actions_with_mode(
     config: &Config,
     mode: PolicyMode,
 ) -> Result<Response> {
-    match mode {
-        PolicyMode::Enforce => {
-            // Normal execution - delegate to existing function
-            execute_rule_actions(event, rule, config).await
-        }
-        PolicyMode::Warn => {
-            // Never block, inject warning instead
-            execute_rule_actions_warn_mode(event, rule, config).await
-        }
-        PolicyMode::Audit => {
-            // Log only, no blocking or injection
-            Ok(Response::allow())
+    #[cfg(not(feature = "process"))]
+    if let Some(action) = rule.actions.process_action() {
+        return Ok(process_disabled(rule, action, mode));
+    }
+
+    let span = tracing::debug_span!("rule_actions", rule = %rule.name, mode = %mode);
+    async {
+        match mode {
+            // Warn mode never blocks: the actions inject warnings instead
+            PolicyMode::Enforce | PolicyMode::Warn => {
+                let response = if let Some(ref steps) = rule.actions.pipeline {
+                    execute_pipeline(event, rule, steps, config, mode).await?
+                } else if actions_apply(&rule.actions, rule, event) {
+                    let response = execute_rule_actions(event, rule, config, mode).await?;
+                    apply_output_actions(response, event, rule, mode)
+                } else {
+                    return Ok(Response::allow());
+                };
+                Ok(throttle_warning(response, event, rule, config, mode))
+            }
+            PolicyMode::Audit => {
+                // Log only, no blocking or injection
+                Ok(Response::allow())
+            }
         }
     }
+    .instrument(span)
+    .await
 }
 
-/// Execute rule actions in warn mode (never blocks, injects warnings)
-async fn execute_rule_actions_warn_mode(
+/// Drop a warn-mode rule's warning if it was shown too recently this session
+///
+/// Only applies with `settings.warn_throttle`, and only to the generated
+/// `[WARNING]` text: context the rule injects itself is always kept. When
+/// the warning is shown again it notes how many times it was suppressed in
+/// between. State errors are logged and the warning is shown.
+fn throttle_warning(
+    mut response: Response,
     event: &Event,
     rule: &Rule,
     config: &Config,
-) -> Result<Response> {
-    let actions = &rule.actions;
+    mode: PolicyMode,
+) -> Response {
+    use std::fmt::Write as _;
 
-    // Step 0: Run inline validation (if present) - convert failures to warnings
-    if let Some(ref expr) = actions.validate_expr {
-        let ctx = build_eval_context_with_custom_functions(event);
-        match eval_boolean_with_context(expr, &ctx) {
-            Ok(true) => {
-                // Validation passed
-            }
-            Ok(false) => {
-                let warning = format!(
-                    "[WARNING] Rule '{}' validation expression '{}' returned false.\n\
-                     This rule is in 'warn' mode - operation will proceed.",
-                    rule.name, expr
-                );
-                return Ok(Response::inject(warning));
+    let Some(ref throttle) = config.settings.warn_throttle else {
+        return response;
+    };
+    if mode != PolicyMode::Warn {
+        return response;
+    }
+    let Some(remaining) = response
+        .context
+        .as_deref()
+        .and_then(strip_warn_mode_warnings)
+    else {
+        return response;
+    };
+    match crate::session::claim_warning(throttle, event, &rule.name) {
+        Ok(None) => {
+            tracing::debug!("Suppressing repeated warning from rule '{}'", rule.name);
+            response.context = (!remaining.is_empty()).then_some(remaining);
+        }
+        Ok(Some(suppressed)) => {
+            if suppressed > 0 {
+                if let Some(context) = response.context.as_mut() {
+                    let _ = write!(
+                        context,
+                        "\n(Suppressed {} time{} since it was last shown.)",
+                        suppressed,
+                        if suppressed == 1 { "" } else { "s" }
+                    );
+                }
             }
-            Err(e) => {
-                let warning = format!(
-                    "[WARNING] Rule '{}' validation expression error: {}.\n\
-                     This rule is in 'warn' mode - operation will proceed.",
-                    rule.name, e
+        }
+        Err(e) => tracing::warn!("Failed to record warning for rule '{}': {:#}", rule.name, e),
+    }
+    response
+}
+
+/// Whether an action set's `when` condition holds (true when there is none)
+///
+/// Like `enabled_when`, an expression error counts as false.
+fn actions_apply(actions: &Actions, rule: &Rule, event: &Event) -> bool {
+    let Some(ref expr) = actions.when else {
+        return true;
+    };
+    let ctx = build_eval_context(event);
+    match eval_boolean_with_context(expr, &ctx) {
+        Ok(result) => {
+            if !result {
+                tracing::debug!(
+                    "Skipping actions of rule '{}': when '{}' is false",
+                    rule.name,
+                    expr
                 );
-                return Ok(Response::inject(warning));
             }
+            result
+        }
+        Err(e) => {
+            tracing::warn!(
+                "when expression failed for rule '{}': {} - skipping its actions",
+                rule.name,
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Run the steps of an `actions:` list in order
+///
+/// Each step runs like a rule with just that step's actions. Context and
+/// messages accumulate across steps. A step that blocks or errors ends the
+/// pipeline unless it sets `continue_on_failure`.
+async fn execute_pipeline(
+    event: &Event,
+    rule: &Rule,
+    steps: &[ActionStep],
+    config: &Config,
+    mode: PolicyMode,
+) -> Result<Response> {
+    let mut response = Response::allow();
+    for (index, step) in steps.iter().enumerate() {
+        if !actions_apply(&step.actions, rule, event) {
+            continue;
         }
-    } else if let Some(ref script) = actions.inline_script {
-        match execute_inline_script(script, event, rule, config).await {
-            Ok(true) => {
-                // Validation passed
+        let step_rule = Rule {
+            actions: step.actions.clone(),
+            ..rule.clone()
+        };
+        let result = execute_rule_actions(event, &step_rule, config, mode)
+            .instrument(tracing::debug_span!("pipeline_step", step = index + 1))
+            .await
+            .map(|step_response| apply_output_actions(step_response, event, &step_rule, mode));
+        match result {
+            Ok(step_response) if step_response.continue_ => {
+                response = merge_responses(response, step_response);
             }
-            Ok(false) => {
-                let warning = format!(
-                    "[WARNING] Rule '{}' inline script validation failed.\n\
-                     This rule is in 'warn' mode - operation will proceed.",
-                    rule.name
+            Ok(step_response) if step.continue_on_failure => {
+                tracing::info!(
+                    "Step {} of rule '{}' blocked; continuing: {}",
+                    index + 1,
+                    rule.name,
+                    step_response.reason.as_deref().unwrap_or("No reason")
                 );
-                return Ok(Response::inject(warning));
             }
-            Err(e) => {
-                let warning = format!(
-                    "[WARNING] Rule '{}' inline script error: {}.\n\
-                     This rule is in 'warn' mode - operation will proceed.",
-                    rule.name, e
+            Ok(step_response) => return Ok(step_response),
+            Err(e) if step.continue_on_failure => {
+                tracing::warn!(
+                    "Step {} of rule '{}' failed; continuing: {}",
+                    index + 1,
+                    rule.name,
+                    e
                 );
-                return Ok(Response::inject(warning));
             }
+            Err(e) => return Err(e),
         }
     }
+    Ok(response)
+}
+
+/// Response for a rule whose action needs the disabled `process` feature
+///
+/// Enforce mode fails closed; warn mode only warns.
+#[cfg(not(feature = "process"))]
+fn process_disabled(rule: &Rule, action: &str, mode: PolicyMode) -> Response {
+    let reason = format!(
+        "Rule '{}' uses '{}', which needs RuleZ built with the 'process' feature",
+        rule.name, action
+    );
+    tracing::warn!("{}", reason);
+    match mode {
+        PolicyMode::Enforce => Response::block(reason),
+        PolicyMode::Warn => Response::inject(format!("[WARNING] {}", reason)),
+        PolicyMode::Audit => Response::allow(),
+    }
+}
 
-    // Convert blocks to warnings
-    if let Some(block) = actions.block {
-        if block {
-            let warning = format!(
-                "[WARNING] Rule '{}' would block this operation: {}\n\
-                 This rule is in 'warn' mode - operation will proceed.",
-                rule.name,
-                rule.description.as_deref().unwrap_or("No description")
-            );
-            return Ok(Response::inject(warning));
+/// POST the event to the rule's `webhook` URL, if any
+///
+/// Delivery failures are logged and never affect the hook outcome. curl is
+/// awaited, not waited on, so a slow endpoint doesn't stall a daemon's
+/// other connections.
+async fn send_rule_webhook(event: &Event, rule: &Rule) {
+    let Some(ref webhook) = rule.actions.webhook else {
+        return;
+    };
+    let payload = serde_json::json!({
+        "rule": rule.name,
+        "event": event,
+    });
+    let headers = webhook
+        .headers()
+        .iter()
+        .map(|(k, v)| (k.clone(), crate::logging::expand_env_vars(v)))
+        .collect();
+    let result = match serde_json::to_vec(&payload) {
+        Ok(body) => {
+            crate::logging::post_via_curl(
+                webhook.url(),
+                &body,
+                &headers,
+                std::time::Duration::from_secs(webhook.timeout_secs()),
+            )
+            .await
         }
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = result {
+        tracing::warn!(
+            "Rule '{}' webhook to {} failed: {}",
+            rule.name,
+            webhook.url(),
+            e
+        );
     }
+}
 
-    // Convert conditional blocks to warnings
-    if let Some(ref pattern) = actions.block_if_match {
-        if let Some(ref tool_input) = event.tool_input {
-            if let Some(content) = tool_input
-                .get("newString")
-                .or_else(|| tool_input.get("content"))
-                .and_then(|c| c.as_str())
-            {
-                if let Ok(regex) = get_or_compile_regex(pattern, false) {
-                    if regex.is_match(content) {
-                        let warning = format!(
-                            "[WARNING] Rule '{}' would block this content (matches pattern '{}').\n\
-                             This rule is in 'warn' mode - operation will proceed.",
-                            rule.name, pattern
-                        );
-                        return Ok(Response::inject(warning));
-                    }
-                } else {
-                    tracing::warn!(
-                        "Invalid block_if_match regex '{}' in rule '{}' — failing closed",
-                        pattern,
-                        rule.name
-                    );
-                }
+/// Apply `system_message` and `permission_decision` to a rule's response
+///
+/// Blocked responses are returned unchanged: only the block reason reaches
+/// the client. The permission decision only applies to `PreToolUse` events
+/// in enforce mode; `deny` is equivalent to `block: true`.
+fn apply_output_actions(
+    mut response: Response,
+    event: &Event,
+    rule: &Rule,
+    mode: PolicyMode,
+) -> Response {
+    if !response.continue_ {
+        return response;
+    }
+
+    let actions = &rule.actions;
+    if let Some(decision) = actions.permission_decision {
+        if mode == PolicyMode::Enforce && event.hook_event_name == EventType::PreToolUse {
+            let reason = format!(
+                "Rule '{}': {}",
+                rule.name,
+                rule.description.as_deref().unwrap_or("No description")
+            );
+            if decision == PermissionDecision::Deny {
+                return Response::block(reason);
             }
+            let mut output = HookSpecificOutput::new(event.hook_event_name);
+            output.permission_decision = Some(decision);
+            output.permission_decision_reason = Some(reason);
+            response.hook_specific_output = Some(output);
         }
     }
 
-    // Handle inline content injection (takes precedence over inject)
-    if let Some(ref inline_content) = actions.inject_inline {
-        return Ok(Response::inject(inline_content.clone()));
+    if let Some(ref message) = actions.system_message {
+        response.system_message = Some(message.clone());
     }
 
-    // Handle command-based injection (after inject_inline, before inject file)
-    if let Some(ref command_str) = actions.inject_command {
-        if let Some(output) = execute_inject_command(command_str, rule, config).await {
-            return Ok(Response::inject(output));
-        }
-        // Command failed or produced no output - continue to next action
+    if actions.suppress_output == Some(true) {
+        response.suppress_output = Some(true);
     }
 
-    // Context injection still works in warn mode
-    if let Some(ref inject_path) = actions.inject {
-        match read_context_file(inject_path).await {
-            Ok(context) => {
-                return Ok(Response::inject(context));
+    // Injected content goes to the model's context unless `inject_as` says
+    // otherwise
+    match actions.inject_as.unwrap_or_default() {
+        InjectTarget::Context => {}
+        InjectTarget::SystemMessage => {
+            if let Some(context) = response.context.take() {
+                response.system_message = Some(match response.system_message.take() {
+                    Some(message) => format!("{message}\n{context}"),
+                    None => context,
+                });
             }
-            Err(e) => {
-                tracing::warn!("Failed to read context file '{}': {}", inject_path, e);
+        }
+        InjectTarget::Stderr => {
+            if let Some(context) = response.context.take() {
+                response.stderr = Some(context);
             }
         }
     }
 
-    // Script execution - convert blocks to warnings
-    if let Some(script_path) = actions.script_path() {
-        match execute_validator_script(event, script_path, rule, config).await {
-            Ok(script_response) => {
-                if !script_response.continue_ {
-                    // Convert block to warning
-                    let warning = format!(
-                        "[WARNING] Validator script '{}' would block this operation: {}\n\
-                         This rule is in 'warn' mode - operation will proceed.",
-                        script_path,
-                        script_response.reason.as_deref().unwrap_or("No reason")
-                    );
-                    return Ok(Response::inject(warning));
-                }
-                return Ok(script_response);
-            }
-            Err(e) => {
-                tracing::warn!("Script execution failed for rule '{}': {}", rule.name, e);
-                if !config.settings.fail_open {
-                    // Even in warn mode, respect fail_open setting
-                    return Err(e);
-                }
-            }
+    response
+}
+
+/// Merge the responses of matched rules, ordering injected context by `inject_priority`
+///
+/// Everything but the context merges in evaluation order. The context pieces
+/// that survive the merge (those from the last blocking rule on) are then
+/// stably sorted, highest `inject_priority` first, so rules that don't set it
+/// keep evaluation order. Identical pieces are merged into the first one.
+/// With `settings.inject_headers` each piece starts with a heading naming
+/// its rules. Finally the pieces are fitted into
+/// `settings.max_context_bytes` (see [`crate::context_budget`]) and each
+/// piece's size is recorded for the audit log.
+fn merge_rule_responses(rule_responses: Vec<(&Rule, Response)>, settings: &Settings) -> Response {
+    let mut response = Response::allow();
+    let mut contexts: Vec<(&Rule, String)> = Vec::new();
+    for (rule, mut rule_response) in rule_responses {
+        if !rule_response.continue_ {
+            // A block replaces everything merged before it
+            contexts.clear();
         }
+        if let Some(context) = rule_response.context.take() {
+            contexts.push((rule, context));
+        }
+        response = merge_responses_with_mode(response, rule_response, rule.effective_mode());
     }
 
-    Ok(Response::allow())
+    contexts.sort_by_key(|(rule, _)| std::cmp::Reverse(rule.effective_inject_priority()));
+
+    // Identical content (ignoring surrounding whitespace) is injected once,
+    // at the position of its highest-priority copy
+    let mut pieces: Vec<context_budget::Piece> = Vec::with_capacity(contexts.len());
+    for (rule, context) in contexts {
+        if let Some(first) = pieces
+            .iter_mut()
+            .find(|piece| piece.text.trim() == context.trim())
+        {
+            first.merged.push(rule.name.clone());
+        } else {
+            pieces.push(context_budget::Piece {
+                rule: rule.name.clone(),
+                text: context,
+                weight: rule.effective_context_weight(),
+                merged: Vec::new(),
+            });
+        }
+    }
+    for piece in &mut pieces {
+        if !piece.merged.is_empty() {
+            tracing::debug!(
+                "Rules {} injected the same context as '{}'; injecting it once",
+                piece.merged.join(", "),
+                piece.rule
+            );
+        }
+        if settings.inject_headers {
+            let mut names = vec![piece.rule.as_str()];
+            names.extend(piece.merged.iter().map(String::as_str));
+            piece.text = format!("## {}\n\n{}", names.join(", "), piece.text);
+        }
+    }
+
+    let budget = context_budget::budget_bytes(
+        settings
+            .max_context_bytes
+            .unwrap_or(settings.max_context_size),
+        settings.max_context_tokens,
+    );
+    let cut = context_budget::fit(&mut pieces, budget, settings.context_truncation);
+    if !cut.is_empty() {
+        tracing::warn!(
+            "Injected context is over the {} byte budget; cut or dropped context from: {}",
+            budget,
+            cut.join(", ")
+        );
+    }
+
+    if !pieces.is_empty() {
+        response.context_blocks = pieces
+            .iter()
+            .map(|piece| ContextEstimate {
+                rule: piece.rule.clone(),
+                bytes: piece.text.len(),
+                tokens: context_budget::estimate_tokens(&piece.text),
+                also_injected_by: piece.merged.clone(),
+            })
+            .collect();
+        let texts: Vec<String> = pieces.into_iter().map(|piece| piece.text).collect();
+        response.context = Some(texts.join(context_budget::SEPARATOR));
+    }
+    response
 }
 
 /// Merge responses with mode awareness
@@ -1739,6 +4853,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -1754,21 +4875,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 block: Some(true),
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(matches_rule(&event, &rule));
@@ -1790,6 +4941,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -1805,21 +4963,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 block: Some(true),
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!matches_rule(&event, &rule));
@@ -1845,6 +5033,24 @@ mod tests {
     // Phase 3: is_rule_enabled Tests
     // =========================================================================
 
+    #[test]
+    fn test_strip_warn_mode_warnings_keeps_rule_context() {
+        let warning = warn_mode_warning("Rule 'x' would block this operation: no");
+        assert_eq!(strip_warn_mode_warnings(&warning).as_deref(), Some(""));
+        assert_eq!(
+            strip_warn_mode_warnings(&format!("{warning}\n\nRead CONTRIBUTING.md")).as_deref(),
+            Some("Read CONTRIBUTING.md")
+        );
+        assert_eq!(
+            strip_warn_mode_warnings(&format!("Read CONTRIBUTING.md\n\n{warning}")).as_deref(),
+            Some("Read CONTRIBUTING.md")
+        );
+        assert_eq!(
+            strip_warn_mode_warnings("[WARNING] from the rule itself"),
+            None
+        );
+    }
+
     #[test]
     fn test_is_rule_enabled_no_condition() {
         let event = Event {
@@ -1859,6 +5065,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -1874,24 +5087,356 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
+        };
+
+        assert!(is_rule_enabled(&rule, &event));
+    }
+
+    #[test]
+    fn test_rule_timings_slowest() {
+        let rule = |name: &str| Rule {
+            name: name.to_string(),
+            ..serde_yaml::from_str("name: x\nmatchers: {}\nactions: {}").unwrap()
+        };
+        let (fast, slow) = (rule("fast"), rule("slow"));
+
+        let mut timings = RuleTimings::default();
+        assert_eq!(timings.slowest(), None);
+        let origin = std::time::Instant::now();
+        let later = origin + Duration::from_millis(3);
+        timings.record(&fast, origin, Duration::from_millis(3));
+        timings.record(&slow, later, Duration::from_millis(2));
+        timings.record(
+            &slow,
+            later + Duration::from_millis(5),
+            Duration::from_millis(2),
+        );
+        assert_eq!(timings.slowest(), Some(("slow", Duration::from_millis(4))));
+
+        assert_eq!(
+            timings.to_log(origin),
+            [
+                RuleTiming {
+                    rule: "fast".to_string(),
+                    offset_us: 0,
+                    duration_us: 3000,
+                },
+                RuleTiming {
+                    rule: "slow".to_string(),
+                    offset_us: 3000,
+                    duration_us: 4000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_latency_budget() {
+        let rule: Rule = serde_yaml::from_str("name: slow\nmatchers: {}\nactions: {}").unwrap();
+        let mut timings = RuleTimings::default();
+        timings.record(&rule, std::time::Instant::now(), Duration::from_millis(40));
+        let config_load = Duration::from_micros(1500);
+
+        // Within budget, or no budget
+        assert_eq!(check_latency_budget(50, 50, config_load, &timings), None);
+        assert_eq!(check_latency_budget(500, 0, config_load, &timings), None);
+
+        assert_eq!(
+            check_latency_budget(60, 50, config_load, &timings),
+            Some(LatencyOverrun {
+                budget_ms: 50,
+                config_load_us: 1500,
+                slowest_rule: Some("slow".to_string()),
+                slowest_rule_us: 40_000,
+            })
+        );
+        let overrun = check_latency_budget(60, 50, config_load, &RuleTimings::default()).unwrap();
+        assert_eq!(overrun.slowest_rule, None);
+        assert_eq!(overrun.slowest_rule_us, 0);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_evaluation_matches_sequential_order() {
+        use std::fmt::Write as _;
+
+        let mut yaml = String::from("version: \"1.0\"\nrules:\n");
+        for i in 0..PARALLEL_THRESHOLD + 4 {
+            let tool = if i % 3 == 0 { "Bash" } else { "MultiEdit" };
+            write!(
+                yaml,
+                "  - name: rule-{i}\n    mode: {mode}\n    priority: {i}\n    matchers:\n      tools: [{tool}]\n    actions:\n      inject_inline: \"note {i}\"\n",
+                mode = if i % 2 == 0 { "warn" } else { "audit" },
+            )
+            .unwrap();
+        }
+        let sequential: Config = serde_yaml::from_str(&yaml).unwrap();
+        let mut parallel = sequential.clone();
+        parallel.settings.parallel_evaluation = true;
+
+        let event = stdlib_test_event();
+        let debug_config = DebugConfig::default();
+        let (seq_rules, seq_response, _, _) = evaluate_rules(&event, &sequential, &debug_config)
+            .await
+            .unwrap();
+        let (par_rules, par_response, _, _) = evaluate_rules(&event, &parallel, &debug_config)
+            .await
+            .unwrap();
+
+        let names = |rules: &[&Rule]| rules.iter().map(|r| r.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&par_rules), names(&seq_rules));
+        assert_eq!(par_rules.first().unwrap().name, "rule-13");
+        assert!(par_response.continue_);
+        assert_eq!(par_response.context, seq_response.context);
+        assert!(par_response.context.unwrap().contains("note 10"));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_evaluation_traces_debug_rules() {
+        use std::fmt::Write as _;
+
+        let mut yaml = String::from(
+            "version: \"1.0\"\nrules:\n  - name: traced\n    log: debug\n    matchers:\n      tools: [MultiEdit]\n    actions:\n      inject_inline: \"traced\"\n",
+        );
+        for i in 0..PARALLEL_THRESHOLD {
+            write!(
+                yaml,
+                "  - name: other-{i}\n    matchers:\n      tools: [Bash]\n    actions:\n      inject_inline: \"note {i}\"\n"
+            )
+            .unwrap();
+        }
+        let sequential: Config = serde_yaml::from_str(&yaml).unwrap();
+        let mut parallel = sequential.clone();
+        parallel.settings.parallel_evaluation = true;
+
+        let event = stdlib_test_event();
+        for config in [&sequential, &parallel] {
+            let (rules, _, evaluations, _) =
+                evaluate_rules(&event, config, &DebugConfig::default())
+                    .await
+                    .unwrap();
+            assert!(logs_rule_evaluations(false, &rules));
+            let traced = evaluations
+                .iter()
+                .find(|evaluation| evaluation.rule_name == "traced")
+                .expect("traced rule recorded without global debug");
+            assert!(traced.matched);
+            assert!(traced.matcher_results.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_evaluation_keeps_enforce_blocking() {
+        use std::fmt::Write as _;
+
+        let mut yaml = String::from(
+            "version: \"1.0\"\nsettings:\n  parallel_evaluation: true\nrules:\n  - name: blocker\n    matchers:\n      tools: [MultiEdit]\n    actions:\n      block: true\n",
+        );
+        for i in 0..PARALLEL_THRESHOLD {
+            write!(
+                yaml,
+                "  - name: audit-{i}\n    mode: audit\n    matchers:\n      tools: [MultiEdit]\n    actions:\n      inject_inline: \"note {i}\"\n"
+            )
+            .unwrap();
+        }
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+
+        let event = stdlib_test_event();
+        let (rules, response, _, _) = evaluate_rules(&event, &config, &DebugConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(rules.len(), PARALLEL_THRESHOLD + 1);
+        assert!(!response.continue_);
+    }
+
+    #[test]
+    fn test_side_effect_free_actions() {
+        let rule = |mode: &str, actions: &str| -> Rule {
+            serde_yaml::from_str(&format!(
+                "name: r\nmode: {mode}\nmatchers: {{}}\nactions: {actions}"
+            ))
+            .unwrap()
         };
+        assert!(is_side_effect_free(&rule(
+            "audit",
+            "{ webhook: { url: \"https://hooks.example.com\" } }"
+        )));
+        for actions in [
+            "{ inject_inline: note }",
+            "{ block: true }",
+            "{ run: check.sh }",
+            "{ inline_script: \"exit 0\" }",
+            "{ inject_command: date }",
+        ] {
+            for mode in ["enforce", "warn"] {
+                assert!(
+                    is_side_effect_free(&rule(mode, actions)),
+                    "{mode} {actions}"
+                );
+            }
+        }
+        for actions in [
+            "{ webhook: { url: \"https://hooks.example.com\" } }",
+            "{ custom: { notify: true } }",
+            "[ { inject_inline: a }, { webhook: { url: \"https://hooks.example.com\" } } ]",
+        ] {
+            assert!(!is_side_effect_free(&rule("warn", actions)), "{actions}");
+        }
+    }
+
+    #[cfg(all(unix, feature = "process"))]
+    #[tokio::test]
+    async fn test_parallel_evaluation_overlaps_validators() {
+        use std::fmt::Write as _;
+
+        let mut yaml = String::from(
+            "version: \"1.0\"\nsettings:\n  script_limits:\n    max_concurrent: 4\nrules:\n",
+        );
+        for name in ["slow-a", "slow-b"] {
+            write!(
+                yaml,
+                "  - name: {name}\n    matchers:\n      tools: [MultiEdit]\n    actions:\n      inline_script: \"sleep 0.4\"\n"
+            )
+            .unwrap();
+        }
+        for i in 0..PARALLEL_THRESHOLD {
+            write!(
+                yaml,
+                "  - name: other-{i}\n    enabled_when: 'tool_name == \"Bash\"'\n    matchers:\n      tools: [MultiEdit]\n    actions:\n      block: true\n"
+            )
+            .unwrap();
+        }
+        let sequential: Config = serde_yaml::from_str(&yaml).unwrap();
+        let mut parallel = sequential.clone();
+        parallel.settings.parallel_evaluation = true;
+
+        // The disabled rules are still candidates, so the set is large enough
+        // for parallel evaluation
+        let event = stdlib_test_event();
+        let started = std::time::Instant::now();
+        let (rules, response, _, _) = evaluate_rules(&event, &parallel, &DebugConfig::default())
+            .await
+            .unwrap();
+        let elapsed = started.elapsed();
+        assert_eq!(rules.len(), 2);
+        assert!(response.continue_);
+        assert!(
+            elapsed < std::time::Duration::from_millis(700),
+            "validators ran one after another: {elapsed:?}"
+        );
+
+        let started = std::time::Instant::now();
+        evaluate_rules(&event, &sequential, &DebugConfig::default())
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= std::time::Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_custom_functions_share_tool_input() {
+        let ctx = build_eval_context_with_custom_functions(&stdlib_test_event());
+        let tool_input = ctx.tool_input.as_ref().expect("tool_input");
+        // The context plus get_fields, get_field and has_field
+        assert_eq!(Arc::strong_count(tool_input), 4);
+
+        let ctx = build_eval_context_with_custom_functions(&Event {
+            tool_input: None,
+            ..stdlib_test_event()
+        });
+        assert!(ctx.tool_input.is_none());
+        assert!(!eval_boolean_with_context(r#"has_field("file_path")"#, &ctx).unwrap());
+    }
+
+    #[test]
+    fn test_eval_context_reads_env_on_demand() {
+        use evalexpr::{Context as _, IterateVariablesContext};
+
+        let ctx = build_eval_context(&stdlib_test_event());
+        assert!(
+            ctx.inner
+                .iter_variable_names()
+                .all(|name| !name.starts_with("env_")),
+            "environment is not copied into each context"
+        );
+
+        let (key, value) = std::env::vars().next().expect("some environment variable");
+        assert_eq!(
+            ctx.get_value(&format!("env_{key}")),
+            Some(&Value::String(value))
+        );
+        assert_eq!(ctx.get_value("env_RULEZ_TEST_SURELY_UNSET_VAR"), None);
+        assert_eq!(
+            ctx.get_value("tool_name"),
+            Some(&Value::String("MultiEdit".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_eval_context_resolves_git_branch_on_demand() {
+        use evalexpr::Context as _;
+
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join(".git")).unwrap();
+        std::fs::write(repo.path().join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        let mut event = stdlib_test_event();
+        event.cwd = Some(repo.path().to_string_lossy().to_string());
 
-        assert!(is_rule_enabled(&rule, &event));
+        let ctx = build_eval_context(&event);
+        assert!(ctx.git_branch.get().is_none(), "HEAD is not read up front");
+        assert!(eval_boolean_with_context(r#"tool_name != """#, &ctx).unwrap());
+        assert!(ctx.git_branch.get().is_none());
+
+        assert!(eval_boolean_with_context(r#"git_branch == "main""#, &ctx).unwrap());
+        // Later reads reuse the first result
+        std::fs::write(repo.path().join(".git/HEAD"), "ref: refs/heads/other\n").unwrap();
+        assert_eq!(
+            ctx.get_value("git_branch"),
+            Some(&Value::String("main".to_string()))
+        );
     }
 
     #[test]
@@ -1914,6 +5459,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -1929,21 +5481,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(is_rule_enabled(&rule, &event));
@@ -1965,6 +5547,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -1982,21 +5571,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!is_rule_enabled(&rule, &event));
@@ -2016,6 +5635,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2031,21 +5657,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         // Invalid expressions should return false (fail-closed)
@@ -2066,6 +5722,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2081,21 +5744,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(is_rule_enabled(&rule, &event));
@@ -2114,21 +5807,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         // Should be false because event.tool_name is "Bash", not "Edit"
@@ -2168,6 +5891,66 @@ mod tests {
         assert_eq!(decision, Decision::Audited);
     }
 
+    #[test]
+    fn test_logs_raw_event_per_rule_level() {
+        let plain = create_rule_with_mode("plain", PolicyMode::Enforce, 0);
+        let mut minimal = create_rule_with_mode("minimal", PolicyMode::Enforce, 0);
+        minimal.log = Some(RuleLogLevel::Minimal);
+        let mut full = create_rule_with_mode("full", PolicyMode::Enforce, 0);
+        full.log = Some(RuleLogLevel::Full);
+
+        assert!(!logs_raw_event(false, &[&plain]));
+        assert!(logs_raw_event(true, &[&plain]));
+        assert!(logs_raw_event(false, &[&full]));
+        assert!(!logs_raw_event(true, &[&plain, &minimal]));
+        assert!(logs_raw_event(true, &[&minimal, &full]));
+        assert!(logs_raw_event(true, &[]));
+
+        let mut off = create_rule_with_mode("off", PolicyMode::Enforce, 0);
+        off.log = Some(RuleLogLevel::Off);
+        let mut debug = create_rule_with_mode("debug", PolicyMode::Enforce, 0);
+        debug.log = Some(RuleLogLevel::Debug);
+        assert!(!logs_raw_event(true, &[&off]));
+        assert!(logs_raw_event(false, &[&debug]));
+        assert!(logs_rule_evaluations(false, &[&plain, &debug]));
+        assert!(!logs_rule_evaluations(false, &[&full]));
+        assert!(traces_matchers(false, &debug));
+        assert!(!traces_matchers(false, &full));
+    }
+
+    #[test]
+    fn test_log_suppressed_only_for_quiet_unblocked_events() {
+        let plain = create_rule_with_mode("plain", PolicyMode::Enforce, 0);
+        let mut off = create_rule_with_mode("off", PolicyMode::Enforce, 0);
+        off.log = Some(RuleLogLevel::Off);
+        let entry = |outcome: &str, decision: Option<Decision>| {
+            let mut entry: LogEntry = serde_json::from_value(serde_json::json!({
+                "timestamp": "2026-10-15T00:00:00Z",
+                "event_type": "PreToolUse",
+                "session_id": "s",
+                "rules_matched": [],
+                "outcome": outcome,
+                "timing": {"processing_ms": 0, "rules_evaluated": 0},
+            }))
+            .unwrap();
+            entry.decision = decision;
+            entry
+        };
+
+        let injected = entry("inject", Some(Decision::Allowed));
+        assert!(log_suppressed(&[&off], &injected));
+        assert!(!log_suppressed(&[&off, &plain], &injected));
+        assert!(!log_suppressed(&[], &injected));
+        assert!(!log_suppressed(
+            &[&off],
+            &entry("block", Some(Decision::Blocked))
+        ));
+        assert!(!log_suppressed(
+            &[&off],
+            &entry("inject", Some(Decision::Warned))
+        ));
+    }
+
     #[test]
     fn test_merge_responses_with_mode_enforce() {
         let allow = Response::allow();
@@ -2189,6 +5972,281 @@ mod tests {
         assert!(merged.context.is_some());
     }
 
+    #[test]
+    fn test_merge_responses_keeps_strictest_permission_decision() {
+        let with_decision = |decision| {
+            let mut output = HookSpecificOutput::new(EventType::PreToolUse);
+            output.permission_decision = Some(decision);
+            let mut response = Response::allow();
+            response.hook_specific_output = Some(output);
+            response.system_message = Some(format!("{decision}"));
+            response
+        };
+
+        let merged = merge_responses(
+            with_decision(PermissionDecision::Ask),
+            with_decision(PermissionDecision::Allow),
+        );
+        let output = merged.hook_specific_output.unwrap();
+        assert_eq!(output.permission_decision, Some(PermissionDecision::Ask));
+        assert_eq!(merged.system_message.as_deref(), Some("ask\nallow"));
+    }
+
+    #[test]
+    fn test_merge_responses_suppress_output() {
+        let mut quiet = Response::inject("hint");
+        quiet.suppress_output = Some(true);
+
+        let merged = merge_responses(quiet.clone(), Response::inject("other"));
+        assert_eq!(merged.suppress_output, Some(true));
+        let merged = merge_responses(Response::allow(), quiet.clone());
+        assert_eq!(merged.suppress_output, Some(true));
+        // A block replaces the quiet output and stays visible
+        let merged = merge_responses(quiet.clone(), Response::block("no"));
+        assert!(merged.suppress_output.is_none());
+        // ... including when the quiet response comes after the block
+        let merged = merge_responses(Response::block("no"), quiet);
+        assert!(!merged.continue_);
+        assert!(merged.suppress_output.is_none());
+    }
+
+    #[test]
+    fn test_merge_rule_responses_orders_context_by_inject_priority() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r"
+- name: style
+  matchers: {}
+  actions: {}
+- name: security
+  inject_priority: 10
+  matchers: {}
+  actions: {}
+- name: notes
+  matchers: {}
+  actions: {}
+",
+        )
+        .unwrap();
+        let responses = || {
+            rules
+                .iter()
+                .map(|rule| (rule, Response::inject(format!("{} context", rule.name))))
+                .collect::<Vec<_>>()
+        };
+
+        let merged = merge_rule_responses(responses(), &Settings::default());
+        assert_eq!(
+            merged.context.as_deref(),
+            Some("security context\n\nstyle context\n\nnotes context")
+        );
+
+        let merged = merge_rule_responses(
+            responses(),
+            &Settings {
+                inject_headers: true,
+                ..Settings::default()
+            },
+        );
+        assert_eq!(
+            merged.context.as_deref(),
+            Some(
+                "## security\n\nsecurity context\n\n## style\n\nstyle context\n\n## notes\n\nnotes context"
+            )
+        );
+    }
+
+    #[test]
+    fn test_merge_rule_responses_injects_duplicates_once() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r"
+- name: python-style
+  matchers: {}
+  actions: {}
+- name: notes
+  matchers: {}
+  actions: {}
+- name: python-lint
+  inject_priority: 5
+  matchers: {}
+  actions: {}
+",
+        )
+        .unwrap();
+        let responses = || {
+            vec![
+                (&rules[0], Response::inject("Use black.\n")),
+                (&rules[1], Response::inject("Notes")),
+                (&rules[2], Response::inject("Use black.")),
+            ]
+        };
+
+        let merged = merge_rule_responses(responses(), &Settings::default());
+        assert_eq!(merged.context.as_deref(), Some("Use black.\n\nNotes"));
+        assert_eq!(merged.context_blocks[0].rule, "python-lint");
+        assert_eq!(merged.context_blocks[0].also_injected_by, ["python-style"]);
+        assert!(merged.context_blocks[1].also_injected_by.is_empty());
+
+        let merged = merge_rule_responses(
+            responses(),
+            &Settings {
+                inject_headers: true,
+                ..Settings::default()
+            },
+        );
+        assert!(
+            merged
+                .context
+                .unwrap()
+                .starts_with("## python-lint, python-style\n\nUse black.\n\n## notes")
+        );
+    }
+
+    #[test]
+    fn test_apply_output_actions_inject_as() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r"
+- name: to-user
+  matchers: {}
+  actions:
+    system_message: 'Heads up'
+    inject_as: system_message
+- name: to-transcript
+  matchers: {}
+  actions:
+    inject_as: stderr
+",
+        )
+        .unwrap();
+        let event = stdlib_test_event();
+
+        let response = apply_output_actions(
+            Response::inject("Deploys are frozen"),
+            &event,
+            &rules[0],
+            PolicyMode::Enforce,
+        );
+        assert!(response.context.is_none());
+        assert_eq!(
+            response.system_message.as_deref(),
+            Some("Heads up\nDeploys are frozen")
+        );
+
+        let response = apply_output_actions(
+            Response::inject("Deploys are frozen"),
+            &event,
+            &rules[1],
+            PolicyMode::Enforce,
+        );
+        assert!(response.context.is_none());
+        assert_eq!(response.stderr.as_deref(), Some("Deploys are frozen"));
+
+        let merged = merge_responses(response.clone(), response);
+        assert_eq!(
+            merged.stderr.as_deref(),
+            Some("Deploys are frozen\nDeploys are frozen")
+        );
+    }
+
+    #[test]
+    fn test_merge_rule_responses_block_drops_earlier_context() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r"
+- name: before
+  inject_priority: 5
+  matchers: {}
+  actions: {}
+- name: blocker
+  matchers: {}
+  actions: {}
+- name: after
+  inject_priority: 1
+  matchers: {}
+  actions: {}
+",
+        )
+        .unwrap();
+        let merged = merge_rule_responses(
+            vec![
+                (&rules[0], Response::inject("before")),
+                (&rules[1], Response::block("blocked")),
+                (&rules[2], Response::inject("after")),
+            ],
+            &Settings::default(),
+        );
+        assert!(!merged.continue_);
+        assert_eq!(merged.context.as_deref(), Some("after"));
+    }
+
+    #[test]
+    fn test_finalize_hook_output_additional_context() {
+        let mut response = Response::inject("remember the style guide");
+        finalize_hook_output(&mut response, EventType::UserPromptSubmit);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            json["hookSpecificOutput"]["hookEventName"],
+            "UserPromptSubmit"
+        );
+        assert_eq!(
+            json["hookSpecificOutput"]["additionalContext"],
+            "remember the style guide"
+        );
+        assert_eq!(json["context"], "remember the style guide");
+
+        // PreToolUse has no additionalContext field
+        let mut response = Response::inject("ctx");
+        finalize_hook_output(&mut response, EventType::PreToolUse);
+        assert!(response.hook_specific_output.is_none());
+    }
+
+    #[test]
+    fn test_apply_prompt_rewrites_chains_in_priority_order() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r#"
+- name: reminder
+  matchers:
+    operations: [UserPromptSubmit]
+  actions:
+    rewrite_prompt:
+      prepend: "Be careful."
+      append: "Run the tests."
+- name: strip-token
+  mode: warn
+  matchers:
+    operations: [UserPromptSubmit]
+  actions:
+    rewrite_prompt:
+      redact: ['ghp_\w+']
+"#,
+        )
+        .unwrap();
+        let rules: Vec<&Rule> = rules.iter().collect();
+        let mut event = stdlib_test_event();
+        event.hook_event_name = EventType::UserPromptSubmit;
+        event.prompt = Some("push it".to_string());
+
+        let response = apply_prompt_rewrites(Response::allow(), &event, &rules);
+        assert!(response.continue_);
+        assert_eq!(
+            response.updated_prompt.as_deref(),
+            Some("Be careful.\n\npush it\n\nRun the tests.")
+        );
+        assert_eq!(
+            response.context.as_deref(),
+            Some("Be careful.\n\nRun the tests.")
+        );
+
+        // A warn-mode redaction only warns and leaves the prompt alone
+        event.prompt = Some("use ghp_abc123".to_string());
+        let response = apply_prompt_rewrites(Response::allow(), &event, &rules[1..]);
+        assert!(response.continue_);
+        assert!(response.updated_prompt.is_none());
+        assert!(response.context.unwrap().contains("would redact"));
+
+        // Blocked prompts are left untouched
+        let response = apply_prompt_rewrites(Response::block("no"), &event, &rules);
+        assert!(response.updated_prompt.is_none());
+    }
+
     #[test]
     fn test_rule_effective_mode_defaults_to_enforce() {
         let rule = Rule {
@@ -2204,21 +6262,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None, // No mode specified
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Enforce);
     }
@@ -2238,21 +6326,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: Some(PolicyMode::Audit),
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Audit);
     }
@@ -2275,21 +6393,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: Some(mode),
             priority: Some(priority),
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         }
     }
 
@@ -2468,6 +6616,48 @@ mod tests {
         assert!(!matches_prompt("production server", &pm));
     }
 
+    #[test]
+    fn test_matches_prompt_regex_set() {
+        let keywords: Vec<String> = ["deploy", "release", "rollback", "hotfix", "not: staging"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let pm = PromptMatch::Complex {
+            patterns: keywords.clone(),
+            mode: MatchMode::Any,
+            case_insensitive: true,
+            anchor: None,
+        };
+        assert!(matches_prompt("Ship the HOTFIX to staging", &pm));
+        assert!(matches_prompt("tidy up the readme", &pm)); // "not: staging" holds
+        assert!(!matches_prompt("tidy up staging", &pm));
+
+        let all = PromptMatch::Complex {
+            patterns: vec!["deploy".to_string(), "not: staging".to_string()],
+            mode: MatchMode::All,
+            case_insensitive: false,
+            anchor: None,
+        };
+        assert!(matches_prompt("deploy to prod", &all));
+        assert!(!matches_prompt("deploy to staging", &all));
+
+        // The compiled set is cached and reused
+        let set =
+            get_or_compile_regex_set(&["deploy".to_string(), "release".to_string()], true).unwrap();
+        assert_eq!(set.len(), 2);
+        assert!(
+            REGEX_SET_CACHE
+                .lock()
+                .unwrap()
+                .contains("deploy\0release:true")
+        );
+
+        // One invalid pattern falls back to per-pattern matching (fail-closed for it)
+        let with_invalid = PromptMatch::Simple(vec!["[unclosed".to_string(), "deploy".to_string()]);
+        assert!(matches_prompt("deploy now", &with_invalid));
+        assert!(!matches_prompt("[unclosed", &with_invalid));
+    }
+
     #[test]
     fn test_matches_prompt_case_insensitive() {
         // PROMPT-02: Case-insensitive matching
@@ -2618,6 +6808,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: Some("please delete the database".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2633,21 +6830,51 @@ mod tests {
                 prompt_match: Some(PromptMatch::Simple(vec!["delete".to_string()])),
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(matches_rule(&event, &rule));
@@ -2667,7 +6894,14 @@ mod tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
-            prompt: None, // No prompt
+            prompt: None, // No prompt,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2683,21 +6917,51 @@ mod tests {
                 prompt_match: Some(PromptMatch::Simple(vec!["test".to_string()])),
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         // Should NOT match - rule has prompt_match but event has no prompt
@@ -2719,6 +6983,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: Some("run sudo command".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2734,21 +7005,51 @@ mod tests {
                 prompt_match: Some(PromptMatch::Simple(vec!["sudo".to_string()])),
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         // Should match - tool AND prompt_match both match
@@ -2767,6 +7068,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: Some("run sudo command".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         // Should NOT match - tool doesn't match
@@ -2792,6 +7100,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: Some("hello world".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         // Build context and verify prompt is there
@@ -2816,6 +7131,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: Some("dangerous delete operation".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         // Rule with enabled_when checking prompt
@@ -2833,21 +7155,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(is_rule_enabled(&rule, &event));
@@ -2865,6 +7217,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         // Rule should fail because prompt variable doesn't exist
@@ -2889,6 +7248,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: Some("delete everything".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2904,21 +7270,51 @@ mod tests {
                 prompt_match: Some(PromptMatch::Simple(vec!["delete".to_string()])),
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         let (matched, results) = matches_rule_with_debug(&event, &rule);
@@ -2947,6 +7343,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -2962,21 +7365,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -2997,6 +7430,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3012,21 +7452,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3050,6 +7520,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3065,21 +7542,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3103,6 +7610,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3118,21 +7632,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3156,6 +7700,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3171,21 +7722,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3212,6 +7793,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3227,21 +7815,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["user.name".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3266,6 +7884,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -3284,21 +7909,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3323,6 +7978,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -3341,21 +8003,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3380,6 +8072,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3395,21 +8094,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3434,6 +8163,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3449,21 +8185,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["items".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3488,6 +8254,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -3506,21 +8279,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3545,6 +8348,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -3562,22 +8372,52 @@ mod tests {
                 command_match: None,
                 prompt_match: None,
                 require_fields: None,           // NOT in require_fields
-                field_types: Some(field_types), // Only in field_types
+                field_types: Some(field_types), // Only in field_types,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         // Should fail because 'count' is missing (field_types implies existence)
@@ -3607,6 +8447,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3622,21 +8469,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["file_path".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3662,6 +8539,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3681,21 +8565,51 @@ mod tests {
                     "mode".to_string(),
                 ]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3721,6 +8635,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3740,21 +8661,51 @@ mod tests {
                     "mode".to_string(),
                 ]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3779,6 +8730,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3794,21 +8752,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3832,6 +8820,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3847,21 +8842,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3884,6 +8909,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3899,21 +8931,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["command".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -3940,6 +9002,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -3955,21 +9024,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["user.name".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -3999,6 +9098,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -4014,21 +9120,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["input.user.address.city".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4055,6 +9191,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -4070,21 +9213,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: Some(vec!["user.address.city".to_string()]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -4111,6 +9284,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -4129,21 +9309,51 @@ mod tests {
                     "user.phone".to_string(), // Missing
                 ]),
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -4168,6 +9378,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4186,21 +9403,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4224,6 +9471,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4242,21 +9496,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4280,6 +9564,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4298,21 +9589,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4336,6 +9657,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4354,21 +9682,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4392,6 +9750,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4410,21 +9775,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4448,6 +9843,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4466,21 +9868,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4504,6 +9936,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4522,21 +9961,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(validate_required_fields(&rule, &event));
@@ -4560,6 +10029,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4578,21 +10054,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -4616,6 +10122,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4634,21 +10147,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         assert!(!validate_required_fields(&rule, &event));
@@ -4674,6 +10217,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let mut field_types = std::collections::HashMap::new();
@@ -4694,21 +10244,51 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: Some(field_types),
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         // All three type errors should be accumulated and reported
@@ -4736,6 +10316,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4763,6 +10350,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4789,6 +10383,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4814,6 +10415,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4842,6 +10450,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4872,6 +10487,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4908,6 +10530,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4933,6 +10562,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4958,6 +10594,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -4985,6 +10628,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5014,6 +10664,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5039,6 +10696,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5064,6 +10728,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5090,6 +10761,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5125,6 +10803,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5157,6 +10842,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -5172,30 +10864,64 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 validate_expr: Some(r#"has_field("missing_field")"#.to_string()),
                 inject_inline: Some("Should not appear".to_string()),
                 inject: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         let config = Config {
             version: "1.0".to_string(),
             rules: vec![],
             settings: crate::config::Settings::default(),
+            waivers: Vec::new(),
+            index: crate::config::RuleIndex::default(),
         };
 
-        let response = execute_rule_actions(&event, &rule, &config).await.unwrap();
+        let response = execute_rule_actions(&event, &rule, &config, PolicyMode::Enforce)
+            .await
+            .unwrap();
 
         assert!(
             !response.continue_,
@@ -5223,6 +10949,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let rule = Rule {
@@ -5238,30 +10971,64 @@ mod tests {
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 validate_expr: Some(r#"has_field("file_path")"#.to_string()),
                 inject_inline: Some("Validation passed".to_string()),
                 inject: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
 
         let config = Config {
             version: "1.0".to_string(),
             rules: vec![],
             settings: crate::config::Settings::default(),
+            waivers: Vec::new(),
+            index: crate::config::RuleIndex::default(),
         };
 
-        let response = execute_rule_actions(&event, &rule, &config).await.unwrap();
+        let response = execute_rule_actions(&event, &rule, &config, PolicyMode::Enforce)
+            .await
+            .unwrap();
 
         assert!(
             response.continue_,
@@ -5288,6 +11055,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5331,6 +11105,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context_with_custom_functions(&event);
@@ -5483,6 +11264,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context(&event);
@@ -5514,6 +11302,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context(&event);
@@ -5543,6 +11338,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context(&event);
@@ -5566,6 +11368,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context(&event);
@@ -5598,6 +11407,13 @@ mod tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let ctx = build_eval_context(&event);
@@ -5652,4 +11468,658 @@ mod tests {
         // Empty glob set matches nothing
         assert!(!glob_set.is_match("anything.rs"));
     }
+
+    // =============================================================================
+    // Expression standard library tests
+    // =============================================================================
+
+    fn stdlib_test_event() -> Event {
+        Event {
+            hook_event_name: EventType::PreToolUse,
+            tool_name: Some("MultiEdit".to_string()),
+            tool_input: Some(serde_json::json!({
+                "file_path": "/src/Main.RS",
+                "limit": "42",
+                "edits": [
+                    {"old_string": "foo", "new_string": "bar"},
+                    {"old_string": "baz", "new_string": "qux"}
+                ]
+            })),
+            session_id: "test-session".to_string(),
+            timestamp: Utc::now(),
+            user_id: None,
+            transcript_path: None,
+            cwd: None,
+            permission_mode: None,
+            tool_use_id: None,
+            prompt: Some("please deploy to production".to_string()),
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
+        }
+    }
+
+    #[test]
+    fn test_stdlib_string_functions() {
+        let ctx = build_eval_context(&stdlib_test_event());
+
+        for expr in [
+            r#"matches("deploy\\s+to", prompt)"#,
+            r#"!matches("^deploy", prompt)"#,
+            r#"contains(prompt, "production")"#,
+            r#"starts_with(tool_input_file_path, "/src/")"#,
+            r#"ends_with(lower(tool_input_file_path), ".rs")"#,
+            r#"len("héllo") == 5"#,
+            "len(prompt) > 10",
+        ] {
+            assert_eq!(
+                eval_boolean_with_context(expr, &ctx),
+                Ok(true),
+                "expression should be true: {}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_stdlib_to_number() {
+        let ctx = build_eval_context(&stdlib_test_event());
+
+        assert_eq!(
+            eval_boolean_with_context("to_number(tool_input_limit) > 40", &ctx),
+            Ok(true)
+        );
+        assert_eq!(
+            eval_boolean_with_context(r#"to_number("3.5") == 3.5"#, &ctx),
+            Ok(true)
+        );
+        assert!(eval_boolean_with_context(r#"to_number("abc") > 1"#, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_stdlib_get_fields_fans_out_over_arrays() {
+        let ctx = build_eval_context(&stdlib_test_event());
+
+        assert_eq!(
+            eval_boolean_with_context(r#"len(get_fields("edits.old_string")) == 2"#, &ctx),
+            Ok(true)
+        );
+        assert_eq!(
+            eval_boolean_with_context(r#"contains(get_fields("edits.new_string"), "qux")"#, &ctx),
+            Ok(true)
+        );
+        assert_eq!(
+            eval_boolean_with_context(r#"contains(get_fields("edits.1.old_string"), "baz")"#, &ctx),
+            Ok(true)
+        );
+        assert_eq!(
+            eval_boolean_with_context(r#"len(get_fields("missing.path")) == 0"#, &ctx),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_stdlib_available_in_validate_expr_context() {
+        let ctx = build_eval_context_with_custom_functions(&stdlib_test_event());
+
+        assert_eq!(
+            eval_boolean_with_context(
+                r#"has_field("edits") && starts_with(get_field("file_path"), "/src")"#,
+                &ctx
+            ),
+            Ok(true)
+        );
+    }
+
+    // =============================================================================
+    // Event context variable tests
+    // =============================================================================
+
+    #[test]
+    fn test_eval_context_exposes_session_and_file_context() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join(".git")).unwrap();
+        std::fs::write(
+            repo.path().join(".git/HEAD"),
+            "ref: refs/heads/feature/login\n",
+        )
+        .unwrap();
+        let subdir = repo.path().join("src");
+        std::fs::create_dir_all(&subdir).unwrap();
+
+        let mut event = stdlib_test_event();
+        event.cwd = Some(subdir.to_string_lossy().to_string());
+        event.permission_mode = Some("plan".to_string());
+        let ctx = build_eval_context(&event);
+
+        for expr in [
+            r#"session_id == "test-session""#,
+            r#"permission_mode == "plan""#,
+            r#"git_branch == "feature/login""#,
+            r#"file_path == "/src/Main.RS""#,
+            r#"extension == ".RS""#,
+            r#"ends_with(cwd, "src")"#,
+        ] {
+            assert_eq!(
+                eval_boolean_with_context(expr, &ctx),
+                Ok(true),
+                "expression should be true: {}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_context_defaults_to_empty_strings() {
+        let mut event = stdlib_test_event();
+        event.tool_input = None;
+        let ctx = build_eval_context(&event);
+
+        for expr in [
+            r#"cwd == """#,
+            r#"permission_mode == """#,
+            r#"git_branch == """#,
+            r#"file_path == """#,
+            r#"extension == """#,
+        ] {
+            assert_eq!(eval_boolean_with_context(expr, &ctx), Ok(true), "{}", expr);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pre_compact_and_notification_matchers() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r#"
+- name: auto-compact
+  matchers:
+    compact_trigger: auto
+  actions:
+    inject_inline: "Preserve the decisions log"
+- name: permission-prompt
+  matchers:
+    message_match: "(?i)needs your permission"
+  actions:
+    webhook: "http://127.0.0.1:9/unreachable"
+"#,
+        )
+        .unwrap();
+
+        let mut compact = stdlib_test_event();
+        compact.hook_event_name = EventType::PreCompact;
+        compact.tool_name = None;
+        compact.trigger = Some("auto".to_string());
+        assert!(matches_rule(&compact, &rules[0]));
+        assert!(!matches_rule(&compact, &rules[1]));
+        compact.trigger = Some("manual".to_string());
+        assert!(!matches_rule(&compact, &rules[0]));
+        let (matched, results) = matches_rule_with_debug(&compact, &rules[0]);
+        assert!(!matched);
+        assert_eq!(results.unwrap().compact_trigger_matched, Some(false));
+
+        let mut notification = stdlib_test_event();
+        notification.hook_event_name = EventType::Notification;
+        notification.tool_name = None;
+        notification.message = Some("Claude needs your permission to use Bash".to_string());
+        assert!(matches_rule(&notification, &rules[1]));
+        let ctx = build_eval_context(&notification);
+        assert_eq!(
+            eval_boolean_with_context(r#"contains(message, "permission") && trigger == """#, &ctx),
+            Ok(true)
+        );
+
+        // An unreachable webhook is logged, never surfaced to the caller
+        send_rule_webhook(&notification, &rules[1]).await;
+    }
+
+    #[tokio::test]
+    async fn test_rule_webhook_does_not_block_runtime() {
+        // Accepts connections but never answers, so curl runs to its timeout
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let rule: Rule = serde_yaml::from_str(&format!(
+            r#"
+name: slow-webhook
+matchers: {{}}
+actions:
+  webhook:
+    url: "http://{}/hook"
+    timeout_secs: 1
+"#,
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+
+        let event = stdlib_test_event();
+        let started = std::time::Instant::now();
+        let ((), ticked) = tokio::join!(send_rule_webhook(&event, &rule), async {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            started.elapsed()
+        });
+        assert!(
+            ticked < std::time::Duration::from_millis(500),
+            "other tasks stalled for {ticked:?}"
+        );
+        assert!(started.elapsed() >= std::time::Duration::from_millis(900));
+    }
+
+    #[test]
+    fn test_unknown_tool_and_event_detection() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: custom-tool
+    matchers:
+      tools: [DeployTool]
+    actions:
+      block: true
+"#,
+        )
+        .unwrap();
+        let mut event = stdlib_test_event();
+        for (tool, unknown) in [
+            ("MultiEdit", false),
+            ("mcp__github__create_issue", false),
+            ("DeployTool", false),
+            ("BrandNewTool", true),
+        ] {
+            event.tool_name = Some(tool.to_string());
+            assert_eq!(unknown_tool(&event, &config).is_some(), unknown, "{}", tool);
+        }
+        config.settings.known_tools = vec!["BrandNewTool".to_string()];
+        assert!(unknown_tool(&event, &config).is_none());
+
+        let known = serde_json::json!({"hook_event_name": "SubagentStop"});
+        assert_eq!(unknown_event_name(&known), None);
+        let legacy = serde_json::json!({"event_type": "PreToolUse"});
+        assert_eq!(unknown_event_name(&legacy), None);
+        let new = serde_json::json!({"hook_event_name": "PreThink"});
+        assert_eq!(unknown_event_name(&new), Some("PreThink"));
+        assert_eq!(unknown_event_name(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn test_default_policy_entry_precedence() {
+        let policy: std::collections::HashMap<String, PermissionDecision> =
+            serde_yaml::from_str("{Bash: ask, PreToolUse: deny, '*': allow, Stop: deny}").unwrap();
+        let mut event = stdlib_test_event();
+
+        event.tool_name = Some("Bash".to_string());
+        assert_eq!(
+            default_policy_entry(&policy, &event),
+            Some(("Bash", PermissionDecision::Ask))
+        );
+        event.tool_name = Some("Read".to_string());
+        assert_eq!(
+            default_policy_entry(&policy, &event),
+            Some(("PreToolUse", PermissionDecision::Deny))
+        );
+
+        // Tool keys only apply to PreToolUse
+        event.hook_event_name = EventType::PostToolUse;
+        event.tool_name = Some("Bash".to_string());
+        assert_eq!(default_policy_entry(&policy, &event), None);
+        event.hook_event_name = EventType::Stop;
+        assert_eq!(
+            default_policy_entry(&policy, &event),
+            Some(("Stop", PermissionDecision::Deny))
+        );
+    }
+
+    #[test]
+    fn test_script_timeout_precedence() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: validator
+    matchers: {}
+    actions:
+      run: ./check.sh
+settings:
+  script_timeout: 3
+  script_timeouts:
+    run_ms: 250
+"#,
+        )
+        .unwrap();
+        let mut rule = config.rules[0].clone();
+
+        assert_eq!(
+            script_timeout(&rule, &config, ScriptKind::Run),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            script_timeout(&rule, &config, ScriptKind::InjectCommand),
+            Duration::from_secs(3)
+        );
+
+        // Metadata without a timeout (e.g. only a priority) keeps the budgets
+        let mut metadata: crate::models::RuleMetadata =
+            serde_yaml::from_str("priority: 10").unwrap();
+        rule.metadata = Some(metadata.clone());
+        assert_eq!(
+            script_timeout(&rule, &config, ScriptKind::Run),
+            Duration::from_millis(250)
+        );
+
+        metadata.timeout = Some(5);
+        rule.metadata = Some(metadata);
+        assert_eq!(
+            script_timeout(&rule, &config, ScriptKind::Run),
+            Duration::from_secs(5),
+            "legacy metadata.timeout still applies"
+        );
+
+        rule.timeout_ms = Some(40);
+        config.settings.script_timeouts.inline_script_ms = Some(900);
+        assert_eq!(
+            script_timeout(&rule, &config, ScriptKind::InlineScript),
+            Duration::from_millis(40)
+        );
+    }
+
+    #[test]
+    fn test_resolve_git_branch_detached_and_worktree() {
+        let repo = tempfile::tempdir().unwrap();
+        let git_dir = repo.path().join("real-git-dir");
+        std::fs::create_dir_all(&git_dir).unwrap();
+        std::fs::write(git_dir.join("HEAD"), "0123456789abcdef\n").unwrap();
+        std::fs::write(repo.path().join(".git"), "gitdir: real-git-dir\n").unwrap();
+
+        assert_eq!(resolve_git_branch(repo.path()), Some("0123456".to_string()));
+    }
+
+    // =============================================================================
+    // Script environment tests
+    // =============================================================================
+
+    #[test]
+    fn test_render_event_template() {
+        let event_value = serde_json::to_value(stdlib_test_event()).unwrap();
+
+        assert_eq!(
+            render_event_template("--file={{ tool_input.file_path }}", &event_value),
+            "--file=/src/Main.RS"
+        );
+        assert_eq!(
+            render_event_template("{{tool_name}}:{{session_id}}", &event_value),
+            "MultiEdit:test-session"
+        );
+        assert_eq!(
+            render_event_template("{{tool_input.edits.0}}", &event_value),
+            r#"{"new_string":"bar","old_string":"foo"}"#
+        );
+        assert_eq!(render_event_template("[{{missing}}]", &event_value), "[]");
+        assert_eq!(
+            render_event_template("literal {{unterminated", &event_value),
+            "literal {{unterminated"
+        );
+    }
+
+    #[test]
+    fn test_resolve_working_dir() {
+        let project = tempfile::tempdir().unwrap();
+        let mut event = stdlib_test_event();
+        event.cwd = Some(project.path().to_string_lossy().to_string());
+
+        // Default: the event cwd
+        assert_eq!(
+            resolve_working_dir(None, &event),
+            Some(project.path().to_path_buf())
+        );
+        // Configured: templated and relative to the event cwd
+        assert_eq!(
+            resolve_working_dir(Some("sessions/{{session_id}}"), &event),
+            Some(project.path().join("sessions/test-session"))
+        );
+
+        // Missing event cwd directory falls back to inheriting
+        event.cwd = Some(project.path().join("gone").to_string_lossy().to_string());
+        assert_eq!(resolve_working_dir(None, &event), None);
+        event.cwd = None;
+        assert_eq!(resolve_working_dir(None, &event), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_context_file_respects_inject_roots() {
+        let base = tempfile::tempdir().unwrap();
+        let project = base.path().join("project");
+        std::fs::create_dir_all(project.join(".claude")).unwrap();
+        std::fs::write(project.join(".claude/context.md"), "in root").unwrap();
+        std::fs::write(project.join(".claude/.credentials.json"), "{}").unwrap();
+        std::fs::write(project.join("notes.md"), "project file").unwrap();
+        std::fs::write(base.path().join("secret.txt"), "outside").unwrap();
+
+        // Default roots, resolved against the config's project as on load
+        let mut config = Config::default();
+        crate::config::resolve_inject_roots(&mut config.settings.inject_roots, &project);
+
+        let inside = project.join(".claude/context.md");
+        let content = read_context_file(&inside.to_string_lossy(), &config).await;
+        assert_eq!(content.unwrap(), "in root");
+
+        // Absolute paths, traversal, and the rest of the project are rejected
+        for rejected in [
+            base.path().join("secret.txt"),
+            project.join(".claude/../../secret.txt"),
+            project.join("notes.md"),
+            PathBuf::from("/etc/passwd"),
+        ] {
+            assert!(
+                read_context_file(&rejected.to_string_lossy(), &config)
+                    .await
+                    .is_err(),
+                "{}",
+                rejected.display()
+            );
+        }
+        let err = read_context_file("/etc/passwd", &config).await.unwrap_err();
+        assert!(err.to_string().contains("inject_roots"), "{}", err);
+
+        // Credential files are refused by default and inside any root
+        assert!(
+            read_context_file("~/.claude/.credentials.json", &config)
+                .await
+                .is_err()
+        );
+        config.settings.inject_roots = vec![project.to_string_lossy().to_string()];
+        assert!(
+            read_context_file(&project.join("notes.md").to_string_lossy(), &config)
+                .await
+                .is_ok()
+        );
+        let err = read_context_file(
+            &project.join(".claude/.credentials.json").to_string_lossy(),
+            &config,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("credential"), "{}", err);
+    }
+
+    #[test]
+    fn test_injects_outside_roots() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(project.path().join(".claude")).unwrap();
+        std::fs::write(project.path().join(".claude/context.md"), "in root").unwrap();
+        std::fs::create_dir_all(project.path().join("docs")).unwrap();
+        std::fs::write(project.path().join("docs/DECISIONS.md"), "outside").unwrap();
+        let config_path = project.path().join(".claude/hooks.yaml");
+        std::fs::write(
+            &config_path,
+            r#"
+version: "1.0"
+rules:
+  - name: standards
+    matchers: {}
+    actions:
+      inject: .claude/context.md
+  - name: decisions
+    matchers: {}
+    actions:
+      inject: docs/DECISIONS.md
+  - name: missing
+    matchers: {}
+    actions:
+      inject: docs/missing.md
+"#,
+        )
+        .unwrap();
+
+        let config = Config::read_compiled(&config_path).unwrap();
+        assert_eq!(
+            injects_outside_roots(&config),
+            vec![(
+                "decisions".to_string(),
+                project
+                    .path()
+                    .join("docs/DECISIONS.md")
+                    .to_string_lossy()
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_head_tail_keeps_ends() {
+        let content: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        let content = content.join("\n");
+        assert_eq!(
+            head_tail(&content, 2, 1),
+            "line 1\nline 2\n… [7 lines omitted]\nline 10"
+        );
+        assert_eq!(head_tail(&content, 5, 5), content);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_summarize_context_uses_command_or_falls_back() {
+        let rule: Rule = serde_yaml::from_str("name: r\nmatchers: {}\nactions: {}").unwrap();
+        let event = stdlib_test_event();
+        let config = Config::default();
+        let content: Vec<String> = (1..=200).map(|i| format!("commit {i}")).collect();
+        let content = content.join("\n");
+        let summarize = |command: Option<&str>| Summarize {
+            max_bytes: 100,
+            command: command.map(ToString::to_string),
+            head_lines: 3,
+            tail_lines: 2,
+        };
+
+        // Small content is left alone
+        let small =
+            summarize_context("short".into(), &summarize(None), &event, &rule, &config).await;
+        assert_eq!(small, "short");
+
+        let summary = summarize_context(
+            content.clone(),
+            &summarize(Some("wc -l | tr -d ' '")),
+            &event,
+            &rule,
+            &config,
+        )
+        .await;
+        assert_eq!(summary.trim(), "199");
+
+        // A failing summarizer falls back to head/tail extraction
+        let fallback =
+            "commit 1\ncommit 2\ncommit 3\n… [195 lines omitted]\ncommit 199\ncommit 200";
+        let summary = summarize_context(
+            content.clone(),
+            &summarize(Some("exit 3")),
+            &event,
+            &rule,
+            &config,
+        )
+        .await;
+        assert_eq!(summary, fallback);
+
+        // So does one the inject_command_policy does not allow
+        let mut config = Config::default();
+        config.settings.inject_command_policy =
+            Some(crate::command_allowlist::InjectCommandPolicy {
+                allow: Some(vec!["tail".to_string()]),
+                allow_shell: false,
+            });
+        let summary =
+            summarize_context(content, &summarize(Some("wc -l")), &event, &rule, &config).await;
+        assert_eq!(summary, fallback);
+    }
+
+    #[test]
+    fn test_rule_index_matches_full_scan() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: bash-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push"
+    actions: {}
+  - name: pre-any
+    matchers:
+      operations: [PreToolUse, PostToolUse]
+    actions: {}
+  - name: write-ts
+    matchers:
+      tools: [Write]
+      extensions: [".ts"]
+    actions: {}
+  - name: prompt
+    matchers:
+      operations: [UserPromptSubmit]
+      prompt_match: ["deploy"]
+    actions: {}
+  - name: subagent
+    matchers:
+      operations: [SubagentStop]
+    actions: {}
+  - name: always
+    matchers: {}
+    actions: {}
+"#,
+        )
+        .unwrap();
+
+        let mut events = Vec::new();
+        for (event_type, tool) in [
+            (EventType::PreToolUse, Some("Bash")),
+            (EventType::PostToolUse, Some("Write")),
+            (EventType::PreToolUse, Some("Read")),
+            (EventType::UserPromptSubmit, None),
+            (EventType::AfterAgent, None),
+            (EventType::SessionStart, None),
+        ] {
+            let mut event = stdlib_test_event();
+            event.hook_event_name = event_type;
+            event.tool_name = tool.map(str::to_string);
+            event.tool_input = Some(serde_json::json!({
+                "command": "git push origin main",
+                "filePath": "src/app.ts"
+            }));
+            event.prompt = Some("deploy it".to_string());
+            events.push(event);
+        }
+
+        for event in &events {
+            let full: Vec<&str> = config
+                .enabled_rules()
+                .into_iter()
+                .filter(|rule| matches_rule(event, rule))
+                .map(|rule| rule.name.as_str())
+                .collect();
+            let indexed: Vec<&str> = config
+                .candidate_rules(event.tool_name.as_deref(), event.hook_event_name)
+                .into_iter()
+                .filter(|rule| matches_rule(event, rule))
+                .map(|rule| rule.name.as_str())
+                .collect();
+            assert_eq!(indexed, full, "event {:?}", event.hook_event_name);
+        }
+    }
 }
@@ -0,0 +1,166 @@
+//! Who is running the agent, for the `users` and `teams` matchers.
+//!
+//! The user is the event's `user_id`, or the `user_env` variable (default
+//! `RULEZ_USER`) when the agent doesn't send one. Teams come from the
+//! `teams` map in `settings.identity` and from the comma-separated
+//! `teams_env` variable (default `RULEZ_TEAMS`):
+//!
+//! ```yaml
+//! settings:
+//!   identity:
+//!     teams:
+//!       release-managers: [alice@example.com, bob@example.com]
+//!       contractors: ["*@contractor.example.com"]
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Event;
+
+/// Identity settings (`settings.identity`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdentityConfig {
+    /// Environment variable naming the user when the event has no `user_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_env: Option<String>,
+
+    /// Environment variable listing the user's teams, comma-separated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub teams_env: Option<String>,
+
+    /// Team name to members (user ids or `*` globs)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub teams: BTreeMap<String, Vec<String>>,
+}
+
+/// Variable naming the user when `user_env` is not set
+pub const DEFAULT_USER_ENV: &str = "RULEZ_USER";
+
+/// Variable listing teams when `teams_env` is not set
+pub const DEFAULT_TEAMS_ENV: &str = "RULEZ_TEAMS";
+
+/// The resolved user and their teams
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Identity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub teams: Vec<String>,
+}
+
+/// Whether `user` matches an entry of `patterns` (exact, or a `*` glob)
+pub fn user_matches(patterns: &[String], user: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern == user
+            || (pattern.contains('*')
+                && globset::Glob::new(pattern)
+                    .is_ok_and(|glob| glob.compile_matcher().is_match(user)))
+    })
+}
+
+/// Resolve the identity behind an event
+pub fn resolve(event: &Event, config: Option<&IdentityConfig>) -> Identity {
+    let default = IdentityConfig::default();
+    let config = config.unwrap_or(&default);
+    let env = |name: Option<&String>, fallback: &str| {
+        std::env::var(name.map_or(fallback, String::as_str))
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+
+    let user = event
+        .user_id
+        .clone()
+        .or_else(|| env(config.user_env.as_ref(), DEFAULT_USER_ENV));
+    let mut teams: Vec<String> = env(config.teams_env.as_ref(), DEFAULT_TEAMS_ENV)
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|team| !team.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(ref user) = user {
+        for (team, members) in &config.teams {
+            if user_matches(members, user) && !teams.contains(team) {
+                teams.push(team.clone());
+            }
+        }
+    }
+    Identity { user, teams }
+}
+
+/// The event's identity, resolved with default settings if RuleZ hasn't filled it in
+pub fn of(event: &Event) -> std::borrow::Cow<'_, Identity> {
+    match event.identity {
+        Some(ref identity) => std::borrow::Cow::Borrowed(identity),
+        None => std::borrow::Cow::Owned(resolve(event, None)),
+    }
+}
+
+/// Whether the event's user is one of `users`
+pub fn matches_users(event: &Event, users: &[String]) -> bool {
+    of(event)
+        .user
+        .as_deref()
+        .is_some_and(|user| user_matches(users, user))
+}
+
+/// Whether the event's user is in one of `teams`
+pub fn matches_teams(event: &Event, teams: &[String]) -> bool {
+    of(event).teams.iter().any(|team| teams.contains(team))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(user_id: Option<&str>) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "session_id": "identity-test",
+            "user_id": user_id,
+        }))
+        .unwrap()
+    }
+
+    fn config() -> IdentityConfig {
+        serde_yaml::from_str(
+            r#"
+user_env: IDENTITY_TEST_UNSET_USER
+teams_env: IDENTITY_TEST_UNSET_TEAMS
+teams:
+  release-managers: [alice@example.com]
+  contractors: ["*@contractor.example.com"]
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_teams_from_config() {
+        let config = config();
+        let alice = resolve(&event(Some("alice@example.com")), Some(&config));
+        assert_eq!(alice.user.as_deref(), Some("alice@example.com"));
+        assert_eq!(alice.teams, vec!["release-managers"]);
+
+        let eve = resolve(&event(Some("eve@contractor.example.com")), Some(&config));
+        assert_eq!(eve.teams, vec!["contractors"]);
+
+        assert_eq!(resolve(&event(None), Some(&config)), Identity::default());
+    }
+
+    #[test]
+    fn test_users_and_teams_matchers() {
+        let mut alice = event(Some("alice@example.com"));
+        alice.identity = Some(resolve(&alice, Some(&config())));
+        assert!(matches_users(&alice, &["*@example.com".to_string()]));
+        assert!(!matches_users(&alice, &["bob@example.com".to_string()]));
+        assert!(matches_teams(&alice, &["release-managers".to_string()]));
+        assert!(!matches_teams(&alice, &["contractors".to_string()]));
+    }
+}
@@ -0,0 +1,273 @@
+//! Global kill switch.
+//!
+//! `RULEZ_DISABLED=1` in the hook's environment, or `rulez disable --all`,
+//! turns RuleZ off: every event is allowed without loading the config or
+//! evaluating any rule. Disabling is never silent, though. Each bypassed
+//! event is still logged with `decision: bypassed` and the reason, so the
+//! audit log shows when policy was off and why.
+//!
+//! `rulez disable --all` writes `~/.claude/logs/disabled.json`, optionally
+//! with an expiry (`--ttl 2h`); `rulez enable --all` removes it. An expired
+//! file is removed by the first event after the expiry.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::logging::Logger;
+use crate::models::{
+    Decision, Event, EventDetails, LOG_SCHEMA_VERSION, LogEntry, LogTiming, Outcome,
+};
+
+/// Environment variable that disables RuleZ when set to `1`, `true` or `yes`
+pub const ENV_VAR: &str = "RULEZ_DISABLED";
+
+/// A `rulez disable --all` that is in effect
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DisabledState {
+    /// When RuleZ was disabled
+    pub since: DateTime<Utc>,
+
+    /// When RuleZ turns itself back on (never if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Why RuleZ was disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Who disabled it ($USER)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+}
+
+impl DisabledState {
+    /// One-line description for logs and `rulez disable` output
+    pub fn describe(&self) -> String {
+        use std::fmt::Write;
+
+        let mut text = "disabled by `rulez disable --all`".to_string();
+        if let Some(ref by) = self.by {
+            let _ = write!(text, " ({by})");
+        }
+        match self.until {
+            Some(until) => {
+                let _ = write!(text, " until {}", until.to_rfc3339());
+            }
+            None => text.push_str(" until `rulez enable --all`"),
+        }
+        if let Some(ref reason) = self.reason {
+            let _ = write!(text, ": {reason}");
+        }
+        text
+    }
+}
+
+/// Why RuleZ is disabled right now, or `None` if it is enabled
+pub fn bypass_reason(now: DateTime<Utc>) -> Option<String> {
+    bypass_reason_in(&state_path(), std::env::var(ENV_VAR).ok().as_deref(), now)
+}
+
+fn bypass_reason_in(path: &Path, env: Option<&str>, now: DateTime<Utc>) -> Option<String> {
+    if env.is_some_and(is_set) {
+        return Some(format!("{ENV_VAR} is set"));
+    }
+    let state: DisabledState = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+    if state.until.is_some_and(|until| until <= now) {
+        let _ = std::fs::remove_file(path);
+        return None;
+    }
+    Some(state.describe())
+}
+
+/// Whether a `RULEZ_DISABLED` value turns RuleZ off
+pub fn is_set(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes"
+    )
+}
+
+/// Disable RuleZ for everyone using this home directory
+///
+/// `ttl` is an age such as `30m`, `2h` or `1d`.
+pub fn disable(
+    ttl: Option<&str>,
+    reason: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<DisabledState> {
+    disable_in(&state_path(), ttl, reason, now)
+}
+
+fn disable_in(
+    path: &Path,
+    ttl: Option<&str>,
+    reason: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<DisabledState> {
+    let until = match ttl {
+        Some(ttl) => {
+            let ttl = ttl.trim();
+            if !ttl.ends_with(['m', 'h', 'd', 'w']) {
+                anyhow::bail!("Invalid --ttl '{}': use an age like 30m, 2h, 1d", ttl);
+            }
+            let age = now - crate::log_export::parse_time_bound(ttl, now)?;
+            if age <= chrono::Duration::zero() {
+                anyhow::bail!("--ttl must be positive");
+            }
+            Some(now + age)
+        }
+        None => None,
+    };
+    let state = DisabledState {
+        since: now,
+        until,
+        reason,
+        by: std::env::var("USER").ok().filter(|user| !user.is_empty()),
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(&state)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(state)
+}
+
+/// Undo `disable`; `false` if RuleZ wasn't disabled that way
+pub fn enable() -> Result<bool> {
+    enable_in(&state_path())
+}
+
+fn enable_in(path: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(true)
+}
+
+/// Log entry for an event let through while RuleZ is disabled
+pub fn log_entry(event: &Event, reason: &str) -> LogEntry {
+    let mut entry = bypassed_entry(
+        format!("{:?}", event.hook_event_name),
+        event.session_id.clone(),
+        event.tool_name.clone(),
+        reason,
+    );
+    entry.timestamp = event.timestamp;
+    entry.event_details = Some(EventDetails::extract(event));
+    entry.tool_use_id.clone_from(&event.tool_use_id);
+    entry
+}
+
+/// Log entry for a bypassed event known only by name (e.g. an unknown event)
+pub fn bypassed_entry(
+    event_type: String,
+    session_id: String,
+    tool_name: Option<String>,
+    reason: &str,
+) -> LogEntry {
+    LogEntry {
+        schema_version: LOG_SCHEMA_VERSION,
+        timestamp: Utc::now(),
+        event_type,
+        session_id,
+        tool_name,
+        rules_matched: Vec::new(),
+        outcome: Outcome::Allow,
+        timing: LogTiming {
+            processing_ms: 0,
+            rules_evaluated: 0,
+            over_budget: None,
+        },
+        metadata: None,
+        event_details: None,
+        response: None,
+        raw_event: None,
+        rule_evaluations: None,
+        mode: None,
+        priority: None,
+        decision: Some(Decision::Bypassed),
+        bypass_reason: Some(reason.to_string()),
+        dry_run: None,
+        governance: None,
+        trust_level: None,
+        session_summary: None,
+        tool_use_id: None,
+        pre_tool_use: None,
+        sample_rate: None,
+        anomaly: None,
+        waivers: None,
+        profile: None,
+        deprecations: None,
+        prev_hash: None,
+        signature: None,
+        rule_timings: Vec::new(),
+    }
+}
+
+fn state_path() -> PathBuf {
+    Logger::default_log_path().with_file_name("disabled.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_var_disables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disabled.json");
+        let now = Utc::now();
+        assert_eq!(bypass_reason_in(&path, None, now), None);
+        assert_eq!(bypass_reason_in(&path, Some("0"), now), None);
+        assert_eq!(
+            bypass_reason_in(&path, Some("1"), now).as_deref(),
+            Some("RULEZ_DISABLED is set")
+        );
+        assert!(bypass_reason_in(&path, Some("TRUE"), now).is_some());
+    }
+
+    #[test]
+    fn test_disable_with_ttl_expires() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("disabled.json");
+        let now = Utc::now();
+
+        let state = disable_in(&path, Some("2h"), Some("incident 42".into()), now).unwrap();
+        assert_eq!(state.until, Some(now + chrono::Duration::hours(2)));
+        let reason = bypass_reason_in(&path, None, now).unwrap();
+        assert!(reason.ends_with(": incident 42"), "{reason}");
+
+        // The first event after the expiry re-enables RuleZ
+        let later = now + chrono::Duration::hours(3);
+        assert_eq!(bypass_reason_in(&path, None, later), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_enable_removes_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disabled.json");
+        let now = Utc::now();
+        disable_in(&path, None, None, now).unwrap();
+        assert!(
+            bypass_reason_in(&path, None, now + chrono::Duration::weeks(52))
+                .unwrap()
+                .contains("until `rulez enable --all`")
+        );
+        assert!(enable_in(&path).unwrap());
+        assert!(!enable_in(&path).unwrap());
+        assert_eq!(bypass_reason_in(&path, None, now), None);
+    }
+
+    #[test]
+    fn test_disable_rejects_bad_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disabled.json");
+        assert!(disable_in(&path, Some("2026-01-01"), None, Utc::now()).is_err());
+        assert!(disable_in(&path, Some("-1h"), None, Utc::now()).is_err());
+    }
+}
@@ -35,6 +35,19 @@
 //!   validator scripts). Supports parallel evaluation when rule count exceeds a
 //!   threshold. Includes an LRU-cached regex compiler.
 //!
+//! - [`engine`] -- `RuleEngine`, the embeddable API: evaluate events against a
+//!   `Config` without stdin/stdout, config discovery or audit logging. The
+//!   default `process` feature enables actions that spawn processes.
+//!
+//! - [`actions`] -- The `Action` trait that every rule action implements, and
+//!   the registry for custom actions that rules use under `actions.custom`.
+//!
+//! - [`matchers`] -- The `Matcher` trait and registry. Embedders register new
+//!   matcher kinds that rules use under `matchers.custom`.
+//!
+//! - [`error`] -- `RulezError`, returned by config loading, validation and
+//!   event processing so callers can match on the kind of failure.
+//!
 //! - [`models`] -- All type definitions: `Event`, `EventType`, `Rule`,
 //!   `Matchers`, `Actions`, `Response`, governance types (`PolicyMode`,
 //!   `Decision`, `Confidence`, `TrustLevel`), logging types (`LogEntry`,
@@ -47,9 +60,9 @@
 //!   `~/.claude/logs/rulez.log` and supports external backends (OTLP, Datadog,
 //!   Splunk).
 //!
-//! - [`adapters`] -- Platform adapters that translate platform-specific event
-//!   formats (Gemini CLI, Copilot, OpenCode) into the canonical RuleZ event
-//!   model.
+//! - [`adapters`] -- `FormatAdapter` implementations that translate each
+//!   agent's hook format (Claude Code, Copilot, Cursor, Gemini CLI, OpenCode)
+//!   to and from the canonical RuleZ event model.
 //!
 //! - [`cli`] -- CLI subcommand implementations (`init`, `install`, `uninstall`,
 //!   `debug`, `validate`, `logs`, `explain`, `repl`, `upgrade`).
@@ -86,7 +99,9 @@
 //! | 2    | Validation error (block) |
 //! | 3    | Runtime error        |
 
-#![forbid(unsafe_code)]
+// `deny` rather than `forbid`: the `limits` module needs a small, audited
+// amount of FFI (rlimits, Windows job objects) and opts in locally.
+#![deny(unsafe_code)]
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::must_use_candidate)]
@@ -108,19 +123,76 @@
 #![allow(clippy::if_not_else)]
 #![allow(clippy::redundant_closure_for_method_calls)]
 
-/// Platform adapters for Gemini CLI, Copilot, and OpenCode event translation.
+/// Rule actions: the `Action` trait and custom action plugins.
+pub mod actions;
+/// Platform adapters for Gemini CLI, Copilot, Cursor, and OpenCode event translation.
 pub mod adapters;
+/// Anomaly detection on blocked events (repeated attempts, block spikes).
+pub mod anomaly;
+/// Signed compliance evidence bundles (`rulez audit export`).
+pub mod audit_bundle;
 /// CLI subcommand implementations (init, install, debug, validate, logs, etc.).
 pub mod cli;
+/// Allowlist and shell restrictions for `inject_command`.
+pub mod command_allowlist;
 /// Configuration loading, parsing, and mtime-based caching for hooks.yaml.
 pub mod config;
+/// Comment-preserving edits to hooks.yaml (rules, priorities, packs).
+pub mod config_edit;
+/// Size budget and truncation for injected context.
+pub mod context_budget;
+/// Periodic digests of the audit log for webhooks and markdown reports.
+pub mod digest;
+/// Embeddable policy engine: evaluate events against a config without the hook binary.
+pub mod engine;
+/// Structured errors for config loading and event processing.
+pub mod error;
+/// Review-age checks on rule governance metadata.
+pub mod governance;
 /// Rule evaluation engine: matching, actions, regex caching, and parallel eval.
 pub mod hooks;
+/// The user and teams behind an event, for `users`/`teams` matchers.
+pub mod identity;
+/// Global kill switch (`RULEZ_DISABLED`, `rulez disable --all`) with an audit trail.
+pub mod kill_switch;
+/// Resource limits (CPU, memory, output) for spawned scripts.
+#[allow(unsafe_code)]
+pub mod limits;
+/// Hash chaining and signing of audit log entries.
+pub mod log_chain;
+/// Flat CSV and Parquet export of audit log entries.
+pub mod log_export;
 /// Structured audit logging with NDJSON output and external backend support.
 pub mod logging;
+/// Custom matcher plugins registered by embedders.
+pub mod matchers;
 /// Type definitions for events, rules, matchers, actions, responses, and governance.
 pub mod models;
 /// OpenCode plugin integration types.
 pub mod opencode;
+/// Built-in rule packs compiled into the binary.
+pub mod packs;
+/// PII detection for `scan_pii` actions.
+pub mod pii;
+/// Per-user and per-role profiles that tighten or relax the rules.
+pub mod profiles;
+/// Built-in self-protection for RuleZ's own config, validators, and logs.
+pub mod protection;
+/// Versioned streaming protocol (handshake, request ids, capabilities).
+pub mod protocol;
+/// Embedded Rhai scripting for `script_rhai` validator actions.
+pub mod scripting;
+/// Secret scanning for `scan_secrets` actions.
+pub mod secrets;
+/// Per-session state and end-of-session summaries.
+pub mod session;
 /// Multi-runtime skill portability layer.
 pub mod skills;
+/// Central policy sync into an overlay directory (`rulez sync`).
+pub mod sync;
+/// Issue tickets for rules that keep blocking.
+pub mod ticketing;
+/// Bounded reads of the session transcript for `transcript_match`.
+pub mod transcript;
+/// Scoped, expiring exemptions from individual rules.
+pub mod waivers;
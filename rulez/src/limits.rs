@@ -0,0 +1,972 @@
+//! Resource limits for spawned scripts (`run`, `inline_script`, `inject_command`).
+//!
+//! A runaway validator must not be able to take the machine down during an
+//! agent run. Limits are configured in `settings.script_limits`:
+//! - `cpu_seconds`: CPU time per process (`RLIMIT_CPU` on Unix, the job
+//!   object per-process user time limit on Windows)
+//! - `memory_mb`: memory per process (`RLIMIT_AS` on Unix, the job object
+//!   per-process memory limit on Windows)
+//! - `max_output_bytes`: bytes read from stdout or stderr before the script
+//!   is killed
+//! - `truncate_stdout_bytes`: stdout kept from `inject_command` and `run`
+//!   scripts; the rest is discarded and the kept part ends with a marker
+//! - `max_concurrent`: scripts running at once (others queue)
+//!
+//! Exceeding a limit surfaces as [`ResourceLimitExceeded`] so callers can fail
+//! closed regardless of `settings.fail_open`. A failed script is only blamed
+//! on a limit when the OS says so: `SIGXCPU`, a `SIGKILL` after the CPU time
+//! ran out (Linux accounting), or a job object limit notification on
+//! Windows. On Unix an allocation failure under `RLIMIT_AS` leaves no such
+//! evidence, so one that merely looks like it is logged as a warning.
+//!
+//! Concurrency is bounded by [`ScriptSlots`]: at most `max_concurrent`
+//! children run at once per `rulez` process, and queued scripts start in
+//! rule priority order.
+
+use std::collections::BinaryHeap;
+use std::process::ExitStatus;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+
+/// Resource limits applied to every spawned script and command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptLimits {
+    /// Maximum CPU time per process in seconds (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+
+    /// Maximum memory per process in megabytes (None = unlimited)
+    ///
+    /// On Unix this limits address space, which runtimes that reserve large
+    /// virtual regions up front (Node.js, the JVM) may exceed quickly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+
+    /// Maximum bytes read from stdout or stderr
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: usize,
+
+    /// Stdout kept from `inject_command` and `run` scripts (None = all of it)
+    ///
+    /// Output past the cap is read and discarded, so the script still runs to
+    /// completion and its exit code counts; `max_output_bytes` still applies
+    /// to everything it writes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncate_stdout_bytes: Option<usize>,
+
+    /// Maximum scripts running at once in this process (0 = unlimited)
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+fn default_max_output_bytes() -> usize {
+    1024 * 1024 // 1MB
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            cpu_seconds: None,
+            memory_mb: None,
+            max_output_bytes: default_max_output_bytes(),
+            truncate_stdout_bytes: None,
+            max_concurrent: default_max_concurrent(),
+        }
+    }
+}
+
+/// A script exceeded one of its resource limits
+#[derive(Debug, thiserror::Error)]
+pub enum ResourceLimitExceeded {
+    /// stdout or stderr grew past `max_output_bytes`
+    #[error("output exceeded {0} bytes")]
+    Output(usize),
+    /// The process was killed for exceeding `cpu_seconds`
+    #[error("CPU time limit of {0}s exceeded")]
+    CpuTime(u64),
+    /// The job object reported the process hit `memory_mb` (Windows)
+    #[error("memory limit of {0}MB exceeded")]
+    Memory(u64),
+}
+
+/// Output of a finished script
+#[derive(Debug)]
+pub struct ScriptOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// User plus system CPU time of the process (Linux only)
+    pub cpu_time: Option<Duration>,
+}
+
+/// Messages runtimes print when an allocation fails (matched lowercase)
+///
+/// Scripts can print these for any reason, so they only produce a warning.
+const ALLOCATION_FAILURE_MARKERS: &[&str] = &[
+    "out of memory",          // Perl, Node.js, glibc tools
+    "cannot allocate memory", // strerror(ENOMEM)
+    "cannot allocate",        // bash xmalloc
+    "memory allocation of",   // Rust's allocation error handler
+    "memoryerror",            // Python
+    "bad_alloc",              // C++
+];
+
+/// Keeps an OS-level container (a Windows job object) alive for a child
+///
+/// Dropping it kills any processes still in the container. On Unix the
+/// limits are inherited through rlimits and this holds nothing.
+pub struct Containment {
+    #[cfg(windows)]
+    job: windows_sys::Win32::Foundation::HANDLE,
+    /// Receives the job's limit notifications
+    #[cfg(windows)]
+    port: windows_sys::Win32::Foundation::HANDLE,
+}
+
+#[cfg(windows)]
+impl Drop for Containment {
+    fn drop(&mut self) {
+        // SAFETY: both handles were created by `windows_job::contain` and are
+        // closed once; null handles are skipped
+        unsafe {
+            for handle in [self.job, self.port] {
+                if !handle.is_null() {
+                    windows_sys::Win32::Foundation::CloseHandle(handle);
+                }
+            }
+        }
+    }
+}
+
+/// Limits the OS reported as violated for a contained process
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Violations {
+    cpu_time: bool,
+    memory: bool,
+}
+
+impl Containment {
+    /// Drain the limit notifications the container received
+    ///
+    /// Only Windows job objects report violations; on Unix this is empty
+    /// and [`ScriptLimits::check_exit`] relies on the exit status instead.
+    fn violations(&self) -> Violations {
+        #[cfg(windows)]
+        {
+            windows_job::violations(self)
+        }
+
+        #[cfg(not(windows))]
+        Violations::default()
+    }
+}
+
+impl ScriptLimits {
+    /// Apply limits that must be set before the process starts (Unix rlimits)
+    pub fn apply(&self, command: &mut Command) {
+        #[cfg(unix)]
+        {
+            let memory_bytes = self.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+            if self.cpu_seconds.is_some() || memory_bytes.is_some() {
+                set_rlimits_before_exec(command, self.cpu_seconds, memory_bytes);
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    /// Contain a freshly spawned child (Windows job object)
+    ///
+    /// The child starts running before it is assigned to the job, so a few
+    /// milliseconds of work escape accounting; limits still apply after that.
+    pub fn contain(&self, child: &Child) -> std::io::Result<Containment> {
+        #[cfg(windows)]
+        {
+            windows_job::contain(self, child)
+        }
+
+        #[cfg(not(windows))]
+        {
+            let _ = child;
+            Ok(Containment {})
+        }
+    }
+
+    /// Check whether a finished process failed because it hit a limit
+    ///
+    /// Only failures the OS attributes to a limit count, so a script that is
+    /// killed or crashes for another reason keeps its normal exit handling.
+    pub fn check_exit(
+        &self,
+        output: &ScriptOutput,
+        containment: &Containment,
+    ) -> Result<(), ResourceLimitExceeded> {
+        if output.status.success() {
+            return Ok(());
+        }
+        let violations = containment.violations();
+
+        if let Some(mb) = self.memory_mb {
+            if violations.memory {
+                return Err(ResourceLimitExceeded::Memory(mb));
+            }
+            if looks_out_of_memory(output) {
+                tracing::warn!(
+                    "Script failed with what looks like an allocation failure; it may have hit memory_mb ({}MB)",
+                    mb
+                );
+            }
+        }
+        if let Some(seconds) = self.cpu_seconds
+            && (violations.cpu_time || killed_for_cpu_time(output, seconds))
+        {
+            return Err(ResourceLimitExceeded::CpuTime(seconds));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a failed process looks like it ran out of memory
+///
+/// An `ENOMEM` exit code or an allocation-failure message; neither proves
+/// the limit was hit.
+fn looks_out_of_memory(output: &ScriptOutput) -> bool {
+    #[cfg(unix)]
+    if output.status.code() == Some(libc::ENOMEM) {
+        return true;
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    ALLOCATION_FAILURE_MARKERS
+        .iter()
+        .any(|marker| stderr.contains(marker))
+}
+
+/// Whether the kernel stopped the process for using up `RLIMIT_CPU`
+///
+/// The soft limit sends `SIGXCPU`. A process that ignores it is killed at
+/// the hard limit with `SIGKILL`, which is only blamed on the CPU limit when
+/// the accounted CPU time shows the limit was reached.
+fn killed_for_cpu_time(output: &ScriptOutput, seconds: u64) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        match output.status.signal() {
+            Some(libc::SIGXCPU) => true,
+            Some(libc::SIGKILL) => output
+                .cpu_time
+                .is_some_and(|used| used >= Duration::from_secs(seconds)),
+            _ => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (output, seconds);
+        false
+    }
+}
+
+/// Install a pre-exec hook that lowers the child's rlimits
+#[cfg(unix)]
+fn set_rlimits_before_exec(
+    command: &mut Command,
+    cpu_seconds: Option<u64>,
+    memory_bytes: Option<u64>,
+) {
+    // SAFETY: the closure only calls getrlimit/setrlimit, which are
+    // async-signal-safe, and allocates nothing
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(seconds) = cpu_seconds {
+                // Soft limit sends SIGXCPU, hard limit one second later SIGKILL
+                set_rlimit(libc::RLIMIT_CPU, seconds, seconds.saturating_add(1))?;
+            }
+            if let Some(bytes) = memory_bytes {
+                set_rlimit(libc::RLIMIT_AS, bytes, bytes)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Lower a resource limit, never raising it above the current hard limit
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let mut current = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `current` is a valid, writable rlimit
+    if unsafe { libc::getrlimit(resource, &raw mut current) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let cap = |value: u64| -> libc::rlim_t {
+        let value = libc::rlim_t::try_from(value).unwrap_or(libc::RLIM_INFINITY);
+        if current.rlim_max == libc::RLIM_INFINITY {
+            value
+        } else {
+            value.min(current.rlim_max)
+        }
+    };
+    let limit = libc::rlimit {
+        rlim_cur: cap(soft),
+        rlim_max: cap(hard),
+    };
+
+    // SAFETY: `limit` is a valid rlimit
+    if unsafe { libc::setrlimit(resource, &raw const limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+#[cfg(windows)]
+mod windows_job {
+    use super::{Containment, ScriptLimits, Violations};
+    use tokio::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::IO::{
+        CreateIoCompletionPort, GetQueuedCompletionStatus, OVERLAPPED,
+    };
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        JOB_OBJECT_LIMIT_PROCESS_MEMORY, JOB_OBJECT_LIMIT_PROCESS_TIME,
+        JOBOBJECT_ASSOCIATE_COMPLETION_PORT, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JobObjectAssociateCompletionPortInformation, JobObjectExtendedLimitInformation,
+        SetInformationJobObject,
+    };
+    use windows_sys::Win32::System::SystemServices::{
+        JOB_OBJECT_MSG_END_OF_PROCESS_TIME, JOB_OBJECT_MSG_PROCESS_MEMORY_LIMIT,
+    };
+
+    pub(super) fn contain(limits: &ScriptLimits, child: &Child) -> std::io::Result<Containment> {
+        let Some(process) = child.raw_handle() else {
+            // Already exited - nothing left to contain
+            return Ok(Containment {
+                job: std::ptr::null_mut(),
+                port: std::ptr::null_mut(),
+            });
+        };
+
+        // SAFETY: plain Win32 calls on handles we own; every failure path
+        // closes the handles before returning
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return Err(std::io::Error::last_os_error());
+            }
+            let port = CreateIoCompletionPort(INVALID_HANDLE_VALUE, std::ptr::null_mut(), 0, 1);
+            if port.is_null() {
+                let err = std::io::Error::last_os_error();
+                CloseHandle(job);
+                return Err(err);
+            }
+            let containment = Containment { job, port };
+
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            if let Some(seconds) = limits.cpu_seconds {
+                // Expressed in 100-nanosecond ticks
+                info.BasicLimitInformation.PerProcessUserTimeLimit =
+                    i64::try_from(seconds.saturating_mul(10_000_000)).unwrap_or(i64::MAX);
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+            }
+            if let Some(mb) = limits.memory_mb {
+                info.ProcessMemoryLimit =
+                    usize::try_from(mb.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX);
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+            }
+
+            let notify = JOBOBJECT_ASSOCIATE_COMPLETION_PORT {
+                CompletionKey: job,
+                CompletionPort: port,
+            };
+
+            let configured = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                std::ptr::from_ref(&info).cast(),
+                u32::try_from(std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>())
+                    .unwrap_or(u32::MAX),
+            ) != 0
+                && SetInformationJobObject(
+                    job,
+                    JobObjectAssociateCompletionPortInformation,
+                    std::ptr::from_ref(&notify).cast(),
+                    u32::try_from(std::mem::size_of::<JOBOBJECT_ASSOCIATE_COMPLETION_PORT>())
+                        .unwrap_or(u32::MAX),
+                ) != 0;
+            if !configured || AssignProcessToJobObject(job, process.cast()) == 0 {
+                // Dropping the containment closes both handles
+                return Err(std::io::Error::last_os_error());
+            }
+
+            Ok(containment)
+        }
+    }
+
+    /// Drain queued job notifications without waiting
+    pub(super) fn violations(containment: &Containment) -> Violations {
+        let mut violations = Violations::default();
+        if containment.port.is_null() {
+            return violations;
+        }
+
+        let mut message = 0u32;
+        let mut key = 0usize;
+        let mut overlapped: *mut OVERLAPPED = std::ptr::null_mut();
+        // SAFETY: the port is a live completion port and every out-pointer
+        // refers to a local
+        while unsafe {
+            GetQueuedCompletionStatus(
+                containment.port,
+                &raw mut message,
+                &raw mut key,
+                &raw mut overlapped,
+                0,
+            )
+        } != 0
+        {
+            match message {
+                JOB_OBJECT_MSG_END_OF_PROCESS_TIME => violations.cpu_time = true,
+                JOB_OBJECT_MSG_PROCESS_MEMORY_LIMIT => violations.memory = true,
+                _ => {}
+            }
+        }
+        violations
+    }
+}
+
+/// Process-wide slots shared by every script and command spawn
+pub static SCRIPT_SLOTS: LazyLock<ScriptSlots> = LazyLock::new(ScriptSlots::default);
+
+/// Default for `max_concurrent`: one script per available CPU
+fn default_max_concurrent() -> usize {
+    std::thread::available_parallelism().map_or(4, std::num::NonZeroUsize::get)
+}
+
+/// Priority-aware counting semaphore for child processes
+///
+/// Unlike a FIFO semaphore, a released slot goes to the waiting script
+/// with the highest rule priority (ties in arrival order), so important
+/// validators aren't starved behind a burst of low-priority ones.
+#[derive(Default)]
+pub struct ScriptSlots {
+    state: Mutex<SlotState>,
+}
+
+#[derive(Default)]
+struct SlotState {
+    in_use: usize,
+    next_seq: u64,
+    waiters: BinaryHeap<SlotWaiter>,
+}
+
+struct SlotWaiter {
+    priority: i32,
+    seq: u64,
+    wake: tokio::sync::oneshot::Sender<()>,
+}
+
+impl PartialEq for SlotWaiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl Eq for SlotWaiter {}
+
+impl PartialOrd for SlotWaiter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SlotWaiter {
+    /// Max-heap order: higher priority first, then earlier arrival
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// A held slot; released (or handed to the next waiter) on drop
+pub struct ScriptSlot<'a> {
+    slots: Option<&'a ScriptSlots>,
+}
+
+impl Drop for ScriptSlot<'_> {
+    fn drop(&mut self) {
+        if let Some(slots) = self.slots {
+            slots.release();
+        }
+    }
+}
+
+/// A queued acquisition; gives back a slot it was handed if cancelled
+struct PendingSlot<'a> {
+    slots: &'a ScriptSlots,
+    wake: Option<tokio::sync::oneshot::Receiver<()>>,
+}
+
+impl Drop for PendingSlot<'_> {
+    fn drop(&mut self) {
+        if let Some(mut wake) = self.wake.take() {
+            wake.close();
+            if wake.try_recv().is_ok() {
+                self.slots.release();
+            }
+        }
+    }
+}
+
+impl ScriptSlots {
+    /// Wait for a slot (`limit` 0 = unlimited)
+    ///
+    /// Dropping the returned future before it completes leaves the queue
+    /// without consuming a slot, so callers can bound the wait with a timeout.
+    pub async fn acquire(&self, limit: usize, priority: i32) -> ScriptSlot<'_> {
+        if limit == 0 {
+            return ScriptSlot { slots: None };
+        }
+
+        let wake = {
+            let mut state = self.lock();
+            if state.in_use < limit {
+                state.in_use += 1;
+                return ScriptSlot { slots: Some(self) };
+            }
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiters.push(SlotWaiter {
+                priority,
+                seq,
+                wake: tx,
+            });
+            rx
+        };
+
+        let mut pending = PendingSlot {
+            slots: self,
+            wake: Some(wake),
+        };
+        if let Some(wake) = pending.wake.as_mut() {
+            // The sender is only dropped after a successful send or when the
+            // waiter is skipped, which can't happen while we still listen
+            wake.await.ok();
+        }
+        pending.wake = None;
+        ScriptSlot { slots: Some(self) }
+    }
+
+    /// Number of slots currently held
+    #[cfg(test)]
+    fn in_use(&self) -> usize {
+        self.lock().in_use
+    }
+
+    /// Hand a slot to the best live waiter, or free it
+    fn release(&self) {
+        let mut state = self.lock();
+        while let Some(waiter) = state.waiters.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.in_use = state.in_use.saturating_sub(1);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SlotState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Wait for a child, reading stdout/stderr up to `max_bytes` each
+///
+/// If either stream grows past the limit the child is killed and
+/// [`ResourceLimitExceeded::Output`] is returned. Streams that weren't
+/// piped are returned empty. With `stdout_cap`, only that much stdout is
+/// kept (ending in [`TRUNCATION_MARKER`](crate::context_budget::TRUNCATION_MARKER)
+/// if anything was dropped).
+pub async fn wait_with_limited_output(
+    child: &mut Child,
+    max_bytes: usize,
+    stdout_cap: Option<usize>,
+) -> anyhow::Result<ScriptOutput> {
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let mut stdout_len = 0;
+    let mut stdout_buf = Vec::new();
+    let mut stderr_buf = Vec::new();
+    let mut stdout_chunk = vec![0u8; 8192];
+    let mut stderr_chunk = vec![0u8; 8192];
+    let mut stdout_done = stdout.is_none();
+    let mut stderr_done = stderr.is_none();
+
+    while !(stdout_done && stderr_done) {
+        tokio::select! {
+            read = read_chunk(&mut stdout, &mut stdout_chunk), if !stdout_done => {
+                match read? {
+                    0 => stdout_done = true,
+                    n => {
+                        stdout_len += n;
+                        let keep = stdout_cap.map_or(n, |cap| {
+                            n.min(cap.saturating_sub(stdout_buf.len()))
+                        });
+                        stdout_buf.extend_from_slice(&stdout_chunk[..keep]);
+                    }
+                }
+            }
+            read = read_chunk(&mut stderr, &mut stderr_chunk), if !stderr_done => {
+                match read? {
+                    0 => stderr_done = true,
+                    n => stderr_buf.extend_from_slice(&stderr_chunk[..n]),
+                }
+            }
+        }
+
+        if stdout_len > max_bytes || stderr_buf.len() > max_bytes {
+            child.kill().await.ok();
+            child.wait().await.ok();
+            return Err(ResourceLimitExceeded::Output(max_bytes).into());
+        }
+    }
+
+    if stdout_len > stdout_buf.len() {
+        mark_truncated(&mut stdout_buf);
+    }
+    let cpu_time = exited_cpu_time(child).await;
+    let status = child.wait().await?;
+    Ok(ScriptOutput {
+        status,
+        stdout: stdout_buf,
+        stderr: stderr_buf,
+        cpu_time,
+    })
+}
+
+/// Wait for a child to exit and read its CPU time before it is reaped
+///
+/// `waitid` with `WNOWAIT` leaves the zombie in place, so its accounting in
+/// `/proc/<pid>/stat` is still readable; `Child::wait` reaps it afterwards.
+#[cfg(target_os = "linux")]
+async fn exited_cpu_time(child: &Child) -> Option<Duration> {
+    let pid = child.id()?;
+    tokio::task::spawn_blocking(move || {
+        // SAFETY: siginfo_t is plain data and waitid only writes into it
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `info` is a valid, writable siginfo_t
+            let waited = unsafe {
+                libc::waitid(
+                    libc::P_PID,
+                    pid,
+                    &raw mut info,
+                    libc::WEXITED | libc::WNOWAIT,
+                )
+            };
+            if waited == 0 {
+                break;
+            }
+            if std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+                return None;
+            }
+        }
+
+        // SAFETY: sysconf has no preconditions
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        parse_cpu_time(&stat, u64::try_from(ticks).ok()?)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn exited_cpu_time(_child: &Child) -> Option<Duration> {
+    None
+}
+
+/// User plus system time from a `/proc/<pid>/stat` line
+///
+/// `utime` and `stime` are fields 14 and 15, counted in clock ticks; the
+/// command name (field 2) may contain spaces, so fields are counted from
+/// its closing parenthesis.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpu_time(stat: &str, ticks_per_second: u64) -> Option<Duration> {
+    if ticks_per_second == 0 {
+        return None;
+    }
+    let mut fields = stat[stat.rfind(')')? + 1..].split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    let ticks = utime.saturating_add(stime);
+    Some(
+        Duration::from_secs(ticks / ticks_per_second)
+            + Duration::from_nanos((ticks % ticks_per_second) * 1_000_000_000 / ticks_per_second),
+    )
+}
+
+/// End capped output with the truncation marker, without splitting a character
+fn mark_truncated(buf: &mut Vec<u8>) {
+    let marker = crate::context_budget::TRUNCATION_MARKER.as_bytes();
+    let mut end = buf.len().saturating_sub(marker.len());
+    while end > 0 && (buf[end] & 0b1100_0000) == 0b1000_0000 {
+        end -= 1;
+    }
+    buf.truncate(end);
+    buf.extend_from_slice(marker);
+}
+
+/// Read the next chunk from an optional stream (0 = end of stream)
+async fn read_chunk<R: AsyncRead + Unpin>(
+    reader: &mut Option<R>,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    match reader {
+        Some(reader) => reader.read(buf).await,
+        None => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    fn shell(script: &str) -> Command {
+        let mut command = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", script]);
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", script]);
+            cmd
+        };
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        command
+    }
+
+    #[tokio::test]
+    async fn test_slots_bound_concurrency() {
+        let slots = ScriptSlots::default();
+        let first = slots.acquire(2, 0).await;
+        let _second = slots.acquire(2, 0).await;
+        assert_eq!(slots.in_use(), 2);
+
+        // Third caller must wait until a slot is released
+        let waited =
+            tokio::time::timeout(std::time::Duration::from_millis(50), slots.acquire(2, 0)).await;
+        assert!(waited.is_err());
+        assert_eq!(slots.in_use(), 2);
+
+        drop(first);
+        assert_eq!(slots.in_use(), 1);
+        let _third = slots.acquire(2, 0).await;
+        assert_eq!(slots.in_use(), 2);
+
+        // Limit 0 means unlimited
+        let _free = slots.acquire(0, 0).await;
+        assert_eq!(slots.in_use(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_slots_wake_highest_priority_first() {
+        let slots = std::sync::Arc::new(ScriptSlots::default());
+        let held = slots.acquire(1, 0).await;
+        let order = std::sync::Arc::new(Mutex::new(Vec::new()));
+
+        let mut tasks = Vec::new();
+        for priority in [1, 10, 5] {
+            let slots = slots.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                let _slot = slots.acquire(1, priority).await;
+                order.lock().unwrap().push(priority);
+            }));
+            // Let each task queue before the next one
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![10, 5, 1]);
+        assert_eq!(slots.in_use(), 0);
+    }
+
+    #[test]
+    fn test_limits_deserialize_with_defaults() {
+        let limits: ScriptLimits = serde_yaml::from_str("cpu_seconds: 2").unwrap();
+        assert_eq!(limits.cpu_seconds, Some(2));
+        assert_eq!(limits.memory_mb, None);
+        assert_eq!(limits.max_output_bytes, 1024 * 1024);
+        assert!(limits.max_concurrent >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_output_within_limit() {
+        let mut child = shell("echo hello").spawn().unwrap();
+        let output = wait_with_limited_output(&mut child, 1024, None)
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("hello"));
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_output_over_limit_kills_child() {
+        let mut child = shell("yes").spawn().unwrap();
+        let err = wait_with_limited_output(&mut child, 4096, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ResourceLimitExceeded>(),
+            Some(ResourceLimitExceeded::Output(4096))
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_stdout_cap_truncates_with_marker() {
+        let mut child = shell("head -c 100000 /dev/zero | tr '\\0' a; exit 3")
+            .spawn()
+            .unwrap();
+        let output = wait_with_limited_output(&mut child, 1024 * 1024, Some(100))
+            .await
+            .unwrap();
+        assert_eq!(
+            output.status.code(),
+            Some(3),
+            "the script ran to completion"
+        );
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout.len(), 100);
+        assert!(stdout.starts_with("aaa"));
+        assert!(stdout.ends_with(crate::context_budget::TRUNCATION_MARKER));
+
+        let mut child = shell("echo hello").spawn().unwrap();
+        let output = wait_with_limited_output(&mut child, 1024, Some(100))
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+    }
+
+    #[test]
+    fn test_mark_truncated_keeps_utf8_whole() {
+        let mut buf = "ééééééééééééééééé".as_bytes().to_vec();
+        let len = buf.len();
+        mark_truncated(&mut buf);
+        let text = String::from_utf8(buf).expect("still valid UTF-8");
+        assert!(text.len() <= len);
+        assert!(text.ends_with(crate::context_budget::TRUNCATION_MARKER));
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_cpu_limit_kills_busy_loop() {
+        let limits = ScriptLimits {
+            cpu_seconds: Some(1),
+            ..ScriptLimits::default()
+        };
+        let (output, containment) = run_limited(&limits, "while :; do :; done").await;
+        assert!(matches!(
+            limits.check_exit(&output, &containment),
+            Err(ResourceLimitExceeded::CpuTime(1))
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(target_os = "linux")]
+    async fn test_cpu_limit_confirms_sigkill_by_cpu_time() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let limits = ScriptLimits {
+            cpu_seconds: Some(1),
+            ..ScriptLimits::default()
+        };
+        // Ignoring SIGXCPU runs on to the hard limit and SIGKILL
+        let (output, containment) = run_limited(&limits, "trap '' XCPU; while :; do :; done").await;
+        assert_eq!(output.status.signal(), Some(libc::SIGKILL));
+        assert!(output.cpu_time.unwrap() >= Duration::from_secs(1));
+        assert!(matches!(
+            limits.check_exit(&output, &containment),
+            Err(ResourceLimitExceeded::CpuTime(1))
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_sigkill_without_cpu_use_is_not_a_limit() {
+        let limits = ScriptLimits {
+            cpu_seconds: Some(5),
+            memory_mb: Some(512),
+            ..ScriptLimits::default()
+        };
+        let (output, containment) = run_limited(&limits, "kill -9 $$").await;
+        assert!(!output.status.success());
+        assert!(limits.check_exit(&output, &containment).is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_out_of_memory_output_is_not_a_limit() {
+        let limits = ScriptLimits {
+            memory_mb: Some(64),
+            ..ScriptLimits::default()
+        };
+        // Without containment evidence these keep their normal exit handling
+        for script in ["echo 'MemoryError' >&2; exit 1", "exit 12"] {
+            let (output, containment) = run_limited(&limits, script).await;
+            assert!(looks_out_of_memory(&output), "{script}");
+            assert!(limits.check_exit(&output, &containment).is_ok(), "{script}");
+        }
+    }
+
+    #[test]
+    fn test_parse_cpu_time_from_proc_stat() {
+        let stat = "4242 (my (odd) script) Z 1 4242 4242 0 -1 4194560 100 0 0 0 \
+                    150 75 0 0 20 0 1 0 12345 0 0";
+        assert_eq!(parse_cpu_time(stat, 100), Some(Duration::from_millis(2250)));
+        assert_eq!(parse_cpu_time("4242 (sh) Z 1", 100), None);
+        assert_eq!(parse_cpu_time(stat, 0), None);
+    }
+
+    /// Run a shell script under `limits` and wait for it (10s cap)
+    async fn run_limited(limits: &ScriptLimits, script: &str) -> (ScriptOutput, Containment) {
+        let mut command = shell(script);
+        limits.apply(&mut command);
+        let mut child = command.spawn().unwrap();
+        let containment = limits.contain(&child).unwrap();
+        let output = tokio::time::timeout(
+            Duration::from_secs(10),
+            wait_with_limited_output(&mut child, 1024, None),
+        )
+        .await
+        .expect("script should finish")
+        .unwrap();
+        (output, containment)
+    }
+}
@@ -0,0 +1,642 @@
+//! Tamper-evident audit log: hash chaining and signatures.
+//!
+//! With `logging.integrity.hash_chain`, every entry written to the local log
+//! carries `prev_hash`, the SHA-256 of the previous line exactly as written.
+//! Editing or removing any entry breaks the link from the entry after it,
+//! which `rulez verify-logs` reports.
+//!
+//! A chain alone can be recomputed by whoever can write the file. With
+//! `logging.integrity.signing_key_env`, each chained entry also carries an
+//! HMAC-SHA256 `signature` over the line, so rewriting the chain requires the
+//! key. Keep the key out of reach of the agent (e.g. set it only in the
+//! environment the hooks run with, not in files the agent can read).
+//!
+//! Removing the newest entries, or emptying the file, leaves no broken link.
+//! Writers therefore keep a checkpoint of the chain in `<log>.head`: the
+//! number of chained entries, the hash of the newest one, and the hash the
+//! first line links to. It is signed like the entries.
+//!
+//! Hook processes append concurrently, so reading the previous line and
+//! appending the next one happens under a lock file next to the log.
+
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// `prev_hash` of the first entry in a chain
+pub const GENESIS_HASH: &str =
+    "sha256:0000000000000000000000000000000000000000000000000000000000000000";
+
+const SIGNATURE_PREFIX: &str = ",\"signature\":\"hmac-sha256:";
+
+/// Chaining state for one log file
+pub struct LogChain {
+    path: PathBuf,
+    key: Option<Vec<u8>>,
+}
+
+impl LogChain {
+    pub fn new(path: PathBuf, key: Option<Vec<u8>>) -> Self {
+        Self { path, key }
+    }
+
+    /// Lock the log, then return the link for the next entry
+    ///
+    /// Keep the link until the entry has been written and flushed, then pass
+    /// it to [`LogChain::record`].
+    pub fn next_link(&self) -> Result<ChainLink> {
+        let lock = ChainLock::acquire(&lock_path(&self.path))?;
+        let previous = last_line(&self.path)?;
+        let head = self.head_to_extend(previous.as_deref());
+        Ok(ChainLink {
+            _lock: lock,
+            prev_hash: chain_hash(previous.as_deref()),
+            head,
+        })
+    }
+
+    /// Update the checkpoint for `entry`, just appended, and release the lock
+    pub fn record(&self, link: ChainLink, entry: &str) -> Result<()> {
+        let Some(mut head) = link.head else {
+            return Ok(());
+        };
+        head.entries += 1;
+        head.last_hash = chain_hash(Some(entry.as_bytes()));
+        store_head(&self.path, &head, self.key.as_deref())
+    }
+
+    /// Checkpoint the next entry extends
+    ///
+    /// A new chain starts one. A checkpoint that is missing or unreadable
+    /// while the log already holds chained entries is not recreated, since
+    /// that would vouch for whatever the log was cut down to;
+    /// `rulez verify-logs` reports it instead.
+    fn head_to_extend(&self, previous: Option<&[u8]>) -> Option<ChainHead> {
+        match read_head(&self.path, self.key.as_deref()) {
+            Ok(Some((head, true))) => Some(head),
+            Ok(None) if previous.and_then(prev_hash).is_none() => Some(ChainHead::default()),
+            Ok(Some((_, false))) => {
+                tracing::warn!(
+                    "Log checkpoint {} has an invalid signature; not updating it",
+                    head_path(&self.path).display()
+                );
+                None
+            }
+            Ok(None) => {
+                tracing::warn!(
+                    "Log checkpoint {} is missing; not recreating it",
+                    head_path(&self.path).display()
+                );
+                None
+            }
+            Err(e) => {
+                tracing::warn!("{:#}; not updating it", e);
+                None
+            }
+        }
+    }
+
+    /// Append the signature to a serialized entry, if a key is configured
+    ///
+    /// `signature` is the entry's last serialized field, so the signed line
+    /// is the same as serializing the entry with its signature set.
+    pub fn sign(&self, line: String) -> String {
+        match &self.key {
+            Some(key) => sign_line(&line, key),
+            None => line,
+        }
+    }
+}
+
+/// Where the next entry joins the chain, holding the log lock
+pub struct ChainLink {
+    _lock: ChainLock,
+    /// `prev_hash` for the next entry
+    pub prev_hash: String,
+    /// Checkpoint to update, if it can be trusted
+    head: Option<ChainHead>,
+}
+
+/// Checkpoint of a chain, kept in `<log>.head`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ChainHead {
+    /// Chained entries in the log
+    entries: usize,
+    /// `prev_hash` of the log's first line, if that line is chained: the
+    /// genesis hash until old entries are purged
+    anchor: String,
+    /// Hash of the newest chained entry
+    last_hash: String,
+}
+
+impl Default for ChainHead {
+    fn default() -> Self {
+        Self {
+            entries: 0,
+            anchor: GENESIS_HASH.to_string(),
+            last_hash: GENESIS_HASH.to_string(),
+        }
+    }
+}
+
+/// Exclusive lock on a log file, held as `<log>.lock`
+pub struct ChainLock(PathBuf);
+
+impl ChainLock {
+    /// Lock files older than this are left by a crashed writer
+    const STALE_AFTER: Duration = Duration::from_secs(10);
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn acquire(path: &Path) -> Result<Self> {
+        let started = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(_) => return Ok(Self(path.to_path_buf())),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(path)
+                        .and_then(|meta| meta.modified())
+                        .is_ok_and(|modified| {
+                            modified.elapsed().unwrap_or_default() > Self::STALE_AFTER
+                        });
+                    if stale {
+                        let _ = std::fs::remove_file(path);
+                        continue;
+                    }
+                    if started.elapsed() > Self::WAIT {
+                        anyhow::bail!("Timed out waiting for log lock {}", path.display());
+                    }
+                    std::thread::sleep(Duration::from_millis(2));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ChainLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Lock a file against concurrent writers, e.g. a log while rewriting it
+/// or session state during a read-modify-write
+pub(crate) fn lock_log(log_path: &Path) -> Result<ChainLock> {
+    ChainLock::acquire(&lock_path(log_path))
+}
+
+fn lock_path(log_path: &Path) -> PathBuf {
+    let mut name = log_path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+fn head_path(log_path: &Path) -> PathBuf {
+    let mut name = log_path.as_os_str().to_owned();
+    name.push(".head");
+    PathBuf::from(name)
+}
+
+/// Read the checkpoint of a log, and whether it can be trusted: with a key,
+/// only if its signature is valid
+fn read_head(log_path: &Path, key: Option<&[u8]>) -> Result<Option<(ChainHead, bool)>> {
+    let path = head_path(log_path);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let text = text.trim_end();
+    let head = serde_json::from_str(text)
+        .with_context(|| format!("Log checkpoint {} is not valid", path.display()))?;
+    let trusted = key.is_none_or(|key| verify_signature(text, key) == Some(true));
+    Ok(Some((head, trusted)))
+}
+
+/// Replace the checkpoint of a log, signed if a key is given
+pub(crate) fn store_head(log_path: &Path, head: &ChainHead, key: Option<&[u8]>) -> Result<()> {
+    let mut line = serde_json::to_string(head)?;
+    if let Some(key) = key {
+        line = sign_line(&line, key);
+    }
+    let path = head_path(log_path);
+    let mut temp = path.clone().into_os_string();
+    temp.push(".tmp");
+    std::fs::write(&temp, line + "\n")
+        .and_then(|()| std::fs::rename(&temp, &path))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Checkpoint for a log after `removed` is cut from its start, leaving
+/// `kept`; `None` when the log has no checkpoint
+///
+/// Call with the log locked, and store the result once the log is rewritten.
+/// A signed checkpoint can only be carried over with the key.
+pub(crate) fn purged_head(
+    log_path: &Path,
+    removed: &[u8],
+    kept: &[u8],
+    key: Option<&[u8]>,
+) -> Result<Option<ChainHead>> {
+    let Some((mut head, trusted)) = read_head(log_path, key)? else {
+        return Ok(None);
+    };
+    let signed = std::fs::read_to_string(head_path(log_path))?.contains(SIGNATURE_PREFIX);
+    if !trusted {
+        anyhow::bail!(
+            "Log checkpoint {} has an invalid signature",
+            head_path(log_path).display()
+        );
+    }
+    if signed && key.is_none() {
+        anyhow::bail!(
+            "Log checkpoint {} is signed; the signing key is needed to purge the log",
+            head_path(log_path).display()
+        );
+    }
+    let removed_chained = lines(removed)
+        .filter(|line| prev_hash(line).is_some())
+        .count();
+    head.entries = head.entries.saturating_sub(removed_chained);
+    head.anchor = lines(kept)
+        .next()
+        .and_then(prev_hash)
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+    Ok(Some(head))
+}
+
+fn lines(content: &[u8]) -> impl Iterator<Item = &[u8]> {
+    content
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+}
+
+/// `prev_hash` of a serialized entry, if it is chained
+fn prev_hash(line: &[u8]) -> Option<String> {
+    serde_json::from_slice::<serde_json::Value>(line)
+        .ok()?
+        .get("prev_hash")?
+        .as_str()
+        .map(String::from)
+}
+
+/// `prev_hash` value linking to `previous` (the genesis hash for none)
+pub fn chain_hash(previous: Option<&[u8]>) -> String {
+    match previous {
+        Some(line) => format!("sha256:{}", hex(&Sha256::digest(line))),
+        None => GENESIS_HASH.to_string(),
+    }
+}
+
+/// Last non-empty line of a file, without its newline
+fn last_line(path: &Path) -> Result<Option<Vec<u8>>> {
+    const CHUNK: u64 = 8 * 1024;
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    // Read backwards until the tail holds a full line
+    let mut pos = file.metadata()?.len();
+    let mut tail = Vec::new();
+    loop {
+        let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+        if let Some(newline) = body.iter().rposition(|&b| b == b'\n') {
+            return Ok(Some(body[newline + 1..].to_vec()));
+        }
+        if pos == 0 {
+            return Ok((!body.is_empty()).then(|| body.to_vec()));
+        }
+        let start = pos.saturating_sub(CHUNK);
+        let mut chunk = vec![0; usize::try_from(pos - start)?];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        pos = start;
+    }
+}
+
+fn sign_line(line: &str, key: &[u8]) -> String {
+    let unsigned = line.strip_suffix('}').unwrap_or(line);
+    format!(
+        "{}{}{}\"}}",
+        unsigned,
+        SIGNATURE_PREFIX,
+        hex(&hmac_sha256(key, line.as_bytes()))
+    )
+}
+
+/// Check a signed line; `None` when the line carries no signature
+fn verify_signature(line: &str, key: &[u8]) -> Option<bool> {
+    let start = line.rfind(SIGNATURE_PREFIX)?;
+    let signature = line[start + SIGNATURE_PREFIX.len()..].strip_suffix("\"}")?;
+    let unsigned = format!("{}}}", &line[..start]);
+    Some(hex(&hmac_sha256(key, unsigned.as_bytes())) == signature)
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+// =============================================================================
+// Verification
+// =============================================================================
+
+/// Result of checking a log file's chain
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ChainReport {
+    /// Non-empty lines read
+    pub entries: usize,
+    /// Entries carrying `prev_hash`
+    pub chained: usize,
+    /// Entries whose signature was checked and is valid
+    pub signed: usize,
+    /// Broken links, bad signatures, and unchained entries after the chain began
+    pub problems: Vec<String>,
+}
+
+impl ChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Verify the hash chain (and, with `key`, the signatures) of a log file
+///
+/// Entries written before chaining was enabled are skipped, and the chain
+/// starts at the first chained entry. That entry must link to the line
+/// before it or, as the first line, to the anchor in the checkpoint. The
+/// checkpoint must match the chain's length and newest entry. With a key,
+/// every chained entry and the checkpoint must carry a valid signature.
+pub fn verify_log_file(path: &Path, key: Option<&[u8]>) -> Result<ChainReport> {
+    let _lock = lock_log(path)?;
+    let content =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let head = read_head(path, key);
+    let anchor = match &head {
+        Ok(Some((head, _))) => head.anchor.clone(),
+        _ => GENESIS_HASH.to_string(),
+    };
+    let mut report = ChainReport::default();
+    let mut previous: Option<&[u8]> = None;
+    let mut newest: Option<&[u8]> = None;
+
+    for (index, line) in content.split(|&b| b == b'\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let number = index + 1;
+        report.entries += 1;
+
+        match prev_hash(line) {
+            Some(prev_hash) => {
+                report.chained += 1;
+                let expected = previous.map_or_else(|| anchor.clone(), |_| chain_hash(previous));
+                if prev_hash != expected {
+                    report.problems.push(if previous.is_some() {
+                        format!(
+                            "line {}: previous entry was modified or removed (prev_hash mismatch)",
+                            number
+                        )
+                    } else {
+                        format!(
+                            "line {}: chain does not start here (earlier entries removed)",
+                            number
+                        )
+                    });
+                }
+
+                if let Some(key) = key {
+                    match std::str::from_utf8(line)
+                        .ok()
+                        .and_then(|line| verify_signature(line, key))
+                    {
+                        Some(true) => report.signed += 1,
+                        Some(false) => report
+                            .problems
+                            .push(format!("line {}: invalid signature", number)),
+                        None => report
+                            .problems
+                            .push(format!("line {}: entry is not signed", number)),
+                    }
+                }
+                newest = Some(line);
+            }
+            None if newest.is_some() => report.problems.push(format!(
+                "line {}: entry is not chained (inserted or rewritten)",
+                number
+            )),
+            None => {}
+        }
+        previous = Some(line);
+    }
+
+    report
+        .problems
+        .extend(check_head(&head_path(path), head, report.chained, newest));
+    Ok(report)
+}
+
+/// Compare the end of the chain with the log's checkpoint
+fn check_head(
+    path: &Path,
+    head: Result<Option<(ChainHead, bool)>>,
+    chained: usize,
+    newest: Option<&[u8]>,
+) -> Option<String> {
+    let head = match head {
+        Ok(Some((head, true))) => head,
+        Ok(Some((_, false))) => {
+            return Some(format!("{}: invalid signature", path.display()));
+        }
+        Ok(None) if chained == 0 => return None,
+        Ok(None) => {
+            return Some(format!(
+                "{}: checkpoint is missing (removed, or the log was truncated)",
+                path.display()
+            ));
+        }
+        Err(e) => return Some(format!("{:#}", e)),
+    };
+    if head.entries != chained {
+        Some(format!(
+            "{}: checkpoint records {} chained entries, the log has {} (entries removed or added)",
+            path.display(),
+            head.entries,
+            chained
+        ))
+    } else if chain_hash(newest) != head.last_hash {
+        Some(format!(
+            "{}: newest entry does not match the checkpoint (removed or rewritten)",
+            path.display()
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(chain: &LogChain, body: &str) {
+        use std::io::Write;
+
+        let next = chain.next_link().unwrap();
+        let line = chain.sign(format!(
+            "{{\"n\":\"{}\",\"prev_hash\":\"{}\"}}",
+            body, next.prev_hash
+        ));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&chain.path)
+            .unwrap();
+        writeln!(file, "{}", line).unwrap();
+        chain.record(next, &line).unwrap();
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // RFC 4231 test case 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_last_line_across_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        assert_eq!(last_line(&path).unwrap(), None);
+
+        let long = "x".repeat(20_000);
+        std::fs::write(&path, format!("first\n{long}\n")).unwrap();
+        assert_eq!(last_line(&path).unwrap(), Some(long.into_bytes()));
+
+        std::fs::write(&path, "only").unwrap();
+        assert_eq!(last_line(&path).unwrap(), Some(b"only".to_vec()));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        std::fs::write(&path, "{\"n\":\"before chaining\"}\n").unwrap();
+        let chain = LogChain::new(path.clone(), None);
+        for body in ["a", "b", "c", "d"] {
+            append(&chain, body);
+        }
+        assert!(!lock_path(&path).exists());
+
+        let report = verify_log_file(&path, None).unwrap();
+        assert!(report.is_intact(), "{:?}", report.problems);
+        assert_eq!((report.entries, report.chained), (5, 4));
+
+        // Deleting entry "b" breaks the link from "c"
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let tampered = [lines[0], lines[1], lines[3], lines[4]].join("\n") + "\n";
+        std::fs::write(&path, tampered).unwrap();
+        let report = verify_log_file(&path, None).unwrap();
+        assert_eq!(report.problems.len(), 2);
+        assert_eq!(
+            report.problems[0],
+            "line 3: previous entry was modified or removed (prev_hash mismatch)"
+        );
+        assert!(report.problems[1].contains("records 4 chained entries, the log has 3"));
+    }
+
+    #[test]
+    fn test_verify_detects_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let chain = LogChain::new(path.clone(), None);
+        for body in ["a", "b", "c"] {
+            append(&chain, body);
+        }
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        // Dropping the newest entry leaves every remaining link intact
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[1])).unwrap();
+        let report = verify_log_file(&path, None).unwrap();
+        assert_eq!(report.problems.len(), 1);
+        assert!(report.problems[0].contains("records 3 chained entries, the log has 2"));
+
+        // Dropping the oldest one breaks the anchor
+        std::fs::write(&path, format!("{}\n{}\n", lines[1], lines[2])).unwrap();
+        let report = verify_log_file(&path, None).unwrap();
+        assert_eq!(
+            report.problems[0],
+            "line 1: chain does not start here (earlier entries removed)"
+        );
+
+        // An emptied log still has its checkpoint
+        std::fs::write(&path, "").unwrap();
+        assert!(!verify_log_file(&path, None).unwrap().is_intact());
+
+        // Removing the checkpoint too is reported, and appending does not
+        // recreate it
+        std::fs::write(&path, &content).unwrap();
+        std::fs::remove_file(head_path(&path)).unwrap();
+        append(&chain, "d");
+        assert!(!head_path(&path).exists());
+        let report = verify_log_file(&path, None).unwrap();
+        assert_eq!(report.problems.len(), 1);
+        assert!(report.problems[0].contains("checkpoint is missing"));
+    }
+
+    #[test]
+    fn test_verify_signatures() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let chain = LogChain::new(path.clone(), Some(b"secret".to_vec()));
+        append(&chain, "a");
+        append(&chain, "b");
+
+        let report = verify_log_file(&path, Some(b"secret")).unwrap();
+        assert!(report.is_intact(), "{:?}", report.problems);
+        assert_eq!(report.signed, 2);
+
+        let report = verify_log_file(&path, Some(b"wrong")).unwrap();
+        assert_eq!(report.problems.len(), 3);
+        assert!(
+            report
+                .problems
+                .iter()
+                .all(|problem| problem.contains("invalid signature"))
+        );
+
+        // A rewritten chain without the key has no valid signatures
+        let unsigned = LogChain::new(dir.path().join("forged.log"), None);
+        append(&unsigned, "a");
+        let report = verify_log_file(&unsigned.path, Some(b"secret")).unwrap();
+        assert_eq!(report.problems.len(), 2);
+        assert_eq!(report.problems[0], "line 1: entry is not signed");
+        assert!(report.problems[1].ends_with("forged.log.head: invalid signature"));
+    }
+}
@@ -0,0 +1,611 @@
+//! Export audit log entries to CSV and Parquet for offline analysis.
+//!
+//! Both formats share one flat row layout (see [`COLUMNS`]): nested fields
+//! such as `timing` and `event_details` become plain columns, so the files
+//! load directly into pandas, DuckDB, or a spreadsheet.
+//!
+//! The Parquet writer is deliberately minimal: one row group, one
+//! uncompressed PLAIN-encoded data page per column, and every column
+//! OPTIONAL. That is enough for any Parquet reader and keeps the binary free
+//! of an Arrow dependency.
+
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{EventDetails, LogEntry};
+
+/// Physical type of an export column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// UTF-8 string
+    Text,
+    /// 64-bit integer
+    Int,
+    /// Milliseconds since the Unix epoch (UTC)
+    Timestamp,
+}
+
+/// Export columns, in file order
+pub const COLUMNS: &[(&str, ColumnType)] = &[
+    ("schema_version", ColumnType::Int),
+    ("timestamp", ColumnType::Timestamp),
+    ("event_type", ColumnType::Text),
+    ("session_id", ColumnType::Text),
+    ("tool_name", ColumnType::Text),
+    ("tool_use_id", ColumnType::Text),
+    ("outcome", ColumnType::Text),
+    ("decision", ColumnType::Text),
+    ("mode", ColumnType::Text),
+    ("priority", ColumnType::Int),
+    ("rules_matched", ColumnType::Text),
+    ("rules_matched_count", ColumnType::Int),
+    ("processing_ms", ColumnType::Int),
+    ("rules_evaluated", ColumnType::Int),
+    ("command", ColumnType::Text),
+    ("file_path", ColumnType::Text),
+    ("pattern", ColumnType::Text),
+    ("reason", ColumnType::Text),
+    ("permission_decision", ColumnType::Text),
+    ("context_length", ColumnType::Int),
+    ("trust_level", ColumnType::Text),
+    ("sample_rate", ColumnType::Int),
+    ("anomaly", ColumnType::Text),
+];
+
+/// One cell of an export row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Null,
+    Text(String),
+    Int(i64),
+}
+
+impl Cell {
+    fn text(value: Option<impl ToString>) -> Self {
+        value.map_or(Self::Null, |v| Self::Text(v.to_string()))
+    }
+
+    fn int(value: Option<impl TryInto<i64>>) -> Self {
+        value
+            .and_then(|v| v.try_into().ok())
+            .map_or(Self::Null, Self::Int)
+    }
+}
+
+/// Flatten a log entry into cells matching [`COLUMNS`]
+pub fn flatten(entry: &LogEntry) -> Vec<Cell> {
+    let (command, file_path, pattern) = entry
+        .event_details
+        .as_ref()
+        .map(detail_columns)
+        .unwrap_or_default();
+    let response = entry.response.as_ref();
+
+    vec![
+        Cell::int(Some(entry.schema_version)),
+        Cell::Int(entry.timestamp.timestamp_millis()),
+        Cell::Text(entry.event_type.clone()),
+        Cell::Text(entry.session_id.clone()),
+        Cell::text(entry.tool_name.as_ref()),
+        Cell::text(entry.tool_use_id.as_ref()),
+        Cell::text(serde_name(&entry.outcome)),
+        Cell::text(entry.decision),
+        Cell::text(entry.mode),
+        Cell::int(entry.priority),
+        Cell::Text(entry.rules_matched.join(",")),
+        Cell::int(Some(entry.rules_matched.len())),
+        Cell::int(Some(entry.timing.processing_ms)),
+        Cell::int(Some(entry.timing.rules_evaluated)),
+        Cell::text(command),
+        Cell::text(file_path),
+        Cell::text(pattern),
+        Cell::text(response.and_then(|r| r.reason.as_ref())),
+        Cell::text(response.and_then(|r| r.permission_decision.as_ref().and_then(serde_name))),
+        Cell::int(response.and_then(|r| r.context_length)),
+        Cell::text(entry.trust_level),
+        Cell::int(entry.sample_rate),
+        Cell::text(entry.anomaly.as_ref().and_then(|a| serde_name(&a.kind))),
+    ]
+}
+
+/// Serialized name of a unit enum variant, e.g. `"block"`
+fn serde_name<T: serde::Serialize>(value: &T) -> Option<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+/// `(command, file_path, pattern)` of the tool details
+pub(crate) fn detail_columns(
+    details: &EventDetails,
+) -> (Option<String>, Option<String>, Option<String>) {
+    match details {
+        EventDetails::Bash { command } => (Some(command.clone()), None, None),
+        EventDetails::Write { file_path }
+        | EventDetails::Edit { file_path }
+        | EventDetails::Read { file_path } => (None, Some(file_path.clone()), None),
+        EventDetails::Glob { pattern, path } | EventDetails::Grep { pattern, path } => {
+            (None, path.clone(), pattern.clone())
+        }
+        EventDetails::Permission { tool_details, .. } => detail_columns(tool_details),
+        EventDetails::Session { .. } | EventDetails::Unknown { .. } => (None, None, None),
+    }
+}
+
+/// Parse a `--since`/`--until` value
+///
+/// Accepts an RFC 3339 timestamp, a date (`2026-01-01`, midnight UTC), or a
+/// relative age such as `30d`, `12h`, `45m`, or `2w`, measured back from `now`.
+pub fn parse_time_bound(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+
+    let value = value.trim();
+    let split = value.len().saturating_sub(1);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().with_context(|| {
+        format!(
+            "Invalid time '{}': use RFC 3339 (2026-01-01T00:00:00Z), a date (2026-01-01), or an age like 30d, 12h, 45m, 2w",
+            value
+        )
+    })?;
+    let age = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => anyhow::bail!("Invalid time unit in '{}': use m, h, d, or w", value),
+    };
+    Ok(now - age)
+}
+
+// =============================================================================
+// CSV
+// =============================================================================
+
+/// Write entries as CSV (RFC 4180) with a header row
+///
+/// Timestamps are written as RFC 3339 strings; nulls as empty fields.
+pub fn write_csv<W: Write>(entries: &[LogEntry], mut out: W) -> Result<()> {
+    let header: Vec<&str> = COLUMNS.iter().map(|(name, _)| *name).collect();
+    writeln!(out, "{}", header.join(","))?;
+
+    for entry in entries {
+        let fields: Vec<String> = flatten(entry)
+            .into_iter()
+            .zip(COLUMNS)
+            .map(|(cell, (_, kind))| match (cell, kind) {
+                (Cell::Null, _) => String::new(),
+                (Cell::Int(millis), ColumnType::Timestamp) => {
+                    DateTime::from_timestamp_millis(millis)
+                        .map(|t| t.to_rfc3339())
+                        .unwrap_or_default()
+                }
+                (Cell::Int(n), _) => n.to_string(),
+                (Cell::Text(s), _) => csv_field(&s),
+            })
+            .collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// =============================================================================
+// Parquet
+// =============================================================================
+
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+// Parquet enum values (parquet.thrift)
+const TYPE_INT64: i32 = 2;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const REPETITION_OPTIONAL: i32 = 1;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+/// Write entries as a Parquet file
+pub fn write_parquet<W: Write>(entries: &[LogEntry], mut out: W) -> Result<()> {
+    let rows: Vec<Vec<Cell>> = entries.iter().map(flatten).collect();
+    let num_rows = i64::try_from(rows.len())?;
+
+    let mut file = PARQUET_MAGIC.to_vec();
+    let mut chunks = Vec::with_capacity(COLUMNS.len());
+    for (index, (name, kind)) in COLUMNS.iter().enumerate() {
+        let offset = i64::try_from(file.len())?;
+        let page = data_page(rows.iter().map(|row| &row[index]));
+
+        let mut header = Thrift::default();
+        header.i32(1, PAGE_DATA);
+        header.i32(2, i32::try_from(page.len())?);
+        header.i32(3, i32::try_from(page.len())?);
+        header.begin_struct(5);
+        header.i32(1, i32::try_from(rows.len())?);
+        header.i32(2, ENCODING_PLAIN);
+        header.i32(3, ENCODING_RLE);
+        header.i32(4, ENCODING_RLE);
+        header.end_struct();
+        header.stop();
+
+        let size = i64::try_from(header.buf.len() + page.len())?;
+        file.extend_from_slice(&header.buf);
+        file.extend_from_slice(&page);
+        chunks.push((*name, *kind, offset, size));
+    }
+
+    let mut meta = Thrift::default();
+    meta.i32(1, 1);
+    meta.list(2, Thrift::STRUCT, COLUMNS.len() + 1);
+    meta.list_struct(|schema| {
+        schema.binary(4, b"schema");
+        schema.i32(5, i32::try_from(COLUMNS.len()).unwrap_or(i32::MAX));
+    });
+    for (name, kind) in COLUMNS {
+        meta.list_struct(|element| {
+            let (physical, converted) = match kind {
+                ColumnType::Text => (TYPE_BYTE_ARRAY, Some(CONVERTED_UTF8)),
+                ColumnType::Int => (TYPE_INT64, None),
+                ColumnType::Timestamp => (TYPE_INT64, Some(CONVERTED_TIMESTAMP_MILLIS)),
+            };
+            element.i32(1, physical);
+            element.i32(3, REPETITION_OPTIONAL);
+            element.binary(4, name.as_bytes());
+            if let Some(converted) = converted {
+                element.i32(6, converted);
+            }
+        });
+    }
+    meta.i64(3, num_rows);
+    meta.list(4, Thrift::STRUCT, 1);
+    meta.list_struct(|row_group| {
+        row_group.list(1, Thrift::STRUCT, chunks.len());
+        for (name, kind, offset, size) in &chunks {
+            row_group.list_struct(|chunk| {
+                chunk.i64(2, *offset);
+                chunk.begin_struct(3);
+                chunk.i32(
+                    1,
+                    if *kind == ColumnType::Text {
+                        TYPE_BYTE_ARRAY
+                    } else {
+                        TYPE_INT64
+                    },
+                );
+                chunk.list(2, Thrift::I32, 2);
+                chunk.list_i32(ENCODING_PLAIN);
+                chunk.list_i32(ENCODING_RLE);
+                chunk.list(3, Thrift::BINARY, 1);
+                chunk.list_binary(name.as_bytes());
+                chunk.i32(4, CODEC_UNCOMPRESSED);
+                chunk.i64(5, num_rows);
+                chunk.i64(6, *size);
+                chunk.i64(7, *size);
+                chunk.i64(9, *offset);
+                chunk.end_struct();
+            });
+        }
+        row_group.i64(2, chunks.iter().map(|(.., size)| size).sum());
+        row_group.i64(3, num_rows);
+    });
+    meta.binary(
+        6,
+        concat!("rulez version ", env!("CARGO_PKG_VERSION")).as_bytes(),
+    );
+    meta.stop();
+
+    file.extend_from_slice(&meta.buf);
+    file.extend_from_slice(&u32::try_from(meta.buf.len())?.to_le_bytes());
+    file.extend_from_slice(PARQUET_MAGIC);
+
+    out.write_all(&file)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Data page body: definition levels, then PLAIN values of non-null cells
+fn data_page<'a>(cells: impl Iterator<Item = &'a Cell>) -> Vec<u8> {
+    let mut defined = Vec::new();
+    let mut values = Vec::new();
+    for cell in cells {
+        defined.push(*cell != Cell::Null);
+        match cell {
+            Cell::Null => {}
+            Cell::Int(n) => values.extend_from_slice(&n.to_le_bytes()),
+            Cell::Text(s) => {
+                let len = u32::try_from(s.len()).unwrap_or(u32::MAX);
+                values.extend_from_slice(&len.to_le_bytes());
+                values.extend_from_slice(s.as_bytes());
+            }
+        }
+    }
+
+    // Definition levels (bit width 1) as a single bit-packed run of the
+    // RLE/bit-packing hybrid, prefixed with its byte length
+    let mut levels = Vec::new();
+    let groups = defined.len().div_ceil(8);
+    write_uleb128(&mut levels, ((groups as u64) << 1) | 1);
+    for group in defined.chunks(8) {
+        let byte = group
+            .iter()
+            .enumerate()
+            .fold(0u8, |byte, (bit, &set)| byte | (u8::from(set) << bit));
+        levels.push(byte);
+    }
+
+    let mut page = Vec::with_capacity(4 + levels.len() + values.len());
+    page.extend_from_slice(
+        &u32::try_from(levels.len())
+            .unwrap_or(u32::MAX)
+            .to_le_bytes(),
+    );
+    page.extend_from_slice(&levels);
+    page.extend_from_slice(&values);
+    page
+}
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Minimal Thrift compact protocol encoder for Parquet metadata
+#[derive(Default)]
+struct Thrift {
+    buf: Vec<u8>,
+    last_field: Vec<i16>,
+    field: i16,
+}
+
+impl Thrift {
+    const I32: u8 = 5;
+    const I64: u8 = 6;
+    const BINARY: u8 = 8;
+    const LIST: u8 = 9;
+    const STRUCT: u8 = 12;
+
+    fn field_header(&mut self, id: i16, kind: u8) {
+        if let Ok(delta @ 1..=15) = u8::try_from(id - self.field) {
+            self.buf.push((delta << 4) | kind);
+        } else {
+            self.buf.push(kind);
+            self.varint(zigzag(i64::from(id)));
+        }
+        self.field = id;
+    }
+
+    fn varint(&mut self, value: u64) {
+        write_uleb128(&mut self.buf, value);
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field_header(id, Self::I32);
+        self.varint(zigzag(i64::from(value)));
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field_header(id, Self::I64);
+        self.varint(zigzag(value));
+    }
+
+    fn binary(&mut self, id: i16, value: &[u8]) {
+        self.field_header(id, Self::BINARY);
+        self.list_binary(value);
+    }
+
+    fn begin_struct(&mut self, id: i16) {
+        self.field_header(id, Self::STRUCT);
+        self.last_field.push(self.field);
+        self.field = 0;
+    }
+
+    fn end_struct(&mut self) {
+        self.stop();
+        self.field = self.last_field.pop().unwrap_or(0);
+    }
+
+    fn stop(&mut self) {
+        self.buf.push(0);
+    }
+
+    fn list(&mut self, id: i16, element: u8, len: usize) {
+        self.field_header(id, Self::LIST);
+        if len < 15 {
+            self.buf.push(((len as u8) << 4) | element);
+        } else {
+            self.buf.push(0xf0 | element);
+            self.varint(len as u64);
+        }
+    }
+
+    fn list_i32(&mut self, value: i32) {
+        self.varint(zigzag(i64::from(value)));
+    }
+
+    fn list_binary(&mut self, value: &[u8]) {
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+
+    /// Write one struct element of a list
+    fn list_struct(&mut self, body: impl FnOnce(&mut Self)) {
+        self.last_field.push(self.field);
+        self.field = 0;
+        body(self);
+        self.end_struct();
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{LOG_SCHEMA_VERSION, ResponseSummary};
+
+    fn entry(command: Option<&str>) -> LogEntry {
+        let mut entry: LogEntry = serde_json::from_value(serde_json::json!({
+            "schema_version": LOG_SCHEMA_VERSION,
+            "timestamp": "2026-10-01T12:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": "s1",
+            "tool_name": "Bash",
+            "rules_matched": ["no-force-push", "audit-git"],
+            "outcome": "block",
+            "timing": {"processing_ms": 3, "rules_evaluated": 7},
+        }))
+        .unwrap();
+        entry.event_details = command.map(|c| EventDetails::Bash {
+            command: c.to_string(),
+        });
+        entry.response = Some(ResponseSummary {
+            continue_: false,
+            reason: Some("Force \"push\", blocked".to_string()),
+            context_length: None,
+            permission_decision: None,
+        });
+        entry
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        let now = DateTime::parse_from_rfc3339("2026-10-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_time_bound("30d", now).unwrap().to_rfc3339(),
+            "2026-09-15T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_time_bound("12h", now).unwrap().to_rfc3339(),
+            "2026-10-14T12:00:00+00:00"
+        );
+        assert_eq!(
+            parse_time_bound("2026-01-01T00:00:00Z", now)
+                .unwrap()
+                .to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_time_bound("2026-03-01", now).unwrap().to_rfc3339(),
+            "2026-03-01T00:00:00+00:00"
+        );
+        assert!(parse_time_bound("30y", now).is_err());
+        assert!(parse_time_bound("soon", now).is_err());
+    }
+
+    #[test]
+    fn test_flatten_matches_columns() {
+        let cells = flatten(&entry(Some("git push --force")));
+        assert_eq!(cells.len(), COLUMNS.len());
+
+        let column = |name: &str| {
+            let index = COLUMNS.iter().position(|(n, _)| *n == name).unwrap();
+            cells[index].clone()
+        };
+        assert_eq!(column("outcome"), Cell::Text("block".to_string()));
+        assert_eq!(
+            column("rules_matched"),
+            Cell::Text("no-force-push,audit-git".to_string())
+        );
+        assert_eq!(column("rules_matched_count"), Cell::Int(2));
+        assert_eq!(
+            column("command"),
+            Cell::Text("git push --force".to_string())
+        );
+        assert_eq!(column("file_path"), Cell::Null);
+        assert_eq!(column("processing_ms"), Cell::Int(3));
+    }
+
+    #[test]
+    fn test_write_csv_quotes_fields() {
+        let mut out = Vec::new();
+        write_csv(&[entry(Some("echo a,b"))], &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let mut lines = csv.lines();
+
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("schema_version,timestamp,event_type")
+        );
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("2,2026-10-01T12:00:00+00:00,PreToolUse,s1,Bash,,block,"));
+        assert!(row.contains(",\"no-force-push,audit-git\",2,3,7,\"echo a,b\",,,"));
+        assert!(row.contains("\"Force \"\"push\"\", blocked\""));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_write_parquet_layout() {
+        let entries = [entry(Some("ls")), entry(None)];
+        let mut out = Vec::new();
+        write_parquet(&entries, &mut out).unwrap();
+
+        assert_eq!(&out[..4], PARQUET_MAGIC);
+        assert_eq!(&out[out.len() - 4..], PARQUET_MAGIC);
+        let footer_len =
+            u32::from_le_bytes(out[out.len() - 8..out.len() - 4].try_into().unwrap()) as usize;
+        let footer = &out[out.len() - 8 - footer_len..out.len() - 8];
+        // FileMetaData starts with version = 1 (field 1, i32, zigzag 2)
+        assert_eq!(&footer[..2], &[0x15, 0x02]);
+        for (name, _) in COLUMNS {
+            assert!(
+                footer
+                    .windows(name.len())
+                    .any(|window| window == name.as_bytes()),
+                "missing column {name} in footer"
+            );
+        }
+
+        // Definition levels mark the null; only defined values are stored
+        let page = data_page([Cell::Int(2), Cell::Null, Cell::Int(5)].iter());
+        assert_eq!(&page[..4], &2u32.to_le_bytes());
+        assert_eq!(&page[4..6], &[0x03, 0b101]);
+        assert_eq!(page.len(), 6 + 16);
+    }
+
+    #[test]
+    fn test_thrift_field_deltas() {
+        let mut thrift = Thrift::default();
+        thrift.i32(1, 1);
+        thrift.i64(3, -1);
+        thrift.begin_struct(20);
+        thrift.i32(1, 0);
+        thrift.end_struct();
+        thrift.stop();
+        assert_eq!(
+            thrift.buf,
+            vec![0x15, 0x02, 0x26, 0x01, 0x0c, 0x28, 0x15, 0x00, 0x00, 0x00]
+        );
+    }
+}
@@ -3,11 +3,14 @@ use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::Duration;
-
-use crate::models::LogEntry;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::log_chain::LogChain;
+use crate::models::{Decision, LOG_SCHEMA_VERSION, LogEntry, Outcome};
 use serde::{Deserialize, Serialize};
 
 // =============================================================================
@@ -22,17 +25,197 @@ use serde::{Deserialize, Serialize};
 ///     backends:
 ///       - type: otlp
 ///         endpoint: "http://localhost:4318/v1/logs"
+///       - type: otlp_traces
+///         endpoint: "http://localhost:4318/v1/traces"
 ///       - type: datadog
 ///         api_key: "${DD_API_KEY}"
 ///       - type: splunk
 ///         endpoint: "https://splunk:8088/services/collector/event"
 ///         token: "${SPLUNK_HEC_TOKEN}"
+///       - type: syslog
+///         facility: local0
+///       - type: journald
+///     flush_interval_ms: 1000
+///     queue_capacity: 1024
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LoggingConfig {
+    /// Local log file; relative paths are resolved from the project directory
+    /// (defaults to ~/.claude/logs/rulez.log)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
     /// External logging backends
     #[serde(default)]
     pub backends: Vec<BackendConfig>,
+
+    /// Longest the background writer buffers entries while the queue stays busy
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+
+    /// Entries that may wait for the background writer before logging blocks
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+
+    /// Keep only a sample of low-value entries
+    #[serde(default)]
+    pub sampling: LogSampling,
+
+    /// Tamper evidence for the local log file
+    #[serde(default)]
+    pub integrity: LogIntegrity,
+
+    /// Remove local log entries older than this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            backends: Vec::new(),
+            flush_interval_ms: default_flush_interval_ms(),
+            queue_capacity: default_queue_capacity(),
+            sampling: LogSampling::default(),
+            integrity: LogIntegrity::default(),
+            retention_days: None,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// The local log file: `path` resolved against the working directory
+    /// (the project a hook runs in), or the global default
+    pub fn log_path(&self) -> PathBuf {
+        match std::env::current_dir() {
+            Ok(dir) => self.log_path_in(&dir),
+            Err(_) => self.log_path_in(Path::new("")),
+        }
+    }
+
+    /// The local log file of the project at `project_root`, for readers
+    /// that do not run inside it (e.g. the desktop UI)
+    pub fn log_path_in(&self, project_root: &Path) -> PathBuf {
+        let Some(ref path) = self.path else {
+            return Logger::default_log_path();
+        };
+        let path = crate::digest::expand_home(path);
+        if path.is_absolute() {
+            return path;
+        }
+        project_root.join(path)
+    }
+}
+
+/// Tamper evidence for the local log file (see [`crate::log_chain`])
+///
+/// ```yaml
+/// settings:
+///   logging:
+///     integrity:
+///       hash_chain: true
+///       signing_key_env: RULEZ_LOG_KEY
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct LogIntegrity {
+    /// Link each entry to the previous one with a SHA-256 hash
+    #[serde(default)]
+    pub hash_chain: bool,
+
+    /// Environment variable holding a key to sign chained entries with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key_env: Option<String>,
+}
+
+impl LogIntegrity {
+    /// Signing key from `signing_key_env`, if configured and set
+    pub fn signing_key(&self) -> Option<Vec<u8>> {
+        let var = self.signing_key_env.as_deref()?;
+        match std::env::var(var) {
+            Ok(key) if !key.is_empty() => Some(key.into_bytes()),
+            _ => {
+                tracing::warn!("Log signing key variable {} is not set", var);
+                None
+            }
+        }
+    }
+}
+
+/// Sampling of low-value log entries: keep one in every N
+///
+/// Blocks, warnings, context injections, and session summaries are always
+/// logged. Kept entries of a sampled kind record `sample_rate` so counts can
+/// be scaled back up.
+///
+/// ```yaml
+/// settings:
+///   logging:
+///     sampling:
+///       allow: 20   # 1 in 20 allowed events
+///       audit: 5    # 1 in 5 audit-mode matches
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LogSampling {
+    /// Keep 1 in N allowed events (no block, warning, or injection)
+    #[serde(default = "keep_every_entry")]
+    pub allow: u32,
+
+    /// Keep 1 in N events decided by an audit-mode rule
+    #[serde(default = "keep_every_entry")]
+    pub audit: u32,
+}
+
+impl Default for LogSampling {
+    fn default() -> Self {
+        Self {
+            allow: keep_every_entry(),
+            audit: keep_every_entry(),
+        }
+    }
+}
+
+fn keep_every_entry() -> u32 {
+    1
+}
+
+impl LogSampling {
+    /// The 1-in-N rate that applies to an entry (1 for entries always kept)
+    pub fn rate_for(&self, entry: &LogEntry) -> u32 {
+        let always_kept = entry.outcome != Outcome::Allow
+            || entry.session_summary.is_some()
+            || entry.waivers.is_some()
+            || matches!(entry.decision, Some(Decision::Blocked | Decision::Warned));
+        if always_kept {
+            1
+        } else if entry.decision == Some(Decision::Audited) {
+            self.audit.max(1)
+        } else {
+            self.allow.max(1)
+        }
+    }
+
+    /// Decide whether to log an entry, marking kept entries with their rate
+    pub fn sample(&self, entry: &mut LogEntry) -> bool {
+        let rate = self.rate_for(entry);
+        if rate == 1 {
+            return true;
+        }
+        // Hook processes log one entry each, so a counter wouldn't work
+        let keep = uuid::Uuid::new_v4().as_u128() % u128::from(rate) == 0;
+        if keep {
+            entry.sample_rate = Some(rate);
+        }
+        keep
+    }
+}
+
+fn default_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_queue_capacity() -> usize {
+    1024
 }
 
 /// Configuration for a single logging backend.
@@ -47,6 +230,15 @@ pub enum BackendConfig {
         #[serde(default = "default_timeout")]
         timeout_secs: u64,
     },
+    /// OpenTelemetry traces: a span per evaluation with a child span per rule
+    #[serde(rename = "otlp_traces")]
+    OtlpTraces {
+        endpoint: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default = "default_timeout")]
+        timeout_secs: u64,
+    },
     /// Datadog Log Management via HTTP API
     Datadog {
         #[serde(default = "default_datadog_endpoint")]
@@ -64,6 +256,62 @@ pub enum BackendConfig {
         #[serde(default = "default_timeout")]
         timeout_secs: u64,
     },
+    /// Local syslog daemon (RFC 3164), or a remote syslog server over UDP
+    Syslog {
+        #[serde(default = "default_syslog_socket")]
+        socket: String,
+        /// `host:port` of a remote server; replaces the local socket when set
+        #[serde(default)]
+        address: Option<String>,
+        #[serde(default)]
+        facility: SyslogFacility,
+    },
+    /// systemd journal, with log fields as journal fields
+    Journald {
+        #[serde(default = "default_journald_socket")]
+        socket: String,
+    },
+    /// Windows Event Log (Application log)
+    Eventlog {
+        #[serde(default = "default_eventlog_source")]
+        source: String,
+    },
+}
+
+/// Syslog facility for the `syslog` backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFacility {
+    #[default]
+    User,
+    Daemon,
+    Auth,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    fn code(self) -> u8 {
+        match self {
+            Self::User => 1,
+            Self::Daemon => 3,
+            Self::Auth => 4,
+            Self::Local0 => 16,
+            Self::Local1 => 17,
+            Self::Local2 => 18,
+            Self::Local3 => 19,
+            Self::Local4 => 20,
+            Self::Local5 => 21,
+            Self::Local6 => 22,
+            Self::Local7 => 23,
+        }
+    }
 }
 
 fn default_timeout() -> u64 {
@@ -75,6 +323,15 @@ fn default_datadog_endpoint() -> String {
 fn default_splunk_sourcetype() -> String {
     "rulez".to_string()
 }
+fn default_syslog_socket() -> String {
+    "/dev/log".to_string()
+}
+fn default_journald_socket() -> String {
+    "/run/systemd/journal/socket".to_string()
+}
+fn default_eventlog_source() -> String {
+    "rulez".to_string()
+}
 
 // =============================================================================
 // Backend Trait and Implementations
@@ -87,7 +344,7 @@ trait LogBackend: Send + Sync {
 }
 
 /// Expand `${VAR}` references in strings.
-fn expand_env_vars(s: &str) -> String {
+pub(crate) fn expand_env_vars(s: &str) -> String {
     let mut result = s.to_string();
     while let Some(start) = result.find("${") {
         if let Some(end) = result[start..].find('}') {
@@ -159,6 +416,105 @@ impl LogBackend for OtlpBackend {
     }
 }
 
+// --- OTLP Traces Backend ---
+
+struct OtlpTracesBackend {
+    endpoint: String,
+    headers: HashMap<String, String>,
+    timeout: Duration,
+}
+
+impl LogBackend for OtlpTracesBackend {
+    fn send(&self, entry: &LogEntry) -> Result<()> {
+        let payload = serde_json::to_vec(&otlp_trace_payload(entry))?;
+        send_via_curl(&self.endpoint, &payload, &self.headers, self.timeout)
+    }
+    fn name(&self) -> &'static str {
+        "otlp_traces"
+    }
+}
+
+/// Random hex-encoded OTLP id of `bytes` bytes (16 for traces, 8 for spans)
+fn otlp_id(bytes: usize) -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..bytes * 2].to_string()
+}
+
+/// OTLP/JSON trace export request for one evaluation
+///
+/// The root `rulez.evaluate` span starts at the event timestamp and lasts
+/// `processing_ms`. Each evaluated rule becomes a `rulez.rule` child span
+/// placed at its offset within the evaluation.
+fn otlp_trace_payload(entry: &LogEntry) -> serde_json::Value {
+    let string_attr = |key: &str, value: &str| serde_json::json!({ "key": key, "value": { "stringValue": value } });
+    let nanos = |micros: u64| i64::try_from(micros.saturating_mul(1000)).unwrap_or(i64::MAX);
+
+    let trace_id = otlp_id(16);
+    let root_id = otlp_id(8);
+    let start = entry.timestamp.timestamp_nanos_opt().unwrap_or(0);
+    let end = start.saturating_add(nanos(entry.timing.processing_ms.saturating_mul(1000)));
+
+    let mut attributes = vec![
+        string_attr("rulez.event_type", &entry.event_type),
+        string_attr("rulez.session_id", &entry.session_id),
+        string_attr("rulez.outcome", &format!("{:?}", entry.outcome)),
+        string_attr("rulez.rules_matched", &entry.rules_matched.join(",")),
+        serde_json::json!({
+            "key": "rulez.rules_evaluated",
+            "value": { "intValue": entry.timing.rules_evaluated.to_string() }
+        }),
+    ];
+    if let Some(ref tool_name) = entry.tool_name {
+        attributes.push(string_attr("rulez.tool_name", tool_name));
+    }
+    if let Some(ref decision) = entry.decision {
+        attributes.push(string_attr("rulez.decision", &format!("{:?}", decision)));
+    }
+
+    let mut spans = vec![serde_json::json!({
+        "traceId": trace_id,
+        "spanId": root_id,
+        "name": "rulez.evaluate",
+        "kind": 1,
+        "startTimeUnixNano": start.to_string(),
+        "endTimeUnixNano": end.to_string(),
+        "attributes": attributes,
+    })];
+    for timing in &entry.rule_timings {
+        let rule_start = start.saturating_add(nanos(timing.offset_us));
+        spans.push(serde_json::json!({
+            "traceId": trace_id,
+            "spanId": otlp_id(8),
+            "parentSpanId": root_id,
+            "name": "rulez.rule",
+            "kind": 1,
+            "startTimeUnixNano": rule_start.to_string(),
+            "endTimeUnixNano": rule_start.saturating_add(nanos(timing.duration_us)).to_string(),
+            "attributes": [
+                string_attr("rulez.rule", &timing.rule),
+                {
+                    "key": "rulez.matched",
+                    "value": { "boolValue": entry.rules_matched.contains(&timing.rule) }
+                }
+            ],
+        }));
+    }
+
+    serde_json::json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    string_attr("service.name", "rulez"),
+                    string_attr("host.name", &hostname())
+                ]
+            },
+            "scopeSpans": [{
+                "scope": { "name": "rulez.audit" },
+                "spans": spans
+            }]
+        }]
+    })
+}
+
 // --- Datadog Backend ---
 
 struct DatadogBackend {
@@ -221,16 +577,281 @@ impl LogBackend for SplunkBackend {
     }
 }
 
+// --- System Log Backends (syslog, journald, Windows Event Log) ---
+
+/// Syslog severity of an entry: warning for blocks, informational otherwise
+fn syslog_severity(entry: &LogEntry) -> u8 {
+    match entry.outcome {
+        crate::models::Outcome::Block => 4,
+        _ => 6,
+    }
+}
+
+/// Lowercase outcome name, as written to the log file
+fn outcome_name(entry: &LogEntry) -> String {
+    serde_json::to_value(&entry.outcome)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// One-line summary of an entry for system logs
+fn summary_line(entry: &LogEntry) -> String {
+    format!(
+        "{} {} {} rules=[{}] session={} {}ms",
+        entry.event_type,
+        entry.tool_name.as_deref().unwrap_or("-"),
+        outcome_name(entry),
+        entry.rules_matched.join(","),
+        entry.session_id,
+        entry.timing.processing_ms
+    )
+}
+
+struct SyslogBackend {
+    socket: PathBuf,
+    address: Option<String>,
+    facility: SyslogFacility,
+}
+
+impl SyslogBackend {
+    /// RFC 3164 message whose text is the JSON log entry
+    ///
+    /// Local daemons stamp the hostname themselves; it is only included for
+    /// remote servers.
+    fn format(&self, entry: &LogEntry) -> Result<String> {
+        let priority = self.facility.code() * 8 + syslog_severity(entry);
+        let timestamp = entry
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%b %e %H:%M:%S");
+        let host = if self.address.is_some() {
+            format!("{} ", hostname())
+        } else {
+            String::new()
+        };
+        Ok(format!(
+            "<{}>{} {}rulez[{}]: {}",
+            priority,
+            timestamp,
+            host,
+            std::process::id(),
+            serde_json::to_string(entry)?
+        ))
+    }
+}
+
+impl LogBackend for SyslogBackend {
+    fn send(&self, entry: &LogEntry) -> Result<()> {
+        let message = self.format(entry)?;
+        if let Some(ref address) = self.address {
+            use std::net::{ToSocketAddrs, UdpSocket};
+
+            let target = address
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Could not resolve syslog address '{}'", address))?;
+            let bind = if target.is_ipv4() {
+                "0.0.0.0:0"
+            } else {
+                "[::]:0"
+            };
+            UdpSocket::bind(bind)?.send_to(message.as_bytes(), target)?;
+            return Ok(());
+        }
+        send_datagram(&self.socket, message.as_bytes())
+    }
+    fn name(&self) -> &'static str {
+        "syslog"
+    }
+}
+
+struct JournaldBackend {
+    socket: PathBuf,
+}
+
+impl JournaldBackend {
+    /// Journal fields for an entry: a summary `MESSAGE`, the standard syslog
+    /// fields, and `RULEZ_*` fields including the full JSON entry
+    fn fields(entry: &LogEntry) -> Result<Vec<(&'static str, String)>> {
+        let mut fields = vec![
+            ("MESSAGE", summary_line(entry)),
+            ("PRIORITY", syslog_severity(entry).to_string()),
+            ("SYSLOG_IDENTIFIER", "rulez".to_string()),
+            ("SYSLOG_PID", std::process::id().to_string()),
+            ("RULEZ_EVENT_TYPE", entry.event_type.clone()),
+            ("RULEZ_SESSION_ID", entry.session_id.clone()),
+            ("RULEZ_OUTCOME", outcome_name(entry)),
+            ("RULEZ_RULES_MATCHED", entry.rules_matched.join(",")),
+            (
+                "RULEZ_PROCESSING_MS",
+                entry.timing.processing_ms.to_string(),
+            ),
+        ];
+        if let Some(ref tool_name) = entry.tool_name {
+            fields.push(("RULEZ_TOOL_NAME", tool_name.clone()));
+        }
+        if let Some(ref decision) = entry.decision {
+            fields.push(("RULEZ_DECISION", format!("{:?}", decision).to_lowercase()));
+        }
+        fields.push(("RULEZ_ENTRY", serde_json::to_string(entry)?));
+        Ok(fields)
+    }
+}
+
+/// Encode fields in the journal's native protocol
+///
+/// Values containing a newline use the length-prefixed binary form.
+fn journal_payload(fields: &[(&str, String)]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for (key, value) in fields {
+        payload.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            payload.push(b'\n');
+            payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            payload.push(b'=');
+        }
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(b'\n');
+    }
+    payload
+}
+
+impl LogBackend for JournaldBackend {
+    fn send(&self, entry: &LogEntry) -> Result<()> {
+        send_datagram(&self.socket, &journal_payload(&Self::fields(entry)?))
+    }
+    fn name(&self) -> &'static str {
+        "journald"
+    }
+}
+
+/// Send one datagram to a local Unix socket
+#[cfg(unix)]
+fn send_datagram(socket: &std::path::Path, payload: &[u8]) -> Result<()> {
+    let sender = std::os::unix::net::UnixDatagram::unbound()?;
+    sender
+        .send_to(payload, socket)
+        .map_err(|e| anyhow::anyhow!("Failed to write to {}: {}", socket.display(), e))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_datagram(_socket: &std::path::Path, _payload: &[u8]) -> Result<()> {
+    anyhow::bail!("Local syslog and journald sockets require a Unix system")
+}
+
+struct EventlogBackend {
+    source: String,
+}
+
+impl LogBackend for EventlogBackend {
+    /// Write the entry to the Application log via `eventcreate`
+    #[cfg(windows)]
+    fn send(&self, entry: &LogEntry) -> Result<()> {
+        use std::process::{Command, Stdio};
+
+        let event_type = match syslog_severity(entry) {
+            4 => "WARNING",
+            _ => "INFORMATION",
+        };
+        let status = Command::new("eventcreate")
+            .args(["/L", "APPLICATION", "/T", event_type, "/ID", "1000", "/SO"])
+            .arg(&self.source)
+            .arg("/D")
+            .arg(format!(
+                "{} {}",
+                summary_line(entry),
+                serde_json::to_string(entry)?
+            ))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| anyhow::anyhow!("Failed to run eventcreate: {}", e))?;
+        if !status.success() {
+            anyhow::bail!("eventcreate exited with status: {}", status);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    fn send(&self, _entry: &LogEntry) -> Result<()> {
+        anyhow::bail!(
+            "Windows Event Log (source '{}') is only available on Windows",
+            self.source
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        "eventlog"
+    }
+}
+
 // =============================================================================
 // HTTP Transport (via curl — avoids TLS library dependency)
 // =============================================================================
 
-fn send_via_curl(
+pub(crate) fn send_via_curl(
+    url: &str,
+    body: &[u8],
+    headers: &HashMap<String, String>,
+    timeout: Duration,
+) -> Result<()> {
+    let mut child = curl_command(url, headers, timeout)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to spawn curl for logging backend: {}", e))?;
+
+    if let Some(ref mut stdin) = child.stdin {
+        stdin.write_all(body)?;
+    }
+    drop(child.stdin.take()); // Close stdin so curl proceeds
+
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("curl exited with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// [`send_via_curl`] for async callers: waits on curl without blocking the
+/// runtime, and kills it if it outlives `timeout`
+pub(crate) async fn post_via_curl(
     url: &str,
     body: &[u8],
     headers: &HashMap<String, String>,
     timeout: Duration,
 ) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut child = tokio::process::Command::from(curl_command(url, headers, timeout))
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to spawn curl: {}", e))?;
+
+    let send = async {
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(body).await?;
+        } // Dropping stdin closes it so curl proceeds
+        Ok::<_, anyhow::Error>(child.wait().await?)
+    };
+    // curl enforces --max-time itself; the extra second covers startup
+    let status = tokio::time::timeout(timeout + Duration::from_secs(1), send)
+        .await
+        .map_err(|_| anyhow::anyhow!("curl timed out after {:?}", timeout))??;
+    if !status.success() {
+        anyhow::bail!("curl exited with status: {}", status);
+    }
+
+    Ok(())
+}
+
+fn curl_command(
+    url: &str,
+    headers: &HashMap<String, String>,
+    timeout: Duration,
+) -> std::process::Command {
     use std::process::{Command, Stdio};
 
     let mut cmd = Command::new("curl");
@@ -253,22 +874,7 @@ fn send_via_curl(
         .stdin(Stdio::piped())
         .stdout(Stdio::null())
         .stderr(Stdio::null());
-
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| anyhow::anyhow!("Failed to spawn curl for logging backend: {}", e))?;
-
-    if let Some(ref mut stdin) = child.stdin {
-        stdin.write_all(body)?;
-    }
-    drop(child.stdin.take()); // Close stdin so curl proceeds
-
-    let status = child.wait()?;
-    if !status.success() {
-        anyhow::bail!("curl exited with status: {}", status);
-    }
-
-    Ok(())
+    cmd
 }
 
 // =============================================================================
@@ -293,6 +899,18 @@ fn create_backends(config: &LoggingConfig) -> Vec<Box<dyn LogBackend>> {
                         .collect(),
                     timeout: Duration::from_secs(*timeout_secs),
                 }),
+                BackendConfig::OtlpTraces {
+                    endpoint,
+                    headers,
+                    timeout_secs,
+                } => Box::new(OtlpTracesBackend {
+                    endpoint: endpoint.clone(),
+                    headers: headers
+                        .iter()
+                        .map(|(k, v)| (k.clone(), expand_env_vars(v)))
+                        .collect(),
+                    timeout: Duration::from_secs(*timeout_secs),
+                }),
                 BackendConfig::Datadog {
                     endpoint,
                     api_key,
@@ -313,6 +931,21 @@ fn create_backends(config: &LoggingConfig) -> Vec<Box<dyn LogBackend>> {
                     sourcetype: sourcetype.clone(),
                     timeout: Duration::from_secs(*timeout_secs),
                 }),
+                BackendConfig::Syslog {
+                    socket,
+                    address,
+                    facility,
+                } => Box::new(SyslogBackend {
+                    socket: PathBuf::from(socket),
+                    address: address.as_deref().map(expand_env_vars),
+                    facility: *facility,
+                }),
+                BackendConfig::Journald { socket } => Box::new(JournaldBackend {
+                    socket: PathBuf::from(socket),
+                }),
+                BackendConfig::Eventlog { source } => Box::new(EventlogBackend {
+                    source: source.clone(),
+                }),
             }
         })
         .collect()
@@ -324,12 +957,28 @@ fn create_backends(config: &LoggingConfig) -> Vec<Box<dyn LogBackend>> {
 
 /// JSON Lines logger with optional external backends.
 ///
-/// Always writes to the local JSON Lines file. When external backends are
-/// configured, forwards entries to each backend. Backend failures are logged
-/// as warnings but do not block local logging (fail-open).
+/// Entries are handed to a dedicated writer thread over a bounded channel,
+/// so file appends and backend requests never add to a hook's response time.
+/// The writer appends to the local JSON Lines file, flushes it whenever the
+/// queue drains (and at least every `flush_interval_ms` under sustained load),
+/// and forwards each entry to the configured backends.
+/// Backend failures are logged as warnings but do not block local logging
+/// (fail-open). When the queue is full, [`Logger::log`] waits for room rather
+/// than dropping audit entries.
+///
+/// Call [`Logger::flush`] (or drop the logger) before the process exits;
+/// [`flush_global_logger`] does this for the global logger.
 pub struct Logger {
-    writer: Mutex<BufWriter<File>>,
-    external_backends: Vec<Box<dyn LogBackend>>,
+    sender: Option<SyncSender<WriterMessage>>,
+    worker: Option<JoinHandle<()>>,
+    /// Shared with the writer thread
+    #[allow(dead_code)]
+    external_backends: Arc<Vec<Box<dyn LogBackend>>>,
+}
+
+enum WriterMessage {
+    Entry(Box<LogEntry>),
+    Flush(mpsc::Sender<()>),
 }
 
 impl Logger {
@@ -341,41 +990,62 @@ impl Logger {
 
     /// Create a new logger with external backends from configuration
     pub fn with_backends(logging_config: &LoggingConfig) -> Result<Self> {
-        let path = Self::default_log_path();
-
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let file = OpenOptions::new().create(true).append(true).open(&path)?;
-        let writer = BufWriter::new(file);
-        let external_backends = create_backends(logging_config);
-
-        Ok(Self {
-            writer: Mutex::new(writer),
-            external_backends,
-        })
+        Self::spawn(
+            logging_config.log_path(),
+            create_backends(logging_config),
+            logging_config,
+        )
     }
 
     /// Create a new logger with a custom log file path
-    #[allow(dead_code)]
     pub fn with_path<P: Into<PathBuf>>(path: P) -> Result<Self> {
-        let path = path.into();
+        Self::spawn(path.into(), Vec::new(), &LoggingConfig::default())
+    }
 
+    fn spawn(
+        path: PathBuf,
+        backends: Vec<Box<dyn LogBackend>>,
+        logging_config: &LoggingConfig,
+    ) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
+        if let Some(days) = logging_config.retention_days {
+            let key = logging_config.integrity.signing_key();
+            if let Err(e) = enforce_retention(&path, days, Utc::now(), key.as_deref()) {
+                tracing::warn!("Failed to apply log retention: {}", e);
+            }
+        }
 
         let file = OpenOptions::new().create(true).append(true).open(&path)?;
         let writer = BufWriter::new(file);
+        let external_backends = Arc::new(backends);
+        let (sender, receiver) = mpsc::sync_channel(logging_config.queue_capacity.max(1));
+        let flush_interval = Duration::from_millis(logging_config.flush_interval_ms.max(1));
+
+        let chain = logging_config
+            .integrity
+            .hash_chain
+            .then(|| LogChain::new(path, logging_config.integrity.signing_key()));
+
+        let backends = Arc::clone(&external_backends);
+        let worker = std::thread::Builder::new()
+            .name("rulez-log-writer".to_string())
+            .spawn(move || {
+                run_writer(writer, &backends, chain.as_ref(), &receiver, flush_interval);
+            })?;
 
         Ok(Self {
-            writer: Mutex::new(writer),
-            external_backends: Vec::new(),
+            sender: Some(sender),
+            worker: Some(worker),
+            external_backends,
         })
     }
 
     /// Get the default log file path (~/.claude/logs/rulez.log)
+    ///
+    /// The log this process writes may be elsewhere (`logging.path`); use
+    /// [`log_path`] to read it.
     pub fn default_log_path() -> PathBuf {
         let mut path = dirs::home_dir().expect("Could not determine home directory");
         path.push(".claude");
@@ -384,32 +1054,122 @@ impl Logger {
         path
     }
 
-    /// Log an entry to the JSON Lines file and all configured backends.
+    /// Queue an entry for the JSON Lines file and all configured backends.
     pub fn log(&self, entry: LogEntry) -> Result<()> {
-        // Always write to local JSON Lines file first
-        let json = serde_json::to_string(&entry)?;
-        let mut writer = self.writer.lock().unwrap();
-        writeln!(writer, "{}", json)?;
-        writer.flush()?;
-
-        // Forward to external backends (fail-open)
-        for backend in &self.external_backends {
-            if let Err(e) = backend.send(&entry) {
-                tracing::warn!(
-                    "External logging backend '{}' failed: {}",
-                    backend.name(),
-                    e
-                );
-            }
-        }
-
-        Ok(())
+        self.send(WriterMessage::Entry(Box::new(entry)))
     }
 
     /// Log an entry asynchronously
     pub async fn log_async(&self, entry: LogEntry) -> Result<()> {
         self.log(entry)
     }
+
+    /// Wait until every queued entry is written and flushed to disk
+    pub fn flush(&self) -> Result<()> {
+        let (ack, done) = mpsc::channel();
+        self.send(WriterMessage::Flush(ack))?;
+        done.recv()
+            .map_err(|_| anyhow::anyhow!("Log writer stopped before flushing"))
+    }
+
+    fn send(&self, message: WriterMessage) -> Result<()> {
+        self.sender
+            .as_ref()
+            .and_then(|sender| sender.send(message).ok())
+            .ok_or_else(|| anyhow::anyhow!("Log writer is not running"))
+    }
+}
+
+impl Drop for Logger {
+    fn drop(&mut self) {
+        // Closing the channel makes the writer drain, flush, and exit
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Append a hash-chained entry; the log stays locked until it is on disk
+fn write_chained(
+    writer: &mut BufWriter<File>,
+    chain: &LogChain,
+    entry: &mut LogEntry,
+) -> Result<()> {
+    let next = chain.next_link()?;
+    entry.prev_hash = Some(next.prev_hash.clone());
+    entry.signature = None;
+    let line = chain.sign(serde_json::to_string(entry)?);
+    writeln!(writer, "{}", line)?;
+    writer.flush()?;
+    chain.record(next, &line)
+}
+
+/// Writer thread: append queued entries, flushing on request, whenever the
+/// queue drains, and at least every `flush_interval` under sustained load
+fn run_writer(
+    mut writer: BufWriter<File>,
+    backends: &[Box<dyn LogBackend>],
+    chain: Option<&LogChain>,
+    receiver: &Receiver<WriterMessage>,
+    flush_interval: Duration,
+) {
+    let flush = |writer: &mut BufWriter<File>, last_flush: &mut Instant| {
+        if let Err(e) = writer.flush() {
+            tracing::warn!("Failed to flush log file: {}", e);
+        }
+        *last_flush = Instant::now();
+    };
+    let mut last_flush = Instant::now();
+
+    // Blocks until a message arrives; ends when every Logger handle is gone
+    while let Ok(first) = receiver.recv() {
+        let mut next = Some(first);
+        while let Some(message) = next {
+            match message {
+                WriterMessage::Entry(mut entry) => {
+                    write_entry(&mut writer, backends, chain, &mut entry);
+                    if last_flush.elapsed() >= flush_interval {
+                        flush(&mut writer, &mut last_flush);
+                    }
+                }
+                WriterMessage::Flush(ack) => {
+                    flush(&mut writer, &mut last_flush);
+                    let _ = ack.send(());
+                }
+            }
+            next = receiver.try_recv().ok();
+        }
+        flush(&mut writer, &mut last_flush);
+    }
+}
+
+fn write_entry(
+    writer: &mut BufWriter<File>,
+    backends: &[Box<dyn LogBackend>],
+    chain: Option<&LogChain>,
+    entry: &mut LogEntry,
+) {
+    let written = match chain {
+        Some(chain) => write_chained(writer, chain, entry),
+        None => serde_json::to_string(entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(writeln!(writer, "{}", json)?)),
+    };
+    if let Err(e) = written {
+        tracing::warn!("Failed to write log entry: {:#}", e);
+    }
+
+    // Forward to external backends (fail-open)
+    for backend in backends {
+        if let Err(e) = backend.send(entry) {
+            tracing::warn!(
+                "External logging backend '{}' failed: {}",
+                backend.name(),
+                e
+            );
+        }
+    }
 }
 
 // =============================================================================
@@ -422,15 +1182,14 @@ pub struct LogQuery {
 }
 
 impl LogQuery {
-    /// Create a new log query for the default log file
+    /// Create a new log query for this process's log file (see [`log_path`])
     pub fn new() -> Self {
         Self {
-            log_path: Logger::default_log_path(),
+            log_path: log_path(),
         }
     }
 
     /// Create a new log query for a custom log file
-    #[allow(dead_code)]
     pub fn with_path<P: Into<PathBuf>>(path: P) -> Self {
         Self {
             log_path: path.into(),
@@ -457,7 +1216,7 @@ impl LogQuery {
             }
         }
 
-        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        entries.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
 
         if let Some(limit) = filters.limit {
             entries.truncate(limit);
@@ -533,6 +1292,18 @@ use std::sync::OnceLock;
 
 static GLOBAL_LOGGER: OnceLock<Logger> = OnceLock::new();
 
+/// The configured log file, once [`init_global_logger_with_config`] has run
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// The log file this process writes to: `logging.path` from the loaded
+/// config, or ~/.claude/logs/rulez.log
+pub fn log_path() -> PathBuf {
+    LOG_PATH
+        .get()
+        .cloned()
+        .unwrap_or_else(Logger::default_log_path)
+}
+
 /// Initialize the global logger (no external backends)
 #[allow(dead_code)]
 pub fn init_global_logger() -> Result<()> {
@@ -543,13 +1314,10 @@ pub fn init_global_logger() -> Result<()> {
     Ok(())
 }
 
-/// Initialize the global logger with external backends from config.
+/// Initialize the global logger with external backends and options from config.
 pub fn init_global_logger_with_config(logging_config: &LoggingConfig) -> Result<()> {
-    let logger = if logging_config.backends.is_empty() {
-        Logger::new()?
-    } else {
-        Logger::with_backends(logging_config)?
-    };
+    let _ = LOG_PATH.set(logging_config.log_path());
+    let logger = Logger::with_backends(logging_config)?;
     GLOBAL_LOGGER
         .set(logger)
         .map_err(|_| anyhow::anyhow!("Logger already initialized"))?;
@@ -569,6 +1337,237 @@ pub async fn log_entry(entry: LogEntry) -> Result<()> {
     Ok(())
 }
 
+/// Write out everything queued on the global logger
+///
+/// Must run before the process exits, including `std::process::exit` paths;
+/// the global logger is never dropped.
+pub fn flush_global_logger() {
+    if let Some(logger) = global_logger() {
+        if let Err(e) = logger.flush() {
+            tracing::warn!("Failed to flush log: {}", e);
+        }
+    }
+}
+
+/// Flushes the global logger when dropped, e.g. at the end of `main`
+pub struct FlushOnDrop;
+
+impl Drop for FlushOnDrop {
+    fn drop(&mut self) {
+        flush_global_logger();
+    }
+}
+
+// =============================================================================
+// Log Schema Upgrade
+// =============================================================================
+
+/// Counts from rewriting a log file to the current schema
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UpgradeSummary {
+    /// Entries migrated from an older schema version
+    pub upgraded: usize,
+    /// Entries already at the current (or a newer) schema version
+    pub current: usize,
+    /// Lines that could not be read as log entries, kept unchanged
+    pub unreadable: usize,
+}
+
+/// Rewrite older entries in the log file at `path` to [`LOG_SCHEMA_VERSION`]
+///
+/// Lines that aren't valid entries are kept as they are. The file is only
+/// replaced (atomically) when at least one entry was upgraded.
+pub fn upgrade_log_file(path: &Path) -> Result<UpgradeSummary> {
+    let mut summary = UpgradeSummary::default();
+    if !path.exists() {
+        return Ok(summary);
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let mut output = String::with_capacity(content.len());
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        match upgrade_log_line(line) {
+            Ok(Some(upgraded)) => {
+                summary.upgraded += 1;
+                output.push_str(&upgraded);
+            }
+            Ok(None) => {
+                summary.current += 1;
+                output.push_str(line);
+            }
+            Err(_) => {
+                summary.unreadable += 1;
+                output.push_str(line);
+            }
+        }
+        output.push('\n');
+    }
+
+    if summary.upgraded > 0 {
+        let tmp = path.with_extension("upgrade.tmp");
+        std::fs::write(&tmp, output)?;
+        std::fs::rename(&tmp, path)?;
+    }
+    Ok(summary)
+}
+
+/// Upgrade one log line, or `None` if it is already current
+///
+/// Schema 1 -> 2 adds `schema_version` and writes `outcome` in lowercase
+/// (the capitalized spelling is accepted on read).
+fn upgrade_log_line(line: &str) -> Result<Option<String>> {
+    let value: serde_json::Value = serde_json::from_str(line)?;
+    let version = value
+        .get("schema_version")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(1);
+    if version >= u64::from(LOG_SCHEMA_VERSION) {
+        return Ok(None);
+    }
+
+    let entry: LogEntry = serde_json::from_value(value)?;
+    let entry = LogEntry {
+        schema_version: LOG_SCHEMA_VERSION,
+        ..entry
+    };
+    Ok(Some(serde_json::to_string(&entry)?))
+}
+
+// =============================================================================
+// Retention
+// =============================================================================
+
+/// How far past the retention cutoff the oldest entry may be before the log
+/// is rewritten, so retention rewrites the file about once a day rather than
+/// on every event
+const RETENTION_SLACK_DAYS: i64 = 1;
+
+/// Result of purging old entries from a log file
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PurgeSummary {
+    /// Entries removed (or that would be, on a dry run)
+    pub removed: usize,
+    /// Entries left in the file
+    pub kept: usize,
+    pub bytes_removed: u64,
+}
+
+/// Remove entries older than `before` from the start of a log file
+///
+/// The log is append-only, so old entries form a prefix of the file. Lines
+/// are removed through the last entry older than `before` that comes ahead
+/// of the first newer one; an older entry after a newer one (clock skew)
+/// is kept. Removing a prefix leaves a hash chain verifiable: the chain's
+/// checkpoint is moved to the first remaining entry, signed with `key` if
+/// the checkpoint is signed.
+///
+/// The file is rewritten in place under the chain lock, so writers holding
+/// it open keep appending to the same file.
+pub fn purge_log_file(
+    path: &Path,
+    before: DateTime<Utc>,
+    dry_run: bool,
+    key: Option<&[u8]>,
+) -> Result<PurgeSummary> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if !path.exists() {
+        return Ok(PurgeSummary::default());
+    }
+    let _lock = (!dry_run)
+        .then(|| crate::log_chain::lock_log(path))
+        .transpose()?;
+    let mut file = OpenOptions::new().read(true).write(!dry_run).open(path)?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+
+    let (removed, offset) = purge_prefix(&content, before);
+    let summary = PurgeSummary {
+        removed,
+        kept: content[offset..]
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .count(),
+        bytes_removed: offset as u64,
+    };
+    if dry_run || removed == 0 {
+        return Ok(summary);
+    }
+    let head = crate::log_chain::purged_head(path, &content[..offset], &content[offset..], key)?;
+
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&content[offset..])?;
+    file.set_len((content.len() - offset) as u64)?;
+    file.sync_all()?;
+    if let Some(head) = head {
+        crate::log_chain::store_head(path, &head, key)?;
+    }
+    Ok(summary)
+}
+
+/// Entries to remove and the byte offset the kept part starts at
+fn purge_prefix(content: &[u8], before: DateTime<Utc>) -> (usize, usize) {
+    let mut removed = 0;
+    let mut lines = 0;
+    let mut offset = 0;
+    let mut start = 0;
+    for line in content.split_inclusive(|&b| b == b'\n') {
+        start += line.len();
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        lines += 1;
+        match entry_timestamp(line) {
+            Some(timestamp) if timestamp >= before => break,
+            Some(_) => {
+                removed = lines;
+                offset = start;
+            }
+            None => {}
+        }
+    }
+    (removed, offset)
+}
+
+fn entry_timestamp(line: &[u8]) -> Option<DateTime<Utc>> {
+    #[derive(Deserialize)]
+    struct Timestamp {
+        timestamp: DateTime<Utc>,
+    }
+    serde_json::from_slice::<Timestamp>(line)
+        .ok()
+        .map(|t| t.timestamp)
+}
+
+/// Apply `logging.retention_days` if the oldest entry is well past it
+///
+/// Only the first line is read unless a purge is due. Returns what was
+/// purged, if anything.
+pub fn enforce_retention(
+    path: &Path,
+    retention_days: u32,
+    now: DateTime<Utc>,
+    key: Option<&[u8]>,
+) -> Result<Option<PurgeSummary>> {
+    use std::io::BufRead;
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut first = Vec::new();
+    std::io::BufReader::new(file).read_until(b'\n', &mut first)?;
+
+    let cutoff = now - chrono::Duration::days(i64::from(retention_days));
+    let due = entry_timestamp(&first)
+        .is_some_and(|oldest| oldest < cutoff - chrono::Duration::days(RETENTION_SLACK_DAYS));
+    if !due {
+        return Ok(None);
+    }
+    purge_log_file(path, cutoff, false, key).map(Some)
+}
+
 // =============================================================================
 // Log Rotation
 // =============================================================================
@@ -635,6 +1634,7 @@ mod tests {
         let logger = Logger::with_path(temp_file.path()).unwrap();
 
         let entry = LogEntry {
+            schema_version: LOG_SCHEMA_VERSION,
             timestamp: Utc::now(),
             event_type: "PreToolUse".to_string(),
             session_id: "test-session".to_string(),
@@ -644,10 +1644,13 @@ mod tests {
             timing: LogTiming {
                 processing_ms: 5,
                 rules_evaluated: 3,
+                over_budget: None,
             },
             metadata: Some(LogMetadata {
                 injected_files: None,
                 validator_output: Some("blocked by policy".to_string()),
+                context_tokens: None,
+                context_blocks: None,
             }),
             event_details: None,
             response: None,
@@ -656,11 +1659,25 @@ mod tests {
             mode: None,
             priority: None,
             decision: None,
+            bypass_reason: None,
+            dry_run: None,
             governance: None,
             trust_level: None,
+            session_summary: None,
+            tool_use_id: None,
+            pre_tool_use: None,
+            rule_timings: Vec::new(),
+            sample_rate: None,
+            prev_hash: None,
+            signature: None,
+            anomaly: None,
+            waivers: None,
+            profile: None,
+            deprecations: None,
         };
 
         logger.log_async(entry.clone()).await.unwrap();
+        logger.flush().unwrap();
 
         let query = LogQuery::with_path(temp_file.path());
         let filters = QueryFilters {
@@ -671,6 +1688,184 @@ mod tests {
         let entries = query.query(filters).unwrap();
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].session_id, "test-session");
+        assert_eq!(entries[0].schema_version, LOG_SCHEMA_VERSION);
+    }
+
+    fn dated_line(timestamp: &str, session: &str) -> String {
+        format!(
+            r#"{{"schema_version":2,"timestamp":"{timestamp}","event_type":"PreToolUse","session_id":"{session}","rules_matched":[],"outcome":"allow","timing":{{"processing_ms":1,"rules_evaluated":1}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_purge_removes_old_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let lines = [
+            dated_line("2026-01-01T00:00:00Z", "a"),
+            "not json".to_string(),
+            dated_line("2026-02-01T00:00:00Z", "b"),
+            dated_line("2026-04-01T00:00:00Z", "c"),
+            // Clock skew: older than the cutoff but after a newer entry
+            dated_line("2026-02-15T00:00:00Z", "d"),
+        ];
+        let original = format!("{}\n", lines.join("\n"));
+        std::fs::write(&path, &original).unwrap();
+        let before = "2026-03-01T00:00:00Z".parse().unwrap();
+
+        let dry = purge_log_file(&path, before, true, None).unwrap();
+        assert_eq!((dry.removed, dry.kept), (3, 2));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+
+        let summary = purge_log_file(&path, before, false, None).unwrap();
+        assert_eq!(summary, dry);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content, format!("{}\n{}\n", lines[3], lines[4]));
+        assert_eq!(
+            summary.bytes_removed as usize,
+            original.len() - content.len()
+        );
+
+        // Nothing older left at the start
+        assert_eq!(
+            purge_log_file(&path, before, false, None).unwrap().removed,
+            0
+        );
+    }
+
+    #[test]
+    fn test_purge_keeps_chain_verifiable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let config = LoggingConfig {
+            integrity: LogIntegrity {
+                hash_chain: true,
+                signing_key_env: None,
+            },
+            ..LoggingConfig::default()
+        };
+        let logger = Logger::spawn(path.clone(), Vec::new(), &config).unwrap();
+        for (i, day) in ["2026-01-01", "2026-02-01", "2026-04-01"]
+            .iter()
+            .enumerate()
+        {
+            let mut entry: LogEntry =
+                serde_json::from_str(&dated_line(&format!("{day}T00:00:00Z"), &i.to_string()))
+                    .unwrap();
+            entry.schema_version = LOG_SCHEMA_VERSION;
+            logger.log(entry).unwrap();
+        }
+        logger.flush().unwrap();
+
+        let summary =
+            purge_log_file(&path, "2026-03-01T00:00:00Z".parse().unwrap(), false, None).unwrap();
+        assert_eq!(summary.removed, 2);
+        let report = crate::log_chain::verify_log_file(&path, None).unwrap();
+        assert_eq!(report.chained, 1);
+        assert!(report.is_intact(), "{:?}", report.problems);
+
+        // The open writer keeps appending to the purged file
+        let entry: LogEntry =
+            serde_json::from_str(&dated_line("2026-04-02T00:00:00Z", "late")).unwrap();
+        logger.log(entry).unwrap();
+        logger.flush().unwrap();
+        let report = crate::log_chain::verify_log_file(&path, None).unwrap();
+        assert_eq!(report.chained, 2);
+        assert!(report.is_intact(), "{:?}", report.problems);
+    }
+
+    #[test]
+    fn test_enforce_retention_waits_for_slack() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let now: DateTime<Utc> = "2026-10-15T00:00:00Z".parse().unwrap();
+        assert!(enforce_retention(&path, 30, now, None).unwrap().is_none());
+
+        std::fs::write(
+            &path,
+            format!(
+                "{}\n{}\n",
+                dated_line("2026-09-15T00:00:00Z", "a"),
+                dated_line("2026-10-14T00:00:00Z", "b")
+            ),
+        )
+        .unwrap();
+        // Oldest entry is exactly 30 days old: within the slack
+        assert!(enforce_retention(&path, 30, now, None).unwrap().is_none());
+
+        let later = now + chrono::Duration::days(2);
+        let summary = enforce_retention(&path, 30, later, None).unwrap().unwrap();
+        assert_eq!((summary.removed, summary.kept), (1, 1));
+    }
+
+    #[test]
+    fn test_upgrade_log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let legacy = r#"{"timestamp":"2025-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"old","rules_matched":[],"outcome":"Block","timing":{"processing_ms":1,"rules_evaluated":1}}"#;
+        let current = r#"{"schema_version":2,"timestamp":"2026-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"new","rules_matched":[],"outcome":"allow","timing":{"processing_ms":1,"rules_evaluated":1}}"#;
+        std::fs::write(&path, format!("{legacy}\n\nnot json\n{current}\n")).unwrap();
+
+        // Legacy entries are readable before the upgrade
+        let old: LogEntry = serde_json::from_str(legacy).unwrap();
+        assert_eq!(old.schema_version, 1);
+        assert_eq!(old.outcome, crate::models::Outcome::Block);
+
+        let summary = upgrade_log_file(&path).unwrap();
+        assert_eq!(
+            summary,
+            UpgradeSummary {
+                upgraded: 1,
+                current: 1,
+                unreadable: 1,
+            }
+        );
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(
+            lines[0].starts_with(r#"{"schema_version":2,"#),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[0].contains(r#""outcome":"block""#));
+        assert_eq!(lines[1], "not json");
+        assert_eq!(lines[2], current);
+
+        // Nothing left to upgrade; the file is untouched
+        assert_eq!(upgrade_log_file(&path).unwrap().upgraded, 0);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(
+            upgrade_log_file(&dir.path().join("missing.log")).unwrap(),
+            UpgradeSummary::default()
+        );
+    }
+
+    #[test]
+    fn test_log_path_in_resolves_against_project() {
+        let config = LoggingConfig {
+            path: Some(".claude/logs/rulez.log".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.log_path_in(Path::new("/work/app")),
+            PathBuf::from("/work/app/.claude/logs/rulez.log")
+        );
+
+        let config = LoggingConfig {
+            path: Some("/var/log/rulez.log".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.log_path_in(Path::new("/work/app")),
+            PathBuf::from("/var/log/rulez.log")
+        );
+
+        assert_eq!(
+            LoggingConfig::default().log_path_in(Path::new("/work/app")),
+            Logger::default_log_path()
+        );
     }
 
     #[test]
@@ -728,6 +1923,161 @@ backends:
         assert_eq!(config.backends.len(), 3);
     }
 
+    #[test]
+    fn test_otlp_trace_payload() {
+        let config: LoggingConfig = serde_yaml::from_str(
+            "backends:\n  - type: otlp_traces\n    endpoint: \"http://localhost:4318/v1/traces\"\n",
+        )
+        .unwrap();
+        assert_eq!(create_backends(&config)[0].name(), "otlp_traces");
+
+        let entry: LogEntry = serde_json::from_value(serde_json::json!({
+            "timestamp": "2026-01-01T00:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": "trace-session",
+            "tool_name": "Bash",
+            "rules_matched": ["block-rm"],
+            "outcome": "block",
+            "timing": {"processing_ms": 3, "rules_evaluated": 2}
+        }))
+        .unwrap();
+        let entry = LogEntry {
+            rule_timings: vec![
+                crate::models::RuleTiming {
+                    rule: "block-rm".to_string(),
+                    offset_us: 100,
+                    duration_us: 250,
+                },
+                crate::models::RuleTiming {
+                    rule: "audit-bash".to_string(),
+                    offset_us: 400,
+                    duration_us: 50,
+                },
+            ],
+            ..entry
+        };
+
+        let payload = otlp_trace_payload(&entry);
+        let spans = payload["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+        assert_eq!(spans.len(), 3);
+
+        let root = &spans[0];
+        let start: i64 = 1_767_225_600_000_000_000;
+        assert_eq!(root["name"], "rulez.evaluate");
+        assert_eq!(root["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(root["spanId"].as_str().unwrap().len(), 16);
+        assert_eq!(root["startTimeUnixNano"], start.to_string());
+        assert_eq!(root["endTimeUnixNano"], (start + 3_000_000).to_string());
+        assert!(root["attributes"].as_array().unwrap().contains(
+            &serde_json::json!({"key": "rulez.outcome", "value": {"stringValue": "Block"}})
+        ));
+
+        let rule = &spans[1];
+        assert_eq!(rule["traceId"], root["traceId"]);
+        assert_eq!(rule["parentSpanId"], root["spanId"]);
+        assert_eq!(rule["startTimeUnixNano"], (start + 100_000).to_string());
+        assert_eq!(rule["endTimeUnixNano"], (start + 350_000).to_string());
+        assert_eq!(rule["attributes"][0]["value"]["stringValue"], "block-rm");
+        assert_eq!(rule["attributes"][1]["value"]["boolValue"], true);
+        assert_eq!(spans[2]["attributes"][1]["value"]["boolValue"], false);
+    }
+
+    fn system_log_entry() -> LogEntry {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": "2026-01-01T00:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": "syslog-session",
+            "tool_name": "Bash",
+            "rules_matched": ["block-rm"],
+            "outcome": "block",
+            "timing": {"processing_ms": 3, "rules_evaluated": 2}
+        }))
+        .unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_syslog_backend_local_and_udp() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("log.sock");
+        let receiver = std::os::unix::net::UnixDatagram::bind(&socket).unwrap();
+        let mut buf = [0u8; 8192];
+
+        let config: LoggingConfig = serde_yaml::from_str(&format!(
+            "backends:\n  - type: syslog\n    socket: {}\n    facility: local0\n",
+            socket.display()
+        ))
+        .unwrap();
+        let backends = create_backends(&config);
+        assert_eq!(backends[0].name(), "syslog");
+        backends[0].send(&system_log_entry()).unwrap();
+
+        let len = receiver.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        // local0 (16) * 8 + warning (4) for a block
+        assert!(message.starts_with("<132>"), "{message}");
+        let json = message.split_once(": ").unwrap().1;
+        let logged: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(logged["session_id"], "syslog-session");
+
+        // Remote servers get the hostname and a UDP datagram
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let backend = SyslogBackend {
+            socket: socket.clone(),
+            address: Some(server.local_addr().unwrap().to_string()),
+            facility: SyslogFacility::User,
+        };
+        let entry = LogEntry {
+            outcome: crate::models::Outcome::Allow,
+            ..system_log_entry()
+        };
+        backend.send(&entry).unwrap();
+        let len = server.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<14>"), "{message}");
+        assert!(message.contains(&format!(" {} rulez[", hostname())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_journald_backend_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("journal.sock");
+        let receiver = std::os::unix::net::UnixDatagram::bind(&socket).unwrap();
+
+        let config: LoggingConfig = serde_yaml::from_str(&format!(
+            "backends:\n  - type: journald\n    socket: {}\n",
+            socket.display()
+        ))
+        .unwrap();
+        create_backends(&config)[0]
+            .send(&system_log_entry())
+            .unwrap();
+
+        let mut buf = [0u8; 8192];
+        let len = receiver.recv(&mut buf).unwrap();
+        let payload = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(payload.contains(
+            "MESSAGE=PreToolUse Bash block rules=[block-rm] session=syslog-session 3ms\n"
+        ));
+        assert!(payload.contains("PRIORITY=4\n"));
+        assert!(payload.contains("SYSLOG_IDENTIFIER=rulez\n"));
+        assert!(payload.contains("RULEZ_OUTCOME=block\n"));
+        assert!(payload.contains("RULEZ_TOOL_NAME=Bash\n"));
+        assert!(payload.contains("RULEZ_ENTRY={"));
+    }
+
+    #[test]
+    fn test_journal_payload_binary_values() {
+        let payload = journal_payload(&[("A", "one".to_string()), ("B", "two\nlines".to_string())]);
+        let mut expected = b"A=one\nB\n".to_vec();
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        expected.extend_from_slice(b"two\nlines\n");
+        assert_eq!(payload, expected);
+    }
+
     #[test]
     fn test_create_backends() {
         let config = LoggingConfig {
@@ -736,6 +2086,7 @@ backends:
                 headers: HashMap::new(),
                 timeout_secs: 5,
             }],
+            ..Default::default()
         };
         let backends = create_backends(&config);
         assert_eq!(backends.len(), 1);
@@ -746,6 +2097,84 @@ backends:
     fn test_default_logging_config() {
         let config = LoggingConfig::default();
         assert!(config.backends.is_empty());
+        assert_eq!(config.flush_interval_ms, 1000);
+        assert_eq!(config.queue_capacity, 1024);
+    }
+
+    fn sample_entry(session_id: &str) -> LogEntry {
+        serde_json::from_value(serde_json::json!({
+            "schema_version": LOG_SCHEMA_VERSION,
+            "timestamp": "2026-10-01T12:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": session_id,
+            "rules_matched": [],
+            "outcome": "allow",
+            "timing": {"processing_ms": 1, "rules_evaluated": 1},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_log_sampling() {
+        let sampling = LogSampling {
+            allow: u32::MAX,
+            audit: 7,
+        };
+
+        let mut allowed = sample_entry("allowed");
+        assert_eq!(sampling.rate_for(&allowed), u32::MAX);
+        assert!(!sampling.sample(&mut allowed));
+        assert_eq!(allowed.sample_rate, None);
+
+        let mut audited = sample_entry("audited");
+        audited.decision = Some(Decision::Audited);
+        assert_eq!(sampling.rate_for(&audited), 7);
+
+        let mut blocked = sample_entry("blocked");
+        blocked.outcome = Outcome::Block;
+        let mut warned = sample_entry("warned");
+        warned.outcome = Outcome::Inject;
+        warned.decision = Some(Decision::Warned);
+        for entry in [&mut blocked, &mut warned] {
+            assert_eq!(sampling.rate_for(entry), 1);
+            assert!(sampling.sample(entry));
+            assert_eq!(entry.sample_rate, None);
+        }
+
+        // Rates of 0 or 1 keep everything
+        let keep_all = LogSampling { allow: 0, audit: 1 };
+        assert!(keep_all.sample(&mut allowed));
+        assert!(keep_all.sample(&mut audited));
+    }
+
+    #[test]
+    fn test_logger_writes_in_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rulez.log");
+        let config = LoggingConfig {
+            flush_interval_ms: 10,
+            queue_capacity: 1,
+            ..Default::default()
+        };
+        let logger = Logger::spawn(path.clone(), Vec::new(), &config).unwrap();
+
+        // A full queue waits for the writer instead of dropping entries
+        for i in 0..5 {
+            logger.log(sample_entry(&format!("s{i}"))).unwrap();
+        }
+
+        // Entries become visible once the queue drains, without an explicit flush
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while std::fs::read_to_string(&path).unwrap().lines().count() < 5 {
+            assert!(Instant::now() < deadline, "entries were never flushed");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        logger.log(sample_entry("last")).unwrap();
+        drop(logger);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 6);
+        assert!(content.lines().last().unwrap().contains("\"last\""));
     }
 
     #[test]
@@ -763,6 +2192,7 @@ backends:
                 headers: HashMap::new(),
                 timeout_secs: 5,
             }],
+            ..Default::default()
         };
         let logger = Logger::with_backends(&config).unwrap();
         assert_eq!(logger.external_backends.len(), 1);
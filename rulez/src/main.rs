@@ -3,15 +3,44 @@ use clap::{Parser, Subcommand};
 use std::io::{self, Read};
 use tracing::{error, info};
 
+use adapters::FormatAdapter;
+
+mod actions;
 mod adapters;
+mod anomaly;
+mod audit_bundle;
 mod cli;
+mod command_allowlist;
 mod config;
+mod config_edit;
+mod context_budget;
+mod digest;
+mod error;
+mod governance;
 mod hooks;
+mod identity;
+mod kill_switch;
+mod limits;
+mod log_chain;
+mod log_export;
 mod logging;
+mod matchers;
 mod models;
 mod opencode;
+mod packs;
+mod pii;
+mod profiles;
+mod protection;
+mod protocol;
 mod schema;
+mod scripting;
+mod secrets;
+mod session;
 mod skills;
+mod sync;
+mod ticketing;
+mod transcript;
+mod waivers;
 
 #[derive(Parser)]
 #[command(name = "rulez")]
@@ -22,10 +51,42 @@ struct Cli {
     #[arg(long, global = true)]
     debug_logs: bool,
 
+    /// Read newline-delimited events from stdin and print one JSON response per line
+    #[arg(long)]
+    batch: bool,
+
+    /// Speak the versioned streaming protocol (v2) on stdin/stdout
+    #[arg(long, conflicts_with = "batch")]
+    stream: bool,
+
+    /// Evaluate and log hook events normally, but always allow (or RULEZ_DRY_RUN=1)
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Agent that sent the hook event on stdin: claude, copilot, cursor, gemini, opencode (auto-detected if not specified)
+    #[arg(long, value_name = "AGENT", value_parser = parse_agent)]
+    agent: Option<&'static dyn adapters::FormatAdapter>,
+
+    /// Forward the hook event to a `rulez daemon` on this socket (or RULEZ_SOCKET)
+    #[arg(long, value_name = "PATH")]
+    socket: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+/// Resolve `--agent` to a built-in format adapter
+fn parse_agent(name: &str) -> Result<&'static dyn adapters::FormatAdapter, String> {
+    adapters::find(name).ok_or_else(|| {
+        let names: Vec<_> = adapters::ADAPTERS.iter().map(|a| a.name()).collect();
+        format!(
+            "unknown agent '{}' (expected one of: {})",
+            name,
+            names.join(", ")
+        )
+    })
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize RuleZ configuration in current project
@@ -85,6 +146,8 @@ enum Commands {
     },
     /// Query and display logs
     Logs {
+        #[command(subcommand)]
+        subcommand: Option<LogsSubcommand>,
         /// Number of recent log entries to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
@@ -94,10 +157,131 @@ enum Commands {
         /// Filter by policy mode (enforce, warn, audit)
         #[arg(long)]
         mode: Option<String>,
-        /// Filter by decision (allowed, blocked, warned, audited)
+        /// Filter by decision (allowed, blocked, warned, audited, bypassed)
         #[arg(long)]
         decision: Option<String>,
     },
+    /// Show rule hit statistics from the audit log
+    Stats {
+        /// Break statistics down per rule (matches, block rate, latency, last fired)
+        #[arg(long)]
+        per_rule: bool,
+        /// Report format for --per-rule
+        #[arg(long, value_enum, default_value_t = cli::stats::StatsFormat::Text)]
+        format: cli::stats::StatsFormat,
+        /// Only entries since this time (RFC3339, or an age like 30d, 12h)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries until this time (RFC3339, or an age like 1d)
+        #[arg(long)]
+        until: Option<String>,
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Log file to read (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Summarize decisions for a period and post or save the digest
+    Digest {
+        /// Period to summarize, ending now
+        #[arg(long, value_enum, default_value_t = digest::DigestPeriod::Daily)]
+        period: digest::DigestPeriod,
+        /// Start of the window instead of one period ago (RFC3339, or an age like 3d)
+        #[arg(long)]
+        since: Option<String>,
+        /// POST the digest to this URL (defaults to settings.digest.webhook)
+        #[arg(long)]
+        webhook: Option<String>,
+        /// Write the markdown to this file, or - for stdout (defaults to settings.digest.output)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Print JSON instead of markdown to stdout
+        #[arg(long)]
+        json: bool,
+        /// Log file to read (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Report policy events for review (use 'rulez report --help' for subcommands)
+    Report {
+        #[command(subcommand)]
+        subcommand: ReportSubcommand,
+    },
+    /// Verify the tamper-evident hash chain of the audit log
+    VerifyLogs {
+        /// Log file to verify (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+        /// Environment variable holding the signing key (defaults to
+        /// logging.integrity.signing_key_env)
+        #[arg(long)]
+        key_env: Option<String>,
+    },
+    /// Export or verify signed compliance evidence bundles
+    Audit {
+        #[command(subcommand)]
+        subcommand: AuditSubcommand,
+    },
+    /// Fetch central policies into the overlay directory (use 'rulez sync sign' to publish)
+    Sync {
+        #[command(subcommand)]
+        subcommand: Option<SyncSubcommand>,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Sync from this source instead of settings.sync.source
+        #[arg(long)]
+        source: Option<String>,
+        /// Report what would change without installing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rule governance reports (use 'rulez governance --help' for subcommands)
+    Governance {
+        #[command(subcommand)]
+        subcommand: GovernanceSubcommand,
+    },
+    /// Browse, install and remove built-in rule packs
+    Packs {
+        #[command(subcommand)]
+        subcommand: PacksSubcommand,
+    },
+    /// Edit rules in place, keeping comments and formatting
+    Rule {
+        #[command(subcommand)]
+        subcommand: RuleSubcommand,
+    },
+    /// Turn RuleZ off: allow every event, but log each one as bypassed
+    Disable {
+        /// Disable every rule (single rules: 'rulez rule disable')
+        #[arg(long, required = true)]
+        all: bool,
+        /// Turn RuleZ back on after this long (e.g. 30m, 2h, 1d)
+        #[arg(long)]
+        ttl: Option<String>,
+        /// Why RuleZ is disabled, recorded on every bypassed event
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Turn RuleZ back on after 'rulez disable --all'
+    Enable {
+        /// Enable every rule again
+        #[arg(long, required = true)]
+        all: bool,
+    },
+    /// Print editor completion data as JSON (tools, rule names, config keys)
+    CompletionData {
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Audit log to read observed tool names from (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        log: Option<std::path::PathBuf>,
+    },
     /// Explain rules or events (use 'rulez explain --help' for subcommands)
     Explain {
         #[command(subcommand)]
@@ -128,12 +312,19 @@ enum Commands {
         /// Show detailed output for each test case
         #[arg(short, long)]
         verbose: bool,
+        /// Output results as JSON
+        #[arg(long)]
+        json: bool,
     },
-    /// Check for and install newer rulez binary releases
-    Upgrade {
+    /// Check for and install newer rulez binary releases (signature-verified)
+    #[command(name = "self-update", visible_alias = "upgrade")]
+    SelfUpdate {
         /// Only check for updates, do not install
         #[arg(long)]
         check: bool,
+        /// Report current and latest versions as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Analyze rule quality and detect issues
     Lint {
@@ -143,6 +334,39 @@ enum Commands {
         /// Show detailed analysis
         #[arg(short, long)]
         verbose: bool,
+        /// Also flag rules that never matched in the audit log
+        #[arg(long)]
+        with_logs: bool,
+        /// Log window for --with-logs (RFC3339, or an age like 30d, 12h)
+        #[arg(long, requires = "with_logs", default_value = cli::lint::DEFAULT_LOG_WINDOW)]
+        since: String,
+        /// Log file for --with-logs (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long, requires = "with_logs")]
+        log_path: Option<std::path::PathBuf>,
+    },
+    /// Remove deprecated rules past their remove_after date
+    Migrate {
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Show what would be removed without changing the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Serve hook evaluations over a Unix domain socket with warm caches
+    Daemon {
+        /// Socket path (default: ~/.claude/rulez.sock)
+        #[arg(long, value_name = "PATH")]
+        socket: Option<std::path::PathBuf>,
+        /// Also serve the JSON HTTP API on this loopback address (e.g. 127.0.0.1:7878)
+        #[arg(long, value_name = "ADDR")]
+        http: Option<std::net::SocketAddr>,
+        /// Send a digest on this schedule to the settings.digest destinations
+        #[arg(long, value_enum, value_name = "PERIOD")]
+        digest: Option<digest::DigestPeriod>,
+        /// Sync central policies every settings.sync.interval_hours
+        #[arg(long)]
+        sync: bool,
     },
     /// Manage skills across AI coding runtimes
     Skills {
@@ -151,6 +375,240 @@ enum Commands {
     },
 }
 
+/// Subcommands for the report command
+#[derive(Subcommand)]
+enum ReportSubcommand {
+    /// Map blocked and warned events to files as a PR comment or CI annotations
+    Pr {
+        /// Only events from this session (repeatable; defaults to all sessions)
+        #[arg(long = "session", value_name = "ID")]
+        sessions: Vec<String>,
+        /// Only events since this time (RFC3339, or an age like 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only events until this time (RFC3339, or an age like 1d)
+        #[arg(long)]
+        until: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = cli::report::ReportFormat::Markdown)]
+        format: cli::report::ReportFormat,
+        /// Repository root that file paths are made relative to (defaults to the current directory)
+        #[arg(long)]
+        root: Option<std::path::PathBuf>,
+        /// Post the markdown report as a comment on this PR (uses GITHUB_TOKEN and GITHUB_REPOSITORY)
+        #[arg(long, value_name = "NUMBER")]
+        comment: Option<u64>,
+        /// Exit with status 1 if any event was blocked
+        #[arg(long)]
+        fail_on_block: bool,
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Log file to read (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+}
+
+/// Subcommands for the audit command
+#[derive(Subcommand)]
+enum AuditSubcommand {
+    /// Write a signed archive of config, decision summaries, and records for a period
+    Export {
+        /// Start of the period: a quarter (Q3, 2026-Q3), RFC3339, or an age like 90d
+        #[arg(long)]
+        since: Option<String>,
+        /// End of the period (defaults to the end of the quarter, or now)
+        #[arg(long)]
+        until: Option<String>,
+        /// Archive to write (defaults to rulez-audit-<from>-<to>.tar)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Log file to read (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+        /// Environment variable holding the signing key (defaults to
+        /// logging.integrity.signing_key_env)
+        #[arg(long)]
+        key_env: Option<String>,
+    },
+    /// Check a bundle's file hashes and signature
+    Verify {
+        /// Bundle archive to verify
+        bundle: std::path::PathBuf,
+        /// Environment variable holding the signing key (defaults to
+        /// logging.integrity.signing_key_env)
+        #[arg(long)]
+        key_env: Option<String>,
+    },
+}
+
+/// Subcommands for the sync command
+#[derive(Subcommand)]
+enum SyncSubcommand {
+    /// Write a manifest and signature for a directory of policy files
+    Sign {
+        /// Directory of *.yaml rule lists
+        dir: std::path::PathBuf,
+        /// Write a bundle tar here instead of adding the manifest to the directory
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Environment variable holding the hex-encoded ed25519 private key
+        #[arg(long)]
+        key_env: Option<String>,
+    },
+}
+
+/// Subcommands for the governance command
+#[derive(Subcommand)]
+enum GovernanceSubcommand {
+    /// List each rule's last review date and flag rules overdue for review
+    Report {
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List every rule with its mode, priority, owner, confidence, tags and review date
+    List {
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Only rules with this governance tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Subcommands for the packs command
+#[derive(Subcommand)]
+enum PacksSubcommand {
+    /// List the built-in packs and whether the config enables them
+    List {
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Enable a pack in settings.builtin_packs
+    Install {
+        /// Pack name
+        name: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Disable a pack in settings.builtin_packs
+    Remove {
+        /// Pack name
+        name: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+}
+
+/// Subcommands for the rule command
+#[derive(Subcommand)]
+enum RuleSubcommand {
+    /// Enable a rule (metadata.enabled: true)
+    Enable {
+        /// Rule name
+        name: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Disable a rule (metadata.enabled: false)
+    Disable {
+        /// Rule name
+        name: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Set a rule's priority
+    Priority {
+        /// Rule name
+        name: String,
+        /// New priority (higher runs first)
+        #[arg(allow_negative_numbers = true)]
+        priority: i32,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Delete a rule and the comments just above it
+    Remove {
+        /// Rule name
+        name: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Apply a JSON array of edits (insert_rule, update_rule, delete_rule, set_enabled, set_priority)
+    Apply {
+        /// File with the edits (defaults to stdin)
+        #[arg(long)]
+        file: Option<String>,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Print the updated config instead of a summary
+        #[arg(long)]
+        print: bool,
+    },
+}
+
+/// Subcommands for the logs command
+#[derive(Subcommand)]
+enum LogsSubcommand {
+    /// Rewrite older log entries to the current schema version
+    Upgrade {
+        /// Log file to upgrade (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Export log entries as flat CSV or Parquet files for analysis
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = cli::logs::ExportFormat::Csv)]
+        format: cli::logs::ExportFormat,
+        /// Only entries since this time (RFC3339, or an age like 30d, 12h)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries until this time (RFC3339, or an age like 1d)
+        #[arg(long)]
+        until: Option<String>,
+        /// Output file, or - for stdout (defaults to rulez-logs.<format>)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Log file to export (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Remove entries older than a cutoff from the log
+    Purge {
+        /// Remove entries before this time (RFC3339, a date like 2026-01-01, or an age like 90d;
+        /// defaults to settings.logging.retention_days ago)
+        #[arg(long)]
+        before: Option<String>,
+        /// Show what would be removed without changing the log
+        #[arg(long)]
+        dry_run: bool,
+        /// Log file to purge (defaults to ~/.claude/logs/rulez.log)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+}
+
 /// Subcommands for the explain command
 #[derive(Subcommand)]
 enum ExplainSubcommand {
@@ -294,20 +752,48 @@ enum OpenCodeSubcommand {
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
-
     let cli = Cli::parse();
 
+    // Initialize tracing (batch and stream modes keep stdout for JSONL responses only).
+    // Closing spans report their duration when enabled (e.g. RUST_LOG=rulez=debug).
+    // RULEZ_DEBUG=1|trace turns tracing on for one invocation, on stderr so the
+    // hook response on stdout stays intact; an explicit RUST_LOG still wins.
+    let debug_level = models::debug_env();
+    let filter = match debug_level {
+        Some(level) if std::env::var_os("RUST_LOG").is_none() => {
+            tracing_subscriber::EnvFilter::new(format!("rulez={level}"))
+        }
+        _ => tracing_subscriber::EnvFilter::from_default_env(),
+    };
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE);
+    if cli.batch || cli.stream || debug_level.is_some() {
+        subscriber.with_writer(io::stderr).init();
+    } else {
+        subscriber.init();
+    }
+
+    // A disabled RuleZ still answers hooks when the config it bypasses is
+    // broken; the hook path logs the bypass itself
+    let bypassed =
+        runs_hook(cli.command.as_ref()) && kill_switch::bypass_reason(chrono::Utc::now()).is_some();
+
     // Load config to get settings for DebugConfig
-    let config = config::Config::load(None)?;
+    let config = match config::Config::load(None) {
+        Err(e) if bypassed => {
+            tracing::warn!("RuleZ is disabled; ignoring config error: {}", e);
+            config::Config::default()
+        }
+        loaded => loaded?,
+    };
 
     // Initialize the global logger with external backends from config
     if let Err(e) = logging::init_global_logger_with_config(&config.settings.logging) {
         tracing::warn!("Failed to initialize logger: {}", e);
     }
+    // Log entries are written in the background; flush them however main returns
+    let _flush_logs = logging::FlushOnDrop;
 
     match cli.command {
         Some(Commands::Init {
@@ -350,6 +836,37 @@ async fn main() -> Result<()> {
             cli::validate::run(config).await?;
         }
         Some(Commands::Logs {
+            subcommand: Some(LogsSubcommand::Upgrade { path }),
+            ..
+        }) => {
+            cli::logs::upgrade(path)?;
+        }
+        Some(Commands::Logs {
+            subcommand:
+                Some(LogsSubcommand::Export {
+                    format,
+                    since,
+                    until,
+                    output,
+                    path,
+                }),
+            ..
+        }) => {
+            cli::logs::export(format, since, until, output, path)?;
+        }
+        Some(Commands::Logs {
+            subcommand:
+                Some(LogsSubcommand::Purge {
+                    before,
+                    dry_run,
+                    path,
+                }),
+            ..
+        }) => {
+            cli::logs::purge(before, dry_run, path)?;
+        }
+        Some(Commands::Logs {
+            subcommand: None,
             limit,
             since,
             mode,
@@ -357,6 +874,121 @@ async fn main() -> Result<()> {
         }) => {
             cli::logs::run(limit, since, mode, decision).await?;
         }
+        Some(Commands::Stats {
+            per_rule,
+            format,
+            since,
+            until,
+            output,
+            path,
+        }) => {
+            cli::stats::run(per_rule, format, since, until, output, path)?;
+        }
+        Some(Commands::Digest {
+            period,
+            since,
+            webhook,
+            output,
+            json,
+            path,
+        }) => {
+            cli::digest::run(period, since, webhook, output, json, path)?;
+        }
+        Some(Commands::VerifyLogs { path, key_env }) => {
+            cli::logs::verify(path, key_env)?;
+        }
+        Some(Commands::Report { subcommand }) => match subcommand {
+            ReportSubcommand::Pr {
+                sessions,
+                since,
+                until,
+                format,
+                root,
+                comment,
+                fail_on_block,
+                output,
+                path,
+            } => cli::report::pr(
+                sessions,
+                since,
+                until,
+                format,
+                root,
+                comment,
+                fail_on_block,
+                output,
+                path,
+            )?,
+        },
+        Some(Commands::Audit { subcommand }) => match subcommand {
+            AuditSubcommand::Export {
+                since,
+                until,
+                output,
+                path,
+                key_env,
+            } => cli::audit::export(since, until, output, path, key_env)?,
+            AuditSubcommand::Verify { bundle, key_env } => {
+                cli::audit::verify(&bundle, key_env)?;
+            }
+        },
+        Some(Commands::Sync {
+            subcommand,
+            config,
+            source,
+            dry_run,
+            json,
+        }) => match subcommand {
+            Some(SyncSubcommand::Sign {
+                dir,
+                output,
+                key_env,
+            }) => cli::sync::sign(&dir, output.as_deref(), key_env)?,
+            None => cli::sync::run(config, source, dry_run, json)?,
+        },
+        Some(Commands::Governance { subcommand }) => match subcommand {
+            GovernanceSubcommand::Report { config, json } => {
+                cli::governance::report(config, json)?;
+            }
+            GovernanceSubcommand::List { config, tag, json } => {
+                cli::governance::list(config, tag.as_deref(), json)?;
+            }
+        },
+        Some(Commands::Packs { subcommand }) => match subcommand {
+            PacksSubcommand::List { config, json } => cli::packs::list(config, json)?,
+            PacksSubcommand::Install { name, config } => cli::packs::install(config, &name)?,
+            PacksSubcommand::Remove { name, config } => cli::packs::remove(config, &name)?,
+        },
+        Some(Commands::CompletionData { config, log }) => {
+            cli::completion_data::run(config, log)?;
+        }
+        Some(Commands::Disable {
+            all: _,
+            ttl,
+            reason,
+        }) => {
+            cli::kill_switch::disable(ttl.as_deref(), reason)?;
+        }
+        Some(Commands::Enable { all: _ }) => {
+            cli::kill_switch::enable()?;
+        }
+        Some(Commands::Rule { subcommand }) => match subcommand {
+            RuleSubcommand::Enable { name, config } => cli::rule::set_enabled(config, &name, true)?,
+            RuleSubcommand::Disable { name, config } => {
+                cli::rule::set_enabled(config, &name, false)?;
+            }
+            RuleSubcommand::Priority {
+                name,
+                priority,
+                config,
+            } => cli::rule::set_priority(config, &name, priority)?,
+            RuleSubcommand::Remove { name, config } => cli::rule::remove(config, &name)?,
+            RuleSubcommand::Apply {
+                file,
+                config,
+                print,
+            } => cli::rule::apply(config, file, print)?,
+        },
         Some(Commands::Explain {
             subcommand,
             event_id,
@@ -398,7 +1030,12 @@ async fn main() -> Result<()> {
                 cli::copilot_install::run(binary, print).await?;
             }
             CopilotSubcommand::Hook => {
-                cli::copilot_hook::run(cli.debug_logs).await?;
+                cli::agent_hook::run(
+                    &adapters::copilot::CopilotAdapter,
+                    cli.debug_logs,
+                    cli.dry_run,
+                )
+                .await?;
             }
         },
         Some(Commands::Gemini { subcommand }) => match subcommand {
@@ -413,7 +1050,12 @@ async fn main() -> Result<()> {
                 cli::gemini_install::run(scope, binary, print).await?;
             }
             GeminiSubcommand::Hook => {
-                cli::gemini_hook::run(cli.debug_logs).await?;
+                cli::agent_hook::run(
+                    &adapters::gemini::GeminiAdapter,
+                    cli.debug_logs,
+                    cli.dry_run,
+                )
+                .await?;
             }
         },
         Some(Commands::OpenCode { subcommand }) => match subcommand {
@@ -428,17 +1070,80 @@ async fn main() -> Result<()> {
                 cli::opencode_install::run(scope, binary, print).await?;
             }
             OpenCodeSubcommand::Hook => {
-                cli::opencode_hook::run(cli.debug_logs).await?;
+                cli::agent_hook::run(
+                    &adapters::opencode::OpenCodeAdapter,
+                    cli.debug_logs,
+                    cli.dry_run,
+                )
+                .await?;
             }
         },
-        Some(Commands::Test { test_file, verbose }) => {
-            cli::test::run(test_file, verbose).await?;
+        Some(Commands::Test {
+            test_file,
+            verbose,
+            json,
+        }) => {
+            cli::test::run(test_file, verbose, json).await?;
         }
-        Some(Commands::Upgrade { check }) => {
-            cli::upgrade::run(check).await?;
+        Some(Commands::SelfUpdate { check, json }) => {
+            cli::upgrade::run(check, json).await?;
+        }
+        Some(Commands::Lint {
+            config,
+            verbose,
+            with_logs,
+            since,
+            log_path,
+        }) => {
+            let usage = with_logs.then_some(cli::lint::LogUsage {
+                since,
+                path: log_path,
+            });
+            cli::lint::run(config, verbose, usage).await?;
         }
-        Some(Commands::Lint { config, verbose }) => {
-            cli::lint::run(config, verbose).await?;
+        Some(Commands::Migrate { config, dry_run }) => {
+            cli::migrate::run(config, dry_run)?;
+        }
+        Some(Commands::Daemon {
+            ref socket,
+            http,
+            digest,
+            sync: sync_policies,
+        }) => {
+            let socket = match socket {
+                Some(path) => path.clone(),
+                None => cli::daemon::default_socket_path()?,
+            };
+            let serve = cli::daemon::serve(&socket, http, |event_value| respond(&cli, event_value));
+            if digest.is_some() {
+                let destinations = config::Config::load(None)?.settings.digest;
+                if destinations.is_none_or(|d| d.webhook.is_none() && d.output.is_none()) {
+                    anyhow::bail!(
+                        "--digest needs settings.digest.webhook or settings.digest.output"
+                    );
+                }
+            }
+            if sync_policies && config::Config::load(None)?.settings.sync.is_none() {
+                anyhow::bail!("--sync needs settings.sync");
+            }
+            let digest_task = async {
+                match digest {
+                    Some(period) => digest::run_schedule(period).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let sync_task = async {
+                if sync_policies {
+                    sync::run_schedule().await
+                } else {
+                    std::future::pending().await
+                }
+            };
+            tokio::select! {
+                result = serve => result?,
+                result = digest_task => result?,
+                result = sync_task => result?,
+            }
         }
         Some(Commands::Skills { subcommand }) => match subcommand {
             SkillsSubcommand::Install {
@@ -464,13 +1169,36 @@ async fn main() -> Result<()> {
         },
         None => {
             // No subcommand provided, read from stdin for hook processing
-            process_hook_event(&cli, &config).await?;
+            if cli.batch {
+                process_batch(&cli).await?;
+            } else if cli.stream {
+                process_stream(&cli).await?;
+            } else {
+                process_hook_event(&cli, &config).await?;
+            }
         }
     }
 
     Ok(())
 }
 
+/// Whether a command answers hook events (the default stdin mode or an
+/// agent's `hook` subcommand)
+fn runs_hook(command: Option<&Commands>) -> bool {
+    matches!(
+        command,
+        None | Some(
+            Commands::Copilot {
+                subcommand: CopilotSubcommand::Hook
+            } | Commands::Gemini {
+                subcommand: GeminiSubcommand::Hook
+            } | Commands::OpenCode {
+                subcommand: OpenCodeSubcommand::Hook
+            }
+        )
+    )
+}
+
 async fn process_hook_event(cli: &Cli, _config: &config::Config) -> Result<()> {
     let mut buffer = String::new();
     io::stdin().read_to_string(&mut buffer)?;
@@ -487,48 +1215,132 @@ async fn process_hook_event(cli: &Cli, _config: &config::Config) -> Result<()> {
         std::process::exit(1);
     })?;
 
+    // Other agents' payloads go through their format adapter; Claude Code's
+    // keep the daemon and unknown-event handling below
+    let adapter = cli.agent.unwrap_or_else(|| adapters::detect(&event_value));
+    if adapter.name() != adapters::claude::ClaudeAdapter.name() {
+        return cli::agent_hook::run_event(adapter, event_value, cli.debug_logs, cli.dry_run).await;
+    }
+
+    // A daemon evaluates with its own flags, so dry runs stay in-process
+    let socket = cli
+        .socket
+        .clone()
+        .or_else(|| std::env::var_os("RULEZ_SOCKET").map(std::path::PathBuf::from))
+        .filter(|_| !cli.dry_run && !models::dry_run_env());
+    let response = match socket {
+        Some(socket) => match cli::daemon::forward(&socket, event_value.clone()).await {
+            Ok(response) => response,
+            Err(e) => {
+                // Fall back to in-process evaluation so hooks keep working
+                tracing::warn!("rulez daemon unavailable ({:#}), evaluating in-process", e);
+                respond(cli, event_value).await?
+            }
+        },
+        None => respond(cli, event_value).await?,
+    };
+    emit_response(&response)
+}
+
+/// Evaluate one parsed hook event
+async fn respond(cli: &Cli, event_value: serde_json::Value) -> Result<models::Response> {
     // Step 2: Validate against Event schema (REQ-SCHEMA-04: fail-open)
     // Schema deviations (extra fields, wrong optional types) log a warning
     // but continue processing. This does NOT catch missing required fields --
     // that is handled by serde deserialization below.
     schema::validate_event_schema(&event_value);
 
+    // Hook events this version doesn't know follow settings.unknown_event_policy
+    if let Some(name) = hooks::unknown_event_name(&event_value) {
+        let debug_config =
+            models::DebugConfig::new(cli.debug_logs, false).with_dry_run(cli.dry_run);
+        return Ok(hooks::process_unknown_event(&event_value, name, &debug_config).await?);
+    }
+
     // Step 3: Deserialize to strongly-typed Event struct (fail-closed)
     // Missing required fields (hook_event_name, session_id) are fatal because
     // the Event struct cannot be constructed without them. This is intentional:
     // fail-open applies to schema validation, not to type construction.
-    let event: models::Event = serde_json::from_value(event_value).map_err(|e| {
-        error!("Failed to deserialize hook event: {}", e);
-        e
-    })?;
+    let event = adapters::claude::ClaudeAdapter
+        .parse(event_value)
+        .map_err(|e| {
+            error!("Failed to deserialize hook event: {}", e);
+            e
+        })?
+        .event;
 
     info!(
         "Processing event: {} ({})",
         event.hook_event_name, event.session_id
     );
 
-    // Reload config using the event's cwd so we read the correct project's hooks.yaml
-    let project_config =
-        config::Config::load(event.cwd.as_ref().map(|p| std::path::Path::new(p.as_str())))?;
-    let debug_config = models::DebugConfig::new(cli.debug_logs, project_config.settings.debug_logs);
-    let response = hooks::process_event(event, &debug_config).await?;
-
-    if !response.continue_ {
-        // Claude Code hooks protocol: exit code 2 BLOCKS the tool call.
-        // Only stderr is used as the error message and fed back to Claude.
-        // Exit code 0 with "continue":false only stops the conversation,
-        // it does NOT prevent the tool from executing.
-        let reason = response
-            .reason
-            .as_deref()
-            .unwrap_or("Blocked by RuleZ policy");
-        eprintln!("{}", reason);
-        std::process::exit(2);
-    }
+    // Reload config using the event's cwd so we read the correct project's
+    // hooks.yaml; while RuleZ is disabled it isn't read, and process_event
+    // logs the bypass
+    let project_debug_logs = if kill_switch::bypass_reason(chrono::Utc::now()).is_some() {
+        false
+    } else {
+        config::Config::load(event.cwd.as_ref().map(|p| std::path::Path::new(p.as_str())))?
+            .settings
+            .debug_logs
+    };
+    let debug_config =
+        models::DebugConfig::new(cli.debug_logs, project_debug_logs).with_dry_run(cli.dry_run);
+    Ok(hooks::process_event(event, &debug_config).await?)
+}
 
-    // For allowed responses (with or without context injection), output JSON to stdout
-    let json = serde_json::to_string(&response)?;
-    println!("{}", json);
+/// Batch mode: one event per stdin line, one JSON response per stdout line
+///
+/// Blocked events are reported as `"continue": false` responses instead of
+/// exit code 2, and a line that can't be processed yields
+/// `{"line": N, "error": "..."}`, so one bad event doesn't stop the batch.
+/// Blank lines are skipped. The config is loaded once per project and reused
+/// through the config cache.
+async fn process_batch(cli: &Cli) -> Result<()> {
+    use std::io::{BufRead, Write};
 
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for (index, line) in stdin.lock().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result = match serde_json::from_str(&line) {
+            Ok(event_value) => respond(cli, event_value).await,
+            Err(e) => Err(anyhow::anyhow!("Failed to parse hook event JSON: {}", e)),
+        };
+        let json = match result {
+            Ok(response) => serde_json::to_string(&response)?,
+            Err(e) => {
+                error!("Batch line {}: {:#}", index + 1, e);
+                serde_json::json!({"line": index + 1, "error": format!("{e:#}")}).to_string()
+            }
+        };
+        writeln!(stdout, "{}", json)?;
+    }
+    stdout.flush()?;
     Ok(())
 }
+
+/// Stream mode: protocol v2 sessions on stdin/stdout (see `protocol`)
+///
+/// Like batch mode, blocked events are `"continue": false` responses rather
+/// than exit code 2.
+async fn process_stream(cli: &Cli) -> Result<()> {
+    let mut lines = protocol::LineReader::new(
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        protocol::MAX_LINE_BYTES,
+    );
+    let mut stdout = tokio::io::stdout();
+    protocol::serve(&mut lines, &mut stdout, None, &|event_value| {
+        respond(cli, event_value)
+    })
+    .await
+}
+
+/// Write a hook response: exit 2 with the reason on stderr to block,
+/// otherwise JSON on stdout
+fn emit_response(response: &models::Response) -> Result<()> {
+    cli::agent_hook::write_output(&adapters::claude::ClaudeAdapter::output(response)?)
+}
@@ -0,0 +1,202 @@
+//! Custom matcher plugins.
+//!
+//! The built-in matchers (`tools`, `command_match`, ...) are fields of
+//! [`Matchers`](crate::models::Matchers). Embedders add new kinds, such as an
+//! LDAP group or container runtime check, by implementing [`Matcher`] and
+//! registering it before the config is loaded. Rules then configure it under
+//! `matchers.custom`, keyed by the matcher's name:
+//!
+//! ```yaml
+//! matchers:
+//!   tools: ["Bash"]
+//!   custom:
+//!     ldap_group: release-managers
+//! ```
+//!
+//! Custom matchers run after the built-in ones, in name order, and every one
+//! must match. A name with no registered matcher never matches (fail closed),
+//! and `Config::validate` rejects it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use anyhow::Result;
+
+use crate::models::Event;
+
+/// A matcher kind that rules can use under `matchers.custom`
+pub trait Matcher: Send + Sync {
+    /// Key under `matchers.custom`, e.g. `"ldap_group"`
+    fn name(&self) -> &str;
+
+    /// Check a rule's configured value when the config is validated
+    fn validate(&self, value: &serde_json::Value) -> Result<()> {
+        let _ = value;
+        Ok(())
+    }
+
+    /// Whether the event satisfies the rule's configured value
+    fn matches(&self, event: &Event, value: &serde_json::Value) -> bool;
+}
+
+static REGISTRY: LazyLock<RwLock<HashMap<String, Arc<dyn Matcher>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Register a matcher, replacing (and returning) one with the same name
+#[allow(dead_code)] // Library API for embedders
+pub fn register(matcher: impl Matcher + 'static) -> Option<Arc<dyn Matcher>> {
+    let matcher: Arc<dyn Matcher> = Arc::new(matcher);
+    REGISTRY
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(matcher.name().to_string(), matcher)
+}
+
+/// Remove a registered matcher
+#[allow(dead_code)] // Library API for embedders
+pub fn unregister(name: &str) -> Option<Arc<dyn Matcher>> {
+    REGISTRY
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .remove(name)
+}
+
+/// Look up a registered matcher by name
+pub fn get(name: &str) -> Option<Arc<dyn Matcher>> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(name)
+        .cloned()
+}
+
+/// Names of all registered matchers, sorted
+#[allow(dead_code)] // Library API for embedders
+pub fn registered() -> Vec<String> {
+    let mut names: Vec<String> = REGISTRY
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+/// Check every entry of a rule's `matchers.custom`
+///
+/// Errors name the offending matcher.
+pub(crate) fn validate_custom(custom: &BTreeMap<String, serde_json::Value>) -> Result<()> {
+    for (name, value) in custom {
+        let Some(matcher) = get(name) else {
+            anyhow::bail!("Unknown custom matcher '{}'", name);
+        };
+        matcher
+            .validate(value)
+            .map_err(|e| e.context(format!("Invalid value for custom matcher '{}'", name)))?;
+    }
+    Ok(())
+}
+
+/// Whether every custom matcher matches
+pub(crate) fn matches_custom(event: &Event, custom: &BTreeMap<String, serde_json::Value>) -> bool {
+    custom
+        .iter()
+        .all(|(name, value)| matches_one(event, name, value))
+}
+
+/// Result of each custom matcher, for debug output
+pub(crate) fn evaluate_custom(
+    event: &Event,
+    custom: &BTreeMap<String, serde_json::Value>,
+) -> BTreeMap<String, bool> {
+    custom
+        .iter()
+        .map(|(name, value)| (name.clone(), matches_one(event, name, value)))
+        .collect()
+}
+
+fn matches_one(event: &Event, name: &str, value: &serde_json::Value) -> bool {
+    let _span = tracing::trace_span!("matcher", kind = "custom", name).entered();
+    if let Some(matcher) = get(name) {
+        matcher.matches(event, value)
+    } else {
+        tracing::warn!("Unknown custom matcher '{}' in rule — failing closed", name);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Matches when the event's `cwd` starts with the configured prefix
+    struct CwdPrefix;
+
+    impl Matcher for CwdPrefix {
+        fn name(&self) -> &'static str {
+            "test_cwd_prefix"
+        }
+
+        fn validate(&self, value: &serde_json::Value) -> Result<()> {
+            if value.is_string() {
+                Ok(())
+            } else {
+                anyhow::bail!("expected a string")
+            }
+        }
+
+        fn matches(&self, event: &Event, value: &serde_json::Value) -> bool {
+            let prefix = value.as_str().unwrap_or_default();
+            event
+                .cwd
+                .as_deref()
+                .is_some_and(|cwd| cwd.starts_with(prefix))
+        }
+    }
+
+    fn event(cwd: &str) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "matchers",
+            "cwd": cwd
+        }))
+        .unwrap()
+    }
+
+    fn custom(name: &str, value: serde_json::Value) -> BTreeMap<String, serde_json::Value> {
+        BTreeMap::from([(name.to_string(), value)])
+    }
+
+    #[test]
+    fn test_registered_matcher_is_used() {
+        register(CwdPrefix);
+        assert!(registered().contains(&"test_cwd_prefix".to_string()));
+
+        let config = custom("test_cwd_prefix", serde_json::json!("/srv"));
+        assert!(matches_custom(&event("/srv/app"), &config));
+        assert!(!matches_custom(&event("/home/me"), &config));
+        assert_eq!(
+            evaluate_custom(&event("/home/me"), &config),
+            BTreeMap::from([("test_cwd_prefix".to_string(), false)])
+        );
+    }
+
+    #[test]
+    fn test_validate_custom() {
+        register(CwdPrefix);
+        assert!(validate_custom(&custom("test_cwd_prefix", serde_json::json!("/srv"))).is_ok());
+
+        let err = validate_custom(&custom("test_cwd_prefix", serde_json::json!(3))).unwrap_err();
+        assert!(format!("{:#}", err).contains("expected a string"));
+
+        let err = validate_custom(&custom("no_such_matcher", serde_json::json!(true))).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown custom matcher 'no_such_matcher'");
+    }
+
+    #[test]
+    fn test_unknown_matcher_fails_closed() {
+        let config = custom("never_registered", serde_json::json!(true));
+        assert!(!matches_custom(&event("/srv"), &config));
+    }
+}
@@ -34,6 +34,20 @@ impl std::fmt::Display for PolicyMode {
     }
 }
 
+/// How much of an event is logged when a rule matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLogLevel {
+    /// Don't log the event unless it was blocked or warned
+    Off,
+    /// Never log the raw event, even with debug logging
+    Minimal,
+    /// Always log the raw event (redacted like debug logs)
+    Full,
+    /// Log the raw event and rule evaluations, as with debug logging
+    Debug,
+}
+
 /// Confidence level for rule metadata
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -65,6 +79,8 @@ pub enum Decision {
     Warned,
     /// Rule matched but only logged (audit mode)
     Audited,
+    /// RuleZ was disabled and let the operation through unevaluated
+    Bypassed,
 }
 
 impl std::fmt::Display for Decision {
@@ -74,6 +90,7 @@ impl std::fmt::Display for Decision {
             Decision::Blocked => write!(f, "blocked"),
             Decision::Warned => write!(f, "warned"),
             Decision::Audited => write!(f, "audited"),
+            Decision::Bypassed => write!(f, "bypassed"),
         }
     }
 }
@@ -87,6 +104,7 @@ impl std::str::FromStr for Decision {
             "blocked" => Ok(Decision::Blocked),
             "warned" => Ok(Decision::Warned),
             "audited" => Ok(Decision::Audited),
+            "bypassed" => Ok(Decision::Bypassed),
             _ => Err(format!("Invalid decision: {}", s)),
         }
     }
@@ -99,24 +117,40 @@ impl std::str::FromStr for Decision {
 /// Trust level for validator scripts
 ///
 /// Indicates the provenance and verification status of a validator script.
-/// This is informational in v1.1 - enforcement planned for future versions.
+/// Enforced through `settings.minimum_trust` and `settings.restrict_untrusted`.
 ///
 /// # Trust Levels
+/// - `Untrusted`: Script from external/untrusted source (lowest)
 /// - `Local`: Script exists in the local project repository
-/// - `Verified`: Script has been cryptographically verified (future)
-/// - `Untrusted`: Script from external/untrusted source
+/// - `Verified`: Script is pinned with a `sha256` hash (highest)
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TrustLevel {
     /// Script is local to the project
     #[default]
     Local,
-    /// Script has been verified (cryptographic verification - future)
+    /// Script content is pinned with a `sha256` hash
     Verified,
     /// Script from external or untrusted source
     Untrusted,
 }
 
+impl TrustLevel {
+    /// Position in the trust ordering (higher is more trusted)
+    fn rank(self) -> u8 {
+        match self {
+            TrustLevel::Untrusted => 0,
+            TrustLevel::Local => 1,
+            TrustLevel::Verified => 2,
+        }
+    }
+
+    /// Check whether this level meets a required minimum
+    pub fn satisfies(self, minimum: TrustLevel) -> bool {
+        self.rank() >= minimum.rank()
+    }
+}
+
 impl std::fmt::Display for TrustLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -316,6 +350,13 @@ pub enum RunAction {
         /// Trust level for the script
         #[serde(skip_serializing_if = "Option::is_none")]
         trust: Option<TrustLevel>,
+        /// Expected SHA-256 of the script file (hex); execution is blocked
+        /// if the file no longer matches
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
+        /// Environment and arguments passed to the script
+        #[serde(flatten)]
+        environment: ScriptEnvironment,
     },
 }
 
@@ -335,6 +376,691 @@ impl RunAction {
             RunAction::Extended { trust, .. } => trust.unwrap_or(TrustLevel::Local),
         }
     }
+
+    /// Get the trust level enforced at runtime
+    ///
+    /// `verified` is only honoured for scripts pinned with `sha256`;
+    /// an unpinned script claiming it is treated as `local`.
+    pub fn effective_trust_level(&self) -> TrustLevel {
+        match self.trust_level() {
+            TrustLevel::Verified if self.sha256().is_none() => TrustLevel::Local,
+            level => level,
+        }
+    }
+
+    /// Get the script environment (None = inherit the parent environment)
+    pub fn environment(&self) -> Option<&ScriptEnvironment> {
+        match self {
+            RunAction::Simple(_) => None,
+            RunAction::Extended { environment, .. } => Some(environment),
+        }
+    }
+
+    /// Get the pinned SHA-256 of the script (if any)
+    pub fn sha256(&self) -> Option<&str> {
+        match self {
+            RunAction::Simple(_) => None,
+            RunAction::Extended { sha256, .. } => sha256.as_deref(),
+        }
+    }
+}
+
+/// Environment variables and arguments passed to a script
+///
+/// By default scripts inherit the full parent environment. `env_clear`
+/// drops it (keeping only `PATH`, `HOME`, and the OS temp/system variables
+/// needed to start a process) so secrets don't leak to untrusted validators.
+///
+/// Values in `env`, `args`, and `cwd` may reference event fields with
+/// `{{path}}` templates using dot notation, e.g. `{{tool_name}}` or
+/// `{{tool_input.file_path}}`. Missing fields render as empty strings.
+///
+/// Scripts run in the event's working directory unless `cwd` says otherwise.
+///
+/// ```yaml
+/// actions:
+///   run:
+///     script: .claude/validators/check.py
+///     env_clear: true
+///     env:
+///       RULEZ_SESSION: "{{session_id}}"
+///     args: ["--file", "{{tool_input.file_path}}"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScriptEnvironment {
+    /// Variables set for the script (templated)
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+    /// Start from an empty environment instead of inheriting the parent's
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub env_clear: bool,
+    /// Command-line arguments passed after the script path (templated)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Working directory (templated, relative to the event cwd; None = event cwd)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+/// Interpreter used to run an inline script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptShell {
+    /// POSIX shell (`sh`)
+    Sh,
+    /// Bash (on Windows, typically Git Bash or WSL)
+    Bash,
+    /// PowerShell (`powershell` on Windows, `pwsh` elsewhere)
+    Powershell,
+    /// Windows command interpreter (`cmd.exe`)
+    Cmd,
+    /// Python 3 (`python` on Windows, `python3` elsewhere)
+    Python,
+}
+
+impl ScriptShell {
+    /// File extension for script files, so the interpreter recognises them
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScriptShell::Sh | ScriptShell::Bash => "sh",
+            ScriptShell::Powershell => "ps1",
+            ScriptShell::Cmd => "cmd",
+            ScriptShell::Python => "py",
+        }
+    }
+
+    /// Default program for this interpreter
+    pub fn program(self) -> &'static str {
+        match self {
+            ScriptShell::Sh => "sh",
+            ScriptShell::Bash => "bash",
+            ScriptShell::Powershell => {
+                if cfg!(windows) {
+                    "powershell"
+                } else {
+                    "pwsh"
+                }
+            }
+            ScriptShell::Cmd => "cmd",
+            ScriptShell::Python => {
+                if cfg!(windows) {
+                    "python"
+                } else {
+                    "python3"
+                }
+            }
+        }
+    }
+
+    /// Arguments placed before a script file path
+    pub fn file_args(self) -> &'static [&'static str] {
+        match self {
+            ScriptShell::Sh | ScriptShell::Bash | ScriptShell::Python => &[],
+            ScriptShell::Powershell => &[
+                "-NoProfile",
+                "-NonInteractive",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+            ],
+            ScriptShell::Cmd => &["/D", "/C"],
+        }
+    }
+
+    /// Arguments that run `code` directly from the command line, followed
+    /// by the script's own `args`
+    ///
+    /// Returns None for `cmd`, which can only run multi-line batch files.
+    pub fn inline_args(self, code: &str, args: &[String]) -> Option<Vec<String>> {
+        let mut inline = match self {
+            // `$0` is the script name, `args` become `$1`...
+            ScriptShell::Sh | ScriptShell::Bash => {
+                vec![
+                    "-c".to_string(),
+                    code.to_string(),
+                    "rulez-inline".to_string(),
+                ]
+            }
+            ScriptShell::Python => vec!["-c".to_string(), code.to_string()],
+            ScriptShell::Powershell => {
+                // PowerShell joins everything after -Command into one command,
+                // so arguments are embedded as single-quoted literals
+                let quoted: Vec<String> = args
+                    .iter()
+                    .map(|arg| format!("'{}'", arg.replace('\'', "''")))
+                    .collect();
+                return Some(vec![
+                    "-NoProfile".to_string(),
+                    "-NonInteractive".to_string(),
+                    "-ExecutionPolicy".to_string(),
+                    "Bypass".to_string(),
+                    "-Command".to_string(),
+                    format!("& {{\n{}\n}} {}", code, quoted.join(" ")),
+                ]);
+            }
+            ScriptShell::Cmd => return None,
+        };
+        inline.extend(args.iter().cloned());
+        Some(inline)
+    }
+
+    /// Infer the interpreter from a script's shebang line (if any)
+    pub fn from_shebang(code: &str) -> Option<Self> {
+        let first_line = code.trim_start().lines().next()?;
+        let interpreter = first_line.strip_prefix("#!")?;
+        if interpreter.contains("python") {
+            Some(ScriptShell::Python)
+        } else if interpreter.contains("pwsh") || interpreter.contains("powershell") {
+            Some(ScriptShell::Powershell)
+        } else if interpreter.contains("bash") {
+            Some(ScriptShell::Bash)
+        } else if interpreter.contains("sh") {
+            Some(ScriptShell::Sh)
+        } else {
+            None
+        }
+    }
+
+    /// Split a shebang line into interpreter program and arguments
+    ///
+    /// `#!/usr/bin/env python3 -u` yields `("python3", ["-u"])`, so the
+    /// interpreter is looked up on PATH just as `env` would.
+    pub fn parse_shebang(code: &str) -> Option<(String, Vec<String>)> {
+        let first_line = code.trim_start().lines().next()?;
+        let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+        let mut program = words.next()?;
+        if program.ends_with("/env") {
+            program = words.by_ref().find(|word| *word != "-S")?;
+        }
+        Some((
+            program.to_string(),
+            words.map(ToString::to_string).collect(),
+        ))
+    }
+}
+
+impl std::fmt::Display for ScriptShell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptShell::Sh => write!(f, "sh"),
+            ScriptShell::Bash => write!(f, "bash"),
+            ScriptShell::Powershell => write!(f, "powershell"),
+            ScriptShell::Cmd => write!(f, "cmd"),
+            ScriptShell::Python => write!(f, "python"),
+        }
+    }
+}
+
+/// Inline script action configuration
+///
+/// Supports two YAML formats:
+/// ```yaml
+/// # Simple format: executed via its shebang (Unix)
+/// actions:
+///   inline_script: |
+///     #!/bin/bash
+///     exit 0
+///
+/// # Extended format with an explicit interpreter and environment
+/// actions:
+///   inline_script:
+///     shell: powershell
+///     env_clear: true
+///     code: |
+///       exit 0
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InlineScript {
+    /// Simple string format: script body with optional shebang
+    Simple(String),
+    /// Extended object format with interpreter and environment
+    Extended {
+        /// Interpreter used to run the script (None = same as simple format)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        shell: Option<ScriptShell>,
+        /// Script body
+        code: String,
+        /// Environment and arguments passed to the script
+        #[serde(flatten)]
+        environment: ScriptEnvironment,
+    },
+}
+
+impl InlineScript {
+    /// Get the script body regardless of format
+    pub fn code(&self) -> &str {
+        match self {
+            InlineScript::Simple(code) | InlineScript::Extended { code, .. } => code,
+        }
+    }
+
+    /// Resolve the interpreter to run the script with
+    ///
+    /// Returns None when the script should be executed directly so the
+    /// kernel honours its shebang (simple format on Unix). On Windows, where
+    /// shebangs are ignored and `sh` often doesn't exist, the interpreter is
+    /// inferred from the shebang and falls back to PowerShell.
+    pub fn resolve_shell(&self) -> Option<ScriptShell> {
+        match self {
+            InlineScript::Extended {
+                shell: Some(shell), ..
+            } => Some(*shell),
+            _ if cfg!(windows) => {
+                Some(ScriptShell::from_shebang(self.code()).unwrap_or(ScriptShell::Powershell))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the script environment (None = inherit the parent environment)
+    pub fn environment(&self) -> Option<&ScriptEnvironment> {
+        match self {
+            InlineScript::Simple(_) => None,
+            InlineScript::Extended { environment, .. } => Some(environment),
+        }
+    }
+}
+
+/// Command for the inject_command action
+///
+/// Supports two YAML formats:
+/// ```yaml
+/// # Simple format: runs in the event's working directory
+/// actions:
+///   inject_command: "git branch --show-current"
+///
+/// # Extended format with an explicit working directory
+/// actions:
+///   inject_command:
+///     command: "npm ls --depth=0"
+///     cwd: frontend
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InjectCommand {
+    /// Simple string format: just the command
+    Simple(String),
+    /// Extended object format with working directory
+    Extended {
+        /// Shell command to execute
+        command: String,
+        /// Working directory (templated, relative to the event cwd)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+    },
+}
+
+impl InjectCommand {
+    /// Get the command regardless of format
+    pub fn command(&self) -> &str {
+        match self {
+            InjectCommand::Simple(command) | InjectCommand::Extended { command, .. } => command,
+        }
+    }
+
+    /// Get the configured working directory (None = event cwd)
+    pub fn cwd(&self) -> Option<&str> {
+        match self {
+            InjectCommand::Simple(_) => None,
+            InjectCommand::Extended { cwd, .. } => cwd.as_deref(),
+        }
+    }
+}
+
+/// Shrinking of oversized `inject` and `inject_command` content
+///
+/// Content over `max_bytes` is piped through `command` (content on stdin,
+/// summary on stdout) or, without one, cut down to its first and last lines.
+/// The result is capped at `max_bytes` either way.
+/// ```yaml
+/// actions:
+///   inject_command: "git log --stat -100"
+///   summarize:
+///     max_bytes: 4096
+///     command: "llm -s 'Summarize these commits in ten bullet points'"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Summarize {
+    /// Content larger than this many bytes is summarized
+    #[serde(default = "default_summarize_max_bytes")]
+    pub max_bytes: usize,
+
+    /// Summarizer command; without one, head/tail extraction is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Lines kept from the start by head/tail extraction
+    #[serde(default = "default_summarize_head_lines")]
+    pub head_lines: usize,
+
+    /// Lines kept from the end by head/tail extraction
+    #[serde(default = "default_summarize_tail_lines")]
+    pub tail_lines: usize,
+}
+
+fn default_summarize_max_bytes() -> usize {
+    8192
+}
+
+fn default_summarize_head_lines() -> usize {
+    40
+}
+
+fn default_summarize_tail_lines() -> usize {
+    20
+}
+
+/// Where a rule's injected content is delivered (`actions.inject_as`)
+///
+/// The hook output field used for each target:
+/// - `context`: `hookSpecificOutput.additionalContext` on events that
+///   accept it (and the legacy top-level `context`), read by the model
+/// - `system_message`: `systemMessage`, shown to the user, not the model
+/// - `stderr`: written to stderr, which the client shows in the transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectTarget {
+    /// The model's context (default)
+    #[default]
+    Context,
+    /// The user-facing system message
+    SystemMessage,
+    /// The transcript, via stderr
+    Stderr,
+}
+
+/// Configuration for the webhook action
+///
+/// POSTs `{"rule": ..., "event": ...}` as JSON when the rule matches.
+/// Supports two YAML formats:
+/// ```yaml
+/// # Simple format: just the URL
+/// actions:
+///   webhook: "https://hooks.example.com/rulez"
+///
+/// # Extended format with headers (`${VAR}` is expanded) and timeout
+/// actions:
+///   webhook:
+///     url: "https://hooks.slack.com/services/..."
+///     headers:
+///       Authorization: "Bearer ${WEBHOOK_TOKEN}"
+///     timeout_secs: 3
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum WebhookAction {
+    /// Simple string format: just the URL
+    Simple(String),
+    /// Extended object format with headers and timeout
+    Extended {
+        /// URL to POST to
+        url: String,
+        /// Extra HTTP headers
+        #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+        headers: std::collections::HashMap<String, String>,
+        /// Request timeout in seconds (default 5)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_secs: Option<u64>,
+    },
+}
+
+impl WebhookAction {
+    /// Get the URL regardless of format
+    pub fn url(&self) -> &str {
+        match self {
+            WebhookAction::Simple(url) | WebhookAction::Extended { url, .. } => url,
+        }
+    }
+
+    /// Get the configured headers (empty for the simple format)
+    pub fn headers(&self) -> std::collections::HashMap<String, String> {
+        match self {
+            WebhookAction::Simple(_) => std::collections::HashMap::new(),
+            WebhookAction::Extended { headers, .. } => headers.clone(),
+        }
+    }
+
+    /// Get the request timeout in seconds
+    pub fn timeout_secs(&self) -> u64 {
+        match self {
+            WebhookAction::Simple(_) => 5,
+            WebhookAction::Extended { timeout_secs, .. } => timeout_secs.unwrap_or(5),
+        }
+    }
+}
+
+/// Configuration for the transcript_match matcher
+///
+/// Supports two YAML formats:
+/// ```yaml
+/// # Simple format: regex against the last 20 messages of either role
+/// matchers:
+///   transcript_match: "migration policy"
+///
+/// # Extended format
+/// matchers:
+///   transcript_match:
+///     pattern: "(?i)I acknowledge the migration policy"
+///     role: assistant      # user | assistant | any
+///     last_messages: 10
+///     max_bytes: 65536
+///     negate: true         # match when the pattern is NOT found
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum TranscriptMatch {
+    /// Simple string format: just the regex
+    Simple(String),
+    /// Extended object format
+    Extended {
+        /// Regex matched against each message's text
+        pattern: String,
+        /// Which messages to search (default `any`)
+        #[serde(default)]
+        role: crate::transcript::TranscriptRole,
+        /// How many recent messages to search (default 20)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_messages: Option<usize>,
+        /// How many bytes to read from the end of the transcript (default 256 KiB)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_bytes: Option<u64>,
+        /// Match when no message matches the pattern
+        #[serde(default)]
+        negate: bool,
+    },
+}
+
+impl TranscriptMatch {
+    /// Get the regex regardless of format
+    pub fn pattern(&self) -> &str {
+        match self {
+            TranscriptMatch::Simple(pattern) | TranscriptMatch::Extended { pattern, .. } => pattern,
+        }
+    }
+
+    /// Which messages to search
+    pub fn role(&self) -> crate::transcript::TranscriptRole {
+        match self {
+            TranscriptMatch::Simple(_) => crate::transcript::TranscriptRole::Any,
+            TranscriptMatch::Extended { role, .. } => *role,
+        }
+    }
+
+    /// How many recent messages to search
+    pub fn last_messages(&self) -> usize {
+        match self {
+            TranscriptMatch::Simple(_) => 20,
+            TranscriptMatch::Extended { last_messages, .. } => last_messages.unwrap_or(20),
+        }
+    }
+
+    /// Maximum bytes read from the end of the transcript
+    pub fn max_bytes(&self) -> u64 {
+        match self {
+            TranscriptMatch::Simple(_) => 256 * 1024,
+            TranscriptMatch::Extended { max_bytes, .. } => max_bytes.unwrap_or(256 * 1024),
+        }
+    }
+
+    /// Whether the match is inverted
+    pub fn negate(&self) -> bool {
+        match self {
+            TranscriptMatch::Simple(_) => false,
+            TranscriptMatch::Extended { negate, .. } => *negate,
+        }
+    }
+}
+
+/// Configuration for the rewrite_prompt action (UserPromptSubmit events)
+///
+/// ```yaml
+/// actions:
+///   rewrite_prompt:
+///     prepend: "Reminder: never commit directly to main."
+///     redact: ['AKIA[0-9A-Z]{16}']
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PromptRewrite {
+    /// Text added before the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepend: Option<String>,
+
+    /// Text added after the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub append: Option<String>,
+
+    /// Regexes whose matches are removed from the prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact: Vec<String>,
+
+    /// Replacement for redacted text (default `[REDACTED]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact_with: Option<String>,
+}
+
+impl PromptRewrite {
+    /// Replacement for redacted text
+    pub fn replacement(&self) -> &str {
+        self.redact_with.as_deref().unwrap_or("[REDACTED]")
+    }
+}
+
+/// What happens when a category of PII is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiSeverity {
+    /// Category is not scanned
+    Off,
+    /// Allowed; the finding is only logged
+    Audit,
+    /// Allowed with a warning injected as context
+    Warn,
+    /// Operation is blocked
+    Block,
+}
+
+impl std::fmt::Display for PiiSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PiiSeverity::Off => write!(f, "off"),
+            PiiSeverity::Audit => write!(f, "audit"),
+            PiiSeverity::Warn => write!(f, "warn"),
+            PiiSeverity::Block => write!(f, "block"),
+        }
+    }
+}
+
+/// Per-category severities for the scan_pii action
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PiiSeverities {
+    /// Email addresses
+    #[serde(default = "default_pii_warn")]
+    pub email: PiiSeverity,
+    /// Phone numbers
+    #[serde(default = "default_pii_warn")]
+    pub phone: PiiSeverity,
+    /// National identifiers (US SSN, UK National Insurance number)
+    #[serde(default = "default_pii_block")]
+    pub national_id: PiiSeverity,
+}
+
+fn default_pii_warn() -> PiiSeverity {
+    PiiSeverity::Warn
+}
+
+fn default_pii_block() -> PiiSeverity {
+    PiiSeverity::Block
+}
+
+impl Default for PiiSeverities {
+    fn default() -> Self {
+        Self {
+            email: default_pii_warn(),
+            phone: default_pii_warn(),
+            national_id: default_pii_block(),
+        }
+    }
+}
+
+/// Configuration for the scan_pii action
+///
+/// Supports two YAML formats:
+/// ```yaml
+/// # Simple format: default severities
+/// actions:
+///   scan_pii: true
+///
+/// # Per-category severities: block, warn, audit, or off
+/// actions:
+///   scan_pii:
+///     email: audit
+///     phone: warn
+///     national_id: block
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScanPii {
+    /// `true` scans with default severities, `false` disables scanning
+    Enabled(bool),
+    /// Explicit per-category severities
+    Severities(PiiSeverities),
+}
+
+impl ScanPii {
+    /// Get the effective severities (None = scanning disabled)
+    pub fn severities(&self) -> Option<PiiSeverities> {
+        match self {
+            ScanPii::Enabled(true) => Some(PiiSeverities::default()),
+            ScanPii::Enabled(false) => None,
+            ScanPii::Severities(severities) => Some(*severities),
+        }
+    }
+}
+
+/// Permission decision reported to Claude Code for `PreToolUse` events
+///
+/// Ordered from least to most restrictive, so merging matched rules keeps
+/// the strictest decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionDecision {
+    /// Approve the tool call without prompting the user
+    Allow,
+    /// Ask the user to confirm the tool call
+    Ask,
+    /// Refuse the tool call (same as `block: true`)
+    Deny,
+}
+
+impl std::fmt::Display for PermissionDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermissionDecision::Allow => write!(f, "allow"),
+            PermissionDecision::Ask => write!(f, "ask"),
+            PermissionDecision::Deny => write!(f, "deny"),
+        }
+    }
 }
 
 /// Governance metadata for rules - provenance and documentation
@@ -392,7 +1118,8 @@ pub struct Rule {
     /// Conditions that trigger the rule
     pub matchers: Matchers,
 
-    /// Actions to take when rule matches
+    /// Actions to take when rule matches: one set, or a pipeline of steps
+    #[serde(with = "actions_pipeline")]
     pub actions: Actions,
 
     // === Phase 2 Governance Fields ===
@@ -406,6 +1133,26 @@ pub struct Rule {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<i32>,
 
+    /// Order of this rule's injected context among matched rules (higher first)
+    /// Default: 0, which keeps evaluation order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inject_priority: Option<i32>,
+
+    /// Share of `settings.max_context_bytes` this rule's context gets when cut
+    /// Default: 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_weight: Option<u32>,
+
+    /// Whether this rule's validator errors allow the operation
+    /// Default: `settings.fail_open`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_open: Option<bool>,
+
+    /// Timeout in milliseconds for this rule's scripts and commands
+    /// Default: `settings.script_timeouts`, then `settings.script_timeout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
     /// Governance metadata (provenance, documentation)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub governance: Option<GovernanceMetadata>,
@@ -413,6 +1160,23 @@ pub struct Rule {
     /// Legacy metadata field (for backward compatibility)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<RuleMetadata>,
+
+    /// How much of the event to log when this rule matches (off, minimal, full, debug)
+    /// Default: the raw event is logged only with debug logging
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<RuleLogLevel>,
+
+    /// Rule is being phased out: it still runs, but matches log a notice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+
+    /// Rule to use instead of this deprecated one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+
+    /// Date after which `rulez migrate` removes the deprecated rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remove_after: Option<String>,
 }
 
 /// Conditions that trigger a rule
@@ -453,10 +1217,49 @@ pub struct Matchers {
     /// Implicitly requires field existence (field_types implies require_fields)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub field_types: Option<std::collections::HashMap<String, String>>,
+
+    /// Regex matched against the tool response text (PostToolUse events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_match: Option<String>,
+
+    /// Match only when the tool response does (true) or doesn't (false)
+    /// report a failure (PostToolUse events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_failed: Option<bool>,
+
+    /// Regex matched against the notification text (Notification events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_match: Option<String>,
+
+    /// Compaction trigger to match, `manual` or `auto` (PreCompact events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_trigger: Option<String>,
+
+    /// Regex matched against recent messages in the session transcript
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript_match: Option<TranscriptMatch>,
+
+    /// Rule names, any of which matched this tool call's PreToolUse event
+    /// (PostToolUse events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_rules: Option<Vec<String>>,
+
+    /// User ids (or `*` globs) the rule applies to (see `settings.identity`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users: Option<Vec<String>>,
+
+    /// Teams the rule applies to (see `settings.identity`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams: Option<Vec<String>>,
+
+    /// Registered custom matchers and their configured values, all of which
+    /// must match (see `matchers::Matcher`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom: Option<std::collections::BTreeMap<String, serde_json::Value>>,
 }
 
 /// Actions to take when rule matches
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Actions {
     /// Path to context file to inject
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -467,8 +1270,35 @@ pub struct Actions {
     pub inject_inline: Option<String>,
 
     /// Shell command to execute and inject stdout as context
+    /// (supports string or object format)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inject_command: Option<InjectCommand>,
+
+    /// Summarize `inject` and `inject_command` content over a size threshold
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   inject_command: "git log -100"
+    ///   summarize:
+    ///     max_bytes: 4096
+    ///     head_lines: 30
+    ///     tail_lines: 10
+    /// ```
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub inject_command: Option<String>,
+    pub summarize: Option<Summarize>,
+
+    /// Deliver injected content to the model's context (default), the
+    /// user-facing system message, or the transcript
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   inject_inline: "Deploys are frozen until Monday"
+    ///   inject_as: system_message
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inject_as: Option<InjectTarget>,
 
     /// Validator script to execute (supports string or object format)
     ///
@@ -507,7 +1337,7 @@ pub struct Actions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub validate_expr: Option<String>,
 
-    /// Inline shell script for validation
+    /// Inline script for validation
     ///
     /// When present, the script is executed with event JSON on stdin.
     /// - Exit code 0 = validation passes (allow operation)
@@ -519,20 +1349,238 @@ pub struct Actions {
     ///   inline_script: |
     ///     #!/bin/bash
     ///     jq -e '.tool == "Write"' > /dev/null
+    ///
+    /// # Or with an explicit interpreter (bash, sh, powershell, cmd, python)
+    /// actions:
+    ///   inline_script:
+    ///     shell: python
+    ///     code: |
+    ///       import json, sys
+    ///       sys.exit(0 if json.load(sys.stdin)["tool_name"] == "Write" else 1)
     /// ```
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub inline_script: Option<String>,
+    pub inline_script: Option<InlineScript>,
+
+    /// Embedded Rhai script for validation (runs in-process, no shell)
+    ///
+    /// The script sees `event`, `tool_name`, `event_type`, and `prompt`, plus
+    /// `get_field`/`has_field` and the `allow()`/`block(reason)`/`inject(text)`
+    /// decision helpers. Returning `false` or `block(...)` blocks the operation.
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   script_rhai: |
+    ///     if get_field("command").contains("--force") {
+    ///       block("force push is not allowed")
+    ///     }
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_rhai: Option<String>,
+
+    /// Scan written content for secrets (known token formats and
+    /// high-entropy assignments) and block when any are found
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   scan_secrets: true
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_secrets: Option<bool>,
+
+    /// Scan written content and prompts for PII (emails, phone numbers,
+    /// national IDs) with a block/warn/audit severity per category
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   scan_pii:
+    ///     email: audit
+    ///     national_id: block
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_pii: Option<ScanPii>,
+
+    /// Permission decision for `PreToolUse` events (allow, ask, or deny),
+    /// emitted as `hookSpecificOutput.permissionDecision`
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   permission_decision: ask
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_decision: Option<PermissionDecision>,
+
+    /// Message shown to the user (not the model), emitted as `systemMessage`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_message: Option<String>,
+
+    /// Hide the hook's output from the transcript, emitted as `suppressOutput`
+    ///
+    /// Blocks are always shown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppress_output: Option<bool>,
+
+    /// POST the event to a URL when the rule matches
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   webhook: "https://hooks.example.com/rulez"
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookAction>,
+
+    /// Rewrite the user's prompt (UserPromptSubmit events)
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   rewrite_prompt:
+    ///     prepend: "Follow the team's coding standards."
+    ///     redact: ['ghp_[A-Za-z0-9]{36}']
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite_prompt: Option<PromptRewrite>,
+
+    /// Registered custom actions and their configured values, run after the
+    /// built-in actions (see `actions::Action`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom: Option<std::collections::BTreeMap<String, serde_json::Value>>,
+
+    /// Condition checked after the rule matches; the actions are skipped
+    /// unless it is true (same variables and functions as `enabled_when`)
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   - block: true
+    ///     when: 'env_CI == "true"'
+    ///   - inject: .claude/context/terraform.md
+    ///     when: 'ends_with(tool_input_file_path, ".tf")'
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+
+    /// Steps run in order when `actions:` is a list (all other fields unset)
+    ///
+    /// Example YAML usage:
+    /// ```yaml
+    /// actions:
+    ///   - validate_expr: 'has_field("file_path")'
+    ///   - inject_inline: "Follow the migration guide."
+    ///   - webhook: https://hooks.example.com/rulez
+    ///     continue_on_failure: true
+    /// ```
+    #[serde(skip)]
+    pub pipeline: Option<Vec<ActionStep>>,
+}
+
+/// One step of an `actions:` list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActionStep {
+    /// The step's actions, with the same fields as a single `actions:` map
+    #[serde(flatten)]
+    pub actions: Actions,
+
+    /// Keep going when this step blocks or fails
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub continue_on_failure: bool,
+}
+
+/// (De)serialize `Rule::actions` as either a map or a list of steps
+mod actions_pipeline {
+    use std::fmt;
+
+    use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+    use serde::de::{MapAccess, SeqAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{ActionStep, Actions};
+
+    pub fn serialize<S: Serializer>(actions: &Actions, serializer: S) -> Result<S::Ok, S::Error> {
+        match actions.pipeline {
+            Some(ref steps) => steps.serialize(serializer),
+            None => actions.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Actions, D::Error> {
+        struct ActionsVisitor;
+
+        impl<'de> Visitor<'de> for ActionsVisitor {
+            type Value = Actions;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of actions or a list of action steps")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Actions, A::Error> {
+                Actions::deserialize(MapAccessDeserializer::new(map))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Actions, A::Error> {
+                let steps = Vec::<ActionStep>::deserialize(SeqAccessDeserializer::new(seq))?;
+                Ok(Actions {
+                    pipeline: Some(steps),
+                    ..Actions::default()
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ActionsVisitor)
+    }
 }
 
 impl Actions {
+    /// The action sets to run: each pipeline step, or just these actions
+    pub fn steps(&self) -> Box<dyn Iterator<Item = &Actions> + '_> {
+        match self.pipeline {
+            Some(ref steps) => Box::new(steps.iter().map(|step| &step.actions)),
+            None => Box::new(std::iter::once(self)),
+        }
+    }
+
     /// Get the script path from run action (if present)
     pub fn script_path(&self) -> Option<&str> {
         self.run.as_ref().map(|r| r.script_path())
     }
 
     /// Get the trust level from run action (defaults to Local)
+    ///
+    /// For a pipeline, the first step with a `run` action decides.
     pub fn trust_level(&self) -> Option<TrustLevel> {
-        self.run.as_ref().map(|r| r.trust_level())
+        self.steps()
+            .find_map(|actions| actions.run.as_ref())
+            .map(RunAction::trust_level)
+    }
+
+    /// Name of the first action that spawns a process, if any
+    ///
+    /// These actions are unavailable when the `process` feature is disabled.
+    #[allow(dead_code)] // Checked only without the `process` feature
+    pub fn process_action(&self) -> Option<&'static str> {
+        if let Some(ref steps) = self.pipeline {
+            steps.iter().find_map(|step| step.actions.process_action())
+        } else if self.inline_script.is_some() {
+            Some("inline_script")
+        } else if self.inject_command.is_some() {
+            Some("inject_command")
+        } else if self
+            .summarize
+            .as_ref()
+            .is_some_and(|summarize| summarize.command.is_some())
+        {
+            Some("summarize")
+        } else if self.run.is_some() {
+            Some("run")
+        } else if self.webhook.is_some() {
+            Some("webhook")
+        } else {
+            None
+        }
     }
 }
 
@@ -543,9 +1591,11 @@ pub struct RuleMetadata {
     #[serde(default)]
     pub priority: i32,
 
-    /// Script execution timeout in seconds
-    #[serde(default = "default_timeout")]
-    pub timeout: u32,
+    /// Script execution timeout in seconds (legacy; prefer `timeout_ms`)
+    ///
+    /// Unset falls through to `settings.script_timeouts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
 
     /// Whether this rule is enabled
     #[serde(default = "default_enabled")]
@@ -734,13 +1784,56 @@ trust: verified
     }
 
     #[test]
-    fn test_run_action_extended_without_trust() {
-        let yaml = r"
-script: .claude/validators/check.py
-";
-        let action: RunAction = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(action.script_path(), ".claude/validators/check.py");
-        assert_eq!(action.trust_level(), TrustLevel::Local); // Default
+    fn test_run_action_extended_without_trust() {
+        let yaml = r"
+script: .claude/validators/check.py
+";
+        let action: RunAction = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(action.script_path(), ".claude/validators/check.py");
+        assert_eq!(action.trust_level(), TrustLevel::Local); // Default
+    }
+
+    #[test]
+    fn test_run_action_extended_with_sha256() {
+        let yaml = r"
+script: .claude/validators/check.py
+sha256: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855
+";
+        let action: RunAction = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            action.sha256(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+
+        let simple: RunAction = serde_yaml::from_str("check.sh").unwrap();
+        assert!(simple.sha256().is_none());
+    }
+
+    #[test]
+    fn test_trust_level_ordering() {
+        assert!(TrustLevel::Verified.satisfies(TrustLevel::Local));
+        assert!(TrustLevel::Local.satisfies(TrustLevel::Local));
+        assert!(TrustLevel::Untrusted.satisfies(TrustLevel::Untrusted));
+        assert!(!TrustLevel::Untrusted.satisfies(TrustLevel::Local));
+        assert!(!TrustLevel::Local.satisfies(TrustLevel::Verified));
+    }
+
+    #[test]
+    fn test_run_action_effective_trust_requires_pin() {
+        let unpinned: RunAction =
+            serde_yaml::from_str("script: check.sh\ntrust: verified").unwrap();
+        assert_eq!(unpinned.trust_level(), TrustLevel::Verified);
+        assert_eq!(unpinned.effective_trust_level(), TrustLevel::Local);
+
+        let pinned: RunAction = serde_yaml::from_str(
+            "script: check.sh\ntrust: verified\nsha256: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        )
+        .unwrap();
+        assert_eq!(pinned.effective_trust_level(), TrustLevel::Verified);
+
+        let untrusted: RunAction =
+            serde_yaml::from_str("script: check.sh\ntrust: untrusted").unwrap();
+        assert_eq!(untrusted.effective_trust_level(), TrustLevel::Untrusted);
     }
 
     #[test]
@@ -857,21 +1950,51 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Enforce);
     }
@@ -891,21 +2014,51 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: Some(PolicyMode::Audit),
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Audit);
     }
@@ -925,21 +2078,51 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_priority(), 0);
     }
@@ -959,21 +2142,51 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: Some(100),
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_priority(), 100);
     }
@@ -993,25 +2206,55 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: None,
             governance: None,
             metadata: Some(RuleMetadata {
                 priority: 50,
-                timeout: 5,
+                timeout: Some(5),
                 enabled: true,
             }),
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_priority(), 50);
     }
@@ -1031,25 +2274,55 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: Some(100), // New field takes precedence
             governance: None,
             metadata: Some(RuleMetadata {
                 priority: 50, // Legacy field
-                timeout: 5,
+                timeout: Some(5),
                 enabled: true,
             }),
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         };
         assert_eq!(rule.effective_priority(), 100);
     }
@@ -1123,21 +2396,51 @@ reason: Code quality
                 prompt_match: None,
                 require_fields: None,
                 field_types: None,
+                response_match: None,
+                tool_failed: None,
+                message_match: None,
+                compact_trigger: None,
+                transcript_match: None,
+                pre_rules: None,
+                users: None,
+                teams: None,
+                custom: None,
             },
             actions: Actions {
                 inject: None,
                 inject_inline: None,
                 inject_command: None,
+                summarize: None,
+                inject_as: None,
+                suppress_output: None,
                 run: None,
                 block: None,
                 block_if_match: None,
                 validate_expr: None,
                 inline_script: None,
+                script_rhai: None,
+                scan_secrets: None,
+                scan_pii: None,
+                permission_decision: None,
+                system_message: None,
+                webhook: None,
+                rewrite_prompt: None,
+                custom: None,
+                when: None,
+                pipeline: None,
             },
             mode: None,
             priority: Some(priority),
             governance: None,
             metadata: None,
+            log: None,
+            deprecated: None,
+            replacement: None,
+            remove_after: None,
+            inject_priority: None,
+            context_weight: None,
+            fail_open: None,
+            timeout_ms: None,
         }
     }
 
@@ -1303,7 +2606,10 @@ inject_command: "git branch --show-current"
 "#;
         let actions: Actions = serde_yaml::from_str(yaml).unwrap();
         assert!(actions.inject_command.is_some());
-        assert_eq!(actions.inject_command.unwrap(), "git branch --show-current");
+        assert_eq!(
+            actions.inject_command.unwrap().command(),
+            "git branch --show-current"
+        );
     }
 
     #[test]
@@ -1321,7 +2627,7 @@ actions:
         assert_eq!(rule.name, "branch-context");
         assert!(rule.actions.inject_command.is_some());
         assert_eq!(
-            rule.actions.inject_command.unwrap(),
+            rule.actions.inject_command.unwrap().command(),
             "git branch --show-current"
         );
     }
@@ -1334,11 +2640,24 @@ inject_command: "cat package.json | jq .name"
         let actions: Actions = serde_yaml::from_str(yaml).unwrap();
         assert!(actions.inject_command.is_some());
         assert_eq!(
-            actions.inject_command.unwrap(),
+            actions.inject_command.unwrap().command(),
             "cat package.json | jq .name"
         );
     }
 
+    #[test]
+    fn test_inject_command_extended_yaml() {
+        let yaml = r#"
+inject_command:
+  command: "npm ls --depth=0"
+  cwd: frontend
+"#;
+        let actions: Actions = serde_yaml::from_str(yaml).unwrap();
+        let inject_command = actions.inject_command.unwrap();
+        assert_eq!(inject_command.command(), "npm ls --depth=0");
+        assert_eq!(inject_command.cwd(), Some("frontend"));
+    }
+
     // =========================================================================
     // Phase 3: enabled_when Tests
     // =========================================================================
@@ -2019,6 +3338,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2041,6 +3367,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2065,6 +3398,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2089,6 +3429,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2113,6 +3460,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2138,6 +3492,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2162,6 +3523,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2188,6 +3556,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2214,6 +3589,13 @@ mod event_details_tests {
             permission_mode: None,
             tool_use_id: None,
             prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -2221,6 +3603,46 @@ mod event_details_tests {
             if tool_name == Some("FutureTool".to_string())));
     }
 
+    #[test]
+    fn test_event_type_matches_name_aliases() {
+        assert!(EventType::AfterAgent.matches_name("SubagentStop"));
+        assert!(EventType::AfterAgent.matches_name("AfterAgent"));
+        assert!(EventType::BeforeAgent.matches_name("SubagentStart"));
+        assert!(!EventType::Stop.matches_name("SubagentStop"));
+        assert!(EventType::Stop.is_stop() && EventType::AfterAgent.is_stop());
+        assert!(!EventType::SessionEnd.is_stop());
+    }
+
+    #[test]
+    fn test_event_tool_response_text_and_failure() {
+        let mut event: Event = serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PostToolUse",
+            "session_id": "s",
+            "tool_name": "Bash",
+            "tool_response": {
+                "stdout": "running 3 tests",
+                "stderr": "error[E0308]: mismatched types",
+                "exit_code": 101
+            }
+        }))
+        .unwrap();
+        let text = event.tool_response_text().unwrap();
+        assert!(text.contains("running 3 tests"));
+        assert!(text.contains("mismatched types"));
+        assert!(event.tool_failed());
+
+        event.tool_response = Some(serde_json::json!({"success": true, "filePath": "a.rs"}));
+        assert!(!event.tool_failed());
+
+        event.tool_response = Some(serde_json::json!("plain output"));
+        assert_eq!(event.tool_response_text().as_deref(), Some("plain output"));
+        assert!(!event.tool_failed());
+
+        event.tool_response = None;
+        assert!(event.tool_response_text().is_none());
+        assert!(!event.tool_failed());
+    }
+
     #[test]
     fn test_response_summary_from_response() {
         let response = Response {
@@ -2228,6 +3650,12 @@ mod event_details_tests {
             context: Some("injected context".to_string()),
             reason: Some("for testing".to_string()),
             timing: None,
+            system_message: None,
+            suppress_output: None,
+            hook_specific_output: None,
+            updated_prompt: None,
+            context_blocks: Vec::new(),
+            stderr: None,
         };
 
         let summary = ResponseSummary::from_response(&response);
@@ -2250,10 +3678,21 @@ mod event_details_tests {
         let config = DebugConfig::new(false, false);
         assert!(!config.enabled);
     }
-}
 
-fn default_timeout() -> u32 {
-    5
+    #[test]
+    fn test_parse_debug_level() {
+        assert_eq!(parse_debug_level("1"), Some("debug"));
+        assert_eq!(parse_debug_level("Debug"), Some("debug"));
+        assert_eq!(parse_debug_level("trace"), Some("trace"));
+        assert_eq!(parse_debug_level("0"), None);
+        assert_eq!(parse_debug_level(""), None);
+    }
+
+    #[test]
+    fn test_debug_config_with_dry_run() {
+        assert!(DebugConfig::default().with_dry_run(true).dry_run);
+        assert!(!DebugConfig::default().with_dry_run(false).dry_run);
+    }
 }
 
 fn default_enabled() -> bool {
@@ -2310,6 +3749,87 @@ pub struct Event {
     /// User prompt text (sent by Claude Code on UserPromptSubmit events)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt: Option<String>,
+
+    /// Tool result (sent by Claude Code on PostToolUse events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_response: Option<serde_json::Value>,
+
+    /// True when the agent is already continuing because a Stop hook
+    /// blocked it (sent by Claude Code on Stop/SubagentStop events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_hook_active: Option<bool>,
+
+    /// What started compaction, `manual` or `auto` (sent by Claude Code on
+    /// PreCompact events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<String>,
+
+    /// Instructions the user passed to `/compact` (PreCompact events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<String>,
+
+    /// Notification text (sent by Claude Code on Notification events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// RuleZ's PreToolUse decision for this `tool_use_id` (filled in by
+    /// RuleZ on PostToolUse events, never read from the client)
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub pre_tool_use: Option<crate::session::ToolUseRecord>,
+
+    /// Who is running the agent and their teams (filled in by RuleZ from
+    /// `user_id` and `settings.identity`, never read from the client)
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub identity: Option<crate::identity::Identity>,
+}
+
+impl Event {
+    /// Text of the tool response, for `response_match`
+    ///
+    /// A string response is used as-is; for structured responses every
+    /// string value (stdout, stderr, error, file content, ...) is joined
+    /// with newlines.
+    pub fn tool_response_text(&self) -> Option<String> {
+        fn collect<'a>(value: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+            match value {
+                serde_json::Value::String(text) => out.push(text),
+                serde_json::Value::Array(items) => items.iter().for_each(|v| collect(v, out)),
+                serde_json::Value::Object(map) => map.values().for_each(|v| collect(v, out)),
+                _ => {}
+            }
+        }
+
+        let mut parts = Vec::new();
+        collect(self.tool_response.as_ref()?, &mut parts);
+        Some(parts.join("\n"))
+    }
+
+    /// Whether the tool response reports a failure
+    ///
+    /// Recognizes `success: false`, `is_error`/`isError: true`,
+    /// `interrupted: true`, a non-zero `exit_code`/`exitCode`, and a
+    /// non-empty `error`.
+    pub fn tool_failed(&self) -> bool {
+        let Some(response) = self.tool_response.as_ref() else {
+            return false;
+        };
+        let flag = |key: &str| response.get(key).and_then(serde_json::Value::as_bool);
+        let exit_code = ["exit_code", "exitCode"]
+            .iter()
+            .find_map(|key| response.get(key).and_then(serde_json::Value::as_i64));
+        let error = response
+            .get("error")
+            .is_some_and(|e| !e.is_null() && e.as_str() != Some(""));
+
+        flag("success") == Some(false)
+            || flag("is_error") == Some(true)
+            || flag("isError") == Some(true)
+            || flag("interrupted") == Some(true)
+            || exit_code.is_some_and(|code| code != 0)
+            || error
+    }
 }
 
 /// Supported hook event types
@@ -2339,6 +3859,45 @@ pub enum EventType {
     Setup,
 }
 
+impl EventType {
+    /// Every event type, in declaration order
+    pub const ALL: [EventType; 16] = [
+        EventType::PreToolUse,
+        EventType::PostToolUse,
+        EventType::PermissionRequest,
+        EventType::UserPromptSubmit,
+        EventType::BeforeAgent,
+        EventType::AfterAgent,
+        EventType::BeforeModel,
+        EventType::AfterModel,
+        EventType::BeforeToolSelection,
+        EventType::SessionStart,
+        EventType::SessionEnd,
+        EventType::PreCompact,
+        EventType::Stop,
+        EventType::PostToolUseFailure,
+        EventType::Notification,
+        EventType::Setup,
+    ];
+
+    /// Whether a name from `matchers.operations` refers to this event type
+    ///
+    /// Accepts the canonical name and the Claude Code aliases
+    /// (`SubagentStart`, `SubagentStop`).
+    pub fn matches_name(&self, name: &str) -> bool {
+        match self {
+            EventType::BeforeAgent if name == "SubagentStart" => true,
+            EventType::AfterAgent if name == "SubagentStop" => true,
+            _ => self.to_string() == name,
+        }
+    }
+
+    /// Stop and SubagentStop events, where a block keeps the agent working
+    pub fn is_stop(&self) -> bool {
+        matches!(self, EventType::Stop | EventType::AfterAgent)
+    }
+}
+
 impl std::fmt::Display for EventType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -2383,6 +3942,65 @@ pub struct Response {
     /// Performance metrics
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timing: Option<Timing>,
+
+    /// Message shown to the user in the client UI
+    #[serde(rename = "systemMessage", skip_serializing_if = "Option::is_none")]
+    pub system_message: Option<String>,
+
+    /// Hide the hook's stdout from the transcript
+    #[serde(rename = "suppressOutput", skip_serializing_if = "Option::is_none")]
+    pub suppress_output: Option<bool>,
+
+    /// Event-specific fields from Claude Code's hook output schema
+    #[serde(rename = "hookSpecificOutput", skip_serializing_if = "Option::is_none")]
+    pub hook_specific_output: Option<HookSpecificOutput>,
+
+    /// Prompt after `rewrite_prompt` actions (UserPromptSubmit events)
+    #[serde(rename = "updatedPrompt", skip_serializing_if = "Option::is_none")]
+    pub updated_prompt: Option<String>,
+
+    /// Size of each rule's injected context, for the audit log (not sent to the client)
+    #[serde(skip)]
+    pub context_blocks: Vec<ContextEstimate>,
+
+    /// Text for the hook's stderr (`inject_as: stderr`), shown in the transcript
+    #[serde(skip)]
+    pub stderr: Option<String>,
+}
+
+/// Claude Code's `hookSpecificOutput` object
+///
+/// `permissionDecision` applies to `PreToolUse`; `additionalContext` to
+/// `UserPromptSubmit`, `SessionStart`, and `PostToolUse`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HookSpecificOutput {
+    /// Event this output answers, e.g. `PreToolUse`
+    pub hook_event_name: String,
+
+    /// Permission decision for the tool call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_decision: Option<PermissionDecision>,
+
+    /// Explanation shown with the permission decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_decision_reason: Option<String>,
+
+    /// Context added to the model's input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_context: Option<String>,
+}
+
+impl HookSpecificOutput {
+    /// Create an empty output for an event
+    pub fn new(event_type: EventType) -> Self {
+        Self {
+            hook_event_name: event_type.to_string(),
+            permission_decision: None,
+            permission_decision_reason: None,
+            additional_context: None,
+        }
+    }
 }
 
 /// Gemini CLI output structure for hook responses
@@ -2449,6 +4067,29 @@ pub struct CopilotHookResponse {
     pub tool_input: Option<serde_json::Value>,
 }
 
+/// Cursor agent hook output structure
+///
+/// `before*` hooks answer with `permission`; `beforeSubmitPrompt` answers
+/// with `continue`. Observational hooks (`afterFileEdit`, `stop`) get `{}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CursorHookResponse {
+    /// Decision for shell, MCP, and file-read hooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<PermissionDecision>,
+
+    /// Whether the prompt may be submitted (`beforeSubmitPrompt`)
+    #[serde(rename = "continue", skip_serializing_if = "Option::is_none")]
+    pub continue_: Option<bool>,
+
+    /// Message shown to the user in Cursor
+    #[serde(rename = "userMessage", skip_serializing_if = "Option::is_none")]
+    pub user_message: Option<String>,
+
+    /// Message sent to the agent
+    #[serde(rename = "agentMessage", skip_serializing_if = "Option::is_none")]
+    pub agent_message: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Timing {
     /// Total processing time in milliseconds
@@ -2458,9 +4099,23 @@ pub struct Timing {
     pub rules_evaluated: usize,
 }
 
+/// Current audit log schema version (see `docs/log-schema.md`)
+///
+/// Bumped only for changes that would break an existing consumer; new
+/// optional fields are added within a version.
+pub const LOG_SCHEMA_VERSION: u32 = 2;
+
+fn legacy_log_schema_version() -> u32 {
+    1
+}
+
 /// Structured audit log record
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LogEntry {
+    /// Log schema version; entries written before versioning read as 1
+    #[serde(default = "legacy_log_schema_version")]
+    pub schema_version: u32,
+
     /// ISO 8601 timestamp with microsecond precision
     pub timestamp: DateTime<Utc>,
 
@@ -2513,10 +4168,18 @@ pub struct LogEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<i32>,
 
-    /// Decision outcome (Allowed, Blocked, Warned, Audited)
+    /// Decision outcome (Allowed, Blocked, Warned, Audited, Bypassed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub decision: Option<Decision>,
 
+    /// Why RuleZ was disabled (`decision: bypassed` only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bypass_reason: Option<String>,
+
+    /// Set when `--dry-run` let the event through regardless of `decision`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+
     /// Governance metadata from the primary matched rule
     #[serde(skip_serializing_if = "Option::is_none")]
     pub governance: Option<GovernanceMetadata>,
@@ -2524,14 +4187,76 @@ pub struct LogEntry {
     /// Trust level of validator script (if run action was executed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trust_level: Option<TrustLevel>,
+
+    /// Session totals, on SessionEnd entries when `session_summary` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_summary: Option<crate::session::SessionSummary>,
+
+    /// Tool call identifier, pairing PreToolUse and PostToolUse entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+
+    /// The PreToolUse decision, on PostToolUse entries of the same tool call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_tool_use: Option<crate::session::ToolUseRecord>,
+
+    /// Set when the entry was sampled: it stands for about this many events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+
+    /// The detected anomaly, on `Anomaly` entries (see `settings.anomaly_detection`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anomaly: Option<crate::anomaly::Anomaly>,
+
+    /// Waivers that suppressed matched rules for this event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waivers: Option<Vec<crate::waivers::AppliedWaiver>>,
+
+    /// Profile resolved for the user (see `settings.profiles`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Notices for deprecated rules that matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecations: Option<Vec<String>>,
+
+    /// SHA-256 of the previous log line, when the log is hash-chained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+
+    /// HMAC-SHA256 of the line, when chained entries are signed. Must stay
+    /// the last serialized field (see `log_chain`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+
+    /// Time spent on each evaluated rule, for trace export (not written to the log file)
+    #[serde(skip)]
+    pub rule_timings: Vec<RuleTiming>,
+}
+
+/// Time one rule took during an evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleTiming {
+    /// Rule name
+    pub rule: String,
+
+    /// When work on the rule started, in microseconds after processing began
+    pub offset_us: u64,
+
+    /// Time spent matching the rule and running its actions, in microseconds
+    pub duration_us: u64,
 }
 
 /// Result of rule evaluation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Outcome {
+    // Schema 1 entries may use the capitalized spelling
+    #[serde(alias = "Allow")]
     Allow,
+    #[serde(alias = "Block")]
     Block,
+    #[serde(alias = "Inject")]
     Inject,
 }
 
@@ -2543,6 +4268,27 @@ pub struct LogTiming {
 
     /// Rules checked
     pub rules_evaluated: usize,
+
+    /// Where the time went, when processing exceeded `settings.latency_budget_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub over_budget: Option<LatencyOverrun>,
+}
+
+/// Breakdown of an event that took longer than the latency budget
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LatencyOverrun {
+    /// Configured budget in milliseconds
+    pub budget_ms: u64,
+
+    /// Time spent loading the config, in microseconds
+    pub config_load_us: u64,
+
+    /// Rule that took longest to match and run its actions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slowest_rule: Option<String>,
+
+    /// Time spent on the slowest rule, in microseconds
+    pub slowest_rule_us: u64,
 }
 
 /// Additional log context
@@ -2555,6 +4301,28 @@ pub struct LogMetadata {
     /// Script execution results
     #[serde(skip_serializing_if = "Option::is_none")]
     pub validator_output: Option<String>,
+
+    /// Estimated tokens of all injected context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_tokens: Option<usize>,
+
+    /// Injected context per rule, with estimated tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_blocks: Option<Vec<ContextEstimate>>,
+}
+
+/// Size of one rule's injected context
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextEstimate {
+    /// Rule that injected the context
+    pub rule: String,
+    /// Bytes injected, after any `max_context_bytes` cut
+    pub bytes: usize,
+    /// Estimated tokens (see `context_budget::estimate_tokens`)
+    pub tokens: usize,
+    /// Other rules that injected the same content, merged into this block
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_injected_by: Vec<String>,
 }
 
 // =============================================================================
@@ -2625,6 +4393,10 @@ pub struct ResponseSummary {
     /// Length of injected context (if any)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_length: Option<usize>,
+
+    /// Permission decision sent to the client (if any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_decision: Option<PermissionDecision>,
 }
 
 /// Per-rule evaluation details (debug mode only)
@@ -2671,6 +4443,42 @@ pub struct MatcherResults {
     /// Whether field validation (require_fields/field_types) passed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub field_validation_matched: Option<bool>,
+
+    /// Whether response_match regex matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_match_matched: Option<bool>,
+
+    /// Whether the tool_failed matcher matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_failed_matched: Option<bool>,
+
+    /// Whether message_match regex matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_match_matched: Option<bool>,
+
+    /// Whether compact_trigger matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_trigger_matched: Option<bool>,
+
+    /// Whether transcript_match matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript_match_matched: Option<bool>,
+
+    /// Whether pre_rules matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_rules_matched: Option<bool>,
+
+    /// Whether the user matched `users`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users_matched: Option<bool>,
+
+    /// Whether one of the user's teams matched `teams`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams_matched: Option<bool>,
+
+    /// Whether each custom matcher matched, by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_matched: Option<std::collections::BTreeMap<String, bool>>,
 }
 
 /// Debug mode configuration
@@ -2678,16 +4486,55 @@ pub struct MatcherResults {
 pub struct DebugConfig {
     /// Whether debug logging is enabled
     pub enabled: bool,
+
+    /// Evaluate and log normally, but always allow (`--dry-run` or `RULEZ_DRY_RUN=1`)
+    pub dry_run: bool,
 }
 
 impl DebugConfig {
     /// Create a new DebugConfig from CLI flag and config setting
     pub fn new(cli_flag: bool, config_setting: bool) -> Self {
-        let enabled = cli_flag || std::env::var("CCH_DEBUG_LOGS").is_ok() || config_setting;
-        Self { enabled }
+        let enabled = cli_flag
+            || std::env::var("CCH_DEBUG_LOGS").is_ok()
+            || debug_env().is_some()
+            || config_setting;
+        Self {
+            enabled,
+            dry_run: dry_run_env(),
+        }
+    }
+
+    /// Also dry-run when `flag` is set (the `--dry-run` CLI flag)
+    #[must_use]
+    pub fn with_dry_run(mut self, flag: bool) -> Self {
+        self.dry_run |= flag;
+        self
+    }
+}
+
+/// Tracing level requested by `RULEZ_DEBUG`, if debugging is on
+///
+/// `1`, `true`, `yes` or `debug` mean `debug`; `trace` means `trace`.
+pub fn debug_env() -> Option<&'static str> {
+    std::env::var("RULEZ_DEBUG")
+        .ok()
+        .and_then(|value| parse_debug_level(&value))
+}
+
+fn parse_debug_level(value: &str) -> Option<&'static str> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "trace" => Some("trace"),
+        "debug" => Some("debug"),
+        other if crate::kill_switch::is_set(other) => Some("debug"),
+        _ => None,
     }
 }
 
+/// Whether `RULEZ_DRY_RUN` requests a dry run (`1`, `true` or `yes`)
+pub fn dry_run_env() -> bool {
+    std::env::var("RULEZ_DRY_RUN").is_ok_and(|value| crate::kill_switch::is_set(&value))
+}
+
 impl EventDetails {
     /// Extract typed details from an Event
     pub fn extract(event: &Event) -> Self {
@@ -2792,6 +4639,10 @@ impl ResponseSummary {
             continue_: response.continue_,
             reason: response.reason.clone(),
             context_length: response.context.as_ref().map(|c| c.len()),
+            permission_decision: response
+                .hook_specific_output
+                .as_ref()
+                .and_then(|output| output.permission_decision),
         }
     }
 }
@@ -2800,7 +4651,7 @@ impl Default for RuleMetadata {
     fn default() -> Self {
         Self {
             priority: 0,
-            timeout: default_timeout(),
+            timeout: None,
             enabled: default_enabled(),
         }
     }
@@ -2811,6 +4662,32 @@ impl Default for RuleMetadata {
 // =============================================================================
 
 impl Rule {
+    /// Whether the rule is marked `deprecated: true`
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated == Some(true)
+    }
+
+    /// Deprecation notice for logs and lint, if the rule is deprecated
+    pub fn deprecation_notice(&self) -> Option<String> {
+        if !self.is_deprecated() {
+            return None;
+        }
+        let replacement = self
+            .replacement
+            .as_ref()
+            .map(|r| format!("; use '{}' instead", r))
+            .unwrap_or_default();
+        let removal = self
+            .remove_after
+            .as_ref()
+            .map(|date| format!(" (removal after {})", date))
+            .unwrap_or_default();
+        Some(format!(
+            "Rule '{}' is deprecated{}{}",
+            self.name, replacement, removal
+        ))
+    }
+
     /// Get the effective policy mode (defaults to Enforce)
     #[allow(dead_code)] // Used in Phase 2.1-T05 (mode-based action execution)
     pub fn effective_mode(&self) -> PolicyMode {
@@ -2826,6 +4703,22 @@ impl Rule {
             .unwrap_or(0)
     }
 
+    /// Get the effective injection order (defaults to 0)
+    pub fn effective_inject_priority(&self) -> i32 {
+        self.inject_priority.unwrap_or(0)
+    }
+
+    /// Whether errors running this rule's actions allow the operation
+    /// (defaults to `settings.fail_open`)
+    pub fn effective_fail_open(&self, settings_fail_open: bool) -> bool {
+        self.fail_open.unwrap_or(settings_fail_open)
+    }
+
+    /// Get the effective context weight (defaults to 1)
+    pub fn effective_context_weight(&self) -> u32 {
+        self.context_weight.unwrap_or(1)
+    }
+
     /// Check if the rule is enabled
     /// Uses legacy metadata.enabled field, defaults to true
     #[allow(dead_code)] // Used in Phase 2.1-T05 (mode-based action execution)
@@ -2853,6 +4746,12 @@ impl Response {
             context: None,
             reason: None,
             timing: None,
+            system_message: None,
+            suppress_output: None,
+            hook_specific_output: None,
+            updated_prompt: None,
+            context_blocks: Vec::new(),
+            stderr: None,
         }
     }
 
@@ -2863,6 +4762,12 @@ impl Response {
             context: None,
             reason: Some(reason.into()),
             timing: None,
+            system_message: None,
+            suppress_output: None,
+            hook_specific_output: None,
+            updated_prompt: None,
+            context_blocks: Vec::new(),
+            stderr: None,
         }
     }
 
@@ -2873,6 +4778,12 @@ impl Response {
             context: Some(context.into()),
             reason: None,
             timing: None,
+            system_message: None,
+            suppress_output: None,
+            hook_specific_output: None,
+            updated_prompt: None,
+            context_blocks: Vec::new(),
+            stderr: None,
         }
     }
 }
@@ -3089,6 +5000,7 @@ actions:
         assert!(rule.actions.validate_expr.is_none());
 
         let script = rule.actions.inline_script.unwrap();
+        let script = script.code();
         assert!(script.contains("#!/bin/bash"));
         assert!(script.contains("exit 0"));
     }
@@ -3142,9 +5054,188 @@ actions:
         assert!(rule.actions.inline_script.is_some());
 
         let script = rule.actions.inline_script.unwrap();
+        let script = script.code();
         assert!(script.contains("#!/bin/bash"));
         assert!(script.contains("set -e"));
         assert!(script.contains("jq -r"));
         assert!(script.contains("--force"));
     }
+
+    #[test]
+    fn test_actions_inline_script_extended_format() {
+        let yaml = r"
+name: test-inline-script-shell
+matchers:
+  tools: [Bash]
+actions:
+  inline_script:
+    shell: python
+    code: |
+      import sys
+      sys.exit(0)
+";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let script = rule.actions.inline_script.unwrap();
+        assert_eq!(script.resolve_shell(), Some(ScriptShell::Python));
+        assert!(script.code().contains("sys.exit(0)"));
+    }
+
+    #[test]
+    fn test_script_shell_from_shebang() {
+        assert_eq!(
+            ScriptShell::from_shebang("#!/usr/bin/env python3\nprint(1)"),
+            Some(ScriptShell::Python)
+        );
+        assert_eq!(
+            ScriptShell::from_shebang("#!/bin/bash\nexit 0"),
+            Some(ScriptShell::Bash)
+        );
+        assert_eq!(
+            ScriptShell::from_shebang("#!/bin/sh\nexit 0"),
+            Some(ScriptShell::Sh)
+        );
+        assert_eq!(
+            ScriptShell::from_shebang("#!/usr/bin/env pwsh\nexit 0"),
+            Some(ScriptShell::Powershell)
+        );
+        assert_eq!(ScriptShell::from_shebang("exit 0"), None);
+    }
+
+    #[test]
+    fn test_script_shell_parse_shebang() {
+        assert_eq!(
+            ScriptShell::parse_shebang("#!/bin/bash -e\nexit 0"),
+            Some(("/bin/bash".to_string(), vec!["-e".to_string()]))
+        );
+        assert_eq!(
+            ScriptShell::parse_shebang("#!/usr/bin/env -S python3 -u\n"),
+            Some(("python3".to_string(), vec!["-u".to_string()]))
+        );
+        assert_eq!(ScriptShell::parse_shebang("exit 0"), None);
+    }
+
+    #[test]
+    fn test_script_shell_inline_args() {
+        let args = vec!["a b".to_string(), "it's".to_string()];
+        assert_eq!(
+            ScriptShell::Bash.inline_args("exit 0", &args).unwrap(),
+            vec!["-c", "exit 0", "rulez-inline", "a b", "it's"]
+        );
+        assert_eq!(
+            ScriptShell::Python.inline_args("pass", &args).unwrap(),
+            vec!["-c", "pass", "a b", "it's"]
+        );
+        let pwsh = ScriptShell::Powershell
+            .inline_args("exit 0", &args)
+            .unwrap();
+        assert_eq!(pwsh.last().unwrap(), "& {\nexit 0\n} 'a b' 'it''s'");
+        assert!(ScriptShell::Cmd.inline_args("exit /b 0", &args).is_none());
+    }
+
+    #[test]
+    fn test_inline_script_simple_resolves_per_platform() {
+        let script = InlineScript::Simple("#!/bin/bash\nexit 0".to_string());
+        if cfg!(windows) {
+            assert_eq!(script.resolve_shell(), Some(ScriptShell::Bash));
+            assert_eq!(
+                InlineScript::Simple("exit 0".to_string()).resolve_shell(),
+                Some(ScriptShell::Powershell)
+            );
+        } else {
+            assert_eq!(script.resolve_shell(), None);
+        }
+    }
+
+    #[test]
+    fn test_run_action_extended_with_environment() {
+        let yaml = r#"
+script: .claude/validators/check.py
+trust: local
+env_clear: true
+env:
+  RULEZ_SESSION: "{{session_id}}"
+args: ["--file", "{{tool_input.file_path}}"]
+"#;
+        let run: RunAction = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(run.script_path(), ".claude/validators/check.py");
+
+        let environment = run.environment().unwrap();
+        assert!(environment.env_clear);
+        assert_eq!(environment.env["RULEZ_SESSION"], "{{session_id}}");
+        assert_eq!(environment.args, vec!["--file", "{{tool_input.file_path}}"]);
+
+        let simple: RunAction = serde_yaml::from_str("check.sh").unwrap();
+        assert!(simple.environment().is_none());
+    }
+
+    #[test]
+    fn test_inline_script_extended_without_shell() {
+        let yaml = r"
+code: |
+  #!/bin/sh
+  exit 0
+env_clear: true
+";
+        let script: InlineScript = serde_yaml::from_str(yaml).unwrap();
+        assert!(script.environment().unwrap().env_clear);
+        if !cfg!(windows) {
+            assert_eq!(script.resolve_shell(), None);
+        }
+    }
+
+    #[test]
+    fn test_actions_pipeline_deserialization() {
+        let yaml = r#"
+name: validate-inject-notify
+matchers:
+  tools: [Write]
+actions:
+  - validate_expr: 'has_field("file_path")'
+  - inject_inline: "Follow the migration guide."
+  - webhook: https://hooks.example.com/rulez
+    continue_on_failure: true
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let steps = rule.actions.pipeline.as_ref().unwrap();
+        assert_eq!(steps.len(), 3);
+        assert!(steps[0].actions.validate_expr.is_some());
+        assert!(!steps[0].continue_on_failure);
+        assert!(steps[2].continue_on_failure);
+        assert!(rule.actions.inject_inline.is_none());
+        assert_eq!(rule.actions.steps().count(), 3);
+        assert_eq!(rule.actions.process_action(), Some("webhook"));
+
+        // Pipelines survive a serialization round trip (compiled config cache)
+        let json = serde_json::to_string(&rule).unwrap();
+        let round_trip: Rule = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip, rule);
+    }
+
+    #[test]
+    fn test_summarize_command_is_a_process_action() {
+        let yaml = r#"
+name: long-context
+matchers:
+  tools: [Bash]
+actions:
+  inject_inline: "context"
+  summarize:
+    max_bytes: 1024
+"#;
+        let mut rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.actions.process_action(), None);
+
+        rule.actions.summarize.as_mut().unwrap().command = Some("head -c 5".to_string());
+        assert_eq!(rule.actions.process_action(), Some("summarize"));
+    }
+
+    #[test]
+    fn test_actions_map_is_a_single_step() {
+        let rule: Rule =
+            serde_yaml::from_str("name: r\nmatchers: {}\nactions:\n  block: true\n").unwrap();
+        assert!(rule.actions.pipeline.is_none());
+        let steps: Vec<&Actions> = rule.actions.steps().collect();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].block, Some(true));
+    }
 }
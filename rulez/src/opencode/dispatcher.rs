@@ -2,8 +2,7 @@ use anyhow::Result;
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::adapters::opencode::{OpenCodeEvent, translate_response};
-use crate::hooks;
+use crate::adapters::{AdaptedEvent, process_dual_fire};
 use crate::models::{DebugConfig, Response};
 use crate::opencode::audit::{OpenCodeAuditEntry, OpenCodeAuditLogger};
 use crate::opencode::config::OpenCodePluginConfig;
@@ -19,11 +18,12 @@ impl OpenCodeDispatcher {
         Self { config, logger }
     }
 
+    /// Evaluate an OpenCode event, honouring event filters and writing the audit log
     pub async fn dispatch(
         &self,
-        opencode_event: OpenCodeEvent,
+        opencode_event: &AdaptedEvent,
         debug_config: &DebugConfig,
-    ) -> Result<serde_json::Value> {
+    ) -> Result<Response> {
         let start_time = std::time::Instant::now();
         let event_id = Uuid::new_v4().to_string();
 
@@ -33,10 +33,15 @@ impl OpenCodeDispatcher {
             .event_filters
             .contains(&opencode_event.hook_event_name)
         {
-            return Ok(translate_response(&Response::allow(), &opencode_event));
+            return Ok(Response::allow());
         }
 
-        let response = hooks::process_event(opencode_event.event.clone(), debug_config).await?;
+        let response = process_dual_fire(
+            opencode_event.event.clone(),
+            &opencode_event.additional_event_types,
+            debug_config,
+        )
+        .await?;
         let latency_ms = start_time.elapsed().as_millis() as u64;
 
         // Audit logging
@@ -58,6 +63,6 @@ impl OpenCodeDispatcher {
 
         self.logger.log_async(entry).await;
 
-        Ok(translate_response(&response, &opencode_event))
+        Ok(response)
     }
 }
@@ -0,0 +1,250 @@
+//! PII detection for `actions.scan_pii`.
+//!
+//! Scans the text a tool is about to write (the same fields as
+//! `scan_secrets`) and the prompt of `UserPromptSubmit` events for:
+//! - email addresses (RFC 2606 example domains are ignored)
+//! - phone numbers (NANP and `+` international formats)
+//! - national IDs (US Social Security numbers, UK National Insurance numbers)
+//!
+//! Each category maps to a severity (block/warn/audit/off); the most severe
+//! finding decides the outcome. Findings carry a redacted preview only.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::models::{Event, PiiSeverities, PiiSeverity};
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+-]+@([A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,})\b")
+        .expect("valid email pattern")
+});
+
+static PHONE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?x)
+        (?:^|[^\w+])
+        (
+          \+[1-9]\d{0,2}[\s.-]?\(?\d{1,4}\)?(?:[\s.-]?\d{2,4}){2,3}   # international
+          | \(?\b[2-9]\d{2}\)?[\s.-]\d{3}[\s.-]\d{4}                  # NANP
+        )\b",
+    )
+    .expect("valid phone pattern")
+});
+
+static SSN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{3})-(\d{2})-(\d{4})\b").expect("valid SSN pattern"));
+
+static NINO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z] ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b")
+        .expect("valid NINO pattern")
+});
+
+/// Domains reserved for documentation (RFC 2606)
+const EXAMPLE_DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+/// Category of personal data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PiiCategory {
+    Email,
+    Phone,
+    NationalId,
+}
+
+impl std::fmt::Display for PiiCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PiiCategory::Email => write!(f, "email address"),
+            PiiCategory::Phone => write!(f, "phone number"),
+            PiiCategory::NationalId => write!(f, "national ID"),
+        }
+    }
+}
+
+/// PII found in scanned text
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiiFinding {
+    /// What kind of data was found
+    pub category: PiiCategory,
+    /// Severity configured for the category
+    pub severity: PiiSeverity,
+    /// Redacted preview, e.g. `j***@acme.io`
+    pub redacted: String,
+}
+
+impl std::fmt::Display for PiiFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.category, self.redacted)
+    }
+}
+
+/// Scan an event's written content and prompt
+pub fn scan_event(event: &Event, severities: &PiiSeverities) -> Vec<PiiFinding> {
+    let written = event
+        .tool_input
+        .as_ref()
+        .map(crate::secrets::written_text)
+        .unwrap_or_default();
+    written
+        .into_iter()
+        .chain(event.prompt.as_deref())
+        .flat_map(|text| scan_text(text, severities))
+        .collect()
+}
+
+/// Scan a block of text, skipping categories set to `off`
+pub fn scan_text(text: &str, severities: &PiiSeverities) -> Vec<PiiFinding> {
+    let mut findings = Vec::new();
+    let mut push = |category, severity, redacted| {
+        if severity != PiiSeverity::Off {
+            findings.push(PiiFinding {
+                category,
+                severity,
+                redacted,
+            });
+        }
+    };
+
+    for found in EMAIL.captures_iter(text) {
+        let domain = found[1].to_ascii_lowercase();
+        if !EXAMPLE_DOMAINS.contains(&domain.as_str()) {
+            push(
+                PiiCategory::Email,
+                severities.email,
+                redact_email(&found[0]),
+            );
+        }
+    }
+    for found in PHONE.captures_iter(text) {
+        push(PiiCategory::Phone, severities.phone, redact_tail(&found[1]));
+    }
+    for found in SSN.captures_iter(text) {
+        if is_valid_ssn(&found[1], &found[2], &found[3]) {
+            push(
+                PiiCategory::NationalId,
+                severities.national_id,
+                redact_tail(&found[0]),
+            );
+        }
+    }
+    for found in NINO.find_iter(text) {
+        push(
+            PiiCategory::NationalId,
+            severities.national_id,
+            redact_tail(found.as_str()),
+        );
+    }
+
+    findings
+}
+
+/// Most severe finding's severity (Off when there are none)
+pub fn max_severity(findings: &[PiiFinding]) -> PiiSeverity {
+    findings
+        .iter()
+        .map(|f| f.severity)
+        .max()
+        .unwrap_or(PiiSeverity::Off)
+}
+
+/// Redact every PII match in a JSON value in place
+pub fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => *text = redact_text(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        serde_json::Value::Object(map) => map.values_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Replace every PII match in a string with its redacted preview
+fn redact_text(text: &str) -> String {
+    let text = EMAIL.replace_all(text, |caps: &regex::Captures| redact_email(&caps[0]));
+    let text = SSN.replace_all(&text, |caps: &regex::Captures| redact_tail(&caps[0]));
+    let text = NINO.replace_all(&text, |caps: &regex::Captures| redact_tail(&caps[0]));
+    PHONE
+        .replace_all(&text, |caps: &regex::Captures| {
+            caps[0].replace(&caps[1], &redact_tail(&caps[1]))
+        })
+        .into_owned()
+}
+
+/// SSNs never start with 000, 666, or 9xx, and no group is all zeros
+fn is_valid_ssn(area: &str, group: &str, serial: &str) -> bool {
+    area != "000" && area != "666" && !area.starts_with('9') && group != "00" && serial != "0000"
+}
+
+/// Keep the first character of the local part and the domain
+fn redact_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first: String = local.chars().take(1).collect();
+            format!("{first}***@{domain}")
+        }
+        None => "***".to_string(),
+    }
+}
+
+/// Keep only the last two characters
+fn redact_tail(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let tail: String = chars[chars.len().saturating_sub(2)..].iter().collect();
+    format!("***{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(text: &str) -> Vec<PiiCategory> {
+        scan_text(text, &PiiSeverities::default())
+            .iter()
+            .map(|f| f.category)
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_each_category() {
+        assert_eq!(categories("contact jane.doe@acme.io"), [PiiCategory::Email]);
+        assert_eq!(
+            categories("call (415) 555-0132 today"),
+            [PiiCategory::Phone]
+        );
+        assert_eq!(categories("call +44 20 7946 0958"), [PiiCategory::Phone]);
+        assert_eq!(categories("ssn: 123-45-6789"), [PiiCategory::NationalId]);
+        assert_eq!(categories("NI: AB 12 34 56 C"), [PiiCategory::NationalId]);
+    }
+
+    #[test]
+    fn test_ignores_non_pii() {
+        assert!(categories("user@example.com").is_empty());
+        assert!(categories("ssn: 000-12-3456 and 666-12-3456").is_empty());
+        assert!(categories("version 1.2.3, port 8080, 2024-01-15").is_empty());
+        assert!(categories("let x = 12345678;").is_empty());
+    }
+
+    #[test]
+    fn test_severities_and_redaction() {
+        let severities = PiiSeverities {
+            email: PiiSeverity::Audit,
+            phone: PiiSeverity::Off,
+            national_id: PiiSeverity::Block,
+        };
+        let findings = scan_text("jane@acme.io 415-555-0132", &severities);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].redacted, "j***@acme.io");
+        assert_eq!(max_severity(&findings), PiiSeverity::Audit);
+
+        let findings = scan_text("jane@acme.io 123-45-6789", &severities);
+        assert_eq!(max_severity(&findings), PiiSeverity::Block);
+        assert_eq!(findings[1].redacted, "***89");
+    }
+
+    #[test]
+    fn test_redact_value() {
+        let mut value = serde_json::json!({"prompt": "email jane@acme.io, SSN 123-45-6789"});
+        redact_value(&mut value);
+        let text = value.to_string();
+        assert!(!text.contains("jane@acme.io"));
+        assert!(!text.contains("123-45-6789"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Role profiles that tighten or relax the rules per user.
+//!
+//! A profile adjusts the loaded rules for whoever is running the agent:
+//! contractors might have Bash denied outright while staff run every rule
+//! in `warn` mode.
+//!
+//! ```yaml
+//! settings:
+//!   profiles:
+//!     users:
+//!       alice@example.com: staff
+//!       "*@contractor.example.com": contractor
+//!     default: contractor
+//!     definitions:
+//!       staff:
+//!         mode: warn
+//!       contractor:
+//!         deny_tools: ["Bash"]
+//! ```
+//!
+//! The profile is resolved when the config is loaded for an event: the
+//! event's user (its `user_id`, or the `identity.user_env` variable) is
+//! looked up in `users` (exact names or `*` globs),
+//! then the role named by the `role_env` variable (default `RULEZ_ROLE`),
+//! then `default`. The resolved profile is recorded on the log entry.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::identity::{self, user_matches};
+use crate::models::{Event, PolicyMode, Rule};
+
+/// Profile settings (`settings.profiles`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProfilesConfig {
+    /// Environment variable naming the role when the user is not mapped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_env: Option<String>,
+
+    /// User id (or `*` glob) to profile name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub users: BTreeMap<String, String>,
+
+    /// Profile for users nothing else resolves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+
+    /// Profiles by name
+    #[serde(default)]
+    pub definitions: BTreeMap<String, Profile>,
+}
+
+/// How one profile changes the rules
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Profile {
+    /// Run every rule in this mode (`warn` relaxes, `enforce` tightens)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<PolicyMode>,
+
+    /// Rules left out for this profile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disable_rules: Vec<String>,
+
+    /// Tools blocked outright for this profile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_tools: Vec<String>,
+}
+
+/// Role variable used when `role_env` is not set
+pub const DEFAULT_ROLE_ENV: &str = "RULEZ_ROLE";
+
+impl ProfilesConfig {
+    /// Name of the environment variable holding the role
+    pub fn role_env(&self) -> &str {
+        self.role_env.as_deref().unwrap_or(DEFAULT_ROLE_ENV)
+    }
+
+    /// Profile name for a user: `users` entry, then role variable, then `default`
+    ///
+    /// An exact `users` entry wins over a glob.
+    pub fn resolve(&self, user: Option<&str>, role: Option<&str>) -> Option<&str> {
+        let mapped = user.and_then(|user| {
+            self.users.get(user).or_else(|| {
+                self.users
+                    .iter()
+                    .find(|(pattern, _)| user_matches(std::slice::from_ref(*pattern), user))
+                    .map(|(_, profile)| profile)
+            })
+        });
+        if let Some(profile) = mapped {
+            return Some(profile);
+        }
+        if let Some(role) = role.filter(|role| !role.is_empty()) {
+            if let Some((name, _)) = self.definitions.get_key_value(role) {
+                return Some(name);
+            }
+            tracing::warn!(
+                "{} names unknown profile '{}'; using the default",
+                self.role_env(),
+                role
+            );
+        }
+        self.default.as_deref()
+    }
+
+    /// Problem with the profile settings, if any (checked by `Config::validate`)
+    pub fn problem(&self) -> Option<String> {
+        let named = self.users.values().chain(self.default.as_ref());
+        if let Some(name) = named
+            .into_iter()
+            .find(|name| !self.definitions.contains_key(*name))
+        {
+            return Some(format!("refer to undefined profile '{}'", name));
+        }
+        None
+    }
+}
+
+/// Name of the rule that enforces a profile's `deny_tools`
+pub fn deny_rule_name(profile: &str) -> String {
+    format!("profile-{}-deny-tools", profile)
+}
+
+/// Resolve the profile for an event and apply it to the config
+///
+/// Returns the profile name, for the log entry.
+pub fn apply(config: &mut Config, event: &Event) -> Option<String> {
+    let profiles = config.settings.profiles.as_ref()?;
+    let role = std::env::var(profiles.role_env()).ok();
+    let name = profiles
+        .resolve(identity::of(event).user.as_deref(), role.as_deref())?
+        .to_string();
+    let Some(profile) = profiles.definitions.get(&name).cloned() else {
+        return Some(name);
+    };
+    tracing::debug!("Applying profile '{}'", name);
+
+    config
+        .rules
+        .retain(|rule| !profile.disable_rules.contains(&rule.name));
+    if let Some(mode) = profile.mode {
+        for rule in &mut config.rules {
+            rule.mode = Some(mode);
+        }
+    }
+    if !profile.deny_tools.is_empty() {
+        config.rules.push(deny_rule(&name, &profile.deny_tools));
+    }
+
+    // Rules changed after load, so the tool/event index is stale
+    config.index = crate::config::RuleIndex::default();
+    Some(name)
+}
+
+/// Rule blocking a profile's denied tools, ahead of every other rule
+fn deny_rule(profile: &str, tools: &[String]) -> Rule {
+    serde_json::from_value(serde_json::json!({
+        "name": deny_rule_name(profile),
+        "description": format!("{} not allowed for the '{}' profile", tools.join(", "), profile),
+        "priority": i32::MAX,
+        "matchers": { "tools": tools },
+        "actions": { "block": true },
+        "governance": { "created_by": format!("profile:{}", profile) },
+    }))
+    .expect("profile deny rule is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+version: "1.0"
+settings:
+  profiles:
+    users:
+      alice@example.com: staff
+      "*@contractor.example.com": contractor
+    default: contractor
+    definitions:
+      staff:
+        mode: warn
+        disable_rules: [no-console-log]
+      contractor:
+        deny_tools: ["Bash"]
+rules:
+  - name: no-force-push
+    matchers: { tools: ["Bash"], command_match: "git push --force" }
+    actions: { block: true }
+  - name: no-console-log
+    matchers: { tools: ["Write"] }
+    actions: { block: true }
+"#;
+
+    fn event(user_id: Option<&str>) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "session_id": "profile-test",
+            "user_id": user_id,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_order() {
+        let config: Config = serde_yaml::from_str(YAML).unwrap();
+        let profiles = config.settings.profiles.unwrap();
+        assert_eq!(
+            profiles.resolve(Some("alice@example.com"), None),
+            Some("staff")
+        );
+        assert_eq!(
+            profiles.resolve(Some("bob@contractor.example.com"), Some("staff")),
+            Some("contractor")
+        );
+        assert_eq!(
+            profiles.resolve(Some("carol@example.com"), Some("staff")),
+            Some("staff")
+        );
+        assert_eq!(profiles.resolve(None, Some("admin")), Some("contractor"));
+        assert_eq!(profiles.resolve(None, None), Some("contractor"));
+        assert!(profiles.problem().is_none());
+    }
+
+    #[test]
+    fn test_apply_profiles() {
+        let mut staff: Config = serde_yaml::from_str(YAML).unwrap();
+        assert_eq!(
+            apply(&mut staff, &event(Some("alice@example.com"))).as_deref(),
+            Some("staff")
+        );
+        let names: Vec<&str> = staff.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["no-force-push"]);
+        assert_eq!(staff.rules[0].effective_mode(), PolicyMode::Warn);
+
+        // A user from the environment, not the event, is mapped the same way
+        let mut from_env = event(None);
+        from_env.identity = Some(identity::Identity {
+            user: Some("alice@example.com".to_string()),
+            teams: Vec::new(),
+        });
+        let mut config: Config = serde_yaml::from_str(YAML).unwrap();
+        assert_eq!(apply(&mut config, &from_env).as_deref(), Some("staff"));
+
+        let mut contractor: Config = serde_yaml::from_str(YAML).unwrap();
+        apply(&mut contractor, &event(Some("dan@contractor.example.com")));
+        let candidates: Vec<&str> = contractor
+            .candidate_rules(Some("Bash"), crate::models::EventType::PreToolUse)
+            .into_iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(
+            candidates,
+            vec!["profile-contractor-deny-tools", "no-force-push"]
+        );
+    }
+
+    #[test]
+    fn test_undefined_profile_is_a_problem() {
+        let config: Config =
+            serde_yaml::from_str(&YAML.replace("default: contractor", "default: intern")).unwrap();
+        assert_eq!(
+            config.settings.profiles.unwrap().problem().unwrap(),
+            "refer to undefined profile 'intern'"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! Built-in self-protection for RuleZ's own files.
+//!
+//! An agent that can edit the policy constraining it isn't constrained. This
+//! guard runs before any user rule and stops tool calls that write to:
+//! - the project and global `hooks.yaml` and their compiled caches
+//! - validator scripts referenced by `run` actions
+//! - the audit log directory (`~/.claude/logs`)
+//! - the synced policy overlay (`settings.sync.dir`)
+//! - any extra `settings.self_protection.paths`
+//!
+//! File tools (`Write`, `Edit`, `MultiEdit`, `NotebookEdit`) are checked by
+//! their target path. `Bash` is checked on a best-effort basis: a command is
+//! flagged when it both looks like a write (redirection, `rm`, `mv`, `sed -i`,
+//! ...) and names a protected path, or when it runs `rulez disable` (see
+//! [`crate::kill_switch`]).
+//!
+//! Humans editing these files directly are unaffected; hooks only see agent
+//! tool calls.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::config::{Config, compiled_cache_dir};
+use crate::logging::Logger;
+use crate::models::{Actions, Event, EventType, PolicyMode};
+
+/// Rule name reported in logs when the guard fires
+pub const SELF_PROTECTION_RULE: &str = "rulez:self-protection";
+
+/// Tools that write to the file named in their `tool_input`
+const FILE_WRITE_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+/// Shell syntax that suggests a command modifies files
+static SHELL_WRITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?x)
+        > |
+        (^|[\s;&|(])(rm|mv|cp|tee|truncate|chmod|chown|ln|dd|install|unlink|shred)(\s|$) |
+        (^|[\s;&|(])(sed|perl)\s+(-\S+\s+)*-[a-zA-Z]*i",
+    )
+    .expect("valid shell write regex")
+});
+
+/// A shell command that turns RuleZ off
+static KILL_SWITCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(^|[\s;&|(/])rulez\s+disable\s").expect("valid kill switch regex")
+});
+
+/// Self-protection settings (`settings.self_protection`)
+///
+/// ```yaml
+/// settings:
+///   self_protection:
+///     enabled: true
+///     mode: enforce        # enforce (block), warn, or audit
+///     paths:
+///       - .claude/packs
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelfProtection {
+    /// Whether the guard runs
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// What happens on a violation (enforce blocks)
+    #[serde(default)]
+    pub mode: PolicyMode,
+
+    /// Extra files or directories to protect (relative to the project)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for SelfProtection {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            mode: PolicyMode::default(),
+            paths: Vec::new(),
+        }
+    }
+}
+
+/// Check an event against the protected paths
+///
+/// Returns the block reason when the event writes to a protected path.
+pub fn check(event: &Event, config: &Config, project: Option<&Path>) -> Option<String> {
+    let settings = &config.settings.self_protection;
+    if !settings.enabled
+        || !matches!(
+            event.hook_event_name,
+            EventType::PreToolUse | EventType::PermissionRequest
+        )
+    {
+        return None;
+    }
+
+    let tool_name = event.tool_name.as_deref()?;
+    let tool_input = event.tool_input.as_ref()?;
+    let protected = protected_paths(config, project);
+
+    if FILE_WRITE_TOOLS.contains(&tool_name) {
+        let target = ["file_path", "filePath", "notebook_path"]
+            .iter()
+            .find_map(|key| tool_input.get(key).and_then(|v| v.as_str()))?;
+        let target = resolve(target, project);
+        return protected
+            .iter()
+            .find(|path| target.starts_with(path))
+            .map(|path| violation(tool_name, path));
+    }
+
+    if tool_name == "Bash" {
+        let command = tool_input.get("command").and_then(|v| v.as_str())?;
+        if KILL_SWITCH.is_match(command) {
+            return Some(
+                "RuleZ self-protection: Bash may not run 'rulez disable'. \
+                 Disable RuleZ outside the agent."
+                    .to_string(),
+            );
+        }
+        if !SHELL_WRITE.is_match(command) {
+            return None;
+        }
+        return shell_words(command)
+            .map(|word| resolve(word, project))
+            .find_map(|target| protected.iter().find(|path| target.starts_with(path)))
+            .map(|path| violation(tool_name, path));
+    }
+
+    None
+}
+
+/// Build the block reason for a protected path
+fn violation(tool_name: &str, path: &Path) -> String {
+    format!(
+        "RuleZ self-protection: {} may not modify '{}'. \
+         Edit RuleZ policy files outside the agent, or adjust settings.self_protection.",
+        tool_name,
+        path.display()
+    )
+}
+
+/// Collect the protected paths for this config, normalized
+fn protected_paths(config: &Config, project: Option<&Path>) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    let configs = project
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(dirs::home_dir())
+        .map(|dir| dir.join(".claude").join("hooks.yaml"));
+    paths.extend(configs);
+    paths.push(compiled_cache_dir());
+    if let Some(log_dir) = Logger::default_log_path().parent() {
+        paths.push(log_dir.to_path_buf());
+    }
+    if let Some(ref sync) = config.settings.sync {
+        paths.push(sync.overlay_dir());
+    }
+    if let Some(ref log_path) = config.settings.logging.path {
+        if let Some(log_dir) = resolve(log_path, project).parent() {
+            paths.push(log_dir.to_path_buf());
+        }
+    }
+
+    let configured = config
+        .rules
+        .iter()
+        .flat_map(|rule| rule.actions.steps().filter_map(Actions::script_path))
+        .chain(
+            config
+                .settings
+                .self_protection
+                .paths
+                .iter()
+                .map(String::as_str),
+        );
+    paths.extend(configured.map(|path| resolve(path, project)));
+
+    paths.iter().map(|path| normalize(path)).collect()
+}
+
+/// Resolve a user-supplied path (`~/`, relative to the project) and normalize it
+fn resolve(path: &str, project: Option<&Path>) -> PathBuf {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or_else(|| PathBuf::from(path), |home| home.join(rest)),
+        None => match project {
+            Some(project) => project.join(path),
+            None => PathBuf::from(path),
+        },
+    };
+    normalize(&path)
+}
+
+/// Normalize a path that may not exist yet
+///
+/// Removes `.` and `..` lexically, then resolves symlinks through the
+/// deepest existing ancestor.
+fn normalize(path: &Path) -> PathBuf {
+    let mut lexical = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                lexical.pop();
+            }
+            Component::CurDir => {}
+            other => lexical.push(other),
+        }
+    }
+
+    let mut existing = lexical.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            break;
+        };
+        rest.push(name);
+        existing = parent;
+    }
+
+    let mut normalized = std::fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+    normalized.extend(rest.iter().rev());
+    normalized
+}
+
+/// Split a shell command into path-like words
+///
+/// Strips quotes and leading redirection operators (`>`, `>>`, `2>`).
+fn shell_words(command: &str) -> impl Iterator<Item = &str> {
+    command
+        .split(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '&' | '(' | ')'))
+        .map(|word| {
+            let redirect = word.trim_start_matches(|c: char| c.is_ascii_digit());
+            let word = if redirect.starts_with(['>', '<']) {
+                redirect.trim_start_matches(['>', '<'])
+            } else {
+                word
+            };
+            word.trim_matches(['"', '\''])
+        })
+        .filter(|word| !word.is_empty() && !word.starts_with('-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Rule;
+    use chrono::Utc;
+
+    fn event(tool_name: &str, tool_input: serde_json::Value) -> Event {
+        Event {
+            hook_event_name: EventType::PreToolUse,
+            tool_name: Some(tool_name.to_string()),
+            tool_input: Some(tool_input),
+            session_id: "test-session".to_string(),
+            timestamp: Utc::now(),
+            user_id: None,
+            transcript_path: None,
+            cwd: None,
+            permission_mode: None,
+            tool_use_id: None,
+            prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
+        }
+    }
+
+    fn config_with_validator(script: &str) -> Config {
+        let mut config = Config::default();
+        let rule: Rule = serde_yaml::from_str(&format!(
+            "name: validator\nmatchers:\n  tools: [Bash]\nactions:\n  run: {script}\n"
+        ))
+        .unwrap();
+        config.rules.push(rule);
+        config
+    }
+
+    #[test]
+    fn test_file_tools_blocked_on_protected_paths() {
+        let project = tempfile::tempdir().unwrap();
+        let config = config_with_validator(".claude/validators/check.sh");
+        let project = Some(project.path());
+
+        let write = event(
+            "Write",
+            serde_json::json!({"file_path": ".claude/hooks.yaml"}),
+        );
+        assert!(check(&write, &config, project).is_some());
+
+        let edit = event(
+            "Edit",
+            serde_json::json!({"file_path": "src/../.claude/validators/check.sh"}),
+        );
+        assert!(check(&edit, &config, project).is_some());
+
+        let log_file = Logger::default_log_path();
+        let log = event(
+            "Write",
+            serde_json::json!({"filePath": log_file.to_string_lossy()}),
+        );
+        assert!(check(&log, &config, project).is_some());
+
+        let cache = event(
+            "Write",
+            serde_json::json!({"file_path": compiled_cache_dir().join(".key")}),
+        );
+        assert!(check(&cache, &config, project).is_some());
+
+        let other = event("Write", serde_json::json!({"file_path": "src/main.rs"}));
+        assert!(check(&other, &config, project).is_none());
+    }
+
+    #[test]
+    fn test_project_log_dir_protected() {
+        let project = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.settings.logging.path = Some(".claude/logs/rulez.log".to_string());
+        let project = Some(project.path());
+
+        let log = event(
+            "Write",
+            serde_json::json!({"file_path": ".claude/logs/rulez.log"}),
+        );
+        assert!(check(&log, &config, project).is_some());
+        let bash = event(
+            "Bash",
+            serde_json::json!({"command": "rm -rf .claude/logs"}),
+        );
+        assert!(check(&bash, &config, project).is_some());
+    }
+
+    #[test]
+    fn test_bash_writes_blocked_reads_allowed() {
+        let project = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        let project = Some(project.path());
+
+        for command in [
+            "echo 'rules: []' > .claude/hooks.yaml",
+            "sed -i 's/block: true/block: false/' .claude/hooks.yaml",
+            "echo {} > ~/.claude/logs/compiled/.key",
+            "rm -rf ~/.claude/logs",
+            "cd /tmp && mv ./x \"$PWD\" ; cp evil.yaml ./.claude/hooks.yaml",
+            "rulez disable --all --ttl 1h",
+            "~/.cargo/bin/rulez disable --all",
+        ] {
+            let bash = event("Bash", serde_json::json!({"command": command}));
+            assert!(check(&bash, &config, project).is_some(), "{command}");
+        }
+
+        for command in [
+            "cat .claude/hooks.yaml",
+            "rm -rf target",
+            "ls ~/.claude/logs",
+            "rulez rule list",
+        ] {
+            let bash = event("Bash", serde_json::json!({"command": command}));
+            assert!(check(&bash, &config, project).is_none(), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_extra_paths_and_disable() {
+        let project = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.settings.self_protection.paths = vec![".claude/packs".to_string()];
+        let project = Some(project.path());
+
+        let write = event(
+            "Write",
+            serde_json::json!({"file_path": ".claude/packs/security.yaml"}),
+        );
+        assert!(check(&write, &config, project).is_some());
+
+        config.settings.self_protection.enabled = false;
+        assert!(check(&write, &config, project).is_none());
+    }
+
+    #[test]
+    fn test_sync_overlay_protected() {
+        let project = tempfile::tempdir().unwrap();
+        let overlay = project.path().join("policy");
+        let mut config = Config::default();
+        config.settings.sync = Some(crate::sync::SyncConfig {
+            dir: overlay.to_string_lossy().into_owned(),
+            ..crate::sync::SyncConfig::new("https://policy.example.com/bundle.tar")
+        });
+        let project = Some(project.path());
+
+        let edit = event(
+            "Edit",
+            serde_json::json!({"file_path": overlay.join("guardrails.yaml").to_string_lossy()}),
+        );
+        assert!(check(&edit, &config, project).is_some());
+        let bash = event(
+            "Bash",
+            serde_json::json!({"command": format!("rm -rf {}", overlay.display())}),
+        );
+        assert!(check(&bash, &config, project).is_some());
+    }
+
+    #[test]
+    fn test_only_pre_tool_events_checked() {
+        let project = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        let mut post = event(
+            "Write",
+            serde_json::json!({"file_path": ".claude/hooks.yaml"}),
+        );
+        post.hook_event_name = EventType::PostToolUse;
+        assert!(check(&post, &config, Some(project.path())).is_none());
+    }
+}
@@ -0,0 +1,551 @@
+//! Streaming protocol v2: versioned, newline-delimited JSON.
+//!
+//! `rulez --stream` speaks it on stdin/stdout, and `rulez daemon` switches
+//! a socket connection to it when the first line is a `hello`. Every message
+//! is one JSON object per line with a `type`:
+//!
+//! ```text
+//! → {"type":"hello","protocol":2,"client":"claude-code/2.1",
+//!    "capabilities":{"events":["PreToolUse","Stop"],"output_fields":["systemMessage"]}}
+//! ← {"type":"hello","protocol":2,"server":"rulez/2.3.0",
+//!    "capabilities":{"events":["PreToolUse","Stop"],"output_fields":["continue","systemMessage"]}}
+//! → {"type":"evaluate","id":"1","event":{"hook_event_name":"PreToolUse",...}}
+//! ← {"type":"result","id":"1","response":{"continue":false,"reason":"..."}}
+//! → {"type":"ping","id":7}
+//! ← {"type":"pong","id":7}
+//! → {"type":"shutdown"}
+//! ← {"type":"goodbye"}
+//! ```
+//!
+//! The handshake comes first. The client sends the highest protocol version
+//! it speaks, and the server answers with the version both use, or with an
+//! `unsupported_protocol` error before closing the stream. Capabilities are
+//! negotiated rather than guessed from payload shapes:
+//!
+//! - `events`: the hook event names the client will send. The server replies
+//!   with the ones it supports; evaluating any other event is an
+//!   `unsupported_event` error. An empty list means "everything you support".
+//! - `output_fields`: the response fields the client understands. Responses
+//!   are trimmed to the agreed fields; `continue` is always kept.
+//!
+//! Requests carry an `id` (string or number) that is echoed in the reply.
+//! Errors have the form `{"type":"error","id":...,"error":{"code":"...",
+//! "message":"..."}}`.
+
+use std::collections::BTreeSet;
+use std::future::Future;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::models::{EventType, Response};
+
+/// Longest message line a client may send (the HTTP API's body limit)
+pub const MAX_LINE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Newest protocol version this build speaks
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest version accepted in a `hello` (v1 is the unversioned line protocol)
+pub const MIN_PROTOCOL_VERSION: u32 = 2;
+
+/// Response fields a client can ask for (`continue` is always sent)
+pub const OUTPUT_FIELDS: [&str; 8] = [
+    "continue",
+    "context",
+    "reason",
+    "timing",
+    "systemMessage",
+    "suppressOutput",
+    "hookSpecificOutput",
+    "updatedPrompt",
+];
+
+/// What one side of the stream supports
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Hook event names (`hook_event_name` values)
+    #[serde(default)]
+    pub events: Vec<String>,
+
+    /// Response field names, as serialized
+    #[serde(default)]
+    pub output_fields: Vec<String>,
+}
+
+impl Capabilities {
+    /// Everything this build supports
+    pub fn server() -> Self {
+        let mut events: Vec<String> = EventType::ALL.iter().map(ToString::to_string).collect();
+        events.extend(["SubagentStart".to_string(), "SubagentStop".to_string()]);
+        Self {
+            events,
+            output_fields: OUTPUT_FIELDS.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// The subset of the server's capabilities the client asked for
+    #[must_use]
+    pub fn negotiate(&self, client: &Capabilities) -> Self {
+        let pick = |ours: &[String], theirs: &[String]| -> Vec<String> {
+            ours.iter()
+                .filter(|name| theirs.is_empty() || theirs.contains(name))
+                .cloned()
+                .collect()
+        };
+        let mut output_fields = pick(&self.output_fields, &client.output_fields);
+        if !output_fields.iter().any(|f| f == "continue") {
+            output_fields.insert(0, "continue".to_string());
+        }
+        Self {
+            events: pick(&self.events, &client.events),
+            output_fields,
+        }
+    }
+}
+
+/// A message from the client
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Opens the session; must be the first message
+    Hello {
+        /// Highest protocol version the client speaks
+        protocol: u32,
+        /// Client name and version, for logs
+        #[serde(default)]
+        client: Option<String>,
+        #[serde(default)]
+        capabilities: Capabilities,
+    },
+    /// Evaluate one hook event
+    Evaluate {
+        id: serde_json::Value,
+        event: serde_json::Value,
+    },
+    /// Liveness check
+    Ping {
+        #[serde(default)]
+        id: serde_json::Value,
+    },
+    /// End the session
+    Shutdown,
+}
+
+/// A message from the server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply {
+    /// Handshake answer with the agreed version and capabilities
+    Hello {
+        protocol: u32,
+        server: String,
+        capabilities: Capabilities,
+    },
+    /// The response to an `evaluate` request
+    Result {
+        id: serde_json::Value,
+        response: serde_json::Value,
+    },
+    /// A request failed
+    Error {
+        #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+        id: serde_json::Value,
+        error: ProtocolError,
+    },
+    /// Answer to `ping`
+    Pong { id: serde_json::Value },
+    /// Answer to `shutdown`, sent before the server closes the stream
+    Goodbye,
+}
+
+/// Error details in an `error` reply
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProtocolError {
+    /// `handshake_required`, `unsupported_protocol`, `invalid_request`,
+    /// `unsupported_event` or `evaluation_failed`
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Reply {
+    fn error(id: serde_json::Value, code: &'static str, message: impl Into<String>) -> Self {
+        Self::Error {
+            id,
+            error: ProtocolError {
+                code,
+                message: message.into(),
+            },
+        }
+    }
+}
+
+/// Protocol state for one stream
+#[derive(Debug, Default)]
+pub struct Session {
+    /// Set once the handshake succeeds
+    agreed: Option<Capabilities>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle one line; returns the reply and whether to close the stream
+    pub async fn handle<F, Fut>(&mut self, line: &str, handler: &F) -> (Reply, bool)
+    where
+        F: Fn(serde_json::Value) -> Fut,
+        Fut: Future<Output = Result<Response>>,
+    {
+        let request = match serde_json::from_str::<Request>(line) {
+            Ok(request) => request,
+            Err(e) => {
+                let id = serde_json::from_str::<serde_json::Value>(line)
+                    .ok()
+                    .and_then(|v| v.get("id").cloned())
+                    .unwrap_or_default();
+                return (
+                    Reply::error(id, "invalid_request", e.to_string()),
+                    self.agreed.is_none(),
+                );
+            }
+        };
+
+        match (request, &self.agreed) {
+            (
+                Request::Hello {
+                    protocol,
+                    client,
+                    capabilities,
+                },
+                None,
+            ) => {
+                if protocol < MIN_PROTOCOL_VERSION {
+                    return (
+                        Reply::error(
+                            serde_json::Value::Null,
+                            "unsupported_protocol",
+                            format!(
+                                "Protocol {} is not supported; this server speaks {} to {}",
+                                protocol, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+                            ),
+                        ),
+                        true,
+                    );
+                }
+                tracing::debug!(
+                    "Protocol session with {} (protocol {})",
+                    client.as_deref().unwrap_or("unknown client"),
+                    protocol
+                );
+                let agreed = Capabilities::server().negotiate(&capabilities);
+                self.agreed = Some(agreed.clone());
+                let reply = Reply::Hello {
+                    protocol: protocol.min(PROTOCOL_VERSION),
+                    server: format!("rulez/{}", env!("CARGO_PKG_VERSION")),
+                    capabilities: agreed,
+                };
+                (reply, false)
+            }
+            (Request::Hello { .. }, Some(_)) => (
+                Reply::error(
+                    serde_json::Value::Null,
+                    "invalid_request",
+                    "The handshake was already completed",
+                ),
+                false,
+            ),
+            (_, None) => (
+                Reply::error(
+                    serde_json::Value::Null,
+                    "handshake_required",
+                    "Send a hello message first",
+                ),
+                true,
+            ),
+            (Request::Ping { id }, Some(_)) => (Reply::Pong { id }, false),
+            (Request::Shutdown, Some(_)) => (Reply::Goodbye, true),
+            (Request::Evaluate { id, event }, Some(agreed)) => {
+                let name = event
+                    .get("hook_event_name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or_default();
+                if !agreed.events.iter().any(|e| e == name) {
+                    let message = format!("Event '{}' was not negotiated in the handshake", name);
+                    return (Reply::error(id, "unsupported_event", message), false);
+                }
+                let reply = match handler(event).await {
+                    Ok(response) => Reply::Result {
+                        id,
+                        response: trim_response(&response, &agreed.output_fields),
+                    },
+                    Err(e) => Reply::error(id, "evaluation_failed", format!("{e:#}")),
+                };
+                (reply, false)
+            }
+        }
+    }
+}
+
+/// Serialize a response with only the agreed fields
+fn trim_response(response: &Response, fields: &[String]) -> serde_json::Value {
+    let mut value = serde_json::to_value(response).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        let keep: BTreeSet<&str> = fields.iter().map(String::as_str).collect();
+        object.retain(|key, _| key == "continue" || keep.contains(key.as_str()));
+    }
+    value
+}
+
+/// Newline-delimited reader that refuses over-long lines
+///
+/// Like [`tokio::io::Lines`], but a line longer than the limit is an
+/// `InvalidData` error instead of growing the buffer without bound. The rest
+/// of such a line is left unread, so the caller should close the stream.
+pub struct LineReader<R> {
+    reader: R,
+    max_bytes: usize,
+}
+
+impl<R: AsyncBufRead + Unpin> LineReader<R> {
+    pub fn new(reader: R, max_bytes: usize) -> Self {
+        Self { reader, max_bytes }
+    }
+
+    /// The next line without its `\n` or `\r\n`, or `None` at end of input
+    pub async fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        let mut line = Vec::new();
+        let limit = u64::try_from(self.max_bytes).map_or(u64::MAX, |max| max + 1);
+        if (&mut self.reader)
+            .take(limit)
+            .read_until(b'\n', &mut line)
+            .await?
+            == 0
+        {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        } else if line.len() > self.max_bytes {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("line exceeds {} bytes", self.max_bytes),
+            ));
+        }
+        String::from_utf8(line)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Whether a line opens a v2 session (a `hello` message)
+pub fn is_hello(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .is_ok_and(|v| v.get("type").and_then(|t| t.as_str()) == Some("hello"))
+}
+
+/// Run a session over a line stream until the client closes it or shuts down
+///
+/// `first` is a line the caller already read (the daemon peeks at it to
+/// detect the protocol). An over-long or non-UTF-8 line gets an
+/// `invalid_request` error and ends the session.
+pub async fn serve<R, W, F, Fut>(
+    lines: &mut LineReader<R>,
+    writer: &mut W,
+    first: Option<String>,
+    handler: &F,
+) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+    F: Fn(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    let mut session = Session::new();
+    let mut pending = first;
+    loop {
+        let line = match pending.take() {
+            Some(line) => line,
+            None => match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => return Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    let reply =
+                        Reply::error(serde_json::Value::Null, "invalid_request", e.to_string());
+                    let mut json = serde_json::to_string(&reply)?;
+                    json.push('\n');
+                    writer.write_all(json.as_bytes()).await?;
+                    writer.flush().await?;
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            },
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (reply, close) = session.handle(&line, handler).await;
+        let mut json = serde_json::to_string(&reply)?;
+        json.push('\n');
+        writer.write_all(json.as_bytes()).await?;
+        writer.flush().await?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn handler(event: serde_json::Value) -> Result<Response> {
+        match event["tool_name"].as_str() {
+            Some("Bad") => anyhow::bail!("bad tool"),
+            Some("Bash") => {
+                let mut response = Response::block("no");
+                response.system_message = Some("blocked".to_string());
+                Ok(response)
+            }
+            _ => Ok(Response::allow()),
+        }
+    }
+
+    async fn run(input: &str) -> Vec<serde_json::Value> {
+        run_within(input, MAX_LINE_BYTES).await
+    }
+
+    async fn run_within(input: &str, max_bytes: usize) -> Vec<serde_json::Value> {
+        let mut lines = LineReader::new(input.as_bytes(), max_bytes);
+        let mut output = Vec::new();
+        serve(&mut lines, &mut output, None, &handler)
+            .await
+            .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_handshake_negotiates_capabilities() {
+        let replies = run(concat!(
+            r#"{"type":"hello","protocol":3,"client":"test","capabilities":{"events":["PreToolUse","FutureEvent"],"output_fields":["reason"]}}"#,
+            "\n",
+            r#"{"type":"evaluate","id":"a","event":{"hook_event_name":"PreToolUse","tool_name":"Bash"}}"#,
+            "\n",
+            r#"{"type":"evaluate","id":2,"event":{"hook_event_name":"Stop"}}"#,
+            "\n",
+            r#"{"type":"ping","id":3}"#,
+            "\n",
+            r#"{"type":"shutdown"}"#,
+            "\n",
+            r#"{"type":"ping","id":4}"#,
+            "\n",
+        ))
+        .await;
+
+        assert_eq!(replies.len(), 5, "nothing is read after shutdown");
+        assert_eq!(replies[0]["type"], "hello");
+        assert_eq!(replies[0]["protocol"], 2);
+        assert_eq!(
+            replies[0]["capabilities"]["events"],
+            serde_json::json!(["PreToolUse"])
+        );
+        assert_eq!(
+            replies[0]["capabilities"]["output_fields"],
+            serde_json::json!(["continue", "reason"])
+        );
+
+        assert_eq!(replies[1]["type"], "result");
+        assert_eq!(replies[1]["id"], "a");
+        assert_eq!(
+            replies[1]["response"],
+            serde_json::json!({"continue": false, "reason": "no"}),
+            "systemMessage was not negotiated"
+        );
+
+        assert_eq!(replies[2]["error"]["code"], "unsupported_event");
+        assert_eq!(replies[2]["id"], 2);
+        assert_eq!(replies[3], serde_json::json!({"type": "pong", "id": 3}));
+        assert_eq!(replies[4]["type"], "goodbye");
+    }
+
+    #[tokio::test]
+    async fn test_handshake_required_and_version_checked() {
+        let replies = run(concat!(
+            r#"{"type":"evaluate","id":1,"event":{}}"#,
+            "\n",
+            r#"{"type":"hello","protocol":2}"#,
+            "\n"
+        ))
+        .await;
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0]["error"]["code"], "handshake_required");
+
+        let replies = run("{\"type\":\"hello\",\"protocol\":1}\n").await;
+        assert_eq!(replies[0]["error"]["code"], "unsupported_protocol");
+    }
+
+    #[tokio::test]
+    async fn test_errors_keep_the_session_open() {
+        let replies = run(concat!(
+            r#"{"type":"hello","protocol":2}"#,
+            "\n",
+            r#"{"type":"evaluate","id":1,"event":{"hook_event_name":"PreToolUse","tool_name":"Bad"}}"#,
+            "\n",
+            r#"{"type":"bogus","id":"x"}"#,
+            "\n",
+            r#"{"type":"evaluate","id":3,"event":{"hook_event_name":"SubagentStop"}}"#,
+            "\n",
+        ))
+        .await;
+        assert_eq!(replies.len(), 4);
+        assert_eq!(replies[1]["error"]["code"], "evaluation_failed");
+        assert!(
+            replies[1]["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("bad tool")
+        );
+        assert_eq!(replies[2]["error"]["code"], "invalid_request");
+        assert_eq!(replies[2]["id"], "x");
+        assert_eq!(replies[3]["response"]["continue"], true);
+    }
+
+    #[tokio::test]
+    async fn test_over_long_line_ends_session() {
+        let hello = r#"{"type":"hello","protocol":2}"#;
+        let ping = r#"{"type":"ping","id":1}"#;
+        let input = format!("{hello}\r\n{ping}\n{}\n{ping}\n", "x".repeat(65));
+        let replies = run_within(&input, 64).await;
+        assert_eq!(replies.len(), 3, "{replies:?}");
+        assert_eq!(replies[1]["type"], "pong");
+        assert_eq!(replies[2]["error"]["code"], "invalid_request");
+        assert!(
+            replies[2]["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("exceeds 64 bytes")
+        );
+
+        // A line of exactly the limit is fine
+        let exact = format!("{}\n", "y".repeat(64));
+        let mut lines = LineReader::new(exact.as_bytes(), 64);
+        assert_eq!(lines.next_line().await.unwrap().unwrap().len(), 64);
+        assert_eq!(lines.next_line().await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_is_hello() {
+        assert!(is_hello(r#"{"type":"hello","protocol":2}"#));
+        assert!(!is_hello(r#"{"hook_event_name":"PreToolUse"}"#));
+        assert!(!is_hello("not json"));
+    }
+}
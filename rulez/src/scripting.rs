@@ -0,0 +1,273 @@
+//! Embedded Rhai scripting for validator actions (`actions.script_rhai`).
+//!
+//! Rhai scripts run in-process, so validators with real logic no longer need
+//! an external interpreter, a temp file, or a POSIX shell. The engine is
+//! sandboxed by construction: Rhai has no file, network, or process access,
+//! and operation/call-depth limits stop runaway scripts.
+//!
+//! Scripts see the following scope:
+//! - `event`: the full event as a Rhai object map
+//! - `tool_name`, `event_type`, `prompt`: convenience strings ("" if absent)
+//!
+//! And the following functions:
+//! - `get_field(path)`: value at a dot-notation path in `tool_input` (or `()`)
+//! - `has_field(path)`: whether the path exists and is not null
+//! - `allow()`, `block(reason)`, `inject(context)`: decision helpers
+//!
+//! The value of the last expression decides the outcome:
+//! - `true` or `()` -> allow, `false` -> block
+//! - a decision helper result -> that decision
+
+use anyhow::{Context, Result};
+use rhai::{AST, Dynamic, Engine, Map, Scope};
+
+use crate::models::{Event, dot_to_pointer};
+
+/// Maximum number of operations a script may perform before it is aborted.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Maximum function call nesting depth.
+const MAX_CALL_LEVELS: usize = 32;
+
+/// Maximum size of any string a script may build, in bytes.
+const MAX_STRING_SIZE: usize = 1024 * 1024;
+
+/// Outcome of a Rhai validator script
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptDecision {
+    /// Validation passed
+    Allow,
+    /// Validation failed with the given reason
+    Block(String),
+    /// Validation passed and the script supplied context to inject
+    Inject(String),
+}
+
+/// Build a sandboxed engine with the RuleZ helper functions registered.
+///
+/// `tool_input` is captured by the field helpers; pass `None` for
+/// compile-only use (config validation).
+fn build_engine(tool_input: Option<serde_json::Value>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+
+    // stdout carries the hook response - never let scripts write to it
+    engine.on_print(|text| tracing::debug!("script_rhai print: {}", text));
+    engine.on_debug(|text, _source, _pos| tracing::debug!("script_rhai debug: {}", text));
+
+    let tool_input = std::sync::Arc::new(tool_input);
+
+    let input_for_get = tool_input.clone();
+    engine.register_fn("get_field", move |path: &str| -> Dynamic {
+        input_for_get
+            .as_ref()
+            .as_ref()
+            .and_then(|input| input.pointer(&dot_to_pointer(path)))
+            .and_then(|value| rhai::serde::to_dynamic(value).ok())
+            .unwrap_or(Dynamic::UNIT)
+    });
+
+    let input_for_has = tool_input;
+    engine.register_fn("has_field", move |path: &str| -> bool {
+        matches!(
+            input_for_has
+                .as_ref()
+                .as_ref()
+                .and_then(|input| input.pointer(&dot_to_pointer(path))),
+            Some(value) if !value.is_null()
+        )
+    });
+
+    engine.register_fn("allow", || decision_map("allow", ""));
+    engine.register_fn("block", |reason: &str| decision_map("block", reason));
+    engine.register_fn("inject", |context: &str| decision_map("inject", context));
+
+    engine
+}
+
+/// Build the object map returned by the decision helpers
+fn decision_map(decision: &str, message: &str) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("decision".into(), decision.into());
+    map.insert("message".into(), message.into());
+    map.into()
+}
+
+/// Compile a script without running it (syntax check at config load)
+pub fn compile_rhai_script(script: &str) -> Result<AST> {
+    build_engine(None)
+        .compile(script)
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+/// Run a Rhai validator script against an event
+pub fn run_rhai_script(script: &str, event: &Event) -> Result<ScriptDecision> {
+    let engine = build_engine(event.tool_input.clone());
+    let ast = engine
+        .compile(script)
+        .map_err(|e| anyhow::anyhow!("{}", e))
+        .context("Failed to compile script_rhai")?;
+
+    let mut scope = Scope::new();
+    let event_value = rhai::serde::to_dynamic(event).map_err(|e| anyhow::anyhow!("{}", e))?;
+    scope.push_constant("event", event_value);
+    scope.push_constant("tool_name", event.tool_name.clone().unwrap_or_default());
+    scope.push_constant("event_type", event.hook_event_name.to_string());
+    scope.push_constant("prompt", event.prompt.clone().unwrap_or_default());
+
+    let result: Dynamic = engine
+        .eval_ast_with_scope(&mut scope, &ast)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    interpret_result(result)
+}
+
+/// Convert the script's final value into a decision
+fn interpret_result(result: Dynamic) -> Result<ScriptDecision> {
+    if result.is_unit() {
+        return Ok(ScriptDecision::Allow);
+    }
+
+    if let Ok(passed) = result.as_bool() {
+        return Ok(if passed {
+            ScriptDecision::Allow
+        } else {
+            ScriptDecision::Block("script returned false".to_string())
+        });
+    }
+
+    if let Some(map) = result.read_lock::<Map>() {
+        let decision = map
+            .get("decision")
+            .and_then(|d| d.clone().into_string().ok())
+            .unwrap_or_default();
+        let message = map
+            .get("message")
+            .and_then(|m| m.clone().into_string().ok())
+            .unwrap_or_default();
+
+        return match decision.as_str() {
+            "allow" => Ok(ScriptDecision::Allow),
+            "block" => Ok(ScriptDecision::Block(message)),
+            "inject" => Ok(ScriptDecision::Inject(message)),
+            other => Err(anyhow::anyhow!(
+                "script returned unknown decision '{}'",
+                other
+            )),
+        };
+    }
+
+    Err(anyhow::anyhow!(
+        "script must return a bool or a decision (allow/block/inject), got {}",
+        result.type_name()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventType;
+    use chrono::Utc;
+
+    fn bash_event(command: &str) -> Event {
+        Event {
+            hook_event_name: EventType::PreToolUse,
+            tool_name: Some("Bash".to_string()),
+            tool_input: Some(serde_json::json!({
+                "command": command,
+                "options": {"timeout": 30}
+            })),
+            session_id: "test-session".to_string(),
+            timestamp: Utc::now(),
+            user_id: None,
+            transcript_path: None,
+            cwd: None,
+            permission_mode: None,
+            tool_use_id: None,
+            prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
+        }
+    }
+
+    #[test]
+    fn test_bool_results() {
+        let event = bash_event("ls");
+        assert_eq!(
+            run_rhai_script("true", &event).unwrap(),
+            ScriptDecision::Allow
+        );
+        assert!(matches!(
+            run_rhai_script("false", &event).unwrap(),
+            ScriptDecision::Block(_)
+        ));
+        assert_eq!(
+            run_rhai_script("let x = 1;", &event).unwrap(),
+            ScriptDecision::Allow
+        );
+    }
+
+    #[test]
+    fn test_field_helpers() {
+        let event = bash_event("git push --force");
+        let script = r#"
+            if has_field("command") && get_field("command").contains("--force") {
+                block("force push is not allowed")
+            } else {
+                allow()
+            }
+        "#;
+        assert_eq!(
+            run_rhai_script(script, &event).unwrap(),
+            ScriptDecision::Block("force push is not allowed".to_string())
+        );
+
+        assert_eq!(
+            run_rhai_script(r#"get_field("options.timeout") == 30"#, &event).unwrap(),
+            ScriptDecision::Allow
+        );
+        assert!(matches!(
+            run_rhai_script(r#"has_field("missing")"#, &event).unwrap(),
+            ScriptDecision::Block(_)
+        ));
+    }
+
+    #[test]
+    fn test_event_scope() {
+        let event = bash_event("ls");
+        let script = r#"
+            if tool_name == "Bash" && event.session_id == "test-session" {
+                inject("checked " + event_type)
+            }
+        "#;
+        assert_eq!(
+            run_rhai_script(script, &event).unwrap(),
+            ScriptDecision::Inject("checked PreToolUse".to_string())
+        );
+    }
+
+    #[test]
+    fn test_runaway_script_is_aborted() {
+        let event = bash_event("ls");
+        assert!(run_rhai_script("loop { }", &event).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_return_type() {
+        let event = bash_event("ls");
+        assert!(run_rhai_script("42", &event).is_err());
+    }
+
+    #[test]
+    fn test_compile_rejects_syntax_errors() {
+        assert!(compile_rhai_script("let x = ;").is_err());
+        assert!(compile_rhai_script("has_field(\"a\")").is_ok());
+    }
+}
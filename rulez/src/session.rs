@@ -0,0 +1,587 @@
+//! Session state and end-of-session summaries.
+//!
+//! With `settings.session_summary` set, RuleZ keeps a small state file per
+//! session under `~/.claude/logs/sessions/`. `SessionStart` initializes it,
+//! every later event updates its counters, and `SessionEnd` turns it into a
+//! [`SessionSummary`] that is written to the audit log (and optionally posted
+//! to a webhook) before the state file is removed.
+//!
+//! The same directory holds the blocked-Stop counter used to cap
+//! `settings.max_stop_blocks`, the warnings shown per rule for
+//! `settings.warn_throttle`, and short-lived [`ToolUseRecord`]s that pair a
+//! `PreToolUse` event with its `PostToolUse` by `tool_use_id`.
+//!
+//! ```yaml
+//! settings:
+//!   session_summary:
+//!     webhook: "https://hooks.example.com/rulez"
+//!     headers:
+//!       Authorization: "Bearer ${RULEZ_WEBHOOK_TOKEN}"
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::logging::Logger;
+use crate::models::{Event, EventType, Outcome};
+
+/// Session summary settings (`settings.session_summary`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionSummaryConfig {
+    /// URL the summary is POSTed to as JSON when the session ends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+
+    /// Extra HTTP headers for the webhook (`${VAR}` is expanded)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+
+    /// Webhook timeout in seconds
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_webhook_timeout() -> u64 {
+    5
+}
+
+/// Repetition limits for warn-mode warnings (`settings.warn_throttle`)
+///
+/// A rule's warning is shown the first time it matches in a session and
+/// then again once `every_events` matches have passed or `every_minutes`
+/// have elapsed, whichever comes first. With neither set it is shown once
+/// per session.
+///
+/// ```yaml
+/// settings:
+///   warn_throttle:
+///     every_events: 20
+///     every_minutes: 30
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WarnThrottle {
+    /// Show the warning again on every Nth match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_events: Option<u32>,
+
+    /// Show the warning again once this many minutes have passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_minutes: Option<u64>,
+}
+
+/// When a rule's warning was last shown in a session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct WarningRecord {
+    shown_at: DateTime<Utc>,
+    suppressed: u32,
+}
+
+/// Decide whether a rule's warning is shown for this event
+///
+/// Returns how many times the warning was suppressed since it was last
+/// shown, or `None` to suppress it now.
+pub fn claim_warning(config: &WarnThrottle, event: &Event, rule: &str) -> Result<Option<u32>> {
+    claim_warning_in(&sessions_dir(), config, event, rule)
+}
+
+fn claim_warning_in(
+    dir: &Path,
+    config: &WarnThrottle,
+    event: &Event,
+    rule: &str,
+) -> Result<Option<u32>> {
+    let path = state_path(dir, &event.session_id).with_extension("warnings");
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    // Concurrent hooks for the same session would otherwise lose counts
+    let _lock = crate::log_chain::lock_log(&path)?;
+    let mut records: HashMap<String, WarningRecord> = std::fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    let shown = match records.get_mut(rule) {
+        None => Some(0),
+        Some(record) => {
+            let events_due = config
+                .every_events
+                .is_some_and(|n| record.suppressed + 1 >= n);
+            let time_due = config.every_minutes.is_some_and(|minutes| {
+                let minutes = i64::try_from(minutes).unwrap_or(i64::MAX);
+                event.timestamp - record.shown_at >= chrono::Duration::minutes(minutes)
+            });
+            if events_due || time_due {
+                Some(record.suppressed)
+            } else {
+                record.suppressed += 1;
+                None
+            }
+        }
+    };
+    if shown.is_some() {
+        records.insert(
+            rule.to_string(),
+            WarningRecord {
+                shown_at: event.timestamp,
+                suppressed: 0,
+            },
+        );
+    }
+
+    std::fs::write(&path, serde_json::to_vec(&records)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(shown)
+}
+
+/// Counters accumulated over a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionSummary {
+    /// When RuleZ first saw the session
+    pub started_at: Option<DateTime<Utc>>,
+
+    /// When the session ended
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+
+    /// Events processed, including SessionStart and SessionEnd
+    pub events: u64,
+
+    /// Events blocked
+    pub blocked: u64,
+
+    /// Events that injected context (including warnings)
+    pub injected: u64,
+
+    /// Times each rule matched
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules_matched: BTreeMap<String, u64>,
+
+    /// Tool calls by tool name (PreToolUse events)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, u64>,
+}
+
+impl SessionSummary {
+    /// Count one processed event
+    pub fn record(&mut self, event: &Event, rules_matched: &[String], outcome: &Outcome) {
+        self.started_at.get_or_insert(event.timestamp);
+        self.events += 1;
+        match outcome {
+            Outcome::Block => self.blocked += 1,
+            Outcome::Inject => self.injected += 1,
+            Outcome::Allow => {}
+        }
+        for rule in rules_matched {
+            *self.rules_matched.entry(rule.clone()).or_default() += 1;
+        }
+        if event.hook_event_name == EventType::PreToolUse {
+            if let Some(ref tool) = event.tool_name {
+                *self.tools.entry(tool.clone()).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// What RuleZ decided for a tool call at `PreToolUse`
+///
+/// Written on `PreToolUse` and consumed by the matching `PostToolUse` (or
+/// `PostToolUseFailure`) event with the same `tool_use_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolUseRecord {
+    /// When the PreToolUse event was processed
+    pub timestamp: DateTime<Utc>,
+
+    /// Outcome of the PreToolUse event
+    pub outcome: Outcome,
+
+    /// Rules that matched the PreToolUse event
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules_matched: Vec<String>,
+}
+
+/// Records older than this are never consumed (the tool call was abandoned)
+const TOOL_USE_TTL_SECS: i64 = 3600;
+
+/// Remember the PreToolUse decision for a tool call
+///
+/// Blocked calls are not recorded: their PostToolUse never fires. Stale
+/// records from abandoned calls are pruned on each write.
+pub fn record_tool_use(event: &Event, rules_matched: &[String], outcome: &Outcome) -> Result<()> {
+    record_tool_use_in(&tool_uses_dir(), event, rules_matched, outcome)
+}
+
+fn record_tool_use_in(
+    dir: &Path,
+    event: &Event,
+    rules_matched: &[String],
+    outcome: &Outcome,
+) -> Result<()> {
+    let Some(ref tool_use_id) = event.tool_use_id else {
+        return Ok(());
+    };
+    if *outcome == Outcome::Block {
+        return Ok(());
+    }
+    prune_tool_uses(dir, event.timestamp);
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let record = ToolUseRecord {
+        timestamp: event.timestamp,
+        outcome: outcome.clone(),
+        rules_matched: rules_matched.to_vec(),
+    };
+    let path = state_path(dir, tool_use_id);
+    std::fs::write(&path, serde_json::to_vec(&record)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Take the PreToolUse record for a tool call, removing it
+pub fn take_tool_use(tool_use_id: &str) -> Option<ToolUseRecord> {
+    take_tool_use_in(&tool_uses_dir(), tool_use_id)
+}
+
+fn take_tool_use_in(dir: &Path, tool_use_id: &str) -> Option<ToolUseRecord> {
+    let path = state_path(dir, tool_use_id);
+    let record = serde_json::from_slice(&std::fs::read(&path).ok()?).ok();
+    let _ = std::fs::remove_file(&path);
+    record
+}
+
+fn tool_uses_dir() -> PathBuf {
+    sessions_dir().join("tool-uses")
+}
+
+/// Remove records older than [`TOOL_USE_TTL_SECS`]
+fn prune_tool_uses(dir: &Path, now: DateTime<Utc>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let cutoff = std::time::SystemTime::from(now - chrono::Duration::seconds(TOOL_USE_TTL_SECS));
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        if stale {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Update the session state for an event
+///
+/// Returns the finished summary on `SessionEnd`. State I/O errors are
+/// returned to the caller, which logs them without failing the hook.
+pub fn track(
+    event: &Event,
+    rules_matched: &[String],
+    outcome: &Outcome,
+) -> Result<Option<SessionSummary>> {
+    track_in(&sessions_dir(), event, rules_matched, outcome)
+}
+
+fn track_in(
+    dir: &Path,
+    event: &Event,
+    rules_matched: &[String],
+    outcome: &Outcome,
+) -> Result<Option<SessionSummary>> {
+    let path = state_path(dir, &event.session_id);
+
+    // SessionStart always begins from a clean slate (e.g. after /clear)
+    let mut summary = match event.hook_event_name {
+        EventType::SessionStart => SessionSummary::default(),
+        _ => load(&path).unwrap_or_default(),
+    };
+    summary.record(event, rules_matched, outcome);
+
+    if event.hook_event_name == EventType::SessionEnd {
+        summary.ended_at = Some(event.timestamp);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        return Ok(Some(summary));
+    }
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::write(&path, serde_json::to_vec(&summary)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(None)
+}
+
+/// POST a finished summary to the configured webhook
+///
+/// curl is awaited without blocking the runtime, so a slow endpoint holds up
+/// only this `SessionEnd`, not a daemon's other connections.
+pub async fn send_webhook(
+    config: &SessionSummaryConfig,
+    session_id: &str,
+    summary: &SessionSummary,
+) -> Result<()> {
+    let Some(ref url) = config.webhook else {
+        return Ok(());
+    };
+    let payload = serde_json::json!({
+        "session_id": session_id,
+        "summary": summary,
+    });
+    let headers = config
+        .headers
+        .iter()
+        .map(|(k, v)| (k.clone(), crate::logging::expand_env_vars(v)))
+        .collect();
+    crate::logging::post_via_curl(
+        url,
+        &serde_json::to_vec(&payload)?,
+        &headers,
+        std::time::Duration::from_secs(config.timeout_secs),
+    )
+    .await
+}
+
+/// Consecutive blocked Stop attempts recorded for a session
+pub fn stop_blocks(session_id: &str) -> u32 {
+    std::fs::read_to_string(stop_path(&sessions_dir(), session_id))
+        .ok()
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Record the consecutive blocked Stop attempts (0 clears the counter)
+pub fn set_stop_blocks(session_id: &str, count: u32) -> Result<()> {
+    let dir = sessions_dir();
+    let path = stop_path(&dir, session_id);
+    if count == 0 {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::write(&path, count.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn stop_path(dir: &Path, session_id: &str) -> PathBuf {
+    state_path(dir, session_id).with_extension("stop")
+}
+
+/// Directory holding per-session state files
+pub(crate) fn sessions_dir() -> PathBuf {
+    Logger::default_log_path()
+        .parent()
+        .map_or_else(|| PathBuf::from("sessions"), |dir| dir.join("sessions"))
+}
+
+/// State file for a session; the ID is sanitized so it can't escape `dir`
+fn state_path(dir: &Path, session_id: &str) -> PathBuf {
+    let name: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{name}.json"))
+}
+
+fn load(path: &Path) -> Option<SessionSummary> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: EventType, tool_name: Option<&str>) -> Event {
+        Event {
+            hook_event_name: event_type,
+            tool_name: tool_name.map(String::from),
+            tool_input: None,
+            session_id: "../abc".to_string(),
+            timestamp: Utc::now(),
+            user_id: None,
+            transcript_path: None,
+            cwd: None,
+            permission_mode: None,
+            tool_use_id: None,
+            prompt: None,
+            tool_response: None,
+            stop_hook_active: None,
+            trigger: None,
+            custom_instructions: None,
+            message: None,
+            pre_tool_use: None,
+            identity: None,
+        }
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let rules = vec!["no-force-push".to_string()];
+
+        let start = event(EventType::SessionStart, None);
+        assert!(
+            track_in(dir, &start, &[], &Outcome::Inject)
+                .unwrap()
+                .is_none()
+        );
+
+        let bash = event(EventType::PreToolUse, Some("Bash"));
+        track_in(dir, &bash, &rules, &Outcome::Block).unwrap();
+        track_in(dir, &bash, &[], &Outcome::Allow).unwrap();
+
+        let path = state_path(dir, "../abc");
+        assert!(path.starts_with(dir) && path.exists());
+
+        let end = event(EventType::SessionEnd, None);
+        let summary = track_in(dir, &end, &[], &Outcome::Allow).unwrap().unwrap();
+        assert_eq!(summary.events, 4);
+        assert_eq!(summary.blocked, 1);
+        assert_eq!(summary.injected, 1);
+        assert_eq!(summary.rules_matched["no-force-push"], 1);
+        assert_eq!(summary.tools["Bash"], 2);
+        assert!(summary.started_at.is_some() && summary.ended_at.is_some());
+        assert!(!path.exists(), "state is flushed on SessionEnd");
+    }
+
+    #[test]
+    fn test_session_start_resets_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let bash = event(EventType::PreToolUse, Some("Bash"));
+        track_in(dir, &bash, &[], &Outcome::Allow).unwrap();
+
+        let start = event(EventType::SessionStart, None);
+        track_in(dir, &start, &[], &Outcome::Allow).unwrap();
+        let summary = load(&state_path(dir, "../abc")).unwrap();
+        assert_eq!(summary.events, 1);
+        assert!(summary.tools.is_empty());
+    }
+
+    #[test]
+    fn test_tool_use_record_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let mut pre = event(EventType::PreToolUse, Some("Bash"));
+        pre.tool_use_id = Some("toolu_01".to_string());
+        let rules = vec!["warn-on-deploy".to_string()];
+
+        record_tool_use_in(dir, &pre, &rules, &Outcome::Inject).unwrap();
+        let record = take_tool_use_in(dir, "toolu_01").unwrap();
+        assert_eq!(record.outcome, Outcome::Inject);
+        assert_eq!(record.rules_matched, rules);
+        assert!(
+            take_tool_use_in(dir, "toolu_01").is_none(),
+            "records are consumed"
+        );
+
+        // Blocked calls never reach PostToolUse, so nothing is kept
+        pre.tool_use_id = Some("toolu_02".to_string());
+        record_tool_use_in(dir, &pre, &rules, &Outcome::Block).unwrap();
+        assert!(take_tool_use_in(dir, "toolu_02").is_none());
+    }
+
+    #[test]
+    fn test_claim_warning_repeats_every_n_events_or_minutes() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let config = WarnThrottle {
+            every_events: Some(3),
+            every_minutes: Some(10),
+        };
+        let mut pre = event(EventType::PreToolUse, Some("Bash"));
+
+        let shown: Vec<Option<u32>> = (0..5)
+            .map(|_| claim_warning_in(dir, &config, &pre, "no-force-push").unwrap())
+            .collect();
+        assert_eq!(shown, [Some(0), None, None, Some(2), None]);
+
+        // Other rules are throttled separately
+        assert_eq!(
+            claim_warning_in(dir, &config, &pre, "other").unwrap(),
+            Some(0)
+        );
+
+        pre.timestamp += chrono::Duration::minutes(10);
+        assert_eq!(
+            claim_warning_in(dir, &config, &pre, "no-force-push").unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_claim_warning_once_per_session_without_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let config = WarnThrottle::default();
+        let mut pre = event(EventType::PreToolUse, Some("Bash"));
+
+        assert_eq!(claim_warning_in(dir, &config, &pre, "r").unwrap(), Some(0));
+        pre.timestamp += chrono::Duration::days(1);
+        assert_eq!(claim_warning_in(dir, &config, &pre, "r").unwrap(), None);
+    }
+
+    #[test]
+    fn test_claim_warning_concurrent_claims_count_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let config = WarnThrottle::default();
+        let pre = event(EventType::PreToolUse, Some("Bash"));
+
+        let shown = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| claim_warning_in(dir, &config, &pre, "r").unwrap()))
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().unwrap())
+                .count()
+        });
+        assert_eq!(shown, 1);
+
+        // Every suppressed claim was counted
+        let mut later = pre.clone();
+        later.timestamp += chrono::Duration::minutes(1);
+        let config = WarnThrottle {
+            every_events: Some(1),
+            every_minutes: None,
+        };
+        assert_eq!(
+            claim_warning_in(dir, &config, &later, "r").unwrap(),
+            Some(7)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_webhook_does_not_block_runtime() {
+        // Accepts connections but never answers, so curl runs to its timeout
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = SessionSummaryConfig {
+            webhook: Some(format!("http://{}/summary", listener.local_addr().unwrap())),
+            timeout_secs: 1,
+            ..SessionSummaryConfig::default()
+        };
+
+        let summary = SessionSummary::default();
+        let started = std::time::Instant::now();
+        let (sent, ticked) = tokio::join!(send_webhook(&config, "abc", &summary), async {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            started.elapsed()
+        });
+        assert!(sent.is_err(), "the endpoint never answers");
+        assert!(
+            ticked < std::time::Duration::from_millis(500),
+            "other tasks stalled for {ticked:?}"
+        );
+    }
+}
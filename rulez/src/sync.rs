@@ -0,0 +1,1012 @@
+//! Central policy sync (`rulez sync`).
+//!
+//! Org-wide guardrails are published as a policy bundle: rule files in the
+//! built-in pack format (a YAML list of rules), a `manifest.json` listing each
+//! file's SHA-256, and a `manifest.sig` ed25519 signature of the manifest.
+//! `rulez sync` fetches the bundle, verifies it, reports what changed, and
+//! installs it (manifest and signature included) into the overlay directory.
+//! Configs with `settings.sync` verify the overlay again each time they load
+//! it, then add its rules on top of their own.
+//!
+//! ```yaml
+//! settings:
+//!   sync:
+//!     source: "https://policy.example.com/guardrails.tar"
+//!     public_key: "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"
+//!     interval_hours: 12
+//! ```
+//!
+//! Only the publisher holds the private key, so clients that can read the
+//! config can check bundles but not forge them. Without `public_key`, sync
+//! refuses to run unless `allow_unsigned: true` is set, and even then never
+//! over plain `http://` or `git://`.
+//!
+//! A source is an HTTPS URL of a bundle tar (fetched with curl), a git
+//! repository (`git+https://...` or a URL ending in `.git`, cloned with git),
+//! or a local bundle tar or directory. Overlay rules replace local rules
+//! with the same name, so a project config can't shadow a guardrail.
+//! `rulez sync sign` writes the manifest and signature for publishers.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::audit_bundle::{BLOCK, ManifestFile, read_tar, write_tar_entry};
+use crate::config::{Config, Settings};
+use crate::digest::expand_home;
+use crate::log_chain::hex;
+use crate::models::Rule;
+use ed25519_dalek::{Signature, Signer as _, SigningKey, VerifyingKey};
+
+/// `format` field of a policy manifest
+pub const POLICY_FORMAT: &str = "rulez-policy-bundle";
+
+/// Version of the policy bundle layout
+pub const POLICY_VERSION: u32 = 1;
+
+/// Name of the manifest in a bundle
+pub const MANIFEST: &str = "manifest.json";
+
+/// Name of the manifest signature in a bundle
+pub const SIGNATURE: &str = "manifest.sig";
+
+/// Written into the overlay directory by each sync
+const STATE_FILE: &str = ".sync-state.json";
+
+const SIGNATURE_PREFIX: &str = "ed25519:";
+
+/// Central policy settings (`settings.sync`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncConfig {
+    /// HTTPS bundle URL, git repository, or local bundle tar or directory
+    pub source: String,
+
+    /// Branch or tag for git sources (defaults to the remote's default branch)
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+
+    /// Overlay directory the policies are installed into (`~` is expanded)
+    #[serde(default = "default_dir")]
+    pub dir: String,
+
+    /// Hex-encoded ed25519 public key; bundles and the installed overlay
+    /// must carry a valid signature from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+
+    /// Accept unsigned bundles when no `public_key` is set (authenticated
+    /// transports only)
+    #[serde(default)]
+    pub allow_unsigned: bool,
+
+    /// Hours between syncs under `rulez daemon --sync`
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+
+    /// Download or clone timeout in seconds
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl SyncConfig {
+    /// Settings for a source with every other field at its default
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            git_ref: None,
+            dir: default_dir(),
+            public_key: None,
+            allow_unsigned: false,
+            interval_hours: default_interval_hours(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+
+    /// The overlay directory, with `~` expanded
+    pub fn overlay_dir(&self) -> PathBuf {
+        expand_home(&self.dir)
+    }
+
+    /// The key bundles must be signed with
+    ///
+    /// `None` only when unsigned policies are explicitly allowed (see
+    /// [`SyncConfig::check_unsigned`]).
+    pub fn verifying_key(&self) -> Result<Option<VerifyingKey>> {
+        let Some(key) = self.public_key.as_deref() else {
+            self.check_unsigned()?;
+            return Ok(None);
+        };
+        decode_hex(key)
+            .and_then(|bytes| {
+                VerifyingKey::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("{}", e))
+            })
+            .map(Some)
+            .context("Invalid settings.sync.public_key")
+    }
+
+    /// Fail unless unsigned policies are allowed for this source
+    ///
+    /// Requires `allow_unsigned: true` and a source whose transport
+    /// authenticates the server, so whoever sits on the network can't
+    /// rewrite the policies.
+    pub fn check_unsigned(&self) -> Result<()> {
+        if !self.allow_unsigned {
+            bail!(
+                "settings.sync.public_key is required to verify policies \
+                 (set allow_unsigned: true to accept unsigned bundles)"
+            );
+        }
+        let source = self.source.to_ascii_lowercase();
+        if ["http://", "git+http://", "git://"]
+            .iter()
+            .any(|scheme| source.starts_with(scheme))
+        {
+            bail!(
+                "Unsigned policies can't be fetched over an unauthenticated transport ({}); \
+                 use https or set settings.sync.public_key",
+                self.source
+            );
+        }
+        Ok(())
+    }
+}
+
+fn default_dir() -> String {
+    "~/.claude/rulez/policy".to_string()
+}
+
+fn default_interval_hours() -> u64 {
+    24
+}
+
+fn default_timeout_secs() -> u64 {
+    60
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<ManifestFile>,
+}
+
+/// What the last sync installed (`.sync-state.json` in the overlay directory)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncState {
+    pub source: String,
+    pub synced_at: DateTime<Utc>,
+    /// Hash over the installed files' names and hashes
+    pub digest: String,
+    pub signed: bool,
+}
+
+/// What a sync changed (or would change, for a dry run)
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SyncReport {
+    pub source: String,
+    pub dir: PathBuf,
+    /// The bundle's signature was checked against the signing key
+    pub signed: bool,
+    pub files_added: Vec<String>,
+    pub files_changed: Vec<String>,
+    pub files_removed: Vec<String>,
+    pub rules_added: Vec<String>,
+    pub rules_changed: Vec<String>,
+    pub rules_removed: Vec<String>,
+    /// The overlay directory was rewritten
+    pub installed: bool,
+}
+
+impl SyncReport {
+    /// True when the bundle matches what is installed
+    pub fn is_unchanged(&self) -> bool {
+        self.files_added.is_empty()
+            && self.files_changed.is_empty()
+            && self.files_removed.is_empty()
+    }
+}
+
+type Files = Vec<(String, Vec<u8>)>;
+
+/// Fetch, verify and (unless `dry_run`) install the policies from `config.source`
+pub fn sync(config: &SyncConfig, key: Option<&VerifyingKey>, dry_run: bool) -> Result<SyncReport> {
+    if key.is_none() {
+        config.check_unsigned()?;
+    }
+    let dir = config.overlay_dir();
+    let bundle = fetch(config, &dir)?;
+    let (files, signed) = verify(bundle.clone(), key)?;
+    let rules = rules_of(&files)?;
+    validate_rules(rules.values().cloned().collect())
+        .context("Synced policies are not a valid config")?;
+
+    let (signature_files, installed): (Files, Files) = if dir.is_dir() {
+        read_dir_files(&dir)?
+            .into_iter()
+            .partition(|(name, _)| name == MANIFEST || name == SIGNATURE)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    // A corrupt overlay is reported as all-new rather than blocking the fix
+    let installed_rules = rules_of(&installed).unwrap_or_default();
+
+    let mut report = SyncReport {
+        source: config.source.clone(),
+        dir: dir.clone(),
+        signed,
+        ..SyncReport::default()
+    };
+    diff(
+        &installed.iter().cloned().collect(),
+        &files.iter().cloned().collect(),
+        &mut report.files_added,
+        &mut report.files_changed,
+        &mut report.files_removed,
+    );
+    diff(
+        &installed_rules,
+        &rules,
+        &mut report.rules_added,
+        &mut report.rules_changed,
+        &mut report.rules_removed,
+    );
+
+    // A re-signed bundle is installed even when the policies are unchanged,
+    // since loads verify the installed manifest and signature
+    let unchanged_state = read_state(&dir).is_some_and(|state| state.signed == signed)
+        && [MANIFEST, SIGNATURE].iter().all(|name| {
+            let find = |files: &Files| files.iter().find(|(n, _)| n == name).cloned();
+            find(&signature_files) == find(&bundle)
+        });
+    if !(dry_run || report.is_unchanged() && unchanged_state) {
+        let state = SyncState {
+            source: config.source.clone(),
+            synced_at: Utc::now(),
+            digest: files_digest(&files),
+            signed,
+        };
+        install(&dir, &bundle, &state)?;
+        report.installed = true;
+    }
+    Ok(report)
+}
+
+/// Fetch the source's files into memory
+fn fetch(config: &SyncConfig, dir: &Path) -> Result<Files> {
+    let source = config.source.as_str();
+    let staging = dir.with_extension(format!("fetch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    let result = if let Some(url) = git_url(source) {
+        fetch_git(
+            url,
+            config.git_ref.as_deref(),
+            config.timeout_secs,
+            &staging,
+        )
+    } else if source.starts_with("https://") || source.starts_with("http://") {
+        fetch_https(source, config.timeout_secs, &staging)
+    } else {
+        let path = expand_home(source);
+        if path.is_dir() {
+            read_dir_files(&path)
+        } else {
+            let archive = std::fs::read(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            read_tar(&archive)
+        }
+    };
+    let _ = std::fs::remove_dir_all(&staging);
+    result.with_context(|| format!("Failed to fetch policies from {}", source))
+}
+
+/// The clone URL of a git source, if it is one
+fn git_url(source: &str) -> Option<&str> {
+    source.strip_prefix("git+").or_else(|| {
+        let is_repo = Path::new(source)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("git"));
+        (is_repo || source.starts_with("git@")).then_some(source)
+    })
+}
+
+fn fetch_https(url: &str, timeout_secs: u64, staging: &Path) -> Result<Files> {
+    std::fs::create_dir_all(staging)?;
+    let archive = staging.join("bundle.tar");
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", &timeout_secs.to_string(), "-o"])
+        .arg(&archive)
+        .arg(url)
+        .output()
+        .context("Failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "curl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    read_tar(&std::fs::read(&archive)?)
+}
+
+fn fetch_git(url: &str, git_ref: Option<&str>, timeout_secs: u64, staging: &Path) -> Result<Files> {
+    let checkout = staging.join("repo");
+    let mut cmd = Command::new("git");
+    cmd.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(git_ref) = git_ref {
+        cmd.args(["--branch", git_ref]);
+    }
+    cmd.arg(url).arg(&checkout);
+    let mut child = cmd
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .context("Failed to run git")?;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("git clone timed out after {}s", timeout_secs);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+    if !status.success() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = std::io::Read::read_to_string(&mut pipe, &mut stderr);
+        }
+        bail!("git clone failed: {}", stderr.trim());
+    }
+    read_dir_files(&checkout)
+}
+
+/// Regular files directly in `dir`, skipping dotfiles (`.git`, the sync state)
+fn read_dir_files(dir: &Path) -> Result<Files> {
+    let mut files = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || !entry.file_type()?.is_file() {
+            continue;
+        }
+        files.push((name, std::fs::read(entry.path())?));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Check the manifest and signature, returning the policy files and whether
+/// the signature was verified
+///
+/// With a key, the bundle must be signed by it. Without one, a manifest is
+/// still checked if present. Files the manifest doesn't list are rejected.
+pub fn verify(files: Files, key: Option<&VerifyingKey>) -> Result<(Files, bool)> {
+    for (name, _) in &files {
+        if name.is_empty()
+            || name.starts_with('.')
+            || name.contains(['/', '\\'])
+            || name.contains("..")
+        {
+            bail!("Bundle file '{}' must be a plain file name", name);
+        }
+    }
+    let find = |name: &str| files.iter().find(|(n, _)| n == name).map(|(_, c)| c);
+    let manifest_json = find(MANIFEST);
+
+    let signed = match (key, manifest_json, find(SIGNATURE)) {
+        (None, _, _) => false,
+        (Some(_), None, _) => bail!("Bundle has no {} but a signing key is configured", MANIFEST),
+        (Some(_), Some(_), None) => bail!("Bundle is not signed but a signing key is configured"),
+        (Some(key), Some(manifest), Some(signature)) => {
+            let signature = String::from_utf8_lossy(signature);
+            let signature = signature
+                .trim()
+                .strip_prefix(SIGNATURE_PREFIX)
+                .context("Bundle signature is not an ed25519 signature")
+                .and_then(decode_hex)
+                .map(|bytes| Signature::from_bytes(&bytes))?;
+            if key.verify_strict(manifest, &signature).is_err() {
+                bail!("Bundle signature does not match the signing key");
+            }
+            true
+        }
+    };
+
+    let Some(manifest_json) = manifest_json else {
+        let policies = files
+            .into_iter()
+            .filter(|(name, _)| name != SIGNATURE)
+            .collect();
+        return Ok((policies, signed));
+    };
+    let manifest: PolicyManifest =
+        serde_json::from_slice(manifest_json).context("Invalid bundle manifest")?;
+    if manifest.format != POLICY_FORMAT {
+        bail!("Not a policy bundle (format '{}')", manifest.format);
+    }
+    if manifest.format_version > POLICY_VERSION {
+        bail!(
+            "Policy bundle format version {} is newer than this rulez supports ({})",
+            manifest.format_version,
+            POLICY_VERSION
+        );
+    }
+
+    let mut policies = Vec::new();
+    for listed in &manifest.files {
+        let Some(content) = find(&listed.path) else {
+            bail!("{} is listed in the manifest but missing", listed.path);
+        };
+        if hex(&Sha256::digest(content)) != listed.sha256 {
+            bail!("{} does not match its manifest hash", listed.path);
+        }
+        policies.push((listed.path.clone(), content.clone()));
+    }
+    for (name, _) in &files {
+        if name != MANIFEST && name != SIGNATURE && !manifest.files.iter().any(|f| &f.path == name)
+        {
+            bail!("{} is not listed in the manifest", name);
+        }
+    }
+    policies.sort();
+    Ok((policies, signed))
+}
+
+/// Decode a fixed-length hex string (keys and signatures)
+pub fn decode_hex<const N: usize>(hex: &str) -> Result<[u8; N]> {
+    let hex = hex.trim();
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        bail!("expected {} hex characters, got {}", N * 2, hex.len());
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .with_context(|| format!("invalid hex at position {}", i * 2))?;
+    }
+    Ok(bytes)
+}
+
+/// Rules in the bundle's YAML files, by name
+fn rules_of(files: &Files) -> Result<BTreeMap<String, Rule>> {
+    let mut rules = BTreeMap::new();
+    for (name, content) in files {
+        if !is_policy_file(name) {
+            continue;
+        }
+        let text =
+            std::str::from_utf8(content).with_context(|| format!("{} is not UTF-8", name))?;
+        let parsed: Vec<Rule> =
+            serde_yaml::from_str(text).with_context(|| format!("Failed to parse {}", name))?;
+        for rule in parsed {
+            if rules.contains_key(&rule.name) {
+                bail!(
+                    "Rule '{}' is defined twice in the synced policies",
+                    rule.name
+                );
+            }
+            rules.insert(rule.name.clone(), rule);
+        }
+    }
+    Ok(rules)
+}
+
+/// Validate rules as a config of their own
+fn validate_rules(rules: Vec<Rule>) -> Result<()> {
+    Config {
+        rules,
+        ..Config::default()
+    }
+    .validate()?;
+    Ok(())
+}
+
+fn is_policy_file(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml")
+}
+
+/// Record keys of `new` missing from, different from, or absent in `old`
+fn diff<T: PartialEq>(
+    old: &BTreeMap<String, T>,
+    new: &BTreeMap<String, T>,
+    added: &mut Vec<String>,
+    changed: &mut Vec<String>,
+    removed: &mut Vec<String>,
+) {
+    for (key, value) in new {
+        match old.get(key) {
+            None => added.push(key.clone()),
+            Some(previous) if previous != value => changed.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    removed.extend(old.keys().filter(|key| !new.contains_key(*key)).cloned());
+}
+
+fn files_digest(files: &Files) -> String {
+    let mut hasher = Sha256::new();
+    for (name, content) in files {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(hex(&Sha256::digest(content)).as_bytes());
+        hasher.update([b'\n']);
+    }
+    hex(&hasher.finalize())
+}
+
+/// Replace the overlay directory with the bundle's files in one rename
+fn install(dir: &Path, files: &Files, state: &SyncState) -> Result<()> {
+    let pid = std::process::id();
+    let fresh = dir.with_extension(format!("new-{}", pid));
+    let old = dir.with_extension(format!("old-{}", pid));
+    let _ = std::fs::remove_dir_all(&fresh);
+    std::fs::create_dir_all(&fresh)
+        .with_context(|| format!("Failed to create {}", fresh.display()))?;
+    for (name, content) in files {
+        std::fs::write(fresh.join(name), content)?;
+    }
+    std::fs::write(fresh.join(STATE_FILE), serde_json::to_vec_pretty(state)?)?;
+
+    if dir.exists() {
+        std::fs::rename(dir, &old)
+            .with_context(|| format!("Failed to replace {}", dir.display()))?;
+    }
+    if let Err(e) = std::fs::rename(&fresh, dir) {
+        let _ = std::fs::rename(&old, dir);
+        return Err(e).with_context(|| format!("Failed to install {}", dir.display()));
+    }
+    let _ = std::fs::remove_dir_all(&old);
+    Ok(())
+}
+
+fn read_state(dir: &Path) -> Option<SyncState> {
+    let bytes = std::fs::read(dir.join(STATE_FILE)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Digest of the installed overlay, for cache invalidation (None without `settings.sync`)
+pub fn overlay_digest(settings: &Settings) -> Option<String> {
+    settings
+        .sync
+        .as_ref()
+        .and_then(|sync| read_state(&sync.overlay_dir()))
+        .map(|state| state.digest)
+}
+
+/// Add the overlay's rules to a config with `settings.sync`
+///
+/// The overlay is verified as a fresh bundle would be, so with a
+/// `public_key` edited or unsigned policies fail the load. Overlay rules
+/// replace local rules of the same name. Nothing happens until the first
+/// sync has installed the overlay; an overlay directory with files but no
+/// readable sync state fails the load rather than dropping its policies.
+pub fn apply_overlay(config: &mut Config) -> Result<()> {
+    let Some(ref sync) = config.settings.sync else {
+        return Ok(());
+    };
+    let dir = sync.overlay_dir();
+    if read_state(&dir).is_none() {
+        if std::fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_some()) {
+            bail!(
+                "Synced policies in {} have no readable {}; remove the directory and run `rulez sync`",
+                dir.display(),
+                STATE_FILE
+            );
+        }
+        tracing::debug!("No synced policies in {} yet", dir.display());
+        return Ok(());
+    }
+    let key = sync.verifying_key()?;
+    let (files, _) = verify(read_dir_files(&dir)?, key.as_ref())
+        .with_context(|| format!("Synced policies in {} failed verification", dir.display()))?;
+    let mut rules = rules_of(&files)
+        .with_context(|| format!("Failed to load synced policies from {}", dir.display()))?;
+    for rule in rules.values_mut() {
+        crate::config::resolve_rule_paths(std::slice::from_mut(rule), &dir);
+    }
+    config.rules.retain(|rule| {
+        let replaced = rules.contains_key(&rule.name);
+        if replaced {
+            tracing::debug!("Synced policy replaces local rule '{}'", rule.name);
+        }
+        !replaced
+    });
+    config.rules.extend(rules.into_values());
+    Ok(())
+}
+
+/// Write a manifest (and with a private key, a signature) for the policies in `dir`
+///
+/// With `output`, a bundle tar is written there; otherwise `manifest.json`
+/// and `manifest.sig` are written into `dir`, for publishing from git.
+pub fn sign(dir: &Path, key: Option<&SigningKey>, output: Option<&Path>) -> Result<PolicyManifest> {
+    let files: Files = read_dir_files(dir)?
+        .into_iter()
+        .filter(|(name, _)| name != MANIFEST && name != SIGNATURE)
+        .collect();
+    let rules = rules_of(&files)?;
+    if rules.is_empty() {
+        bail!(
+            "No rules found in {} (expected *.yaml rule lists)",
+            dir.display()
+        );
+    }
+    validate_rules(rules.into_values().collect()).context("Policies are not a valid config")?;
+
+    let now = Utc::now();
+    let manifest = PolicyManifest {
+        format: POLICY_FORMAT.to_string(),
+        format_version: POLICY_VERSION,
+        created_at: now,
+        files: files
+            .iter()
+            .map(|(path, content)| ManifestFile {
+                path: path.clone(),
+                sha256: hex(&Sha256::digest(content)),
+                bytes: content.len() as u64,
+            })
+            .collect(),
+    };
+    let mut manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    manifest_json.push(b'\n');
+    let signature = key.map(|key| {
+        format!(
+            "{}{}\n",
+            SIGNATURE_PREFIX,
+            hex(&key.sign(&manifest_json).to_bytes())
+        )
+    });
+
+    if let Some(output) = output {
+        let mut archive = Vec::new();
+        let mtime = u64::try_from(now.timestamp()).unwrap_or(0);
+        for (path, content) in &files {
+            write_tar_entry(&mut archive, path, content, mtime)?;
+        }
+        write_tar_entry(&mut archive, MANIFEST, &manifest_json, mtime)?;
+        if let Some(ref signature) = signature {
+            write_tar_entry(&mut archive, SIGNATURE, signature.as_bytes(), mtime)?;
+        }
+        archive.extend_from_slice(&[0u8; BLOCK * 2]);
+        std::fs::write(output, archive)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+    } else {
+        std::fs::write(dir.join(MANIFEST), &manifest_json)?;
+        match signature {
+            Some(signature) => std::fs::write(dir.join(SIGNATURE), signature)?,
+            None => {
+                let _ = std::fs::remove_file(dir.join(SIGNATURE));
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// Sync every `settings.sync.interval_hours` until the daemon stops
+///
+/// Settings are re-read before each sync. Failures are logged and retried
+/// at the next interval.
+pub async fn run_schedule() -> Result<()> {
+    loop {
+        let settings = Config::load(None)?.settings.sync;
+        let Some(settings) = settings else {
+            bail!("--sync needs settings.sync");
+        };
+        let interval = std::time::Duration::from_secs(settings.interval_hours.max(1) * 3600);
+        let task = settings.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let key = task.verifying_key()?;
+            sync(&task, key.as_ref(), false)
+        })
+        .await?;
+        if let Ok(ref report) = result
+            && !report.signed
+        {
+            tracing::warn!(
+                "Policies from {} are unsigned (settings.sync.allow_unsigned)",
+                report.source
+            );
+        }
+        match result {
+            Ok(report) if report.installed => tracing::info!(
+                "Synced policies from {}: {} rules added, {} changed, {} removed",
+                report.source,
+                report.rules_added.len(),
+                report.rules_changed.len(),
+                report.rules_removed.len()
+            ),
+            Ok(_) => tracing::debug!("Policies from {} are up to date", settings.source),
+            Err(e) => tracing::warn!("Failed to sync policies from {}: {:#}", settings.source, e),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn private_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn public_key() -> VerifyingKey {
+        private_key().verifying_key()
+    }
+
+    fn write_policies(dir: &Path, extra: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join("guardrails.yaml"),
+            format!(
+                r#"
+- name: org-no-prod-db
+  matchers:
+    tools: ["Bash"]
+    command_match: "psql.*prod"
+  actions:
+    block: true
+{extra}"#
+            ),
+        )
+        .unwrap();
+    }
+
+    fn config(source: &Path, overlay: &Path) -> SyncConfig {
+        SyncConfig {
+            dir: overlay.to_string_lossy().into_owned(),
+            ..SyncConfig::new(source.to_string_lossy())
+        }
+    }
+
+    #[test]
+    fn test_sign_and_sync_bundle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        let bundle = tmp.path().join("bundle.tar");
+        let overlay = tmp.path().join("overlay");
+        write_policies(&published, "");
+        sign(&published, Some(&private_key()), Some(&bundle)).unwrap();
+
+        let config = config(&bundle, &overlay);
+        let report = sync(&config, Some(&public_key()), true).unwrap();
+        assert!(report.signed);
+        assert_eq!(report.rules_added, vec!["org-no-prod-db"]);
+        assert!(!report.installed);
+        assert!(!overlay.exists());
+
+        let report = sync(&config, Some(&public_key()), false).unwrap();
+        assert!(report.installed);
+        assert!(overlay.join("guardrails.yaml").exists());
+        assert!(read_state(&overlay).is_some_and(|s| s.signed));
+
+        let report = sync(&config, Some(&public_key()), false).unwrap();
+        assert!(report.is_unchanged());
+        assert!(!report.installed);
+    }
+
+    #[test]
+    fn test_sync_reports_rule_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        let overlay = tmp.path().join("overlay");
+        write_policies(
+            &published,
+            "- name: org-old\n  matchers: { tools: [\"Write\"] }\n  actions: { block: true }\n",
+        );
+        sign(&published, Some(&private_key()), None).unwrap();
+        let config = config(&published, &overlay);
+        sync(&config, Some(&public_key()), false).unwrap();
+
+        std::fs::write(
+            published.join("guardrails.yaml"),
+            "- name: org-no-prod-db\n  matchers: { tools: [\"Bash\"] }\n  actions: { block: true }\n- name: org-new\n  matchers: { tools: [\"Edit\"] }\n  actions: { block: true }\n",
+        )
+        .unwrap();
+        sign(&published, Some(&private_key()), None).unwrap();
+        let report = sync(&config, Some(&public_key()), false).unwrap();
+        assert_eq!(report.files_changed, vec!["guardrails.yaml"]);
+        assert_eq!(report.rules_added, vec!["org-new"]);
+        assert_eq!(report.rules_changed, vec!["org-no-prod-db"]);
+        assert_eq!(report.rules_removed, vec!["org-old"]);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        write_policies(&published, "");
+        sign(&published, Some(&private_key()), None).unwrap();
+        let files = read_dir_files(&published).unwrap();
+
+        assert!(verify(files.clone(), Some(&public_key())).unwrap().1);
+        let err = verify(
+            files.clone(),
+            Some(&SigningKey::from_bytes(&[8; 32]).verifying_key()),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("signature does not match"));
+
+        let mut tampered = files.clone();
+        tampered[0].1.extend_from_slice(b"\n# edited\n");
+        let err = verify(tampered, None).unwrap_err();
+        assert!(err.to_string().contains("does not match its manifest hash"));
+
+        let mut extra = files.clone();
+        extra.push(("sneaky.yaml".to_string(), b"[]".to_vec()));
+        let err = verify(extra, Some(&public_key())).unwrap_err();
+        assert!(err.to_string().contains("not listed in the manifest"));
+
+        let unsigned: Files = files
+            .into_iter()
+            .filter(|(name, _)| name != SIGNATURE)
+            .collect();
+        assert!(!verify(unsigned.clone(), None).unwrap().1);
+        assert!(verify(unsigned, Some(&public_key())).is_err());
+    }
+
+    #[test]
+    fn test_overlay_replaces_local_rules() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        let overlay = tmp.path().join("overlay");
+        write_policies(&published, "");
+        sign(&published, None, None).unwrap();
+        let sync_config = SyncConfig {
+            dir: overlay.to_string_lossy().into_owned(),
+            allow_unsigned: true,
+            ..SyncConfig::new(published.to_string_lossy())
+        };
+
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: org-no-prod-db
+    mode: audit
+    matchers: { tools: ["Bash"] }
+    actions: { block: true }
+  - name: local
+    matchers: { tools: ["Write"] }
+    actions: { block: true }
+"#,
+        )
+        .unwrap();
+        config.settings.sync = Some(sync_config.clone());
+        assert!(overlay_digest(&config.settings).is_none());
+        apply_overlay(&mut config).unwrap();
+        assert_eq!(config.rules.len(), 2);
+
+        sync(&sync_config, None, false).unwrap();
+        assert!(overlay_digest(&config.settings).is_some());
+        apply_overlay(&mut config).unwrap();
+        let names: Vec<&str> = config.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["local", "org-no-prod-db"]);
+        assert_eq!(config.rules[1].mode, None);
+    }
+
+    #[test]
+    fn test_overlay_is_verified_on_load() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        let overlay = tmp.path().join("overlay");
+        write_policies(&published, "");
+        sign(&published, Some(&private_key()), None).unwrap();
+        let sync_config = SyncConfig {
+            dir: overlay.to_string_lossy().into_owned(),
+            public_key: Some(hex(public_key().as_bytes())),
+            ..SyncConfig::new(published.to_string_lossy())
+        };
+        sync(&sync_config, Some(&public_key()), false).unwrap();
+        assert!(overlay.join(MANIFEST).exists());
+
+        let mut config = Config::default();
+        config.settings.sync = Some(sync_config);
+        apply_overlay(&mut config.clone()).unwrap();
+
+        // Emptying the installed rules breaks the manifest hash
+        std::fs::write(overlay.join("guardrails.yaml"), "[]\n").unwrap();
+        let err = apply_overlay(&mut config.clone()).unwrap_err();
+        assert!(format!("{err:#}").contains("does not match its manifest hash"));
+
+        // A re-listed overlay without the publisher's signature is rejected too
+        write_policies(&overlay, "");
+        sign(&overlay, None, None).unwrap();
+        let err = apply_overlay(&mut config).unwrap_err();
+        assert!(format!("{err:#}").contains("not signed"));
+    }
+
+    #[test]
+    fn test_overlay_without_state_fails_closed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        let overlay = tmp.path().join("overlay");
+        write_policies(&published, "");
+        sign(&published, Some(&private_key()), None).unwrap();
+        let sync_config = SyncConfig {
+            dir: overlay.to_string_lossy().into_owned(),
+            public_key: Some(hex(public_key().as_bytes())),
+            ..SyncConfig::new(published.to_string_lossy())
+        };
+        let mut config = Config::default();
+        config.settings.sync = Some(sync_config.clone());
+
+        // An empty overlay directory is not an installed overlay yet
+        std::fs::create_dir_all(&overlay).unwrap();
+        apply_overlay(&mut config.clone()).unwrap();
+
+        sync(&sync_config, Some(&public_key()), false).unwrap();
+        std::fs::remove_file(overlay.join(STATE_FILE)).unwrap();
+        let err = apply_overlay(&mut config.clone()).unwrap_err();
+        assert!(format!("{err:#}").contains("no readable .sync-state.json"));
+
+        std::fs::write(overlay.join(STATE_FILE), "not json").unwrap();
+        assert!(apply_overlay(&mut config).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_bundles_rejected_by_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        let published = tmp.path().join("published");
+        let overlay = tmp.path().join("overlay");
+        write_policies(&published, "");
+        sign(&published, None, None).unwrap();
+
+        let mut config = config(&published, &overlay);
+        let err = config.verifying_key().unwrap_err();
+        assert!(err.to_string().contains("public_key is required"), "{err}");
+        let err = sync(&config, None, false).unwrap_err();
+        assert!(err.to_string().contains("public_key is required"), "{err}");
+        assert!(!overlay.exists());
+
+        // The opt-in still refuses plaintext transports
+        config.allow_unsigned = true;
+        assert!(config.verifying_key().unwrap().is_none());
+        for source in ["http://policy.example.com/b.tar", "git://example.com/p.git"] {
+            let plaintext = SyncConfig {
+                source: source.to_string(),
+                ..config.clone()
+            };
+            let err = plaintext.verifying_key().unwrap_err();
+            assert!(err.to_string().contains("unauthenticated"), "{err}");
+        }
+        let report = sync(&config, None, false).unwrap();
+        assert!(report.installed && !report.signed);
+
+        // An installed unsigned overlay doesn't load without the opt-in
+        let mut loaded = Config::default();
+        config.allow_unsigned = false;
+        loaded.settings.sync = Some(config);
+        assert!(apply_overlay(&mut loaded).is_err());
+    }
+
+    #[test]
+    fn test_bundle_file_names_must_be_plain() {
+        let files = vec![("../escape.yaml".to_string(), b"[]".to_vec())];
+        assert!(verify(files, None).is_err());
+    }
+
+    #[test]
+    fn test_git_url() {
+        assert_eq!(
+            git_url("git+https://example.com/policies"),
+            Some("https://example.com/policies")
+        );
+        assert_eq!(
+            git_url("https://github.com/acme/policies.git"),
+            Some("https://github.com/acme/policies.git")
+        );
+        assert_eq!(git_url("https://example.com/bundle.tar"), None);
+    }
+}
@@ -0,0 +1,461 @@
+//! Tickets for rules that keep blocking.
+//!
+//! With `settings.anomaly_detection.ticket` set, a rule that blocks more than
+//! `threshold` times within the anomaly window gets an issue filed, so policy
+//! friction is triaged instead of silently retried around. The ticket carries
+//! the aggregated evidence from the block history: counts, sessions, and the
+//! attempts that were blocked.
+//!
+//! Tickets are filed by a shell command (e.g. `gh issue create`), a webhook
+//! (GitHub or Jira issue APIs, or any JSON endpoint), or both. One rule files
+//! at most one ticket per `cooldown_secs`.
+//!
+//! ```yaml
+//! settings:
+//!   anomaly_detection:
+//!     window_secs: 3600
+//!     ticket:
+//!       threshold: 10
+//!       command: 'gh issue create --title "$RULEZ_TICKET_TITLE" --body-file -'
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Most attempts listed in a ticket body
+const MAX_ATTEMPTS: usize = 20;
+
+/// Ticket settings (`settings.anomaly_detection.ticket`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TicketConfig {
+    /// Blocks by one rule within the anomaly window before a ticket is filed
+    #[serde(default = "default_threshold")]
+    pub threshold: u32,
+
+    /// Shell command that files the ticket; the body is passed on stdin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// URL the ticket is POSTed to as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+
+    /// Shape of the webhook payload
+    #[serde(default)]
+    pub format: TicketFormat,
+
+    /// Jira project key (`format: jira`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+
+    /// Jira issue type (`format: jira`)
+    #[serde(default = "default_issue_type")]
+    pub issue_type: String,
+
+    /// Labels added to the issue
+    #[serde(default = "default_labels")]
+    pub labels: Vec<String>,
+
+    /// Extra HTTP headers for the webhook (`${VAR}` is expanded)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+
+    /// Webhook timeout in seconds
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Minimum time between tickets for the same rule, in seconds
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for TicketConfig {
+    fn default() -> Self {
+        Self {
+            threshold: default_threshold(),
+            command: None,
+            webhook: None,
+            format: TicketFormat::default(),
+            project: None,
+            issue_type: default_issue_type(),
+            labels: default_labels(),
+            headers: HashMap::new(),
+            timeout_secs: default_timeout_secs(),
+            cooldown_secs: default_cooldown_secs(),
+        }
+    }
+}
+
+fn default_threshold() -> u32 {
+    10
+}
+
+fn default_issue_type() -> String {
+    "Task".to_string()
+}
+
+fn default_labels() -> Vec<String> {
+    vec!["rulez".to_string()]
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+fn default_cooldown_secs() -> u64 {
+    86_400
+}
+
+/// Webhook payload shape
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketFormat {
+    /// The ticket with its evidence as plain JSON
+    #[default]
+    Generic,
+    /// GitHub's create-issue API (`POST /repos/{owner}/{repo}/issues`)
+    Github,
+    /// Jira's create-issue API (`POST /rest/api/2/issue`)
+    Jira,
+}
+
+/// One blocked attempt listed as evidence
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TicketAttempt {
+    pub at: DateTime<Utc>,
+    pub session_id: String,
+    /// Tool and command, path or prompt, shortened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<String>,
+}
+
+/// A ticket for a rule that crossed the threshold
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ticket {
+    pub rule: String,
+    /// Blocks by the rule within the window
+    pub count: u32,
+    pub window_secs: u64,
+    /// Distinct sessions among those blocks
+    pub sessions: usize,
+    /// Most recent attempts, oldest first
+    pub attempts: Vec<TicketAttempt>,
+}
+
+impl Ticket {
+    /// Build a ticket from the rule's blocks within the window, oldest first
+    pub fn new(rule: &str, window_secs: u64, blocks: Vec<TicketAttempt>) -> Self {
+        let sessions = blocks
+            .iter()
+            .map(|b| b.session_id.as_str())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        let count = u32::try_from(blocks.len()).unwrap_or(u32::MAX);
+        let skip = blocks.len().saturating_sub(MAX_ATTEMPTS);
+        Self {
+            rule: rule.to_string(),
+            count,
+            window_secs,
+            sessions,
+            attempts: blocks.into_iter().skip(skip).collect(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!(
+            "RuleZ: rule '{}' blocked {} times in {}",
+            self.rule,
+            self.count,
+            describe_window(self.window_secs)
+        )
+    }
+
+    /// Markdown body with the aggregated evidence
+    pub fn body(&self) -> String {
+        let mut out = format!(
+            "Rule `{}` blocked **{}** events from **{}** session(s) in {}.\n\n\
+             Frequent blocks usually mean the rule is too broad, or agents need \
+             an approved way to do what they keep attempting. Review the attempts \
+             below and tune the rule, document the alternative, or close this issue.\n\n",
+            self.rule,
+            self.count,
+            self.sessions,
+            describe_window(self.window_secs)
+        );
+        if !self.attempts.is_empty() {
+            let shown = self.attempts.len();
+            if shown < self.count as usize {
+                let _ = write!(out, "Last {} attempts:\n\n", shown);
+            } else {
+                out.push_str("Attempts:\n\n");
+            }
+            out.push_str("| Time (UTC) | Session | Attempt |\n|---|---|---|\n");
+            for attempt in &self.attempts {
+                let _ = writeln!(
+                    out,
+                    "| {} | `{}` | {} |",
+                    attempt.at.format("%Y-%m-%d %H:%M:%S"),
+                    attempt.session_id,
+                    attempt
+                        .attempt
+                        .as_deref()
+                        .map_or_else(|| "-".to_string(), |a| format!("`{}`", table_cell(a)))
+                );
+            }
+            out.push('\n');
+        }
+        let _ = writeln!(
+            out,
+            "Filed by rulez {} (`settings.anomaly_detection.ticket`). \
+             Run `rulez stats --per-rule` or `rulez explain rule {}` for more detail.",
+            env!("CARGO_PKG_VERSION"),
+            self.rule
+        );
+        out
+    }
+
+    /// Webhook payload for the configured format
+    pub fn payload(&self, config: &TicketConfig) -> serde_json::Value {
+        match config.format {
+            TicketFormat::Generic => serde_json::json!({
+                "title": self.title(),
+                "body": self.body(),
+                "labels": config.labels,
+                "ticket": self,
+            }),
+            TicketFormat::Github => serde_json::json!({
+                "title": self.title(),
+                "body": self.body(),
+                "labels": config.labels,
+            }),
+            TicketFormat::Jira => serde_json::json!({
+                "fields": {
+                    "project": { "key": config.project },
+                    "summary": self.title(),
+                    "description": self.body(),
+                    "issuetype": { "name": config.issue_type },
+                    "labels": config.labels,
+                }
+            }),
+        }
+    }
+}
+
+fn describe_window(secs: u64) -> String {
+    match secs {
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Keep a value on one table row
+fn table_cell(value: &str) -> String {
+    value
+        .replace('\n', " ")
+        .replace('|', "\\|")
+        .replace('`', "'")
+}
+
+/// Claim the rule's cooldown slot; `false` if a ticket was filed too recently
+///
+/// Records `now` as the rule's last ticket when it returns `true`.
+pub fn claim(config: &TicketConfig, rule: &str, now: DateTime<Utc>) -> Result<bool> {
+    claim_in(&state_path(), config, rule, now)
+}
+
+fn claim_in(path: &Path, config: &TicketConfig, rule: &str, now: DateTime<Utc>) -> Result<bool> {
+    let mut filed: HashMap<String, DateTime<Utc>> = std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    let cooldown = Duration::seconds(i64::try_from(config.cooldown_secs).unwrap_or(i64::MAX));
+    if filed.get(rule).is_some_and(|last| now - *last < cooldown) {
+        return Ok(false);
+    }
+    filed.retain(|_, last| now - *last < cooldown);
+    filed.insert(rule.to_string(), now);
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, serde_json::to_vec(&filed)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(true)
+}
+
+/// File the ticket through the configured command and webhook
+///
+/// The command runs through the shell in the background with the body on
+/// stdin and `RULEZ_TICKET_TITLE`, `RULEZ_TICKET_RULE`, `RULEZ_TICKET_COUNT`
+/// and `RULEZ_TICKET_JSON` set; its output is discarded.
+pub fn file(config: &TicketConfig, ticket: &Ticket) -> Result<()> {
+    if config.command.is_none() && config.webhook.is_none() {
+        anyhow::bail!("ticket has neither a command nor a webhook");
+    }
+    let mut result = Ok(());
+    if let Some(ref command) = config.command {
+        result = run_command(command, ticket);
+    }
+    if let Some(ref url) = config.webhook {
+        let headers = config
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), crate::logging::expand_env_vars(v)))
+            .collect();
+        let sent = crate::logging::send_via_curl(
+            url,
+            &serde_json::to_vec(&ticket.payload(config))?,
+            &headers,
+            std::time::Duration::from_secs(config.timeout_secs),
+        );
+        result = result.and(sent);
+    }
+    result
+}
+
+fn run_command(command: &str, ticket: &Ticket) -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.env("RULEZ_TICKET_TITLE", ticket.title())
+        .env("RULEZ_TICKET_RULE", &ticket.rule)
+        .env("RULEZ_TICKET_COUNT", ticket.count.to_string())
+        .env("RULEZ_TICKET_JSON", serde_json::to_string(ticket)?)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to spawn ticket command '{}'", command))?;
+    // The body is a few KB at most, so this fits in the pipe buffer
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(ticket.body().as_bytes())?;
+    }
+    // Don't hold up the hook on the command; reap it on its own thread
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+fn state_path() -> PathBuf {
+    crate::session::sessions_dir().join("tickets.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempts(n: usize) -> Vec<TicketAttempt> {
+        let start = Utc::now();
+        (0..n)
+            .map(|i| TicketAttempt {
+                at: start + Duration::seconds(i64::try_from(i).unwrap()),
+                session_id: format!("s{}", i % 3),
+                attempt: Some(format!("Bash: rm -rf /tmp/{i}")),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ticket_aggregates_evidence() {
+        let ticket = Ticket::new("no-rm", 3600, attempts(25));
+        assert_eq!(ticket.count, 25);
+        assert_eq!(ticket.sessions, 3);
+        assert_eq!(ticket.attempts.len(), MAX_ATTEMPTS);
+        assert_eq!(
+            ticket.attempts[0].attempt.as_deref(),
+            Some("Bash: rm -rf /tmp/5")
+        );
+
+        assert_eq!(ticket.title(), "RuleZ: rule 'no-rm' blocked 25 times in 1h");
+        let body = ticket.body();
+        assert!(body.contains("**25** events from **3** session(s)"));
+        assert!(body.contains("Last 20 attempts:"));
+        assert!(body.contains("`Bash: rm -rf /tmp/24`"));
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let ticket = Ticket::new("no-rm", 600, attempts(2));
+        let mut config = TicketConfig::default();
+        assert!(config.format == TicketFormat::Generic);
+        assert_eq!(ticket.payload(&config)["ticket"]["count"], 2);
+
+        config.format = TicketFormat::Github;
+        let github = ticket.payload(&config);
+        assert_eq!(github["labels"][0], "rulez");
+        assert!(github.get("ticket").is_none());
+
+        config.format = TicketFormat::Jira;
+        config.project = Some("SEC".to_string());
+        let jira = ticket.payload(&config);
+        assert_eq!(jira["fields"]["project"]["key"], "SEC");
+        assert_eq!(jira["fields"]["issuetype"]["name"], "Task");
+        assert_eq!(jira["fields"]["summary"], ticket.title());
+    }
+
+    #[test]
+    fn test_cooldown_per_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.json");
+        let config = TicketConfig {
+            cooldown_secs: 3600,
+            ..TicketConfig::default()
+        };
+        let now = Utc::now();
+
+        assert!(claim_in(&path, &config, "no-rm", now).unwrap());
+        assert!(!claim_in(&path, &config, "no-rm", now + Duration::minutes(30)).unwrap());
+        assert!(claim_in(&path, &config, "no-curl", now + Duration::minutes(30)).unwrap());
+        assert!(claim_in(&path, &config, "no-rm", now + Duration::hours(2)).unwrap());
+    }
+
+    #[test]
+    fn test_table_cell_escapes() {
+        assert_eq!(table_cell("a | b\nc `d`"), "a \\| b c 'd'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_receives_ticket() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("ticket.txt");
+        let config = TicketConfig {
+            command: Some(format!(
+                "{{ echo \"$RULEZ_TICKET_RULE $RULEZ_TICKET_COUNT\"; cat; }} > '{}'",
+                out.display()
+            )),
+            ..TicketConfig::default()
+        };
+        let ticket = Ticket::new("no-rm", 600, attempts(2));
+        file(&config, &ticket).unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let written = std::fs::read_to_string(&out).unwrap_or_default();
+            if written.contains("Filed by rulez") {
+                assert!(written.starts_with("no-rm 2\n"));
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "command did not run");
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    }
+}
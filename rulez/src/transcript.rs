@@ -0,0 +1,185 @@
+//! Bounded reads of the session transcript for `transcript_match`.
+//!
+//! Claude Code writes the conversation to `transcript_path` as JSONL, one
+//! entry per line:
+//!
+//! ```json
+//! {"type": "assistant", "message": {"role": "assistant", "content": [{"type": "text", "text": "..."}]}}
+//! ```
+//!
+//! Only the tail of the file is read (at most `max_bytes`), and only the last
+//! `last_messages` user/assistant messages are considered, so hooks stay fast
+//! on long sessions.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Which side of the conversation a message came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptRole {
+    /// The user's messages
+    User,
+    /// The agent's messages
+    Assistant,
+    /// Both
+    #[default]
+    Any,
+}
+
+/// A user or assistant message from the transcript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptMessage {
+    /// `User` or `Assistant`
+    pub role: TranscriptRole,
+    /// Text content, with multiple text blocks joined by newlines
+    pub text: String,
+}
+
+/// Read the last `last_messages` messages of `role` from a transcript
+///
+/// At most `max_bytes` are read from the end of the file. A missing or
+/// unreadable transcript yields no messages; malformed lines are skipped.
+/// Messages are returned oldest first.
+pub fn read_tail(
+    path: &Path,
+    role: TranscriptRole,
+    last_messages: usize,
+    max_bytes: u64,
+) -> Vec<TranscriptMessage> {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(max_bytes);
+    if file.seek(SeekFrom::Start(start)).is_err() {
+        return Vec::new();
+    }
+    let mut buf = Vec::new();
+    if file.take(max_bytes).read_to_end(&mut buf).is_err() {
+        return Vec::new();
+    }
+    let text = String::from_utf8_lossy(&buf);
+
+    let mut lines = text.lines();
+    if start > 0 {
+        // The first line was cut by the seek
+        lines.next();
+    }
+
+    let mut messages: Vec<TranscriptMessage> = lines
+        .rev()
+        .filter_map(parse_message)
+        .filter(|message| role == TranscriptRole::Any || message.role == role)
+        .take(last_messages)
+        .collect();
+    messages.reverse();
+    messages
+}
+
+/// Parse one transcript line into a message (`None` for other entry types)
+fn parse_message(line: &str) -> Option<TranscriptMessage> {
+    let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+    let message = entry.get("message").unwrap_or(&entry);
+    let role = match message
+        .get("role")
+        .or_else(|| entry.get("type"))
+        .and_then(|r| r.as_str())?
+    {
+        "user" => TranscriptRole::User,
+        "assistant" => TranscriptRole::Assistant,
+        _ => return None,
+    };
+
+    let text = match message.get("content")? {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(TranscriptMessage { role, text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_transcript(lines: &[serde_json::Value]) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let body: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        std::fs::write(file.path(), body.join("\n") + "\n").unwrap();
+        file
+    }
+
+    fn transcript() -> tempfile::NamedTempFile {
+        write_transcript(&[
+            serde_json::json!({"type": "user", "message": {"role": "user", "content": "run the migration"}}),
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "I acknowledge the migration policy."},
+                {"type": "tool_use", "name": "Bash", "input": {}}
+            ]}}),
+            serde_json::json!({"type": "summary", "summary": "ignored"}),
+            serde_json::json!({"type": "user", "message": {"role": "user", "content": [
+                {"type": "tool_result", "content": "ok"}
+            ]}}),
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": "Done."}}),
+        ])
+    }
+
+    #[test]
+    fn test_read_tail_filters_roles_and_limits_messages() {
+        let file = transcript();
+
+        let all = read_tail(file.path(), TranscriptRole::Any, 10, 1 << 20);
+        let texts: Vec<_> = all.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "run the migration",
+                "I acknowledge the migration policy.",
+                "Done."
+            ]
+        );
+
+        let assistant = read_tail(file.path(), TranscriptRole::Assistant, 1, 1 << 20);
+        assert_eq!(assistant.len(), 1);
+        assert_eq!(assistant[0].text, "Done.");
+
+        let user = read_tail(file.path(), TranscriptRole::User, 10, 1 << 20);
+        assert_eq!(user.len(), 1);
+        assert_eq!(user[0].role, TranscriptRole::User);
+    }
+
+    #[test]
+    fn test_read_tail_is_bounded_by_bytes() {
+        let file = transcript();
+        let last_line_len = std::fs::read_to_string(file.path())
+            .unwrap()
+            .lines()
+            .last()
+            .unwrap()
+            .len() as u64;
+
+        // Only the final line fits; the partial line before it is dropped
+        let tail = read_tail(file.path(), TranscriptRole::Any, 10, last_line_len + 5);
+        assert_eq!(tail.len(), 1);
+        assert_eq!(tail[0].text, "Done.");
+    }
+
+    #[test]
+    fn test_read_tail_missing_file() {
+        let tail = read_tail(
+            Path::new("/nonexistent/transcript.jsonl"),
+            TranscriptRole::Any,
+            10,
+            1024,
+        );
+        assert!(tail.is_empty());
+    }
+}
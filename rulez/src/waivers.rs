@@ -0,0 +1,282 @@
+//! Narrow, expiring exemptions from individual rules.
+//!
+//! A waiver lets one rule stand aside for a specific path or command until
+//! a fixed date, with a recorded justification and approver, instead of
+//! disabling the whole rule:
+//!
+//! ```yaml
+//! waivers:
+//!   - rule: no-force-push
+//!     commands: ["git push --force origin release-backport"]
+//!     expires: "2026-07-01"
+//!     justification: "Rewriting the backport branch after the bad merge"
+//!     approver: alice
+//! ```
+//!
+//! A waiver needs at least one scope. With both `paths` and `commands`, the
+//! event must match both. Waivers stop applying after their `expires` date;
+//! `rulez lint` flags expired waivers so they get cleaned up. Every time a
+//! waiver suppresses a rule it is recorded on the event's log entry.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::models::{Event, Rule};
+
+/// One exemption (`waivers:` entry)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Waiver {
+    /// Name of the rule being waived
+    pub rule: String,
+
+    /// Glob patterns for the target file path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+
+    /// Regex patterns for the Bash command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<String>>,
+
+    /// Last day the waiver applies (`2026-07-01`)
+    pub expires: String,
+
+    /// Why the rule is waived
+    pub justification: String,
+
+    /// Who approved the waiver
+    pub approver: String,
+
+    /// Tracking ticket
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+}
+
+/// A waiver that suppressed a matched rule, as recorded in the log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppliedWaiver {
+    pub rule: String,
+    pub approver: String,
+    pub expires: String,
+    pub justification: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+}
+
+impl Waiver {
+    /// Parsed `expires` date, if valid
+    pub fn expiry(&self) -> Option<NaiveDate> {
+        crate::governance::parse_review_date(&self.expires)
+    }
+
+    /// Whether the waiver still applies on `today` (the expiry day included)
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        self.expiry().is_some_and(|expiry| today <= expiry)
+    }
+
+    /// Whether the event falls within the waiver's path and command scope
+    pub fn covers(&self, event: &Event) -> bool {
+        if self.paths.is_none() && self.commands.is_none() {
+            return false;
+        }
+        let tool_input = event.tool_input.as_ref();
+        if let Some(ref paths) = self.paths {
+            let Some(file_path) = tool_input
+                .and_then(|ti| ti.get("file_path").or_else(|| ti.get("filePath")))
+                .and_then(|p| p.as_str())
+            else {
+                return false;
+            };
+            if !crate::hooks::build_glob_set(paths).is_match(file_path) {
+                return false;
+            }
+        }
+        if let Some(ref commands) = self.commands {
+            let Some(command) = tool_input
+                .and_then(|ti| ti.get("command"))
+                .and_then(|c| c.as_str())
+            else {
+                return false;
+            };
+            match crate::hooks::get_or_compile_regex_set(commands, false) {
+                Ok(set) if set.is_match(command) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Log record for this waiver
+    pub fn applied(&self) -> AppliedWaiver {
+        AppliedWaiver {
+            rule: self.rule.clone(),
+            approver: self.approver.clone(),
+            expires: self.expires.clone(),
+            justification: self.justification.clone(),
+            ticket: self.ticket.clone(),
+        }
+    }
+
+    /// Problem with the waiver's definition, if any (checked by `Config::validate`)
+    pub fn problem(&self) -> Option<String> {
+        if self.paths.is_none() && self.commands.is_none() {
+            return Some("needs a `paths` or `commands` scope".to_string());
+        }
+        if self.paths.as_ref().is_some_and(Vec::is_empty)
+            || self.commands.as_ref().is_some_and(Vec::is_empty)
+        {
+            return Some("has an empty scope list".to_string());
+        }
+        if self.expiry().is_none() {
+            return Some(format!(
+                "has expires '{}', expected a date like 2026-01-31",
+                self.expires
+            ));
+        }
+        if self.justification.trim().is_empty() {
+            return Some("needs a justification".to_string());
+        }
+        if self.approver.trim().is_empty() {
+            return Some("needs an approver".to_string());
+        }
+        if let Some(ref paths) = self.paths {
+            if let Some((pattern, e)) = paths
+                .iter()
+                .find_map(|p| globset::Glob::new(p).err().map(|e| (p, e)))
+            {
+                return Some(format!("has invalid path glob '{}': {}", pattern, e));
+            }
+        }
+        if let Some(ref commands) = self.commands {
+            if let Some((pattern, e)) = commands
+                .iter()
+                .find_map(|p| regex::Regex::new(p).err().map(|e| (p, e)))
+            {
+                return Some(format!("has invalid command regex '{}': {}", pattern, e));
+            }
+        }
+        None
+    }
+}
+
+/// The active waiver for `rule` that covers the event, if any
+pub fn find<'a>(
+    config: &'a Config,
+    rule: &Rule,
+    event: &Event,
+    today: NaiveDate,
+) -> Option<&'a Waiver> {
+    config
+        .waivers
+        .iter()
+        .find(|w| w.rule == rule.name && w.is_active(today) && w.covers(event))
+}
+
+/// Waivers past their expiry date on `today`
+pub fn expired(config: &Config, today: NaiveDate) -> Vec<&Waiver> {
+    config
+        .waivers
+        .iter()
+        .filter(|w| w.expiry().is_some_and(|expiry| today > expiry))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers: { tools: ["Bash"], command_match: "git push --force" }
+    actions: { block: true }
+  - name: no-vendor-edits
+    matchers: { tools: ["Write"], directories: ["**/vendor/**"] }
+    actions: { block: true }
+waivers:
+  - rule: no-force-push
+    commands: ["git push --force origin backport$"]
+    expires: "2026-07-01"
+    justification: "Fixing the backport branch"
+    approver: alice
+  - rule: no-vendor-edits
+    paths: ["/repo/vendor/patched/**"]
+    expires: "2026-01-01"
+    justification: "Carrying an upstream patch"
+    approver: bob
+"#;
+
+    fn date(s: &str) -> NaiveDate {
+        crate::governance::parse_review_date(s).unwrap()
+    }
+
+    fn event(tool: &str, input: serde_json::Value) -> Event {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": tool,
+            "tool_input": input,
+            "session_id": "waiver-test",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_waiver_scope_and_expiry() {
+        let config: Config = serde_yaml::from_str(YAML).unwrap();
+        let (push, vendor) = (&config.rules[0], &config.rules[1]);
+        let today = date("2026-03-01");
+
+        let waived = event(
+            "Bash",
+            serde_json::json!({"command": "git push --force origin backport"}),
+        );
+        let other = event(
+            "Bash",
+            serde_json::json!({"command": "git push --force origin main"}),
+        );
+        assert!(find(&config, push, &waived, today).is_some());
+        assert!(find(&config, push, &other, today).is_none());
+        assert!(find(&config, push, &waived, date("2026-07-01")).is_some());
+        assert!(find(&config, push, &waived, date("2026-07-02")).is_none());
+
+        // Expired waiver no longer applies
+        let patched = event(
+            "Write",
+            serde_json::json!({"file_path": "/repo/vendor/patched/lib.rs"}),
+        );
+        assert!(find(&config, vendor, &patched, today).is_none());
+        assert!(find(&config, vendor, &patched, date("2025-12-31")).is_some());
+
+        let names: Vec<&str> = expired(&config, today)
+            .into_iter()
+            .map(|w| w.rule.as_str())
+            .collect();
+        assert_eq!(names, vec!["no-vendor-edits"]);
+    }
+
+    #[test]
+    fn test_waiver_problems() {
+        let config: Config = serde_yaml::from_str(YAML).unwrap();
+        assert!(config.waivers.iter().all(|w| w.problem().is_none()));
+
+        let unscoped = Waiver {
+            paths: None,
+            commands: None,
+            ..config.waivers[0].clone()
+        };
+        assert!(unscoped.problem().unwrap().contains("scope"));
+
+        let undated = Waiver {
+            expires: "next quarter".to_string(),
+            ..config.waivers[0].clone()
+        };
+        assert!(undated.problem().unwrap().contains("expires"));
+
+        let unapproved = Waiver {
+            approver: " ".to_string(),
+            ..config.waivers[0].clone()
+        };
+        assert_eq!(unapproved.problem().unwrap(), "needs an approver");
+    }
+}
@@ -154,3 +154,342 @@ rules:
         .success()
         .stdout(predicate::str::contains("[INFO]  missing-priority"));
 }
+
+#[test]
+fn lint_with_logs_flags_unused_and_superseded_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let config = r#"
+version: "1.0"
+rules:
+  - name: "block-push"
+    description: "Block pushes"
+    priority: 100
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push"
+    actions:
+      block: true
+  - name: "block-force-push"
+    description: "Block force pushes"
+    priority: 50
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+  - name: "python-context"
+    description: "Never fires"
+    priority: 10
+    matchers:
+      extensions: [".py"]
+    actions:
+      inject_inline: "Use type hints"
+"#;
+    fs::write(config_dir.join("hooks.yaml"), config).unwrap();
+    let log = temp_dir.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"schema_version":2,"timestamp":"2026-03-01T00:00:00Z","event_type":"PreToolUse","session_id":"a","rules_matched":["block-push","block-force-push"],"outcome":"block","timing":{"processing_ms":1,"rules_evaluated":3}}
+{"schema_version":2,"timestamp":"2026-03-02T00:00:00Z","event_type":"PreToolUse","session_id":"a","rules_matched":["block-push"],"outcome":"block","timing":{"processing_ms":1,"rules_evaluated":3}}
+"#,
+    )
+    .unwrap();
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args([
+            "lint",
+            "--config",
+            ".claude/hooks.yaml",
+            "--with-logs",
+            "--since",
+            "2026-01-01T00:00:00Z",
+            "--log-path",
+        ])
+        .arg(&log)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checked 2 log entries"))
+        .stdout(predicate::str::contains(
+            "[WARN]  unused-rule: Rule 'python-context' never matched since 2026-01-01T00:00:00Z",
+        ))
+        .stdout(predicate::str::contains(
+            "[WARN]  superseded-rule: Rule 'block-force-push' matched 1 time",
+        ))
+        .stdout(predicate::str::contains("Rule 'block-push' never matched").not());
+}
+
+#[test]
+fn lint_since_requires_with_logs() {
+    rulez_cmd()
+        .args(["lint", "--since", "7d"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--with-logs"));
+}
+
+#[test]
+fn lint_and_governance_report_flag_overdue_reviews() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let config = r#"
+version: "1.0"
+settings:
+  governance:
+    review_max_age_days: 30
+    downgrade_overdue: true
+rules:
+  - name: "stale-rule"
+    description: "Reviewed long ago"
+    priority: 1
+    matchers:
+      tools: ["Bash"]
+    actions:
+      block: true
+    governance:
+      last_reviewed: "2020-01-01"
+  - name: "odd-date"
+    description: "Unparseable review date"
+    priority: 1
+    matchers:
+      tools: ["Write"]
+    actions:
+      block: true
+    governance:
+      last_reviewed: "sometime"
+"#;
+    fs::write(config_dir.join("hooks.yaml"), config).unwrap();
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["lint", "--config", ".claude/hooks.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[WARN]  review-overdue: Rule 'stale-rule' was last reviewed",
+        ))
+        .stdout(predicate::str::contains("running in warn mode"))
+        .stdout(predicate::str::contains(
+            "[WARN]  invalid-review-date: Rule 'odd-date'",
+        ));
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["governance", "report", "--config", ".claude/hooks.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("enforce→warn"))
+        .stdout(predicate::str::contains("Summary: 2 rules, 1 overdue"));
+
+    let output = rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args([
+            "governance",
+            "report",
+            "--json",
+            "--config",
+            ".claude/hooks.yaml",
+        ])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["rules"][0]["rule"], "stale-rule");
+    assert_eq!(report["rules"][0]["status"], "overdue");
+    assert_eq!(report["rules"][0]["runs_as"], "warn");
+    assert_eq!(report["rules"][1]["status"], "invalid-date");
+}
+
+#[test]
+fn waivers_exempt_scoped_events_and_lint_flags_expired_ones() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let config = r#"
+version: "1.0"
+rules:
+  - name: "no-force-push"
+    description: "Block force pushes"
+    priority: 1
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+waivers:
+  - rule: no-force-push
+    commands: ["origin backport$"]
+    expires: "2999-01-01"
+    justification: "Rewriting the backport branch"
+    approver: alice
+  - rule: no-force-push
+    commands: ["origin legacy$"]
+    expires: "2020-01-01"
+    justification: "Old migration"
+    approver: bob
+"#;
+    fs::write(config_dir.join("hooks.yaml"), config).unwrap();
+
+    let push = |branch: &str| {
+        format!(
+            r#"{{"hook_event_name": "PreToolUse", "tool_name": "Bash",
+                "tool_input": {{"command": "git push --force origin {}"}},
+                "session_id": "waiver-test"}}"#,
+            branch
+        )
+    };
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .write_stdin(push("backport"))
+        .assert()
+        .code(0);
+    for branch in ["main", "legacy"] {
+        rulez_cmd()
+            .current_dir(temp_dir.path())
+            .write_stdin(push(branch))
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("no-force-push"));
+    }
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["lint", "--config", ".claude/hooks.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[WARN]  expired-waiver: Waiver for rule 'no-force-push' (approved by bob) expired on 2020-01-01",
+        ));
+}
+
+#[test]
+fn lint_flags_deprecated_rules_and_migrate_removes_expired_ones() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let config = r#"version: "1.0"
+rules:
+  # Superseded by new-guard
+  - name: "old-guard"
+    description: "Old force-push guard"
+    priority: 1
+    deprecated: true
+    replacement: new-guard
+    remove_after: "2020-01-01"
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push -f"
+    actions:
+      block: true
+
+  - name: "new-guard"
+    description: "Force-push guard"
+    priority: 1
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push (-f|--force)"
+    actions:
+      block: true
+"#;
+    let path = config_dir.join("hooks.yaml");
+    fs::write(&path, config).unwrap();
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["lint", "--config", ".claude/hooks.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[WARN]  deprecated-rule: Rule 'old-guard' is deprecated; use 'new-guard' instead (removal after 2020-01-01); past its removal date",
+        ));
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["migrate", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would remove 'old-guard' (removal after 2020-01-01, replaced by 'new-guard')",
+        ));
+    assert_eq!(fs::read_to_string(&path).unwrap(), config);
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["migrate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 rule removed"));
+    let migrated = fs::read_to_string(&path).unwrap();
+    assert!(!migrated.contains("old-guard"));
+    assert!(!migrated.contains("Superseded"));
+    assert!(migrated.contains("name: \"new-guard\""));
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["migrate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No deprecated rules"));
+}
+
+#[test]
+fn governance_list_shows_inventory_filtered_by_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let config = r#"
+version: "1.0"
+rules:
+  - name: "no-secrets"
+    priority: 50
+    matchers:
+      tools: ["Write"]
+    actions:
+      block: true
+    governance:
+      author: security-team
+      confidence: high
+      last_reviewed: "2026-09-01"
+      tags: [security, compliance]
+  - name: "style-hints"
+    mode: warn
+    matchers:
+      tools: ["Edit"]
+    actions:
+      inject_inline: "Follow the style guide"
+    governance:
+      tags: [style]
+"#;
+    fs::write(config_dir.join("hooks.yaml"), config).unwrap();
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["governance", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"no-secrets\s+enforce\s+50\s+security-team\s+high\s+2026-09-01\s+security, compliance").unwrap())
+        .stdout(predicate::str::is_match(r"style-hints\s+warn\s+0\s+-\s+-\s+-\s+style").unwrap())
+        .stdout(predicate::str::contains("2 rules"));
+
+    let output = rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["governance", "list", "--tag", "Security", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rows.as_array().unwrap().len(), 1);
+    assert_eq!(rows[0]["rule"], "no-secrets");
+    assert_eq!(rows[0]["owner"], "security-team");
+    assert_eq!(
+        rows[0]["tags"],
+        serde_json::json!(["security", "compliance"])
+    );
+}
@@ -0,0 +1,201 @@
+//! Integration tests for the `rulez sync` command
+
+#![allow(deprecated)] // cargo_bin deprecation - matches other test files
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn rulez_cmd() -> Command {
+    Command::cargo_bin("rulez").unwrap()
+}
+
+const FORCE_PUSH: &str = r#"{
+  "hook_event_name": "PreToolUse",
+  "tool_name": "Bash",
+  "tool_input": { "command": "git push --force origin main" },
+  "session_id": "sync-test"
+}"#;
+
+/// ed25519 private key (hex) the test publisher signs with
+const PRIVATE_KEY: &str = "0707070707070707070707070707070707070707070707070707070707070707";
+
+/// Publish a signed bundle and write a project config that syncs it
+fn setup(temp_dir: &TempDir) {
+    let published = temp_dir.path().join("published");
+    fs::create_dir_all(&published).unwrap();
+    fs::write(
+        published.join("guardrails.yaml"),
+        r#"
+- name: org-no-force-push
+  description: "Org guardrail"
+  matchers:
+    tools: ["Bash"]
+    command_match: "git push --force"
+  actions:
+    block: true
+"#,
+    )
+    .unwrap();
+
+    let output = rulez_cmd()
+        .args(["sync", "sign", "published", "--output", "bundle.tar"])
+        .args(["--key-env", "TEST_POLICY_KEY"])
+        .env("TEST_POLICY_KEY", PRIVATE_KEY)
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Signed 1 file"))
+        .get_output()
+        .stdout
+        .clone();
+    let public_key = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("Public key: "))
+        .expect("public key printed")
+        .to_string();
+
+    let config_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("hooks.yaml"),
+        format!(
+            r#"
+version: "1.0"
+settings:
+  sync:
+    source: "{}"
+    dir: "{}"
+    public_key: "{}"
+rules: []
+"#,
+            temp_dir.path().join("bundle.tar").display(),
+            temp_dir.path().join("overlay").display(),
+            public_key
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn sync_installs_signed_policies_that_then_apply() {
+    let temp_dir = TempDir::new().unwrap();
+    setup(&temp_dir);
+
+    // Nothing synced yet: the event is allowed
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .write_stdin(FORCE_PUSH)
+        .assert()
+        .code(0);
+
+    rulez_cmd()
+        .args(["sync", "--dry-run"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Signature: verified (ed25519)"))
+        .stdout(predicate::str::contains("+ org-no-force-push"))
+        .stdout(predicate::str::contains("Dry run: nothing installed"));
+
+    rulez_cmd()
+        .args(["sync"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed to"));
+
+    rulez_cmd()
+        .current_dir(temp_dir.path())
+        .write_stdin(FORCE_PUSH)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("org-no-force-push"));
+
+    rulez_cmd()
+        .args(["sync"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Policies are up to date"));
+}
+
+#[test]
+fn sync_rejects_wrong_signing_key() {
+    let temp_dir = TempDir::new().unwrap();
+    setup(&temp_dir);
+
+    // Re-sign the bundle with a different private key
+    rulez_cmd()
+        .args(["sync", "sign", "published", "--output", "bundle.tar"])
+        .args(["--key-env", "TEST_POLICY_KEY"])
+        .env("TEST_POLICY_KEY", "08".repeat(32))
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    rulez_cmd()
+        .args(["sync"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("signature does not match"));
+    assert!(!temp_dir.path().join("overlay").exists());
+}
+
+#[test]
+fn tampered_overlay_fails_to_load() {
+    let temp_dir = TempDir::new().unwrap();
+    setup(&temp_dir);
+    rulez_cmd()
+        .args(["sync"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    fs::write(temp_dir.path().join("overlay/guardrails.yaml"), "[]\n").unwrap();
+    rulez_cmd()
+        .args(["validate"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed verification"));
+}
+
+#[test]
+fn unsigned_bundle_rejected_by_default() {
+    let temp_dir = TempDir::new().unwrap();
+    setup(&temp_dir);
+    let config_path = temp_dir.path().join(".claude/hooks.yaml");
+    let config = fs::read_to_string(&config_path).unwrap();
+    let unsigned: String = config
+        .lines()
+        .filter(|line| !line.contains("public_key"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    fs::write(&config_path, &unsigned).unwrap();
+
+    rulez_cmd()
+        .args(["sync"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("public_key is required"));
+    assert!(!temp_dir.path().join("overlay").exists());
+
+    // The explicit opt-in installs, with a warning
+    fs::write(
+        &config_path,
+        unsigned.replace("  sync:\n", "  sync:\n    allow_unsigned: true\n"),
+    )
+    .unwrap();
+    rulez_cmd()
+        .args(["sync"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Signature: NOT VERIFIED"))
+        .stderr(predicate::str::contains("unsigned"));
+}
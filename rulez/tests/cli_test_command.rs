@@ -113,6 +113,42 @@ tests:
         .stdout(predicate::str::contains("expected: allow, actual: block"));
 }
 
+#[test]
+fn test_json_reports_each_expectation() {
+    let temp_dir = TempDir::new().unwrap();
+    init_project(&temp_dir);
+
+    let test_yaml = temp_dir.path().join("test-json.yaml");
+    let content = r#"
+tests:
+  - name: "force push is blocked"
+    event_type: PreToolUse
+    tool: Bash
+    command: "git push --force origin main"
+    expected: block
+  - name: "wrong expectation"
+    event_type: PreToolUse
+    tool: Bash
+    command: "echo hello"
+    expected: block
+"#;
+    fs::write(&test_yaml, content).unwrap();
+
+    let output = rulez_cmd()
+        .current_dir(temp_dir.path())
+        .args(["test", test_yaml.to_str().unwrap(), "--json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["passed"], 1);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["results"][0]["passed"], true);
+    assert_eq!(report["results"][1]["name"], "wrong expectation");
+    assert_eq!(report["results"][1]["actual"], "allow");
+}
+
 #[test]
 fn test_missing_file_errors() {
     let temp_dir = TempDir::new().unwrap();
@@ -111,3 +111,51 @@ fn copilot_hook_runner_handles_empty_stdin() -> Result<(), Box<dyn std::error::E
 
     Ok(())
 }
+
+#[test]
+fn copilot_payload_is_auto_detected_on_main_hook_path() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let hooks_yaml = "version: \"1.0\"\n\nrules:\n  - name: block-bash\n    description: \"block bash\"\n    matchers:\n      tools: [\"Bash\"]\n      command_match: \"git push.*\"\n      operations: [\"PreToolUse\"]\n    actions:\n      block: true\n";
+    write_hooks_config(&temp_dir.path().join(".claude/hooks.yaml"), hooks_yaml)?;
+
+    let input = serde_json::json!({
+        "session_id": "sess-main",
+        "hook_event_name": "preToolUse",
+        "cwd": temp_dir.path().to_string_lossy(),
+        "tool_name": "shell",
+        "tool_input": {"command": "git push --force"}
+    });
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("rulez"))
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .write_stdin(input.to_string())
+        .output()?;
+
+    // Copilot's format: JSON decision with exit 0, not Claude Code's exit 2
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        payload.get("permissionDecision").and_then(Value::as_str),
+        Some("deny")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unknown_agent_is_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("rulez"))
+        .args(["--agent", "codex"])
+        .write_stdin("{}")
+        .output()?;
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown agent 'codex'") && stderr.contains("opencode"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
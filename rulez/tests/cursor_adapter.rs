@@ -0,0 +1,173 @@
+use rulez::adapters::cursor::{detect, parse_event, translate_response};
+use rulez::models::{
+    CursorHookResponse, EventType, HookSpecificOutput, PermissionDecision, Response,
+};
+use serde_json::json;
+
+#[test]
+fn test_parse_shell_execution_maps_to_bash() {
+    let input = json!({
+        "conversation_id": "conv-1",
+        "generation_id": "gen-1",
+        "hook_event_name": "beforeShellExecution",
+        "workspace_roots": ["/work/project"],
+        "command": "git push --force",
+        "cwd": "/work/project/sub"
+    });
+
+    let parsed = parse_event(input).expect("parse event");
+    assert_eq!(parsed.event.hook_event_name, EventType::PreToolUse);
+    assert_eq!(parsed.event.tool_name.as_deref(), Some("Bash"));
+    assert_eq!(parsed.event.session_id, "conv-1");
+    assert_eq!(parsed.event.cwd.as_deref(), Some("/work/project/sub"));
+
+    let tool_input = parsed.event.tool_input.expect("tool input");
+    assert_eq!(tool_input["command"], "git push --force");
+    assert_eq!(tool_input["cursor_hook_event_name"], "beforeShellExecution");
+}
+
+#[test]
+fn test_parse_mcp_execution_decodes_tool_input() {
+    let input = json!({
+        "conversation_id": "conv-2",
+        "hook_event_name": "beforeMCPExecution",
+        "workspace_roots": ["/work/project"],
+        "tool_name": "mcp__github__create_issue",
+        "tool_input": "{\"title\": \"bug\"}",
+        "url": "https://mcp.example.com"
+    });
+
+    let parsed = parse_event(input).expect("parse event");
+    assert_eq!(parsed.event.hook_event_name, EventType::PreToolUse);
+    assert_eq!(
+        parsed.event.tool_name.as_deref(),
+        Some("mcp__github__create_issue")
+    );
+    // No cwd in the payload: fall back to the first workspace root
+    assert_eq!(parsed.event.cwd.as_deref(), Some("/work/project"));
+
+    let tool_input = parsed.event.tool_input.expect("tool input");
+    assert_eq!(tool_input["title"], "bug");
+    assert_eq!(tool_input["url"], "https://mcp.example.com");
+    assert!(tool_input.get("tool_name").is_none());
+}
+
+#[test]
+fn test_parse_file_and_prompt_hooks() {
+    let read = parse_event(json!({
+        "conversation_id": "conv-3",
+        "hook_event_name": "beforeReadFile",
+        "file_path": "/work/project/.env"
+    }))
+    .expect("parse event");
+    assert_eq!(read.event.tool_name.as_deref(), Some("Read"));
+    assert_eq!(
+        read.event.tool_input.unwrap()["file_path"],
+        "/work/project/.env"
+    );
+
+    let edit = parse_event(json!({
+        "conversation_id": "conv-3",
+        "hook_event_name": "afterFileEdit",
+        "file_path": "src/main.rs",
+        "edits": [{"old_string": "a", "new_string": "b"}]
+    }))
+    .expect("parse event");
+    assert_eq!(edit.event.hook_event_name, EventType::PostToolUse);
+    assert_eq!(edit.event.tool_name.as_deref(), Some("Edit"));
+
+    let prompt = parse_event(json!({
+        "conversation_id": "conv-3",
+        "hook_event_name": "beforeSubmitPrompt",
+        "prompt": "deploy to prod"
+    }))
+    .expect("parse event");
+    assert_eq!(prompt.event.hook_event_name, EventType::UserPromptSubmit);
+    assert_eq!(prompt.event.prompt.as_deref(), Some("deploy to prod"));
+
+    let stop = parse_event(json!({
+        "conversation_id": "conv-3",
+        "hook_event_name": "stop",
+        "status": "completed"
+    }))
+    .expect("parse event");
+    assert_eq!(stop.event.hook_event_name, EventType::Stop);
+}
+
+#[test]
+fn test_detect_cursor_payloads() {
+    assert!(detect(&json!({
+        "conversation_id": "conv-1",
+        "hook_event_name": "beforeShellExecution"
+    })));
+    assert!(detect(&json!({"hook_event_name": "beforeSubmitPrompt"})));
+    assert!(!detect(&json!({
+        "session_id": "sess-1",
+        "hook_event_name": "PreToolUse"
+    })));
+    assert!(!detect(&json!({"hook_event_name": "PreToolUse"})));
+    assert!(!detect(&json!([])));
+}
+
+#[test]
+fn test_translate_block_to_deny() {
+    let parsed = parse_event(json!({
+        "conversation_id": "conv-1",
+        "hook_event_name": "beforeShellExecution",
+        "command": "rm -rf /"
+    }))
+    .expect("parse event");
+
+    let output = translate_response(&Response::block("destructive command"), &parsed);
+    assert_eq!(output.permission, Some(PermissionDecision::Deny));
+    assert_eq!(output.user_message.as_deref(), Some("destructive command"));
+    assert_eq!(output.agent_message.as_deref(), Some("destructive command"));
+
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(json["permission"], "deny");
+    assert_eq!(json["userMessage"], "destructive command");
+}
+
+#[test]
+fn test_translate_allow_with_context_and_ask() {
+    let parsed = parse_event(json!({
+        "conversation_id": "conv-1",
+        "hook_event_name": "beforeShellExecution",
+        "command": "npm publish"
+    }))
+    .expect("parse event");
+
+    let output = translate_response(&Response::inject("Run tests first"), &parsed);
+    assert_eq!(output.permission, Some(PermissionDecision::Allow));
+    assert_eq!(output.agent_message.as_deref(), Some("Run tests first"));
+
+    let mut ask = Response::allow();
+    let mut hook_output = HookSpecificOutput::new(EventType::PreToolUse);
+    hook_output.permission_decision = Some(PermissionDecision::Ask);
+    ask.hook_specific_output = Some(hook_output);
+    let output = translate_response(&ask, &parsed);
+    assert_eq!(output.permission, Some(PermissionDecision::Ask));
+}
+
+#[test]
+fn test_translate_prompt_and_observational_hooks() {
+    let prompt = parse_event(json!({
+        "conversation_id": "conv-1",
+        "hook_event_name": "beforeSubmitPrompt",
+        "prompt": "hello"
+    }))
+    .expect("parse event");
+    let output = translate_response(&Response::block("no secrets"), &prompt);
+    assert_eq!(output.continue_, Some(false));
+    assert_eq!(output.permission, None);
+
+    let edit = parse_event(json!({
+        "conversation_id": "conv-1",
+        "hook_event_name": "afterFileEdit",
+        "file_path": "a.rs"
+    }))
+    .expect("parse event");
+    let output = translate_response(&Response::allow(), &edit);
+    assert_eq!(output, CursorHookResponse::default());
+    assert_eq!(serde_json::to_string(&output).unwrap(), "{}");
+}
@@ -0,0 +1,87 @@
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const BLOCK_PUSH_YAML: &str = "version: \"1.0\"\n\nrules:\n  - name: block-push\n    description: \"block force push\"\n    matchers:\n      tools: [\"Bash\"]\n      command_match: \"git push.*--force\"\n      operations: [\"PreToolUse\"]\n    actions:\n      block: true\n";
+
+fn write_hooks_config(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let claude_dir = dir.join(".claude");
+    fs::create_dir_all(&claude_dir)?;
+    fs::write(claude_dir.join("hooks.yaml"), BLOCK_PUSH_YAML)?;
+    Ok(())
+}
+
+fn run(dir: &Path, args: &[&str], input: &Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("rulez"))
+        .current_dir(dir)
+        .env("HOME", dir)
+        .args(args)
+        .write_stdin(input.to_string())
+        .output()?;
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn cursor_payload_is_auto_detected_and_denied() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(temp_dir.path())?;
+
+    let input = serde_json::json!({
+        "conversation_id": "conv-1",
+        "generation_id": "gen-1",
+        "hook_event_name": "beforeShellExecution",
+        "workspace_roots": [temp_dir.path().to_string_lossy()],
+        "command": "git push origin main --force",
+        "cwd": temp_dir.path().to_string_lossy()
+    });
+
+    let payload = run(temp_dir.path(), &[], &input)?;
+    assert_eq!(payload["permission"], "deny");
+    assert!(
+        payload["userMessage"]
+            .as_str()
+            .is_some_and(|m| m.contains("block-push")),
+        "{payload}"
+    );
+    Ok(())
+}
+
+#[test]
+fn cursor_agent_flag_allows_unmatched_command() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(temp_dir.path())?;
+
+    let input = serde_json::json!({
+        "conversation_id": "conv-2",
+        "hook_event_name": "beforeShellExecution",
+        "workspace_roots": [temp_dir.path().to_string_lossy()],
+        "command": "git status"
+    });
+
+    let payload = run(temp_dir.path(), &["--agent", "cursor"], &input)?;
+    assert_eq!(payload["permission"], "allow");
+    Ok(())
+}
+
+#[test]
+fn cursor_dry_run_allows_blocked_command() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(temp_dir.path())?;
+
+    let input = serde_json::json!({
+        "conversation_id": "conv-3",
+        "hook_event_name": "beforeShellExecution",
+        "workspace_roots": [temp_dir.path().to_string_lossy()],
+        "command": "git push --force"
+    });
+
+    let payload = run(temp_dir.path(), &["--dry-run"], &input)?;
+    assert_eq!(payload["permission"], "allow");
+    Ok(())
+}
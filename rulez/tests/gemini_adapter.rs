@@ -1,4 +1,4 @@
-use rulez::adapters::gemini::{parse_event, translate_response};
+use rulez::adapters::gemini::{detect, parse_event, translate_response};
 use rulez::models::{EventType, GeminiDecision, Response};
 use serde_json::json;
 
@@ -122,6 +122,11 @@ fn test_parse_agent_event_keeps_prompt_fields() {
 
     let parsed = parse_event(input).expect("parse event");
     assert_eq!(parsed.event.hook_event_name, EventType::BeforeAgent);
+    assert_eq!(parsed.event.prompt.as_deref(), Some("Summarize this"));
+    assert_eq!(
+        parsed.additional_event_types,
+        vec![EventType::UserPromptSubmit]
+    );
 
     let tool_input = parsed.event.tool_input.expect("tool input");
     let map = tool_input.as_object().expect("tool input object");
@@ -191,3 +196,20 @@ fn test_translate_response_non_tool_block_sets_continue_false() {
     assert_eq!(output.reason.as_deref(), Some("policy denied"));
     assert_eq!(output.continue_, Some(false));
 }
+
+#[test]
+fn test_detect_gemini_tool_hooks() {
+    assert!(detect(
+        &json!({"session_id": "s", "hook_event_name": "BeforeTool"})
+    ));
+    assert!(detect(
+        &json!({"session_id": "s", "hook_event_name": "AfterTool"})
+    ));
+    // Names shared with RuleZ event types need --agent gemini
+    assert!(!detect(
+        &json!({"session_id": "s", "hook_event_name": "BeforeAgent"})
+    ));
+    assert!(!detect(
+        &json!({"session_id": "s", "hook_event_name": "PreToolUse"})
+    ));
+}
@@ -18,7 +18,7 @@ fn gemini_hook_runner_outputs_json_with_hook_event_name() -> Result<(), Box<dyn
     let claude_dir = temp_dir.path().join(".claude");
     fs::create_dir_all(&claude_dir)?;
 
-    let inject_path = temp_dir.path().join("tool_override.json");
+    let inject_path = claude_dir.join("tool_override.json");
     fs::write(&inject_path, r#"{"filePath":"/tmp/override.txt"}"#)?;
 
     let hooks_yaml = format!(
@@ -70,7 +70,7 @@ fn gemini_hook_runner_denies_with_override() -> Result<(), Box<dyn std::error::E
     let claude_dir = temp_dir.path().join(".claude");
     fs::create_dir_all(&claude_dir)?;
 
-    let inject_path = temp_dir.path().join("deny_override.json");
+    let inject_path = claude_dir.join("deny_override.json");
     fs::write(&inject_path, r#"{"reason":"policy"}"#)?;
 
     let hooks_yaml = format!(
@@ -117,3 +117,136 @@ fn gemini_hook_runner_denies_with_override() -> Result<(), Box<dyn std::error::E
 
     Ok(())
 }
+
+const PROMPT_RULES_YAML: &str = "version: \"1.0\"\n\nrules:\n  - name: no-prod-deploys\n    description: \"block prod deploys\"\n    matchers:\n      operations: [\"UserPromptSubmit\"]\n      prompt_match: [\"deploy.*prod\"]\n    actions:\n      block: true\n\n  - name: tool-failed\n    description: \"hint after failures\"\n    matchers:\n      operations: [\"PostToolUseFailure\"]\n    actions:\n      inject_inline: \"Check the error before retrying\"\n";
+
+fn run_rulez(
+    dir: &Path,
+    args: &[&str],
+    input: &Value,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("rulez"))
+        .current_dir(dir)
+        .env("HOME", dir)
+        .args(args)
+        .write_stdin(input.to_string())
+        .output()?;
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn gemini_before_agent_dual_fires_user_prompt_submit() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(
+        &temp_dir.path().join(".claude/hooks.yaml"),
+        PROMPT_RULES_YAML,
+    )?;
+
+    let input = serde_json::json!({
+        "session_id": "sess-dual",
+        "hook_event_name": "BeforeAgent",
+        "cwd": temp_dir.path().to_string_lossy(),
+        "prompt": "please deploy to prod now"
+    });
+
+    let payload = run_rulez(temp_dir.path(), &["gemini", "hook"], &input)?;
+    assert_eq!(payload["decision"], "deny");
+
+    // Same payload through the main entry point
+    let payload = run_rulez(temp_dir.path(), &["--agent", "gemini"], &input)?;
+    assert_eq!(payload["decision"], "deny");
+    Ok(())
+}
+
+#[test]
+fn gemini_after_tool_failure_is_auto_detected() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(
+        &temp_dir.path().join(".claude/hooks.yaml"),
+        PROMPT_RULES_YAML,
+    )?;
+
+    let input = serde_json::json!({
+        "session_id": "sess-fail",
+        "hook_event_name": "AfterTool",
+        "cwd": temp_dir.path().to_string_lossy(),
+        "tool_name": "run_shell_command",
+        "tool_input": {"command": "cargo test"},
+        "error": "exit status 101"
+    });
+
+    let payload = run_rulez(temp_dir.path(), &[], &input)?;
+    assert_eq!(payload["decision"], "allow");
+    assert_eq!(payload["systemMessage"], "Check the error before retrying");
+    Ok(())
+}
+
+#[test]
+fn gemini_dual_fire_is_logged_once_with_both_types_merged() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(
+        &temp_dir.path().join(".claude/hooks.yaml"),
+        "version: \"1.0\"\n\nrules:\n  - name: agent-hint\n    matchers:\n      operations: [\"BeforeAgent\"]\n    actions:\n      inject_inline: \"Agent context\"\n\n  - name: prompt-hint\n    matchers:\n      operations: [\"UserPromptSubmit\"]\n      prompt_match: [\"refactor\"]\n    actions:\n      inject_inline: \"Prompt context\"\n",
+    )?;
+
+    let input = serde_json::json!({
+        "session_id": "sess-dual-log",
+        "hook_event_name": "BeforeAgent",
+        "cwd": temp_dir.path().to_string_lossy(),
+        "prompt": "refactor the parser"
+    });
+    let payload = run_rulez(temp_dir.path(), &["--agent", "gemini"], &input)?;
+    assert_eq!(payload["decision"], "allow");
+    assert_eq!(payload["systemMessage"], "Agent context\n\nPrompt context");
+
+    let log = fs::read_to_string(temp_dir.path().join(".claude/logs/rulez.log"))?;
+    let entries: Vec<Value> = log
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(entries.len(), 1, "{log}");
+    assert_eq!(entries[0]["event_type"], "BeforeAgent");
+    assert_eq!(
+        entries[0]["rules_matched"],
+        serde_json::json!(["agent-hint", "prompt-hint"])
+    );
+    Ok(())
+}
+
+#[test]
+fn gemini_dual_fire_applies_default_policy_only_when_nothing_matched()
+-> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    write_hooks_config(
+        &temp_dir.path().join(".claude/hooks.yaml"),
+        "version: \"1.0\"\n\nsettings:\n  default_policy:\n    UserPromptSubmit: deny\n\nrules:\n  - name: agent-hint\n    matchers:\n      operations: [\"BeforeAgent\"]\n    actions:\n      inject_inline: \"Agent context\"\n",
+    )?;
+    let input = |prompt: &str| {
+        serde_json::json!({
+            "session_id": "sess-dual-default",
+            "hook_event_name": "BeforeAgent",
+            "cwd": temp_dir.path().to_string_lossy(),
+            "prompt": prompt
+        })
+    };
+
+    // The BeforeAgent rule matched, so UserPromptSubmit's default is not used
+    let payload = run_rulez(temp_dir.path(), &["--agent", "gemini"], &input("hello"))?;
+    assert_eq!(payload["decision"], "allow");
+    assert_eq!(payload["systemMessage"], "Agent context");
+
+    // With no rule matching either type, the default decides
+    write_hooks_config(
+        &temp_dir.path().join(".claude/hooks.yaml"),
+        "version: \"1.0\"\n\nsettings:\n  default_policy:\n    UserPromptSubmit: deny\n\nrules: []\n",
+    )?;
+    let payload = run_rulez(temp_dir.path(), &["--agent", "gemini"], &input("hello"))?;
+    assert_eq!(payload["decision"], "deny");
+    Ok(())
+}
@@ -709,6 +709,61 @@ rules:
     let _ = evidence.save(&evidence_dir());
 }
 
+#[test]
+#[cfg(unix)]
+fn test_e2e_inline_script_timeout_ms() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("e2e_inline_script_timeout_ms", "OQ-SCRIPT");
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("Failed to create .claude dir");
+
+    // A sub-second budget from settings.script_timeouts
+    let config = r#"
+version: "1.0"
+rules:
+  - name: slow-check
+    matchers:
+      tools: [Bash]
+    actions:
+      inline_script: |
+        #!/bin/bash
+        sleep 5
+        exit 0
+settings:
+  script_timeout: 30
+  script_timeouts:
+    inline_script_ms: 200
+"#;
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("Failed to write config");
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "echo test"},
+        "session_id": "test-session"
+    }"#;
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .write_stdin(event)
+        .output()
+        .expect("command should run");
+
+    assert_eq!(output.status.code(), Some(2), "timeout fails closed");
+    let duration = timer.elapsed_ms();
+    assert!(
+        duration < 3000,
+        "Should time out after 200ms, got {}ms",
+        duration
+    );
+
+    evidence.pass("inline_script_ms gives a sub-second timeout", duration);
+    let _ = evidence.save(&evidence_dir());
+}
+
 // =============================================================================
 // SCRIPT-06: Config Validation
 // =============================================================================
@@ -981,3 +1036,282 @@ rules:
     );
     let _ = evidence.save(&evidence_dir());
 }
+
+// =============================================================================
+// Configurable Interpreter
+// =============================================================================
+
+#[test]
+#[cfg(unix)] // Uses the POSIX `sh` interpreter
+fn test_e2e_inline_script_explicit_shell_without_shebang() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("e2e_inline_script_explicit_shell", "OQ-SCRIPT");
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("Failed to create .claude dir");
+
+    // No shebang: the configured interpreter runs the script
+    let config = r#"
+version: "1.0"
+rules:
+  - name: script-explicit-shell
+    matchers:
+      tools: [Bash]
+    actions:
+      inline_script:
+        shell: sh
+        code: |
+          if grep -q 'rm -rf'; then
+            exit 1
+          fi
+          exit 0
+"#;
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("Failed to write config");
+
+    let blocked_event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "rm -rf /"},
+        "session_id": "test-session"
+    }"#;
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .write_stdin(blocked_event)
+        .output()
+        .expect("command should run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "Should block when sh script exits 1. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let allowed_event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "ls"},
+        "session_id": "test-session"
+    }"#;
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .write_stdin(allowed_event)
+        .output()
+        .expect("command should run");
+
+    assert!(
+        output.status.success(),
+        "Should allow when sh script exits 0. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    evidence.pass(
+        "inline_script with explicit shell runs without shebang",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+#[test]
+#[cfg(windows)] // cmd.exe is only available on Windows
+fn test_e2e_inline_script_cmd_exit_nonzero_blocks() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("Failed to create .claude dir");
+
+    let config = r#"
+version: "1.0"
+rules:
+  - name: script-cmd
+    matchers:
+      tools: [Bash]
+    actions:
+      inline_script:
+        shell: cmd
+        code: |
+          @echo off
+          exit /b 1
+"#;
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("Failed to write config");
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "dir"},
+        "session_id": "test-session"
+    }"#;
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .write_stdin(event)
+        .output()
+        .expect("command should run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "cmd script exit 1 should block"
+    );
+}
+
+// =============================================================================
+// Controlled Environment and Arguments
+// =============================================================================
+
+#[test]
+#[cfg(unix)] // Uses the POSIX `sh` interpreter
+fn test_e2e_inline_script_env_clear_and_templated_args() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("e2e_inline_script_env_args", "OQ-SCRIPT");
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("Failed to create .claude dir");
+
+    // Passes only if the secret is gone, the explicit variable is set,
+    // and the templated argument carries the file path
+    let config = r#"
+version: "1.0"
+rules:
+  - name: script-env-args
+    matchers:
+      tools: [Write]
+    actions:
+      inline_script:
+        shell: sh
+        env_clear: true
+        env:
+          RULEZ_TOOL: "{{tool_name}}"
+        args: ["{{tool_input.file_path}}"]
+        code: |
+          [ -z "$RULEZ_TEST_SECRET" ] || exit 1
+          [ "$RULEZ_TOOL" = "Write" ] || exit 1
+          [ "$1" = "/test/file.txt" ] || exit 1
+          exit 0
+      inject_inline: "Environment verified"
+"#;
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("Failed to write config");
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Write",
+        "tool_input": {"file_path": "/test/file.txt", "content": "hello"},
+        "session_id": "test-session"
+    }"#;
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .env("RULEZ_TEST_SECRET", "s3cret")
+        .write_stdin(event)
+        .output()
+        .expect("command should run");
+
+    assert!(
+        output.status.success(),
+        "Script should see a cleared environment and templated args. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Environment verified"),
+        "Should inject context after script passes: {}",
+        stdout
+    );
+
+    evidence.pass(
+        "inline_script env_clear drops parent secrets; env and args are templated",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+// =============================================================================
+// No Temp Files
+// =============================================================================
+
+#[test]
+#[cfg(unix)] // Uses #!/bin/bash shebangs
+fn test_e2e_inline_script_writes_no_temp_files() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("e2e_inline_script_no_temp_files", "OQ-SCRIPT");
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("Failed to create .claude dir");
+    let script_tmp = tempfile::tempdir().expect("Failed to create script temp dir");
+
+    // Shebang script, shebang-less script, and one with arguments
+    let config = r#"
+version: "1.0"
+rules:
+  - name: shebang-script
+    matchers:
+      tools: [Bash]
+    actions:
+      inline_script: |
+        #!/bin/bash
+        grep -q '"command"' || exit 1
+        exit 0
+  - name: plain-script
+    matchers:
+      tools: [Bash]
+    actions:
+      inline_script: |
+        [ "$0" = "rulez-inline" ] || exit 1
+  - name: script-with-args
+    matchers:
+      tools: [Bash]
+    actions:
+      inline_script:
+        shell: bash
+        args: ["{{tool_input.command}}"]
+        code: |
+          [ "$1" = "ls -la" ] || exit 1
+"#;
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("Failed to write config");
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "ls -la"},
+        "session_id": "test-session"
+    }"#;
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .env("TMPDIR", script_tmp.path())
+        .write_stdin(event)
+        .output()
+        .expect("command should run");
+
+    assert!(
+        output.status.success(),
+        "All inline scripts should pass. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let leftovers: Vec<_> = fs::read_dir(script_tmp.path())
+        .expect("read temp dir")
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name())
+        .filter(|name| name.to_string_lossy().starts_with("rulez-inline"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "Inline scripts should not write temp files: {:?}",
+        leftovers
+    );
+
+    evidence.pass(
+        "inline scripts run without writing temp files",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
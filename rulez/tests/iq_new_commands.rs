@@ -198,6 +198,54 @@ fn test_debug_verbose_shows_rules() {
         .stdout(predicate::str::contains("block-force-push"));
 }
 
+#[test]
+fn test_debug_json_includes_matcher_trace() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+
+    let output = cch_cmd()
+        .current_dir(temp_dir.path())
+        .args([
+            "debug",
+            "PreToolUse",
+            "--tool",
+            "Bash",
+            "--command",
+            "git push --force origin main",
+            "--json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let evaluation = result["evaluations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["ruleName"] == "block-force-push")
+        .unwrap();
+    assert_eq!(evaluation["matched"], true);
+    assert_eq!(evaluation["action"], "block");
+    assert!(evaluation["elapsedUs"].is_u64());
+
+    let matchers = evaluation["matchers"].as_array().unwrap();
+    let command = matchers
+        .iter()
+        .find(|m| m["matcher"] == "command_match")
+        .unwrap();
+    assert_eq!(command["matched"], true);
+    assert_eq!(command["pattern"], "git push.*(--force|-f).*(main|master)");
+    assert_eq!(command["input"], "git push --force origin main");
+    let tools = matchers.iter().find(|m| m["matcher"] == "tools").unwrap();
+    assert_eq!(tools["input"], "Bash");
+}
+
 #[test]
 fn test_debug_invalid_event_type() {
     let temp_dir = TempDir::new().unwrap();
@@ -383,6 +431,14 @@ fn test_install_creates_settings_json() {
         content.contains("SessionStart"),
         "Should have SessionStart hook"
     );
+    assert!(
+        content.contains("SessionEnd"),
+        "Should have SessionEnd hook"
+    );
+    assert!(
+        content.contains("PreCompact") && content.contains("Notification"),
+        "Should have PreCompact and Notification hooks"
+    );
     assert!(
         content.contains("\"matcher\""),
         "Should have matcher field in nested structure"
@@ -442,3 +498,169 @@ fn test_repl_help() {
         .success()
         .stdout(predicate::str::contains("interactive debug mode"));
 }
+
+// =============================================================================
+// Packs Command Tests
+// =============================================================================
+
+#[test]
+fn test_packs_install_list_remove() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["packs", "install", "shell-safety"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed pack 'shell-safety'"));
+
+    let hooks_yaml = temp_dir.path().join(".claude").join("hooks.yaml");
+    let content = fs::read_to_string(&hooks_yaml).unwrap();
+    assert!(content.contains("builtin_packs: [shell-safety]"));
+
+    let output = cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["packs", "list", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let packs: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let shell = packs
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == "shell-safety")
+        .unwrap();
+    assert_eq!(shell["installed"], true);
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["packs", "install", "no-such-pack"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown builtin pack"));
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["packs", "remove", "shell-safety"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed pack 'shell-safety'"));
+    assert!(
+        !fs::read_to_string(&hooks_yaml)
+            .unwrap()
+            .contains("builtin_packs")
+    );
+}
+
+#[test]
+fn test_rule_edits_keep_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).unwrap();
+    let hooks_yaml = claude_dir.join("hooks.yaml");
+    fs::write(
+        &hooks_yaml,
+        r#"version: "1.0"
+
+rules:
+  # Never push to main
+  - name: block-push
+    priority: 10  # reviewed by security
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push.*main"
+    actions:
+      block: true
+"#,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["rule", "priority", "block-push", "-5"])
+        .assert()
+        .success();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["rule", "disable", "block-push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Disabled rule 'block-push'"));
+
+    let content = fs::read_to_string(&hooks_yaml).unwrap();
+    assert!(content.contains("  # Never push to main\n"));
+    assert!(content.contains("    priority: -5  # reviewed by security\n"));
+    assert!(content.contains("    metadata:\n      enabled: false\n"));
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["rule", "apply", "--print"])
+        .write_stdin(
+            r#"[{"op": "insert_rule", "rule": {"name": "warn-env",
+                 "matchers": {"tools": ["Read"], "extensions": [".env"]},
+                 "actions": {"block": false}}},
+                {"op": "set_enabled", "name": "block-push", "enabled": true}]"#,
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  - name: warn-env\n"))
+        .stdout(predicate::str::contains("# reviewed by security"));
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["rule", "remove", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Rule 'missing' not found"));
+    let content = fs::read_to_string(&hooks_yaml).unwrap();
+    assert!(content.contains("      enabled: true\n"));
+}
+
+#[test]
+fn test_completion_data_lists_rules_and_keys() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+
+    let output = cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["completion-data", "--log", "missing.log"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let data: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert!(
+        data["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|t| t["name"] == "Bash")
+    );
+    assert!(!data["rules"].as_array().unwrap().is_empty());
+    assert!(
+        data["matcher_keys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|k| k["key"] == "command_match" && k["doc"].is_string())
+    );
+    assert!(
+        data["event_types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e == "PreToolUse")
+    );
+}
@@ -83,3 +83,82 @@ fn opencode_hook_file_edited_event() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+const PERMISSION_RULES_YAML: &str = "version: \"1.0\"\n\nrules:\n  - name: no-recursive-deletes\n    description: \"never approve rm -rf\"\n    matchers:\n      tools: [\"Bash\"]\n      command_match: \"rm -rf\"\n      operations: [\"PermissionRequest\"]\n    actions:\n      block: true\n\n  - name: tool-failed\n    description: \"hint after failures\"\n    matchers:\n      operations: [\"PostToolUseFailure\"]\n    actions:\n      inject_inline: \"Check the error before retrying\"\n";
+
+fn run_rulez(
+    dir: &std::path::Path,
+    args: &[&str],
+    input: &Value,
+) -> Result<(Option<i32>, Value), Box<dyn std::error::Error>> {
+    let claude_dir = dir.join(".claude");
+    fs::create_dir_all(&claude_dir)?;
+    fs::write(claude_dir.join("hooks.yaml"), PERMISSION_RULES_YAML)?;
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("rulez"))
+        .current_dir(dir)
+        .env("HOME", dir)
+        .args(args)
+        .write_stdin(input.to_string())
+        .output()?;
+    Ok((
+        output.status.code(),
+        serde_json::from_slice(&output.stdout)?,
+    ))
+}
+
+#[test]
+fn opencode_permission_ask_denies_matching_rule() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = tempfile::tempdir()?;
+    let input = serde_json::json!({
+        "session_id": "sess-perm",
+        "hook_event_name": "permission.ask",
+        "tool_name": "bash",
+        "tool_input": {"command": "rm -rf /tmp/x"},
+        "cwd": tmp.path().to_string_lossy()
+    });
+
+    let (code, response) = run_rulez(tmp.path(), &["opencode", "hook"], &input)?;
+    assert_eq!(code, Some(2));
+    assert_eq!(response["status"], "deny");
+
+    // Auto-detected on the main hook path
+    let (code, response) = run_rulez(tmp.path(), &[], &input)?;
+    assert_eq!(code, Some(2));
+    assert_eq!(response["status"], "deny");
+    Ok(())
+}
+
+#[test]
+fn opencode_permission_ask_without_decision_asks() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = tempfile::tempdir()?;
+    let input = serde_json::json!({
+        "session_id": "sess-perm",
+        "hook_event_name": "permission.ask",
+        "tool_name": "bash",
+        "tool_input": {"command": "ls"},
+        "cwd": tmp.path().to_string_lossy()
+    });
+
+    let (code, response) = run_rulez(tmp.path(), &["--agent", "opencode"], &input)?;
+    assert_eq!(code, Some(0));
+    assert_eq!(response["status"], "ask");
+    Ok(())
+}
+
+#[test]
+fn opencode_failed_tool_dual_fires() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = tempfile::tempdir()?;
+    let input = serde_json::json!({
+        "session_id": "sess-fail",
+        "hook_event_name": "tool.execute.after",
+        "tool_name": "bash",
+        "tool_input": {"command": "cargo test", "success": false},
+        "cwd": tmp.path().to_string_lossy()
+    });
+
+    let (code, response) = run_rulez(tmp.path(), &["opencode", "hook"], &input)?;
+    assert_eq!(code, Some(0));
+    assert_eq!(response["context"], "Check the error before retrying");
+    Ok(())
+}
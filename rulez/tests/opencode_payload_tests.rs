@@ -1,5 +1,5 @@
-use rulez::adapters::opencode::parse_event;
-use rulez::models::EventType;
+use rulez::adapters::opencode::{detect, parse_event, translate_response};
+use rulez::models::{EventType, HookSpecificOutput, PermissionDecision, Response};
 use serde_json::json;
 
 #[test]
@@ -92,3 +92,69 @@ fn test_parse_file_edited() {
     let tool_input = result.event.tool_input.unwrap();
     assert_eq!(tool_input["extra_field"], "some-value");
 }
+
+#[test]
+fn test_parse_permission_ask_maps_to_permission_request() {
+    let input = json!({
+        "session_id": "sess-perm",
+        "hook_event_name": "permission.ask",
+        "tool_name": "bash",
+        "tool_input": {"command": "rm -rf build", "title": "Run rm -rf build"}
+    });
+
+    let parsed = parse_event(input).expect("parse event");
+    assert_eq!(parsed.event.hook_event_name, EventType::PermissionRequest);
+    assert_eq!(parsed.event.tool_name.as_deref(), Some("Bash"));
+    assert!(parsed.additional_event_types.is_empty());
+}
+
+#[test]
+fn test_translate_permission_ask_status() {
+    let parsed = parse_event(json!({
+        "session_id": "sess-perm",
+        "hook_event_name": "permission.ask",
+        "tool_name": "bash"
+    }))
+    .expect("parse event");
+
+    let denied = translate_response(&Response::block("no deletes"), &parsed);
+    assert_eq!(denied["status"], "deny");
+    assert_eq!(denied["continue"], false);
+
+    // No RuleZ decision: OpenCode asks the user as usual
+    let undecided = translate_response(&Response::allow(), &parsed);
+    assert_eq!(undecided["status"], "ask");
+
+    let mut approved = Response::allow();
+    let mut output = HookSpecificOutput::new(EventType::PermissionRequest);
+    output.permission_decision = Some(PermissionDecision::Allow);
+    approved.hook_specific_output = Some(output);
+    assert_eq!(translate_response(&approved, &parsed)["status"], "allow");
+
+    // Other events carry no status
+    let before = parse_event(json!({
+        "session_id": "sess-perm",
+        "hook_event_name": "tool.execute.before",
+        "tool_name": "bash"
+    }))
+    .expect("parse event");
+    assert!(
+        translate_response(&Response::allow(), &before)
+            .get("status")
+            .is_none()
+    );
+}
+
+#[test]
+fn test_detect_opencode_payloads() {
+    assert!(detect(
+        &json!({"session_id": "s", "hook_event_name": "tool.execute.before"})
+    ));
+    assert!(detect(
+        &json!({"session_id": "s", "hook_event_name": "permission.ask"})
+    ));
+    assert!(!detect(
+        &json!({"session_id": "s", "hook_event_name": "PreToolUse"})
+    ));
+    assert!(!detect(&json!({"session_id": "s"})));
+}
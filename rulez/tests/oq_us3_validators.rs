@@ -236,3 +236,430 @@ print("Done")
     );
     let _ = evidence.save(&evidence_dir());
 }
+
+/// Test that a pinned validator runs only while its hash matches
+#[test]
+#[cfg(unix)]
+fn test_us3_validator_sha256_pinning() {
+    use sha2::{Digest, Sha256};
+    use std::os::unix::fs::PermissionsExt;
+
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("validator_sha256_pinning", "OQ-US3");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    let validators_dir = claude_dir.join("validators");
+    fs::create_dir_all(&validators_dir).expect("create validators");
+
+    let script = "#!/bin/sh\nexit 0\n";
+    let script_path = validators_dir.join("allow.sh");
+    fs::write(&script_path, script).expect("write script");
+    fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let hash: String = Sha256::digest(script.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let config = format!(
+        r#"
+version: "1.0"
+rules:
+  - name: pinned-validator
+    matchers:
+      tools: ["Bash"]
+    actions:
+      run:
+        script: .claude/validators/allow.sh
+        sha256: {hash}
+settings:
+  fail_open: true
+"#
+    );
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("write config");
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "ls"},
+        "session_id": "test-session-sha256"
+    }"#;
+
+    // Matching hash: the validator runs and allows
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .write_stdin(event)
+        .assert()
+        .success();
+
+    // Tampered script: blocked even though fail_open is set
+    fs::write(&script_path, "#!/bin/sh\nexit 0 # edited\n").expect("edit script");
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .write_stdin(event)
+        .output()
+        .expect("command should run");
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "tampered validator must block"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hash verification"));
+
+    evidence.pass(
+        "Pinned validator runs on hash match and blocks on mismatch",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that a pinned bare script name is hashed where it runs from (PATH)
+#[test]
+#[cfg(unix)]
+fn test_us3_pinned_bare_script_verifies_path_copy() {
+    use sha2::{Digest, Sha256};
+    use std::os::unix::fs::PermissionsExt;
+
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("pinned_bare_script_verifies_path_copy", "OQ-US3");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::create_dir_all(&bin_dir).expect("create bin");
+
+    let script = "#!/bin/sh\nexit 0\n";
+    let hash: String = Sha256::digest(script.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    // Unmodified copy in the working directory, edited copy on PATH
+    let cwd_copy = temp_dir.path().join("rulez-pinned-check");
+    let path_copy = bin_dir.join("rulez-pinned-check");
+    fs::write(&cwd_copy, script).expect("write cwd copy");
+    fs::write(&path_copy, "#!/bin/sh\nexit 0 # edited\n").expect("write PATH copy");
+    for copy in [&cwd_copy, &path_copy] {
+        fs::set_permissions(copy, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let config = format!(
+        r#"
+version: "1.0"
+rules:
+  - name: pinned-validator
+    matchers:
+      tools: ["Bash"]
+    actions:
+      run:
+        script: rulez-pinned-check
+        sha256: {hash}
+settings:
+  fail_open: true
+"#
+    );
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("write config");
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "ls"},
+        "session_id": "test-session-sha256-path"
+    }"#;
+    let path = std::env::join_paths(
+        std::iter::once(bin_dir.clone()).chain(std::env::split_paths(
+            &std::env::var_os("PATH").unwrap_or_default(),
+        )),
+    )
+    .unwrap();
+    let run = |path: &std::ffi::OsStr| {
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("PATH", path)
+            .write_stdin(event)
+            .output()
+            .expect("command should run")
+    };
+
+    let output = run(&path);
+    assert_eq!(output.status.code(), Some(2), "edited PATH copy must block");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hash verification"));
+
+    // The PATH copy is what gets verified and run
+    fs::write(&path_copy, script).expect("restore PATH copy");
+    assert!(run(&path).status.success());
+
+    // A pinned script that can't be resolved is rejected
+    fs::remove_file(&path_copy).expect("remove PATH copy");
+    let output = run(&path);
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "missing pinned script must block"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("script not found"));
+
+    evidence.pass(
+        "Pinned bare script name is verified and run from its PATH location",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that settings.minimum_trust and restrict_untrusted are enforced
+#[test]
+#[cfg(unix)]
+fn test_us3_validator_trust_enforcement() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("validator_trust_enforcement", "OQ-US3");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    let validators_dir = claude_dir.join("validators");
+    fs::create_dir_all(&validators_dir).expect("create validators");
+
+    // Blocks when the secret leaks into its environment
+    let script_path = validators_dir.join("check-env.sh");
+    fs::write(
+        &script_path,
+        "#!/bin/sh\n[ -z \"$RULEZ_TEST_SECRET\" ] || { echo leaked >&2; exit 1; }\n",
+    )
+    .expect("write script");
+    fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let event = r#"{
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "ls"},
+        "session_id": "test-session-trust"
+    }"#;
+    let run = |mode: &str, settings: &str| {
+        let config = format!(
+            r#"
+version: "1.0"
+rules:
+  - name: untrusted-validator
+    mode: {mode}
+    matchers:
+      tools: ["Bash"]
+    actions:
+      run:
+        script: .claude/validators/check-env.sh
+        trust: untrusted
+settings:
+{settings}
+"#
+        );
+        fs::write(claude_dir.join("hooks.yaml"), config).expect("write config");
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("RULEZ_TEST_SECRET", "hunter2")
+            .write_stdin(event)
+            .output()
+            .expect("command should run")
+    };
+
+    // Below minimum_trust: refused even with fail_open
+    let output = run("enforce", "  fail_open: true\n  minimum_trust: local");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("trust level 'untrusted'"));
+
+    // Warn mode: the refusal becomes a warning
+    let output = run("warn", "  minimum_trust: local");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("WARNING"));
+
+    // Unrestricted untrusted script sees the secret and blocks
+    let output = run("enforce", "  restrict_untrusted: false");
+    assert_eq!(output.status.code(), Some(2));
+
+    // Restricted: the environment is cleared, so the script allows
+    let output = run("enforce", "  restrict_untrusted: true");
+    assert!(output.status.success());
+
+    evidence.pass(
+        "Untrusted validators are refused below minimum_trust and restricted on request",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that a Stop rule keeps the agent working until its check passes
+#[test]
+#[cfg(unix)]
+fn test_us3_stop_gate_until_check_passes() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("stop_gate_until_check_passes", "OQ-US3");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: tests-must-pass
+    description: "Run the test suite and fix failures before finishing"
+    matchers:
+      operations: ["Stop", "SubagentStop"]
+    actions:
+      inline_script: "test -f tests-passed"
+settings:
+  max_stop_blocks: 2
+"#,
+    )
+    .expect("write config");
+
+    let stop = |event_name: &str, active: bool| {
+        let event = serde_json::json!({
+            "hook_event_name": event_name,
+            "session_id": "stop-gate-session",
+            "stop_hook_active": active
+        });
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run")
+    };
+
+    let output = stop("Stop", false);
+    assert_eq!(output.status.code(), Some(2), "Stop MUST be blocked");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("tests-must-pass"));
+    assert_eq!(stop("SubagentStop", true).status.code(), Some(2));
+
+    // Limit reached: the agent is let go with a message
+    let output = stop("Stop", true);
+    assert!(output.status.success(), "loop guard MUST allow the stop");
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    assert!(
+        response["systemMessage"]
+            .as_str()
+            .unwrap()
+            .contains("after 2 blocked attempts")
+    );
+
+    // Once the condition holds, the agent may stop
+    assert_eq!(stop("Stop", false).status.code(), Some(2));
+    fs::write(project.path().join("tests-passed"), "").expect("write marker");
+    assert!(stop("Stop", true).status.success());
+
+    evidence.pass(
+        "Stop gated by inline script with loop guard",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that a rule's fail_open overrides settings.fail_open for validator errors
+#[test]
+fn test_us3_rule_fail_open_override() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("rule_fail_open_override", "OQ-US3");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+settings:
+  fail_open: false
+rules:
+  - name: advisory-lint
+    fail_open: true
+    matchers:
+      tools: ["Bash"]
+      command_match: "lint"
+    actions:
+      run: ".claude/validators/missing-lint.sh"
+  - name: deploy-guard
+    matchers:
+      tools: ["Bash"]
+      command_match: "deploy"
+    actions:
+      run: ".claude/validators/missing-guard.sh"
+"#,
+    )
+    .expect("write config");
+
+    let run = |command: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "tool_input": {"command": command},
+            "session_id": "test-session-rule-fail-open"
+        });
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run")
+    };
+
+    assert!(
+        run("npm run lint").status.success(),
+        "advisory validator fails open"
+    );
+    let output = run("./deploy.sh");
+    assert!(!output.status.success(), "guard validator fails closed");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("deploy-guard"));
+
+    evidence.pass(
+        "rule-level fail_open overrides settings.fail_open",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// `strict_paths` turns a missing validator into a load error naming the rule
+#[test]
+fn test_us3_strict_paths_rejects_missing_script() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("strict_paths_rejects_missing_script", "OQ-US3");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: secrets-check
+    matchers:
+      tools: ["Write"]
+    actions:
+      run: .claude/validators/check-secrets.sh
+settings:
+  strict_paths: true
+"#,
+    )
+    .expect("write config");
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .arg("validate")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "check-secrets.sh' in rule 'secrets-check' does not exist",
+        ));
+
+    evidence.pass(
+        "strict_paths reports a missing run script at load",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
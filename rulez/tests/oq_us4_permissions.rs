@@ -181,3 +181,150 @@ fn test_us4_file_operation_explanation() {
     );
     let _ = evidence.save(&evidence_dir());
 }
+
+/// Test that permission_decision and system_message reach hookSpecificOutput
+#[test]
+fn test_us4_permission_decision_output() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("permission_decision_output", "OQ-US4");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: confirm-migrations
+    description: "Database migrations need a human check"
+    matchers:
+      tools: ["Bash"]
+      command_match: "migrate"
+    actions:
+      permission_decision: ask
+      system_message: "RuleZ: migration requires confirmation"
+  - name: deny-drop
+    description: "Dropping the database is not allowed"
+    matchers:
+      tools: ["Bash"]
+      command_match: "dropdb"
+    actions:
+      permission_decision: deny
+"#,
+    )
+    .expect("write config");
+
+    let run = |command: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "tool_input": {"command": command},
+            "session_id": "test-session-permission-decision"
+        });
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run")
+    };
+
+    let output = run("rake db:migrate");
+    assert!(output.status.success());
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    let specific = &response["hookSpecificOutput"];
+    assert_eq!(specific["hookEventName"], "PreToolUse");
+    assert_eq!(specific["permissionDecision"], "ask");
+    assert!(
+        specific["permissionDecisionReason"]
+            .as_str()
+            .unwrap()
+            .contains("confirm-migrations")
+    );
+    assert_eq!(
+        response["systemMessage"],
+        "RuleZ: migration requires confirmation"
+    );
+
+    let output = run("dropdb production");
+    assert_eq!(output.status.code(), Some(2), "deny MUST block");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("deny-drop"), "got: {stderr}");
+
+    evidence.pass(
+        "permission_decision emitted as hookSpecificOutput; deny blocks",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that suppress_output hides informational output but never a block
+#[test]
+fn test_us4_suppress_output() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("suppress_output", "OQ-US4");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: cargo-hints
+    matchers:
+      tools: ["Bash"]
+      command_match: "cargo"
+    actions:
+      inject_inline: "Prefer cargo nextest"
+      suppress_output: true
+  - name: no-publish
+    matchers:
+      tools: ["Bash"]
+      command_match: "cargo publish"
+    actions:
+      block: true
+"#,
+    )
+    .expect("write config");
+
+    let run = |command: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "tool_input": {"command": command},
+            "session_id": "test-session-suppress-output"
+        });
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run")
+    };
+
+    let output = run("cargo test");
+    assert!(output.status.success());
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    assert_eq!(response["suppressOutput"], true);
+    assert_eq!(response["context"], "Prefer cargo nextest");
+
+    let output = run("cargo publish");
+    assert_eq!(output.status.code(), Some(2), "blocks stay visible");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no-publish"));
+
+    let output = run("ls");
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    assert!(response.get("suppressOutput").is_none());
+
+    evidence.pass(
+        "suppress_output emitted as suppressOutput; blocks unaffected",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
@@ -206,3 +206,1424 @@ fn test_us5_logs_show_timing() {
     );
     let _ = evidence.save(&evidence_dir());
 }
+
+/// Test that exceeding the latency budget logs the slowest rule
+#[test]
+fn test_us5_latency_budget_logged() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("latency_budget_logged", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: quick-check
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inject_inline: "quick"
+  - name: slow-validator
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inline_script: |
+        #!/bin/bash
+        sleep 0.2
+        exit 0
+settings:
+  latency_budget_ms: 1
+"#,
+    )
+    .expect("write config");
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": {"command": "ls"},
+                "session_id": "budget-session"
+            })
+            .to_string(),
+        )
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entry: serde_json::Value = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .find(|entry: &serde_json::Value| entry["session_id"] == "budget-session")
+        .expect("entry logged");
+    let over_budget = &entry["timing"]["over_budget"];
+    assert_eq!(over_budget["budget_ms"], 1);
+    assert_eq!(over_budget["slowest_rule"], "slow-validator");
+    assert!(over_budget["slowest_rule_us"].as_u64().unwrap() >= 200_000);
+
+    evidence.pass(
+        &format!("Latency overrun logged: {over_budget}"),
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that role profiles change the rules per user and are logged
+#[test]
+fn test_us5_profile_logged() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("profile_logged", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: no-rm-rf
+    matchers:
+      tools: ["Bash"]
+      command_match: "rm -rf"
+    actions:
+      block: true
+settings:
+  profiles:
+    users:
+      "*@contractor.example.com": contractor
+    default: staff
+    definitions:
+      staff:
+        mode: warn
+      contractor:
+        deny_tools: ["Bash"]
+"#,
+    )
+    .expect("write config");
+
+    let run_as = |session: &str, user: Option<&str>, env_user: Option<&str>, command: &str| {
+        let mut cmd = Command::cargo_bin("rulez").expect("binary exists");
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .env_remove("RULEZ_ROLE")
+            .env_remove("RULEZ_USER");
+        if let Some(env_user) = env_user {
+            cmd.env("RULEZ_USER", env_user);
+        }
+        cmd.write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": {"command": command},
+                "session_id": session,
+                "user_id": user
+            })
+            .to_string(),
+        )
+        .assert()
+    };
+    let run = |session: &str, user: &str, command: &str| run_as(session, Some(user), None, command);
+    // Contractors cannot use Bash at all
+    run("contractor-session", "eve@contractor.example.com", "ls")
+        .code(2)
+        .stderr(predicate::str::contains("profile-contractor-deny-tools"));
+    // Staff only get a warning, even for a blocking rule
+    run("staff-session", "sam@example.com", "rm -rf build").success();
+    // A user named by RULEZ_USER gets their profile too
+    run_as(
+        "env-session",
+        None,
+        Some("mallory@contractor.example.com"),
+        "ls",
+    )
+    .code(2)
+    .stderr(predicate::str::contains("profile-contractor-deny-tools"));
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entries: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let profile_of = |session: &str| {
+        entries
+            .iter()
+            .find(|entry| entry["session_id"] == session)
+            .map(|entry| entry["profile"].clone())
+            .expect("entry logged")
+    };
+    assert_eq!(profile_of("contractor-session"), "contractor");
+    assert_eq!(profile_of("staff-session"), "staff");
+    assert_eq!(profile_of("env-session"), "contractor");
+
+    evidence.pass("Profiles applied per user and logged", timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that `logs upgrade` stamps older entries with the current schema version
+#[test]
+fn test_us5_logs_upgrade() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("logs_upgrade", "OQ-US5");
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let log = dir.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"timestamp":"2025-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"legacy","rules_matched":["r"],"outcome":"Inject","timing":{"processing_ms":1,"rules_evaluated":1}}
+"#,
+    )
+    .expect("write log");
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .args(["logs", "upgrade", "--path"])
+        .arg(&log)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 upgraded, 0 already at schema version 2, 0 unreadable",
+        ));
+
+    let entry: serde_json::Value =
+        serde_json::from_str(fs::read_to_string(&log).expect("read log").trim())
+            .expect("entry is JSON");
+    assert_eq!(entry["schema_version"], 2);
+    assert_eq!(entry["outcome"], "inject");
+
+    evidence.pass(&format!("Upgraded entry: {entry}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that `rulez logs export` writes flat CSV rows within the time window
+#[test]
+fn test_us5_logs_export_csv() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("logs_export_csv", "OQ-US5");
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let log = dir.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"schema_version":2,"timestamp":"2026-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"old","rules_matched":[],"outcome":"allow","timing":{"processing_ms":1,"rules_evaluated":1}}
+{"schema_version":2,"timestamp":"2026-03-01T00:00:00Z","event_type":"PreToolUse","session_id":"new","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":2,"rules_evaluated":3},"event_details":{"tool_type":"Bash","command":"git push --force"},"decision":"blocked"}
+"#,
+    )
+    .expect("write log");
+    let csv = dir.path().join("export.csv");
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .args([
+            "logs",
+            "export",
+            "--since",
+            "2026-02-01T00:00:00Z",
+            "--path",
+        ])
+        .arg(&log)
+        .arg("--output")
+        .arg(&csv)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 log entries"));
+
+    let content = fs::read_to_string(&csv).expect("read export");
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2, "header plus one row: {content}");
+    assert!(lines[0].starts_with("schema_version,timestamp,event_type,session_id"));
+    assert!(lines[1].contains(",new,Bash,,block,blocked,"));
+    assert!(lines[1].contains(",git push --force,"));
+
+    evidence.pass(&format!("Exported CSV:\n{content}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that `rulez stats --per-rule` reports hits, blocks and idle rules
+#[test]
+fn test_us5_stats_per_rule() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("stats_per_rule", "OQ-US5");
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    fs::create_dir_all(dir.path().join(".claude")).expect("create .claude");
+    fs::write(
+        dir.path().join(".claude/hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: no-force-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push --force"
+    actions:
+      block: true
+  - name: never-fires
+    matchers:
+      tools: [Write]
+    actions:
+      block: false
+"#,
+    )
+    .expect("write config");
+    let log = dir.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"schema_version":2,"timestamp":"2026-03-01T00:00:00Z","event_type":"PreToolUse","session_id":"a","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":2,"rules_evaluated":2},"decision":"blocked"}
+{"schema_version":2,"timestamp":"2026-03-02T00:00:00Z","event_type":"PreToolUse","session_id":"a","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":4,"rules_evaluated":2},"decision":"blocked"}
+"#,
+    )
+    .expect("write log");
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(dir.path())
+        .args(["stats", "--per-rule", "--format", "markdown", "--path"])
+        .arg(&log)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "| `no-force-push` | 2 | 100.0% | 3ms | 2026-03-02 00:00 |",
+        ))
+        .stdout(predicate::str::contains(
+            "| `never-fires` | 0 | 0.0% | - | never |  |",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+
+    evidence.pass(
+        &format!("Per-rule report:\n{}", String::from_utf8_lossy(&output)),
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that `rulez digest` summarizes the window as markdown
+#[test]
+fn test_us5_digest_markdown() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("digest_markdown", "OQ-US5");
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let log = dir.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"schema_version":2,"timestamp":"2026-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"old","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":1,"rules_evaluated":1}}
+{"schema_version":2,"timestamp":"2026-03-01T00:00:00Z","event_type":"PreToolUse","session_id":"a","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":2,"rules_evaluated":2},"decision":"blocked"}
+{"schema_version":2,"timestamp":"2026-03-01T01:00:00Z","event_type":"PreToolUse","session_id":"b","tool_name":"Bash","rules_matched":["warn-sudo"],"outcome":"inject","timing":{"processing_ms":2,"rules_evaluated":2},"event_details":{"tool_type":"Bash","command":"sudo apt update"},"decision":"warned"}
+"#,
+    )
+    .expect("write log");
+    let report = dir.path().join("digests/today.md");
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(dir.path())
+        .args(["digest", "--since", "2026-02-01T00:00:00Z", "--path"])
+        .arg(&log)
+        .arg("--output")
+        .arg(&report)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote daily digest to"));
+
+    let markdown = fs::read_to_string(&report).expect("read digest");
+    assert!(markdown.starts_with("# RuleZ digest: 2026-02-01 00:00 to "));
+    assert!(markdown.contains("**2** events in **2** sessions: 1 blocked, 1 injected."));
+    assert!(markdown.contains("| `no-force-push` | 1 | 1 |"));
+    assert!(markdown.contains("- `sudo apt update` (warn-sudo): 1×"));
+
+    evidence.pass(&format!("Digest:\n{markdown}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that audit export writes a signed bundle that verify accepts
+#[test]
+fn test_us5_audit_export_bundle() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("audit_export_bundle", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: no-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+"#,
+    )
+    .expect("write config");
+    let log = project.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"schema_version":2,"timestamp":"2026-02-01T00:00:00Z","event_type":"PreToolUse","session_id":"a","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":2,"rules_evaluated":1},"decision":"blocked"}
+{"schema_version":2,"timestamp":"2026-02-02T00:00:00Z","event_type":"PreToolUse","session_id":"b","tool_name":"Bash","rules_matched":["warn-sudo"],"outcome":"inject","timing":{"processing_ms":2,"rules_evaluated":1},"decision":"warned"}
+{"schema_version":2,"timestamp":"2026-05-01T00:00:00Z","event_type":"PreToolUse","session_id":"c","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":2,"rules_evaluated":1},"decision":"blocked"}
+"#,
+    )
+    .expect("write log");
+    let bundle = project.path().join("bundle.tar");
+
+    let rulez = |args: &[&str], key: &str| {
+        let mut cmd = Command::cargo_bin("rulez").expect("binary exists");
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .env("RULEZ_AUDIT_KEY", key)
+            .args(args);
+        cmd
+    };
+    let log_arg = log.to_str().expect("utf-8 path");
+    rulez(
+        &[
+            "audit",
+            "export",
+            "--since",
+            "2026-Q1",
+            "--path",
+            log_arg,
+            "-o",
+            "bundle.tar",
+            "--key-env",
+            "RULEZ_AUDIT_KEY",
+        ],
+        "s3cret",
+    )
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("covering 2 log entries"));
+
+    let archive = fs::read(&bundle).expect("read bundle");
+    let text = String::from_utf8_lossy(&archive);
+    for name in [
+        "manifest.json",
+        "manifest.sig",
+        "config/project/hooks.yaml",
+        "config/effective.json",
+        "config/lock.json",
+        "decisions/summary.json",
+        "records/bypasses.ndjson",
+        "records/approvals.ndjson",
+    ] {
+        assert!(text.contains(name), "bundle is missing {name}");
+    }
+    assert!(text.contains("\"session_id\":\"b\""));
+    assert!(!text.contains("\"session_id\":\"c\""));
+
+    let args = [
+        "audit",
+        "verify",
+        "bundle.tar",
+        "--key-env",
+        "RULEZ_AUDIT_KEY",
+    ];
+    rulez(&args, "s3cret")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Signature is valid"));
+    rulez(&args, "wrong")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Signature is INVALID"));
+
+    evidence.pass(
+        &format!("Exported and verified {} byte bundle", archive.len()),
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that report pr maps a session's blocked events to annotations
+#[test]
+fn test_us5_report_pr_annotations() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("report_pr_annotations", "OQ-US5");
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let log = dir.path().join("rulez.log");
+    fs::write(
+        &log,
+        r#"{"schema_version":2,"timestamp":"2026-03-01T00:00:00Z","event_type":"PreToolUse","session_id":"pr-session","tool_name":"Write","rules_matched":["no-env-writes"],"outcome":"block","timing":{"processing_ms":1,"rules_evaluated":1},"event_details":{"tool_type":"Write","file_path":"/repo/config/.env"},"response":{"continue":false,"reason":"Secrets belong in the vault"},"decision":"blocked"}
+{"schema_version":2,"timestamp":"2026-03-01T00:01:00Z","event_type":"PreToolUse","session_id":"other","tool_name":"Bash","rules_matched":["no-force-push"],"outcome":"block","timing":{"processing_ms":1,"rules_evaluated":1},"event_details":{"tool_type":"Bash","command":"git push --force"},"decision":"blocked"}
+"#,
+    )
+    .expect("write log");
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .args([
+            "report",
+            "pr",
+            "--session",
+            "pr-session",
+            "--format",
+            "json",
+            "--root",
+            "/repo",
+            "--fail-on-block",
+            "--path",
+        ])
+        .arg(&log)
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).expect("json report");
+    assert_eq!(report["blocked"], 1);
+    assert_eq!(report["events"].as_array().map(Vec::len), Some(1));
+    assert_eq!(report["annotations"][0]["path"], "config/.env");
+    assert_eq!(report["annotations"][0]["annotation_level"], "failure");
+
+    evidence.pass(&format!("Report: {report}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that logs purge removes old entries, and --dry-run leaves the log alone
+#[test]
+fn test_us5_logs_purge() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("logs_purge", "OQ-US5");
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let log = dir.path().join("rulez.log");
+    let original = r#"{"schema_version":2,"timestamp":"2026-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"old","rules_matched":[],"outcome":"allow","timing":{"processing_ms":1,"rules_evaluated":1}}
+{"schema_version":2,"timestamp":"2026-01-02T00:00:00Z","event_type":"PreToolUse","session_id":"old","rules_matched":[],"outcome":"allow","timing":{"processing_ms":1,"rules_evaluated":1}}
+{"schema_version":2,"timestamp":"2026-03-01T00:00:00Z","event_type":"PreToolUse","session_id":"new","rules_matched":[],"outcome":"allow","timing":{"processing_ms":1,"rules_evaluated":1}}
+"#;
+    fs::write(&log, original).expect("write log");
+
+    let purge = |dry_run: bool| {
+        let mut cmd = Command::cargo_bin("rulez").expect("binary exists");
+        cmd.args(["logs", "purge", "--before", "2026-02-01", "--path"])
+            .arg(&log);
+        if dry_run {
+            cmd.arg("--dry-run");
+        }
+        cmd.assert().success()
+    };
+
+    purge(true).stdout(predicate::str::contains("Would remove 2 entries"));
+    assert_eq!(fs::read_to_string(&log).expect("read log"), original);
+
+    purge(false).stdout(predicate::str::contains("Removed 2 entries"));
+    let remaining = fs::read_to_string(&log).expect("read log");
+    assert_eq!(remaining.lines().count(), 1);
+    assert!(remaining.contains("\"session_id\":\"new\""));
+
+    evidence.pass(&format!("Remaining:\n{remaining}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that SessionEnd logs a summary of the session
+#[test]
+fn test_us5_session_summary_logged() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("session_summary_logged", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: project-briefing
+    matchers:
+      operations: ["SessionStart"]
+    actions:
+      inject_inline: "This repo uses conventional commits."
+  - name: no-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+settings:
+  session_summary: {}
+"#,
+    )
+    .expect("write config");
+
+    let run = |event: serde_json::Value| {
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run")
+    };
+
+    let output = run(serde_json::json!({
+        "hook_event_name": "SessionStart",
+        "session_id": "summary-session"
+    }));
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    assert_eq!(
+        response["hookSpecificOutput"]["additionalContext"],
+        "This repo uses conventional commits."
+    );
+
+    run(serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "git push --force origin main"},
+        "session_id": "summary-session"
+    }));
+    run(serde_json::json!({
+        "hook_event_name": "SessionEnd",
+        "session_id": "summary-session"
+    }));
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let end: serde_json::Value = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .find(|entry: &serde_json::Value| entry["event_type"] == "SessionEnd")
+        .expect("SessionEnd entry logged");
+    let summary = &end["session_summary"];
+    assert_eq!(summary["events"], 3);
+    assert_eq!(summary["blocked"], 1);
+    assert_eq!(summary["rules_matched"]["no-force-push"], 1);
+    assert!(
+        !home
+            .path()
+            .join(".claude/logs/sessions/summary-session.json")
+            .exists(),
+        "session state is flushed on SessionEnd"
+    );
+
+    evidence.pass(
+        &format!("Session summary logged: {summary}"),
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that repeated identical blocked attempts log an Anomaly entry
+#[test]
+fn test_us5_repeated_blocks_logged_as_anomaly() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("repeated_blocks_logged_as_anomaly", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: no-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+settings:
+  anomaly_detection:
+    repeated_blocks: 3
+"#,
+    )
+    .expect("write config");
+
+    for _ in 0..4 {
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": {"command": "git push --force origin main"},
+                    "session_id": "looping-session"
+                })
+                .to_string(),
+            )
+            .assert()
+            .code(2);
+    }
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let anomalies: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .filter(|entry: &serde_json::Value| entry["event_type"] == "Anomaly")
+        .collect();
+    assert_eq!(anomalies.len(), 1, "reported once at the threshold: {log}");
+    assert_eq!(anomalies[0]["anomaly"]["kind"], "repeated_block");
+    assert_eq!(anomalies[0]["anomaly"]["count"], 3);
+    assert_eq!(anomalies[0]["rules_matched"][0], "no-force-push");
+    assert_eq!(anomalies[0]["session_id"], "looping-session");
+
+    evidence.pass(
+        &format!("Anomaly logged: {}", anomalies[0]),
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that a rule blocking past the ticket threshold files one ticket
+#[cfg(unix)]
+#[test]
+fn test_us5_repeated_blocks_file_ticket() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("repeated_blocks_file_ticket", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    let tickets = project.path().join("tickets.md");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        format!(
+            r#"version: "1.0"
+rules:
+  - name: no-rm-rf
+    matchers:
+      tools: ["Bash"]
+      command_match: "rm -rf"
+    actions:
+      block: true
+settings:
+  anomaly_detection:
+    repeated_blocks: 0
+    block_spike: 0
+    ticket:
+      threshold: 1
+      command: 'cat >> "{}"'
+"#,
+            tickets.display()
+        ),
+    )
+    .expect("write config");
+
+    for (i, session) in ["s1", "s2", "s1"].iter().enumerate() {
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": {"command": format!("rm -rf build-{i}")},
+                    "session_id": session
+                })
+                .to_string(),
+            )
+            .assert()
+            .code(2);
+    }
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    let body = loop {
+        let body = fs::read_to_string(&tickets).unwrap_or_default();
+        if body.contains("Filed by rulez") || std::time::Instant::now() > deadline {
+            break body;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    };
+    assert_eq!(
+        body.matches("Filed by rulez").count(),
+        1,
+        "one ticket: {body}"
+    );
+    assert!(body.contains("Rule `no-rm-rf` blocked **2** events from **2** session(s)"));
+    assert!(body.contains("`Bash: rm -rf build-0`"));
+    assert!(body.contains("`Bash: rm -rf build-1`"));
+
+    evidence.pass(&format!("Ticket body:\n{body}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that logging.path routes the log into the project and log: off quiets a rule
+#[test]
+fn test_us5_project_log_path_and_rule_levels() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("project_log_path_and_rule_levels", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: noisy-reminder
+    log: off
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inject_inline: "Run tests before committing."
+  - name: no-force-push
+    log: debug
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+settings:
+  logging:
+    path: .claude/logs/rulez.log
+"#,
+    )
+    .expect("write config");
+
+    for (command, code) in [("ls", 0), ("git push --force", 2)] {
+        Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": {"command": command},
+                    "session_id": "project-log"
+                })
+                .to_string(),
+            )
+            .assert()
+            .code(code);
+    }
+
+    assert!(!home.path().join(".claude/logs/rulez.log").exists());
+    let log = fs::read_to_string(claude_dir.join("logs/rulez.log")).expect("read project log");
+    let entries: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    // The injection matched only the quiet rule; the block is always logged
+    assert_eq!(entries.len(), 1, "{log}");
+    assert_eq!(entries[0]["outcome"], "block");
+    assert!(entries[0]["raw_event"].is_object());
+    assert!(entries[0]["rule_evaluations"].is_array());
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["logs", "--limit", "5"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 log entries"));
+
+    evidence.pass(&format!("Project log:\n{log}"), timer.elapsed_ms());
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that PostToolUse sees and logs the PreToolUse decision for the same tool_use_id
+#[test]
+fn test_us5_tool_use_correlation() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("tool_use_correlation", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: deploy-warning
+    matchers:
+      tools: ["Bash"]
+      operations: ["PreToolUse"]
+      command_match: "deploy"
+    actions:
+      inject_inline: "Deploys go to production."
+  - name: deploy-follow-up
+    matchers:
+      operations: ["PostToolUse"]
+      pre_rules: ["deploy-warning"]
+    actions:
+      inject_inline: "Check the deploy dashboard."
+"#,
+    )
+    .expect("write config");
+
+    let run = |event: serde_json::Value| {
+        let output = Command::cargo_bin("rulez")
+            .expect("binary exists")
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let event = |hook: &str, tool_use_id: &str| {
+        serde_json::json!({
+            "hook_event_name": hook,
+            "tool_name": "Bash",
+            "tool_input": {"command": "./deploy.sh"},
+            "tool_response": {"stdout": "ok"},
+            "tool_use_id": tool_use_id,
+            "session_id": "correlation-session"
+        })
+    };
+
+    run(event("PreToolUse", "toolu_pre"));
+    assert!(run(event("PostToolUse", "toolu_pre")).contains("deploy dashboard"));
+    // A PostToolUse without a recorded PreToolUse doesn't match pre_rules
+    assert!(!run(event("PostToolUse", "toolu_other")).contains("deploy dashboard"));
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let post: serde_json::Value = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .find(|entry: &serde_json::Value| {
+            entry["event_type"] == "PostToolUse" && entry["tool_use_id"] == "toolu_pre"
+        })
+        .expect("PostToolUse entry logged");
+    assert_eq!(post["pre_tool_use"]["outcome"], "inject");
+    assert_eq!(post["pre_tool_use"]["rules_matched"][0], "deploy-warning");
+
+    evidence.pass(
+        "PostToolUse paired with PreToolUse by tool_use_id",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that hash-chained, signed entries verify and that tampering is detected
+#[test]
+fn test_us5_verify_logs_detects_tampering() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("verify_logs_detects_tampering", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: block-force-push
+    matchers:
+      tools: ["Bash"]
+      command_match: "git push --force"
+    actions:
+      block: true
+settings:
+  logging:
+    integrity:
+      hash_chain: true
+      signing_key_env: RULEZ_TEST_LOG_KEY
+"#,
+    )
+    .expect("write config");
+
+    let rulez = || {
+        let mut command = Command::cargo_bin("rulez").expect("binary exists");
+        command
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env("RULEZ_TEST_LOG_KEY", "test-key");
+        command
+    };
+    for command in ["ls", "git push --force", "pwd"] {
+        rulez()
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": {"command": command},
+                    "session_id": "chain-session"
+                })
+                .to_string(),
+            )
+            .assert()
+            .code(if command == "git push --force" { 2 } else { 0 });
+    }
+
+    rulez()
+        .arg("verify-logs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 entries, 3 chained, 3 with valid signatures",
+        ))
+        .stdout(predicate::str::contains("Log chain is intact"));
+
+    // Remove the evidence of the blocked push
+    let log_path = home.path().join(".claude/logs/rulez.log");
+    let log = fs::read_to_string(&log_path).expect("read log");
+    let kept: Vec<&str> = log
+        .lines()
+        .filter(|line| !line.contains("\"outcome\":\"block\""))
+        .collect();
+    fs::write(&log_path, kept.join("\n") + "\n").expect("rewrite log");
+
+    rulez()
+        .arg("verify-logs")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "line 2: previous entry was modified or removed",
+        ))
+        .stdout(predicate::str::contains("Log chain is BROKEN"));
+
+    evidence.pass(
+        "Deleting a chained entry is reported by verify-logs",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that injected context is logged with per-rule token estimates
+#[test]
+fn test_us5_context_tokens_logged() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("context_tokens_logged", "OQ-US5");
+
+    let project = tempfile::tempdir().expect("create project dir");
+    let home = tempfile::tempdir().expect("create home dir");
+    fs::create_dir_all(project.path().join(".claude")).expect("create .claude");
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: short-note
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inject_inline: "12345678"
+  - name: long-note
+    matchers:
+      tools: ["Bash"]
+    actions:
+      inject_inline: "1234567890123456"
+"#,
+    )
+    .expect("write config");
+
+    Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": {"command": "ls"},
+                "session_id": "tokens-session"
+            })
+            .to_string(),
+        )
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entry: serde_json::Value = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .find(|entry: &serde_json::Value| entry["session_id"] == "tokens-session")
+        .expect("entry logged");
+    let metadata = &entry["metadata"];
+    // 8 + 2 (separator) + 16 bytes at four bytes per token
+    assert_eq!(metadata["context_tokens"], 7);
+    assert_eq!(
+        metadata["context_blocks"],
+        serde_json::json!([
+            {"rule": "short-note", "bytes": 8, "tokens": 2},
+            {"rule": "long-note", "bytes": 16, "tokens": 4}
+        ])
+    );
+
+    evidence.pass(
+        &format!("Context token estimates logged: {metadata}"),
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that identical context from two rules is injected once and both
+/// rules are noted in the log
+#[test]
+fn test_us5_duplicate_context_merged() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("duplicate_context_merged", "OQ-US5");
+
+    let project = tempfile::tempdir().expect("create project dir");
+    let home = tempfile::tempdir().expect("create home dir");
+    fs::create_dir_all(project.path().join(".claude")).expect("create .claude");
+    fs::write(project.path().join(".claude/style.md"), "Run cargo fmt.\n").expect("write context");
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: rust-style
+    matchers:
+      tools: ["Edit"]
+    actions:
+      inject: .claude/style.md
+  - name: team-style
+    matchers:
+      tools: ["Edit"]
+    actions:
+      inject_inline: "Run cargo fmt."
+"#,
+    )
+    .expect("write config");
+
+    let output = Command::cargo_bin("rulez")
+        .expect("binary exists")
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Edit",
+                "tool_input": {"file_path": "src/lib.rs"},
+                "session_id": "dedupe-session",
+                "cwd": project.path()
+            })
+            .to_string(),
+        )
+        .output()
+        .expect("run rulez");
+    assert!(output.status.success());
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("response is JSON");
+    assert_eq!(response["context"], "Run cargo fmt.\n");
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entry: serde_json::Value = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .find(|entry: &serde_json::Value| entry["session_id"] == "dedupe-session")
+        .expect("entry logged");
+    assert_eq!(
+        entry["metadata"]["context_blocks"],
+        serde_json::json!([
+            {"rule": "rust-style", "bytes": 15, "tokens": 4, "also_injected_by": ["team-style"]}
+        ])
+    );
+
+    evidence.pass(
+        "Identical context injected once with contributing rules logged",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that the kill switch allows everything but logs each event as bypassed
+#[test]
+fn test_us5_kill_switch_logs_bypassed_events() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("kill_switch_logs_bypassed_events", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: no-rm-rf
+    matchers:
+      tools: ["Bash"]
+      command_match: "rm -rf"
+    actions:
+      block: true
+"#,
+    )
+    .expect("write config");
+
+    let rulez = || {
+        let mut command = Command::cargo_bin("rulez").expect("binary exists");
+        command
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env_remove("RULEZ_DISABLED");
+        command
+    };
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "rm -rf build"},
+        "session_id": "kill-switch-session"
+    })
+    .to_string();
+
+    rulez()
+        .env("RULEZ_DISABLED", "1")
+        .write_stdin(event.clone())
+        .assert()
+        .success();
+
+    rulez()
+        .args(["disable", "--all", "--ttl", "1h", "--reason", "incident 42"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("incident 42"));
+    rulez().write_stdin(event.clone()).assert().success();
+
+    rulez().args(["enable", "--all"]).assert().success();
+    rulez().write_stdin(event).assert().code(2);
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entries: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    assert_eq!(entries.len(), 3, "bypassed events are logged too");
+    assert_eq!(entries[0]["decision"], "bypassed");
+    assert_eq!(entries[0]["bypass_reason"], "RULEZ_DISABLED is set");
+    assert_eq!(entries[0]["event_details"]["command"], "rm -rf build");
+    assert_eq!(entries[1]["decision"], "bypassed");
+    assert!(
+        entries[1]["bypass_reason"]
+            .as_str()
+            .unwrap()
+            .ends_with(": incident 42")
+    );
+    assert_eq!(entries[2]["outcome"], "block");
+
+    evidence.pass(
+        "Kill switch allows events and logs them as bypassed",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that the kill switch bypasses hooks even when the config is broken
+#[test]
+fn test_us5_kill_switch_bypasses_broken_config() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("kill_switch_bypasses_broken_config", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(claude_dir.join("hooks.yaml"), "rules: [unclosed\n").expect("write config");
+
+    let rulez = || {
+        let mut command = Command::cargo_bin("rulez").expect("binary exists");
+        command
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env("RULEZ_DISABLED", "1");
+        command
+    };
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "rm -rf build"},
+        "session_id": "kill-switch-broken",
+        "cwd": project.path().to_string_lossy()
+    });
+
+    rulez().write_stdin(event.to_string()).assert().success();
+    let gemini = serde_json::json!({
+        "hook_event_name": "BeforeTool",
+        "tool_name": "run_shell_command",
+        "tool_input": {"command": "rm -rf build"},
+        "session_id": "kill-switch-broken",
+        "cwd": project.path().to_string_lossy()
+    });
+    rulez()
+        .args(["gemini", "hook"])
+        .write_stdin(gemini.to_string())
+        .assert()
+        .success();
+
+    // Without the kill switch the broken config still fails the hook
+    rulez()
+        .env_remove("RULEZ_DISABLED")
+        .write_stdin(event.to_string())
+        .assert()
+        .failure();
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entries: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    assert_eq!(entries.len(), 2, "both bypasses logged: {log}");
+    for entry in &entries {
+        assert_eq!(entry["decision"], "bypassed");
+        assert_eq!(entry["bypass_reason"], "RULEZ_DISABLED is set");
+    }
+
+    evidence.pass(
+        "Kill switch allows and logs events despite a malformed config",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// `--dry-run` / `RULEZ_DRY_RUN` evaluate and log normally but always allow
+#[test]
+fn test_us5_dry_run_logs_but_allows() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("dry_run_logs_but_allows", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: no-rm-rf
+    matchers:
+      tools: ["Bash"]
+      command_match: "rm -rf"
+    actions:
+      block: true
+"#,
+    )
+    .expect("write config");
+
+    let rulez = || {
+        let mut command = Command::cargo_bin("rulez").expect("binary exists");
+        command
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env_remove("RULEZ_DRY_RUN");
+        command
+    };
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "rm -rf build"},
+        "session_id": "dry-run-session"
+    })
+    .to_string();
+
+    rulez()
+        .arg("--dry-run")
+        .write_stdin(event.clone())
+        .assert()
+        .success();
+    rulez()
+        .env("RULEZ_DRY_RUN", "1")
+        .write_stdin(event.clone())
+        .assert()
+        .success();
+    rulez().write_stdin(event).assert().code(2);
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entries: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    assert_eq!(entries.len(), 3);
+    for entry in &entries[..2] {
+        assert_eq!(entry["outcome"], "block", "the would-be decision is logged");
+        assert_eq!(entry["decision"], "blocked");
+        assert_eq!(entry["dry_run"], true);
+    }
+    assert!(entries[2].get("dry_run").is_none());
+
+    evidence.pass(
+        "Dry run logs what would have been blocked but allows it",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// `RULEZ_DEBUG` turns on debug logging and a stderr trace for one invocation
+#[test]
+fn test_us5_rulez_debug_env() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("rulez_debug_env", "OQ-US5");
+
+    let home = tempfile::tempdir().expect("create temp home");
+    let project = tempfile::tempdir().expect("create temp project");
+    let claude_dir = project.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: git-context
+    matchers:
+      tools: ["Bash"]
+      command_match: "^git "
+    actions:
+      inject_inline: "Use conventional commits"
+"#,
+    )
+    .expect("write config");
+
+    let rulez = || {
+        let mut command = Command::cargo_bin("rulez").expect("binary exists");
+        command
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env_remove("RUST_LOG")
+            .env_remove("CCH_DEBUG_LOGS")
+            .env_remove("RULEZ_DEBUG");
+        command
+    };
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "git status"},
+        "session_id": "debug-env-session"
+    })
+    .to_string();
+
+    let output = rulez()
+        .env("RULEZ_DEBUG", "1")
+        .write_stdin(event.clone())
+        .output()
+        .expect("command should run");
+    assert!(output.status.success());
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout stays a JSON response");
+    assert_eq!(response["continue"], true);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Processing event"));
+
+    let output = rulez()
+        .write_stdin(event)
+        .output()
+        .expect("command should run");
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+
+    let log = fs::read_to_string(home.path().join(".claude/logs/rulez.log")).expect("read log");
+    let entries: Vec<serde_json::Value> = log
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0]["raw_event"].is_object());
+    assert!(entries[0]["rule_evaluations"].is_array());
+    assert!(entries[1].get("raw_event").is_none());
+
+    evidence.pass(
+        "RULEZ_DEBUG enables debug logging and tracing for one invocation",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}